@@ -1,7 +1,8 @@
 //! Analyst Agent
 //!
 //! Synthesizes intelligence summaries from scraped content and artifacts.
-//! - Senses: ScrapedContent + ExtractedArtifacts signals
+//! Pages scraped in several chunks are reassembled and excerpted as a whole.
+//! - Senses: ScrapedContent + ExtractedArtifacts signals (plus any EnrichedArtifacts)
 //! - Emits: Summary signal
 //!
 //! ## Multi-Specialist Mode
@@ -11,18 +12,229 @@
 //! Enable with `AnalystAgent::new_with_specialists()`.
 
 use async_trait::async_trait;
-use tracing::info;
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use tracing::{info, warn};
 
-use robin_core::{AgentType, Artifact, Field, OsintPayload, Signal};
+use robin_core::{
+    add_tag, key_insights, ActorDatabase, ActorProfile, AgentType, Artifact, CollectionKind, EnrichmentFinding, Field, OsintPayload,
+    PayloadKind, SharedKnowledge, SharedVectors, Signal, VectorEntry, MAX_VECTOR_TEXT_CHARS,
+};
 
-use crate::{AgentConfig, AgentError, OsintAgent, SharedBackend, SpecialistSystem};
+use crate::{AgentConfig, AgentError, OsintAgent, SharedBackend, SharedEmbedder, SpecialistSystem};
+
+/// Most enrichment findings listed to the analyst
+const MAX_ENRICHMENT_LINES: usize = 30;
+
+/// Most past-case sightings listed to the analyst
+const MAX_PRIOR_CASE_LINES: usize = 20;
+
+/// Most historical documents pulled from the vector store
+const MAX_HISTORICAL_MATCHES: usize = 5;
+
+/// Characters of each historical document shown to the analyst
+const HISTORICAL_EXCERPT_CHARS: usize = 400;
+
+/// Characters of each scraped page shown to the analyst
+const PAGE_EXCERPT_CHARS: usize = 1500;
+
+/// Scraped pages as (url, chunk texts in order), in the order first seen
+fn group_pages(content: &[Signal]) -> Vec<(String, Vec<String>)> {
+    let mut pages: Vec<(String, Vec<(usize, String)>)> = Vec::new();
+    for signal in content {
+        let OsintPayload::ScrapedContent { url, text, chunk, .. } = &signal.payload else {
+            continue;
+        };
+        let index = chunk.map_or(0, |c| c.index);
+        match pages.iter_mut().find(|(u, _)| u == url) {
+            Some((_, chunks)) if !chunks.iter().any(|(i, _)| *i == index) => chunks.push((index, text.clone())),
+            Some(_) => {}
+            None => pages.push((url.clone(), vec![(index, text.clone())])),
+        }
+    }
+    pages
+        .into_iter()
+        .map(|(url, mut chunks)| {
+            chunks.sort_by_key(|(index, _)| *index);
+            (url, chunks.into_iter().map(|(_, text)| text).collect())
+        })
+        .collect()
+}
+
+/// Up to `budget` characters of a page, shared between its chunks
+fn page_excerpt(chunks: &[String], budget: usize) -> String {
+    let per_chunk = budget / chunks.len().max(1);
+    chunks
+        .iter()
+        .map(|text| {
+            let excerpt: String = text.chars().take(per_chunk).collect();
+            if excerpt.len() < text.len() {
+                format!("{}...", excerpt)
+            } else {
+                excerpt
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Render enrichment findings for the analyst, public exploits first
+///
+/// `enrichments` is (artifact value, source, finding).
+fn enrichment_section(enrichments: &[(String, String, EnrichmentFinding)]) -> String {
+    let mut sorted: Vec<_> = enrichments.iter().collect();
+    sorted.sort_by_key(|(_, _, f)| f.finding_type != "exploit");
+    sorted
+        .into_iter()
+        .take(MAX_ENRICHMENT_LINES)
+        .map(|(value, source, f)| {
+            let flag = if f.finding_type == "exploit" { "PUBLIC EXPLOIT " } else { "" };
+            format!(
+                "- {} [{}] {}{}: {} — {}{}",
+                value,
+                source,
+                flag,
+                f.finding_type,
+                f.title,
+                f.snippet,
+                f.url.as_deref().map(|u| format!(" ({})", u)).unwrap_or_default()
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Append the screenshots taken of scraped pages to the report, as evidence
+fn with_screenshots(summary: String, content: &[Signal]) -> String {
+    let shots: Vec<String> = content
+        .iter()
+        .filter_map(|signal| match &signal.payload {
+            OsintPayload::ScrapedContent { url, screenshot: Some(path), .. } => Some(format!("- {}: `{}`", url, path)),
+            _ => None,
+        })
+        .collect();
+    if shots.is_empty() {
+        return summary;
+    }
+    format!("{}\n\n## Screenshots\n\n{}\n", summary.trim_end(), shots.join("\n"))
+}
+
+/// Append the tags of the signals the report was written from, for slicing exported reports
+fn with_tags(summary: String, tags: &[String]) -> String {
+    if tags.is_empty() {
+        return summary;
+    }
+    let tags: Vec<String> = tags.iter().map(|tag| format!("`{}`", tag)).collect();
+    format!("{}\n\n## Tags\n\n{}\n", summary.trim_end(), tags.join(", "))
+}
+
+/// Append the pages that changed since earlier runs to the report
+fn with_changes(summary: String, changes: &[Signal]) -> String {
+    let lines: Vec<String> = changes
+        .iter()
+        .filter_map(|signal| match &signal.payload {
+            OsintPayload::ContentChanged { url, summary, .. } => Some(format!("- {}: {}", url, summary)),
+            _ => None,
+        })
+        .collect();
+    if lines.is_empty() {
+        return summary;
+    }
+    format!("{}\n\n## Changes Since Earlier Runs\n\n{}\n", summary.trim_end(), lines.join("\n"))
+}
+
+/// Append the handles seen in chat logs and forum threads to the report
+fn with_handles(summary: String, threads: &[Signal]) -> String {
+    let mut handles: BTreeMap<&str, (usize, BTreeSet<&str>)> = BTreeMap::new();
+    for signal in threads {
+        if let OsintPayload::ThreadMessages { source_url, author, messages, .. } = &signal.payload {
+            let (count, sources) = handles.entry(author.as_str()).or_default();
+            *count += messages.len();
+            sources.insert(source_url.as_str());
+        }
+    }
+    if handles.is_empty() {
+        return summary;
+    }
+    let mut handles: Vec<_> = handles.into_iter().collect();
+    handles.sort_by(|a, b| b.1 .0.cmp(&a.1 .0).then(a.0.cmp(b.0)));
+    let lines: Vec<String> = handles
+        .into_iter()
+        .map(|(author, (count, sources))| {
+            let sources: Vec<&str> = sources.into_iter().collect();
+            format!("- **{}**: {} messages in {}", author, count, sources.join(", "))
+        })
+        .collect();
+    format!("{}\n\n## Handles in Conversations\n\n{}\n", summary.trim_end(), lines.join("\n"))
+}
+
+/// Most actor dossiers in a report
+const MAX_DOSSIERS: usize = 10;
+
+/// Most aliases, sites, wallets, keys or contacts listed per dossier line
+const MAX_DOSSIER_ITEMS: usize = 10;
+
+/// Append a dossier for each profiled actor to the report, best-evidenced first
+fn with_dossiers(summary: String, profiles: &[Signal]) -> String {
+    // Profiles are re-emitted as evidence arrives; the latest holds the most
+    let mut latest: BTreeMap<&str, &ActorProfile> = BTreeMap::new();
+    for signal in profiles {
+        if let OsintPayload::ActorProfile { profile } = &signal.payload {
+            let current = latest.entry(profile.name.as_str()).or_insert(profile);
+            if profile.evidence > current.evidence {
+                *current = profile;
+            }
+        }
+    }
+    if latest.is_empty() {
+        return summary;
+    }
+    let mut profiles: Vec<&ActorProfile> = latest.into_values().collect();
+    profiles.sort_by(|a, b| b.evidence.cmp(&a.evidence).then(a.name.cmp(&b.name)));
+    let join = |items: &BTreeSet<String>| {
+        let shown = items.iter().take(MAX_DOSSIER_ITEMS).map(String::as_str).collect::<Vec<_>>().join(", ");
+        match items.len().saturating_sub(MAX_DOSSIER_ITEMS) {
+            0 => shown,
+            more => format!("{} and {} more", shown, more),
+        }
+    };
+    let dossiers: Vec<String> = profiles
+        .into_iter()
+        .take(MAX_DOSSIERS)
+        .map(|profile| {
+            let mut lines = vec![format!("### {}\n", profile.name)];
+            for (label, items) in [
+                ("Aliases", &profile.aliases),
+                ("Seen on", &profile.sites),
+                ("Wallets", &profile.wallets),
+                ("PGP keys", &profile.pgp_keys),
+                ("Contacts", &profile.contacts),
+            ] {
+                if !items.is_empty() {
+                    lines.push(format!("- **{}**: {}", label, join(items)));
+                }
+            }
+            if !profile.timeline.is_empty() {
+                lines.push("- **Activity**:".to_string());
+                for event in &profile.timeline {
+                    lines.push(format!("  - {}: {} ({})", event.at.format("%Y-%m-%d %H:%M"), event.activity, event.source));
+                }
+            }
+            lines.join("\n")
+        })
+        .collect();
+    format!("{}\n\n## Actor Dossiers\n\n{}\n", summary.trim_end(), dossiers.join("\n\n"))
+}
 
 /// Analyst agent - synthesizes intelligence summaries
 pub struct AnalystAgent {
     config: AgentConfig,
     backend: SharedBackend,
     specialist_system: Option<SpecialistSystem>,
-    summary_generated: bool,
+    actors: ActorDatabase,
+    knowledge: Option<SharedKnowledge>,
+    vectors: Option<(SharedVectors, SharedEmbedder)>,
+    /// Investigations summarized (`None` = unscoped signals)
+    summarized: HashSet<Option<String>>,
 }
 
 impl AnalystAgent {
@@ -32,7 +244,10 @@ impl AnalystAgent {
             config,
             backend,
             specialist_system: None,
-            summary_generated: false,
+            actors: ActorDatabase::embedded(),
+            knowledge: None,
+            vectors: None,
+            summarized: HashSet::new(),
         }
     }
 
@@ -43,10 +258,125 @@ impl AnalystAgent {
             config,
             backend,
             specialist_system: Some(specialist_system),
-            summary_generated: false,
+            actors: ActorDatabase::embedded(),
+            knowledge: None,
+            vectors: None,
+            summarized: HashSet::new(),
         }
     }
 
+    /// Normalize actor aliases in the summary against `actors`
+    pub fn with_actors(mut self, actors: ActorDatabase) -> Self {
+        self.actors = actors;
+        self
+    }
+
+    /// Consult past cases before writing the summary, and record this one after
+    pub fn with_knowledge(mut self, knowledge: SharedKnowledge) -> Self {
+        self.knowledge = Some(knowledge);
+        self
+    }
+
+    /// Search past collections before writing the summary, and index this run's content after
+    pub fn with_vectors(mut self, store: SharedVectors, embedder: SharedEmbedder) -> Self {
+        self.vectors = Some((store, embedder));
+        self
+    }
+
+    /// Past documents most similar to the query, excluding pages collected in this run
+    async fn historical_matches(&self, query: &str, current: &[(String, Vec<String>)]) -> Vec<String> {
+        let Some((store, embedder)) = &self.vectors else {
+            return Vec::new();
+        };
+        let query_vector = match embedder.embed(&[query.to_string()]).await {
+            Ok(mut vectors) if !vectors.is_empty() => vectors.remove(0),
+            Ok(_) => return Vec::new(),
+            Err(e) => {
+                warn!("Could not embed query for historical search: {}", e);
+                return Vec::new();
+            }
+        };
+
+        let store = store.lock().unwrap();
+        let hits = match store.search(embedder.model_name(), &query_vector, MAX_HISTORICAL_MATCHES + current.len()) {
+            Ok(hits) => hits,
+            Err(e) => {
+                warn!("Historical search failed: {}", e);
+                return Vec::new();
+            }
+        };
+        hits.into_iter()
+            .filter(|(_, entry)| !current.iter().any(|(url, _)| *url == entry.url))
+            .take(MAX_HISTORICAL_MATCHES)
+            .map(|(score, entry)| {
+                let excerpt: String = entry.text.chars().take(HISTORICAL_EXCERPT_CHARS).collect();
+                format!(
+                    "- [{:.2}] {} ({}, collected {}): {}",
+                    score,
+                    entry.title,
+                    entry.url,
+                    entry.collected_at.format("%Y-%m-%d"),
+                    excerpt.split_whitespace().collect::<Vec<_>>().join(" ")
+                )
+            })
+            .collect()
+    }
+
+    /// Add an investigation's scraped pages and pastes to the vector store
+    async fn index_collections(&self, field: &Field, investigation: Option<&str>) -> Result<usize, AgentError> {
+        let Some((store, embedder)) = &self.vectors else {
+            return Ok(0);
+        };
+        let signals: Vec<Signal> = field
+            .sense_in(investigation, &[PayloadKind::ScrapedContent, PayloadKind::PasteContent], 0.0)
+            .into_iter()
+            .cloned()
+            .collect();
+        // A chunked page is indexed whole, as one excerpt shared between its chunks
+        let mut documents: Vec<(String, CollectionKind, String, String)> = group_pages(&signals)
+            .into_iter()
+            .map(|(url, chunks)| {
+                let title = signals
+                    .iter()
+                    .find_map(|signal| match &signal.payload {
+                        OsintPayload::ScrapedContent { url: u, title, .. } if *u == url => Some(title.clone()),
+                        _ => None,
+                    })
+                    .unwrap_or_default();
+                let text = page_excerpt(&chunks, MAX_VECTOR_TEXT_CHARS);
+                (url, CollectionKind::Scraped, title, text)
+            })
+            .collect();
+        documents.extend(signals.iter().filter_map(|signal| match &signal.payload {
+            OsintPayload::PasteContent { url, site, title, content, .. } => Some((
+                url.clone(),
+                CollectionKind::Paste,
+                title.clone().unwrap_or_else(|| site.clone()),
+                content.chars().take(MAX_VECTOR_TEXT_CHARS).collect(),
+            )),
+            _ => None,
+        }));
+        if documents.is_empty() {
+            return Ok(0);
+        }
+
+        let inputs: Vec<String> = documents.iter().map(|(_, _, title, text)| format!("{}\n{}", title, text)).collect();
+        let embeddings = embedder.embed(&inputs).await.map_err(|e| AgentError::Llm(e.to_string()))?;
+
+        let mut store = store.lock().unwrap();
+        let mut added = 0;
+        for ((url, kind, title, text), embedding) in documents.into_iter().zip(embeddings) {
+            let entry = VectorEntry::new(&url, kind, &title, &text, embedding);
+            if store
+                .insert(embedder.model_name(), entry)
+                .map_err(|e| AgentError::Llm(e.to_string()))?
+            {
+                added += 1;
+            }
+        }
+        Ok(added)
+    }
+
     /// Check if multi-specialist mode is enabled
     pub fn has_specialists(&self) -> bool {
         self.specialist_system.is_some()
@@ -63,21 +393,17 @@ impl AnalystAgent {
     async fn generate_summary(
         &self,
         query: &str,
-        content: &[(String, String)], // (url, text)
+        content: &[(String, Vec<String>)], // (url, chunk texts)
         artifacts: &[Artifact],
+        enrichments: &[(String, String, EnrichmentFinding)],
+        prior_cases: &[String],
+        history: &[String],
     ) -> Result<String, AgentError> {
         // Build content string
         let content_str = content
             .iter()
             .take(10)
-            .map(|(url, text)| {
-                let truncated = if text.len() > 1500 {
-                    format!("{}...", &text[..1500])
-                } else {
-                    text.clone()
-                };
-                format!("### {}\n{}\n", url, truncated)
-            })
+            .map(|(url, chunks)| format!("### {}\n{}\n", url, page_excerpt(chunks, PAGE_EXCERPT_CHARS)))
             .collect::<Vec<_>>()
             .join("\n");
 
@@ -88,6 +414,21 @@ impl AnalystAgent {
             .map(|a| format!("- {:?}: {} (confidence: {:.2})", a.artifact_type, a.value, a.confidence))
             .collect::<Vec<_>>()
             .join("\n");
+        let artifacts_str = if enrichments.is_empty() {
+            artifacts_str
+        } else {
+            format!("{}\n\n## External Enrichment\n{}", artifacts_str, enrichment_section(enrichments))
+        };
+        let artifacts_str = if prior_cases.is_empty() {
+            artifacts_str
+        } else {
+            format!("{}\n\n## Prior Cases\n{}", artifacts_str, prior_cases.join("\n"))
+        };
+        let artifacts_str = if history.is_empty() {
+            artifacts_str
+        } else {
+            format!("{}\n\n## Historical Collections\n{}", artifacts_str, history.join("\n"))
+        };
 
         // Use specialist system if available, otherwise fall back to lead-only
         if let Some(ref specialist_system) = self.specialist_system {
@@ -116,41 +457,12 @@ impl AnalystAgent {
                 .map_err(|e| AgentError::Llm(e.to_string()))
         }
     }
-}
-
-#[async_trait]
-impl OsintAgent for AnalystAgent {
-    fn id(&self) -> &str {
-        &self.config.id
-    }
-
-    fn agent_type(&self) -> &str {
-        "analyst"
-    }
-
-    fn sense<'a>(&self, field: &'a Field) -> Vec<&'a Signal> {
-        if self.summary_generated {
-            return Vec::new();
-        }
-
-        // Sense scraped content and artifacts
-        field.sense_where(|signal| {
-            signal.effective_intensity(field.now()) >= self.config.sensing_threshold
-                && matches!(
-                    &signal.payload,
-                    OsintPayload::ScrapedContent { .. } | OsintPayload::ExtractedArtifacts { .. }
-                )
-        })
-    }
-
-    async fn process(&mut self, field: &mut Field) -> Result<Vec<String>, AgentError> {
-        if self.summary_generated {
-            return Err(AgentError::NoWork);
-        }
 
+    /// Summarize one investigation, once enough of it has been scraped
+    async fn summarize(&mut self, field: &mut Field, investigation: Option<&str>) -> Result<Vec<String>, AgentError> {
         // Get the original query
         let refined_signals: Vec<_> = field
-            .sense_where(|s| matches!(&s.payload, OsintPayload::RefinedQuery { .. }))
+            .sense_in(investigation, &[PayloadKind::RefinedQuery], 0.0)
             .into_iter()
             .cloned()
             .collect();
@@ -167,44 +479,36 @@ impl OsintAgent for AnalystAgent {
 
         // Collect scraped content
         let content_signals: Vec<_> = field
-            .sense_where(|s| matches!(&s.payload, OsintPayload::ScrapedContent { .. }))
+            .sense_in(investigation, &[PayloadKind::ScrapedContent], 0.0)
             .into_iter()
             .cloned()
             .collect();
 
-        if content_signals.len() < 3 {
+        // Pages with their chunks, in chunk order
+        let content = group_pages(&content_signals);
+
+        if content.len() < 3 {
             // Wait for more content before generating summary
             return Err(AgentError::NotReady(format!(
-                "Only {} content signals, waiting for more",
-                content_signals.len()
+                "Only {} pages scraped, waiting for more",
+                content.len()
             )));
         }
 
         // Collect artifacts
         let artifact_signals: Vec<_> = field
-            .sense_where(|s| matches!(&s.payload, OsintPayload::ExtractedArtifacts { .. }))
+            .sense_in(investigation, &[PayloadKind::ExtractedArtifacts], 0.0)
             .into_iter()
             .cloned()
             .collect();
 
         info!(
-            "Analyst processing {} content signals, {} artifact signals",
+            "Analyst processing {} content signals ({} pages), {} artifact signals",
             content_signals.len(),
+            content.len(),
             artifact_signals.len()
         );
 
-        // Extract content tuples
-        let content: Vec<(String, String)> = content_signals
-            .iter()
-            .filter_map(|signal| {
-                if let OsintPayload::ScrapedContent { url, text, .. } = &signal.payload {
-                    Some((url.clone(), text.clone()))
-                } else {
-                    None
-                }
-            })
-            .collect();
-
         // Flatten artifacts
         let artifacts: Vec<Artifact> = artifact_signals
             .iter()
@@ -218,18 +522,110 @@ impl OsintAgent for AnalystAgent {
             .flatten()
             .collect();
 
+        // Enrichment findings (advisories, exploits, web mentions) that arrived so far
+        let enrichments: Vec<(String, String, EnrichmentFinding)> = field
+            .sense_in(investigation, &[PayloadKind::EnrichedArtifacts], 0.0)
+            .into_iter()
+            .filter_map(|signal| match &signal.payload {
+                OsintPayload::EnrichedArtifacts { artifact, source, findings } => Some(
+                    findings
+                        .iter()
+                        .map(|f| (artifact.value.clone(), source.clone(), f.clone()))
+                        .collect::<Vec<_>>(),
+                ),
+                _ => None,
+            })
+            .flatten()
+            .collect();
+
+        // Entities seen in earlier investigations
+        let mut prior_cases = Vec::new();
+        if let Some(knowledge) = &self.knowledge {
+            let knowledge = knowledge.lock().unwrap();
+            let sightings = knowledge.recall(&artifacts);
+            let sightings = &sightings[..sightings.len().min(MAX_PRIOR_CASE_LINES)];
+            prior_cases.extend(sightings.iter().map(|sighting| format!("- {}", sighting.describe())));
+            let mut cases: Vec<_> = sightings.iter().map(|s| s.case).collect();
+            cases.dedup_by_key(|case| case.id);
+            for case in cases.into_iter().filter(|case| !case.insights.is_empty()) {
+                prior_cases.push(format!("- Case #{} key insights: {}", case.id, case.insights.join("; ")));
+            }
+        }
+        if !prior_cases.is_empty() {
+            info!("Analyst found {} links to past cases", prior_cases.len());
+        }
+
+        // Similar content from earlier collections
+        let history = self.historical_matches(&query, &content).await;
+        if !history.is_empty() {
+            info!("Analyst pulled {} documents from past collections", history.len());
+        }
+
         // Generate summary
-        let summary = self.generate_summary(&query, &content, &artifacts).await?;
+        let summary = self
+            .generate_summary(&query, &content, &artifacts, &enrichments, &prior_cases, &history)
+            .await?;
+        let summary = self.actors.normalize_report(&summary);
+        let summary = with_screenshots(summary, &content_signals);
+        let changes: Vec<Signal> = field
+            .sense_in(investigation, &[PayloadKind::ContentChanged], 0.0)
+            .into_iter()
+            .cloned()
+            .collect();
+        let summary = with_changes(summary, &changes);
+        let threads: Vec<Signal> = field
+            .sense_in(investigation, &[PayloadKind::ThreadMessages], 0.0)
+            .into_iter()
+            .cloned()
+            .collect();
+        let summary = with_handles(summary, &threads);
+        let profiles: Vec<Signal> = field
+            .sense_in(investigation, &[PayloadKind::ActorProfile], 0.0)
+            .into_iter()
+            .cloned()
+            .collect();
+        let summary = with_dossiers(summary, &profiles);
+
+        // The report and case are tagged like the signals they were built from
+        let mut tags = Vec::new();
+        for signal in refined_signals.iter().take(1).chain(&content_signals).chain(&artifact_signals) {
+            for tag in &signal.tags {
+                add_tag(&mut tags, tag);
+            }
+        }
+        let summary = with_tags(summary, &tags);
+
+        if let Some(knowledge) = &self.knowledge {
+            let case = knowledge.lock().unwrap().record_case(&query, &artifacts, key_insights(&summary), tags);
+            info!("Recorded investigation as case #{}", case);
+        }
+        match self.index_collections(field, investigation).await {
+            Ok(added) if added > 0 => info!("Indexed {} documents for future searches", added),
+            Ok(_) => {}
+            // The summary is done; a failed index only costs future recall
+            Err(e) => warn!("Could not index collected content: {}", e),
+        }
 
-        self.summary_generated = true;
+        self.summarized.insert(investigation.map(str::to_string));
 
-        // Emit summary signal
+        // Emit summary signal, derived from everything it was written from
+        let enrichment_signals = field.sense_in(investigation, &[PayloadKind::EnrichedArtifacts], 0.0);
+        let parents: Vec<String> = refined_signals
+            .iter()
+            .take(1)
+            .chain(&content_signals)
+            .chain(&artifact_signals)
+            .chain(&changes)
+            .chain(enrichment_signals)
+            .map(|signal| signal.origin_hash.clone())
+            .collect();
         let summary_signal = Signal::builder(OsintPayload::Summary {
             markdown: summary.clone(),
             artifact_count: artifacts.len(),
             source_count: content.len(),
         })
         .origin(&self.config.id)
+        .parents(parents)
         .confidence(0.95)
         .ttl(300.0) // Summary persists for 5 minutes
         .build();
@@ -241,12 +637,72 @@ impl OsintAgent for AnalystAgent {
 
         Ok(vec![hash])
     }
+}
+
+#[async_trait]
+impl OsintAgent for AnalystAgent {
+    fn id(&self) -> &str {
+        &self.config.id
+    }
+
+    fn agent_type(&self) -> &str {
+        "analyst"
+    }
+
+    fn senses(&self) -> &[PayloadKind] {
+        &[
+            PayloadKind::RefinedQuery,
+            PayloadKind::ScrapedContent,
+            PayloadKind::ExtractedArtifacts,
+            PayloadKind::EnrichedArtifacts,
+            PayloadKind::ContentChanged,
+            PayloadKind::ThreadMessages,
+            PayloadKind::ActorProfile,
+        ]
+    }
+
+    fn emits(&self) -> &[PayloadKind] {
+        &[PayloadKind::Summary]
+    }
+
+    fn sense<'a>(&self, field: &'a Field) -> Vec<&'a Signal> {
+        // Sense scraped content and artifacts of investigations not yet summarized
+        field
+            .sense_kinds(
+                &[PayloadKind::ScrapedContent, PayloadKind::ExtractedArtifacts],
+                self.config.sensing_threshold,
+            )
+            .into_iter()
+            .filter(|signal| !self.summarized.contains(&signal.investigation_id))
+            .collect()
+    }
+
+    async fn process(&mut self, field: &mut Field) -> Result<Vec<String>, AgentError> {
+        // One summary per investigation, each written only from its own signals
+        let investigations: Vec<Option<String>> = field
+            .investigations(&[PayloadKind::ScrapedContent])
+            .into_iter()
+            .map(|id| id.map(str::to_string))
+            .filter(|id| !self.summarized.contains(id))
+            .collect();
+        let mut emitted = None;
+        let mut waiting = None;
+        for investigation in investigations {
+            match self.summarize(field, investigation.as_deref()).await {
+                Ok(hashes) => emitted.get_or_insert_with(Vec::new).extend(hashes),
+                Err(e @ AgentError::NotReady(_)) => waiting = Some(e),
+                Err(AgentError::NoWork) => {}
+                Err(e) => return Err(e),
+            }
+        }
+        emitted.ok_or_else(|| waiting.unwrap_or(AgentError::NoWork))
+    }
 
     fn heartbeat(&self, field: &mut Field) {
         let signal = Signal::builder(OsintPayload::Heartbeat {
             agent_id: self.config.id.clone(),
             agent_type: AgentType::Analyst,
-            capacity: if self.summary_generated { 0.0 } else { 1.0 },
+            capacity: if self.summarized.is_empty() { 1.0 } else { 0.0 },
         })
         .origin(&self.config.id)
         .ttl(10.0)
@@ -255,3 +711,76 @@ impl OsintAgent for AnalystAgent {
         field.emit(signal);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dossiers_cap_long_lists() {
+        let profile = ActorProfile {
+            name: "vendor_x".to_string(),
+            wallets: (0..12).map(|i| format!("wallet-{:02}", i)).collect(),
+            evidence: 1,
+            ..Default::default()
+        };
+        let signal = Signal::builder(OsintPayload::ActorProfile { profile }).build();
+        let report = with_dossiers("# Summary".to_string(), &[signal]);
+        assert!(report.contains("## Actor Dossiers\n\n### vendor_x\n\n- **Wallets**: wallet-00,"));
+        assert!(report.contains("wallet-09 and 2 more\n"));
+        assert!(!report.contains("wallet-10"));
+    }
+
+    #[test]
+    fn test_report_lists_tags() {
+        assert_eq!(with_tags("# Report\n".to_string(), &[]), "# Report\n");
+        let tags = vec!["engagement:acme".to_string(), "severity:high".to_string()];
+        assert_eq!(
+            with_tags("# Report\n".to_string(), &tags),
+            "# Report\n\n## Tags\n\n`engagement:acme`, `severity:high`\n"
+        );
+    }
+
+    struct NoBackend;
+
+    #[async_trait]
+    impl crate::LlmBackend for NoBackend {
+        async fn generate(&self, _system: &str, _user: &str) -> Result<String, crate::LlmError> {
+            Ok(String::new())
+        }
+
+        fn model_name(&self) -> &str {
+            "none"
+        }
+    }
+
+    #[tokio::test]
+    async fn test_chunked_pages_are_indexed_whole() {
+        let store: SharedVectors = Default::default();
+        let agent = AnalystAgent::new(AgentConfig::default(), std::sync::Arc::new(NoBackend))
+            .with_vectors(store.clone(), std::sync::Arc::new(crate::HashingEmbedder));
+        let mut field = Field::new();
+        for (index, text) in ["victims listed below", "lockbit affiliate panel credentials"].into_iter().enumerate() {
+            field.emit(
+                Signal::builder(OsintPayload::ScrapedContent {
+                    url: "http://leak.onion/".to_string(),
+                    title: "Leaks".to_string(),
+                    text: text.to_string(),
+                    char_count: text.len(),
+                    screenshot: None,
+                    language: None,
+                    original_text: None,
+                    chunk: Some(robin_core::ContentChunk { index, count: 2, offset: index * 100 }),
+                    metadata: None,
+                })
+                .build(),
+            );
+        }
+
+        assert_eq!(agent.index_collections(&field, None).await.unwrap(), 1);
+        let store = store.lock().unwrap();
+        let entry = &store.entries()[0];
+        assert_eq!(entry.title, "Leaks");
+        assert!(entry.text.contains("victims listed") && entry.text.contains("affiliate panel"));
+    }
+}
@@ -1,6 +1,8 @@
 //! LLM backend abstraction
 //!
-//! Supports OpenAI-compatible APIs and Anthropic Claude.
+//! Supports OpenAI-compatible APIs and Anthropic Claude. Backends made by
+//! [`create_backend`] and [`create_anthropic_backend`] clean up responses
+//! (see [`crate::response`]) before agents see them.
 
 use async_openai::{
     config::OpenAIConfig,
@@ -14,6 +16,8 @@ use async_trait::async_trait;
 use std::sync::Arc;
 use thiserror::Error;
 
+use crate::{normalized, ModelRegistry, DEFAULT_MAX_OUTPUT_TOKENS};
+
 /// LLM backend errors
 #[derive(Debug, Error)]
 pub enum LlmError {
@@ -51,7 +55,7 @@ pub struct OpenAIBackendConfig {
     pub model: String,
     /// Temperature (0.0 - 2.0)
     pub temperature: f32,
-    /// Max tokens
+    /// Max output tokens per request (capped at the model's limit from the [`ModelRegistry`])
     pub max_tokens: u16,
 }
 
@@ -62,7 +66,7 @@ impl Default for OpenAIBackendConfig {
             base_url: None,
             model: "gpt-4o-mini".to_string(),
             temperature: 0.0,
-            max_tokens: 4096,
+            max_tokens: DEFAULT_MAX_OUTPUT_TOKENS as u16,
         }
     }
 }
@@ -74,6 +78,7 @@ impl OpenAIBackendConfig {
             model: model.to_string(),
             ..Default::default()
         }
+        .with_models(&ModelRegistry::embedded())
     }
 
     pub fn openrouter(api_key: &str, model: &str) -> Self {
@@ -83,6 +88,7 @@ impl OpenAIBackendConfig {
             model: model.to_string(),
             ..Default::default()
         }
+        .with_models(&ModelRegistry::embedded())
     }
 
     pub fn local(base_url: &str, model: &str) -> Self {
@@ -92,6 +98,13 @@ impl OpenAIBackendConfig {
             model: model.to_string(),
             ..Default::default()
         }
+        .with_models(&ModelRegistry::embedded())
+    }
+
+    /// Cap the output tokens at the model's limit from `models`
+    pub fn with_models(mut self, models: &ModelRegistry) -> Self {
+        self.max_tokens = u16::try_from(models.request_tokens(&self.model)).unwrap_or(u16::MAX);
+        self
     }
 }
 
@@ -167,7 +180,7 @@ pub struct AnthropicConfig {
     pub api_key: String,
     /// Model name (e.g., claude-3-5-sonnet-20241022)
     pub model: String,
-    /// Max tokens
+    /// Max output tokens per request (capped at the model's limit from the [`ModelRegistry`])
     pub max_tokens: u32,
 }
 
@@ -176,8 +189,15 @@ impl AnthropicConfig {
         Self {
             api_key: api_key.to_string(),
             model: model.to_string(),
-            max_tokens: 4096,
+            max_tokens: DEFAULT_MAX_OUTPUT_TOKENS,
         }
+        .with_models(&ModelRegistry::embedded())
+    }
+
+    /// Cap the output tokens at the model's limit from `models`
+    pub fn with_models(mut self, models: &ModelRegistry) -> Self {
+        self.max_tokens = models.request_tokens(&self.model);
+        self
     }
 }
 
@@ -244,12 +264,12 @@ impl LlmBackend for AnthropicBackend {
 /// Thread-safe reference to an LLM backend
 pub type SharedBackend = Arc<dyn LlmBackend>;
 
-/// Create a shared OpenAI-compatible backend
+/// Create a shared OpenAI-compatible backend, with normalized responses
 pub fn create_backend(config: OpenAIBackendConfig) -> Result<SharedBackend, LlmError> {
-    Ok(Arc::new(OpenAIBackend::new(config)?))
+    Ok(normalized(Arc::new(OpenAIBackend::new(config)?)))
 }
 
-/// Create a shared Anthropic backend
+/// Create a shared Anthropic backend, with normalized responses
 pub fn create_anthropic_backend(config: AnthropicConfig) -> Result<SharedBackend, LlmError> {
-    Ok(Arc::new(AnthropicBackend::new(config)?))
+    Ok(normalized(Arc::new(AnthropicBackend::new(config)?)))
 }
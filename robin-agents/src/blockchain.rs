@@ -12,8 +12,9 @@ use std::time::Duration;
 use tracing::{info, warn};
 
 use robin_core::{
-    AgentType, Artifact, ArtifactType, Field, OsintPayload, Signal, TemporalPattern, WalletAnalysis,
+    AgentType, Artifact, ArtifactType, Field, OsintPayload, PayloadKind, Signal, TemporalPattern, WalletAnalysis,
 };
+use robin_tor::api_client;
 
 use crate::traits::{AgentConfig, AgentError, OsintAgent};
 
@@ -26,6 +27,8 @@ pub struct BlockchainConfig {
     pub request_timeout: Duration,
     /// Minimum transaction count to analyze patterns
     pub min_tx_for_patterns: u32,
+    /// Send API requests through this proxy URL (None = direct connection)
+    pub proxy: Option<String>,
 }
 
 impl Default for BlockchainConfig {
@@ -34,6 +37,7 @@ impl Default for BlockchainConfig {
             etherscan_api_key: env::var("ETHERSCAN_API_KEY").ok(),
             request_timeout: Duration::from_secs(30),
             min_tx_for_patterns: 3,
+            proxy: None,
         }
     }
 }
@@ -48,11 +52,12 @@ pub struct BlockchainAgent {
 
 impl BlockchainAgent {
     pub fn new(config: AgentConfig, blockchain_config: BlockchainConfig) -> Self {
-        let client = Client::builder()
-            .timeout(blockchain_config.request_timeout)
-            .user_agent("Robin-SMESH/1.0")
-            .build()
-            .expect("Failed to create HTTP client");
+        let client = api_client(
+            blockchain_config.proxy.as_deref(),
+            blockchain_config.request_timeout,
+            Some("Robin-SMESH/1.0"),
+        )
+        .expect("Invalid blockchain proxy");
 
         Self {
             config,
@@ -391,26 +396,35 @@ impl OsintAgent for BlockchainAgent {
         "blockchain_analyst"
     }
 
+    fn senses(&self) -> &[PayloadKind] {
+        &[PayloadKind::ExtractedArtifacts]
+    }
+
+    fn emits(&self) -> &[PayloadKind] {
+        &[PayloadKind::BlockchainAnalysis]
+    }
+
     fn sense<'a>(&self, field: &'a Field) -> Vec<&'a Signal> {
         field.sense_by_type(self.config.sensing_threshold).extracted_artifacts
     }
 
     async fn process(&mut self, field: &mut Field) -> Result<Vec<String>, AgentError> {
         // Collect crypto artifacts to analyze
-        let artifacts_to_analyze: Vec<Artifact> = {
+        // Each with the hash of the signal it was extracted in
+        let artifacts_to_analyze: Vec<(Artifact, String)> = {
             let signals = field.sense_by_type(0.3);
             signals
                 .extracted_artifacts
                 .iter()
                 .filter_map(|signal| {
                     if let OsintPayload::ExtractedArtifacts { artifacts, .. } = &signal.payload {
-                        Some(artifacts.clone())
+                        Some(artifacts.iter().map(|artifact| (artifact.clone(), signal.origin_hash.clone())))
                     } else {
                         None
                     }
                 })
                 .flatten()
-                .filter(|artifact| {
+                .filter(|(artifact, _)| {
                     let key = format!("{:?}:{}", artifact.artifact_type, artifact.value);
                     !self.processed_addresses.contains(&key) && 
                     Self::detect_chain(artifact).is_some()
@@ -420,7 +434,7 @@ impl OsintAgent for BlockchainAgent {
 
         let mut emitted_hashes = Vec::new();
 
-        for artifact in artifacts_to_analyze {
+        for (artifact, parent) in artifacts_to_analyze {
             let key = format!("{:?}:{}", artifact.artifact_type, artifact.value);
             self.processed_addresses.insert(key);
 
@@ -456,6 +470,7 @@ impl OsintAgent for BlockchainAgent {
                         analysis,
                     })
                     .origin(&self.config.id)
+                    .parent(&parent)
                     .confidence(0.8)
                     .ttl(120.0)
                     .build();
@@ -512,6 +527,7 @@ struct BlockstreamTxStatus {
 // Etherscan API response types
 #[derive(Debug, Deserialize)]
 struct EtherscanResponse<T> {
+    #[allow(dead_code)]
     status: String,
     result: T,
 }
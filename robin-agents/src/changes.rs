@@ -0,0 +1,180 @@
+//! Change Detector Agent
+//!
+//! Compares every scraped page with the version earlier runs saw (kept in a
+//! [`robin_core::ChangeStore`]) and reports pages whose content changed.
+//! Pages are compared in their own language: the original text when the
+//! scraper translated them, since translations vary between runs. A page
+//! scraped in several chunks is compared once all of them have arrived.
+//! - Senses: ScrapedContent signals
+//! - Emits: ContentChanged signals
+
+use async_trait::async_trait;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use tracing::info;
+
+use robin_core::{AgentType, Field, OsintPayload, PageCheck, PayloadKind, SharedChanges, Signal};
+
+use crate::{AgentConfig, AgentError, OsintAgent};
+
+/// Change detector agent - flags scraped pages that changed since earlier runs
+pub struct ChangeDetectorAgent {
+    config: AgentConfig,
+    changes: SharedChanges,
+    processed: HashSet<String>,
+    /// Chunks received so far of pages not yet complete, by URL
+    pending: HashMap<String, BTreeMap<usize, (String, String)>>,
+}
+
+impl ChangeDetectorAgent {
+    pub fn new(config: AgentConfig, changes: SharedChanges) -> Self {
+        Self {
+            config,
+            changes,
+            processed: HashSet::new(),
+            pending: HashMap::new(),
+        }
+    }
+}
+
+#[async_trait]
+impl OsintAgent for ChangeDetectorAgent {
+    fn id(&self) -> &str {
+        &self.config.id
+    }
+
+    fn agent_type(&self) -> &str {
+        "change_detector"
+    }
+
+    fn senses(&self) -> &[PayloadKind] {
+        &[PayloadKind::ScrapedContent]
+    }
+
+    fn emits(&self) -> &[PayloadKind] {
+        &[PayloadKind::ContentChanged]
+    }
+
+    fn sense<'a>(&self, field: &'a Field) -> Vec<&'a Signal> {
+        field
+            .sense_kinds(&[PayloadKind::ScrapedContent], 0.0)
+            .into_iter()
+            .filter(|signal| !self.processed.contains(&signal.origin_hash))
+            .collect()
+    }
+
+    async fn process(&mut self, field: &mut Field) -> Result<Vec<String>, AgentError> {
+        let signals: Vec<Signal> = self.sense(field).into_iter().cloned().collect();
+        if signals.is_empty() {
+            return Err(AgentError::NoWork);
+        }
+
+        let mut changed = Vec::new();
+        {
+            let mut changes = self.changes.lock().unwrap();
+            for signal in &signals {
+                self.processed.insert(signal.origin_hash.clone());
+                let OsintPayload::ScrapedContent { url, title, text, original_text, chunk, .. } = &signal.payload else {
+                    continue;
+                };
+                let text = original_text.as_deref().unwrap_or(text);
+                let (page, parents) = match chunk {
+                    None => (text.to_string(), vec![signal.origin_hash.clone()]),
+                    Some(chunk) => {
+                        let parts = self.pending.entry(url.clone()).or_default();
+                        parts.insert(chunk.index, (text.to_string(), signal.origin_hash.clone()));
+                        if parts.len() < chunk.count {
+                            continue;
+                        }
+                        let (texts, hashes): (Vec<_>, Vec<_>) =
+                            self.pending.remove(url).unwrap_or_default().into_values().unzip();
+                        (texts.join("\n"), hashes)
+                    }
+                };
+                if let PageCheck::Changed(diff) = changes.check(url, &page) {
+                    info!("{} changed since {}: {}", url, diff.previous_at.format("%Y-%m-%d"), diff.summary());
+                    changed.push((url.clone(), title.clone(), diff, parents));
+                }
+            }
+        }
+
+        let mut emitted = Vec::new();
+        for (url, title, diff, parents) in changed {
+            let signal = Signal::builder(OsintPayload::ContentChanged {
+                url,
+                title,
+                previous_hash: diff.previous_hash.clone(),
+                hash: diff.hash.clone(),
+                added_lines: diff.added.len(),
+                removed_lines: diff.removed.len(),
+                summary: diff.summary(),
+            })
+            .origin(&self.config.id)
+            .parents(parents)
+            .confidence(1.0)
+            .ttl(300.0)
+            .build();
+            emitted.push(field.emit(signal));
+        }
+        Ok(emitted)
+    }
+
+    fn heartbeat(&self, field: &mut Field) {
+        let signal = Signal::builder(OsintPayload::Heartbeat {
+            agent_id: self.config.id.clone(),
+            agent_type: AgentType::ChangeDetector,
+            capacity: 1.0,
+        })
+        .origin(&self.config.id)
+        .ttl(10.0)
+        .build();
+
+        field.emit(signal);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use robin_core::ChangeStore;
+    use std::sync::{Arc, Mutex};
+
+    fn page(text: &str) -> Signal {
+        Signal::builder(OsintPayload::ScrapedContent {
+            url: "http://leaks.onion/".to_string(),
+            title: "Victims".to_string(),
+            text: text.to_string(),
+            char_count: text.len(),
+            screenshot: None,
+            language: None,
+            original_text: None,
+            chunk: None,
+            metadata: None,
+        })
+        .build()
+    }
+
+    #[tokio::test]
+    async fn test_reports_changed_pages() {
+        let changes = Arc::new(Mutex::new(ChangeStore::default()));
+        changes.lock().unwrap().check("http://leaks.onion/", "acme corp\nglobex");
+        let mut agent = ChangeDetectorAgent::new(AgentConfig::default().with_id("changes-1"), changes.clone());
+
+        let mut field = Field::new();
+        field.emit(page("acme corp\nglobex"));
+        assert!(agent.process(&mut field).await.unwrap().is_empty());
+
+        field.emit(page("acme corp\ninitech"));
+        let emitted = agent.process(&mut field).await.unwrap();
+        assert_eq!(emitted.len(), 1);
+        match &field.get(&emitted[0]).unwrap().payload {
+            OsintPayload::ContentChanged { url, added_lines, removed_lines, summary, hash, .. } => {
+                assert_eq!(url, "http://leaks.onion/");
+                assert_eq!((*added_lines, *removed_lines), (1, 1));
+                assert!(summary.contains("added \"initech\""));
+                assert_eq!(hash, &changes.lock().unwrap().get(url).unwrap().hash);
+            }
+            other => panic!("unexpected payload {:?}", other),
+        }
+        assert!(matches!(agent.process(&mut field).await, Err(AgentError::NoWork)));
+    }
+}
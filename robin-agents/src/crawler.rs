@@ -1,22 +1,46 @@
 //! Crawler Agent
 //!
-//! Searches dark web search engines for results.
-//! - Senses: RefinedQuery signals
+//! Searches the engines in `TorConfig::search_engines` for results: Tor
+//! onion engines, plus I2P eepsite engines when `TorConfig::i2p` is set and
+//! clearnet engines when `TorConfig::clearnet_search` is. Each result is
+//! tagged with the network it lives on. Each query goes to the
+//! engines that index its language and cover the configured focus, and
+//! paginating engines are followed up to `max_pages` result pages. With a
+//! [`SeenStore`](robin_core::SeenStore), results earlier runs already
+//! reported are dropped. When the refined query carries per-category
+//! variants, each engine is searched with the variant for a category it
+//! covers, and results record the phrasing that found them. Several
+//! crawlers split each query's engines between them (see
+//! [`CrawlerAgent::with_partition`]) instead of all searching every engine,
+//! and claim their share of a query on the field before searching, so a
+//! crawler with the same share (on a replica, say) doesn't repeat it.
+//! - Senses: RefinedQuery signals (and TaskClaims, to retry queries whose claim lapsed)
 //! - Emits: RawResult signals
 
 use async_trait::async_trait;
+use std::collections::HashSet;
 use tracing::{debug, info};
 
-use robin_core::{active_engines, AgentType, Field, OsintPayload, Signal};
+use robin_core::{
+    investigation_task, query_for, select_engines, AgentType, ClaimOutcome, EngineCategory, Field, Network, OsintPayload, PayloadKind, SearchEngine,
+    SeenKind, SharedSeen, Signal,
+};
 use robin_tor::{crawl_engines, TorConfig};
 
-use crate::{AgentConfig, AgentError, OsintAgent};
+use crate::{claimed_by_others, AgentConfig, AgentError, OsintAgent};
 
 /// Crawler agent - searches dark web search engines
 pub struct CrawlerAgent {
     config: AgentConfig,
     tor_config: TorConfig,
-    processed_queries: Vec<String>,
+    /// Queries done, by investigation
+    processed_queries: Vec<(Option<String>, String)>,
+    focus: Vec<EngineCategory>,
+    max_pages: u32,
+    /// URLs seen by earlier runs (None = report everything)
+    seen: Option<SharedSeen>,
+    /// This crawler's share of the engines: (index, crawler count)
+    partition: (usize, usize),
 }
 
 impl CrawlerAgent {
@@ -25,12 +49,63 @@ impl CrawlerAgent {
             config,
             tor_config,
             processed_queries: Vec::new(),
+            focus: Vec::new(),
+            max_pages: 1,
+            seen: None,
+            partition: (0, 1),
         }
     }
 
+    /// Crawl only share `index` of `count` of each query's engines
+    ///
+    /// Engines are dealt round-robin in registry order, so crawlers given
+    /// indexes `0..count` search disjoint sets that together cover every
+    /// engine, each with the phrasing picked for it.
+    pub fn with_partition(mut self, index: usize, count: usize) -> Self {
+        let count = count.max(1);
+        self.partition = (index % count, count);
+        self
+    }
+
+    /// Skip results earlier runs reported, and record this run's
+    pub fn with_seen(mut self, seen: SharedSeen) -> Self {
+        self.seen = Some(seen);
+        self
+    }
+
+    /// Only crawl engines covering these categories (empty = all engines)
+    pub fn with_focus(mut self, focus: Vec<EngineCategory>) -> Self {
+        self.focus = focus;
+        self
+    }
+
+    /// Crawl up to `max_pages` result pages of engines that paginate (default 1)
+    pub fn with_max_pages(mut self, max_pages: u32) -> Self {
+        self.max_pages = max_pages.max(1);
+        self
+    }
+
     pub fn with_default_tor(config: AgentConfig) -> Self {
         Self::new(config, TorConfig::default())
     }
+
+    /// Claim ID of this crawler's share of `query` in an investigation
+    fn task_id(&self, investigation: Option<&str>, query: &str) -> String {
+        investigation_task(investigation, &format!("crawl:{}/{}:{}", self.partition.0, self.partition.1, query))
+    }
+
+    fn processed(&self, investigation: &Option<String>, query: &str) -> bool {
+        self.processed_queries.iter().any(|(id, done)| id == investigation && done == query)
+    }
+
+    /// Spare capacity, used as the affinity of claims
+    fn capacity(&self) -> f64 {
+        if self.processed_queries.len() < 3 {
+            1.0
+        } else {
+            0.5
+        }
+    }
 }
 
 #[async_trait]
@@ -43,15 +118,24 @@ impl OsintAgent for CrawlerAgent {
         "crawler"
     }
 
-    fn sense<'a>(&self, field: &'a Field) -> Vec<&'a Signal> {
-        field.sense_where(|signal| {
-            if signal.effective_intensity(field.now()) < self.config.sensing_threshold {
-                return false;
-            }
+    fn senses(&self) -> &[PayloadKind] {
+        // Claims too: a lapsed claim hands its query back
+        &[PayloadKind::RefinedQuery, PayloadKind::TaskClaim]
+    }
 
-            matches!(&signal.payload, OsintPayload::RefinedQuery { refined, .. }
-                if !self.processed_queries.contains(refined))
-        })
+    fn emits(&self) -> &[PayloadKind] {
+        &[PayloadKind::RawResult]
+    }
+
+    fn sense<'a>(&self, field: &'a Field) -> Vec<&'a Signal> {
+        let claimed = claimed_by_others(field, &self.config.id);
+        field
+            .sense_kinds(&[PayloadKind::RefinedQuery], self.config.sensing_threshold)
+            .into_iter()
+            .filter(|signal| matches!(&signal.payload, OsintPayload::RefinedQuery { refined, .. }
+                if !self.processed(&signal.investigation_id, refined)
+                    && !claimed.contains(self.task_id(signal.investigation_id.as_deref(), refined).as_str())))
+            .collect()
     }
 
     async fn process(&mut self, field: &mut Field) -> Result<Vec<String>, AgentError> {
@@ -64,35 +148,94 @@ impl OsintAgent for CrawlerAgent {
         let mut emitted = Vec::new();
 
         for signal in signals {
-            if let OsintPayload::RefinedQuery { refined, .. } = &signal.payload {
+            if let OsintPayload::RefinedQuery { refined, variants, .. } = &signal.payload {
+                if let ClaimOutcome::Held { holder } = field.claim_task(
+                    &self.task_id(signal.investigation_id.as_deref(), refined),
+                    &self.config.id,
+                    self.capacity(),
+                ) {
+                    debug!("{} already crawling {}", holder, refined);
+                    continue;
+                }
                 info!("Crawler searching for: {}", refined);
 
                 // Mark as processed
-                self.processed_queries.push(refined.clone());
+                self.processed_queries.push((signal.investigation_id.clone(), refined.clone()));
+
+                // Active engines for every configured network, routed by language and focus
+                let registry = &self.tor_config.search_engines;
+                let mut engines = select_engines(registry.active(Network::Tor), refined, &self.focus);
+                if self.tor_config.i2p.is_some() {
+                    engines.extend(select_engines(registry.active(Network::I2p), refined, &self.focus));
+                }
+                if self.tor_config.clearnet_search {
+                    engines.extend(select_engines(registry.active(Network::Clearnet), refined, &self.focus));
+                }
+                let (index, count) = self.partition;
+                let total = engines.len();
+                let engines: Vec<&SearchEngine> = engines.into_iter().skip(index).step_by(count).collect();
+                debug!("Crawling {} of {} search engines", engines.len(), total);
 
-                // Get active search engines
-                let engines: Vec<_> = active_engines().collect();
-                debug!("Crawling {} search engines", engines.len());
+                // Engines grouped by the phrasing they are searched with
+                let mut groups: Vec<(&str, Vec<&SearchEngine>)> = Vec::new();
+                for engine in engines {
+                    let query = query_for(engine, refined, variants);
+                    match groups.iter_mut().find(|(q, _)| *q == query) {
+                        Some((_, group)) => group.push(engine),
+                        None => groups.push((query, vec![engine])),
+                    }
+                }
 
-                // Crawl all engines concurrently
-                let results = crawl_engines(
-                    &engines,
-                    refined,
-                    &self.tor_config,
-                    self.config.max_concurrent,
-                )
-                .await;
+                // Crawl each group's engines concurrently; a URL found twice keeps its first phrasing
+                let mut results = Vec::new();
+                let mut found: HashSet<String> = HashSet::new();
+                for (query, group) in groups {
+                    if query != refined {
+                        debug!("Searching {} engines for variant: {}", group.len(), query);
+                    }
+                    let group_results = crawl_engines(
+                        &group,
+                        query,
+                        &self.tor_config,
+                        self.config.max_concurrent,
+                        self.max_pages,
+                    )
+                    .await;
+                    for result in group_results {
+                        if found.insert(result.url.trim_end_matches('/').to_lowercase()) {
+                            results.push((result, (query != refined).then(|| query.to_string())));
+                        }
+                    }
+                }
 
                 info!("Found {} raw results", results.len());
+                let results = match &self.seen {
+                    Some(seen) => {
+                        let mut seen = seen.lock().unwrap();
+                        let fresh: Vec<_> = results
+                            .into_iter()
+                            .filter(|(r, _)| !seen.seen_before(SeenKind::Result, &r.url))
+                            .collect();
+                        for (result, _) in &fresh {
+                            seen.record(SeenKind::Result, &result.url);
+                        }
+                        debug!("{} results not seen by earlier runs", fresh.len());
+                        fresh
+                    }
+                    None => results,
+                };
 
                 // Emit a signal for each result
-                for result in results {
+                for (result, query) in results {
                     let result_signal = Signal::builder(OsintPayload::RawResult {
                         url: result.url.clone(),
                         title: result.title.clone(),
                         engine: result.engine.clone(),
+                        network: Network::classify(&result.url),
+                        query,
                     })
                     .origin(&self.config.id)
+                    .parent(&signal.origin_hash)
                     .confidence(0.7) // Lower confidence until filtered
                     .ttl(90.0)
                     .build();
@@ -112,11 +255,7 @@ impl OsintAgent for CrawlerAgent {
         let signal = Signal::builder(OsintPayload::Heartbeat {
             agent_id: self.config.id.clone(),
             agent_type: AgentType::Crawler,
-            capacity: if self.processed_queries.len() < 3 {
-                1.0
-            } else {
-                0.5
-            },
+            capacity: self.capacity(),
         })
         .origin(&self.config.id)
         .ttl(10.0)
@@ -125,3 +264,163 @@ impl OsintAgent for CrawlerAgent {
         field.emit(signal);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use robin_core::SearchEngineRegistry;
+    use robin_tor::{engine_request, Cassette, Interaction};
+
+    #[tokio::test]
+    async fn test_crawler_replays_engine_results() {
+        // The crawler only queries the engines in its registry
+        let search_engines =
+            SearchEngineRegistry::from_toml("[[engine]]\nname = \"Local\"\nurl_template = \"http://local.onion/?q={query}\"\n")
+                .unwrap();
+        let engine = search_engines.get("Local").unwrap().clone();
+        let request = engine_request(&engine, "ransomware");
+        let cassette = Cassette::from_interactions(vec![Interaction {
+            method: request.method.to_string(),
+            url: request.url.clone(),
+            request_body: request.body.clone(),
+            status: 200,
+            body: r#"<a href="http://2gzyxa5ihm7nsggfxnu52rck2vv4rvmdlkiu3zzui5du4xyclen53wid.onion/">Leak forum</a>"#
+                .to_string(),
+        }]);
+        let tor_config = TorConfig {
+            cassette: Some(cassette),
+            max_retries: 0,
+            search_engines,
+            ..Default::default()
+        };
+        let mut agent = CrawlerAgent::new(AgentConfig::default().with_id("crawler-1"), tor_config.clone());
+
+        let mut field = Field::new();
+        let query = field.emit(Signal::builder(OsintPayload::RefinedQuery {
+            original: "ransomware".to_string(),
+            refined: "ransomware".to_string(),
+            confidence: 1.0,
+            raw: None,
+            variants: Vec::new(),
+        }).build());
+
+        let emitted = agent.process(&mut field).await.unwrap();
+        assert_eq!(emitted.len(), 1);
+        let result = field.get(&emitted[0]).unwrap();
+        assert!(matches!(&result.payload, OsintPayload::RawResult { title, engine: name, .. }
+            if title == "Leak forum" && *name == engine.name));
+        assert_eq!(result.parent_hashes, vec![query]);
+
+        // The query is not crawled twice, by this crawler or one with the same share
+        assert!(matches!(agent.process(&mut field).await, Err(AgentError::NoWork)));
+        assert_eq!(field.task_holder("crawl:0/1:ransomware"), Some("crawler-1"));
+        let mut twin = CrawlerAgent::new(AgentConfig::default().with_id("crawler-2"), tor_config);
+        assert!(matches!(twin.process(&mut field).await, Err(AgentError::NoWork)));
+    }
+
+    #[tokio::test]
+    async fn test_crawler_searches_engines_with_their_variant() {
+        let search_engines = SearchEngineRegistry::from_toml(
+            "[[engine]]\nname = \"Index\"\nurl_template = \"http://index.onion/?q={query}\"\ncategories = [\"index\"]\n\n\
+             [[engine]]\nname = \"Leaks\"\nurl_template = \"http://leaks.onion/?q={query}\"\ncategories = [\"leaks\"]\n",
+        )
+        .unwrap();
+        let page = |url: &str, link: &str| Interaction {
+            method: "GET".to_string(),
+            url: url.to_string(),
+            request_body: None,
+            status: 200,
+            body: format!(r#"<a href="{}">Result</a>"#, link),
+        };
+        let cassette = Cassette::from_interactions(vec![
+            page("http://index.onion/?q=lockbit+ransomware", "http://aaaaaaaaaaaaaaaaaaaaaaaaaa.onion/"),
+            page("http://leaks.onion/?q=lockbit+victim+data", "http://bbbbbbbbbbbbbbbbbbbbbbbbbb.onion/"),
+        ]);
+        let tor_config = TorConfig {
+            cassette: Some(cassette),
+            max_retries: 0,
+            search_engines,
+            ..Default::default()
+        };
+        let mut agent = CrawlerAgent::new(AgentConfig::default().with_id("crawler-1"), tor_config);
+
+        let mut field = Field::new();
+        field.emit(Signal::builder(OsintPayload::RefinedQuery {
+            original: "lockbit".to_string(),
+            refined: "lockbit ransomware".to_string(),
+            confidence: 1.0,
+            raw: None,
+            variants: vec![robin_core::QueryVariant {
+                category: EngineCategory::Leaks,
+                query: "lockbit victim data".to_string(),
+            }],
+        }).build());
+
+        let emitted = agent.process(&mut field).await.unwrap();
+        let mut found: Vec<(String, Option<String>)> = emitted
+            .iter()
+            .filter_map(|hash| match &field.get(hash).unwrap().payload {
+                OsintPayload::RawResult { engine, query, .. } => Some((engine.clone(), query.clone())),
+                _ => None,
+            })
+            .collect();
+        found.sort();
+        assert_eq!(
+            found,
+            vec![("Index".to_string(), None), ("Leaks".to_string(), Some("lockbit victim data".to_string()))]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_partitioned_crawlers_split_engines() {
+        let search_engines = SearchEngineRegistry::from_toml(
+            "[[engine]]\nname = \"A\"\nurl_template = \"http://a.onion/?q={query}\"\n\n\
+             [[engine]]\nname = \"B\"\nurl_template = \"http://b.onion/?q={query}\"\n\n\
+             [[engine]]\nname = \"C\"\nurl_template = \"http://c.onion/?q={query}\"\n",
+        )
+        .unwrap();
+        let interactions = ["a", "b", "c"]
+            .iter()
+            .map(|engine| Interaction {
+                method: "GET".to_string(),
+                url: format!("http://{}.onion/?q=lockbit", engine),
+                request_body: None,
+                status: 200,
+                body: format!(r#"<a href="http://{}.onion/">Result</a>"#, engine.repeat(26)),
+            })
+            .collect();
+        let tor_config = TorConfig {
+            cassette: Some(Cassette::from_interactions(interactions)),
+            max_retries: 0,
+            search_engines,
+            ..Default::default()
+        };
+
+        let mut engines = Vec::new();
+        for index in 0..2 {
+            let id = format!("crawler-{}", index + 1);
+            let mut agent = CrawlerAgent::new(AgentConfig::default().with_id(&id), tor_config.clone()).with_partition(index, 2);
+            let mut field = Field::new();
+            field.emit(Signal::builder(OsintPayload::RefinedQuery {
+                original: "lockbit".to_string(),
+                refined: "lockbit".to_string(),
+                confidence: 1.0,
+                raw: None,
+                variants: Vec::new(),
+            }).build());
+            let mut mine: Vec<String> = agent
+                .process(&mut field)
+                .await
+                .unwrap()
+                .iter()
+                .filter_map(|hash| match &field.get(hash).unwrap().payload {
+                    OsintPayload::RawResult { engine, .. } => Some(engine.clone()),
+                    _ => None,
+                })
+                .collect();
+            mine.sort();
+            engines.push(mine);
+        }
+        assert_eq!(engines, vec![vec!["A".to_string(), "C".to_string()], vec!["B".to_string()]]);
+    }
+}
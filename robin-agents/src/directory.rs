@@ -0,0 +1,181 @@
+//! Directory Agent
+//!
+//! Seeds the crawl from onion link directories (Hidden Wiki style lists),
+//! which list sites the search engines miss. Directories are loaded once, on
+//! the first query, and matched locally for every refined query.
+//! - Senses: RefinedQuery signals
+//! - Emits: RawResult signals
+
+use async_trait::async_trait;
+use tracing::{debug, info, warn};
+
+use robin_core::{AgentType, Field, Network, OsintPayload, PayloadKind, Signal};
+use robin_tor::{load_directory, match_directory, DirectoryEntry, TorConfig};
+
+use crate::{AgentConfig, AgentError, OsintAgent};
+
+/// Most directory entries emitted per query
+const MAX_MATCHES: usize = 25;
+
+/// Directory agent - matches queries against onion link directories
+pub struct DirectoryAgent {
+    config: AgentConfig,
+    tor_config: TorConfig,
+    sources: Vec<String>,
+    entries: Option<Vec<DirectoryEntry>>,
+    /// Queries done, by investigation
+    processed_queries: Vec<(Option<String>, String)>,
+}
+
+impl DirectoryAgent {
+    /// `sources` are directory URLs or local files
+    pub fn new(config: AgentConfig, tor_config: TorConfig, sources: Vec<String>) -> Self {
+        Self {
+            config,
+            tor_config,
+            sources,
+            entries: None,
+            processed_queries: Vec::new(),
+        }
+    }
+
+    async fn load(&mut self) -> &[DirectoryEntry] {
+        if self.entries.is_none() {
+            let mut entries = Vec::new();
+            for source in &self.sources {
+                match load_directory(source, &self.tor_config).await {
+                    Ok(loaded) => {
+                        info!("Loaded {} entries from directory {}", loaded.len(), source);
+                        entries.extend(loaded);
+                    }
+                    Err(e) => warn!("Failed to load directory {}: {}", source, e),
+                }
+            }
+            self.entries = Some(entries);
+        }
+        self.entries.as_deref().unwrap_or_default()
+    }
+}
+
+#[async_trait]
+impl OsintAgent for DirectoryAgent {
+    fn id(&self) -> &str {
+        &self.config.id
+    }
+
+    fn agent_type(&self) -> &str {
+        "directory"
+    }
+
+    fn senses(&self) -> &[PayloadKind] {
+        &[PayloadKind::RefinedQuery]
+    }
+
+    fn emits(&self) -> &[PayloadKind] {
+        &[PayloadKind::RawResult]
+    }
+
+    fn sense<'a>(&self, field: &'a Field) -> Vec<&'a Signal> {
+        field
+            .sense_kinds(&[PayloadKind::RefinedQuery], self.config.sensing_threshold)
+            .into_iter()
+            .filter(|signal| matches!(&signal.payload, OsintPayload::RefinedQuery { refined, .. }
+                if !self.processed_queries.iter().any(|(id, done)| *id == signal.investigation_id && done == refined)))
+            .collect()
+    }
+
+    async fn process(&mut self, field: &mut Field) -> Result<Vec<String>, AgentError> {
+        let queries: Vec<(String, String, Option<String>)> = self
+            .sense(field)
+            .iter()
+            .filter_map(|s| match &s.payload {
+                OsintPayload::RefinedQuery { refined, .. } => {
+                    Some((refined.clone(), s.origin_hash.clone(), s.investigation_id.clone()))
+                }
+                _ => None,
+            })
+            .collect();
+
+        if queries.is_empty() {
+            return Err(AgentError::NoWork);
+        }
+
+        let id = self.config.id.clone();
+        let mut emitted = Vec::new();
+        for (query, parent, investigation) in queries {
+            self.processed_queries.push((investigation, query.clone()));
+            let entries = self.load().await;
+            let matches = match_directory(entries, &query, MAX_MATCHES);
+            debug!("Directory matched {} entries for: {}", matches.len(), query);
+
+            for entry in matches {
+                let signal = Signal::builder(OsintPayload::RawResult {
+                    url: entry.url.clone(),
+                    title: entry.label(),
+                    engine: format!("directory:{}", entry.category.as_deref().unwrap_or(&entry.source)),
+                    network: Network::classify(&entry.url),
+                    query: None,
+                })
+                .origin(&id)
+                .parent(&parent)
+                .confidence(0.6)
+                .ttl(90.0)
+                .build();
+                emitted.push(field.emit(signal));
+            }
+        }
+
+        info!("Directory emitted {} seed results", emitted.len());
+        Ok(emitted)
+    }
+
+    fn heartbeat(&self, field: &mut Field) {
+        let signal = Signal::builder(OsintPayload::Heartbeat {
+            agent_id: self.config.id.clone(),
+            agent_type: AgentType::Directory,
+            capacity: 1.0,
+        })
+        .origin(&self.config.id)
+        .ttl(10.0)
+        .build();
+
+        field.emit(signal);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_directory_seeds_matching_results() {
+        let path = std::env::temp_dir().join(format!("robin-directory-{}.txt", std::process::id()));
+        std::fs::write(
+            &path,
+            "# Leaks\nhttp://leaksite.onion/ ransomware leak blog\n# Markets\nhttp://market.onion/ card shop\n",
+        )
+        .unwrap();
+        let mut agent = DirectoryAgent::new(
+            AgentConfig::default().with_id("directory-1"),
+            TorConfig::default(),
+            vec![path.display().to_string(), "/nonexistent/directory.html".to_string()],
+        );
+
+        let mut field = Field::new();
+        field.emit(Signal::builder(OsintPayload::RefinedQuery {
+            original: "ransomware".to_string(),
+            refined: "ransomware leaks".to_string(),
+            confidence: 1.0,
+            raw: None,
+            variants: Vec::new(),
+        }).build());
+
+        let emitted = agent.process(&mut field).await.unwrap();
+        assert_eq!(emitted.len(), 1);
+        assert!(matches!(&field.get(&emitted[0]).unwrap().payload,
+            OsintPayload::RawResult { url, engine, .. } if url == "http://leaksite.onion/" && engine == "directory:Leaks"));
+        assert!(matches!(agent.process(&mut field).await, Err(AgentError::NoWork)));
+
+        std::fs::remove_file(&path).ok();
+    }
+}
@@ -0,0 +1,96 @@
+//! Text embedding backends for the vector store
+//!
+//! [`HashingEmbedder`] works offline with no API key; [`OpenAIEmbedder`]
+//! calls any OpenAI-compatible `/embeddings` endpoint for real semantic
+//! vectors. A vector store only accepts vectors from the model it was
+//! built with, so switching embedders means starting a new store.
+
+use async_openai::{config::OpenAIConfig, types::CreateEmbeddingRequestArgs, Client};
+use async_trait::async_trait;
+use std::sync::Arc;
+
+use robin_core::{hash_embedding, HASHING_MODEL};
+
+use crate::LlmError;
+
+/// Turns texts into embedding vectors
+#[async_trait]
+pub trait Embedder: Send + Sync {
+    /// One vector per input text, in order
+    async fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>, LlmError>;
+
+    /// Model name recorded in the vector store
+    fn model_name(&self) -> &str;
+}
+
+/// Thread-safe reference to an embedder
+pub type SharedEmbedder = Arc<dyn Embedder>;
+
+/// Offline embedder using [`robin_core::hash_embedding`]
+pub struct HashingEmbedder;
+
+#[async_trait]
+impl Embedder for HashingEmbedder {
+    async fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>, LlmError> {
+        Ok(texts.iter().map(|t| hash_embedding(t)).collect())
+    }
+
+    fn model_name(&self) -> &str {
+        HASHING_MODEL
+    }
+}
+
+/// OpenAI-compatible embedding backend
+pub struct OpenAIEmbedder {
+    client: Client<OpenAIConfig>,
+    model: String,
+}
+
+impl OpenAIEmbedder {
+    pub fn new(api_key: &str, base_url: Option<&str>, model: &str) -> Self {
+        let mut config = OpenAIConfig::new().with_api_key(api_key);
+        if let Some(base_url) = base_url {
+            config = config.with_api_base(base_url);
+        }
+        Self {
+            client: Client::with_config(config),
+            model: model.to_string(),
+        }
+    }
+}
+
+#[async_trait]
+impl Embedder for OpenAIEmbedder {
+    async fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>, LlmError> {
+        if texts.is_empty() {
+            return Ok(Vec::new());
+        }
+        let request = CreateEmbeddingRequestArgs::default()
+            .model(&self.model)
+            .input(texts.to_vec())
+            .build()
+            .map_err(|e| LlmError::Api(e.to_string()))?;
+
+        let response = self
+            .client
+            .embeddings()
+            .create(request)
+            .await
+            .map_err(|e| LlmError::Api(e.to_string()))?;
+
+        let mut data = response.data;
+        data.sort_by_key(|e| e.index);
+        if data.len() != texts.len() {
+            return Err(LlmError::Api(format!(
+                "expected {} embeddings, got {}",
+                texts.len(),
+                data.len()
+            )));
+        }
+        Ok(data.into_iter().map(|e| e.embedding).collect())
+    }
+
+    fn model_name(&self) -> &str {
+        &self.model
+    }
+}
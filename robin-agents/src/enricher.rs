@@ -3,20 +3,32 @@
 //! Enriches extracted artifacts by querying:
 //! - GitHub Search (emails, usernames, code snippets)
 //! - Brave Search (IPs, domains, hashes, general web)
+//! - GitHub Security Advisories and Exploit-DB (CVEs: patches, public exploits)
+//! - Onionoo (Tor relays and bridges: role, hosting, uptime, operator contact)
 //!
 //! Artifacts are prioritized for enrichment based on type:
 //! - High: Email, Username, Domain, IP
 //! - Medium: Hash, Bitcoin address
 //! - Low: URL, OnionAddress (less useful for surface web)
+//!
+//! Certificate, SSH host key and JA3/JA4 fingerprints are enriched too, and
+//! any found in the responses are emitted as extracted artifacts of their
+//! own, so the next round pivots on the infrastructure they point to.
+//!
+//! Tor relay fingerprints and bridge lines only go to Onionoo, and only as
+//! the SHA-1 of the fingerprint, so a bridge shared in chatter isn't
+//! disclosed to search engines or in the lookup URL.
 
 use async_trait::async_trait;
 use reqwest::Client;
 use serde::Deserialize;
+use sha1::{Digest, Sha1};
 use tracing::{debug, info};
 
 use robin_core::{
-    AgentType, Artifact, ArtifactType, EnrichmentFinding, Field, OsintPayload, Signal,
+    extract_fingerprints, AgentType, Artifact, ArtifactType, EnrichmentFinding, Field, OsintPayload, PayloadKind, Signal,
 };
+use robin_tor::api_client;
 
 use crate::{AgentConfig, AgentError, OsintAgent};
 
@@ -33,6 +45,12 @@ pub struct EnrichmentConfig {
     pub enable_github: bool,
     /// Enable Brave search
     pub enable_brave: bool,
+    /// Look up CVEs in GitHub Security Advisories and Exploit-DB
+    pub enable_advisories: bool,
+    /// Look up Tor relays and bridges in Onionoo
+    pub enable_onionoo: bool,
+    /// Send API requests through this proxy URL (None = direct connection)
+    pub proxy: Option<String>,
 }
 
 impl Default for EnrichmentConfig {
@@ -43,6 +61,9 @@ impl Default for EnrichmentConfig {
             max_results_per_artifact: 5,
             enable_github: true,
             enable_brave: true,
+            enable_advisories: true,
+            enable_onionoo: true,
+            proxy: None,
         }
     }
 }
@@ -57,13 +78,13 @@ pub struct EnrichmentAgent {
 
 impl EnrichmentAgent {
     pub fn new(config: AgentConfig, enrichment_config: EnrichmentConfig) -> Self {
+        // Falling back to a direct client would leak the analyst's IP
+        let http_client = api_client(enrichment_config.proxy.as_deref(), std::time::Duration::from_secs(30), None)
+            .expect("Invalid enrichment proxy");
         Self {
             config,
             enrichment_config,
-            http_client: Client::builder()
-                .timeout(std::time::Duration::from_secs(30))
-                .build()
-                .unwrap_or_default(),
+            http_client,
             processed_artifacts: std::collections::HashSet::new(),
         }
     }
@@ -82,7 +103,13 @@ impl EnrichmentAgent {
                 | ArtifactType::Md5
                 | ArtifactType::Bitcoin
                 | ArtifactType::Ethereum
-        )
+                | ArtifactType::CertFingerprint
+                | ArtifactType::SshHostKey
+                | ArtifactType::Ja3
+                | ArtifactType::Ja4
+        ) || (artifact.artifact_type == ArtifactType::Cve && self.enrichment_config.enable_advisories)
+            || (matches!(artifact.artifact_type, ArtifactType::TorRelay | ArtifactType::TorBridge)
+                && self.enrichment_config.enable_onionoo)
     }
 
     /// Build search query for GitHub based on artifact type
@@ -98,6 +125,9 @@ impl EnrichmentAgent {
             ArtifactType::Bitcoin | ArtifactType::Ethereum => {
                 Some(format!("\"{}\"", artifact.value))
             }
+            ArtifactType::CertFingerprint | ArtifactType::SshHostKey | ArtifactType::Ja3 | ArtifactType::Ja4 => {
+                Some(format!("\"{}\"", artifact.value))
+            }
             _ => None,
         }
     }
@@ -116,6 +146,9 @@ impl EnrichmentAgent {
             }
             ArtifactType::Bitcoin => format!("\"{}\" ransomware bitcoin", artifact.value),
             ArtifactType::Ethereum => format!("\"{}\" cryptocurrency scam", artifact.value),
+            ArtifactType::CertFingerprint => format!("\"{}\" certificate", artifact.value),
+            ArtifactType::SshHostKey => format!("\"{}\" ssh host key", artifact.value),
+            ArtifactType::Ja3 | ArtifactType::Ja4 => format!("\"{}\" tls fingerprint c2", artifact.value),
             _ => format!("\"{}\"", artifact.value),
         }
     }
@@ -238,10 +271,126 @@ impl EnrichmentAgent {
         }
     }
 
+    /// Look up a CVE in GitHub Security Advisories (severity, patched versions, PoC references)
+    async fn search_github_advisories(&self, cve: &str) -> Vec<EnrichmentFinding> {
+        let url = format!("https://api.github.com/advisories?cve_id={}", urlencoding::encode(cve));
+        let mut request = self.http_client
+            .get(&url)
+            .header("Accept", "application/vnd.github+json")
+            .header("User-Agent", "robin-smesh-osint/0.1");
+        if let Some(token) = &self.enrichment_config.github_token {
+            request = request.header("Authorization", format!("token {}", token));
+        }
+
+        match request.send().await {
+            Ok(response) if response.status().is_success() => match response.json::<Vec<GitHubAdvisory>>().await {
+                Ok(advisories) => advisory_findings(&advisories),
+                Err(e) => {
+                    debug!("Failed to parse GitHub advisories: {}", e);
+                    vec![]
+                }
+            },
+            Ok(response) => {
+                debug!("GitHub advisory lookup failed: {}", response.status());
+                vec![]
+            }
+            Err(e) => {
+                debug!("GitHub advisory request failed: {}", e);
+                vec![]
+            }
+        }
+    }
+
+    /// Look up public exploits for a CVE on Exploit-DB
+    async fn search_exploitdb(&self, cve: &str) -> Vec<EnrichmentFinding> {
+        let id = cve.trim_start_matches("CVE-").trim_start_matches("cve-");
+        let url = format!("https://www.exploit-db.com/search?cve={}", urlencoding::encode(id));
+
+        match self.http_client
+            .get(&url)
+            .header("Accept", "application/json")
+            .header("X-Requested-With", "XMLHttpRequest")
+            .header("User-Agent", "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36")
+            .send()
+            .await
+        {
+            Ok(response) if response.status().is_success() => match response.json::<serde_json::Value>().await {
+                Ok(data) => exploitdb_findings(&data, self.enrichment_config.max_results_per_artifact),
+                Err(e) => {
+                    debug!("Failed to parse Exploit-DB response: {}", e);
+                    vec![]
+                }
+            },
+            Ok(response) => {
+                debug!("Exploit-DB search failed: {}", response.status());
+                vec![]
+            }
+            Err(e) => {
+                debug!("Exploit-DB request failed: {}", e);
+                vec![]
+            }
+        }
+    }
+
+    /// Look up a Tor relay or bridge in Onionoo by its hashed fingerprint
+    async fn search_onionoo(&self, fingerprint: &str) -> Vec<EnrichmentFinding> {
+        let Some(hashed) = hashed_fingerprint(fingerprint) else {
+            return vec![];
+        };
+        let url = format!("https://onionoo.torproject.org/details?lookup={}", hashed);
+
+        match self.http_client
+            .get(&url)
+            .header("Accept", "application/json")
+            .header("User-Agent", "robin-smesh-osint/0.1")
+            .send()
+            .await
+        {
+            Ok(response) if response.status().is_success() => match response.json::<OnionooDetails>().await {
+                Ok(details) => onionoo_findings(&details),
+                Err(e) => {
+                    debug!("Failed to parse Onionoo response: {}", e);
+                    vec![]
+                }
+            },
+            Ok(response) => {
+                debug!("Onionoo lookup failed: {}", response.status());
+                vec![]
+            }
+            Err(e) => {
+                debug!("Onionoo request failed: {}", e);
+                vec![]
+            }
+        }
+    }
+
     /// Enrich a single artifact from all configured sources
     async fn enrich_artifact(&self, artifact: &Artifact) -> Vec<(String, Vec<EnrichmentFinding>)> {
         let mut results = Vec::new();
 
+        if matches!(artifact.artifact_type, ArtifactType::TorRelay | ArtifactType::TorBridge) {
+            if let Some(fingerprint) = relay_fingerprint(&artifact.value) {
+                let findings = self.search_onionoo(fingerprint).await;
+                if !findings.is_empty() {
+                    results.push(("onionoo".to_string(), findings));
+                }
+            }
+            return results;
+        }
+
+        if artifact.artifact_type == ArtifactType::Cve {
+            let cve = artifact.value.to_uppercase();
+            let (advisories, exploits) =
+                tokio::join!(self.search_github_advisories(&cve), self.search_exploitdb(&cve));
+            if !advisories.is_empty() {
+                results.push(("github_advisories".to_string(), advisories));
+            }
+            if !exploits.is_empty() {
+                results.push(("exploitdb".to_string(), exploits));
+            }
+            return results;
+        }
+
         if self.enrichment_config.enable_github {
             let findings = self.search_github(artifact).await;
             if !findings.is_empty() {
@@ -270,26 +419,35 @@ impl OsintAgent for EnrichmentAgent {
         "enricher"
     }
 
+    fn senses(&self) -> &[PayloadKind] {
+        &[PayloadKind::ExtractedArtifacts]
+    }
+
+    fn emits(&self) -> &[PayloadKind] {
+        &[PayloadKind::EnrichedArtifacts, PayloadKind::ExtractedArtifacts]
+    }
+
     fn sense<'a>(&self, field: &'a Field) -> Vec<&'a Signal> {
         field.sense_by_type(self.config.sensing_threshold).extracted_artifacts
     }
 
     async fn process(&mut self, field: &mut Field) -> Result<Vec<String>, AgentError> {
         // Collect artifacts to process (to avoid borrow issues)
-        let artifacts_to_process: Vec<Artifact> = {
+        // Each with the hash of the signal it was extracted in
+        let artifacts_to_process: Vec<(Artifact, String)> = {
             let signals = field.sense_by_type(0.3);
             signals
                 .extracted_artifacts
                 .iter()
                 .filter_map(|signal| {
                     if let OsintPayload::ExtractedArtifacts { artifacts, .. } = &signal.payload {
-                        Some(artifacts.clone())
+                        Some(artifacts.iter().map(|artifact| (artifact.clone(), signal.origin_hash.clone())))
                     } else {
                         None
                     }
                 })
                 .flatten()
-                .filter(|artifact| {
+                .filter(|(artifact, _)| {
                     let artifact_key = format!("{:?}:{}", artifact.artifact_type, artifact.value);
                     !self.processed_artifacts.contains(&artifact_key) && self.should_enrich(artifact)
                 })
@@ -298,7 +456,7 @@ impl OsintAgent for EnrichmentAgent {
 
         let mut emitted_hashes = Vec::new();
 
-        for artifact in artifacts_to_process {
+        for (artifact, parent) in artifacts_to_process {
             let artifact_key = format!("{:?}:{}", artifact.artifact_type, artifact.value);
             self.processed_artifacts.insert(artifact_key);
 
@@ -319,17 +477,32 @@ impl OsintAgent for EnrichmentAgent {
                         artifact.artifact_type
                     );
 
+                    let pivots = pivot_artifacts(&artifact, &findings);
                     let signal = Signal::builder(OsintPayload::EnrichedArtifacts {
                         artifact: artifact.clone(),
                         source: source.clone(),
                         findings,
                     })
                     .origin(&self.config.id)
+                    .parent(&parent)
                     .confidence(0.7)
                     .ttl(120.0)
                     .build();
 
                     let hash = field.emit(signal);
+                    if let Some(first) = pivots.first() {
+                        info!("Pivoting on {} fingerprints from {} findings", pivots.len(), source);
+                        let signal = Signal::builder(OsintPayload::ExtractedArtifacts {
+                            source_url: first.source.clone().unwrap_or_else(|| source.clone()),
+                            artifacts: pivots,
+                        })
+                        .origin(&self.config.id)
+                        .parent(&hash)
+                        .confidence(0.6)
+                        .ttl(120.0)
+                        .build();
+                        emitted_hashes.push(field.emit(signal));
+                    }
                     emitted_hashes.push(hash);
                 }
             }
@@ -371,6 +544,256 @@ struct GitHubRepository {
     description: Option<String>,
 }
 
+// Onionoo details document types
+#[derive(Debug, Default, Deserialize)]
+struct OnionooDetails {
+    #[serde(default)]
+    relays: Vec<OnionooRelay>,
+    #[serde(default)]
+    bridges: Vec<OnionooBridge>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OnionooRelay {
+    nickname: String,
+    fingerprint: String,
+    #[serde(default)]
+    or_addresses: Vec<String>,
+    #[serde(default)]
+    exit_addresses: Vec<String>,
+    #[serde(default)]
+    country_name: Option<String>,
+    #[serde(default, rename = "as")]
+    as_number: Option<String>,
+    #[serde(default)]
+    as_name: Option<String>,
+    #[serde(default)]
+    flags: Vec<String>,
+    #[serde(default)]
+    first_seen: Option<String>,
+    #[serde(default)]
+    last_seen: Option<String>,
+    #[serde(default)]
+    running: bool,
+    #[serde(default)]
+    contact: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OnionooBridge {
+    nickname: String,
+    hashed_fingerprint: String,
+    #[serde(default)]
+    transports: Vec<String>,
+    #[serde(default)]
+    first_seen: Option<String>,
+    #[serde(default)]
+    last_seen: Option<String>,
+    #[serde(default)]
+    running: bool,
+}
+
+/// Relay fingerprint of a relay artifact, or the last field of a bridge line
+fn relay_fingerprint(value: &str) -> Option<&str> {
+    value
+        .split_whitespace()
+        .last()
+        .filter(|fp| fp.len() == 40 && fp.chars().all(|c| c.is_ascii_hexdigit()))
+}
+
+/// SHA-1 of a fingerprint's bytes, as Onionoo looks up relays and bridges
+fn hashed_fingerprint(fingerprint: &str) -> Option<String> {
+    let bytes = (0..fingerprint.len())
+        .step_by(2)
+        .map(|i| fingerprint.get(i..i + 2).and_then(|hex| u8::from_str_radix(hex, 16).ok()))
+        .collect::<Option<Vec<u8>>>()?;
+    Some(Sha1::digest(&bytes).iter().map(|b| format!("{:02X}", b)).collect())
+}
+
+/// Findings for the relays and bridges of an Onionoo details response
+fn onionoo_findings(details: &OnionooDetails) -> Vec<EnrichmentFinding> {
+    let seen = |first: &Option<String>, last: &Option<String>, running: bool| {
+        format!(
+            "{}, first seen {}, last seen {}",
+            if running { "running" } else { "offline" },
+            first.as_deref().unwrap_or("?"),
+            last.as_deref().unwrap_or("?")
+        )
+    };
+    let mut findings = Vec::new();
+    for relay in &details.relays {
+        let role = if relay.flags.iter().any(|f| f == "Exit") {
+            "Exit relay"
+        } else if relay.flags.iter().any(|f| f == "Guard") {
+            "Guard relay"
+        } else {
+            "Relay"
+        };
+        let hosting: Vec<&str> = [relay.as_name.as_deref(), relay.as_number.as_deref(), relay.country_name.as_deref()]
+            .into_iter()
+            .flatten()
+            .collect();
+        let mut snippet = vec![relay.or_addresses.join(", "), seen(&relay.first_seen, &relay.last_seen, relay.running)];
+        if !relay.exit_addresses.is_empty() {
+            snippet.push(format!("exits from {}", relay.exit_addresses.join(", ")));
+        }
+        if let Some(contact) = &relay.contact {
+            snippet.push(format!("contact: {}", contact));
+        }
+        findings.push(EnrichmentFinding {
+            finding_type: "tor_relay".to_string(),
+            title: format!("{} {} ({})", role, relay.nickname, hosting.join(", ")),
+            url: Some(format!("https://metrics.torproject.org/rs.html#details/{}", relay.fingerprint)),
+            snippet: snippet.join("; "),
+            relevance: 0.8,
+        });
+    }
+    for bridge in &details.bridges {
+        findings.push(EnrichmentFinding {
+            finding_type: "tor_bridge".to_string(),
+            title: format!("Bridge {} ({})", bridge.nickname, if bridge.transports.is_empty() { "vanilla".to_string() } else { bridge.transports.join(", ") }),
+            url: Some(format!("https://metrics.torproject.org/rs.html#details/{}", bridge.hashed_fingerprint)),
+            snippet: seen(&bridge.first_seen, &bridge.last_seen, bridge.running),
+            relevance: 0.8,
+        });
+    }
+    findings
+}
+
+/// Fingerprints in `findings` other than the enriched artifact itself
+fn pivot_artifacts(artifact: &Artifact, findings: &[EnrichmentFinding]) -> Vec<Artifact> {
+    let mut pivots: Vec<Artifact> = Vec::new();
+    for finding in findings {
+        let text = format!("{}\n{}", finding.title, finding.snippet);
+        for found in extract_fingerprints(&text, finding.url.as_deref()) {
+            let same = |a: &Artifact| a.artifact_type == found.artifact_type && a.value == found.value;
+            if !same(artifact) && !pivots.iter().any(same) {
+                pivots.push(found);
+            }
+        }
+    }
+    pivots
+}
+
+/// Reference URLs that point at exploit code rather than write-ups
+const EXPLOIT_REFERENCE_MARKERS: &[&str] = &["exploit", "packetstormsecurity.com", "/poc", "-poc", "_poc"];
+
+/// Findings for GitHub advisories: one per advisory, plus known exploit references
+fn advisory_findings(advisories: &[GitHubAdvisory]) -> Vec<EnrichmentFinding> {
+    let mut findings = Vec::new();
+    for advisory in advisories {
+        let patched: Vec<String> = advisory
+            .vulnerabilities
+            .iter()
+            .map(|v| {
+                let package = v.package.as_ref().map_or("?", |p| p.name.as_str());
+                match &v.first_patched_version {
+                    Some(version) => format!("{} fixed in {}", package, version),
+                    None => format!("{} has no patched release", package),
+                }
+            })
+            .collect();
+        let score = advisory.cvss.as_ref().and_then(|c| c.score).map(|s| format!(", CVSS {:.1}", s));
+        findings.push(EnrichmentFinding {
+            finding_type: "advisory".to_string(),
+            title: format!(
+                "{} ({}{}): {}",
+                advisory.ghsa_id,
+                advisory.severity.as_deref().unwrap_or("unrated"),
+                score.unwrap_or_default(),
+                advisory.summary
+            ),
+            url: Some(advisory.html_url.clone()),
+            snippet: if patched.is_empty() { "No affected packages listed".to_string() } else { patched.join("; ") },
+            relevance: 0.8,
+        });
+
+        for reference in &advisory.references {
+            let lower = reference.to_lowercase();
+            if EXPLOIT_REFERENCE_MARKERS.iter().any(|m| lower.contains(m)) {
+                findings.push(EnrichmentFinding {
+                    finding_type: "exploit".to_string(),
+                    title: format!("Public exploit/PoC referenced by {}", advisory.ghsa_id),
+                    url: Some(reference.clone()),
+                    snippet: "Listed in the advisory references".to_string(),
+                    relevance: 0.85,
+                });
+            }
+        }
+    }
+    findings
+}
+
+/// Exploit findings from an Exploit-DB search response
+///
+/// Parsed leniently: the site's JSON is undocumented and mixes strings and numbers.
+fn exploitdb_findings(data: &serde_json::Value, limit: usize) -> Vec<EnrichmentFinding> {
+    let text = |v: &serde_json::Value| match v {
+        serde_json::Value::String(s) => Some(s.clone()),
+        serde_json::Value::Number(n) => Some(n.to_string()),
+        _ => None,
+    };
+    let Some(rows) = data.get("data").and_then(|d| d.as_array()) else {
+        return vec![];
+    };
+    rows.iter()
+        .filter_map(|row| {
+            let id = row.get("id").and_then(text)?;
+            let title = match row.get("description") {
+                Some(serde_json::Value::Array(parts)) => parts.get(1).and_then(text),
+                Some(other) => text(other),
+                None => None,
+            }
+            .unwrap_or_else(|| format!("Exploit {}", id));
+            let verified = matches!(row.get("verified").and_then(text).as_deref(), Some("1"))
+                || row.get("verified").and_then(|v| v.as_bool()) == Some(true);
+            let kind = row.get("type_id").and_then(text).unwrap_or_default();
+            Some(EnrichmentFinding {
+                finding_type: "exploit".to_string(),
+                title: format!("EDB-{}: {}", id, title),
+                url: Some(format!("https://www.exploit-db.com/exploits/{}", id)),
+                snippet: format!(
+                    "{} exploit{}",
+                    if kind.is_empty() { "Public" } else { kind.as_str() },
+                    if verified { ", verified by Exploit-DB" } else { "" }
+                ),
+                relevance: if verified { 0.95 } else { 0.85 },
+            })
+        })
+        .take(limit)
+        .collect()
+}
+
+// GitHub Security Advisories API response types
+#[derive(Debug, Deserialize)]
+struct GitHubAdvisory {
+    ghsa_id: String,
+    html_url: String,
+    summary: String,
+    severity: Option<String>,
+    cvss: Option<GitHubCvss>,
+    #[serde(default)]
+    references: Vec<String>,
+    #[serde(default)]
+    vulnerabilities: Vec<GitHubAdvisoryVulnerability>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GitHubCvss {
+    score: Option<f64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GitHubAdvisoryVulnerability {
+    package: Option<GitHubAdvisoryPackage>,
+    first_patched_version: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GitHubAdvisoryPackage {
+    name: String,
+}
+
 // Brave Search API response types
 #[derive(Debug, Deserialize)]
 struct BraveSearchResponse {
@@ -423,6 +846,34 @@ mod tests {
         assert!(query.unwrap().contains("hacker@dark.net"));
     }
 
+    #[test]
+    fn test_cve_findings() {
+        let advisories: Vec<GitHubAdvisory> = serde_json::from_str(r#"[{
+            "ghsa_id": "GHSA-jfh8-c2jp-5v3q", "html_url": "https://github.com/advisories/GHSA-jfh8-c2jp-5v3q",
+            "summary": "Remote code injection in Log4j", "severity": "critical", "cvss": {"score": 10.0},
+            "references": ["https://logging.apache.org/log4j/2.x/security.html",
+                           "https://github.com/kozmer/log4j-shell-poc"],
+            "vulnerabilities": [{"package": {"ecosystem": "maven", "name": "org.apache.logging.log4j:log4j-core"},
+                                 "first_patched_version": "2.15.0"}]
+        }]"#).unwrap();
+        let findings = advisory_findings(&advisories);
+        assert_eq!(findings.len(), 2);
+        assert!(findings[0].title.contains("critical, CVSS 10.0"));
+        assert_eq!(findings[0].snippet, "org.apache.logging.log4j:log4j-core fixed in 2.15.0");
+        assert_eq!(findings[1].finding_type, "exploit");
+
+        let edb = serde_json::json!({"data": [
+            {"id": "50592", "description": ["50592", "Apache Log4j2 2.14.1 - Information Disclosure"],
+             "type_id": "remote", "verified": "1"},
+            {"id": 51183, "description": "Log4Shell scanner", "verified": 0}
+        ]});
+        let exploits = exploitdb_findings(&edb, 5);
+        assert_eq!(exploits.len(), 2);
+        assert_eq!(exploits[0].url.as_deref(), Some("https://www.exploit-db.com/exploits/50592"));
+        assert_eq!(exploits[0].snippet, "remote exploit, verified by Exploit-DB");
+        assert_eq!(exploits[1].title, "EDB-51183: Log4Shell scanner");
+    }
+
     #[test]
     fn test_brave_query() {
         let agent = EnrichmentAgent::new(
@@ -438,4 +889,65 @@ mod tests {
         assert!(query.contains("ransomware"));
         assert!(query.contains("bitcoin"));
     }
+
+    #[test]
+    fn test_onionoo_findings() {
+        let bridge = Artifact::new(
+            ArtifactType::TorBridge,
+            "obfs4 192.0.2.10:443 2B280B23E1107BB62ABFC40DDCC8824814F80A72".to_string(),
+        );
+        let agent = EnrichmentAgent::new(AgentConfig::default().with_id("test"), EnrichmentConfig::default());
+        assert!(agent.should_enrich(&bridge));
+        assert_eq!(agent.github_query(&bridge), None);
+        let fingerprint = relay_fingerprint(&bridge.value).unwrap();
+        // Only the hash of the fingerprint leaves the process
+        assert_eq!(hashed_fingerprint(fingerprint).unwrap(), "5481936581E23D2D178105D44DB6915AB06BFB7F");
+        assert_eq!(hashed_fingerprint("zz"), None);
+
+        let details: OnionooDetails = serde_json::from_str(r#"{
+            "relays": [{"nickname": "exitnode1", "fingerprint": "9695DFC35FFEB861329B9F1AB04C46397020CE31",
+                        "or_addresses": ["198.51.100.7:9001"], "exit_addresses": ["198.51.100.8"],
+                        "country_name": "Germany", "as": "AS24940", "as_name": "Hetzner Online GmbH",
+                        "flags": ["Exit", "Fast", "Running"], "first_seen": "2023-01-02 00:00:00",
+                        "last_seen": "2024-05-01 12:00:00", "running": true, "contact": "abuse@example.org"}],
+            "bridges": [{"nickname": "bridge7", "hashed_fingerprint": "0011AABB", "transports": ["obfs4"],
+                         "running": false}]
+        }"#).unwrap();
+        let findings = onionoo_findings(&details);
+        assert_eq!(findings.len(), 2);
+        assert_eq!(findings[0].title, "Exit relay exitnode1 (Hetzner Online GmbH, AS24940, Germany)");
+        assert!(findings[0].snippet.contains("exits from 198.51.100.8"));
+        assert!(findings[0].snippet.contains("contact: abuse@example.org"));
+        assert_eq!(findings[1].title, "Bridge bridge7 (obfs4)");
+        assert!(findings[1].snippet.starts_with("offline"));
+    }
+
+    #[test]
+    fn test_fingerprint_pivots() {
+        let agent = EnrichmentAgent::new(
+            AgentConfig::default().with_id("test"),
+            EnrichmentConfig::default(),
+        );
+        let ja3 = Artifact::new(ArtifactType::Ja3, "e7d705a3286e19ea42f587b344ee6865".to_string());
+        assert!(agent.should_enrich(&ja3));
+        assert_eq!(agent.github_query(&ja3).as_deref(), Some("\"e7d705a3286e19ea42f587b344ee6865\""));
+
+        let finding = |snippet: &str| EnrichmentFinding {
+            finding_type: "web_search".to_string(),
+            title: "Cobalt Strike team server".to_string(),
+            url: Some("https://intel.example/cs".to_string()),
+            snippet: snippet.to_string(),
+            relevance: 0.7,
+        };
+        let pivots = pivot_artifacts(
+            &ja3,
+            &[
+                finding("JA3: e7d705a3286e19ea42f587b344ee6865, JA3S ae4edc6faf64d08308082ad26be60767"),
+                finding("ja3s=ae4edc6faf64d08308082ad26be60767 host key SHA256:Xo3m4JxDJ8Rc7J5SkNbj0Wg+Wz0bL8cqgdJrEgCz6tA"),
+            ],
+        );
+        let values: Vec<&str> = pivots.iter().map(|a| a.value.as_str()).collect();
+        assert_eq!(values, vec!["ae4edc6faf64d08308082ad26be60767", "SHA256:Xo3m4JxDJ8Rc7J5SkNbj0Wg+Wz0bL8cqgdJrEgCz6tA"]);
+        assert_eq!(pivots[0].source.as_deref(), Some("https://intel.example/cs"));
+    }
 }
@@ -1,6 +1,8 @@
 //! Extractor Agent
 //!
-//! Extracts intelligence artifacts (IOCs, TTPs, etc.) from scraped content.
+//! Extracts intelligence artifacts (IOCs, TTPs, etc.) from scraped content,
+//! every chunk of a long page included, and from the page's metadata
+//! (`mailto:` and payment links, author tags, image hashes).
 //! - Senses: ScrapedContent signals
 //! - Emits: ExtractedArtifacts signals
 
@@ -8,14 +10,16 @@ use async_trait::async_trait;
 use std::collections::HashSet;
 use tracing::{debug, info};
 
-use robin_core::{extract_artifacts, AgentType, Field, OsintPayload, Signal};
+use robin_core::{extract_artifacts, ActorDatabase, Artifact, ArtifactType, AgentType, Field, OsintPayload, PayloadKind, Signal};
 
 use crate::{AgentConfig, AgentError, OsintAgent};
 
 /// Extractor agent - identifies IOCs and artifacts in content
 pub struct ExtractorAgent {
     config: AgentConfig,
-    processed_urls: HashSet<String>,
+    /// Pages done, by investigation, URL and chunk index
+    processed_urls: HashSet<(Option<String>, String, usize)>,
+    actors: ActorDatabase,
 }
 
 impl ExtractorAgent {
@@ -23,8 +27,15 @@ impl ExtractorAgent {
         Self {
             config,
             processed_urls: HashSet::new(),
+            actors: ActorDatabase::embedded(),
         }
     }
+
+    /// Match threat actors against `actors` instead of the bundled list
+    pub fn with_actors(mut self, actors: ActorDatabase) -> Self {
+        self.actors = actors;
+        self
+    }
 }
 
 #[async_trait]
@@ -37,19 +48,29 @@ impl OsintAgent for ExtractorAgent {
         "extractor"
     }
 
-    fn sense<'a>(&self, field: &'a Field) -> Vec<&'a Signal> {
-        field.sense_where(|signal| {
-            if signal.effective_intensity(field.now()) < self.config.sensing_threshold {
-                return false;
-            }
+    fn senses(&self) -> &[PayloadKind] {
+        &[PayloadKind::ScrapedContent]
+    }
+
+    fn emits(&self) -> &[PayloadKind] {
+        &[PayloadKind::ExtractedArtifacts]
+    }
 
-            match &signal.payload {
-                OsintPayload::ScrapedContent { url, .. } => {
-                    !self.processed_urls.contains(url)
+    fn sense<'a>(&self, field: &'a Field) -> Vec<&'a Signal> {
+        field
+            .sense_kinds(&[PayloadKind::ScrapedContent], self.config.sensing_threshold)
+            .into_iter()
+            .filter(|signal| match &signal.payload {
+                OsintPayload::ScrapedContent { url, chunk, .. } => {
+                    !self.processed_urls.contains(&(
+                        signal.investigation_id.clone(),
+                        url.clone(),
+                        chunk.map_or(0, |c| c.index),
+                    ))
                 }
                 _ => false,
-            }
-        })
+            })
+            .collect()
     }
 
     async fn process(&mut self, field: &mut Field) -> Result<Vec<String>, AgentError> {
@@ -62,12 +83,31 @@ impl OsintAgent for ExtractorAgent {
         let mut emitted = Vec::new();
 
         for signal in signals {
-            if let OsintPayload::ScrapedContent { url, text, .. } = &signal.payload {
+            if let OsintPayload::ScrapedContent { url, text, original_text, chunk, metadata, .. } = &signal.payload {
                 // Mark as processed
-                self.processed_urls.insert(url.clone());
+                self.processed_urls
+                    .insert((signal.investigation_id.clone(), url.clone(), chunk.map_or(0, |c| c.index)));
+                // Addresses and handles are copied verbatim from the page, not the translation
+                let text = original_text.as_ref().unwrap_or(text);
 
                 // Extract artifacts using regex patterns
-                let artifacts = extract_artifacts(text, Some(url));
+                let mut artifacts = extract_artifacts(text, Some(url));
+                artifacts.extend(self.actors.extract_actors(text, Some(url)));
+                if let Some(metadata) = metadata {
+                    let images = metadata.images.iter().flat_map(|image| {
+                        let context = format!("image {}", image.url);
+                        let sha256 = Artifact::new(ArtifactType::Sha256, image.sha256.clone());
+                        let dhash = image.dhash.clone().map(|dhash| Artifact::new(ArtifactType::ImageHash, dhash));
+                        std::iter::once(sha256)
+                            .chain(dhash)
+                            .map(move |artifact| artifact.with_context(&context).with_source(url))
+                    });
+                    for artifact in extract_artifacts(&metadata.render(), Some(url)).into_iter().chain(images) {
+                        if !artifacts.iter().any(|a| a.artifact_type == artifact.artifact_type && a.value == artifact.value) {
+                            artifacts.push(artifact);
+                        }
+                    }
+                }
 
                 if artifacts.is_empty() {
                     debug!("No artifacts found in {}", url);
@@ -81,6 +121,7 @@ impl OsintAgent for ExtractorAgent {
                     artifacts,
                 })
                 .origin(&self.config.id)
+                .parent(&signal.origin_hash)
                 .confidence(0.85)
                 .ttl(180.0)
                 .build();
@@ -124,6 +165,11 @@ mod tests {
             title: "Test".to_string(),
             text: "Contact: admin@test.onion BTC: 1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa".to_string(),
             char_count: 100,
+            screenshot: None,
+            language: None,
+            original_text: None,
+            chunk: None,
+            metadata: None,
         })
         .origin("scraper")
         .build();
@@ -133,4 +179,41 @@ mod tests {
         let sensed = agent.sense(&field);
         assert_eq!(sensed.len(), 1);
     }
+
+    #[tokio::test]
+    async fn test_image_hashes_become_artifacts() {
+        let mut agent = ExtractorAgent::new(AgentConfig::default().with_id("extractor-1"));
+        let metadata = robin_core::PageMetadata {
+            images: vec![robin_core::ImageDigest {
+                url: "http://shop.onion/banner.png".to_string(),
+                bytes: 2048,
+                sha256: "ab".repeat(32),
+                dhash: Some("f0e1d2c3b4a59687".to_string()),
+            }],
+            ..Default::default()
+        };
+        let mut field = Field::new();
+        field.emit(
+            Signal::builder(OsintPayload::ScrapedContent {
+                url: "http://shop.onion/".to_string(),
+                title: "Shop".to_string(),
+                text: "Welcome".to_string(),
+                char_count: 7,
+                screenshot: None,
+                language: None,
+                original_text: None,
+                chunk: None,
+                metadata: Some(metadata),
+            })
+            .build(),
+        );
+
+        let emitted = agent.process(&mut field).await.unwrap();
+        let OsintPayload::ExtractedArtifacts { artifacts, .. } = &field.get(&emitted[0]).unwrap().payload else {
+            panic!("expected artifacts");
+        };
+        let types: Vec<_> = artifacts.iter().map(|a| &a.artifact_type).collect();
+        assert_eq!(types, vec![&ArtifactType::Sha256, &ArtifactType::ImageHash]);
+        assert_eq!(artifacts[1].context.as_deref(), Some("image http://shop.onion/banner.png"));
+    }
 }
@@ -0,0 +1,442 @@
+//! Feed Watcher Agent
+//!
+//! Polls RSS/Atom feeds (vendor research blogs, CERT advisories, ransomware
+//! leak trackers) and emits items relevant to the investigation as clearnet
+//! results, so the filter and analyst see the public reporting around what
+//! the dark web crawl finds.
+//! - Senses: RefinedQuery signals
+//! - Emits: RawResult signals (engine `feed:<feed title>`)
+
+use async_trait::async_trait;
+use reqwest::Client;
+use scraper::Html;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::time::{Duration, Instant};
+use tracing::{debug, info, warn};
+
+use robin_core::{AgentType, Field, Network, OsintPayload, PayloadKind, Signal};
+use robin_tor::api_client;
+
+use crate::traits::{AgentConfig, AgentError, OsintAgent};
+
+/// Feeds watched by `--feeds`
+pub const DEFAULT_FEEDS: &[&str] = &[
+    "https://www.cisa.gov/cybersecurity-advisories/all.xml",
+    "https://www.ransomware.live/rss.xml",
+    "https://thedfirreport.com/feed/",
+    "https://krebsonsecurity.com/feed/",
+    "https://www.bleepingcomputer.com/feed/",
+];
+
+/// Longest summary kept per item, in characters
+const MAX_SUMMARY: usize = 300;
+
+/// Configuration for the feed watcher agent
+#[derive(Debug, Clone)]
+pub struct FeedWatcherConfig {
+    /// RSS or Atom feed URLs
+    pub feeds: Vec<String>,
+    /// How often feeds are re-fetched for new items
+    pub poll_interval: Duration,
+    /// Request timeout
+    pub request_timeout: Duration,
+    /// Maximum items emitted per query and poll
+    pub max_items_per_query: usize,
+}
+
+impl Default for FeedWatcherConfig {
+    fn default() -> Self {
+        Self {
+            feeds: DEFAULT_FEEDS.iter().map(|f| f.to_string()).collect(),
+            poll_interval: Duration::from_secs(600),
+            request_timeout: Duration::from_secs(30),
+            max_items_per_query: 10,
+        }
+    }
+}
+
+/// One feed entry
+#[derive(Debug, Clone, PartialEq)]
+pub struct FeedItem {
+    pub title: String,
+    pub link: String,
+    /// Description or summary, as plain text
+    pub summary: String,
+    /// Publication date as written in the feed
+    pub published: Option<String>,
+    /// Title of the feed the item came from
+    pub feed: String,
+}
+
+/// Parse an RSS 2.0 or Atom document
+pub fn parse_feed(xml: &str, fallback_name: &str) -> Vec<FeedItem> {
+    let (open, close) = if xml.contains("<entry") { ("<entry", "</entry>") } else { ("<item", "</item>") };
+    let header = xml.find(open).map_or(xml, |start| &xml[..start]);
+    let feed = element_text(header, "title").unwrap_or_else(|| fallback_name.to_string());
+
+    let mut items = Vec::new();
+    let mut rest = xml;
+    while let Some(start) = find_tag(rest, open) {
+        let Some(len) = rest[start..].find(close) else {
+            break;
+        };
+        let block = &rest[start..start + len];
+        rest = &rest[start + len + close.len()..];
+
+        let Some(link) = element_text(block, "link")
+            .filter(|l| !l.is_empty())
+            .or_else(|| atom_link(block))
+        else {
+            continue;
+        };
+        let summary = ["description", "summary", "content"]
+            .iter()
+            .find_map(|tag| element_text(block, tag))
+            .map(|html| plain_text(&html))
+            .unwrap_or_default();
+        items.push(FeedItem {
+            title: element_text(block, "title").map(|t| plain_text(&t)).unwrap_or_else(|| link.clone()),
+            link,
+            summary: summary.chars().take(MAX_SUMMARY).collect(),
+            published: ["pubDate", "published", "updated"].iter().find_map(|tag| element_text(block, tag)),
+            feed: feed.clone(),
+        });
+    }
+    items
+}
+
+/// Items mentioning any query term (3+ characters), best first
+pub fn match_feed_items<'a>(items: impl IntoIterator<Item = &'a FeedItem>, query: &str) -> Vec<&'a FeedItem> {
+    let terms: Vec<String> = query
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|t| t.chars().count() >= 3)
+        .map(str::to_lowercase)
+        .collect();
+
+    let mut scored: Vec<(usize, &FeedItem)> = items
+        .into_iter()
+        .filter_map(|item| {
+            let title = item.title.to_lowercase();
+            let summary = item.summary.to_lowercase();
+            let score: usize = terms
+                .iter()
+                .map(|t| if title.contains(t.as_str()) { 2 } else { usize::from(summary.contains(t.as_str())) })
+                .sum();
+            (score > 0).then_some((score, item))
+        })
+        .collect();
+    scored.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+    scored.into_iter().map(|(_, item)| item).collect()
+}
+
+/// Position of `<name` followed by `>`, `/` or whitespace (so `<item` doesn't match `<itemref`)
+fn find_tag(xml: &str, open: &str) -> Option<usize> {
+    let mut offset = 0;
+    while let Some(pos) = xml[offset..].find(open) {
+        let at = offset + pos;
+        let next = xml[at + open.len()..].chars().next();
+        if matches!(next, Some(c) if c == '>' || c == '/' || c.is_whitespace()) {
+            return Some(at);
+        }
+        offset = at + open.len();
+    }
+    None
+}
+
+/// Decoded text of the first `<name>` element in `block`
+fn element_text(block: &str, name: &str) -> Option<String> {
+    let start = find_tag(block, &format!("<{}", name))?;
+    let open_end = start + block[start..].find('>')?;
+    if block[..open_end].ends_with('/') {
+        return None;
+    }
+    let close = format!("</{}>", name);
+    let inner = &block[open_end + 1..open_end + 1 + block[open_end + 1..].find(&close)?];
+    let inner = inner.trim();
+    let inner = inner
+        .strip_prefix("<![CDATA[")
+        .and_then(|s| s.strip_suffix("]]>"))
+        .map(str::to_string)
+        .unwrap_or_else(|| decode_entities(inner));
+    Some(inner.trim().to_string())
+}
+
+/// `href` of the item's alternate (or first) Atom `<link/>`
+fn atom_link(block: &str) -> Option<String> {
+    let mut fallback = None;
+    let mut rest = block;
+    while let Some(start) = find_tag(rest, "<link") {
+        let end = start + rest[start..].find('>')?;
+        let tag = &rest[start..end];
+        rest = &rest[end..];
+        let Some(href) = attribute(tag, "href") else {
+            continue;
+        };
+        match attribute(tag, "rel").as_deref() {
+            None | Some("alternate") => return Some(href),
+            _ => fallback = fallback.or(Some(href)),
+        }
+    }
+    fallback
+}
+
+fn attribute(tag: &str, name: &str) -> Option<String> {
+    ['"', '\''].iter().find_map(|quote| {
+        let marker = format!(" {}={}", name, quote);
+        let start = tag.find(&marker)? + marker.len();
+        let len = tag[start..].find(*quote)?;
+        Some(decode_entities(&tag[start..start + len]))
+    })
+}
+
+fn decode_entities(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+/// Feed descriptions are often HTML; keep their text only
+fn plain_text(html: &str) -> String {
+    let text: String = Html::parse_fragment(html).root_element().text().collect();
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Feed watcher agent - surfaces public reporting relevant to the query
+pub struct FeedWatcherAgent {
+    config: AgentConfig,
+    feed_config: FeedWatcherConfig,
+    client: Client,
+    /// Latest items per feed URL
+    items: BTreeMap<String, Vec<FeedItem>>,
+    last_poll: Option<Instant>,
+    queries: Vec<String>,
+    /// Hash of the RefinedQuery signal each query came from
+    query_hashes: HashMap<String, String>,
+    emitted_links: HashSet<String>,
+}
+
+impl FeedWatcherAgent {
+    pub fn new(config: AgentConfig, feed_config: FeedWatcherConfig) -> Self {
+        let client = api_client(
+            None,
+            feed_config.request_timeout,
+            Some("Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36"),
+        )
+        .expect("Failed to create HTTP client");
+
+        Self {
+            config,
+            feed_config,
+            client,
+            items: BTreeMap::new(),
+            last_poll: None,
+            queries: Vec::new(),
+            query_hashes: HashMap::new(),
+            emitted_links: HashSet::new(),
+        }
+    }
+
+    /// Re-fetch every feed; feeds that fail keep their previous items
+    async fn poll(&mut self) {
+        let feeds = self.feed_config.feeds.clone();
+        let fetches = feeds.iter().map(|url| {
+            let client = self.client.clone();
+            async move {
+                let result = match client.get(url).send().await {
+                    Ok(resp) if resp.status().is_success() => resp.text().await.map_err(|e| e.to_string()),
+                    Ok(resp) => Err(format!("HTTP {}", resp.status())),
+                    Err(e) => Err(e.to_string()),
+                };
+                (url, result)
+            }
+        });
+
+        for (url, result) in futures::future::join_all(fetches).await {
+            match result {
+                Ok(xml) => {
+                    let items = parse_feed(&xml, url);
+                    debug!("Feed {}: {} items", url, items.len());
+                    self.items.insert(url.clone(), items);
+                }
+                Err(e) => warn!("Feed {} failed: {}", url, e),
+            }
+        }
+        self.last_poll = Some(Instant::now());
+    }
+
+    fn poll_due(&self) -> bool {
+        self.last_poll.is_none_or(|at| at.elapsed() >= self.feed_config.poll_interval)
+    }
+}
+
+#[async_trait]
+impl OsintAgent for FeedWatcherAgent {
+    fn id(&self) -> &str {
+        &self.config.id
+    }
+
+    fn agent_type(&self) -> &str {
+        "feed_watcher"
+    }
+
+    fn senses(&self) -> &[PayloadKind] {
+        &[PayloadKind::RefinedQuery]
+    }
+
+    fn emits(&self) -> &[PayloadKind] {
+        &[PayloadKind::RawResult]
+    }
+
+    fn wakes_itself(&self) -> bool {
+        // Re-polls feeds on its own interval
+        true
+    }
+
+    fn sense<'a>(&self, field: &'a Field) -> Vec<&'a Signal> {
+        field
+            .sense_kinds(&[PayloadKind::RefinedQuery], self.config.sensing_threshold)
+            .into_iter()
+            .filter(|signal| matches!(&signal.payload, OsintPayload::RefinedQuery { refined, .. }
+                if !self.queries.contains(refined)))
+            .collect()
+    }
+
+    async fn process(&mut self, field: &mut Field) -> Result<Vec<String>, AgentError> {
+        let new_queries: Vec<(String, String)> = self
+            .sense(field)
+            .iter()
+            .filter_map(|s| match &s.payload {
+                OsintPayload::RefinedQuery { refined, .. } => Some((refined.clone(), s.origin_hash.clone())),
+                _ => None,
+            })
+            .collect();
+
+        // New queries match the current items; a due poll re-matches every query
+        let repoll = !self.queries.is_empty() && self.poll_due();
+        if new_queries.is_empty() && !repoll {
+            return Err(AgentError::NoWork);
+        }
+        for (query, hash) in new_queries {
+            self.query_hashes.insert(query.clone(), hash);
+            self.queries.push(query);
+        }
+        if self.poll_due() {
+            self.poll().await;
+        }
+
+        let id = self.config.id.clone();
+        let mut emitted = Vec::new();
+        for query in &self.queries {
+            let matches = match_feed_items(self.items.values().flatten(), query);
+            for item in matches.into_iter().take(self.feed_config.max_items_per_query) {
+                if !self.emitted_links.insert(item.link.clone()) {
+                    continue;
+                }
+                let signal = Signal::builder(OsintPayload::RawResult {
+                    url: item.link.clone(),
+                    title: format!("{} - {}", item.title, item.summary),
+                    engine: format!("feed:{}", item.feed),
+                    network: Network::classify(&item.link),
+                    query: None,
+                })
+                .origin(&id)
+                .parents(self.query_hashes.get(query))
+                .confidence(0.7)
+                .ttl(90.0)
+                .build();
+                emitted.push(field.emit(signal));
+            }
+        }
+
+        if emitted.is_empty() {
+            return Err(AgentError::NoWork);
+        }
+        info!("Feed watcher emitted {} items", emitted.len());
+        Ok(emitted)
+    }
+
+    fn heartbeat(&self, field: &mut Field) {
+        let signal = Signal::builder(OsintPayload::Heartbeat {
+            agent_id: self.config.id.clone(),
+            agent_type: AgentType::FeedWatcher,
+            capacity: 1.0,
+        })
+        .origin(&self.config.id)
+        .ttl(10.0)
+        .build();
+
+        field.emit(signal);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RSS: &str = r#"<?xml version="1.0"?>
+        <rss version="2.0"><channel><title>CERT Advisories</title><link>https://cert.example/</link>
+          <item><title>LockBit affiliate &amp; loader</title><link>https://cert.example/a1</link>
+            <description><![CDATA[<p>New <b>ransomware</b> loader observed</p>]]></description>
+            <pubDate>Mon, 05 Oct 2026 10:00:00 GMT</pubDate></item>
+          <item><title>Patch Tuesday</title><link>https://cert.example/a2</link>
+            <description>Monthly &lt;i&gt;updates&lt;/i&gt;</description></item>
+        </channel></rss>"#;
+
+    const ATOM: &str = r#"<feed xmlns="http://www.w3.org/2005/Atom"><title>Threat Blog</title>
+          <entry><title>Ransomware leak site tracker</title>
+            <link rel="replies" href="https://blog.example/p1#comments"/>
+            <link rel="alternate" href="https://blog.example/p1"/>
+            <summary>Weekly victims</summary><updated>2026-10-01T00:00:00Z</updated></entry>
+        </feed>"#;
+
+    #[test]
+    fn test_parse_rss_and_atom() {
+        let items = parse_feed(RSS, "https://cert.example/feed");
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0].title, "LockBit affiliate & loader");
+        assert_eq!(items[0].summary, "New ransomware loader observed");
+        assert_eq!(items[0].feed, "CERT Advisories");
+        assert_eq!(items[1].summary, "Monthly updates");
+
+        let entries = parse_feed(ATOM, "atom");
+        assert_eq!(entries[0].link, "https://blog.example/p1");
+        assert_eq!(entries[0].published.as_deref(), Some("2026-10-01T00:00:00Z"));
+
+        let mut all = items;
+        all.extend(entries);
+        let matches = match_feed_items(&all, "ransomware lockbit");
+        assert_eq!(matches.len(), 2);
+        assert_eq!(matches[0].link, "https://cert.example/a1");
+    }
+
+    #[tokio::test]
+    async fn test_feed_watcher_emits_each_item_once() {
+        let mut agent = FeedWatcherAgent::new(
+            AgentConfig::default().with_id("feeds-1"),
+            FeedWatcherConfig {
+                feeds: Vec::new(),
+                ..Default::default()
+            },
+        );
+        agent.items.insert("atom".to_string(), parse_feed(ATOM, "atom"));
+        agent.last_poll = Some(Instant::now());
+
+        let mut field = Field::new();
+        field.emit(Signal::builder(OsintPayload::RefinedQuery {
+            original: "ransomware".to_string(),
+            refined: "ransomware victims".to_string(),
+            confidence: 1.0,
+            raw: None,
+            variants: Vec::new(),
+        }).build());
+
+        let emitted = agent.process(&mut field).await.unwrap();
+        assert_eq!(emitted.len(), 1);
+        assert!(matches!(&field.get(&emitted[0]).unwrap().payload,
+            OsintPayload::RawResult { engine, .. } if engine == "feed:Threat Blog"));
+        assert!(matches!(agent.process(&mut field).await, Err(AgentError::NoWork)));
+    }
+}
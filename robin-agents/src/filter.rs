@@ -1,15 +1,21 @@
 //! Filter Agent
 //!
-//! Ranks search results by relevance using LLM analysis.
+//! Ranks search results by relevance using LLM analysis. Results are first
+//! pre-scored lexically (query-term overlap, title heuristics): obvious junk
+//! is dropped and only the best-scoring batch goes into the prompt, so the
+//! filter can take hundreds of raw results at once. With a
+//! [`Translator`](crate::Translator), titles in other languages are scored,
+//! ranked and passed on in English.
 //! - Senses: RawResult signals (batched)
 //! - Emits: FilteredResult signals (top N)
 
 use async_trait::async_trait;
+use std::collections::HashMap;
 use tracing::{debug, info};
 
-use robin_core::{AgentType, Field, OsintPayload, Signal};
+use robin_core::{detect_language, AgentType, Field, OsintPayload, PayloadKind, Signal};
 
-use crate::{AgentConfig, AgentError, OsintAgent, SharedBackend};
+use crate::{to_english, AgentConfig, AgentError, OsintAgent, SharedBackend, SharedTranslator};
 
 /// System prompt for relevance filtering
 const FILTER_SYSTEM_PROMPT: &str = r#"
@@ -28,11 +34,90 @@ Search Query: {query}
 Search Results:
 "#;
 
+/// Words too common to count as query-term overlap
+const STOPWORDS: &[&str] = &[
+    "the", "and", "for", "with", "from", "that", "this", "are", "was", "not", "you", "your", "www", "http",
+    "https", "html", "php", "onion", "i2p",
+];
+
+/// Title fragments of pages that are never worth a look
+const JUNK_TITLES: &[&str] = &[
+    "404 not found",
+    "403 forbidden",
+    "page not found",
+    "index of /",
+    "domain for sale",
+    "site is down",
+    "under construction",
+    "default web page",
+    "welcome to nginx",
+];
+
+/// Lowercase alphanumeric terms worth matching
+fn terms(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .map(|t| t.to_lowercase())
+        .filter(|t| t.chars().count() >= 3 && !STOPWORDS.contains(&t.as_str()))
+        .collect()
+}
+
+/// Cheap lexical relevance of a result to `query`, in 0.0..=1.0
+///
+/// Mostly the share of query terms found in the title or URL, with a small
+/// bonus for descriptive titles. A title that is empty or just a URL (many
+/// engines give no other) is left out, so only the URL is scored. `None`
+/// means obvious junk: an error or placeholder page.
+pub fn keyword_score(query: &str, url: &str, title: &str) -> Option<f64> {
+    let mut title = title.trim();
+    let scheme = title.get(..8).unwrap_or(title).to_ascii_lowercase();
+    if title == url || scheme.starts_with("http://") || scheme.starts_with("https://") {
+        title = "";
+    }
+    let lowered = title.to_lowercase();
+    if JUNK_TITLES.iter().any(|junk| lowered.contains(junk)) {
+        return None;
+    }
+
+    let mut query_terms = terms(query);
+    query_terms.sort();
+    query_terms.dedup();
+    let found: Vec<String> = terms(title).into_iter().chain(terms(url)).collect();
+    let overlap = if query_terms.is_empty() {
+        0.0
+    } else {
+        let hits = query_terms
+            .iter()
+            .filter(|q| found.iter().any(|t| t.starts_with(q.as_str())))
+            .count();
+        hits as f64 / query_terms.len() as f64
+    };
+
+    let words = title.split_whitespace().count();
+    let descriptive = if words >= 3 { 0.2 } else { 0.1 * words as f64 };
+    Some(0.8 * overlap + descriptive)
+}
+
+/// `(url, title)` results with junk dropped, best keyword score first
+///
+/// Ties keep their original order.
+pub fn prescore(query: &str, results: Vec<(String, String)>) -> Vec<(String, String, f64)> {
+    let mut scored: Vec<(String, String, f64)> = results
+        .into_iter()
+        .filter_map(|(url, title)| keyword_score(query, &url, &title).map(|score| (url, title, score)))
+        .collect();
+    scored.sort_by(|a, b| b.2.total_cmp(&a.2));
+    scored
+}
+
 /// Filter agent - ranks results by relevance
 pub struct FilterAgent {
     config: AgentConfig,
     backend: SharedBackend,
     batch_size: usize,
+    /// Translates non-English titles (None = rank titles as found)
+    translator: Option<SharedTranslator>,
+    /// English titles by original, since the same results are filtered every tick
+    translated_titles: HashMap<String, String>,
 }
 
 impl FilterAgent {
@@ -40,77 +125,48 @@ impl FilterAgent {
         Self {
             config,
             backend,
-            batch_size: 50, // Process up to 50 results at a time
+            batch_size: 50, // Send up to 50 pre-scored results to the LLM at a time
+            translator: None,
+            translated_titles: HashMap::new(),
         }
     }
 
-    async fn filter_results(
-        &self,
-        query: &str,
-        results: &[(usize, String, String)], // (index, url, title)
-    ) -> Result<Vec<usize>, AgentError> {
-        // Format results for LLM
-        let results_str: String = results
-            .iter()
-            .map(|(idx, url, title)| {
-                // Truncate URL at .onion for display
-                let short_url = url
-                    .find(".onion")
-                    .map(|i| &url[..i + 6])
-                    .unwrap_or(url);
-                format!("{}. {} - {}", idx, short_url, title)
-            })
-            .collect::<Vec<_>>()
-            .join("\n");
-
-        let system = FILTER_SYSTEM_PROMPT.replace("{query}", query);
-
-        let response = self
-            .backend
-            .generate(&system, &results_str)
-            .await
-            .map_err(|e| AgentError::Llm(e.to_string()))?;
-
-        // Parse indices from response
-        let indices: Vec<usize> = response
-            .split(|c: char| !c.is_ascii_digit())
-            .filter_map(|s| s.parse().ok())
-            .filter(|&idx| idx > 0 && idx <= results.len())
-            .take(20)
-            .collect();
-
-        Ok(indices)
-    }
-}
-
-#[async_trait]
-impl OsintAgent for FilterAgent {
-    fn id(&self) -> &str {
-        &self.config.id
+    /// Translate titles in other languages to English before ranking
+    pub fn with_translator(mut self, translator: SharedTranslator) -> Self {
+        self.translator = Some(translator);
+        self
     }
 
-    fn agent_type(&self) -> &str {
-        "filter"
+    /// `title` in English (itself when English, unknown, or untranslatable)
+    async fn english_title(&mut self, title: String) -> String {
+        let Some(translator) = &self.translator else {
+            return title;
+        };
+        if let Some(english) = self.translated_titles.get(&title) {
+            return english.clone();
+        }
+        let english = to_english(translator.as_ref(), &title, detect_language(&title))
+            .await
+            .unwrap_or_else(|| title.clone());
+        self.translated_titles.insert(title, english.clone());
+        english
     }
 
-    fn sense<'a>(&self, field: &'a Field) -> Vec<&'a Signal> {
-        // First, check for refined queries to get the current query
-        let _refined_signals = field.sense_where(|signal| {
-            signal.effective_intensity(field.now()) >= self.config.sensing_threshold
-                && matches!(&signal.payload, OsintPayload::RefinedQuery { .. })
-        });
-
-        // Then sense raw results
-        field.sense_where(|signal| {
-            signal.effective_intensity(field.now()) >= self.config.sensing_threshold
-                && matches!(&signal.payload, OsintPayload::RawResult { .. })
-        })
+    /// Results per LLM call (the best pre-scored ones)
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
     }
 
-    async fn process(&mut self, field: &mut Field) -> Result<Vec<String>, AgentError> {
+    /// Rank the raw results of one investigation against its refined query
+    async fn filter_investigation(
+        &mut self,
+        field: &mut Field,
+        investigation: Option<&str>,
+    ) -> Result<Vec<String>, AgentError> {
         // Get current refined query
         let refined_signals: Vec<_> = field
-            .sense_where(|s| matches!(&s.payload, OsintPayload::RefinedQuery { .. }))
+            .sense_in(investigation, &[PayloadKind::RefinedQuery], 0.0)
             .into_iter()
             .cloned()
             .collect();
@@ -127,7 +183,7 @@ impl OsintAgent for FilterAgent {
 
         // Collect raw results
         let raw_signals: Vec<_> = field
-            .sense_where(|s| matches!(&s.payload, OsintPayload::RawResult { .. }))
+            .sense_in(investigation, &[PayloadKind::RawResult], 0.0)
             .into_iter()
             .cloned()
             .collect();
@@ -138,18 +194,23 @@ impl OsintAgent for FilterAgent {
 
         info!("Filter processing {} raw results for query: {}", raw_signals.len(), query);
 
-        // Build indexed list
-        let indexed: Vec<(usize, String, String)> = raw_signals
-            .iter()
-            .enumerate()
-            .filter_map(|(idx, signal)| {
-                if let OsintPayload::RawResult { url, title, .. } = &signal.payload {
-                    Some((idx + 1, url.clone(), title.clone()))
-                } else {
-                    None
-                }
-            })
+        // Drop junk and keep the best-scoring batch, indexed for the LLM
+        let mut results: Vec<(String, String)> = Vec::new();
+        for signal in &raw_signals {
+            if let OsintPayload::RawResult { url, title, .. } = &signal.payload {
+                results.push((url.clone(), self.english_title(title.clone()).await));
+            }
+        }
+        let scored = prescore(&query, results);
+        debug!("Pre-scoring kept {} of {} raw results", scored.len(), raw_signals.len());
+        if scored.is_empty() {
+            return Err(AgentError::NoWork);
+        }
+        let indexed: Vec<(usize, String, String)> = scored
+            .into_iter()
             .take(self.batch_size)
+            .enumerate()
+            .map(|(idx, (url, title, _))| (idx + 1, url, title))
             .collect();
 
         // Get filtered indices from LLM
@@ -170,6 +231,9 @@ impl OsintAgent for FilterAgent {
                     reason: format!("Ranked #{} by relevance filter", rank + 1),
                 })
                 .origin(&self.config.id)
+                .parents(raw_signals.iter().filter(|s| {
+                    matches!(&s.payload, OsintPayload::RawResult { url: raw_url, .. } if raw_url == url)
+                }).map(|s| &s.origin_hash))
                 .confidence(0.85)
                 .ttl(120.0)
                 .build();
@@ -183,6 +247,85 @@ impl OsintAgent for FilterAgent {
         Ok(emitted)
     }
 
+    async fn filter_results(
+        &self,
+        query: &str,
+        results: &[(usize, String, String)], // (index, url, title)
+    ) -> Result<Vec<usize>, AgentError> {
+        // Format results for LLM
+        let results_str: String = results
+            .iter()
+            .map(|(idx, url, title)| {
+                // Truncate URL at .onion for display
+                let short_url = url
+                    .find(".onion")
+                    .map(|i| &url[..i + 6])
+                    .unwrap_or(url);
+                format!("{}. {} - {}", idx, short_url, title)
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        let system = FILTER_SYSTEM_PROMPT.replace("{query}", query);
+
+        let response = self
+            .backend
+            .generate(&system, &results_str)
+            .await
+            .map_err(|e| AgentError::Llm(e.to_string()))?;
+
+        // Parse indices from response
+        let indices: Vec<usize> = response
+            .split(|c: char| !c.is_ascii_digit())
+            .filter_map(|s| s.parse().ok())
+            .filter(|&idx| idx > 0 && idx <= results.len())
+            .take(20)
+            .collect();
+
+        Ok(indices)
+    }
+}
+
+#[async_trait]
+impl OsintAgent for FilterAgent {
+    fn id(&self) -> &str {
+        &self.config.id
+    }
+
+    fn agent_type(&self) -> &str {
+        "filter"
+    }
+
+    fn senses(&self) -> &[PayloadKind] {
+        &[PayloadKind::RawResult]
+    }
+
+    fn emits(&self) -> &[PayloadKind] {
+        &[PayloadKind::FilteredResult]
+    }
+
+    fn sense<'a>(&self, field: &'a Field) -> Vec<&'a Signal> {
+        field.sense_kinds(&[PayloadKind::RawResult], self.config.sensing_threshold)
+    }
+
+    async fn process(&mut self, field: &mut Field) -> Result<Vec<String>, AgentError> {
+        // Each investigation's results are ranked against its own query
+        let investigations: Vec<Option<String>> = field
+            .investigations(&[PayloadKind::RefinedQuery])
+            .into_iter()
+            .map(|id| id.map(str::to_string))
+            .collect();
+        let mut emitted = None;
+        for investigation in investigations {
+            match self.filter_investigation(field, investigation.as_deref()).await {
+                Ok(hashes) => emitted.get_or_insert_with(Vec::new).extend(hashes),
+                Err(AgentError::NoWork) => {}
+                Err(e) => return Err(e),
+            }
+        }
+        emitted.ok_or(AgentError::NoWork)
+    }
+
     fn heartbeat(&self, field: &mut Field) {
         let signal = Signal::builder(OsintPayload::Heartbeat {
             agent_id: self.config.id.clone(),
@@ -196,3 +339,96 @@ impl OsintAgent for FilterAgent {
         field.emit(signal);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::LlmBackend;
+    use robin_core::Network;
+    use std::sync::Arc;
+
+    /// Selects every result it's shown
+    struct SelectAll;
+
+    #[async_trait]
+    impl LlmBackend for SelectAll {
+        async fn generate(&self, _system: &str, user: &str) -> Result<String, crate::LlmError> {
+            Ok((1..=user.lines().count()).map(|i| i.to_string()).collect::<Vec<_>>().join(","))
+        }
+
+        fn model_name(&self) -> &str {
+            "mock"
+        }
+    }
+
+    #[tokio::test]
+    async fn test_investigations_filtered_apart() {
+        let mut agent = FilterAgent::new(AgentConfig::default().with_id("filter-1"), Arc::new(SelectAll));
+        let mut field = Field::new();
+        for (investigation, query, titles) in [
+            ("inv-a", "lockbit leak", ["LockBit leak site", "Lockbit leak mirror"]),
+            ("inv-b", "carding forum", ["Carding forum index", "Best carding forum"]),
+        ] {
+            let refined = field.emit(
+                Signal::builder(OsintPayload::RefinedQuery {
+                    original: query.to_string(),
+                    refined: query.to_string(),
+                    confidence: 1.0,
+                    raw: None,
+                    variants: Vec::new(),
+                })
+                .investigation(investigation)
+                .build(),
+            );
+            for (i, title) in titles.iter().enumerate() {
+                field.emit(
+                    Signal::builder(OsintPayload::RawResult {
+                        url: format!("http://{}{}.onion/", investigation, i),
+                        title: title.to_string(),
+                        engine: "Ahmia".to_string(),
+                        network: Network::Tor,
+                        query: None,
+                    })
+                    .parent(&refined)
+                    .build(),
+                );
+            }
+        }
+
+        assert_eq!(agent.process(&mut field).await.unwrap().len(), 4);
+        for investigation in ["inv-a", "inv-b"] {
+            let filtered = field.sense_in(Some(investigation), &[PayloadKind::FilteredResult], 0.0);
+            assert_eq!(filtered.len(), 2);
+            assert!(filtered.iter().all(|signal| matches!(&signal.payload,
+                OsintPayload::FilteredResult { url, .. } if url.starts_with(&format!("http://{}", investigation)))));
+        }
+    }
+
+    #[test]
+    fn test_keyword_score() {
+        let query = "lockbit ransomware leak";
+        let on_topic = keyword_score(query, "http://a.onion/", "LockBit 3.0 leak site mirror").unwrap();
+        let partial = keyword_score(query, "http://b.onion/ransomware", "Victim blog").unwrap();
+        let off_topic = keyword_score(query, "http://c.onion/", "Cheap gift cards").unwrap();
+        assert!(on_topic > partial && partial > off_topic, "{} {} {}", on_topic, partial, off_topic);
+
+        // Without a real title, only the URL is scored
+        assert_eq!(keyword_score(query, "http://d.onion/", ""), Some(0.0));
+        assert_eq!(keyword_score(query, "http://d.onion/", "http://d.onion/"), Some(0.0));
+        let url_only = keyword_score(query, "http://d.onion/lockbit/leak", "http://d.onion/lockbit/leak").unwrap();
+        assert!(url_only > off_topic && url_only < on_topic, "{}", url_only);
+        assert_eq!(keyword_score(query, "http://d.onion/", "404 Not Found - lockbit"), None);
+    }
+
+    #[test]
+    fn test_prescore_ranks_and_drops_junk() {
+        let results = vec![
+            ("http://a.onion/".to_string(), "Forum index".to_string()),
+            ("http://b.onion/".to_string(), "Index of /files".to_string()),
+            ("http://c.onion/".to_string(), "Conti leaks archive".to_string()),
+        ];
+        let scored = prescore("conti leaks", results);
+        let urls: Vec<&str> = scored.iter().map(|(url, _, _)| url.as_str()).collect();
+        assert_eq!(urls, vec!["http://c.onion/", "http://a.onion/"]);
+    }
+}
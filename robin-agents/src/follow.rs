@@ -0,0 +1,133 @@
+//! Link following
+//!
+//! Scrapers with a [`LinkFrontier`] turn hidden service links found on
+//! scraped pages into new `FilteredResult` signals, so an investigation can
+//! reach past the pages search engines returned. Search results are depth 0,
+//! pages linked from them depth 1, and so on; following stops at the depth
+//! limit or once the run's page budget is spent. The frontier is shared by
+//! every scraper, so limits and deduplication hold across agents.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+/// Frontier shared by the scrapers of one run
+pub type SharedFrontier = Arc<Mutex<LinkFrontier>>;
+
+/// Limits on link following
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FollowLimits {
+    /// Hops away from a search result (1 = only links on result pages)
+    pub max_depth: u32,
+    /// Followed pages per run, across all scrapers
+    pub max_pages: usize,
+    /// Links followed from any one page
+    pub max_links_per_page: usize,
+}
+
+impl Default for FollowLimits {
+    fn default() -> Self {
+        Self {
+            max_depth: 1,
+            max_pages: 30,
+            max_links_per_page: 10,
+        }
+    }
+}
+
+/// Depth of every page seen so far and the budget left
+#[derive(Debug, Clone)]
+pub struct LinkFrontier {
+    limits: FollowLimits,
+    depths: HashMap<String, u32>,
+    followed: usize,
+}
+
+impl LinkFrontier {
+    pub fn new(limits: FollowLimits) -> Self {
+        Self {
+            limits,
+            depths: HashMap::new(),
+            followed: 0,
+        }
+    }
+
+    /// Wrap for sharing between scrapers
+    pub fn shared(self) -> SharedFrontier {
+        Arc::new(Mutex::new(self))
+    }
+
+    pub fn limits(&self) -> FollowLimits {
+        self.limits
+    }
+
+    /// Depth of `url`; pages the frontier didn't hand out are search results (0)
+    pub fn depth(&self, url: &str) -> u32 {
+        self.depths.get(url).copied().unwrap_or(0)
+    }
+
+    /// Record `url` as scraped, so links back to it aren't followed
+    pub fn visit(&mut self, url: &str) {
+        self.depths.entry(url.to_string()).or_insert(0);
+    }
+
+    /// Links found on `from` that should be followed, with their depth
+    ///
+    /// Admitted links count against the budget and are never admitted again.
+    pub fn admit(&mut self, from: &str, links: &[String]) -> Vec<(String, u32)> {
+        let depth = self.depth(from) + 1;
+        if depth > self.limits.max_depth {
+            return Vec::new();
+        }
+
+        let mut admitted = Vec::new();
+        for link in links {
+            if admitted.len() == self.limits.max_links_per_page || self.followed == self.limits.max_pages {
+                break;
+            }
+            if link == from || self.depths.contains_key(link) {
+                continue;
+            }
+            self.depths.insert(link.clone(), depth);
+            self.followed += 1;
+            admitted.push((link.clone(), depth));
+        }
+        admitted
+    }
+
+    /// Pages admitted so far
+    pub fn followed(&self) -> usize {
+        self.followed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn links(urls: &[&str]) -> Vec<String> {
+        urls.iter().map(|u| u.to_string()).collect()
+    }
+
+    #[test]
+    fn test_frontier_depth_and_budget() {
+        let mut frontier = LinkFrontier::new(FollowLimits {
+            max_depth: 2,
+            max_pages: 3,
+            max_links_per_page: 2,
+        });
+        frontier.visit("http://a.onion/");
+
+        // Per-page cap, and the page itself is never re-admitted
+        let first = frontier.admit("http://a.onion/", &links(&["http://a.onion/", "http://b.onion/", "http://c.onion/", "http://d.onion/"]));
+        assert_eq!(first, vec![("http://b.onion/".to_string(), 1), ("http://c.onion/".to_string(), 1)]);
+
+        // Depth 2 is allowed, depth 3 is not
+        let second = frontier.admit("http://b.onion/", &links(&["http://a.onion/", "http://e.onion/", "http://f.onion/"]));
+        assert_eq!(second, vec![("http://e.onion/".to_string(), 2)]);
+        assert!(frontier.admit("http://e.onion/", &links(&["http://g.onion/"])).is_empty());
+
+        // Budget of 3 is spent
+        assert!(frontier.admit("http://a.onion/", &links(&["http://h.onion/"])).is_empty());
+        assert_eq!(frontier.followed(), 3);
+    }
+}
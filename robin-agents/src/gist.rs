@@ -0,0 +1,599 @@
+//! Gist Watcher Agent
+//!
+//! Credentials pushed to public gists are usually revoked within hours, so
+//! waiting for a paste search to index them is too slow. The gist watcher
+//! polls GitHub's public gists feed (and optionally the public push events,
+//! for secrets committed to repositories), fetches the text of new gists,
+//! and emits those mentioning the investigation's refined query or a
+//! watchlist selector (an email domain, key prefix, brand) as pastes,
+//! minutes after they appear.
+//! - Senses: RefinedQuery signals
+//! - Emits: PasteContent signals (site `gist` or `github`)
+//!
+//! Matches are scored like other pastes ([`crate::score_paste`]) and only
+//! those [`PasteScoring`] accepts are emitted. Set `GITHUB_TOKEN` for the
+//! 5,000 requests an hour of an authenticated client instead of 60.
+
+use async_trait::async_trait;
+use reqwest::Client;
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::time::{Duration, Instant};
+use tracing::{debug, info, warn};
+
+use robin_core::{AgentType, Field, OsintPayload, PayloadKind, Signal};
+use robin_tor::{api_client, read_body};
+
+use crate::paste::{score_paste, PasteScoring};
+use crate::traits::{AgentConfig, AgentError, OsintAgent};
+
+/// Recently fetched gists kept for matching queries that arrive later
+const MAX_RECENT: usize = 500;
+
+/// Gist IDs and commit SHAs remembered as fetched (the feeds list the last 100)
+const MAX_FETCHED: usize = 5000;
+
+/// Configuration for the gist watcher agent
+#[derive(Debug, Clone)]
+pub struct GistWatcherConfig {
+    /// How often the feeds are polled
+    pub poll_interval: Duration,
+    /// Request timeout
+    pub request_timeout: Duration,
+    /// GitHub token (raises the API rate limit)
+    pub github_token: Option<String>,
+    /// Also scan the patches of public push events
+    pub push_events: bool,
+    /// Most gists or commits fetched per poll
+    pub max_fetches: usize,
+    /// Files and patches larger than this (bytes) are skipped, unread when
+    /// the server declares their length
+    pub max_file_size: u64,
+    /// Text matched in every gist regardless of the query (case-insensitive)
+    pub watchlist: Vec<String>,
+    /// Which matches are emitted
+    pub scoring: PasteScoring,
+    /// Send requests through this proxy URL (None = direct connection)
+    pub proxy: Option<String>,
+}
+
+impl Default for GistWatcherConfig {
+    fn default() -> Self {
+        Self {
+            poll_interval: Duration::from_secs(60),
+            request_timeout: Duration::from_secs(30),
+            github_token: std::env::var("GITHUB_TOKEN").ok(),
+            push_events: false,
+            max_fetches: 30,
+            max_file_size: 512 * 1024,
+            watchlist: Vec::new(),
+            scoring: PasteScoring::default(),
+            proxy: None,
+        }
+    }
+}
+
+/// A public gist or pushed commit with its text
+#[derive(Debug, Clone, PartialEq)]
+pub struct GistItem {
+    /// Page of the gist or commit
+    pub url: String,
+    /// `gist` or `github`
+    pub site: String,
+    pub description: Option<String>,
+    pub author: Option<String>,
+    pub created_at: Option<String>,
+    /// Text of its files (or the commit's patch)
+    pub content: String,
+}
+
+/// Public gists feed entry
+#[derive(Debug, Clone, Deserialize)]
+pub struct GistEntry {
+    pub id: String,
+    pub html_url: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub created_at: Option<String>,
+    #[serde(default)]
+    pub owner: Option<GitHubUser>,
+    #[serde(default)]
+    pub files: BTreeMap<String, GistFile>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct GitHubUser {
+    pub login: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct GistFile {
+    #[serde(default)]
+    pub raw_url: Option<String>,
+    #[serde(default)]
+    pub size: u64,
+}
+
+/// Public events API entry (only push events are read)
+#[derive(Debug, Deserialize)]
+struct GitHubEvent {
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(default)]
+    actor: Option<GitHubUser>,
+    #[serde(default)]
+    repo: Option<EventRepo>,
+    #[serde(default)]
+    created_at: Option<String>,
+    #[serde(default)]
+    payload: EventPayload,
+}
+
+#[derive(Debug, Deserialize)]
+struct EventRepo {
+    name: String,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct EventPayload {
+    #[serde(default)]
+    head: Option<String>,
+}
+
+/// Lowercased terms a gist must all contain to match `query` (3+ characters)
+pub fn query_terms(query: &str) -> Vec<String> {
+    query
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|t| t.chars().count() >= 3)
+        .map(str::to_lowercase)
+        .collect()
+}
+
+/// Whether `text` holds every term (and at least one)
+pub fn matches_terms(text: &str, terms: &[String]) -> bool {
+    if terms.is_empty() {
+        return false;
+    }
+    let text = text.to_lowercase();
+    terms.iter().all(|term| text.contains(term.as_str()))
+}
+
+/// Gist watcher agent - surfaces fresh public gists and commits mentioning the query
+pub struct GistWatcherAgent {
+    config: AgentConfig,
+    gist_config: GistWatcherConfig,
+    client: Client,
+    /// Recently fetched gists and commits, newest last
+    items: VecDeque<GistItem>,
+    /// Gist IDs and commit SHAs already fetched
+    fetched: HashSet<String>,
+    /// The same, oldest first, to forget beyond [`MAX_FETCHED`]
+    fetched_order: VecDeque<String>,
+    last_poll: Option<Instant>,
+    queries: Vec<String>,
+    /// Hash of the RefinedQuery signal each query came from
+    query_hashes: HashMap<String, String>,
+    /// URLs of recent items already emitted
+    emitted: HashSet<String>,
+}
+
+impl GistWatcherAgent {
+    pub fn new(config: AgentConfig, gist_config: GistWatcherConfig) -> Self {
+        let client = api_client(gist_config.proxy.as_deref(), gist_config.request_timeout, Some("robin-smesh"))
+            .expect("Invalid gist watcher proxy");
+
+        Self {
+            config,
+            gist_config,
+            client,
+            items: VecDeque::new(),
+            fetched: HashSet::new(),
+            fetched_order: VecDeque::new(),
+            last_poll: None,
+            queries: Vec::new(),
+            query_hashes: HashMap::new(),
+            emitted: HashSet::new(),
+        }
+    }
+
+    /// GET a GitHub API or raw URL; None (logged) on failure
+    async fn get(&self, url: &str) -> Option<String> {
+        self.send(url).await?.text().await.ok()
+    }
+
+    /// GET a file, skipping it (unread, if it declares its length) past `max_file_size`
+    async fn get_file(&self, url: &str) -> Option<String> {
+        let response = self.send(url).await?;
+        let limit = usize::try_from(self.gist_config.max_file_size).unwrap_or(usize::MAX);
+        match read_body(response, url, limit).await {
+            (body, Ok(())) => Some(String::from_utf8_lossy(&body).into_owned()),
+            (_, Err(e)) => {
+                debug!("Skipping {}: {}", url, e);
+                None
+            }
+        }
+    }
+
+    async fn send(&self, url: &str) -> Option<reqwest::Response> {
+        let mut request = self.client.get(url).header("Accept", "application/vnd.github+json");
+        if let Some(token) = &self.gist_config.github_token {
+            request = request.bearer_auth(token);
+        }
+        match request.send().await {
+            Ok(response) if response.status().is_success() => Some(response),
+            Ok(response) => {
+                warn!("GitHub request {} failed: {}", url, response.status());
+                None
+            }
+            Err(e) => {
+                warn!("GitHub request {} failed: {}", url, e);
+                None
+            }
+        }
+    }
+
+    /// New public gists with their text
+    async fn poll_gists(&mut self) -> Vec<GistItem> {
+        let Some(body) = self.get("https://api.github.com/gists/public?per_page=100").await else {
+            return Vec::new();
+        };
+        let entries: Vec<GistEntry> = serde_json::from_str(&body).unwrap_or_else(|e| {
+            debug!("Unreadable gists feed: {}", e);
+            Vec::new()
+        });
+        let mut items = Vec::new();
+        for entry in entries {
+            if items.len() >= self.gist_config.max_fetches || !self.mark_fetched(&entry.id) {
+                continue;
+            }
+            let mut content = String::new();
+            for (name, file) in &entry.files {
+                let Some(raw_url) = file.raw_url.as_deref().filter(|_| file.size <= self.gist_config.max_file_size)
+                else {
+                    continue;
+                };
+                if let Some(text) = self.get_file(raw_url).await {
+                    content.push_str(&format!("==> {} <==\n{}\n", name, text));
+                }
+            }
+            if content.is_empty() {
+                continue;
+            }
+            items.push(GistItem {
+                url: entry.html_url,
+                site: "gist".to_string(),
+                description: entry.description.filter(|d| !d.is_empty()),
+                author: entry.owner.map(|o| o.login),
+                created_at: entry.created_at,
+                content,
+            });
+        }
+        items
+    }
+
+    /// Patches of new public push events
+    async fn poll_push_events(&mut self) -> Vec<GistItem> {
+        let Some(body) = self.get("https://api.github.com/events?per_page=100").await else {
+            return Vec::new();
+        };
+        let events: Vec<GitHubEvent> = serde_json::from_str(&body).unwrap_or_default();
+        let mut items = Vec::new();
+        for event in events.into_iter().filter(|e| e.kind == "PushEvent") {
+            let (Some(repo), Some(sha)) = (event.repo, event.payload.head) else {
+                continue;
+            };
+            if items.len() >= self.gist_config.max_fetches || !self.mark_fetched(&sha) {
+                continue;
+            }
+            let url = format!("https://github.com/{}/commit/{}", repo.name, sha);
+            let Some(patch) = self.get_file(&format!("{}.patch", url)).await else {
+                continue;
+            };
+            items.push(GistItem {
+                url,
+                site: "github".to_string(),
+                description: Some(repo.name),
+                author: event.actor.map(|a| a.login),
+                created_at: event.created_at,
+                content: patch,
+            });
+        }
+        items
+    }
+
+    async fn poll(&mut self) {
+        let mut items = self.poll_gists().await;
+        if self.gist_config.push_events {
+            items.extend(self.poll_push_events().await);
+        }
+        debug!("Gist watcher fetched {} new gists and commits", items.len());
+        self.keep(items);
+        self.last_poll = Some(Instant::now());
+    }
+
+    /// Add fetched items to the recent ones, dropping the oldest past [`MAX_RECENT`]
+    fn keep(&mut self, items: Vec<GistItem>) {
+        for item in items {
+            if self.items.len() == MAX_RECENT {
+                self.items.pop_front();
+            }
+            self.items.push_back(item);
+        }
+        // Items no longer kept can't match again
+        let recent: HashSet<&str> = self.items.iter().map(|item| item.url.as_str()).collect();
+        self.emitted.retain(|url| recent.contains(url.as_str()));
+    }
+
+    /// Remember `id` as fetched; false if it already was
+    fn mark_fetched(&mut self, id: &str) -> bool {
+        if !self.fetched.insert(id.to_string()) {
+            return false;
+        }
+        self.fetched_order.push_back(id.to_string());
+        if self.fetched_order.len() > MAX_FETCHED {
+            if let Some(oldest) = self.fetched_order.pop_front() {
+                self.fetched.remove(&oldest);
+            }
+        }
+        true
+    }
+
+    fn poll_due(&self) -> bool {
+        self.last_poll.is_none_or(|at| at.elapsed() >= self.gist_config.poll_interval)
+    }
+
+    /// Whether there is anything to match gists against
+    fn watching(&self) -> bool {
+        !self.queries.is_empty() || !self.gist_config.watchlist.is_empty()
+    }
+
+    /// Query (or None for the watchlist) each recent item matches, first match only
+    fn matches(&self) -> Vec<(&GistItem, Option<&str>)> {
+        let watchlist: Vec<String> = self.gist_config.watchlist.iter().map(|s| s.to_lowercase()).collect();
+        let queries: Vec<(&str, Vec<String>)> = self.queries.iter().map(|q| (q.as_str(), query_terms(q))).collect();
+        self.items
+            .iter()
+            .filter(|item| !self.emitted.contains(&item.url))
+            .filter_map(|item| {
+                let text = format!("{}\n{}", item.description.as_deref().unwrap_or(""), item.content);
+                if let Some((query, _)) = queries.iter().find(|(_, terms)| matches_terms(&text, terms)) {
+                    return Some((item, Some(*query)));
+                }
+                let lower = text.to_lowercase();
+                watchlist.iter().any(|s| lower.contains(s.as_str())).then_some((item, None))
+            })
+            .collect()
+    }
+}
+
+#[async_trait]
+impl OsintAgent for GistWatcherAgent {
+    fn id(&self) -> &str {
+        &self.config.id
+    }
+
+    fn agent_type(&self) -> &str {
+        "gist_watcher"
+    }
+
+    fn senses(&self) -> &[PayloadKind] {
+        &[PayloadKind::RefinedQuery]
+    }
+
+    fn emits(&self) -> &[PayloadKind] {
+        &[PayloadKind::PasteContent]
+    }
+
+    fn wakes_itself(&self) -> bool {
+        // Polls GitHub on its own interval
+        true
+    }
+
+    fn sense<'a>(&self, field: &'a Field) -> Vec<&'a Signal> {
+        field
+            .sense_kinds(&[PayloadKind::RefinedQuery], self.config.sensing_threshold)
+            .into_iter()
+            .filter(|signal| matches!(&signal.payload, OsintPayload::RefinedQuery { refined, .. }
+                if !self.queries.contains(refined)))
+            .collect()
+    }
+
+    async fn process(&mut self, field: &mut Field) -> Result<Vec<String>, AgentError> {
+        let new_queries: Vec<(String, String)> = self
+            .sense(field)
+            .iter()
+            .filter_map(|s| match &s.payload {
+                OsintPayload::RefinedQuery { refined, .. } => Some((refined.clone(), s.origin_hash.clone())),
+                _ => None,
+            })
+            .collect();
+        for (query, hash) in new_queries.iter().cloned() {
+            self.query_hashes.insert(query.clone(), hash);
+            self.queries.push(query);
+        }
+        if !self.watching() {
+            return Err(AgentError::NoWork);
+        }
+        let polled = self.poll_due();
+        if polled {
+            self.poll().await;
+        }
+        if new_queries.is_empty() && !polled {
+            return Err(AgentError::NoWork);
+        }
+
+        let mut signals = Vec::new();
+        for (item, query) in self.matches() {
+            let score = score_paste(&item.content);
+            if !self.gist_config.scoring.accepts(&score) {
+                debug!("Dropping gist {} (score {:.2})", item.url, score.score);
+                continue;
+            }
+            let signal = Signal::builder(OsintPayload::PasteContent {
+                url: item.url.clone(),
+                site: item.site.clone(),
+                title: item.description.clone(),
+                content: item.content.clone(),
+                created_at: item.created_at.clone(),
+                author: item.author.clone(),
+                language: score.language.map(str::to_string),
+                score: score.score,
+            })
+            .origin(&self.config.id)
+            .parents(query.and_then(|q| self.query_hashes.get(q)))
+            .confidence(0.7)
+            .ttl(300.0)
+            .build();
+            signals.push((item.url.clone(), signal));
+        }
+
+        let mut emitted = Vec::new();
+        for (url, signal) in signals {
+            self.emitted.insert(url);
+            emitted.push(field.emit(signal));
+        }
+        if emitted.is_empty() {
+            return Err(AgentError::NoWork);
+        }
+        info!("Gist watcher emitted {} gists and commits", emitted.len());
+        Ok(emitted)
+    }
+
+    fn heartbeat(&self, field: &mut Field) {
+        let signal = Signal::builder(OsintPayload::Heartbeat {
+            agent_id: self.config.id.clone(),
+            agent_type: AgentType::GistWatcher,
+            capacity: 1.0,
+        })
+        .origin(&self.config.id)
+        .ttl(10.0)
+        .build();
+
+        field.emit(signal);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GISTS: &str = r#"[{"id": "aa11", "html_url": "https://gist.github.com/aa11", "description": "",
+        "created_at": "2026-10-15T09:00:00Z", "owner": {"login": "dev42"},
+        "files": {"config.env": {"filename": "config.env", "raw_url": "https://gist.githubusercontent.com/x/raw", "size": 120}}}]"#;
+
+    fn item(url: &str, content: &str) -> GistItem {
+        GistItem {
+            url: url.to_string(),
+            site: "gist".to_string(),
+            description: None,
+            author: Some("dev42".to_string()),
+            created_at: None,
+            content: content.to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_gist_watcher_matches_queries_and_watchlist() {
+        let entries: Vec<GistEntry> = serde_json::from_str(GISTS).unwrap();
+        assert_eq!(entries[0].owner.as_ref().unwrap().login, "dev42");
+        assert_eq!(entries[0].files["config.env"].size, 120);
+        assert!(matches_terms("Acme Corp VPN creds", &query_terms("acme vpn")));
+        assert!(!matches_terms("Acme Corp", &query_terms("acme vpn")));
+
+        let mut agent = GistWatcherAgent::new(
+            AgentConfig::default().with_id("gists-1"),
+            GistWatcherConfig {
+                watchlist: vec!["@initech.com".to_string()],
+                github_token: None,
+                ..Default::default()
+            },
+        );
+        agent.last_poll = Some(Instant::now());
+        agent.items.extend([
+            item("https://gist.github.com/1", "acme vpn\nadmin@acme.com:Winter2026!\nops@acme.com:hunter22\n"),
+            item("https://gist.github.com/2", "bob@initech.com:letmein99\ncarol@initech.com:Summer2024!\n"),
+            item("https://gist.github.com/3", "acme vpn setup notes, nothing secret here at all really\n"),
+            item("https://gist.github.com/4", "fn main() { println!(\"hello\"); }\n"),
+        ]);
+
+        let mut field = Field::new();
+        let query = field.emit(
+            Signal::builder(OsintPayload::RefinedQuery {
+                original: "acme".to_string(),
+                refined: "acme vpn".to_string(),
+                confidence: 1.0,
+                raw: None,
+                variants: Vec::new(),
+            })
+            .build(),
+        );
+
+        let emitted = agent.process(&mut field).await.unwrap();
+        let urls: Vec<(&str, bool)> = emitted
+            .iter()
+            .map(|hash| field.get(hash).unwrap())
+            .map(|signal| match &signal.payload {
+                OsintPayload::PasteContent { url, .. } => (url.as_str(), signal.parent_hashes.contains(&query)),
+                _ => unreachable!(),
+            })
+            .collect();
+        // The query match is linked to its query; low-scoring and unrelated gists are dropped
+        assert_eq!(urls, vec![("https://gist.github.com/1", true), ("https://gist.github.com/2", false)]);
+        assert!(matches!(agent.process(&mut field).await, Err(AgentError::NoWork)));
+    }
+
+    #[tokio::test]
+    async fn test_oversized_files_are_not_downloaded() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            for declared in [64, 10 << 20] {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let mut request = [0u8; 1024];
+                let _ = stream.read(&mut request).await.unwrap();
+                let head = format!("HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n", declared);
+                stream.write_all(head.as_bytes()).await.unwrap();
+                // Only the small file's body is ever sent
+                if declared == 64 {
+                    stream.write_all(&[b'a'; 64]).await.unwrap();
+                } else {
+                    tokio::time::sleep(Duration::from_secs(30)).await;
+                }
+            }
+        });
+
+        let agent = GistWatcherAgent::new(
+            AgentConfig::default(),
+            GistWatcherConfig {
+                max_file_size: 1024,
+                github_token: None,
+                ..Default::default()
+            },
+        );
+        let url = format!("http://{}/file", addr);
+        assert_eq!(agent.get_file(&url).await.map(|text| text.len()), Some(64));
+        let skipped = tokio::time::timeout(Duration::from_secs(5), agent.get_file(&url)).await;
+        assert_eq!(skipped.expect("refused on Content-Length alone"), None);
+    }
+
+    #[test]
+    fn test_fetched_and_emitted_stay_bounded() {
+        let mut agent = GistWatcherAgent::new(AgentConfig::default(), GistWatcherConfig::default());
+        for i in 0..MAX_FETCHED + 10 {
+            assert!(agent.mark_fetched(&i.to_string()));
+        }
+        assert!(!agent.mark_fetched(&(MAX_FETCHED + 9).to_string()));
+        assert_eq!(agent.fetched.len(), MAX_FETCHED);
+        // The oldest are forgotten
+        assert!(agent.mark_fetched("0"));
+
+        agent.keep(vec![item("https://gist.github.com/0", "text")]);
+        agent.emitted.insert("https://gist.github.com/0".to_string());
+        agent.keep((1..=MAX_RECENT).map(|i| item(&format!("https://gist.github.com/{}", i), "text")).collect());
+        assert_eq!(agent.items.len(), MAX_RECENT);
+        assert!(agent.emitted.is_empty());
+    }
+}
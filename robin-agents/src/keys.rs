@@ -0,0 +1,219 @@
+//! Provider API key checks
+//!
+//! Validates an API key before a run instead of minutes into it: lists the
+//! models the key can use, then sends a one-token completion to the model a
+//! run would use, which confirms access to it and returns the rate-limit
+//! headers (the closest thing to a usage tier the APIs expose). OpenRouter
+//! reports its key's label, credit limit and tier directly.
+
+use reqwest::header::HeaderMap;
+use serde::Serialize;
+use std::fmt;
+
+const ANTHROPIC_API: &str = "https://api.anthropic.com/v1";
+const OPENAI_API: &str = "https://api.openai.com/v1";
+const OPENROUTER_API: &str = "https://openrouter.ai/api/v1";
+
+/// Rate-limit headers worth reporting, by provider, with a label each
+const ANTHROPIC_LIMITS: &[(&str, &str)] = &[
+    ("anthropic-ratelimit-requests-limit", "requests/min"),
+    ("anthropic-ratelimit-input-tokens-limit", "input tokens/min"),
+    ("anthropic-ratelimit-output-tokens-limit", "output tokens/min"),
+    ("anthropic-ratelimit-tokens-limit", "tokens/min"),
+];
+const OPENAI_LIMITS: &[(&str, &str)] = &[
+    ("x-ratelimit-limit-requests", "requests/min"),
+    ("x-ratelimit-limit-tokens", "tokens/min"),
+];
+
+/// An LLM provider with an API key
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Provider {
+    Anthropic,
+    OpenAI,
+    OpenRouter,
+}
+
+impl fmt::Display for Provider {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Provider::Anthropic => write!(f, "Anthropic"),
+            Provider::OpenAI => write!(f, "OpenAI"),
+            Provider::OpenRouter => write!(f, "OpenRouter"),
+        }
+    }
+}
+
+/// What a provider said about a key
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct KeyReport {
+    /// The key was accepted
+    pub valid: bool,
+    /// Why the key (or the probe model) was rejected
+    pub error: Option<String>,
+    /// Models the key can list
+    pub models: Vec<String>,
+    /// Model the completion probe used, if it succeeded
+    pub probed_model: Option<String>,
+    /// Organization the key belongs to, if the provider says
+    pub organization: Option<String>,
+    /// Rate limits as `(label, value)`
+    pub rate_limits: Vec<(String, String)>,
+    /// Other key details (OpenRouter: label, credit limit, usage, tier)
+    pub details: Vec<(String, String)>,
+}
+
+/// Check `api_key` against `provider`, probing `model` with a one-token completion
+pub async fn check_key(provider: Provider, api_key: &str, model: &str) -> KeyReport {
+    let client = reqwest::Client::new();
+    let mut report = KeyReport::default();
+
+    // Listing models is free and fails on a bad key
+    let models = match provider {
+        Provider::Anthropic => client
+            .get(format!("{}/models?limit=100", ANTHROPIC_API))
+            .header("x-api-key", api_key)
+            .header("anthropic-version", "2023-06-01"),
+        Provider::OpenAI => client.get(format!("{}/models", OPENAI_API)).bearer_auth(api_key),
+        Provider::OpenRouter => client.get(format!("{}/key", OPENROUTER_API)).bearer_auth(api_key),
+    };
+    let (headers, body) = match send(models).await {
+        Ok(response) => response,
+        Err(e) => {
+            report.error = Some(e);
+            return report;
+        }
+    };
+    report.valid = true;
+    report.organization = organization(&headers);
+    match provider {
+        Provider::OpenRouter => report.details = openrouter_details(&body),
+        _ => report.models = model_ids(&body),
+    }
+
+    let probe = match provider {
+        Provider::Anthropic => client
+            .post(format!("{}/messages", ANTHROPIC_API))
+            .header("x-api-key", api_key)
+            .header("anthropic-version", "2023-06-01")
+            .json(&serde_json::json!({
+                "model": model,
+                "max_tokens": 1,
+                "messages": [{"role": "user", "content": "ping"}],
+            })),
+        Provider::OpenAI | Provider::OpenRouter => {
+            let base = if provider == Provider::OpenAI { OPENAI_API } else { OPENROUTER_API };
+            client.post(format!("{}/chat/completions", base)).bearer_auth(api_key).json(&serde_json::json!({
+                "model": model,
+                "max_tokens": 1,
+                "messages": [{"role": "user", "content": "ping"}],
+            }))
+        }
+    };
+    match send(probe).await {
+        Ok((headers, _)) => {
+            report.probed_model = Some(model.to_string());
+            report.organization = report.organization.or_else(|| organization(&headers));
+            let limits = if provider == Provider::Anthropic { ANTHROPIC_LIMITS } else { OPENAI_LIMITS };
+            report.rate_limits = rate_limits(&headers, limits);
+        }
+        Err(e) => report.error = Some(format!("model {}: {}", model, e)),
+    }
+    report
+}
+
+/// Send a request; errors carry the status and the provider's message
+async fn send(request: reqwest::RequestBuilder) -> Result<(HeaderMap, serde_json::Value), String> {
+    let response = request.send().await.map_err(|e| e.to_string())?;
+    let status = response.status();
+    let headers = response.headers().clone();
+    let body: serde_json::Value = response.json().await.unwrap_or_default();
+    if !status.is_success() {
+        let message = body["error"]["message"].as_str().unwrap_or_else(|| status.canonical_reason().unwrap_or(""));
+        return Err(format!("{} {}", status.as_u16(), message));
+    }
+    Ok((headers, body))
+}
+
+/// Model IDs from an Anthropic or OpenAI model list, sorted
+fn model_ids(body: &serde_json::Value) -> Vec<String> {
+    let mut ids: Vec<String> = body["data"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|model| model["id"].as_str().map(str::to_string))
+        .collect();
+    ids.sort();
+    ids
+}
+
+fn organization(headers: &HeaderMap) -> Option<String> {
+    ["anthropic-organization-id", "openai-organization"]
+        .iter()
+        .find_map(|name| headers.get(*name)?.to_str().ok().map(str::to_string))
+}
+
+fn rate_limits(headers: &HeaderMap, limits: &[(&str, &str)]) -> Vec<(String, String)> {
+    limits
+        .iter()
+        .filter_map(|(header, label)| {
+            let value = headers.get(*header)?.to_str().ok()?;
+            Some((label.to_string(), value.to_string()))
+        })
+        .collect()
+}
+
+/// Label, credit limit, usage and tier from OpenRouter's `/key` response
+fn openrouter_details(body: &serde_json::Value) -> Vec<(String, String)> {
+    let data = &body["data"];
+    let mut details = Vec::new();
+    if let Some(label) = data["label"].as_str() {
+        details.push(("label".to_string(), label.to_string()));
+    }
+    let limit = match data["limit"].as_f64() {
+        Some(limit) => format!("${:.2}", limit),
+        None => "unlimited".to_string(),
+    };
+    details.push(("credit limit".to_string(), limit));
+    if let Some(usage) = data["usage"].as_f64() {
+        details.push(("usage".to_string(), format!("${:.2}", usage)));
+    }
+    if let Some(free) = data["is_free_tier"].as_bool() {
+        details.push(("tier".to_string(), if free { "free" } else { "paid" }.to_string()));
+    }
+    details
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parses_models_and_limits() {
+        let body = serde_json::json!({"data": [{"id": "gpt-4o"}, {"id": "gpt-4o-mini"}, {"object": "model"}]});
+        assert_eq!(model_ids(&body), vec!["gpt-4o".to_string(), "gpt-4o-mini".to_string()]);
+
+        let mut headers = HeaderMap::new();
+        headers.insert("anthropic-ratelimit-requests-limit", "50".parse().unwrap());
+        headers.insert("anthropic-ratelimit-input-tokens-limit", "40000".parse().unwrap());
+        headers.insert("anthropic-organization-id", "org-123".parse().unwrap());
+        assert_eq!(
+            rate_limits(&headers, ANTHROPIC_LIMITS),
+            vec![
+                ("requests/min".to_string(), "50".to_string()),
+                ("input tokens/min".to_string(), "40000".to_string()),
+            ]
+        );
+        assert_eq!(organization(&headers).as_deref(), Some("org-123"));
+    }
+
+    #[test]
+    fn test_openrouter_details() {
+        let body = serde_json::json!({"data": {"label": "sk-or-v1-abc...", "limit": null, "usage": 1.5, "is_free_tier": false}});
+        let details = openrouter_details(&body);
+        assert!(details.contains(&("credit limit".to_string(), "unlimited".to_string())));
+        assert!(details.contains(&("usage".to_string(), "$1.50".to_string())));
+        assert!(details.contains(&("tier".to_string(), "paid".to_string())));
+    }
+}
@@ -4,10 +4,17 @@
 //! - **Refiner**: Optimizes search queries for dark web engines
 //! - **Crawler**: Searches .onion search engines
 //! - **Filter**: Ranks results by relevance
-//! - **Scraper**: Extracts content from dark web sites
+//! - **Scraper**: Extracts content from dark web sites, optionally following links
+//!   and translating non-English pages; scrapers divide URLs through a shared work queue
 //! - **Extractor**: Identifies IOCs and artifacts
 //! - **Enricher**: Queries external OSINT sources (GitHub, Brave)
-//! - **Analyst**: Synthesizes intelligence summaries
+//! - **Feed Watcher**: Surfaces matching items from RSS/Atom security feeds
+//! - **Gist Watcher**: Catches fresh public gists and commits mentioning the query or a watchlist
+//! - **Target Monitor**: Tallies findings about monitored brands for trend alerts
+//! - **Change Detector**: Flags pages that changed since earlier runs scraped them
+//! - **Thread Parser**: Splits chat logs and forum threads into per-author messages
+//! - **Profiler**: Aggregates everything seen about each actor or handle into a profile
+//! - **Analyst**: Synthesizes intelligence summaries, drawing on past collections
 //!
 //! ## Modular Personas
 //!
@@ -15,29 +22,59 @@
 //! See [`persona::PersonaRegistry`] for loading and managing personas.
 
 pub mod backend;
+pub mod models;
+pub mod response;
+pub mod keys;
 pub mod persona;
 pub mod specialists;
 pub mod refiner;
 pub mod crawler;
+pub mod directory;
 pub mod filter;
 pub mod scraper;
+pub mod follow;
+pub mod work_queue;
 pub mod extractor;
 pub mod enricher;
 pub mod blockchain;
 pub mod paste;
+pub mod paste_sites;
+pub mod feed;
+pub mod gist;
+pub mod monitor;
+pub mod changes;
+pub mod threads;
+pub mod profiles;
+pub mod embedder;
+pub mod translator;
 pub mod analyst;
 pub mod traits;
 
 pub use backend::*;
+pub use models::*;
+pub use response::*;
+pub use keys::*;
 pub use persona::*;
 pub use specialists::*;
 pub use refiner::*;
 pub use crawler::*;
+pub use directory::*;
 pub use filter::*;
 pub use scraper::*;
+pub use follow::*;
+pub use work_queue::*;
 pub use extractor::*;
 pub use enricher::*;
 pub use blockchain::*;
 pub use paste::*;
+pub use paste_sites::*;
+pub use feed::*;
+pub use gist::*;
+pub use monitor::*;
+pub use changes::*;
+pub use threads::*;
+pub use profiles::*;
+pub use embedder::*;
+pub use translator::*;
 pub use analyst::*;
 pub use traits::*;
@@ -0,0 +1,182 @@
+//! LLM model registry
+//!
+//! Context window, output limit and list price per model, so backends never
+//! request more output tokens than the model allows and the estimator can
+//! price a run. Starts from the bundled `data/models.toml`; files loaded at
+//! startup add models or replace bundled ones of the same name:
+//!
+//! ```toml
+//! [[model]]
+//! name = "llama-3.3-70b"
+//! context_window = 131072
+//! max_output_tokens = 8192
+//! input_price = 0.0
+//! output_price = 0.0
+//! ```
+
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use thiserror::Error;
+
+/// Bundled models
+const EMBEDDED_MODELS: &str = include_str!("../data/models.toml");
+
+/// Output tokens requested per call, unless the model allows fewer
+///
+/// Reports and refinements fit well within it; asking for a model's full
+/// limit (64,000 for some) only makes runaway answers slower and dearer.
+pub const DEFAULT_MAX_OUTPUT_TOKENS: u32 = 4096;
+
+/// Errors loading a model registry
+#[derive(Debug, Error)]
+pub enum ModelRegistryError {
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Invalid model file: {0}")]
+    Toml(#[from] toml::de::Error),
+}
+
+/// Limits and prices of one model
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ModelInfo {
+    /// Model ID, or a fragment of the IDs it covers (e.g. `claude-sonnet-4`)
+    pub name: String,
+    /// Input plus output tokens per request
+    pub context_window: u32,
+    /// Most output tokens per request
+    pub max_output_tokens: u32,
+    /// USD per million input tokens
+    #[serde(default)]
+    pub input_price: f64,
+    /// USD per million output tokens
+    #[serde(default)]
+    pub output_price: f64,
+}
+
+impl ModelInfo {
+    /// List price in USD of a request with these token counts
+    pub fn cost_usd(&self, input_tokens: usize, output_tokens: usize) -> f64 {
+        (input_tokens as f64 * self.input_price + output_tokens as f64 * self.output_price) / 1e6
+    }
+}
+
+/// Models with known limits and prices
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ModelRegistry {
+    #[serde(default, rename = "model")]
+    models: Vec<ModelInfo>,
+}
+
+impl ModelRegistry {
+    /// The bundled models
+    pub fn embedded() -> Self {
+        Self::from_toml(EMBEDDED_MODELS).expect("bundled models.toml is valid")
+    }
+
+    /// Parse `[[model]]` tables
+    pub fn from_toml(toml: &str) -> Result<Self, ModelRegistryError> {
+        Ok(toml::from_str(toml)?)
+    }
+
+    /// Load a model file
+    pub fn load_file(path: impl AsRef<Path>) -> Result<Self, ModelRegistryError> {
+        Self::from_toml(&std::fs::read_to_string(path)?)
+    }
+
+    /// Add `other`'s models, replacing models of the same name (case-insensitive)
+    pub fn merge(mut self, other: ModelRegistry) -> Self {
+        for model in other.models {
+            match self.models.iter_mut().find(|m| m.name.eq_ignore_ascii_case(&model.name)) {
+                Some(existing) => *existing = model,
+                None => self.models.push(model),
+            }
+        }
+        self
+    }
+
+    /// The entry for `model`: an exact match, else the longest name contained in it
+    ///
+    /// Matching ignores case, so `anthropic/claude-sonnet-4.5` and
+    /// `claude-sonnet-4-20250514` both find `claude-sonnet-4`.
+    pub fn lookup(&self, model: &str) -> Option<&ModelInfo> {
+        let model = model.to_ascii_lowercase();
+        self.models
+            .iter()
+            .find(|m| m.name.eq_ignore_ascii_case(&model))
+            .or_else(|| {
+                self.models
+                    .iter()
+                    .filter(|m| model.contains(&m.name.to_ascii_lowercase()))
+                    .max_by_key(|m| m.name.len())
+            })
+    }
+
+    /// Most output tokens `model` allows ([`DEFAULT_MAX_OUTPUT_TOKENS`] for unknown models)
+    pub fn max_output_tokens(&self, model: &str) -> u32 {
+        self.lookup(model).map_or(DEFAULT_MAX_OUTPUT_TOKENS, |m| m.max_output_tokens)
+    }
+
+    /// Output tokens to request from `model`: the default, capped at its limit
+    pub fn request_tokens(&self, model: &str) -> u32 {
+        self.max_output_tokens(model).min(DEFAULT_MAX_OUTPUT_TOKENS)
+    }
+
+    /// Every model
+    pub fn models(&self) -> &[ModelInfo] {
+        &self.models
+    }
+
+    pub fn len(&self) -> usize {
+        self.models.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.models.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lookup_prefers_longest_fragment() {
+        let models = ModelRegistry::embedded();
+        assert_eq!(models.lookup("gpt-4o-mini-2024-07-18").unwrap().name, "gpt-4o-mini");
+        assert_eq!(models.lookup("GPT-4o").unwrap().name, "gpt-4o");
+        assert_eq!(models.lookup("anthropic/claude-sonnet-4.5").unwrap().name, "claude-sonnet-4");
+        assert_eq!(models.max_output_tokens("claude-sonnet-4-20250514"), 64000);
+        assert_eq!(models.max_output_tokens("local-llama"), DEFAULT_MAX_OUTPUT_TOKENS);
+        assert_eq!(models.request_tokens("claude-sonnet-4-20250514"), DEFAULT_MAX_OUTPUT_TOKENS);
+
+        let sonnet = models.lookup("claude-sonnet-4-20250514").unwrap();
+        assert_eq!(sonnet.cost_usd(1_000_000, 100_000), 4.5);
+
+        // Claude 3 models are still priced
+        for (model, name) in [
+            ("claude-3-7-sonnet-20250219", "claude-3-7-sonnet"),
+            ("claude-3-opus-20240229", "claude-3-opus"),
+            ("claude-3-haiku-20240307", "claude-3-haiku"),
+        ] {
+            let info = models.lookup(model).unwrap();
+            assert_eq!(info.name, name);
+            assert!(info.input_price > 0.0 && info.output_price > 0.0);
+        }
+    }
+
+    #[test]
+    fn test_overrides() {
+        let custom = ModelRegistry::from_toml(
+            "[[model]]\nname = \"GPT-4o\"\ncontext_window = 128000\nmax_output_tokens = 4096\n\n\
+             [[model]]\nname = \"llama-3.3-70b\"\ncontext_window = 131072\nmax_output_tokens = 8192\n",
+        )
+        .unwrap();
+        let models = ModelRegistry::embedded().merge(custom);
+        assert_eq!(models.len(), ModelRegistry::embedded().len() + 1);
+        assert_eq!(models.max_output_tokens("gpt-4o"), 4096);
+        assert_eq!(models.request_tokens("llama-3.3-70b"), DEFAULT_MAX_OUTPUT_TOKENS);
+        assert_eq!(models.lookup("meta-llama/llama-3.3-70b-instruct").unwrap().input_price, 0.0);
+        assert!(ModelRegistry::from_toml("[[model]]\nname = \"x\"\n").is_err());
+    }
+}
@@ -0,0 +1,157 @@
+//! Target Monitor Agent
+//!
+//! Tallies what a run finds about monitored targets (brands, products,
+//! executives): pages and pastes that mention each target, and the wallets
+//! extracted from those pages. The caller records the tally in a
+//! [`robin_core::TrendStore`] after the run to spot spikes in chatter.
+//! - Senses: ScrapedContent, PasteContent and ExtractedArtifacts signals
+//! - Emits: nothing
+
+use async_trait::async_trait;
+use std::collections::HashSet;
+use tracing::info;
+
+use robin_core::{AgentType, ArtifactType, Field, OsintPayload, PayloadKind, SharedTally, Signal};
+
+use crate::{AgentConfig, AgentError, OsintAgent};
+
+/// Target monitor agent - counts findings per monitored target
+pub struct TargetMonitorAgent {
+    config: AgentConfig,
+    tally: SharedTally,
+    processed: HashSet<String>,
+}
+
+impl TargetMonitorAgent {
+    pub fn new(config: AgentConfig, tally: SharedTally) -> Self {
+        Self {
+            config,
+            tally,
+            processed: HashSet::new(),
+        }
+    }
+}
+
+#[async_trait]
+impl OsintAgent for TargetMonitorAgent {
+    fn id(&self) -> &str {
+        &self.config.id
+    }
+
+    fn agent_type(&self) -> &str {
+        "target_monitor"
+    }
+
+    fn senses(&self) -> &[PayloadKind] {
+        &[PayloadKind::ScrapedContent, PayloadKind::PasteContent, PayloadKind::ExtractedArtifacts]
+    }
+
+    fn emits(&self) -> &[PayloadKind] {
+        &[]
+    }
+
+    fn sense<'a>(&self, field: &'a Field) -> Vec<&'a Signal> {
+        field
+            .sense_kinds(self.senses(), 0.0)
+            .into_iter()
+            .filter(|signal| !self.processed.contains(&signal.origin_hash))
+            .collect()
+    }
+
+    async fn process(&mut self, field: &mut Field) -> Result<Vec<String>, AgentError> {
+        let mut signals: Vec<Signal> = self.sense(field).into_iter().cloned().collect();
+        if signals.is_empty() {
+            return Err(AgentError::NoWork);
+        }
+        // Pages first, so wallets can be tied to the targets their page mentions
+        signals.sort_by_key(|s| matches!(s.payload, OsintPayload::ExtractedArtifacts { .. }));
+
+        let mut tally = self.tally.lock().unwrap();
+        for signal in &signals {
+            match &signal.payload {
+                OsintPayload::ScrapedContent { url, title, text, .. } => {
+                    let matched = tally.add_page(url, &format!("{}\n{}", title, text));
+                    if !matched.is_empty() {
+                        info!("Monitored target(s) {} mentioned on {}", matched.join(", "), url);
+                    }
+                }
+                OsintPayload::PasteContent { url, title, content, .. } => {
+                    let matched = tally.add_page(url, &format!("{}\n{}", title.as_deref().unwrap_or_default(), content));
+                    if !matched.is_empty() {
+                        info!("Monitored target(s) {} mentioned in paste {}", matched.join(", "), url);
+                    }
+                }
+                OsintPayload::ExtractedArtifacts { artifacts, .. } => {
+                    let wallets = artifacts.iter().filter(|a| {
+                        matches!(a.artifact_type, ArtifactType::Bitcoin | ArtifactType::Ethereum | ArtifactType::Monero)
+                    });
+                    for wallet in wallets {
+                        if let Some(source) = &wallet.source {
+                            tally.add_wallet(source, &wallet.value);
+                        }
+                    }
+                }
+                _ => {}
+            }
+            self.processed.insert(signal.origin_hash.clone());
+        }
+        Ok(Vec::new())
+    }
+
+    fn heartbeat(&self, field: &mut Field) {
+        let signal = Signal::builder(OsintPayload::Heartbeat {
+            agent_id: self.config.id.clone(),
+            agent_type: AgentType::TargetMonitor,
+            capacity: 1.0,
+        })
+        .origin(&self.config.id)
+        .ttl(10.0)
+        .build();
+
+        field.emit(signal);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use robin_core::{Artifact, RunTally};
+    use std::sync::{Arc, Mutex};
+
+    #[tokio::test]
+    async fn test_tallies_pages_and_wallets() {
+        let tally = Arc::new(Mutex::new(RunTally::new(&["Acme Corp".to_string()])));
+        let mut agent = TargetMonitorAgent::new(AgentConfig::default().with_id("monitor-1"), tally.clone());
+
+        let mut field = Field::new();
+        let page = "http://leak.onion/acme";
+        field.emit(
+            Signal::builder(OsintPayload::ScrapedContent {
+                url: page.to_string(),
+                title: "New victim".to_string(),
+                text: "ACME CORP data published".to_string(),
+                char_count: 24,
+                screenshot: None,
+                language: None,
+                original_text: None,
+                chunk: None,
+                metadata: None,
+            })
+            .build(),
+        );
+        field.emit(
+            Signal::builder(OsintPayload::ExtractedArtifacts {
+                source_url: page.to_string(),
+                artifacts: vec![Artifact::new(ArtifactType::Bitcoin, "bc1qacmewallet".to_string()).with_source(page)],
+            })
+            .build(),
+        );
+
+        assert!(agent.process(&mut field).await.unwrap().is_empty());
+        assert!(matches!(agent.process(&mut field).await, Err(AgentError::NoWork)));
+        let tally = tally.lock().unwrap();
+        let acme = tally.get("Acme Corp").unwrap();
+        assert!(acme.pages.contains(page));
+        assert!(acme.wallets.contains("bc1qacmewallet"));
+    }
+}
@@ -0,0 +1,739 @@
+//! Paste site plugins
+//!
+//! Each site the paste monitor covers is a [`PasteSite`]: it finds pastes
+//! for a query (`search`), or lists recent ones when it can't be searched
+//! (`list`), and turns a fetched paste page into its text (`fetch`,
+//! `parse`). The five built-in sites ([`default_paste_sites`]) are plugins
+//! like any other, so a new site is one small type added to
+//! [`crate::PasteMonitorConfig::sites`].
+//!
+//! Sites that only need a search page or a web search dork and a CSS
+//! selector can be added without code, as a [`SiteSpec`] loaded from TOML:
+//!
+//! ```toml
+//! [[sites]]
+//! name = "telegraph"
+//! dork = "telegra.ph"            # find pastes with `site:telegra.ph "<query>"`
+//! content_selector = "article"
+//!
+//! [[sites]]
+//! name = "throwbin"
+//! search_url = "https://throwbin.io/search?q={query}"
+//! result_selector = "a.paste-title"
+//! raw_url = "{url}/raw"
+//! ```
+
+use async_trait::async_trait;
+use reqwest::Client;
+use scraper::{Html, Selector};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::Path;
+use std::sync::Arc;
+use thiserror::Error;
+use tracing::{debug, warn};
+
+use robin_tor::{header_pairs, FetchRequest, FetchResponse, WarcWriter};
+
+use crate::WebSearch;
+
+/// Errors loading paste site specs
+#[derive(Debug, Error)]
+pub enum PasteSiteError {
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Invalid paste sites: {0}")]
+    Parse(#[from] toml::de::Error),
+
+    #[error("Invalid paste site {0}: {1}")]
+    Invalid(String, String),
+}
+
+/// A paste found on a site
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Paste {
+    pub url: String,
+    /// Name of the site it was found on
+    pub site: String,
+    pub title: Option<String>,
+    /// Paste text (empty until fetched)
+    pub content: String,
+    pub created_at: Option<String>,
+    pub author: Option<String>,
+}
+
+impl Paste {
+    pub fn new(site: &str, url: &str) -> Self {
+        Self {
+            url: url.to_string(),
+            site: site.to_string(),
+            ..Default::default()
+        }
+    }
+}
+
+/// What sites use to reach the network: HTTP through the paste egress and web search
+pub struct PasteContext {
+    client: Client,
+    archive: Option<WarcWriter>,
+    web_search: Option<WebSearch>,
+    /// Most pastes taken from each site per query
+    pub max_pastes: usize,
+    /// Shortest paste text kept
+    pub min_length: usize,
+}
+
+impl PasteContext {
+    pub fn new(client: Client, web_search: Option<WebSearch>, max_pastes: usize, min_length: usize) -> Self {
+        Self {
+            client,
+            archive: None,
+            web_search,
+            max_pastes,
+            min_length,
+        }
+    }
+
+    /// Archive every paste site response to this WARC file
+    pub fn with_archive(mut self, archive: Option<WarcWriter>) -> Self {
+        self.archive = archive;
+        self
+    }
+
+    /// HTTP client for requests `get` doesn't cover
+    pub fn client(&self) -> &Client {
+        &self.client
+    }
+
+    /// Whether a web search is configured for dorking
+    pub fn can_dork(&self) -> bool {
+        self.web_search.is_some()
+    }
+
+    /// GET `url`, archiving the response; returns its status and body
+    pub async fn get(&self, url: &str) -> Result<(u16, String), reqwest::Error> {
+        let response = self.client.get(url).send().await?;
+        let status = response.status().as_u16();
+        let headers = header_pairs(response.headers());
+        let body = response.text().await?;
+
+        if let Some(archive) = &self.archive {
+            let fetched = FetchResponse {
+                url: url.to_string(),
+                status,
+                body,
+                cacheability: Default::default(),
+            };
+            if let Err(e) = archive.write_exchange(&FetchRequest::get(url), &fetched, &headers) {
+                warn!("Failed to archive {}: {}", url, e);
+            }
+            return Ok((status, fetched.body));
+        }
+        Ok((status, body))
+    }
+
+    /// Result URLs of a web search for `dork`
+    pub async fn search_web(&self, dork: &str) -> Vec<String> {
+        let Some(search) = &self.web_search else {
+            return Vec::new();
+        };
+        let count = self.max_pastes.clamp(1, 10);
+        let request = match search {
+            WebSearch::Brave { api_key } => self
+                .client
+                .get(format!(
+                    "https://api.search.brave.com/res/v1/web/search?q={}&count={}",
+                    urlencoding::encode(dork),
+                    count
+                ))
+                .header("Accept", "application/json")
+                .header("X-Subscription-Token", api_key),
+            WebSearch::Google { api_key, engine_id } => self.client.get(format!(
+                "https://www.googleapis.com/customsearch/v1?key={}&cx={}&q={}&num={}",
+                urlencoding::encode(api_key),
+                urlencoding::encode(engine_id),
+                urlencoding::encode(dork),
+                count
+            )),
+        };
+
+        let body = match request.send().await {
+            Ok(response) if response.status().is_success() => response.text().await.unwrap_or_default(),
+            Ok(response) => {
+                warn!("{} search failed: {}", search.name(), response.status());
+                return Vec::new();
+            }
+            Err(e) => {
+                warn!("{} search failed: {}", search.name(), e);
+                return Vec::new();
+            }
+        };
+        let urls = match search {
+            WebSearch::Brave { .. } => serde_json::from_str::<BraveSearchResponse>(&body)
+                .map(|r| r.web.results.into_iter().map(|r| r.url).collect()),
+            WebSearch::Google { .. } => serde_json::from_str::<GoogleSearchResponse>(&body)
+                .map(|r| r.items.into_iter().map(|r| r.link).collect()),
+        };
+        urls.unwrap_or_else(|e| {
+            debug!("Unreadable {} search response: {}", search.name(), e);
+            Vec::new()
+        })
+    }
+
+    /// Paste URLs on `domain` that a web search for `query` finds
+    ///
+    /// `reserved` lists first path segments on the domain that aren't pastes.
+    pub async fn dork(&self, domain: &str, query: &str, reserved: &[&str]) -> Vec<String> {
+        let mut urls: Vec<String> = Vec::new();
+        for result in self.search_web(&site_dork(domain, query)).await {
+            if let Some(url) = paste_url(&result, domain, reserved) {
+                if !urls.contains(&url) {
+                    urls.push(url);
+                }
+            }
+        }
+        urls.truncate(self.max_pastes);
+        urls
+    }
+}
+
+/// A paste or leak-sharing site the paste monitor covers
+#[async_trait]
+pub trait PasteSite: Send + Sync {
+    /// Site name, recorded on its pastes
+    fn name(&self) -> &str;
+
+    /// Pastes matching `query`, or None if the site can't be searched
+    /// (then its recent pastes from [`PasteSite::list`] are taken instead)
+    async fn search(&self, ctx: &PasteContext, query: &str) -> Option<Vec<Paste>>;
+
+    /// Recent pastes, for sites that can't be searched
+    async fn list(&self, _ctx: &PasteContext) -> Vec<Paste> {
+        Vec::new()
+    }
+
+    /// URL of the page holding the paste's text
+    fn raw_url(&self, url: &str) -> String {
+        url.to_string()
+    }
+
+    /// Paste text from that page (the whole body by default)
+    fn parse(&self, body: &str) -> Option<String> {
+        Some(body.to_string())
+    }
+
+    /// Fill in the text of `paste` unless search already did; false if
+    /// it is unavailable or shorter than [`PasteContext::min_length`]
+    async fn fetch(&self, ctx: &PasteContext, paste: &mut Paste) -> bool {
+        if !paste.content.is_empty() {
+            return true;
+        }
+        match ctx.get(&self.raw_url(&paste.url)).await {
+            Ok((status, body)) if (200..300).contains(&status) => match self.parse(&body) {
+                Some(content) if content.len() >= ctx.min_length => {
+                    paste.content = content;
+                    true
+                }
+                _ => false,
+            },
+            Ok((status, _)) => {
+                debug!("Paste {} returned {}", paste.url, status);
+                false
+            }
+            Err(e) => {
+                warn!("Failed to fetch paste content from {}: {}", paste.url, e);
+                false
+            }
+        }
+    }
+}
+
+impl fmt::Debug for dyn PasteSite {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "PasteSite({})", self.name())
+    }
+}
+
+/// Thread-safe reference to a paste site
+pub type SharedPasteSite = Arc<dyn PasteSite>;
+
+/// Pastebin, Rentry, dpaste, ControlC and JustPaste.it
+pub fn default_paste_sites() -> Vec<SharedPasteSite> {
+    vec![
+        Arc::new(Pastebin),
+        Arc::new(Rentry),
+        Arc::new(Dpaste),
+        Arc::new(ControlC),
+        Arc::new(JustPaste),
+    ]
+}
+
+/// Pastebin, searched through the psbdmp.ws dump API (which returns the text too)
+pub struct Pastebin;
+
+#[async_trait]
+impl PasteSite for Pastebin {
+    fn name(&self) -> &str {
+        "pastebin"
+    }
+
+    async fn search(&self, ctx: &PasteContext, query: &str) -> Option<Vec<Paste>> {
+        let search_url = format!("https://psbdmp.ws/api/v3/search/{}", urlencoding::encode(query));
+        let body = match ctx.get(&search_url).await {
+            Ok((_, body)) => body,
+            Err(e) => {
+                warn!("Pastebin search failed: {}", e);
+                return Some(Vec::new());
+            }
+        };
+        let data = serde_json::from_str::<PsbdmpResponse>(&body).map(|r| r.data).unwrap_or_default();
+        Some(
+            data.into_iter()
+                .take(ctx.max_pastes)
+                .map(|paste| Paste {
+                    url: format!("https://pastebin.com/{}", paste.id),
+                    site: self.name().to_string(),
+                    title: paste.title,
+                    content: paste.content,
+                    created_at: paste.time,
+                    author: paste.author,
+                })
+                .collect(),
+        )
+    }
+
+    fn raw_url(&self, url: &str) -> String {
+        format!("{}/raw", url)
+    }
+}
+
+/// Rentry.co, found by dorking (Rentry has no search)
+pub struct Rentry;
+
+#[async_trait]
+impl PasteSite for Rentry {
+    fn name(&self) -> &str {
+        "rentry"
+    }
+
+    async fn search(&self, ctx: &PasteContext, query: &str) -> Option<Vec<Paste>> {
+        if !ctx.can_dork() {
+            debug!("No web search configured, skipping Rentry");
+            return None;
+        }
+        let urls = ctx.dork("rentry.co", query, RENTRY_PAGES).await;
+        Some(urls.iter().map(|url| Paste::new(self.name(), url)).collect())
+    }
+
+    fn parse(&self, html: &str) -> Option<String> {
+        select_text(html, ".markdown-body")
+    }
+}
+
+/// dpaste.org, found by dorking, or its recent pastes without a web search
+pub struct Dpaste;
+
+#[async_trait]
+impl PasteSite for Dpaste {
+    fn name(&self) -> &str {
+        "dpaste"
+    }
+
+    async fn search(&self, ctx: &PasteContext, query: &str) -> Option<Vec<Paste>> {
+        if !ctx.can_dork() {
+            return None;
+        }
+        let urls = ctx.dork("dpaste.org", query, DPASTE_PAGES).await;
+        Some(urls.iter().map(|url| Paste::new(self.name(), url)).collect())
+    }
+
+    async fn list(&self, ctx: &PasteContext) -> Vec<Paste> {
+        match ctx.get("https://dpaste.org/api/?format=json").await {
+            Ok((_, body)) => serde_json::from_str::<Vec<DpasteEntry>>(&body)
+                .unwrap_or_default()
+                .into_iter()
+                .take(ctx.max_pastes)
+                .map(|entry| Paste {
+                    created_at: Some(entry.created),
+                    ..Paste::new(self.name(), &format!("https://dpaste.org/{}", entry.id))
+                })
+                .collect(),
+            Err(e) => {
+                warn!("dpaste listing failed: {}", e);
+                Vec::new()
+            }
+        }
+    }
+
+    fn raw_url(&self, url: &str) -> String {
+        format!("{}/raw", url)
+    }
+}
+
+/// ControlC, through its search page
+pub struct ControlC;
+
+#[async_trait]
+impl PasteSite for ControlC {
+    fn name(&self) -> &str {
+        "controlc"
+    }
+
+    async fn search(&self, ctx: &PasteContext, query: &str) -> Option<Vec<Paste>> {
+        let search_url = format!("https://controlc.com/search?q={}", urlencoding::encode(query));
+        match ctx.get(&search_url).await {
+            Ok((_, html)) => Some(result_links(&html, ".paste-link", &search_url, self.name(), ctx.max_pastes)),
+            Err(e) => {
+                warn!("ControlC search failed: {}", e);
+                Some(Vec::new())
+            }
+        }
+    }
+
+    fn raw_url(&self, url: &str) -> String {
+        format!("{}/raw", url)
+    }
+}
+
+/// JustPaste.it, through its search page
+pub struct JustPaste;
+
+#[async_trait]
+impl PasteSite for JustPaste {
+    fn name(&self) -> &str {
+        "justpaste"
+    }
+
+    async fn search(&self, ctx: &PasteContext, query: &str) -> Option<Vec<Paste>> {
+        let search_url = format!("https://justpaste.it/search?q={}", urlencoding::encode(query));
+        match ctx.get(&search_url).await {
+            Ok((_, html)) => Some(result_links(&html, ".result-item a", &search_url, self.name(), ctx.max_pastes)),
+            Err(e) => {
+                warn!("JustPaste.it search failed: {}", e);
+                Some(Vec::new())
+            }
+        }
+    }
+}
+
+/// A site described in config instead of code
+///
+/// Pastes are found by dorking `dork`, by reading the links matching
+/// `result_selector` on `search_url`, or, failing both, on `list_url`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SiteSpec {
+    pub name: String,
+    /// Domain to dork with the web search (`site:<dork> "<query>"`)
+    #[serde(default)]
+    pub dork: Option<String>,
+    /// First path segments on the dorked domain that aren't pastes
+    #[serde(default)]
+    pub reserved: Vec<String>,
+    /// Search page, with `{query}` standing for the URL-encoded query
+    #[serde(default)]
+    pub search_url: Option<String>,
+    /// Page listing recent pastes
+    #[serde(default)]
+    pub list_url: Option<String>,
+    /// Selector for paste links on the search or list page
+    #[serde(default)]
+    pub result_selector: Option<String>,
+    /// Page with the paste's text, with `{url}` standing for the paste URL (default: the URL)
+    #[serde(default)]
+    pub raw_url: Option<String>,
+    /// Selector for the paste text on that page (default: the whole body)
+    #[serde(default)]
+    pub content_selector: Option<String>,
+}
+
+impl SiteSpec {
+    fn validate(&self) -> Result<(), PasteSiteError> {
+        let invalid = |reason: &str| PasteSiteError::Invalid(self.name.clone(), reason.to_string());
+        if self.name.trim().is_empty() {
+            return Err(PasteSiteError::Invalid(String::new(), "name must not be empty".to_string()));
+        }
+        if self.dork.is_none() && self.search_url.is_none() && self.list_url.is_none() {
+            return Err(invalid("needs a dork, search_url or list_url"));
+        }
+        if (self.search_url.is_some() || self.list_url.is_some()) && self.result_selector.is_none() {
+            return Err(invalid("search_url and list_url need a result_selector"));
+        }
+        if self.search_url.as_ref().is_some_and(|url| !url.contains("{query}")) {
+            return Err(invalid("search_url must contain {query}"));
+        }
+        for selector in self.result_selector.iter().chain(&self.content_selector) {
+            if Selector::parse(selector).is_err() {
+                return Err(invalid(&format!("invalid selector {:?}", selector)));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn shared(self) -> SharedPasteSite {
+        Arc::new(self)
+    }
+}
+
+/// Paste sites described in a TOML file
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SiteSpecs {
+    #[serde(default)]
+    pub sites: Vec<SiteSpec>,
+}
+
+impl SiteSpecs {
+    /// Parse TOML site specs
+    pub fn from_toml(toml: &str) -> Result<Self, PasteSiteError> {
+        let specs: Self = toml::from_str(toml)?;
+        for spec in &specs.sites {
+            spec.validate()?;
+        }
+        Ok(specs)
+    }
+
+    /// Load a TOML site specs file
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, PasteSiteError> {
+        Self::from_toml(&std::fs::read_to_string(path)?)
+    }
+
+    /// The specs as paste sites
+    pub fn into_sites(self) -> Vec<SharedPasteSite> {
+        self.sites.into_iter().map(SiteSpec::shared).collect()
+    }
+}
+
+#[async_trait]
+impl PasteSite for SiteSpec {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn search(&self, ctx: &PasteContext, query: &str) -> Option<Vec<Paste>> {
+        if let Some(domain) = self.dork.as_deref().filter(|_| ctx.can_dork()) {
+            let reserved: Vec<&str> = self.reserved.iter().map(String::as_str).collect();
+            let urls = ctx.dork(domain, query, &reserved).await;
+            return Some(urls.iter().map(|url| Paste::new(&self.name, url)).collect());
+        }
+        let template = self.search_url.as_ref()?;
+        let search_url = template.replace("{query}", &urlencoding::encode(query));
+        Some(self.links(ctx, &search_url).await)
+    }
+
+    async fn list(&self, ctx: &PasteContext) -> Vec<Paste> {
+        match &self.list_url {
+            Some(url) => self.links(ctx, url).await,
+            None => Vec::new(),
+        }
+    }
+
+    fn raw_url(&self, url: &str) -> String {
+        match &self.raw_url {
+            Some(template) => template.replace("{url}", url),
+            None => url.to_string(),
+        }
+    }
+
+    fn parse(&self, body: &str) -> Option<String> {
+        match &self.content_selector {
+            Some(selector) => select_text(body, selector),
+            None => Some(body.to_string()),
+        }
+    }
+}
+
+impl SiteSpec {
+    /// Pastes linked from the page at `url`
+    async fn links(&self, ctx: &PasteContext, url: &str) -> Vec<Paste> {
+        let selector = self.result_selector.as_deref().unwrap_or("a");
+        match ctx.get(url).await {
+            Ok((_, html)) => result_links(&html, selector, url, &self.name, ctx.max_pastes),
+            Err(e) => {
+                warn!("{} search failed: {}", self.name, e);
+                Vec::new()
+            }
+        }
+    }
+}
+
+/// Text of the first element matching `selector`
+fn select_text(html: &str, selector: &str) -> Option<String> {
+    let selector = Selector::parse(selector).ok()?;
+    let document = Html::parse_document(html);
+    document.select(&selector).next().map(|el| el.text().collect::<Vec<_>>().join(" "))
+}
+
+/// Pastes linked by elements matching `selector` on the page at `page_url`
+fn result_links(html: &str, selector: &str, page_url: &str, site: &str, max: usize) -> Vec<Paste> {
+    let (Ok(selector), Ok(base)) = (Selector::parse(selector), reqwest::Url::parse(page_url)) else {
+        return Vec::new();
+    };
+    let document = Html::parse_document(html);
+    let mut pastes: Vec<Paste> = Vec::new();
+    for element in document.select(&selector) {
+        let Some(url) = element.value().attr("href").and_then(|href| base.join(href).ok()) else {
+            continue;
+        };
+        if pastes.iter().any(|p| p.url == url.as_str()) {
+            continue;
+        }
+        let title = element.text().collect::<Vec<_>>().join(" ").trim().to_string();
+        pastes.push(Paste {
+            title: (!title.is_empty()).then_some(title),
+            ..Paste::new(site, url.as_str())
+        });
+        if pastes.len() == max {
+            break;
+        }
+    }
+    pastes
+}
+
+/// `site:` dork for `query` on `domain`
+fn site_dork(domain: &str, query: &str) -> String {
+    format!("site:{} \"{}\"", domain, query.replace('"', ""))
+}
+
+/// Canonical URL of the paste a search result on `domain` points to, if it is one
+///
+/// Pastes live at `https://<domain>/<id>`; other pages (`/`, `/what`, ...) and
+/// other hosts are skipped.
+fn paste_url(result: &str, domain: &str, reserved: &[&str]) -> Option<String> {
+    let url = reqwest::Url::parse(result).ok()?;
+    let host = url.host_str()?.trim_start_matches("www.");
+    if host != domain {
+        return None;
+    }
+    let id = url.path_segments()?.next()?;
+    let valid = !id.is_empty() && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    (valid && !reserved.contains(&id)).then(|| format!("https://{}/{}", domain, id))
+}
+
+/// Psbdmp API response (Pastebin dump search)
+#[derive(Debug, Deserialize)]
+struct PsbdmpResponse {
+    #[serde(default)]
+    data: Vec<PsbdmpPaste>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PsbdmpPaste {
+    id: String,
+    #[serde(default)]
+    title: Option<String>,
+    #[serde(default)]
+    content: String,
+    #[serde(default)]
+    time: Option<String>,
+    #[serde(default)]
+    author: Option<String>,
+}
+
+/// Rentry pages that are not pastes
+const RENTRY_PAGES: &[&str] = &["what", "api", "contacts", "new", "edit"];
+
+/// dpaste pages that are not pastes
+const DPASTE_PAGES: &[&str] = &["about", "api", "history", "static"];
+
+#[derive(Debug, Deserialize)]
+struct BraveSearchResponse {
+    web: BraveWebResults,
+}
+
+#[derive(Debug, Deserialize)]
+struct BraveWebResults {
+    #[serde(default)]
+    results: Vec<BraveWebResult>,
+}
+
+#[derive(Debug, Deserialize)]
+struct BraveWebResult {
+    url: String,
+}
+
+/// Google Custom Search response (no `items` when nothing matched)
+#[derive(Debug, Deserialize)]
+struct GoogleSearchResponse {
+    #[serde(default)]
+    items: Vec<GoogleSearchItem>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GoogleSearchItem {
+    link: String,
+}
+
+/// dpaste API entry
+#[derive(Debug, Deserialize)]
+struct DpasteEntry {
+    id: String,
+    created: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dork_results_to_paste_urls() {
+        assert_eq!(site_dork("rentry.co", "acme \"leak\""), "site:rentry.co \"acme leak\"");
+        assert_eq!(
+            paste_url("https://rentry.co/acme-dump?raw#top", "rentry.co", RENTRY_PAGES).as_deref(),
+            Some("https://rentry.co/acme-dump")
+        );
+        assert_eq!(
+            paste_url("https://www.dpaste.org/AbC12/raw", "dpaste.org", DPASTE_PAGES).as_deref(),
+            Some("https://dpaste.org/AbC12")
+        );
+        assert_eq!(paste_url("https://rentry.co/what", "rentry.co", RENTRY_PAGES), None);
+        assert_eq!(paste_url("https://rentry.co/", "rentry.co", RENTRY_PAGES), None);
+        assert_eq!(paste_url("https://evil-rentry.co/x", "rentry.co", RENTRY_PAGES), None);
+
+        let google: GoogleSearchResponse =
+            serde_json::from_str(r#"{"items": [{"link": "https://rentry.co/x", "title": "x"}]}"#).unwrap();
+        assert_eq!(google.items[0].link, "https://rentry.co/x");
+        let nothing: GoogleSearchResponse = serde_json::from_str(r#"{"kind": "customsearch#search"}"#).unwrap();
+        assert!(nothing.items.is_empty());
+    }
+
+    #[test]
+    fn test_site_specs_parse_pages() {
+        let specs = SiteSpecs::from_toml(
+            "[[sites]]\nname = \"telegraph\"\ndork = \"telegra.ph\"\ncontent_selector = \"article\"\n\n\
+             [[sites]]\nname = \"throwbin\"\nsearch_url = \"https://throwbin.io/search?q={query}\"\n\
+             result_selector = \"a.paste-title\"\nraw_url = \"{url}/raw\"\n",
+        )
+        .unwrap();
+        let sites = specs.clone().into_sites();
+        assert_eq!(sites.iter().map(|s| s.name()).collect::<Vec<_>>(), vec!["telegraph", "throwbin"]);
+
+        let telegraph = &specs.sites[0];
+        assert_eq!(
+            telegraph.parse("<html><body><nav>menu</nav><article>combo list</article></body></html>").as_deref(),
+            Some("combo list")
+        );
+        let throwbin = &specs.sites[1];
+        assert_eq!(throwbin.raw_url("https://throwbin.io/abc"), "https://throwbin.io/abc/raw");
+        let links = result_links(
+            "<a class=\"paste-title\" href=\"/abc\">Acme dump</a><a class=\"paste-title\" href=\"/abc\">again</a>\
+             <a href=\"/about\">About</a><a class=\"paste-title\" href=\"https://throwbin.io/def\"></a>",
+            "a.paste-title",
+            "https://throwbin.io/search?q=acme",
+            &throwbin.name,
+            10,
+        );
+        assert_eq!(links.len(), 2);
+        assert_eq!((links[0].url.as_str(), links[0].title.as_deref()), ("https://throwbin.io/abc", Some("Acme dump")));
+        assert_eq!((links[1].url.as_str(), links[1].title.as_deref()), ("https://throwbin.io/def", None));
+
+        assert_eq!(Rentry.parse("<div class=\"markdown-body\">leak</div>").as_deref(), Some("leak"));
+        assert_eq!(default_paste_sites().len(), 5);
+        assert!(matches!(
+            SiteSpecs::from_toml("[[sites]]\nname = \"x\"\nsearch_url = \"https://x/?q={query}\""),
+            Err(PasteSiteError::Invalid(..))
+        ));
+        assert!(matches!(SiteSpecs::from_toml("[[sites]]\nname = \"x\"\nurl = \"y\""), Err(PasteSiteError::Parse(_))));
+    }
+}
@@ -0,0 +1,184 @@
+//! Profiler Agent
+//!
+//! Folds the signals that reference a handle or threat actor into one
+//! profile per actor (see [`robin_core::ProfileBook`]) and re-emits a
+//! profile each time new evidence changes it, so the field always holds
+//! the latest dossier. Each investigation keeps its own profiles.
+//! - Senses: ExtractedArtifacts, ThreadMessages, PasteContent, ScrapedContent signals
+//! - Emits: ActorProfile signals
+
+use async_trait::async_trait;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use tracing::info;
+
+use robin_core::{ActorDatabase, ActorProfile, AgentType, Field, OsintPayload, PayloadKind, ProfileBook, Signal};
+
+use crate::{AgentConfig, AgentError, OsintAgent};
+
+/// Profiler agent - aggregates evidence per actor
+pub struct ProfilerAgent {
+    config: AgentConfig,
+    actors: ActorDatabase,
+    /// Profiles by investigation (`None` = unscoped signals)
+    books: HashMap<Option<String>, ProfileBook>,
+    processed: HashSet<String>,
+}
+
+impl ProfilerAgent {
+    pub fn new(config: AgentConfig) -> Self {
+        Self {
+            config,
+            actors: ActorDatabase::embedded(),
+            books: HashMap::new(),
+            processed: HashSet::new(),
+        }
+    }
+
+    /// Resolve aliases against `actors` instead of the bundled list
+    pub fn with_actors(mut self, actors: ActorDatabase) -> Self {
+        self.actors = actors;
+        self
+    }
+}
+
+#[async_trait]
+impl OsintAgent for ProfilerAgent {
+    fn id(&self) -> &str {
+        &self.config.id
+    }
+
+    fn agent_type(&self) -> &str {
+        "profiler"
+    }
+
+    fn senses(&self) -> &[PayloadKind] {
+        &[
+            PayloadKind::ExtractedArtifacts,
+            PayloadKind::ThreadMessages,
+            PayloadKind::PasteContent,
+            PayloadKind::ScrapedContent,
+        ]
+    }
+
+    fn emits(&self) -> &[PayloadKind] {
+        &[PayloadKind::ActorProfile]
+    }
+
+    fn sense<'a>(&self, field: &'a Field) -> Vec<&'a Signal> {
+        field
+            .sense_kinds(self.senses(), 0.0)
+            .into_iter()
+            .filter(|signal| !self.processed.contains(&signal.origin_hash))
+            .collect()
+    }
+
+    async fn process(&mut self, field: &mut Field) -> Result<Vec<String>, AgentError> {
+        let signals: Vec<Signal> = self.sense(field).into_iter().cloned().collect();
+        if signals.is_empty() {
+            return Err(AgentError::NoWork);
+        }
+
+        // Changed profiles and the signals that changed them, by investigation and name
+        let mut changed: BTreeMap<(Option<String>, String), (ActorProfile, Vec<String>)> = BTreeMap::new();
+        for signal in &signals {
+            self.processed.insert(signal.origin_hash.clone());
+            let book = self
+                .books
+                .entry(signal.investigation_id.clone())
+                .or_insert_with(|| ProfileBook::new(self.actors.clone()));
+            for profile in book.absorb(signal) {
+                let key = (signal.investigation_id.clone(), profile.name.clone());
+                let entry = changed.entry(key).or_default();
+                entry.0 = profile.clone();
+                entry.1.push(signal.origin_hash.clone());
+            }
+        }
+
+        let mut emitted = Vec::new();
+        for ((_, name), (profile, parents)) in changed {
+            info!("Profile of {} updated from {} signal(s)", name, parents.len());
+            let signal = Signal::builder(OsintPayload::ActorProfile { profile })
+                .origin(&self.config.id)
+                .parents(parents)
+                .confidence(0.7)
+                .ttl(300.0)
+                .build();
+            emitted.push(field.emit(signal));
+        }
+        Ok(emitted)
+    }
+
+    fn heartbeat(&self, field: &mut Field) {
+        let signal = Signal::builder(OsintPayload::Heartbeat {
+            agent_id: self.config.id.clone(),
+            agent_type: AgentType::Profiler,
+            capacity: 1.0,
+        })
+        .origin(&self.config.id)
+        .ttl(10.0)
+        .build();
+
+        field.emit(signal);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use robin_core::{Artifact, ArtifactType};
+
+    fn artifacts(url: &str, actor: &str, wallet: &str) -> Signal {
+        Signal::builder(OsintPayload::ExtractedArtifacts {
+            source_url: url.to_string(),
+            artifacts: vec![
+                Artifact::new(ArtifactType::ThreatActor, actor.to_string()),
+                Artifact::new(ArtifactType::Bitcoin, wallet.to_string()),
+            ],
+        })
+        .origin("extractor-1")
+        .build()
+    }
+
+    fn profile(field: &Field, hash: &str) -> ActorProfile {
+        match &field.get(hash).unwrap().payload {
+            OsintPayload::ActorProfile { profile } => profile.clone(),
+            other => panic!("unexpected payload {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_profiles_refresh_as_evidence_accumulates() {
+        let mut agent = ProfilerAgent::new(AgentConfig::default().with_id("profiler-1"));
+        let mut field = Field::new();
+        field.emit(artifacts("http://leaks.onion/1", "vendor_x", "1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa"));
+
+        let emitted = agent.process(&mut field).await.unwrap();
+        assert_eq!(emitted.len(), 1);
+        let first = profile(&field, &emitted[0]);
+        assert_eq!(first.name, "vendor_x");
+        assert_eq!(first.evidence, 1);
+        assert!(matches!(agent.process(&mut field).await, Err(AgentError::NoWork)));
+
+        // New evidence re-emits the profile with everything so far
+        field.emit(artifacts("http://shop.onion/", "vendor_x", "3J98t1WpEZ73CNmQviecrnyiWrnqRhWNLy"));
+        let emitted = agent.process(&mut field).await.unwrap();
+        let second = profile(&field, &emitted[0]);
+        assert_eq!(second.wallets.len(), 2);
+        assert_eq!(second.sites.len(), 2);
+        assert_eq!(second.evidence, 2);
+
+        // Another investigation starts its own profile
+        let query = field.emit(
+            Signal::builder(OsintPayload::UserQuery { query: "vendor_x".to_string(), priority: 1.0 })
+                .investigation("inv-b")
+                .build(),
+        );
+        let mut scoped = artifacts("http://other.onion/", "vendor_x", "1BoatSLRHtKNngkdXEeobR76b53LETtpyT");
+        scoped.parent_hashes.push(query);
+        field.emit(scoped);
+        let emitted = agent.process(&mut field).await.unwrap();
+        let signal = field.get(&emitted[0]).unwrap();
+        assert_eq!(signal.investigation_id.as_deref(), Some("inv-b"));
+        assert_eq!(profile(&field, &emitted[0]).evidence, 1);
+    }
+}
@@ -0,0 +1,161 @@
+//! LLM response normalization
+//!
+//! Models wrap answers in noise the agents would otherwise parse as
+//! content: reasoning models (often via OpenRouter) leak their chain of
+//! thought in `<think>` blocks, chat models open with "Here is the refined
+//! query:" or fence a one-line answer in ``` markers. Left in, that noise
+//! ends up in search engine URLs (refiner) or as stray numbers (filter).
+//! [`normalize_response`] strips it; [`NormalizedBackend`] applies it to
+//! every response of a backend.
+
+use async_trait::async_trait;
+use std::sync::Arc;
+
+use crate::{LlmBackend, LlmError, SharedBackend};
+
+/// Tags reasoning models put their chain of thought in
+const REASONING_TAGS: &[&str] = &["think", "thinking", "reasoning"];
+
+/// Openings of chatty preamble lines ("Here is the summary:", "Sure!")
+const PREAMBLES: &[&str] = &[
+    "here is", "here's", "here are", "sure", "certainly", "of course", "okay", "ok,", "ok.", "absolutely",
+];
+
+/// Longest line taken for a preamble that doesn't end in a colon ("Sure!")
+const MAX_ACK_CHARS: usize = 40;
+
+/// Strip reasoning blocks, a preamble line and an enclosing code fence
+pub fn normalize_response(text: &str) -> String {
+    let text = strip_reasoning(text);
+    let text = strip_preamble(text.trim());
+    unwrap_fence(text).trim().to_string()
+}
+
+/// Remove `<think>...</think>` style blocks
+///
+/// Some providers drop the opening tag, leaving the reasoning followed by a
+/// bare `</think>`; everything before it goes too. An unclosed block runs to
+/// the end (the answer was cut off mid-thought).
+fn strip_reasoning(text: &str) -> String {
+    let mut text = text.to_string();
+    for tag in REASONING_TAGS {
+        let open = format!("<{}>", tag);
+        let close = format!("</{}>", tag);
+        loop {
+            let lower = text.to_ascii_lowercase();
+            let start = lower.find(&open);
+            let end = lower.find(&close);
+            match (start, end) {
+                (Some(start), Some(end)) if start < end => text.replace_range(start..end + close.len(), ""),
+                (Some(start), None) => text.truncate(start),
+                (_, Some(end)) => text.replace_range(..end + close.len(), ""),
+                (None, None) => break,
+            }
+        }
+    }
+    text
+}
+
+/// Drop a first line like "Here is the refined query:" when more follows
+fn strip_preamble(text: &str) -> &str {
+    let Some((first, rest)) = text.split_once('\n') else {
+        return text;
+    };
+    let line = first.trim().to_lowercase();
+    let chatty = PREAMBLES.iter().any(|p| line.starts_with(p));
+    let ack = line.ends_with(':') || (line.chars().count() <= MAX_ACK_CHARS && line.ends_with(['!', '.']));
+    if chatty && ack && !rest.trim().is_empty() {
+        rest.trim_start()
+    } else {
+        text
+    }
+}
+
+/// The inside of a code fence enclosing the whole text
+///
+/// Only a fence around everything is unwrapped, and only when nothing else
+/// inside is fenced (or it is a Markdown fence), so reports keep their code
+/// blocks.
+fn unwrap_fence(text: &str) -> &str {
+    let Some(inner) = text.strip_prefix("```").and_then(|t| t.strip_suffix("```")) else {
+        return text;
+    };
+    let (info, body) = inner.split_once('\n').unwrap_or(("", inner));
+    let info = info.trim().to_ascii_lowercase();
+    if info.contains(' ') {
+        return text;
+    }
+    let nested = body.lines().any(|line| line.trim_start().starts_with("```"));
+    if nested && info != "markdown" && info != "md" {
+        return text;
+    }
+    body
+}
+
+/// A backend whose responses go through [`normalize_response`]
+pub struct NormalizedBackend {
+    inner: SharedBackend,
+}
+
+impl NormalizedBackend {
+    pub fn new(inner: SharedBackend) -> Self {
+        Self { inner }
+    }
+}
+
+#[async_trait]
+impl LlmBackend for NormalizedBackend {
+    async fn generate(&self, system: &str, user: &str) -> Result<String, LlmError> {
+        let response = normalize_response(&self.inner.generate(system, user).await?);
+        if response.is_empty() {
+            return Err(LlmError::EmptyResponse);
+        }
+        Ok(response)
+    }
+
+    fn model_name(&self) -> &str {
+        self.inner.model_name()
+    }
+}
+
+/// Wrap a shared backend so its responses are normalized
+pub fn normalized(backend: SharedBackend) -> SharedBackend {
+    Arc::new(NormalizedBackend::new(backend))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strips_noise() {
+        assert_eq!(normalize_response("lockbit leak site"), "lockbit leak site");
+        assert_eq!(
+            normalize_response("Here is the refined query:\n\n```\nlockbit leak site\n```"),
+            "lockbit leak site"
+        );
+        assert_eq!(
+            normalize_response("<think>The user wants 3-5 words.\nMaybe 12 results?</think>\nlockbit affiliates"),
+            "lockbit affiliates"
+        );
+        // Opening tag dropped by the provider
+        assert_eq!(normalize_response("Okay, so the query is about...\n</think>\n\n3, 7, 12"), "3, 7, 12");
+        assert_eq!(normalize_response("Sure!\n```text\nransomware payments\n```"), "ransomware payments");
+        // Cut off mid-thought: nothing usable
+        assert_eq!(normalize_response("<THINKING>Let me consider"), "");
+    }
+
+    #[test]
+    fn test_keeps_content() {
+        let report = "## Summary\n\nSure enough, the actor reused infrastructure.\n\n```\n10.0.0.1\n```";
+        assert_eq!(normalize_response(report), report);
+        let fenced = "```bash\ncurl x\n```\n\ntext\n\n```bash\ncurl y\n```";
+        assert_eq!(normalize_response(fenced), fenced);
+        assert_eq!(
+            normalize_response("```markdown\n# Report\n```\ncode\n```\n```"),
+            "# Report\n```\ncode\n```"
+        );
+        // A one-line answer that merely starts like a preamble is the answer
+        assert_eq!(normalize_response("Sure, lockbit leak site"), "Sure, lockbit leak site");
+    }
+}
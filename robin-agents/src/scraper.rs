@@ -1,23 +1,60 @@
 //! Scraper Agent
 //!
-//! Extracts content from filtered dark web URLs.
-//! - Senses: FilteredResult signals
-//! - Emits: ScrapedContent signals
+//! Extracts content from filtered dark web URLs. Malformed onion addresses
+//! are skipped without a request, and with `TorConfig::onion_probe_timeout_secs`
+//! set, unreachable services are dropped after a short probe. Once a host
+//! answers with an unsolved captcha, its other URLs are skipped for the run.
+//! Forum threads and leak site indexes matched by `TorConfig::site_adapters`
+//! come through as one line per post with author and timestamp. With a
+//! [`LinkFrontier`], hidden service links on scraped pages are emitted as
+//! new results within the frontier's depth and page budget. Pages in the
+//! [`SeenStore`](robin_core::SeenStore) from earlier runs are not fetched again.
+//! With a [`Translator`](crate::Translator), pages in other languages are
+//! emitted in English, with the original text kept alongside. Pages longer
+//! than the chunk size are emitted as several chunk signals, in order.
+//! Scrapers claim each URL on the field before fetching it (and through a
+//! shared [`WorkQueue`] within one swarm), so no two of them scrape the
+//! same page for the same investigation.
+//! - Senses: FilteredResult signals (and TaskClaims, to retry URLs whose claim lapsed)
+//! - Emits: ScrapedContent signals (and FilteredResult when following links)
 
 use async_trait::async_trait;
 use std::collections::HashSet;
+use std::time::Duration;
 use tracing::{debug, info, warn};
 
-use robin_core::{AgentType, Field, OsintPayload, Signal};
-use robin_tor::{scrape_url, TorConfig};
+use robin_core::{investigation_task, AgentType, ContentChunk, Field, Network, OsintPayload, PayloadKind, SeenKind, SharedSeen, Signal};
+use robin_tor::{chunk_text, host_key, probe_onion, scrape_url, validate_onion, TorConfig, TorError};
 
-use crate::{AgentConfig, AgentError, OsintAgent};
+use crate::{
+    claimed_by_others, to_english, AgentConfig, AgentError, OsintAgent, SharedFrontier, SharedTranslator,
+    WorkQueue,
+};
+
+/// Relevance kept per hop when following links
+const FOLLOW_RELEVANCE_DECAY: f64 = 0.8;
+
+/// Default size of a ScrapedContent chunk, in bytes
+pub const DEFAULT_CHUNK_CHARS: usize = 4000;
 
 /// Scraper agent - extracts content from dark web sites
 pub struct ScraperAgent {
     config: AgentConfig,
     tor_config: TorConfig,
-    scraped_urls: HashSet<String>,
+    /// URLs done, by investigation
+    scraped_urls: HashSet<(Option<String>, String)>,
+    /// Hosts that answered with a captcha nobody solved
+    captcha_hosts: HashSet<String>,
+    /// Link following state shared with the other scrapers (None = don't follow)
+    frontier: Option<SharedFrontier>,
+    /// Pages scraped by earlier runs (None = scrape everything)
+    seen: Option<SharedSeen>,
+    /// Translates non-English pages (None = emit pages as scraped)
+    translator: Option<SharedTranslator>,
+    /// Longest text in one ScrapedContent signal
+    chunk_chars: usize,
+    /// URL claims shared with the other scrapers (None = scrape whatever is sensed)
+    queue: Option<WorkQueue>,
 }
 
 impl ScraperAgent {
@@ -26,12 +63,115 @@ impl ScraperAgent {
             config,
             tor_config,
             scraped_urls: HashSet::new(),
+            captcha_hosts: HashSet::new(),
+            frontier: None,
+            seen: None,
+            translator: None,
+            chunk_chars: DEFAULT_CHUNK_CHARS,
+            queue: None,
         }
     }
 
+    /// Claim URLs from `queue` before scraping them, leaving others' claims alone
+    pub fn with_work_queue(mut self, queue: WorkQueue) -> Self {
+        self.queue = Some(queue);
+        self
+    }
+
+    /// Split pages into ScrapedContent chunks of at most this many bytes
+    pub fn with_chunk_chars(mut self, chunk_chars: usize) -> Self {
+        self.chunk_chars = chunk_chars.max(1);
+        self
+    }
+
+    /// Translate pages in other languages to English
+    pub fn with_translator(mut self, translator: SharedTranslator) -> Self {
+        self.translator = Some(translator);
+        self
+    }
+
+    /// Skip pages earlier runs scraped, and record this run's
+    pub fn with_seen(mut self, seen: SharedSeen) -> Self {
+        self.seen = Some(seen);
+        self
+    }
+
+    /// Follow links on scraped pages within the frontier's limits
+    pub fn with_frontier(mut self, frontier: SharedFrontier) -> Self {
+        self.frontier = Some(frontier);
+        self
+    }
+
     pub fn with_default_tor(config: AgentConfig) -> Self {
         Self::new(config, TorConfig::default())
     }
+
+    /// Whether `url` is worth a scrape: in scope, well-formed and, if probing, reachable
+    async fn worth_scraping(&self, url: &str) -> bool {
+        // Out-of-scope URLs aren't even probed
+        if let Err(reason) = self.tor_config.crawl_policy.check(url) {
+            info!("Skipping {}: {}", url, reason);
+            return false;
+        }
+        if let Some(seen) = &self.seen {
+            if seen.lock().unwrap().seen_before(SeenKind::Scraped, url) {
+                debug!("Skipping {}: scraped by an earlier run", url);
+                return false;
+            }
+        }
+        if host_key(url).is_some_and(|host| self.captcha_hosts.contains(&host)) {
+            debug!("Skipping {}: host requires a captcha", url);
+            return false;
+        }
+        if Network::of_url(url) != Some(Network::Tor) {
+            return true;
+        }
+        if let Err(e) = validate_onion(url) {
+            debug!("Skipping {}: {}", url, e);
+            return false;
+        }
+        if let Some(secs) = self.tor_config.onion_probe_timeout_secs {
+            if !probe_onion(url, &self.tor_config, Duration::from_secs(secs)).await {
+                debug!("Skipping {}: onion service unreachable", url);
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Emit links on `url` the frontier admits as results one hop deeper
+    ///
+    /// `page` holds the hashes of the page's ScrapedContent signals.
+    fn follow_links(&self, field: &mut Field, url: &str, links: &[String], relevance: f64, page: &[String]) -> Vec<String> {
+        let Some(frontier) = &self.frontier else {
+            return Vec::new();
+        };
+        // Out-of-scope links don't spend the frontier's budget
+        let links: Vec<String> =
+            links.iter().filter(|link| self.tor_config.crawl_policy.check(link).is_ok()).cloned().collect();
+        let admitted = frontier.lock().unwrap().admit(url, &links);
+        if !admitted.is_empty() {
+            debug!("Following {} links from {}", admitted.len(), url);
+        }
+
+        admitted
+            .into_iter()
+            .map(|(link, depth)| {
+                let signal = Signal::builder(OsintPayload::FilteredResult {
+                    url: link.clone(),
+                    title: link,
+                    relevance: relevance * FOLLOW_RELEVANCE_DECAY,
+                    reason: format!("Linked from {} (depth {})", url, depth),
+                })
+                .origin(&self.config.id)
+                .parents(page)
+                .confidence(0.6)
+                .ttl(120.0)
+                .build();
+                field.emit(signal)
+            })
+            .collect()
+    }
 }
 
 #[async_trait]
@@ -44,19 +184,39 @@ impl OsintAgent for ScraperAgent {
         "scraper"
     }
 
-    fn sense<'a>(&self, field: &'a Field) -> Vec<&'a Signal> {
-        field.sense_where(|signal| {
-            if signal.effective_intensity(field.now()) < self.config.sensing_threshold {
-                return false;
-            }
+    fn senses(&self) -> &[PayloadKind] {
+        // Claims too: a lapsed claim hands its URL back
+        &[PayloadKind::FilteredResult, PayloadKind::TaskClaim]
+    }
 
-            match &signal.payload {
+    fn wakes_itself(&self) -> bool {
+        // Queue claims lapse on their own clock, without a field change
+        self.queue.as_ref().is_some_and(|queue| !queue.is_empty())
+    }
+
+    fn emits(&self) -> &[PayloadKind] {
+        if self.frontier.is_some() {
+            &[PayloadKind::ScrapedContent, PayloadKind::FilteredResult]
+        } else {
+            &[PayloadKind::ScrapedContent]
+        }
+    }
+
+    fn sense<'a>(&self, field: &'a Field) -> Vec<&'a Signal> {
+        let claimed = claimed_by_others(field, &self.config.id);
+        field
+            .sense_kinds(&[PayloadKind::FilteredResult], self.config.sensing_threshold)
+            .into_iter()
+            .filter(|signal| match &signal.payload {
                 OsintPayload::FilteredResult { url, .. } => {
-                    !self.scraped_urls.contains(url)
+                    let task = investigation_task(signal.investigation_id.as_deref(), url);
+                    !self.scraped_urls.contains(&(signal.investigation_id.clone(), url.clone()))
+                        && !claimed.contains(task.as_str())
+                        && self.queue.as_ref().and_then(|queue| queue.claimer(&task)).is_none()
                 }
                 _ => false,
-            }
-        })
+            })
+            .collect()
     }
 
     async fn process(&mut self, field: &mut Field) -> Result<Vec<String>, AgentError> {
@@ -68,47 +228,113 @@ impl OsintAgent for ScraperAgent {
 
         let mut emitted = Vec::new();
 
-        // Process URLs with limited concurrency
-        let urls_to_scrape: Vec<(String, String)> = signals
-            .iter()
-            .filter_map(|signal| {
-                if let OsintPayload::FilteredResult { url, title, .. } = &signal.payload {
-                    Some((url.clone(), title.clone()))
-                } else {
-                    None
+        // Process URLs with limited concurrency, claiming each first
+        let mut urls_to_scrape: Vec<(Option<String>, String, String, f64, String)> = Vec::new();
+        for signal in &signals {
+            if urls_to_scrape.len() >= self.config.max_concurrent {
+                break;
+            }
+            let OsintPayload::FilteredResult { url, title, relevance, .. } = &signal.payload else {
+                continue;
+            };
+            let task = investigation_task(signal.investigation_id.as_deref(), url);
+            if self.queue.as_ref().is_some_and(|queue| !queue.claim(&task, &self.config.id)) {
+                continue;
+            }
+            if !field.claim_task(&task, &self.config.id, *relevance).is_accepted() {
+                // Held elsewhere: don't keep the other scrapers off it
+                if let Some(queue) = &self.queue {
+                    queue.release(&task, &self.config.id);
                 }
-            })
-            .take(self.config.max_concurrent)
-            .collect();
+                continue;
+            }
+            urls_to_scrape.push((
+                signal.investigation_id.clone(),
+                url.clone(),
+                title.clone(),
+                *relevance,
+                signal.origin_hash.clone(),
+            ));
+        }
 
         info!("Scraper processing {} URLs", urls_to_scrape.len());
 
-        for (url, title) in urls_to_scrape {
+        for (investigation, url, title, relevance, parent) in urls_to_scrape {
             // Mark as scraped (even if it fails, to avoid retrying)
-            self.scraped_urls.insert(url.clone());
+            self.scraped_urls.insert((investigation, url.clone()));
+            if let Some(frontier) = &self.frontier {
+                frontier.lock().unwrap().visit(&url);
+            }
+
+            if !self.worth_scraping(&url).await {
+                continue;
+            }
 
             match scrape_url(&url, &self.tor_config).await {
                 Ok(page) => {
+                    // Only pages actually fetched count as scraped for later runs
+                    if let Some(seen) = &self.seen {
+                        seen.lock().unwrap().record(SeenKind::Scraped, &url);
+                    }
                     if page.text.is_empty() {
                         debug!("Empty content from {}", url);
                         continue;
                     }
 
-                    let scraped_signal = Signal::builder(OsintPayload::ScrapedContent {
-                        url: url.clone(),
-                        title: page.title.unwrap_or(title),
-                        text: page.text,
-                        char_count: page.char_count,
-                    })
-                    .origin(&self.config.id)
-                    .confidence(0.9)
-                    .ttl(180.0) // Scraped content persists longer
-                    .build();
-
-                    let hash = field.emit(scraped_signal);
-                    emitted.push(hash);
-
-                    debug!("Scraped {} chars from {}", page.char_count, url);
+                    let title = page.title.unwrap_or(title);
+                    let chunks = chunk_text(&page.text, self.chunk_chars);
+                    let count = chunks.len();
+                    let mut screenshot = page.screenshot.map(|path| path.display().to_string());
+                    let mut metadata = (!page.metadata.is_empty()).then_some(page.metadata);
+                    let mut chunk_hashes = Vec::with_capacity(count);
+                    for (index, (offset, piece)) in chunks.into_iter().enumerate() {
+                        let translated = match &self.translator {
+                            Some(translator) => to_english(translator.as_ref(), piece, page.language.as_deref()).await,
+                            None => None,
+                        };
+                        if translated.is_some() {
+                            debug!("Translated {} from {}", url, page.language.as_deref().unwrap_or_default());
+                        }
+                        let (text, original_text) = match translated {
+                            Some(english) => (english, Some(piece.to_string())),
+                            None => (piece.to_string(), None),
+                        };
+
+                        let scraped_signal = Signal::builder(OsintPayload::ScrapedContent {
+                            url: url.clone(),
+                            title: title.clone(),
+                            char_count: piece.len(),
+                            text,
+                            // Evidence of the page, not of each chunk
+                            screenshot: screenshot.take(),
+                            metadata: metadata.take(),
+                            language: page.language.clone(),
+                            original_text,
+                            chunk: (count > 1).then_some(ContentChunk { index, count, offset }),
+                        })
+                        .origin(&self.config.id)
+                        .parent(&parent)
+                        .confidence(0.9)
+                        .ttl(180.0) // Scraped content persists longer
+                        .build();
+
+                        let hash = field.emit(scraped_signal);
+                        chunk_hashes.push(hash.clone());
+                        emitted.push(hash);
+                    }
+
+                    debug!("Scraped {} chars from {} ({} chunks)", page.char_count, url, count);
+                    emitted.extend(self.follow_links(field, &url, &page.links, relevance, &chunk_hashes));
+                }
+                Err(e @ TorError::CaptchaRequired { .. }) => {
+                    warn!("Skipping {}: {}", url, e);
+                    self.captcha_hosts.extend(host_key(&url));
+                }
+                Err(e @ TorError::OutOfScope(_)) => {
+                    info!("Skipping {}", e);
+                }
+                Err(e @ TorError::CircuitOpen(_)) => {
+                    debug!("Skipping {}: {}", url, e);
                 }
                 Err(e) => {
                     warn!("Failed to scrape {}: {}", url, e);
@@ -139,3 +365,256 @@ impl OsintAgent for ScraperAgent {
         field.emit(signal);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{FollowLimits, LinkFrontier};
+    use robin_tor::{Cassette, Interaction};
+
+    const ONION: &str = "http://2gzyxa5ihm7nsggfxnu52rck2vv4rvmdlkiu3zzui5du4xyclen53wid.onion";
+
+    fn filtered(url: &str) -> Signal {
+        Signal::builder(OsintPayload::FilteredResult {
+            url: url.to_string(),
+            title: "Result".to_string(),
+            relevance: 0.9,
+            reason: "test".to_string(),
+        })
+        .build()
+    }
+
+    fn page(path: &str, body: &str) -> Interaction {
+        Interaction {
+            method: "GET".to_string(),
+            url: format!("{}{}", ONION, path),
+            request_body: None,
+            status: 200,
+            body: body.to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_scraper_replays_pages_and_skips_captcha_hosts() {
+        let cassette = Cassette::from_interactions(vec![
+            page("/dump", "<title>Dump</title><body><p>wallet list</p></body>"),
+            page("/login", "<h1>Prove you're human</h1>"),
+        ]);
+        let tor_config = TorConfig {
+            cassette: Some(cassette),
+            max_retries: 0,
+            ..Default::default()
+        };
+        let mut agent = ScraperAgent::new(AgentConfig::default().with_id("scraper-1"), tor_config);
+
+        let mut field = Field::new();
+        field.emit(filtered(&format!("{}/dump", ONION)));
+        let emitted = agent.process(&mut field).await.unwrap();
+        assert_eq!(emitted.len(), 1);
+        assert!(matches!(&field.get(&emitted[0]).unwrap().payload,
+            OsintPayload::ScrapedContent { title, text, .. } if title == "Dump" && text.contains("wallet list")));
+
+        // A captcha yields no content and the host is skipped from then on
+        field.emit(filtered(&format!("{}/login", ONION)));
+        assert!(agent.process(&mut field).await.unwrap().is_empty());
+        assert!(!agent.worth_scraping(&format!("{}/other", ONION)).await);
+    }
+
+    #[tokio::test]
+    async fn test_scraper_follows_links_within_depth() {
+        let cassette = Cassette::from_interactions(vec![
+            page("/index", r#"<body><p>leak index</p><a href="/part1">Part 1</a></body>"#),
+            page("/part1", r#"<body><p>part one</p><a href="/part2">Part 2</a></body>"#),
+        ]);
+        let tor_config = TorConfig {
+            cassette: Some(cassette),
+            max_retries: 0,
+            ..Default::default()
+        };
+        let frontier = LinkFrontier::new(FollowLimits { max_depth: 1, ..Default::default() }).shared();
+        let mut agent =
+            ScraperAgent::new(AgentConfig::default().with_id("scraper-1"), tor_config).with_frontier(frontier.clone());
+        assert!(agent.emits().contains(&PayloadKind::FilteredResult));
+
+        let mut field = Field::new();
+        field.emit(filtered(&format!("{}/index", ONION)));
+        let emitted = agent.process(&mut field).await.unwrap();
+        assert_eq!(emitted.len(), 2);
+        assert!(matches!(&field.get(&emitted[1]).unwrap().payload,
+            OsintPayload::FilteredResult { url, relevance, .. } if url == &format!("{}/part1", ONION) && *relevance < 0.9));
+
+        // The linked page is scraped, but its own links are past the depth limit
+        let emitted = agent.process(&mut field).await.unwrap();
+        assert_eq!(emitted.len(), 1);
+        assert_eq!(frontier.lock().unwrap().followed(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_scraper_splits_long_pages_into_chunks() {
+        let body = "leaked record line. ".repeat(30);
+        let cassette = Cassette::from_interactions(vec![page("/long", &format!("<body><p>{}</p></body>", body))]);
+        let tor_config = TorConfig {
+            cassette: Some(cassette),
+            max_retries: 0,
+            ..Default::default()
+        };
+        let mut agent = ScraperAgent::new(AgentConfig::default().with_id("scraper-1"), tor_config).with_chunk_chars(200);
+
+        let mut field = Field::new();
+        field.emit(filtered(&format!("{}/long", ONION)));
+        let emitted = agent.process(&mut field).await.unwrap();
+        assert!(emitted.len() >= 3);
+
+        let mut next_offset = 0;
+        for (i, hash) in emitted.iter().enumerate() {
+            let OsintPayload::ScrapedContent { text, chunk: Some(chunk), .. } = &field.get(hash).unwrap().payload else {
+                panic!("expected a chunk");
+            };
+            assert_eq!((chunk.index, chunk.count), (i, emitted.len()));
+            assert!(chunk.offset >= next_offset && text.len() <= 200);
+            next_offset = chunk.offset + text.len();
+        }
+    }
+
+    struct Upper;
+
+    #[async_trait]
+    impl crate::Translator for Upper {
+        async fn translate(&self, text: &str, language: &str) -> Result<String, crate::LlmError> {
+            Ok(format!("({}) {}", language, text.to_uppercase()))
+        }
+
+        fn name(&self) -> &str {
+            "upper"
+        }
+    }
+
+    #[tokio::test]
+    async fn test_scraper_translates_other_languages() {
+        let russian = "Продаю свежие базы данных клиентов банков, оплата только в биткоинах через гаранта.";
+        let cassette = Cassette::from_interactions(vec![
+            page("/ru", &format!("<body><p>{}</p></body>", russian)),
+            page("/en", "<body><p>Selling fresh bank customer databases, payment in bitcoin through escrow only.</p></body>"),
+        ]);
+        let tor_config = TorConfig {
+            cassette: Some(cassette),
+            max_retries: 0,
+            ..Default::default()
+        };
+        let mut agent = ScraperAgent::new(AgentConfig::default().with_id("scraper-1"), tor_config)
+            .with_translator(std::sync::Arc::new(Upper));
+
+        let mut field = Field::new();
+        field.emit(filtered(&format!("{}/ru", ONION)));
+        field.emit(filtered(&format!("{}/en", ONION)));
+        let emitted = agent.process(&mut field).await.unwrap();
+        assert_eq!(emitted.len(), 2);
+        for hash in emitted {
+            match &field.get(&hash).unwrap().payload {
+                OsintPayload::ScrapedContent { url, text, language, original_text, .. } if url.ends_with("/ru") => {
+                    assert_eq!(language.as_deref(), Some("ru"));
+                    assert!(text.starts_with("(ru) ПРОДАЮ"));
+                    assert!(original_text.as_deref().unwrap().starts_with("Продаю"));
+                }
+                OsintPayload::ScrapedContent { text, language, original_text, .. } => {
+                    assert_eq!(language.as_deref(), Some("en"));
+                    assert!(text.starts_with("Selling") && original_text.is_none());
+                }
+                other => panic!("unexpected {:?}", other),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_scrapers_divide_urls_through_work_queue() {
+        let cassette = Cassette::from_interactions(
+            (1..=4).map(|i| page(&format!("/p{}", i), &format!("<body><p>page {}</p></body>", i))).collect(),
+        );
+        let tor_config = TorConfig {
+            cassette: Some(cassette),
+            max_retries: 0,
+            ..Default::default()
+        };
+        let queue = WorkQueue::new();
+        let mut first = ScraperAgent::new(AgentConfig::default().with_id("scraper-1"), tor_config.clone())
+            .with_work_queue(queue.clone());
+        let mut second =
+            ScraperAgent::new(AgentConfig::default().with_id("scraper-2"), tor_config).with_work_queue(queue.clone());
+
+        let mut field = Field::new();
+        for i in 1..=4 {
+            field.emit(filtered(&format!("{}/p{}", ONION, i)));
+        }
+        // Both run in the same tick; the second only gets what the first left
+        let mut urls = HashSet::new();
+        for agent in [&mut first, &mut second] {
+            for hash in agent.process(&mut field).await.unwrap() {
+                let OsintPayload::ScrapedContent { url, .. } = &field.get(&hash).unwrap().payload else {
+                    panic!("expected scraped content");
+                };
+                assert_eq!(queue.claimer(url).as_deref(), Some(agent.id()));
+                assert!(urls.insert(url.clone()), "{} scraped twice", url);
+            }
+        }
+        assert_eq!(urls.len(), 4);
+        assert_eq!(field.sense_by_type(0.0).task_claims.len(), 4);
+        assert!(matches!(second.process(&mut field).await, Err(AgentError::NoWork)));
+    }
+
+    #[tokio::test]
+    async fn test_only_fetched_pages_are_recorded_as_seen() {
+        let cassette = Cassette::from_interactions(vec![page("/dump", "<body><p>wallet list</p></body>")]);
+        let tor_config = TorConfig {
+            cassette: Some(cassette),
+            max_retries: 0,
+            ..Default::default()
+        };
+        let seen: SharedSeen = Default::default();
+        let mut scraper = ScraperAgent::new(AgentConfig::default(), tor_config).with_seen(seen.clone());
+
+        let mut field = Field::new();
+        field.emit(filtered(&format!("{}/dump", ONION)));
+        field.emit(filtered(&format!("{}/gone", ONION)));
+        scraper.process(&mut field).await.unwrap();
+        assert_eq!(seen.lock().unwrap().len(SeenKind::Scraped), 1);
+    }
+
+    #[tokio::test]
+    async fn test_investigations_sharing_a_url_each_get_it() {
+        let cassette = Cassette::from_interactions(vec![page("/dump", "<body><p>contact seller@leaks.example</p></body>")]);
+        let tor_config = TorConfig {
+            cassette: Some(cassette),
+            max_retries: 0,
+            ..Default::default()
+        };
+        let queue = WorkQueue::new();
+        let mut first = ScraperAgent::new(AgentConfig::default().with_id("scraper-1"), tor_config.clone())
+            .with_work_queue(queue.clone());
+        let mut second =
+            ScraperAgent::new(AgentConfig::default().with_id("scraper-2"), tor_config).with_work_queue(queue.clone());
+        let mut extractor = crate::ExtractorAgent::new(AgentConfig::default().with_id("extractor-1"));
+
+        let mut field = Field::new();
+        for investigation in ["inv-a", "inv-b"] {
+            let mut signal = filtered(&format!("{}/dump", ONION));
+            signal.investigation_id = Some(investigation.to_string());
+            field.emit(signal);
+        }
+
+        // One scraper takes both; the other finds nothing left
+        let scraped = first.process(&mut field).await.unwrap();
+        assert!(matches!(second.process(&mut field).await, Err(AgentError::NoWork)));
+        let mut investigations: Vec<_> =
+            scraped.iter().map(|hash| field.get(hash).unwrap().investigation_id.clone().unwrap()).collect();
+        investigations.sort();
+        assert_eq!(investigations, ["inv-a", "inv-b"]);
+        assert_eq!(field.sense_by_type(0.0).task_claims.len(), 2);
+
+        // Each investigation's copy is mined for artifacts
+        let extracted = extractor.process(&mut field).await.unwrap();
+        let mut investigations: Vec<_> =
+            extracted.iter().map(|hash| field.get(hash).unwrap().investigation_id.clone().unwrap()).collect();
+        investigations.sort();
+        assert_eq!(investigations, ["inv-a", "inv-b"]);
+    }
+}
@@ -0,0 +1,195 @@
+//! Thread Parser Agent
+//!
+//! Recognizes chat logs and forum threads in scraped pages and pastes (see
+//! [`robin_core::parse_thread`]) and emits each author's messages as a
+//! signal of their own, so handles can be tracked across conversations.
+//! Pages are parsed in their own language, and a page scraped in several
+//! chunks is parsed once all of them have arrived.
+//! - Senses: ScrapedContent, PasteContent signals
+//! - Emits: ThreadMessages signals
+
+use async_trait::async_trait;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use tracing::{debug, info};
+
+use robin_core::{parse_thread, AgentType, Field, OsintPayload, PayloadKind, Signal};
+
+use crate::{AgentConfig, AgentError, OsintAgent};
+
+/// Thread parser agent - splits conversations into per-author messages
+pub struct ThreadParserAgent {
+    config: AgentConfig,
+    processed: HashSet<String>,
+    /// Chunks received so far of pages not yet complete, by URL
+    pending: HashMap<String, BTreeMap<usize, (String, String)>>,
+}
+
+impl ThreadParserAgent {
+    pub fn new(config: AgentConfig) -> Self {
+        Self {
+            config,
+            processed: HashSet::new(),
+            pending: HashMap::new(),
+        }
+    }
+}
+
+#[async_trait]
+impl OsintAgent for ThreadParserAgent {
+    fn id(&self) -> &str {
+        &self.config.id
+    }
+
+    fn agent_type(&self) -> &str {
+        "thread_parser"
+    }
+
+    fn senses(&self) -> &[PayloadKind] {
+        &[PayloadKind::ScrapedContent, PayloadKind::PasteContent]
+    }
+
+    fn emits(&self) -> &[PayloadKind] {
+        &[PayloadKind::ThreadMessages]
+    }
+
+    fn sense<'a>(&self, field: &'a Field) -> Vec<&'a Signal> {
+        field
+            .sense_kinds(self.senses(), 0.0)
+            .into_iter()
+            .filter(|signal| !self.processed.contains(&signal.origin_hash))
+            .collect()
+    }
+
+    async fn process(&mut self, field: &mut Field) -> Result<Vec<String>, AgentError> {
+        let signals: Vec<Signal> = self.sense(field).into_iter().cloned().collect();
+        if signals.is_empty() {
+            return Err(AgentError::NoWork);
+        }
+
+        let mut documents = Vec::new();
+        for signal in &signals {
+            self.processed.insert(signal.origin_hash.clone());
+            match &signal.payload {
+                OsintPayload::PasteContent { url, content, .. } => {
+                    documents.push((url.clone(), content.clone(), vec![signal.origin_hash.clone()]));
+                }
+                OsintPayload::ScrapedContent { url, text, original_text, chunk, .. } => {
+                    let text = original_text.as_deref().unwrap_or(text);
+                    match chunk {
+                        None => documents.push((url.clone(), text.to_string(), vec![signal.origin_hash.clone()])),
+                        Some(chunk) => {
+                            let parts = self.pending.entry(url.clone()).or_default();
+                            parts.insert(chunk.index, (text.to_string(), signal.origin_hash.clone()));
+                            if parts.len() < chunk.count {
+                                continue;
+                            }
+                            let (texts, hashes): (Vec<_>, Vec<_>) =
+                                self.pending.remove(url).unwrap_or_default().into_values().unzip();
+                            documents.push((url.clone(), texts.join("\n"), hashes));
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        let mut emitted = Vec::new();
+        for (url, text, parents) in documents {
+            let Some(thread) = parse_thread(&text) else {
+                debug!("No conversation in {}", url);
+                continue;
+            };
+            let authors = thread.by_author();
+            info!(
+                "Parsed {} {:?} messages from {} authors in {}",
+                thread.messages.len(),
+                thread.format,
+                authors.len(),
+                url
+            );
+            for (author, messages) in authors {
+                let signal = Signal::builder(OsintPayload::ThreadMessages {
+                    source_url: url.clone(),
+                    format: thread.format,
+                    author: author.to_string(),
+                    messages: messages.into_iter().cloned().collect(),
+                    thread_messages: thread.messages.len(),
+                })
+                .origin(&self.config.id)
+                .parents(parents.clone())
+                .confidence(0.8)
+                .ttl(300.0)
+                .build();
+                emitted.push(field.emit(signal));
+            }
+        }
+        Ok(emitted)
+    }
+
+    fn heartbeat(&self, field: &mut Field) {
+        let signal = Signal::builder(OsintPayload::Heartbeat {
+            agent_id: self.config.id.clone(),
+            agent_type: AgentType::ThreadParser,
+            capacity: 1.0,
+        })
+        .origin(&self.config.id)
+        .ttl(10.0)
+        .build();
+
+        field.emit(signal);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_emits_messages_per_author() {
+        let mut agent = ThreadParserAgent::new(AgentConfig::default().with_id("threads-1"));
+        let mut field = Field::new();
+        field.emit(
+            Signal::builder(OsintPayload::PasteContent {
+                url: "https://paste.example/abc".to_string(),
+                site: "pastebin".to_string(),
+                title: None,
+                content: "[21:14] <darkseller> combo lists\n[21:15] <buyer_77> price?\n[21:16] <darkseller> 0.01 btc"
+                    .to_string(),
+                created_at: None,
+                author: None,
+                language: None,
+                score: 0.0,
+            })
+            .build(),
+        );
+        field.emit(
+            Signal::builder(OsintPayload::PasteContent {
+                url: "https://paste.example/def".to_string(),
+                site: "pastebin".to_string(),
+                title: None,
+                content: "just a config file".to_string(),
+                created_at: None,
+                author: None,
+                language: None,
+                score: 0.0,
+            })
+            .build(),
+        );
+
+        let emitted = agent.process(&mut field).await.unwrap();
+        assert_eq!(emitted.len(), 2);
+        let by_author: HashMap<String, usize> = emitted
+            .iter()
+            .filter_map(|hash| match &field.get(hash).unwrap().payload {
+                OsintPayload::ThreadMessages { author, messages, thread_messages, .. } => {
+                    assert_eq!(*thread_messages, 3);
+                    Some((author.clone(), messages.len()))
+                }
+                _ => None,
+            })
+            .collect();
+        assert_eq!(by_author["darkseller"], 2);
+        assert_eq!(by_author["buyer_77"], 1);
+        assert!(matches!(agent.process(&mut field).await, Err(AgentError::NoWork)));
+    }
+}
@@ -1,7 +1,7 @@
 //! Common traits for OSINT agents
 
 use async_trait::async_trait;
-use robin_core::{Field, Signal};
+use robin_core::{Field, PayloadKind, Signal};
 use thiserror::Error;
 
 /// Errors from agent operations
@@ -32,6 +32,20 @@ pub trait OsintAgent: Send + Sync {
     /// Agent type name
     fn agent_type(&self) -> &str;
 
+    /// Payload kinds this agent consumes (heartbeats aside)
+    fn senses(&self) -> &[PayloadKind];
+
+    /// Payload kinds this agent may emit (heartbeats aside)
+    fn emits(&self) -> &[PayloadKind];
+
+    /// Whether the agent can find work without new sensed signals (e.g. polling on a timer)
+    ///
+    /// The swarm skips agents that reported `NoWork` until one of their
+    /// sensed kinds changes, unless this returns true.
+    fn wakes_itself(&self) -> bool {
+        false
+    }
+
     /// Sense relevant signals from the field
     fn sense<'a>(&self, field: &'a Field) -> Vec<&'a Signal>;
 
@@ -0,0 +1,172 @@
+//! Translation of non-English content
+//!
+//! Scraped pages and result titles in other languages are translated to
+//! English before the filter and analyst see them (see
+//! [`robin_core::detect_language`]). [`LlmTranslator`] uses the run's LLM
+//! backend; [`LibreTranslator`] calls a LibreTranslate-compatible API,
+//! which keeps page text away from the LLM provider and costs no tokens.
+
+use async_trait::async_trait;
+use serde::Deserialize;
+use std::sync::Arc;
+use tracing::warn;
+
+use robin_core::is_english;
+
+use crate::{LlmError, SharedBackend};
+
+const TRANSLATOR_SYSTEM_PROMPT: &str = r#"
+You are a translator for threat intelligence analysts. Translate the user's text from the language with ISO 639-1 code "{language}" into English.
+Keep URLs, onion addresses, usernames, email and cryptocurrency addresses, numbers, prices and code exactly as written. Translate slang literally and keep its meaning; do not soften, censor or summarize anything.
+Reply with the translation only.
+"#;
+
+/// Translates text to English
+#[async_trait]
+pub trait Translator: Send + Sync {
+    /// `text` in English; `language` is its detected ISO 639-1 code
+    async fn translate(&self, text: &str, language: &str) -> Result<String, LlmError>;
+
+    /// Translator name for logs
+    fn name(&self) -> &str;
+}
+
+/// Thread-safe reference to a translator
+pub type SharedTranslator = Arc<dyn Translator>;
+
+/// English translation of `text`, if it is in another language and translation works
+///
+/// Failures are logged and yield `None`, so callers carry on with the original.
+pub async fn to_english(translator: &dyn Translator, text: &str, language: Option<&str>) -> Option<String> {
+    let language = language.filter(|_| !is_english(language))?;
+    match translator.translate(text, language).await {
+        Ok(translated) if !translated.trim().is_empty() => Some(translated.trim().to_string()),
+        Ok(_) => None,
+        Err(e) => {
+            warn!("{} could not translate {} text: {}", translator.name(), language, e);
+            None
+        }
+    }
+}
+
+/// Translation with the run's LLM backend
+pub struct LlmTranslator {
+    backend: SharedBackend,
+}
+
+impl LlmTranslator {
+    pub fn new(backend: SharedBackend) -> Self {
+        Self { backend }
+    }
+}
+
+#[async_trait]
+impl Translator for LlmTranslator {
+    async fn translate(&self, text: &str, language: &str) -> Result<String, LlmError> {
+        let system = TRANSLATOR_SYSTEM_PROMPT.replace("{language}", language);
+        self.backend.generate(&system, text).await
+    }
+
+    fn name(&self) -> &str {
+        self.backend.model_name()
+    }
+}
+
+/// LibreTranslate-compatible translation API
+pub struct LibreTranslator {
+    client: reqwest::Client,
+    endpoint: String,
+    api_key: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct LibreResponse {
+    #[serde(rename = "translatedText")]
+    translated_text: String,
+}
+
+impl LibreTranslator {
+    /// Translator for the API at `url` (its base URL or its `/translate` endpoint)
+    pub fn new(url: &str, api_key: Option<String>) -> Self {
+        let url = url.trim_end_matches('/');
+        let endpoint = if url.ends_with("/translate") {
+            url.to_string()
+        } else {
+            format!("{}/translate", url)
+        };
+        Self {
+            client: reqwest::Client::new(),
+            endpoint,
+            api_key,
+        }
+    }
+}
+
+#[async_trait]
+impl Translator for LibreTranslator {
+    async fn translate(&self, text: &str, language: &str) -> Result<String, LlmError> {
+        let mut body = serde_json::json!({
+            "q": text,
+            "source": language,
+            "target": "en",
+            "format": "text",
+        });
+        if let Some(key) = &self.api_key {
+            body["api_key"] = serde_json::Value::String(key.clone());
+        }
+
+        let response = self
+            .client
+            .post(&self.endpoint)
+            .json(&body)
+            .send()
+            .await
+            .map_err(|e| LlmError::Api(e.to_string()))?;
+        if response.status().as_u16() == 429 {
+            return Err(LlmError::RateLimited);
+        }
+        if !response.status().is_success() {
+            let status = response.status();
+            let text = response.text().await.unwrap_or_default();
+            return Err(LlmError::Api(format!("{}: {}", status, text)));
+        }
+        let translated: LibreResponse = response.json().await.map_err(|e| LlmError::Api(e.to_string()))?;
+        Ok(translated.translated_text)
+    }
+
+    fn name(&self) -> &str {
+        &self.endpoint
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::LlmBackend;
+
+    /// Echoes the language it was asked to translate from
+    struct Echo;
+
+    #[async_trait]
+    impl LlmBackend for Echo {
+        async fn generate(&self, system: &str, user: &str) -> Result<String, LlmError> {
+            let language = if system.contains("\"ru\"") { "ru" } else { "??" };
+            Ok(format!("[{}] {}", language, user))
+        }
+
+        fn model_name(&self) -> &str {
+            "echo"
+        }
+    }
+
+    #[tokio::test]
+    async fn test_translates_only_other_languages() {
+        let translator = LlmTranslator::new(Arc::new(Echo));
+        assert_eq!(to_english(&translator, "Продаю базы", Some("ru")).await.as_deref(), Some("[ru] Продаю базы"));
+        assert_eq!(to_english(&translator, "Selling databases", Some("en")).await, None);
+        assert_eq!(to_english(&translator, "???", None).await, None);
+
+        assert_eq!(LibreTranslator::new("http://localhost:5000/", None).endpoint, "http://localhost:5000/translate");
+        assert_eq!(LibreTranslator::new("https://lt.example/translate", None).endpoint, "https://lt.example/translate");
+    }
+}
@@ -0,0 +1,147 @@
+//! Claim-based work sharing
+//!
+//! Agents of one role run in turn against the same field, so without
+//! coordination every scraper senses the same `FilteredResult`s in a tick
+//! and fetches them all before any is marked done. Agents given one
+//! [`WorkQueue`] instead claim each task before starting it: a task goes to
+//! the first agent that claims it, and the others move on to the next
+//! unclaimed one, so N agents divide the work between them.
+//!
+//! Every claim is also made on the field ([`Field::claim_task`]), and
+//! agents skip tasks the field shows held by someone else, so claims made
+//! outside this queue (by a replica or another process sharing the field)
+//! are honoured too. Tasks are keyed by
+//! [`investigation_task`](robin_core::investigation_task), so each
+//! investigation claims its own copy of a shared URL. A queue claim the
+//! field turns down is released, and like field claims, queue claims
+//! expire after [`CLAIM_TTL`] seconds so the tasks of an agent that died
+//! are free again.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use robin_core::{Field, CLAIM_TTL};
+
+/// Tasks claimed by the agents of one role
+///
+/// Cloning is cheap and all clones share the same claims.
+#[derive(Debug, Clone)]
+pub struct WorkQueue {
+    /// Claimer of each claimed task, and when it claimed it
+    claims: Arc<Mutex<HashMap<String, (String, Instant)>>>,
+    ttl: Duration,
+}
+
+impl Default for WorkQueue {
+    fn default() -> Self {
+        Self {
+            claims: Arc::default(),
+            ttl: Duration::from_secs_f64(CLAIM_TTL),
+        }
+    }
+}
+
+impl WorkQueue {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Let claims lapse after `ttl` instead of [`CLAIM_TTL`]
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// Claim `task_id` for `claimer`; false if another agent holds it
+    pub fn claim(&self, task_id: &str, claimer: &str) -> bool {
+        let mut claims = self.claims.lock().unwrap();
+        let now = Instant::now();
+        claims.retain(|_, (_, at)| now.duration_since(*at) < self.ttl);
+        claims
+            .entry(task_id.to_string())
+            .or_insert_with(|| (claimer.to_string(), now))
+            .0
+            == claimer
+    }
+
+    /// Give up `claimer`'s claim on `task_id`, e.g. when the field turned it down
+    pub fn release(&self, task_id: &str, claimer: &str) {
+        let mut claims = self.claims.lock().unwrap();
+        if claims.get(task_id).is_some_and(|(holder, _)| holder == claimer) {
+            claims.remove(task_id);
+        }
+    }
+
+    /// Agent holding `task_id`, if any
+    pub fn claimer(&self, task_id: &str) -> Option<String> {
+        self.claims
+            .lock()
+            .unwrap()
+            .get(task_id)
+            .filter(|(_, at)| at.elapsed() < self.ttl)
+            .map(|(claimer, _)| claimer.clone())
+    }
+
+    /// Tasks currently claimed
+    pub fn len(&self) -> usize {
+        self.claims
+            .lock()
+            .unwrap()
+            .values()
+            .filter(|(_, at)| at.elapsed() < self.ttl)
+            .count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Tasks the field shows held by agents other than `agent_id`
+pub fn claimed_by_others<'a>(field: &'a Field, agent_id: &str) -> HashSet<&'a str> {
+    field
+        .task_holders()
+        .into_iter()
+        .filter(|(_, holder)| *holder != agent_id)
+        .map(|(task, _)| task)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_first_claim_wins() {
+        let queue = WorkQueue::new();
+        let shared = queue.clone();
+        assert!(queue.claim("http://a.onion/", "scraper-1"));
+        assert!(queue.claim("http://a.onion/", "scraper-1"));
+        assert!(!shared.claim("http://a.onion/", "scraper-2"));
+        assert!(shared.claim("http://b.onion/", "scraper-2"));
+        assert_eq!(queue.claimer("http://b.onion/").as_deref(), Some("scraper-2"));
+        assert_eq!(queue.len(), 2);
+
+        // Only the holder can release a claim
+        shared.release("http://a.onion/", "scraper-2");
+        assert_eq!(queue.claimer("http://a.onion/").as_deref(), Some("scraper-1"));
+        queue.release("http://a.onion/", "scraper-1");
+        assert!(shared.claim("http://a.onion/", "scraper-2"));
+
+        let mut field = Field::new();
+        assert!(field.claim_task("http://a.onion/", "scraper-1", 0.9).is_accepted());
+        assert!(claimed_by_others(&field, "scraper-2").contains("http://a.onion/"));
+        assert!(claimed_by_others(&field, "scraper-1").is_empty());
+    }
+
+    #[test]
+    fn test_claims_expire() {
+        let queue = WorkQueue::new().with_ttl(Duration::from_millis(10));
+        assert!(queue.claim("http://a.onion/", "scraper-1"));
+        std::thread::sleep(Duration::from_millis(20));
+        assert_eq!(queue.claimer("http://a.onion/"), None);
+        assert!(queue.is_empty());
+        assert!(queue.claim("http://a.onion/", "scraper-2"));
+    }
+}
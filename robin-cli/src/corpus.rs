@@ -0,0 +1,319 @@
+//! Deduplicated text corpus export
+//!
+//! Builds a JSON-lines corpus from stored collections, for teams training
+//! classifiers or fine-tuning models on their own monitoring domain. Inputs
+//! are vector indexes written with `--vectors` (scraped pages and pastes
+//! with their source metadata) and raw content files under the data
+//! directory's `raw/` (see [`crate::retention`]).
+//!
+//! Each document is cleaned (markup stripped, whitespace normalized), dropped
+//! if shorter than a minimum, deduplicated exactly and by near-duplicate
+//! word-shingle overlap, and annotated with the artifacts and threat actors found in it.
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashSet;
+use std::hash::{Hash, Hasher};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use regex::Regex;
+use robin_core::{extract_artifacts, ActorDatabase, ArtifactType, AtRestKey, CollectionKind, VectorStore};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+use crate::privacy::read_text;
+use crate::retention::{walk_files, DataClass};
+
+static SCRIPT_OR_STYLE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?is)<(script|style)\b.*?</(script|style)>").unwrap());
+static TAG: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(?s)<[^>]*>").unwrap());
+
+/// Words per shingle for near-duplicate detection
+const SHINGLE_WORDS: usize = 3;
+
+/// Corpus build options
+#[derive(Debug, Clone)]
+pub struct CorpusOptions {
+    /// Documents shorter than this (in characters, after cleaning) are dropped
+    pub min_chars: usize,
+    /// Documents sharing at least this fraction of word shingles with a kept one are dropped
+    pub near_duplicate_similarity: f64,
+}
+
+impl Default for CorpusOptions {
+    fn default() -> Self {
+        Self {
+            min_chars: 200,
+            near_duplicate_similarity: 0.8,
+        }
+    }
+}
+
+/// An artifact found in a corpus document
+#[derive(Debug, Clone, Serialize)]
+pub struct Annotation {
+    #[serde(rename = "type")]
+    pub artifact_type: ArtifactType,
+    pub value: String,
+    pub confidence: f64,
+}
+
+/// One corpus line
+#[derive(Debug, Clone, Serialize)]
+pub struct CorpusDocument {
+    /// SHA-256 of the cleaned text
+    pub id: String,
+    /// URL, or path relative to the data directory for raw files
+    pub source: String,
+    /// "scraped", "paste" or "raw"
+    pub kind: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub collected_at: Option<DateTime<Utc>>,
+    pub text: String,
+    pub artifacts: Vec<Annotation>,
+}
+
+/// What a corpus build kept and dropped
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CorpusStats {
+    pub read: usize,
+    pub too_short: usize,
+    pub exact_duplicates: usize,
+    pub near_duplicates: usize,
+    pub written: usize,
+    /// Encrypted raw files skipped for lack of a key
+    pub skipped_encrypted: usize,
+}
+
+/// A document before cleaning
+struct Candidate {
+    source: String,
+    kind: &'static str,
+    title: Option<String>,
+    collected_at: Option<DateTime<Utc>>,
+    text: String,
+}
+
+/// Build the corpus from `vector_files` and `data_dir`'s raw content into `output`
+pub fn build_corpus(
+    data_dir: Option<&Path>,
+    vector_files: &[PathBuf],
+    actors: &ActorDatabase,
+    key: Option<&AtRestKey>,
+    options: &CorpusOptions,
+    output: &Path,
+) -> Result<CorpusStats> {
+    let mut stats = CorpusStats::default();
+    let mut candidates = Vec::new();
+
+    for path in vector_files {
+        let store = VectorStore::load(path, key).with_context(|| format!("failed to load {}", path.display()))?;
+        candidates.extend(store.entries().iter().map(|entry| Candidate {
+            source: entry.url.clone(),
+            kind: match entry.kind {
+                CollectionKind::Scraped => "scraped",
+                CollectionKind::Paste => "paste",
+            },
+            title: Some(entry.title.clone()).filter(|t| !t.is_empty()),
+            collected_at: Some(entry.collected_at),
+            text: entry.text.clone(),
+        }));
+    }
+
+    if let Some(dir) = data_dir.filter(|d| d.join("raw").is_dir()) {
+        for path in walk_files(&dir.join("raw"))? {
+            let relative = path.strip_prefix(dir).unwrap_or(&path);
+            if DataClass::of(relative) != Some(DataClass::RawContent) {
+                continue;
+            }
+            if key.is_none() && robin_core::is_sealed(&std::fs::read(&path)?) {
+                stats.skipped_encrypted += 1;
+                continue;
+            }
+            let Some((text, _)) = read_text(&path, key)? else {
+                continue;
+            };
+            let collected_at = std::fs::metadata(&path)?.modified().ok().map(DateTime::<Utc>::from);
+            candidates.push(Candidate {
+                source: relative.display().to_string(),
+                kind: "raw",
+                title: None,
+                collected_at,
+                text,
+            });
+        }
+    }
+
+    let mut out = std::io::BufWriter::new(
+        std::fs::File::create(output).with_context(|| format!("failed to create {}", output.display()))?,
+    );
+    let mut seen_ids = HashSet::new();
+    let mut kept_shingles: Vec<HashSet<u64>> = Vec::new();
+    for candidate in candidates {
+        stats.read += 1;
+        let text = clean_text(&candidate.text);
+        if text.chars().count() < options.min_chars {
+            stats.too_short += 1;
+            continue;
+        }
+        let id: String = Sha256::digest(text.to_lowercase().as_bytes()).iter().map(|b| format!("{:02x}", b)).collect();
+        if !seen_ids.insert(id.clone()) {
+            stats.exact_duplicates += 1;
+            continue;
+        }
+        let shingles = shingles(&text);
+        if kept_shingles
+            .iter()
+            .any(|kept| jaccard(kept, &shingles) >= options.near_duplicate_similarity)
+        {
+            stats.near_duplicates += 1;
+            continue;
+        }
+        kept_shingles.push(shingles);
+
+        let mut artifacts = extract_artifacts(&text, None);
+        artifacts.extend(actors.extract_actors(&text, None));
+        let document = CorpusDocument {
+            id,
+            source: candidate.source,
+            kind: candidate.kind,
+            title: candidate.title,
+            collected_at: candidate.collected_at,
+            artifacts: artifacts
+                .into_iter()
+                .map(|a| Annotation {
+                    artifact_type: a.artifact_type,
+                    value: a.value,
+                    confidence: a.confidence,
+                })
+                .collect(),
+            text,
+        };
+        writeln!(out, "{}", serde_json::to_string(&document)?)?;
+        stats.written += 1;
+    }
+    out.flush()?;
+    Ok(stats)
+}
+
+/// Strip markup and normalize whitespace, keeping paragraph breaks
+pub fn clean_text(text: &str) -> String {
+    let text = if text.contains("</") {
+        let text = SCRIPT_OR_STYLE.replace_all(text, " ");
+        let text = TAG.replace_all(&text, " ");
+        decode_entities(&text)
+    } else {
+        text.to_string()
+    };
+    text.lines()
+        .map(|line| line.split_whitespace().collect::<Vec<_>>().join(" "))
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn decode_entities(text: &str) -> String {
+    text.replace("&nbsp;", " ")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&")
+}
+
+/// Hashes of every run of [`SHINGLE_WORDS`] lowercased words
+fn shingles(text: &str) -> HashSet<u64> {
+    let words: Vec<String> = text.split_whitespace().map(str::to_lowercase).collect();
+    words
+        .windows(SHINGLE_WORDS.min(words.len()).max(1))
+        .map(|shingle| {
+            let mut hasher = DefaultHasher::new();
+            shingle.hash(&mut hasher);
+            hasher.finish()
+        })
+        .collect()
+}
+
+fn jaccard(a: &HashSet<u64>, b: &HashSet<u64>) -> f64 {
+    let union = a.union(b).count();
+    if union == 0 {
+        return 1.0;
+    }
+    a.intersection(b).count() as f64 / union as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use robin_core::{hash_embedding, VectorEntry, HASHING_MODEL};
+
+    const POST: &str = "LockBit affiliates posted a new victim on the leak site today. The listing includes \
+        a sample of stolen HR documents and a countdown of seven days. Payment is requested to \
+        bc1qxy2kgdygjrsqtzq2n0yrf2493p83kkfjhx0wlh before the files are published for everyone.";
+
+    #[test]
+    fn test_clean_text_strips_markup() {
+        let html = "<html><style>p{}</style><body><p>Hello&nbsp;  <b>world</b></p>\n\n<script>x()</script><p>a &amp; b</p></body></html>";
+        assert_eq!(clean_text(html), "Hello world\na & b");
+        assert_eq!(clean_text("  plain   text \n\n more "), "plain text\nmore");
+    }
+
+    #[test]
+    fn test_build_corpus_deduplicates_and_annotates() {
+        let dir = std::env::temp_dir().join(format!("robin-corpus-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("raw")).unwrap();
+        // Same post as the indexed page, wrapped in markup
+        std::fs::write(dir.join("raw/page.html"), format!("<html><body><p>{}</p></body></html>", POST)).unwrap();
+        std::fs::write(dir.join("raw/short.txt"), "too short").unwrap();
+
+        let mut store = VectorStore::default();
+        for (url, text) in [
+            ("http://leak.onion/post/1", POST.to_string()),
+            // Near duplicate: one word changed
+            ("http://mirror.onion/post/1", POST.replace("today", "tonight")),
+            ("http://forum.onion/t/2", "Selling fresh combo lists, ".repeat(12)),
+        ] {
+            let entry = VectorEntry::new(url, CollectionKind::Scraped, "post", &text, hash_embedding(&text));
+            store.insert(HASHING_MODEL, entry).unwrap();
+        }
+        let index = dir.join("index.json");
+        store.save(&index, None).unwrap();
+
+        let output = dir.join("corpus.jsonl");
+        let stats = build_corpus(
+            Some(&dir),
+            &[index],
+            &ActorDatabase::embedded(),
+            None,
+            &CorpusOptions::default(),
+            &output,
+        )
+        .unwrap();
+        assert_eq!(
+            stats,
+            CorpusStats {
+                read: 5,
+                too_short: 1,
+                exact_duplicates: 1,
+                near_duplicates: 1,
+                written: 2,
+                skipped_encrypted: 0,
+            }
+        );
+
+        let lines: Vec<serde_json::Value> = std::fs::read_to_string(&output)
+            .unwrap()
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+        assert_eq!(lines[0]["source"], "http://leak.onion/post/1");
+        let types: Vec<&str> = lines[0]["artifacts"].as_array().unwrap().iter().map(|a| a["type"].as_str().unwrap()).collect();
+        assert!(types.contains(&"bitcoin") && types.contains(&"threat_actor"), "{:?}", types);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
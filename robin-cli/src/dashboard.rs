@@ -0,0 +1,143 @@
+//! Terminal rendering of live field statistics
+//!
+//! `robin-smesh stats` polls a running query's control socket and draws
+//! signal counts by type, an intensity histogram, per-agent activity
+//! sparklines, Tor bandwidth per agent and the agent pipeline from the
+//! status response.
+
+use std::fmt::Write;
+
+use robin_runtime::ControlResponse;
+
+/// Width of the longest bar, in characters
+const BAR_WIDTH: usize = 40;
+
+const SPARKS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// `count` scaled against `max` as a bar of at most `width` blocks
+fn bar(count: usize, max: usize, width: usize) -> String {
+    if max == 0 || count == 0 {
+        return String::new();
+    }
+    let len = (count * width).div_ceil(max).max(1);
+    "█".repeat(len.min(width))
+}
+
+/// Sparkline of `values`, scaled to their maximum (idle ticks render as `·`)
+fn sparkline(values: &[u32]) -> String {
+    let max = values.iter().copied().max().unwrap_or(0);
+    values
+        .iter()
+        .map(|&v| match v {
+            0 => '·',
+            v => SPARKS[((v as usize * SPARKS.len()).div_ceil(max as usize) - 1).min(SPARKS.len() - 1)],
+        })
+        .collect()
+}
+
+/// Render a status response as a text dashboard
+pub fn render(status: &ControlResponse) -> String {
+    let mut out = String::new();
+    let state = if status.paused { "paused" } else { "running" };
+    let elapsed = status.elapsed_secs;
+    let _ = writeln!(out, "Robin×SMESH field — {} — {}m{:02}s", state, elapsed / 60, elapsed % 60);
+
+    let Some(stats) = &status.stats else {
+        out.push_str("(no field statistics in response)\n");
+        return out;
+    };
+    let _ = write!(
+        out,
+        "{} active signals · avg intensity {:.2} · {} reinforcements",
+        stats.active_signals, stats.avg_intensity, stats.total_reinforcements
+    );
+    if stats.evicted > 0 {
+        let _ = write!(out, " · {} evicted", stats.evicted);
+    }
+    out.push_str("\n\n");
+
+    out.push_str("Signals by type\n");
+    let max = stats.by_kind.values().copied().max().unwrap_or(0);
+    for (kind, count) in &stats.by_kind {
+        let _ = writeln!(out, "  {:<20} {:<width$} {}", kind.as_str(), bar(*count, max, BAR_WIDTH), count, width = BAR_WIDTH);
+    }
+
+    out.push_str("\nIntensity\n");
+    let buckets = stats.intensity_histogram.len().max(1);
+    let max = stats.intensity_histogram.iter().copied().max().unwrap_or(0);
+    for (i, count) in stats.intensity_histogram.iter().enumerate() {
+        let low = i as f64 / buckets as f64;
+        let high = (i + 1) as f64 / buckets as f64;
+        let _ = writeln!(out, "  {:.1}-{:.1}  {:<width$} {}", low, high, bar(*count, max, BAR_WIDTH), count, width = BAR_WIDTH);
+    }
+
+    if let Some(activity) = &status.activity {
+        out.push_str("\nAgent activity (signals per tick)\n");
+        for (agent, ticks) in activity {
+            let total: u32 = ticks.iter().sum();
+            let _ = writeln!(out, "  {:<20} {:<width$} {}", agent, sparkline(ticks), total, width = BAR_WIDTH);
+        }
+    }
+
+    if let Some(bandwidth) = &status.bandwidth {
+        let mb = |bytes: u64| bytes as f64 / 1_048_576.0;
+        let _ = write!(out, "\nTor bandwidth: {:.1} MB sent, {:.1} MB received", mb(bandwidth.total.sent), mb(bandwidth.total.received));
+        match bandwidth.limit {
+            Some(limit) => {
+                let _ = writeln!(out, " ({:.0}% of {:.0} MB cap)", bandwidth.total.total() as f64 * 100.0 / limit.max(1) as f64, mb(limit));
+            }
+            None => out.push('\n'),
+        }
+        for (agent, transfer) in &bandwidth.agents {
+            let _ = writeln!(out, "  {:<20} {:>8.1} MB", agent, mb(transfer.total()));
+        }
+    }
+
+    if let Some(pipeline) = &status.pipeline {
+        out.push_str("\nPipeline (senses → emits)\n");
+        for line in pipeline.render().lines() {
+            let _ = writeln!(out, "  {}", line);
+        }
+        for warning in pipeline.warnings() {
+            let _ = writeln!(out, "  ⚠ {}", warning);
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bars_and_sparklines() {
+        assert_eq!(bar(0, 10, 40), "");
+        assert_eq!(bar(10, 10, 40).chars().count(), 40);
+        assert_eq!(bar(1, 1000, 40), "█");
+        assert_eq!(sparkline(&[0, 1, 4, 8]), "·▁▄█");
+        assert_eq!(sparkline(&[]), "");
+    }
+
+    #[test]
+    fn test_render_status() {
+        let json = r#"{
+            "ok": true, "message": "running", "paused": false, "elapsed_secs": 83,
+            "stats": {"active_signals": 3, "total_intensity": 2.4, "avg_intensity": 0.8,
+                      "total_reinforcements": 1, "history_size": 3,
+                      "by_kind": {"user_query": 1, "raw_result": 2},
+                      "intensity_histogram": [0, 0, 0, 0, 0, 0, 0, 1, 2, 0]},
+            "activity": {"crawler-1": [0, 2, 0]},
+            "pipeline": {"stages": [{"agent_id": "paste-monitor-1", "agent_type": "paste_monitor",
+                                     "senses": ["refined_query"], "emits": ["paste_content"]}]}
+        }"#;
+        let status: ControlResponse = serde_json::from_str(json).unwrap();
+        let out = render(&status);
+
+        assert!(out.contains("running — 1m23s"));
+        assert!(out.lines().any(|l| l.contains("raw_result") && l.ends_with(" 2")));
+        assert!(out.contains("0.8-0.9"));
+        assert!(out.lines().any(|l| l.contains("crawler-1") && l.contains("·█·")));
+        assert!(out.contains("  paste-monitor-1  refined_query → paste_content"));
+        assert!(out.contains("⚠ nothing consumes paste_content (emitted by paste-monitor-1)"));
+    }
+}
@@ -0,0 +1,329 @@
+//! Pre-run cost and coverage estimates
+//!
+//! `robin-smesh estimate` predicts what a `query` run with the same options
+//! would consume: LLM calls and tokens per agent, Tor and third-party API
+//! requests, and wall-clock time. Figures follow the swarm's fixed limits
+//! (filter batch size, scrape cap, specialist count) and typical Tor and LLM
+//! latencies, so they are planning numbers rather than a bill.
+
+use std::fmt::Write;
+
+use robin_agents::{ModelInfo, PasteMonitorConfig, PersonaRegistry};
+
+/// Results a search engine typically returns for one query
+const RESULTS_PER_ENGINE: usize = 15;
+/// Raw results the filter ranks per call
+const FILTER_BATCH: usize = 50;
+/// Results the filter keeps (and scrapers fetch)
+const FILTER_KEEP: usize = 20;
+/// Lifetime of raw results; the filter re-ranks them on every tick until they expire
+const RAW_RESULT_TTL_SECS: f64 = 90.0;
+/// Swarm tick interval
+const TICK_SECS: f64 = 0.5;
+/// Pages and characters per page the analyst reads
+const ANALYST_PAGES: usize = 10;
+const ANALYST_PAGE_CHARS: usize = 1500;
+/// Artifacts listed to the analyst, and the typical number a run finds
+const ANALYST_ARTIFACTS: usize = 50;
+const EXPECTED_ARTIFACTS: usize = 15;
+const EXPECTED_WALLETS: usize = 3;
+/// Concurrent requests per crawler or scraper
+const AGENT_CONCURRENCY: usize = 3;
+/// Typical round trip of one request over Tor
+const TOR_LATENCY_SECS: f64 = 8.0;
+/// Typical LLM time to first token, and generation speed
+const LLM_LATENCY_SECS: f64 = 1.5;
+const OUTPUT_TOKENS_PER_SEC: f64 = 50.0;
+
+/// Options of the run being estimated
+#[derive(Debug, Clone)]
+pub struct RunPlan {
+    pub query: String,
+    pub model: String,
+    /// The model's limits and prices (None for models the registry doesn't know)
+    pub model_info: Option<ModelInfo>,
+    /// Search engines crawled (Tor, plus I2P when enabled)
+    pub engines: usize,
+    pub crawlers: usize,
+    pub scrapers: usize,
+    pub specialists: bool,
+    pub enrich: bool,
+    pub blockchain: bool,
+    pub pastes: bool,
+    pub timeout_secs: u64,
+}
+
+/// LLM usage of one agent
+#[derive(Debug, Clone, PartialEq)]
+pub struct LlmStage {
+    pub agent: &'static str,
+    pub calls: usize,
+    pub input_tokens: usize,
+    pub output_tokens: usize,
+}
+
+/// Predicted consumption of a run
+#[derive(Debug, Clone)]
+pub struct Estimate {
+    pub model: String,
+    pub model_info: Option<ModelInfo>,
+    pub stages: Vec<LlmStage>,
+    /// Most LLM requests in flight at once
+    pub peak_concurrency: usize,
+    pub tor_requests: usize,
+    /// Third-party API requests by service
+    pub api_requests: Vec<(&'static str, usize)>,
+    pub runtime_secs: u64,
+    pub timeout_secs: u64,
+}
+
+impl Estimate {
+    pub fn total_calls(&self) -> usize {
+        self.stages.iter().map(|s| s.calls).sum()
+    }
+
+    pub fn total_input_tokens(&self) -> usize {
+        self.stages.iter().map(|s| s.input_tokens).sum()
+    }
+
+    pub fn total_output_tokens(&self) -> usize {
+        self.stages.iter().map(|s| s.output_tokens).sum()
+    }
+
+    /// Estimated LLM cost in USD (None for models without a known price)
+    pub fn cost_usd(&self) -> Option<f64> {
+        let info = self.model_info.as_ref()?;
+        Some(info.cost_usd(self.total_input_tokens(), self.total_output_tokens()))
+    }
+
+    /// Tokens (input plus output) of the largest single LLM request
+    pub fn largest_request(&self) -> usize {
+        self.stages
+            .iter()
+            .filter(|s| s.calls > 0)
+            .map(|s| (s.input_tokens + s.output_tokens) / s.calls)
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Whether some request would overflow the model's context window
+    pub fn exceeds_context(&self) -> bool {
+        self.model_info
+            .as_ref()
+            .is_some_and(|info| self.largest_request() > info.context_window as usize)
+    }
+
+    /// Whether the run is expected to hit `--timeout` before the report
+    pub fn exceeds_timeout(&self) -> bool {
+        self.timeout_secs > 0 && self.runtime_secs > self.timeout_secs
+    }
+}
+
+/// Rough token count of `chars` characters of English text
+fn tokens(chars: usize) -> usize {
+    chars.div_ceil(4)
+}
+
+/// Seconds for one LLM call producing `output` tokens
+fn llm_secs(output: usize) -> f64 {
+    LLM_LATENCY_SECS + output as f64 / OUTPUT_TOKENS_PER_SEC
+}
+
+fn stage(agent: &'static str, calls: usize, input: usize, output: usize) -> LlmStage {
+    LlmStage {
+        agent,
+        calls,
+        input_tokens: calls * input,
+        output_tokens: calls * output,
+    }
+}
+
+/// Estimate a run of `plan`
+pub fn estimate(plan: &RunPlan) -> Estimate {
+    let personas = PersonaRegistry::load_embedded();
+    let prompt = |id: &str| personas.get(id).map_or(0, |p| tokens(p.system_prompt().len()));
+    let lead_prompt = personas.lead_analyst().map_or(0, |p| tokens(p.system_prompt().len()));
+    let specialist_prompts: Vec<usize> = personas
+        .specialist_analysts()
+        .iter()
+        .map(|p| tokens(p.system_prompt().len()))
+        .collect();
+
+    let raw_results = plan.engines * RESULTS_PER_ENGINE;
+    let pages = raw_results.min(FILTER_KEEP);
+    let mut stages = vec![stage("refiner", 1, prompt("refiner") + tokens(plan.query.len()), 40)];
+
+    // Filter: ~20 tokens per listed result, re-run each tick while results live
+    let filter_output = 60;
+    let filter_calls = if raw_results == 0 {
+        0
+    } else {
+        (RAW_RESULT_TTL_SECS / (TICK_SECS + llm_secs(filter_output))).ceil() as usize
+    };
+    let filter_input = prompt("filter") + raw_results.min(FILTER_BATCH) * 20;
+    stages.push(stage("filter", filter_calls, filter_input, filter_output));
+
+    let content = tokens(pages.min(ANALYST_PAGES) * ANALYST_PAGE_CHARS) + ANALYST_ARTIFACTS.min(EXPECTED_ARTIFACTS) * 12;
+    let report_output = 1500;
+    let (analyst_secs, peak_concurrency) = if plan.specialists && !specialist_prompts.is_empty() {
+        let specialist_output = 1000;
+        let count = specialist_prompts.len();
+        let avg_prompt = specialist_prompts.iter().sum::<usize>() / count;
+        stages.push(stage("specialists", count, avg_prompt + content, specialist_output));
+        stages.push(stage(
+            "lead analyst",
+            1,
+            lead_prompt + tokens(4000) + count * specialist_output,
+            report_output,
+        ));
+        (llm_secs(specialist_output) + llm_secs(report_output), count)
+    } else {
+        stages.push(stage("analyst", 1, lead_prompt + content, report_output));
+        (llm_secs(report_output), 1)
+    };
+
+    // Every crawler crawls every engine
+    let tor_requests = plan.crawlers * plan.engines + pages;
+    let crawl_secs = plan.engines.div_ceil(AGENT_CONCURRENCY) as f64 * TOR_LATENCY_SECS;
+    let scrape_secs =
+        pages.div_ceil((plan.scrapers * AGENT_CONCURRENCY).max(1)) as f64 * TOR_LATENCY_SECS;
+    let runtime = llm_secs(40) + crawl_secs + llm_secs(filter_output) + scrape_secs + analyst_secs;
+
+    let mut api_requests = Vec::new();
+    if plan.enrich {
+        api_requests.push(("GitHub code search", EXPECTED_ARTIFACTS));
+        api_requests.push(("Brave search", EXPECTED_ARTIFACTS));
+    }
+    if plan.blockchain {
+        api_requests.push(("Blockstream / Etherscan", EXPECTED_WALLETS * 2));
+    }
+    if plan.pastes {
+        // Search or listing per site, then content fetches
+        let config = PasteMonitorConfig::default();
+        api_requests.push(("paste sites", config.sites.len() * (1 + config.max_pastes_per_site)));
+    }
+
+    Estimate {
+        model: plan.model.clone(),
+        model_info: plan.model_info.clone(),
+        stages,
+        peak_concurrency,
+        tor_requests,
+        api_requests,
+        runtime_secs: runtime.ceil() as u64,
+        timeout_secs: plan.timeout_secs,
+    }
+}
+
+/// Render an estimate as a text table
+pub fn render(estimate: &Estimate) -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, "Estimate for {}\n", estimate.model);
+    let _ = writeln!(out, "  {:<14} {:>6} {:>12} {:>12}", "LLM stage", "calls", "input tok", "output tok");
+    for s in &estimate.stages {
+        let _ = writeln!(out, "  {:<14} {:>6} {:>12} {:>12}", s.agent, s.calls, s.input_tokens, s.output_tokens);
+    }
+    let _ = writeln!(
+        out,
+        "  {:<14} {:>6} {:>12} {:>12}\n",
+        "total",
+        estimate.total_calls(),
+        estimate.total_input_tokens(),
+        estimate.total_output_tokens()
+    );
+
+    match estimate.cost_usd() {
+        Some(cost) => {
+            let _ = writeln!(out, "LLM cost:      ~${:.2}", cost);
+        }
+        None => out.push_str("LLM cost:      unknown (no price for this model)\n"),
+    }
+    if let Some(info) = &estimate.model_info {
+        let _ = writeln!(
+            out,
+            "Largest request: ~{} of {} context tokens",
+            estimate.largest_request(),
+            info.context_window
+        );
+    }
+    let _ = writeln!(out, "Peak LLM concurrency: {} request(s)", estimate.peak_concurrency);
+    let _ = writeln!(out, "Tor requests:  ~{}", estimate.tor_requests);
+    if !estimate.api_requests.is_empty() {
+        out.push_str("External APIs:\n");
+        for (service, count) in &estimate.api_requests {
+            let _ = writeln!(out, "  {}: ~{} request(s)", service, count);
+        }
+    }
+    let _ = writeln!(out, "Runtime:       ~{}m{:02}s", estimate.runtime_secs / 60, estimate.runtime_secs % 60);
+    if estimate.exceeds_context() {
+        out.push_str("\n⚠️  Some requests are larger than the model's context window and will be rejected\n");
+    }
+    if estimate.exceeds_timeout() {
+        let _ = writeln!(out, "\n⚠️  Longer than --timeout {}s: the run will end before the report", estimate.timeout_secs);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use robin_agents::ModelRegistry;
+
+    fn plan() -> RunPlan {
+        RunPlan {
+            query: "ransomware payments".to_string(),
+            model: "claude-sonnet-4-20250514".to_string(),
+            model_info: ModelRegistry::embedded().lookup("claude-sonnet-4-20250514").cloned(),
+            engines: 16,
+            crawlers: 2,
+            scrapers: 3,
+            specialists: false,
+            enrich: false,
+            blockchain: false,
+            pastes: false,
+            timeout_secs: 300,
+        }
+    }
+
+    #[test]
+    fn test_specialists_add_calls_and_cost() {
+        let single = estimate(&plan());
+        let multi = estimate(&RunPlan { specialists: true, ..plan() });
+        let specialists = PersonaRegistry::load_embedded().specialist_analysts().len();
+
+        assert_eq!(multi.total_calls(), single.total_calls() + specialists);
+        assert_eq!(multi.peak_concurrency, specialists);
+        assert!(multi.cost_usd().unwrap() > single.cost_usd().unwrap());
+        assert_eq!(single.tor_requests, 2 * 16 + FILTER_KEEP);
+    }
+
+    #[test]
+    fn test_unknown_model_and_timeout() {
+        let estimate = estimate(&RunPlan {
+            model: "local-llama".to_string(),
+            model_info: None,
+            timeout_secs: 10,
+            pastes: true,
+            ..plan()
+        });
+        assert_eq!(estimate.cost_usd(), None);
+        assert!(estimate.exceeds_timeout());
+        let out = render(&estimate);
+        assert!(out.contains("unknown (no price"));
+        assert!(out.contains("paste sites:"));
+    }
+
+    #[test]
+    fn test_context_window() {
+        let fits = estimate(&plan());
+        assert!(fits.largest_request() > 0 && !fits.exceeds_context());
+
+        let small = ModelInfo {
+            context_window: 1000,
+            ..plan().model_info.unwrap()
+        };
+        let overflow = estimate(&RunPlan { model_info: Some(small), ..plan() });
+        assert!(overflow.exceeds_context());
+        assert!(render(&overflow).contains("context window"));
+    }
+}
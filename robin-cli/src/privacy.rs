@@ -0,0 +1,450 @@
+//! Subject data export and deletion
+//!
+//! Monitoring can surface personal data about employees or customers. These
+//! helpers find every stored file in the data directory (see
+//! [`crate::retention`] for the layout) and in the stores a query writes
+//! ([`Stores`]) that references a personal selector such as an email address
+//! or name, and either export the matching lines or redact them in place.
+//! Encrypted files are searched when a key is given.
+//!
+//! WARC archives aren't searched: redacting a record would break its length
+//! and digest. Purge them instead.
+//!
+//! The selector itself is never written to the audit log or the action
+//! report; both record its SHA-256 digest instead.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use regex::Regex;
+use robin_core::{is_sealed, journal_is_sealed, journal_lines, open, rewrite_journal, seal, write_sealed, AtRestKey};
+use robin_tor::ResponseCache;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+use crate::retention::{append_audit, walk_files, DataClass, Stores};
+
+/// Replacement for redacted selector occurrences
+pub const REDACTION: &str = "[REDACTED]";
+
+/// A line referencing the selector
+#[derive(Debug, Clone, Serialize)]
+pub struct MatchedLine {
+    /// 1-based line number
+    pub line: usize,
+    /// Full line content
+    pub text: String,
+}
+
+/// A stored file referencing the selector
+#[derive(Debug, Clone, Serialize)]
+pub struct SubjectMatch {
+    /// Matching file
+    pub path: PathBuf,
+    /// Data class of the file
+    pub class: DataClass,
+    /// How the file is stored
+    #[serde(skip)]
+    pub stored_as: StoredAs,
+    /// Whether the file is encrypted at rest
+    pub encrypted: bool,
+    /// Number of selector occurrences
+    pub occurrences: usize,
+    /// Lines containing the selector
+    pub lines: Vec<MatchedLine>,
+}
+
+/// How a stored file is read and rewritten
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StoredAs {
+    /// Whole file, plain or sealed
+    File,
+    /// Field journal, sealed line by line
+    Journal,
+    /// Response cache entry, sealed with the cache's stretched key
+    CacheEntry,
+}
+
+impl StoredAs {
+    /// Whether the file at `path` is encrypted at rest
+    fn is_sealed(self, path: &Path) -> Result<bool> {
+        Ok(match self {
+            StoredAs::Journal => journal_is_sealed(path)?,
+            StoredAs::File | StoredAs::CacheEntry => is_sealed(&std::fs::read(path)?),
+        })
+    }
+
+    /// Key the file at `path` is sealed with under `key`
+    fn key(self, path: &Path, key: Option<&AtRestKey>) -> Result<Option<AtRestKey>> {
+        match (self, key) {
+            (StoredAs::CacheEntry, Some(key)) => {
+                let dir = path.parent().unwrap_or(Path::new("."));
+                Ok(ResponseCache::entry_key(dir, key)?)
+            }
+            (_, key) => Ok(key.cloned()),
+        }
+    }
+
+    /// The file's text, and whether it was encrypted (see [`read_text`])
+    fn read(self, path: &Path, key: Option<&AtRestKey>) -> Result<Option<(String, bool)>> {
+        if self != StoredAs::Journal {
+            return read_text(path, self.key(path, key)?.as_ref());
+        }
+        let encrypted = journal_is_sealed(path)?;
+        let lines = journal_lines(path, key).with_context(|| format!("failed to read {}", path.display()))?;
+        Ok(lines.map(|lines| (lines.join("\n"), encrypted)))
+    }
+
+    /// Replace the file's text, sealed again if it was encrypted
+    fn write(self, path: &Path, text: &str, encrypted: bool, key: Option<&AtRestKey>) -> Result<()> {
+        let key = if encrypted { self.key(path, key)? } else { None };
+        let result = match (self, &key) {
+            (StoredAs::Journal, key) => {
+                let lines: Vec<String> = text.lines().map(str::to_string).collect();
+                rewrite_journal(path, &lines, key.as_ref()).map_err(anyhow::Error::from)
+            }
+            (_, Some(key)) => seal(key, text.as_bytes())
+                .map_err(anyhow::Error::from)
+                .and_then(|data| Ok(std::fs::write(path, data)?)),
+            (_, None) => std::fs::write(path, text).map_err(anyhow::Error::from),
+        };
+        result.with_context(|| format!("failed to write {}", path.display()))
+    }
+}
+
+/// Result of searching the data directory for a selector
+#[derive(Debug, Clone, Default)]
+pub struct SubjectScan {
+    /// Files referencing the selector
+    pub matches: Vec<SubjectMatch>,
+    /// Encrypted files that could not be searched (no key given)
+    pub skipped_encrypted: Vec<PathBuf>,
+}
+
+/// Audit log record for one privacy action
+#[derive(Debug, Serialize)]
+struct PrivacyAuditRecord<'a> {
+    timestamp: &'a str,
+    action: &'a str,
+    selector_sha256: &'a str,
+    path: &'a Path,
+    occurrences: usize,
+}
+
+/// SHA-256 of the normalized selector, for records that must not hold it
+pub fn selector_digest(selector: &str) -> String {
+    Sha256::digest(selector.trim().to_lowercase().as_bytes())
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+fn selector_regex(selector: &str) -> Result<Regex> {
+    let selector = selector.trim();
+    if selector.len() < 3 {
+        anyhow::bail!("selector is too short to match safely: '{}'", selector);
+    }
+    Ok(Regex::new(&format!("(?i){}", regex::escape(selector)))?)
+}
+
+/// Read a managed file as text, opening it if it is encrypted
+///
+/// `Ok(None)` means the file can't be searched (encrypted without a key,
+/// or not text).
+pub(crate) fn read_text(path: &Path, key: Option<&AtRestKey>) -> Result<Option<(String, bool)>> {
+    let data = std::fs::read(path).with_context(|| format!("failed to read {}", path.display()))?;
+    let (data, encrypted) = if is_sealed(&data) {
+        let Some(key) = key else {
+            return Ok(None);
+        };
+        let plain = open(key, &data).with_context(|| format!("failed to decrypt {}", path.display()))?;
+        (plain, true)
+    } else {
+        (data, false)
+    };
+    Ok(String::from_utf8(data).ok().map(|text| (text, encrypted)))
+}
+
+/// Every file a subject data request searches, with its class
+fn subject_files(data_dir: &Path, stores: &Stores) -> Result<Vec<(PathBuf, DataClass, StoredAs)>> {
+    let mut files = Vec::new();
+    for path in walk_files(data_dir)? {
+        let relative = path.strip_prefix(data_dir).unwrap_or(&path);
+        if let Some(class) = DataClass::of(relative) {
+            files.push((path, class, StoredAs::File));
+        }
+    }
+    if let Some(dir) = stores.cache_dir.as_ref().filter(|dir| dir.is_dir()) {
+        for path in walk_files(dir)? {
+            if path.extension().is_some_and(|ext| ext == "json") {
+                files.push((path, DataClass::RawContent, StoredAs::CacheEntry));
+            }
+        }
+    }
+    for (path, class) in stores.files() {
+        let stored_as = if stores.field_stores.contains(path) {
+            StoredAs::Journal
+        } else {
+            StoredAs::File
+        };
+        files.push((path.clone(), class, stored_as));
+    }
+    let documents = [
+        (&stores.vectors, DataClass::RawContent),
+        (&stores.changes, DataClass::RawContent),
+        (&stores.knowledge, DataClass::Artifacts),
+        (&stores.seen, DataClass::Artifacts),
+        (&stores.trends, DataClass::Artifacts),
+    ];
+    for (path, class) in documents {
+        if let Some(path) = path {
+            files.push((path.clone(), class, StoredAs::File));
+        }
+    }
+
+    // A store inside the data directory is only searched once
+    let mut seen = std::collections::HashSet::new();
+    files.retain(|(path, _, _)| path.is_file() && seen.insert(path.clone()));
+    Ok(files)
+}
+
+/// Find every managed file in `data_dir` and `stores` that references `selector`
+pub fn find_subject(data_dir: &Path, stores: &Stores, selector: &str, key: Option<&AtRestKey>) -> Result<SubjectScan> {
+    let pattern = selector_regex(selector)?;
+    let mut scan = SubjectScan::default();
+
+    for (path, class, stored_as) in subject_files(data_dir, stores)? {
+        if key.is_none() && stored_as.is_sealed(&path)? {
+            scan.skipped_encrypted.push(path);
+            continue;
+        }
+        let Some((text, encrypted)) = stored_as.read(&path, key)? else {
+            continue;
+        };
+
+        let occurrences = pattern.find_iter(&text).count();
+        if occurrences == 0 {
+            continue;
+        }
+        let lines = text
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| pattern.is_match(line))
+            .map(|(i, line)| MatchedLine {
+                line: i + 1,
+                text: line.to_string(),
+            })
+            .collect();
+
+        scan.matches.push(SubjectMatch {
+            path,
+            class,
+            stored_as,
+            encrypted,
+            occurrences,
+            lines,
+        });
+    }
+
+    Ok(scan)
+}
+
+/// Write the matched data as JSON to `output`, sealed when a key is given
+pub fn export_subject(
+    data_dir: &Path,
+    selector: &str,
+    scan: &SubjectScan,
+    output: &Path,
+    key: Option<&AtRestKey>,
+) -> Result<()> {
+    let json = serde_json::to_string_pretty(&scan.matches)?;
+    match key {
+        Some(key) => write_sealed(output, key, json.as_bytes()).map_err(anyhow::Error::from),
+        None => std::fs::write(output, json).map_err(anyhow::Error::from),
+    }
+    .with_context(|| format!("failed to write {}", output.display()))?;
+    record(data_dir, "privacy_export", selector, scan)
+}
+
+/// Redact every occurrence of `selector` in the matched files
+///
+/// Encrypted files are re-encrypted with the same key.
+pub fn delete_subject(data_dir: &Path, selector: &str, scan: &SubjectScan, key: Option<&AtRestKey>) -> Result<()> {
+    let pattern = selector_regex(selector)?;
+
+    for m in &scan.matches {
+        let Some((text, encrypted)) = m.stored_as.read(&m.path, key)? else {
+            continue;
+        };
+        let redacted = pattern.replace_all(&text, REDACTION);
+        m.stored_as.write(&m.path, &redacted, encrypted, key)?;
+    }
+
+    record(data_dir, "privacy_delete", selector, scan)
+}
+
+fn record(data_dir: &Path, action: &str, selector: &str, scan: &SubjectScan) -> Result<()> {
+    let timestamp = chrono::Utc::now().to_rfc3339();
+    let digest = selector_digest(selector);
+    let records: Vec<_> = scan
+        .matches
+        .iter()
+        .map(|m| PrivacyAuditRecord {
+            timestamp: &timestamp,
+            action,
+            selector_sha256: &digest,
+            path: &m.path,
+            occurrences: m.occurrences,
+        })
+        .collect();
+    append_audit(data_dir, &records)
+}
+
+/// Markdown report of the actions taken, suitable for signing
+pub fn render_report(action: &str, selector: &str, data_dir: &Path, scan: &SubjectScan) -> String {
+    let mut report = format!(
+        "# Privacy {} report\n\n\
+         - Generated: {}\n\
+         - Data directory: {}\n\
+         - Selector SHA-256: `{}` (the selector itself is not recorded)\n\
+         - Files referencing the subject: {}\n\n",
+        action,
+        chrono::Utc::now().to_rfc3339(),
+        data_dir.display(),
+        selector_digest(selector),
+        scan.matches.len()
+    );
+
+    if !scan.matches.is_empty() {
+        report.push_str("| File | Class | Encrypted | Occurrences |\n|------|-------|-----------|-------------|\n");
+        for m in &scan.matches {
+            report.push_str(&format!(
+                "| {} | {} | {} | {} |\n",
+                m.path.display(),
+                m.class,
+                if m.encrypted { "yes" } else { "no" },
+                m.occurrences
+            ));
+        }
+    }
+
+    if !scan.skipped_encrypted.is_empty() {
+        report.push_str("\n## Not searched (encrypted, no key given)\n\n");
+        for path in &scan.skipped_encrypted {
+            report.push_str(&format!("- {}\n", path.display()));
+        }
+    }
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn data_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("robin-privacy-{}-{}", name, std::process::id()));
+        std::fs::remove_dir_all(&dir).ok();
+        std::fs::create_dir_all(dir.join("raw")).unwrap();
+        std::fs::write(dir.join("raw/page.txt"), "dump:\nJane.Doe@corp.example:hunter2\nother@x.example\n").unwrap();
+        std::fs::write(dir.join("summary_1.md"), "# Report\nNo personal data\n").unwrap();
+        std::fs::write(dir.join("notes.txt"), "jane.doe@corp.example (unmanaged)").unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_find_and_export() {
+        let dir = data_dir("export");
+        let scan = find_subject(&dir, &Stores::default(), "jane.doe@corp.example", None).unwrap();
+
+        // Case-insensitive, managed files only
+        assert_eq!(scan.matches.len(), 1);
+        assert_eq!(scan.matches[0].class, DataClass::RawContent);
+        assert_eq!(scan.matches[0].lines[0].line, 2);
+
+        let out = dir.join("export.json");
+        export_subject(&dir, "jane.doe@corp.example", &scan, &out, None).unwrap();
+        assert!(std::fs::read_to_string(&out).unwrap().contains("hunter2"));
+
+        let audit = std::fs::read_to_string(dir.join(crate::retention::AUDIT_LOG)).unwrap();
+        assert!(!audit.to_lowercase().contains("jane.doe"));
+        assert!(find_subject(&dir, &Stores::default(), "ja", None).is_err());
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_delete_redacts_plain_and_encrypted() {
+        let dir = data_dir("delete");
+        let key = AtRestKey::Key([3u8; 32]);
+        std::fs::create_dir_all(dir.join("artifacts")).unwrap();
+        robin_core::write_sealed(dir.join("artifacts/iocs.json"), &key, b"{\"email\":\"jane.doe@corp.example\"}").unwrap();
+
+        assert_eq!(find_subject(&dir, &Stores::default(), "jane.doe@corp.example", None).unwrap().skipped_encrypted.len(), 1);
+
+        let scan = find_subject(&dir, &Stores::default(), "jane.doe@corp.example", Some(&key)).unwrap();
+        assert_eq!(scan.matches.len(), 2);
+        delete_subject(&dir, "jane.doe@corp.example", &scan, Some(&key)).unwrap();
+
+        assert!(find_subject(&dir, &Stores::default(), "jane.doe@corp.example", Some(&key)).unwrap().matches.is_empty());
+        let raw = std::fs::read_to_string(dir.join("raw/page.txt")).unwrap();
+        assert!(raw.contains("[REDACTED]:hunter2") && raw.contains("other@x.example"));
+        let iocs = robin_core::read_maybe_sealed(dir.join("artifacts/iocs.json"), Some(&key)).unwrap();
+        assert_eq!(iocs, b"{\"email\":\"[REDACTED]\"}");
+
+        let report = render_report("deletion", "jane.doe@corp.example", &dir, &scan);
+        assert!(!report.contains("jane.doe") && report.contains(&selector_digest("Jane.Doe@corp.example")));
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_stores_outside_data_dir() {
+        let dir = data_dir("stores");
+        let key = AtRestKey::passphrase("correct horse");
+        let selector = "jane.doe@corp.example";
+
+        // A sealed field journal holding the subject in a signal
+        let journal = dir.join("run.journal");
+        let mut field = robin_core::Field::new();
+        field.persist_to(&journal, Some(&key)).unwrap();
+        field.emit(
+            robin_core::Signal::builder(robin_core::OsintPayload::UserQuery {
+                query: format!("leaks of {}", selector),
+                priority: 1.0,
+            })
+            .build(),
+        );
+        drop(field);
+
+        // A sealed response cache entry and a plain knowledge store
+        let cache = dir.join("cache");
+        std::fs::create_dir_all(&cache).unwrap();
+        let cache_key = ResponseCache::open(&cache).unwrap().with_key(&key).unwrap();
+        drop(cache_key);
+        let entry_key = ResponseCache::entry_key(&cache, &key).unwrap().unwrap();
+        robin_core::write_sealed(cache.join("ab.json"), &entry_key, format!("{{\"body\":\"{}\"}}", selector).as_bytes()).unwrap();
+        std::fs::write(dir.join("knowledge.json"), format!("{{\"cases\":[\"{}\"]}}", selector)).unwrap();
+
+        let stores = Stores {
+            cache_dir: Some(cache.clone()),
+            field_stores: vec![journal.clone()],
+            knowledge: Some(dir.join("knowledge.json")),
+            ..Default::default()
+        };
+        assert_eq!(find_subject(&dir, &stores, selector, None).unwrap().skipped_encrypted.len(), 2);
+        let scan = find_subject(&dir, &stores, selector, Some(&key)).unwrap();
+        assert_eq!(scan.matches.len(), 4);
+
+        // The export is sealed like the stores it came from
+        let out = dir.join("export.json.enc");
+        export_subject(&dir, selector, &scan, &out, Some(&key)).unwrap();
+        assert!(is_sealed(&std::fs::read(&out).unwrap()));
+
+        delete_subject(&dir, selector, &scan, Some(&key)).unwrap();
+        assert!(find_subject(&dir, &stores, selector, Some(&key)).unwrap().matches.is_empty());
+        let field = robin_core::Field::load(&journal, Some(&key)).unwrap();
+        assert_eq!(field.stats().active_signals, 1);
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
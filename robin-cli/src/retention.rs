@@ -0,0 +1,517 @@
+//! Data retention and purging
+//!
+//! Collected material is kept per data class for a configurable period
+//! (by default raw content 30 days, artifacts 1 year, reports indefinitely)
+//! and purged by `robin-smesh purge`. Every purge action is appended to the
+//! data directory's audit log (`audit.log`, JSON lines).
+//!
+//! Files are classified by location under the data directory: `raw/`,
+//! `artifacts/` and `reports/`; report files written directly into the
+//! data directory (`summary_*`) count as reports. The stores a query
+//! writes elsewhere are purged when given as [`Stores`].
+//!
+//! There is no daemon mode to run purges in the background yet; schedule
+//! `robin-smesh purge` with cron or a systemd timer instead.
+
+use std::fmt;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::{Duration, SystemTime};
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use robin_core::{AtRestKey, ChangeStore, KnowledgeStore, SeenStore, VectorStore};
+use serde::Serialize;
+
+/// Audit log file name inside the data directory
+pub const AUDIT_LOG: &str = "audit.log";
+
+const DAY: u64 = 24 * 60 * 60;
+
+/// Kind of stored data, each with its own retention period
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DataClass {
+    /// Scraped pages and other raw collected content
+    RawContent,
+    /// Extracted IOCs and enrichment results
+    Artifacts,
+    /// Generated reports (and their signatures)
+    Reports,
+}
+
+impl DataClass {
+    /// Class of `path` relative to the data directory, if it is managed data
+    pub fn of(relative: &Path) -> Option<Self> {
+        let mut components = relative.components();
+        let first = components.next()?.as_os_str().to_str()?;
+        let nested = components.next().is_some();
+
+        match first {
+            "raw" if nested => Some(DataClass::RawContent),
+            "artifacts" if nested => Some(DataClass::Artifacts),
+            "reports" if nested => Some(DataClass::Reports),
+            name if !nested && name.starts_with("summary_") => Some(DataClass::Reports),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for DataClass {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DataClass::RawContent => write!(f, "raw content"),
+            DataClass::Artifacts => write!(f, "artifacts"),
+            DataClass::Reports => write!(f, "reports"),
+        }
+    }
+}
+
+/// How long data is kept: a fixed period or indefinitely
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Retention(pub Option<Duration>);
+
+impl Retention {
+    /// Keep forever
+    pub const INDEFINITE: Retention = Retention(None);
+
+    /// Keep for `days` days
+    pub const fn days(days: u64) -> Self {
+        Retention(Some(Duration::from_secs(days * DAY)))
+    }
+}
+
+impl FromStr for Retention {
+    type Err = String;
+
+    /// Parse `30d`, `12h`, `6w`, `1y` or `forever`/`indefinite`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim().to_ascii_lowercase();
+        if s == "forever" || s == "indefinite" {
+            return Ok(Retention::INDEFINITE);
+        }
+
+        let unit_at = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+        let (count, unit) = s.split_at(unit_at);
+        let count: u64 = count
+            .parse()
+            .map_err(|_| format!("invalid retention '{}' (e.g. '30d', '1y', 'forever')", s))?;
+        let secs = match unit {
+            "h" => 60 * 60,
+            "d" | "" => DAY,
+            "w" => 7 * DAY,
+            "y" => 365 * DAY,
+            other => return Err(format!("unknown retention unit '{}' (expected h, d, w or y)", other)),
+        };
+        let total = count
+            .checked_mul(secs)
+            .ok_or_else(|| format!("retention '{}' is too long (use 'forever' to keep indefinitely)", s))?;
+        Ok(Retention(Some(Duration::from_secs(total))))
+    }
+}
+
+impl fmt::Display for Retention {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            None => write!(f, "indefinite"),
+            Some(d) if d.as_secs() % DAY == 0 => write!(f, "{}d", d.as_secs() / DAY),
+            Some(d) => write!(f, "{}h", d.as_secs() / 3600),
+        }
+    }
+}
+
+/// Retention period per data class
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetentionPolicy {
+    /// Scraped pages and other raw content (default 30 days)
+    pub raw_content: Retention,
+    /// Extracted artifacts (default 1 year)
+    pub artifacts: Retention,
+    /// Reports (default indefinite)
+    pub reports: Retention,
+}
+
+impl Default for RetentionPolicy {
+    fn default() -> Self {
+        Self {
+            raw_content: Retention::days(30),
+            artifacts: Retention::days(365),
+            reports: Retention::INDEFINITE,
+        }
+    }
+}
+
+impl RetentionPolicy {
+    /// Retention for `class`
+    pub fn retention(&self, class: DataClass) -> Retention {
+        match class {
+            DataClass::RawContent => self.raw_content,
+            DataClass::Artifacts => self.artifacts,
+            DataClass::Reports => self.reports,
+        }
+    }
+}
+
+/// Stores the CLI writes outside the data directory
+///
+/// Response cache entries, WARC archives, field journals and snapshots,
+/// signal histories and corpora are removed whole as raw content, event
+/// logs as artifacts. The vector index and page version store (raw
+/// content), knowledge store and seen-URL store (artifacts) are rewritten
+/// without expired entries. Subject data requests search all of them but
+/// WARC archives (see [`crate::privacy`]).
+#[derive(Debug, Clone, Default)]
+pub struct Stores {
+    /// Response cache directory (`--cache-dir`)
+    pub cache_dir: Option<PathBuf>,
+    /// WARC archives (`--warc`)
+    pub warcs: Vec<PathBuf>,
+    /// Field journals (`--field-store`)
+    pub field_stores: Vec<PathBuf>,
+    /// Field snapshots (`--export-field`)
+    pub snapshots: Vec<PathBuf>,
+    /// Signal histories (`--export-history`)
+    pub histories: Vec<PathBuf>,
+    /// Event logs (`--events`)
+    pub events: Vec<PathBuf>,
+    /// Exported corpora (`corpus --output`)
+    pub corpora: Vec<PathBuf>,
+    /// Vector index (`--vectors`)
+    pub vectors: Option<PathBuf>,
+    /// Page version store (`--changes`)
+    pub changes: Option<PathBuf>,
+    /// Knowledge store (`--knowledge`)
+    pub knowledge: Option<PathBuf>,
+    /// Seen-URL store (`--seen`)
+    pub seen: Option<PathBuf>,
+    /// Trend history (`--trends`); only counts, so never purged
+    pub trends: Option<PathBuf>,
+}
+
+impl Stores {
+    /// Stores removed whole, with their data class
+    pub fn files(&self) -> impl Iterator<Item = (&PathBuf, DataClass)> {
+        let raw = self
+            .field_stores
+            .iter()
+            .chain(&self.snapshots)
+            .chain(&self.histories)
+            .chain(&self.corpora)
+            .map(|path| (path, DataClass::RawContent));
+        raw.chain(self.events.iter().map(|path| (path, DataClass::Artifacts)))
+    }
+}
+
+/// One file removed, or entries pruned from a store (or, in a dry run, due for it)
+#[derive(Debug, Clone, Serialize)]
+pub struct PurgeAction {
+    /// When the purge ran (RFC 3339)
+    pub timestamp: String,
+    /// "purge" for a removed file, "prune" for entries removed from a store
+    pub action: &'static str,
+    /// Purged file or pruned store
+    pub path: PathBuf,
+    /// Data class the file or entries belonged to
+    pub class: DataClass,
+    /// File age in whole days; for pruned entries, the retention period
+    pub age_days: u64,
+    /// Number of entries pruned from a store
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub entries: Option<usize>,
+    /// Whether the file or store was left as it was
+    pub dry_run: bool,
+}
+
+/// Remove files in `data_dir` and `stores` older than their class's retention
+///
+/// Sealed stores need `key`. Returns the actions taken, which are also
+/// appended to the audit log (dry runs are logged too, marked as such).
+pub fn purge(
+    data_dir: &Path,
+    policy: &RetentionPolicy,
+    stores: &Stores,
+    key: Option<&AtRestKey>,
+    dry_run: bool,
+) -> Result<Vec<PurgeAction>> {
+    let mut purge = Purge {
+        policy,
+        dry_run,
+        now: SystemTime::now(),
+        timestamp: Utc::now().to_rfc3339(),
+        actions: Vec::new(),
+    };
+
+    for path in walk_files(data_dir)? {
+        let relative = path.strip_prefix(data_dir).unwrap_or(&path);
+        if let Some(class) = DataClass::of(relative) {
+            purge.file(path, class)?;
+        }
+    }
+
+    // Cache entries only: the salt file is needed to open the rest
+    if let Some(dir) = stores.cache_dir.as_ref().filter(|dir| dir.is_dir()) {
+        for path in walk_files(dir)? {
+            if path.extension().is_some_and(|ext| ext == "json") {
+                purge.file(path, DataClass::RawContent)?;
+            }
+        }
+    }
+    let warcs = stores.warcs.iter().map(|path| (path, DataClass::RawContent));
+    for (path, class) in warcs.chain(stores.files()) {
+        if path.is_file() {
+            purge.file(path.clone(), class)?;
+        }
+    }
+
+    if let Some(path) = &stores.vectors {
+        purge.entries(path, DataClass::RawContent, |cutoff, dry_run| {
+            let mut store = VectorStore::load(path, key)?;
+            let pruned = store.prune(cutoff);
+            if pruned > 0 && !dry_run {
+                store.save(path, key)?;
+            }
+            Ok(pruned)
+        })?;
+    }
+    if let Some(path) = &stores.changes {
+        purge.entries(path, DataClass::RawContent, |cutoff, dry_run| {
+            let mut store = ChangeStore::load(path, key)?;
+            let pruned = store.prune(cutoff);
+            if pruned > 0 && !dry_run {
+                store.save(path, key)?;
+            }
+            Ok(pruned)
+        })?;
+    }
+    if let Some(path) = &stores.knowledge {
+        purge.entries(path, DataClass::Artifacts, |cutoff, dry_run| {
+            let mut store = KnowledgeStore::load(path, key)?;
+            let pruned = store.prune(cutoff);
+            if pruned > 0 && !dry_run {
+                store.save(path, key)?;
+            }
+            Ok(pruned)
+        })?;
+    }
+    if let Some(path) = &stores.seen {
+        purge.entries(path, DataClass::Artifacts, |cutoff, dry_run| {
+            let mut store = SeenStore::load(path, key)?;
+            let pruned = store.prune(cutoff);
+            if pruned > 0 && !dry_run {
+                store.save(path, key)?;
+            }
+            Ok(pruned)
+        })?;
+    }
+
+    append_audit(data_dir, &purge.actions)?;
+    Ok(purge.actions)
+}
+
+/// State of one purge run
+struct Purge<'a> {
+    policy: &'a RetentionPolicy,
+    dry_run: bool,
+    now: SystemTime,
+    timestamp: String,
+    actions: Vec<PurgeAction>,
+}
+
+impl Purge<'_> {
+    /// Remove `path` if it was last modified longer ago than `class` is kept
+    fn file(&mut self, path: PathBuf, class: DataClass) -> Result<()> {
+        let Retention(Some(max_age)) = self.policy.retention(class) else {
+            return Ok(());
+        };
+
+        let modified = path
+            .metadata()
+            .and_then(|m| m.modified())
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        let age = self.now.duration_since(modified).unwrap_or_default();
+        if age <= max_age {
+            return Ok(());
+        }
+
+        if !self.dry_run {
+            std::fs::remove_file(&path).with_context(|| format!("failed to remove {}", path.display()))?;
+        }
+        self.actions.push(PurgeAction {
+            timestamp: self.timestamp.clone(),
+            action: "purge",
+            path,
+            class,
+            age_days: age.as_secs() / DAY,
+            entries: None,
+            dry_run: self.dry_run,
+        });
+        Ok(())
+    }
+
+    /// Prune the store at `path` of entries older than `class` is kept
+    ///
+    /// `prune` gets the cutoff and whether this is a dry run, and returns
+    /// how many entries it removed (or would remove).
+    fn entries(
+        &mut self,
+        path: &Path,
+        class: DataClass,
+        prune: impl FnOnce(DateTime<Utc>, bool) -> Result<usize>,
+    ) -> Result<()> {
+        let Retention(Some(max_age)) = self.policy.retention(class) else {
+            return Ok(());
+        };
+        if !path.is_file() {
+            return Ok(());
+        }
+
+        let max_age = chrono::Duration::from_std(max_age).context("retention period too long")?;
+        let pruned = prune(Utc::now() - max_age, self.dry_run)
+            .with_context(|| format!("failed to prune {}", path.display()))?;
+        if pruned > 0 {
+            self.actions.push(PurgeAction {
+                timestamp: self.timestamp.clone(),
+                action: "prune",
+                path: path.to_path_buf(),
+                class,
+                age_days: max_age.num_days() as u64,
+                entries: Some(pruned),
+                dry_run: self.dry_run,
+            });
+        }
+        Ok(())
+    }
+}
+
+/// All regular files under `dir`, recursively, in sorted order
+pub fn walk_files(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    let mut pending = vec![dir.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        for entry in std::fs::read_dir(&dir).with_context(|| format!("failed to read {}", dir.display()))? {
+            let entry = entry?;
+            let file_type = entry.file_type()?;
+            if file_type.is_dir() {
+                pending.push(entry.path());
+            } else if file_type.is_file() {
+                files.push(entry.path());
+            }
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// Append `records` as JSON lines to the data directory's audit log
+pub fn append_audit<T: Serialize>(data_dir: &Path, records: &[T]) -> Result<()> {
+    if records.is_empty() {
+        return Ok(());
+    }
+    let path = data_dir.join(AUDIT_LOG);
+    let mut log = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .with_context(|| format!("failed to open audit log {}", path.display()))?;
+    for record in records {
+        writeln!(log, "{}", serde_json::to_string(record)?)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_retention() {
+        assert_eq!("30d".parse(), Ok(Retention::days(30)));
+        assert_eq!("1y".parse(), Ok(Retention::days(365)));
+        assert_eq!("2w".parse(), Ok(Retention::days(14)));
+        assert_eq!("forever".parse(), Ok(Retention::INDEFINITE));
+        assert!("thirty".parse::<Retention>().is_err());
+        assert!("5m".parse::<Retention>().is_err());
+        assert!("18446744073709551615y".parse::<Retention>().is_err());
+    }
+
+    #[test]
+    fn test_classify() {
+        assert_eq!(DataClass::of(Path::new("raw/page.html")), Some(DataClass::RawContent));
+        assert_eq!(DataClass::of(Path::new("artifacts/case/iocs.json")), Some(DataClass::Artifacts));
+        assert_eq!(DataClass::of(Path::new("summary_2026-01-20.md.asc")), Some(DataClass::Reports));
+        assert_eq!(DataClass::of(Path::new(AUDIT_LOG)), None);
+        assert_eq!(DataClass::of(Path::new("raw")), None);
+    }
+
+    #[test]
+    fn test_purge_removes_expired_and_audits() {
+        let dir = std::env::temp_dir().join(format!("robin-retention-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("raw")).unwrap();
+        std::fs::write(dir.join("raw/page.html"), "<html>").unwrap();
+        std::fs::write(dir.join("summary_old.md"), "# Report").unwrap();
+
+        // Zero retention for raw content: everything already written is expired
+        let policy = RetentionPolicy {
+            raw_content: Retention(Some(Duration::ZERO)),
+            ..Default::default()
+        };
+        std::thread::sleep(Duration::from_millis(20));
+
+        let dry = purge(&dir, &policy, &Stores::default(), None, true).unwrap();
+        assert_eq!(dry.len(), 1);
+        assert!(dir.join("raw/page.html").exists());
+
+        let actions = purge(&dir, &policy, &Stores::default(), None, false).unwrap();
+        assert_eq!(actions.len(), 1);
+        assert_eq!(actions[0].class, DataClass::RawContent);
+        assert!(!dir.join("raw/page.html").exists());
+        assert!(dir.join("summary_old.md").exists());
+
+        let audit = std::fs::read_to_string(dir.join(AUDIT_LOG)).unwrap();
+        assert_eq!(audit.lines().count(), 2);
+        assert!(audit.lines().last().unwrap().contains("\"dry_run\":false"));
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_purge_stores() {
+        let dir = std::env::temp_dir().join(format!("robin-retention-stores-{}", std::process::id()));
+        let cache = dir.join("cache");
+        std::fs::create_dir_all(&cache).unwrap();
+        std::fs::write(cache.join("0123abcd.json"), "{}").unwrap();
+        std::fs::write(cache.join("cache.salt"), "salt").unwrap();
+        std::fs::write(dir.join("run.warc"), "WARC/1.1").unwrap();
+
+        let key = AtRestKey::passphrase("correct horse");
+        let mut seen = SeenStore::default();
+        seen.record(robin_core::SeenKind::Scraped, "http://a.onion/");
+        seen.save(dir.join("seen.json"), Some(&key)).unwrap();
+
+        let stores = Stores {
+            cache_dir: Some(cache.clone()),
+            warcs: vec![dir.join("run.warc"), dir.join("missing.warc")],
+            seen: Some(dir.join("seen.json")),
+            ..Default::default()
+        };
+        let policy = RetentionPolicy {
+            raw_content: Retention(Some(Duration::ZERO)),
+            artifacts: Retention(Some(Duration::ZERO)),
+            ..Default::default()
+        };
+        std::thread::sleep(Duration::from_millis(20));
+
+        let actions = purge(&dir, &policy, &stores, Some(&key), false).unwrap();
+        assert_eq!(actions.len(), 3);
+        assert!(!cache.join("0123abcd.json").exists());
+        assert!(cache.join("cache.salt").exists());
+        assert!(!dir.join("run.warc").exists());
+        let pruned = actions.iter().find(|a| a.action == "prune").unwrap();
+        assert_eq!((pruned.class, pruned.entries), (DataClass::Artifacts, Some(1)));
+        assert!(SeenStore::load(dir.join("seen.json"), Some(&key)).unwrap().is_empty());
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
@@ -0,0 +1,184 @@
+//! Detached PGP signatures for reports and exports
+//!
+//! Files written by the CLI can be signed with a team key, producing an
+//! ASCII-armored detached signature next to the file (`<file>.asc`) that
+//! downstream consumers can check with `robin-smesh verify` or `gpg --verify`.
+//!
+//! Requires the `pgp` feature (sequoia-openpgp); without it signing and
+//! verification return an error.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+
+/// Path of the detached signature for `path` (`<path>.asc`)
+pub fn signature_path(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_os_string();
+    name.push(".asc");
+    PathBuf::from(name)
+}
+
+/// Sign `path` with the secret key in `key_path`, writing `<path>.asc`
+///
+/// `passphrase` unlocks an encrypted secret key. Returns the signature path.
+#[cfg(feature = "pgp")]
+pub fn sign_file(path: &Path, key_path: &Path, passphrase: Option<&str>) -> Result<PathBuf> {
+    use std::io::Write;
+
+    use anyhow::Context;
+    use sequoia_openpgp as openpgp;
+    use openpgp::parse::Parse;
+    use openpgp::policy::StandardPolicy;
+    use openpgp::serialize::stream::{Armorer, Message, Signer};
+
+    let policy = StandardPolicy::new();
+    let tsk = openpgp::Cert::from_file(key_path)
+        .with_context(|| format!("failed to read signing key {}", key_path.display()))?;
+
+    let key = tsk
+        .keys()
+        .with_policy(&policy, None)
+        .supported()
+        .alive()
+        .revoked(false)
+        .for_signing()
+        .secret()
+        .next()
+        .with_context(|| format!("no usable signing key in {}", key_path.display()))?;
+
+    let mut secret = key.key().clone();
+    if secret.secret().is_encrypted() {
+        let passphrase = passphrase.context("signing key is encrypted; a passphrase is required")?;
+        secret
+            .secret_mut()
+            .decrypt_in_place(key.key(), &passphrase.into())
+            .context("failed to unlock signing key (wrong passphrase?)")?;
+    }
+    let keypair = secret.into_keypair()?;
+
+    let data = std::fs::read(path).with_context(|| format!("failed to read {}", path.display()))?;
+
+    let mut signature = Vec::new();
+    let message = Message::new(&mut signature);
+    let message = Armorer::new(message)
+        .kind(openpgp::armor::Kind::Signature)
+        .build()?;
+    let mut signer = Signer::new(message, keypair)?.detached().build()?;
+    signer.write_all(&data)?;
+    signer.finalize()?;
+
+    let sig_path = signature_path(path);
+    std::fs::write(&sig_path, signature)
+        .with_context(|| format!("failed to write {}", sig_path.display()))?;
+    Ok(sig_path)
+}
+
+/// Verify the detached `signature` over `path` against the certificate in `cert_path`
+///
+/// Returns the fingerprint of the certificate that made the signature.
+#[cfg(feature = "pgp")]
+pub fn verify_file(path: &Path, signature: &Path, cert_path: &Path) -> Result<String> {
+    use anyhow::Context;
+    use sequoia_openpgp as openpgp;
+    use openpgp::parse::stream::{
+        DetachedVerifierBuilder, MessageLayer, MessageStructure, VerificationHelper,
+    };
+    use openpgp::parse::Parse;
+    use openpgp::policy::StandardPolicy;
+
+    struct Helper {
+        cert: openpgp::Cert,
+    }
+
+    impl VerificationHelper for Helper {
+        fn get_certs(&mut self, _ids: &[openpgp::KeyHandle]) -> openpgp::Result<Vec<openpgp::Cert>> {
+            Ok(vec![self.cert.clone()])
+        }
+
+        fn check(&mut self, structure: MessageStructure) -> openpgp::Result<()> {
+            for layer in structure {
+                if let MessageLayer::SignatureGroup { results } = layer {
+                    if results.iter().any(|r| r.is_ok()) {
+                        return Ok(());
+                    }
+                    if let Some(Err(e)) = results.into_iter().next() {
+                        return Err(openpgp::Error::from(e).into());
+                    }
+                }
+            }
+            Err(anyhow::anyhow!("no valid signature found"))
+        }
+    }
+
+    let policy = StandardPolicy::new();
+    let cert = openpgp::Cert::from_file(cert_path)
+        .with_context(|| format!("failed to read certificate {}", cert_path.display()))?;
+    let fingerprint = cert.fingerprint().to_hex();
+
+    let signature = std::fs::read(signature)
+        .with_context(|| format!("failed to read signature {}", signature.display()))?;
+    let mut verifier = DetachedVerifierBuilder::from_bytes(&signature)?
+        .with_policy(&policy, None, Helper { cert })?;
+
+    verifier
+        .verify_file(path)
+        .with_context(|| format!("signature check failed for {}", path.display()))?;
+    Ok(fingerprint)
+}
+
+#[cfg(not(feature = "pgp"))]
+pub fn sign_file(_path: &Path, _key_path: &Path, _passphrase: Option<&str>) -> Result<PathBuf> {
+    anyhow::bail!("robin-smesh was built without the `pgp` feature")
+}
+
+#[cfg(not(feature = "pgp"))]
+pub fn verify_file(_path: &Path, _signature: &Path, _cert_path: &Path) -> Result<String> {
+    anyhow::bail!("robin-smesh was built without the `pgp` feature")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_signature_path() {
+        assert_eq!(
+            signature_path(Path::new("reports/summary.md")),
+            PathBuf::from("reports/summary.md.asc")
+        );
+    }
+
+    #[cfg(feature = "pgp")]
+    #[test]
+    fn test_sign_and_verify_roundtrip() {
+        use sequoia_openpgp as openpgp;
+        use openpgp::cert::CertBuilder;
+        use openpgp::serialize::Serialize;
+
+        let dir = std::env::temp_dir().join(format!("robin-pgp-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let (cert, _) = CertBuilder::new()
+            .add_userid("Robin Team <team@example.org>")
+            .add_signing_subkey()
+            .set_password(Some("hunter2".into()))
+            .generate()
+            .unwrap();
+        let key_path = dir.join("team.key");
+        let cert_path = dir.join("team.pub");
+        cert.as_tsk().serialize(&mut std::fs::File::create(&key_path).unwrap()).unwrap();
+        cert.serialize(&mut std::fs::File::create(&cert_path).unwrap()).unwrap();
+
+        let report = dir.join("summary.md");
+        std::fs::write(&report, "# Findings\n1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa\n").unwrap();
+
+        assert!(sign_file(&report, &key_path, None).is_err());
+        let sig = sign_file(&report, &key_path, Some("hunter2")).unwrap();
+        assert_eq!(verify_file(&report, &sig, &cert_path).unwrap(), cert.fingerprint().to_hex());
+
+        std::fs::write(&report, "# Findings\ntampered\n").unwrap();
+        assert!(verify_file(&report, &sig, &cert_path).is_err());
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
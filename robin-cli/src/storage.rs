@@ -0,0 +1,334 @@
+//! Object storage for run outputs
+//!
+//! Reports, signatures and other run artifacts can be written to a storage
+//! location instead of (or as well as) the working directory:
+//! - `s3://bucket/prefix` — Amazon S3 or any S3-compatible service (MinIO,
+//!   Ceph, ...) via a custom endpoint, with optional server-side encryption
+//! - `gs://bucket/prefix` — Google Cloud Storage
+//! - `file:///path` or a plain path — a local directory
+//!
+//! Credentials come from the usual environment variables (`AWS_*`,
+//! `GOOGLE_*`). Cloud backends require the `object-storage` feature
+//! (object_store); without it only local directories work.
+
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use anyhow::{Context, Result};
+
+/// Where run outputs are written
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StorageLocation {
+    /// A local directory
+    Local(PathBuf),
+    /// An S3 (or S3-compatible) bucket
+    S3 { bucket: String, prefix: String },
+    /// A Google Cloud Storage bucket
+    Gcs { bucket: String, prefix: String },
+}
+
+impl FromStr for StorageLocation {
+    type Err = anyhow::Error;
+
+    fn from_str(url: &str) -> Result<Self> {
+        let (scheme, rest) = match url.split_once("://") {
+            Some((scheme, rest)) => (scheme.to_ascii_lowercase(), rest),
+            None => return Ok(StorageLocation::Local(PathBuf::from(url))),
+        };
+
+        let split_bucket = || -> Result<(String, String)> {
+            let (bucket, prefix) = rest.split_once('/').unwrap_or((rest, ""));
+            if bucket.is_empty() {
+                anyhow::bail!("storage URL has no bucket: {}", url);
+            }
+            Ok((bucket.to_string(), prefix.trim_matches('/').to_string()))
+        };
+
+        match scheme.as_str() {
+            "file" => Ok(StorageLocation::Local(PathBuf::from(rest))),
+            "s3" | "s3a" => {
+                let (bucket, prefix) = split_bucket()?;
+                Ok(StorageLocation::S3 { bucket, prefix })
+            }
+            "gs" | "gcs" => {
+                let (bucket, prefix) = split_bucket()?;
+                Ok(StorageLocation::Gcs { bucket, prefix })
+            }
+            other => anyhow::bail!(
+                "unsupported storage scheme '{}' (expected s3://, gs:// or file://)",
+                other
+            ),
+        }
+    }
+}
+
+impl fmt::Display for StorageLocation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StorageLocation::Local(dir) => write!(f, "{}", dir.display()),
+            StorageLocation::S3 { bucket, prefix } => write!(f, "s3://{}/{}", bucket, prefix),
+            StorageLocation::Gcs { bucket, prefix } => write!(f, "gs://{}/{}", bucket, prefix),
+        }
+    }
+}
+
+/// S3 server-side encryption mode
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ServerSideEncryption {
+    /// S3-managed keys (SSE-S3, AES256)
+    S3Managed,
+    /// AWS KMS keys (SSE-KMS); key ID optional, defaults to the AWS-managed key
+    Kms,
+    /// Dual-layer KMS encryption (DSSE-KMS)
+    DsseKms,
+    /// Customer-provided key (SSE-C); requires a base64 256-bit key
+    CustomerKey,
+}
+
+impl ServerSideEncryption {
+    /// Value of the S3 encryption setting
+    #[cfg_attr(not(feature = "object-storage"), allow(dead_code))]
+    fn s3_value(self) -> &'static str {
+        match self {
+            ServerSideEncryption::S3Managed => "AES256",
+            ServerSideEncryption::Kms => "aws:kms",
+            ServerSideEncryption::DsseKms => "aws:kms:dsse",
+            ServerSideEncryption::CustomerKey => "sse-c",
+        }
+    }
+}
+
+impl FromStr for ServerSideEncryption {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "aes256" | "sse-s3" => Ok(ServerSideEncryption::S3Managed),
+            "kms" | "sse-kms" => Ok(ServerSideEncryption::Kms),
+            "dsse-kms" => Ok(ServerSideEncryption::DsseKms),
+            "sse-c" => Ok(ServerSideEncryption::CustomerKey),
+            other => Err(format!(
+                "unknown encryption mode '{}' (expected 'aes256', 'kms', 'dsse-kms' or 'sse-c')",
+                other
+            )),
+        }
+    }
+}
+
+/// Object storage settings
+#[derive(Debug, Clone)]
+pub struct StorageConfig {
+    /// Destination bucket/prefix or directory
+    pub location: StorageLocation,
+    /// Custom S3 endpoint (e.g. `http://minio:9000`)
+    pub endpoint: Option<String>,
+    /// S3 region (default: from the environment)
+    pub region: Option<String>,
+    /// S3 server-side encryption
+    pub encryption: Option<ServerSideEncryption>,
+    /// KMS key ID (SSE-KMS/DSSE-KMS) or base64 customer key (SSE-C)
+    pub encryption_key: Option<String>,
+}
+
+impl StorageConfig {
+    /// Storage at `location` with backend defaults
+    pub fn new(location: StorageLocation) -> Self {
+        Self {
+            location,
+            endpoint: None,
+            region: None,
+            encryption: None,
+            encryption_key: None,
+        }
+    }
+
+    /// Check the settings are consistent before any upload is attempted
+    pub fn validate(&self) -> Result<()> {
+        let is_s3 = matches!(self.location, StorageLocation::S3 { .. });
+        if !is_s3 && (self.encryption.is_some() || self.endpoint.is_some() || self.region.is_some()) {
+            anyhow::bail!("endpoint, region and server-side encryption options only apply to s3:// storage");
+        }
+        if self.encryption == Some(ServerSideEncryption::CustomerKey) && self.encryption_key.is_none() {
+            anyhow::bail!("SSE-C encryption requires a base64-encoded customer key");
+        }
+        if self.encryption_key.is_some()
+            && matches!(self.encryption, None | Some(ServerSideEncryption::S3Managed))
+        {
+            anyhow::bail!("an encryption key needs a 'kms', 'dsse-kms' or 'sse-c' encryption mode");
+        }
+        if !matches!(self.location, StorageLocation::Local(_)) && !cfg!(feature = "object-storage") {
+            anyhow::bail!(
+                "{} storage requires robin-smesh built with the `object-storage` feature",
+                self.location
+            );
+        }
+        Ok(())
+    }
+}
+
+/// Object key for `file` under `prefix`
+pub fn object_key(prefix: &str, file: &Path) -> Result<String> {
+    let name = file
+        .file_name()
+        .with_context(|| format!("not a file path: {}", file.display()))?
+        .to_string_lossy();
+    Ok(if prefix.is_empty() {
+        name.into_owned()
+    } else {
+        format!("{}/{}", prefix, name)
+    })
+}
+
+/// Store `files` at the configured location, returning where each one went
+pub async fn store_files(config: &StorageConfig, files: &[PathBuf]) -> Result<Vec<String>> {
+    config.validate()?;
+
+    match &config.location {
+        StorageLocation::Local(dir) => {
+            std::fs::create_dir_all(dir)
+                .with_context(|| format!("failed to create {}", dir.display()))?;
+            files
+                .iter()
+                .map(|file| {
+                    let dest = dir.join(object_key("", file)?);
+                    std::fs::copy(file, &dest)
+                        .with_context(|| format!("failed to copy {} to {}", file.display(), dest.display()))?;
+                    Ok(dest.display().to_string())
+                })
+                .collect()
+        }
+        _ => store_remote(config, files).await,
+    }
+}
+
+#[cfg(feature = "object-storage")]
+async fn store_remote(config: &StorageConfig, files: &[PathBuf]) -> Result<Vec<String>> {
+    use object_store::{path::Path as ObjectPath, ObjectStore, PutPayload};
+
+    let (store, bucket_url, prefix): (Box<dyn ObjectStore>, String, &str) = match &config.location {
+        StorageLocation::S3 { bucket, prefix } => {
+            use object_store::aws::{AmazonS3Builder, AmazonS3ConfigKey};
+
+            let mut builder = AmazonS3Builder::from_env().with_bucket_name(bucket);
+            if let Some(region) = &config.region {
+                builder = builder.with_region(region);
+            }
+            if let Some(endpoint) = &config.endpoint {
+                builder = builder
+                    .with_endpoint(endpoint)
+                    .with_allow_http(endpoint.starts_with("http://"));
+            }
+            if let Some(encryption) = config.encryption {
+                let key_setting = match encryption {
+                    ServerSideEncryption::CustomerKey => "aws_sse_customer_key_base64",
+                    _ => "aws_sse_kms_key_id",
+                };
+                builder = builder.with_config(
+                    "aws_server_side_encryption".parse::<AmazonS3ConfigKey>()?,
+                    encryption.s3_value(),
+                );
+                if let Some(key) = &config.encryption_key {
+                    builder = builder.with_config(key_setting.parse::<AmazonS3ConfigKey>()?, key);
+                }
+            }
+            let store = builder.build().context("failed to configure S3 storage")?;
+            (Box::new(store), format!("s3://{}", bucket), prefix)
+        }
+        StorageLocation::Gcs { bucket, prefix } => {
+            use object_store::gcp::GoogleCloudStorageBuilder;
+
+            let store = GoogleCloudStorageBuilder::from_env()
+                .with_bucket_name(bucket)
+                .build()
+                .context("failed to configure GCS storage")?;
+            (Box::new(store), format!("gs://{}", bucket), prefix)
+        }
+        StorageLocation::Local(_) => unreachable!("local storage is handled by store_files"),
+    };
+
+    let mut stored = Vec::with_capacity(files.len());
+    for file in files {
+        let key = object_key(prefix, file)?;
+        let data = std::fs::read(file).with_context(|| format!("failed to read {}", file.display()))?;
+        store
+            .put(&ObjectPath::from(key.as_str()), PutPayload::from(data))
+            .await
+            .with_context(|| format!("failed to upload {}", file.display()))?;
+        stored.push(format!("{}/{}", bucket_url, key));
+    }
+    Ok(stored)
+}
+
+#[cfg(not(feature = "object-storage"))]
+async fn store_remote(config: &StorageConfig, _files: &[PathBuf]) -> Result<Vec<String>> {
+    anyhow::bail!(
+        "{} storage requires robin-smesh built with the `object-storage` feature",
+        config.location
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_location() {
+        assert_eq!(
+            "s3://osint-runs/cases/acme/".parse::<StorageLocation>().unwrap(),
+            StorageLocation::S3 {
+                bucket: "osint-runs".to_string(),
+                prefix: "cases/acme".to_string()
+            }
+        );
+        assert_eq!(
+            "gs://osint-runs".parse::<StorageLocation>().unwrap(),
+            StorageLocation::Gcs {
+                bucket: "osint-runs".to_string(),
+                prefix: String::new()
+            }
+        );
+        assert_eq!(
+            "file:///var/lib/robin".parse::<StorageLocation>().unwrap(),
+            StorageLocation::Local(PathBuf::from("/var/lib/robin"))
+        );
+        assert!("s3:///prefix".parse::<StorageLocation>().is_err());
+        assert!("ftp://host/dir".parse::<StorageLocation>().is_err());
+    }
+
+    #[test]
+    fn test_validate_encryption_options() {
+        let s3 = |encryption, key: Option<&str>| StorageConfig {
+            encryption,
+            encryption_key: key.map(String::from),
+            ..StorageConfig::new("s3://bucket".parse().unwrap())
+        };
+
+        assert!(s3(Some(ServerSideEncryption::CustomerKey), None).validate().is_err());
+        assert!(s3(None, Some("key")).validate().is_err());
+
+        let mut local = StorageConfig::new(StorageLocation::Local(PathBuf::from("out")));
+        assert!(local.validate().is_ok());
+        local.encryption = Some(ServerSideEncryption::Kms);
+        assert!(local.validate().is_err());
+
+        assert_eq!("dsse-kms".parse(), Ok(ServerSideEncryption::DsseKms));
+    }
+
+    #[tokio::test]
+    async fn test_store_to_local_directory() {
+        let dir = std::env::temp_dir().join(format!("robin-storage-{}", std::process::id()));
+        let report = std::env::temp_dir().join(format!("robin-storage-{}.md", std::process::id()));
+        std::fs::write(&report, "# Findings").unwrap();
+
+        let config = StorageConfig::new(StorageLocation::Local(dir.join("runs")));
+        let stored = store_files(&config, std::slice::from_ref(&report)).await.unwrap();
+
+        assert_eq!(stored.len(), 1);
+        assert_eq!(std::fs::read_to_string(&stored[0]).unwrap(), "# Findings");
+        assert_eq!(object_key("cases/acme", &report).unwrap(), format!("cases/acme/robin-storage-{}.md", std::process::id()));
+
+        std::fs::remove_dir_all(&dir).ok();
+        std::fs::remove_file(&report).ok();
+    }
+}
@@ -0,0 +1,341 @@
+//! Threat actor alias knowledge base
+//!
+//! Groups are tracked under many names (vendor naming schemes, rebrands,
+//! forum handles). [`ActorDatabase`] bundles groups with their aliases,
+//! known TTPs and wallets, matches them in scraped text (tolerating spacing,
+//! punctuation and one-letter typos) and annotates reports so every alias
+//! is tied back to one canonical name. Users extend it with their own JSON.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+use thiserror::Error;
+
+use crate::{Artifact, ArtifactType};
+
+/// Bundled actor list
+const EMBEDDED_ACTORS: &str = include_str!("../data/actors.json");
+
+/// Aliases shorter than this (in compact form) only match exactly
+const MIN_FUZZY_LEN: usize = 6;
+
+/// Errors loading an actor file
+#[derive(Debug, Error)]
+pub enum ActorDbError {
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Invalid actor file: {0}")]
+    Parse(#[from] serde_json::Error),
+}
+
+/// A threat actor and the names it is known by
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct KnownActor {
+    /// Canonical name used in reports
+    pub name: String,
+    /// Other names, matched case-insensitively and fuzzily
+    #[serde(default)]
+    pub aliases: Vec<String>,
+    /// Names that are ordinary words (e.g. "Play"), matched only verbatim
+    #[serde(default)]
+    pub strict_aliases: Vec<String>,
+    /// MITRE ATT&CK technique IDs
+    #[serde(default)]
+    pub ttps: Vec<String>,
+    /// Wallet addresses attributed to the actor
+    #[serde(default)]
+    pub wallets: Vec<String>,
+}
+
+/// An actor found in text
+#[derive(Debug, Clone, PartialEq)]
+pub struct ActorMatch<'a> {
+    pub actor: &'a KnownActor,
+    /// Name (or wallet) that matched
+    pub alias: &'a str,
+    /// Text as it appeared
+    pub matched: String,
+    /// 1.0 for an exact match, lower for typos
+    pub score: f64,
+}
+
+/// Threat actor knowledge base
+#[derive(Debug, Clone, Default)]
+pub struct ActorDatabase {
+    actors: Vec<KnownActor>,
+}
+
+impl ActorDatabase {
+    /// The bundled actor list
+    pub fn embedded() -> Self {
+        Self::from_json(EMBEDDED_ACTORS).expect("bundled actors.json is valid")
+    }
+
+    /// Parse a JSON array of actors
+    pub fn from_json(json: &str) -> Result<Self, ActorDbError> {
+        Ok(Self {
+            actors: serde_json::from_str(json)?,
+        })
+    }
+
+    /// Load a JSON actor file
+    pub fn load_file(path: impl AsRef<Path>) -> Result<Self, ActorDbError> {
+        Self::from_json(&std::fs::read_to_string(path)?)
+    }
+
+    /// Add `other`'s actors; entries with the same canonical name are merged
+    pub fn merge(mut self, other: ActorDatabase) -> Self {
+        for actor in other.actors {
+            match self.actors.iter_mut().find(|a| a.name.eq_ignore_ascii_case(&actor.name)) {
+                Some(existing) => {
+                    merge_unique(&mut existing.aliases, actor.aliases);
+                    merge_unique(&mut existing.strict_aliases, actor.strict_aliases);
+                    merge_unique(&mut existing.ttps, actor.ttps);
+                    merge_unique(&mut existing.wallets, actor.wallets);
+                }
+                None => self.actors.push(actor),
+            }
+        }
+        self
+    }
+
+    pub fn actors(&self) -> &[KnownActor] {
+        &self.actors
+    }
+
+    pub fn len(&self) -> usize {
+        self.actors.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.actors.is_empty()
+    }
+
+    /// The actor known by `name` (canonical name or any alias, exact up to case and punctuation)
+    pub fn lookup(&self, name: &str) -> Option<&KnownActor> {
+        let wanted = compact(name);
+        self.actors.iter().find(|actor| {
+            actor
+                .names()
+                .any(|n| compact(n) == wanted)
+        })
+    }
+
+    /// Actors mentioned in `text`, best match per actor, highest score first
+    pub fn find_in_text<'a>(&'a self, text: &str) -> Vec<ActorMatch<'a>> {
+        let tokens = tokenize(text);
+        let max_words = self
+            .actors
+            .iter()
+            .flat_map(|a| a.names())
+            .map(|n| tokenize(n).len())
+            .max()
+            .unwrap_or(1);
+
+        // Every window of up to `max_words` tokens, by compact form
+        let mut windows: HashMap<String, Vec<(usize, usize)>> = HashMap::new();
+        for start in 0..tokens.len() {
+            let mut key = String::new();
+            for end in start..tokens.len().min(start + max_words) {
+                key.push_str(&compact(tokens[end].2));
+                windows.entry(key.clone()).or_default().push((tokens[start].0, tokens[end].1));
+            }
+        }
+
+        let mut matches = Vec::new();
+        for actor in &self.actors {
+            let mut best: Option<ActorMatch<'a>> = None;
+            let mut consider = |candidate: ActorMatch<'a>| {
+                if best.as_ref().is_none_or(|b| candidate.score > b.score) {
+                    best = Some(candidate);
+                }
+            };
+
+            for alias in &actor.strict_aliases {
+                let verbatim = windows
+                    .get(&compact(alias))
+                    .is_some_and(|ranges| ranges.iter().any(|&(start, end)| text[start..end] == *alias));
+                if verbatim {
+                    consider(ActorMatch { actor, alias, matched: alias.clone(), score: 1.0 });
+                }
+            }
+            let loose = std::iter::once(&actor.name)
+                .chain(&actor.aliases)
+                .filter(|alias| !actor.strict_aliases.contains(alias));
+            for alias in loose {
+                let wanted = compact(alias);
+                if let Some(&(start, end)) = windows.get(&wanted).and_then(|ranges| ranges.first()) {
+                    consider(ActorMatch { actor, alias, matched: text[start..end].to_string(), score: 1.0 });
+                    continue;
+                }
+                if wanted.chars().count() < MIN_FUZZY_LEN {
+                    continue;
+                }
+                let max_distance = if wanted.len() < 10 { 1 } else { 2 };
+                for (window, ranges) in &windows {
+                    if window.len().abs_diff(wanted.len()) > max_distance || window.chars().next() != wanted.chars().next() {
+                        continue;
+                    }
+                    let distance = levenshtein(window, &wanted);
+                    if distance <= max_distance {
+                        let (start, end) = ranges[0];
+                        let score = 1.0 - distance as f64 / wanted.len() as f64;
+                        consider(ActorMatch { actor, alias, matched: text[start..end].to_string(), score });
+                    }
+                }
+            }
+            for wallet in &actor.wallets {
+                if text.contains(wallet.as_str()) {
+                    consider(ActorMatch { actor, alias: wallet, matched: wallet.clone(), score: 1.0 });
+                }
+            }
+            matches.extend(best);
+        }
+        matches.sort_by(|a, b| b.score.total_cmp(&a.score));
+        matches
+    }
+
+    /// ThreatActor artifacts (canonical names) for the actors mentioned in `text`
+    pub fn extract_actors(&self, text: &str, source: Option<&str>) -> Vec<Artifact> {
+        self.find_in_text(text)
+            .into_iter()
+            .map(|m| {
+                let mut artifact = Artifact::new(ArtifactType::ThreatActor, m.actor.name.clone())
+                    .with_confidence(0.9 * m.score)
+                    .with_context(&format!("matched \"{}\"", m.matched));
+                if let Some(source) = source {
+                    artifact = artifact.with_source(source);
+                }
+                artifact
+            })
+            .collect()
+    }
+
+    /// Tie aliases in a report back to canonical names
+    ///
+    /// Where a report names an actor only by an alias, the first mention
+    /// becomes "alias (Canonical)". Exact matches only, so the report text
+    /// is never rewritten on a guess.
+    pub fn normalize_report(&self, report: &str) -> String {
+        let mut insertions: Vec<(usize, String)> = self
+            .find_in_text(report)
+            .into_iter()
+            .filter(|m| m.score >= 1.0 && m.alias != m.actor.name && !m.actor.wallets.iter().any(|w| w == m.alias))
+            .filter(|m| !report.to_lowercase().contains(&m.actor.name.to_lowercase()))
+            .filter_map(|m| {
+                let at = report.find(&m.matched)? + m.matched.len();
+                Some((at, format!(" ({})", m.actor.name)))
+            })
+            .collect();
+        insertions.sort_by_key(|(at, _)| std::cmp::Reverse(*at));
+
+        let mut out = report.to_string();
+        for (at, note) in insertions {
+            out.insert_str(at, &note);
+        }
+        out
+    }
+}
+
+impl KnownActor {
+    /// Canonical name, aliases and strict aliases
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        std::iter::once(self.name.as_str())
+            .chain(self.aliases.iter().map(String::as_str))
+            .chain(self.strict_aliases.iter().map(String::as_str))
+    }
+}
+
+fn merge_unique(into: &mut Vec<String>, from: Vec<String>) {
+    for value in from {
+        if !into.iter().any(|v| v.eq_ignore_ascii_case(&value)) {
+            into.push(value);
+        }
+    }
+}
+
+/// Lowercase alphanumerics only ("LockBit 3.0" → "lockbit30")
+fn compact(text: &str) -> String {
+    text.chars().filter(|c| c.is_alphanumeric()).flat_map(char::to_lowercase).collect()
+}
+
+/// Alphanumeric runs with their byte ranges
+fn tokenize(text: &str) -> Vec<(usize, usize, &str)> {
+    let mut tokens = Vec::new();
+    let mut start = None;
+    for (i, c) in text.char_indices() {
+        match (c.is_alphanumeric(), start) {
+            (true, None) => start = Some(i),
+            (false, Some(s)) => {
+                tokens.push((s, i, &text[s..i]));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    if let Some(s) = start {
+        tokens.push((s, text.len(), &text[s..]));
+    }
+    tokens
+}
+
+fn levenshtein(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut previous = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous + usize::from(ca != *cb);
+            previous = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(previous + 1);
+        }
+    }
+    row[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_actors_in_text() {
+        let db = ActorDatabase::embedded();
+        let text = "Affiliates of Lockbit 3.0 and the Sodinokbi crew; we play games. Play leak site is up.";
+        let matches = db.find_in_text(text);
+        let names: Vec<&str> = matches.iter().map(|m| m.actor.name.as_str()).collect();
+
+        assert!(names.contains(&"LockBit"));
+        // One-letter typo still resolves to REvil
+        let revil = matches.iter().find(|m| m.actor.name == "REvil").unwrap();
+        assert_eq!(revil.matched, "Sodinokbi");
+        assert!(revil.score < 1.0);
+        // "Play" only as written
+        let play = matches.iter().find(|m| m.actor.name == "Play").unwrap();
+        assert_eq!(play.matched, "Play");
+
+        let artifacts = db.extract_actors(text, Some("http://forum.onion/t/1"));
+        assert!(artifacts.iter().all(|a| a.artifact_type == ArtifactType::ThreatActor));
+        assert_eq!(db.lookup("fancy-bear").map(|a| a.name.as_str()), Some("APT28"));
+        assert!(db.find_in_text("continue playing").is_empty());
+    }
+
+    #[test]
+    fn test_user_extension_and_report_normalization() {
+        let custom = ActorDatabase::from_json(
+            r#"[{"name": "LockBit", "aliases": ["Bitwise Spider"]},
+                {"name": "Ghostline", "aliases": ["gh0stl1ne"], "wallets": ["bc1qexamplewallet"]}]"#,
+        )
+        .unwrap();
+        let db = ActorDatabase::embedded().merge(custom);
+        assert!(db.lookup("Bitwise Spider").is_some_and(|a| a.name == "LockBit"));
+        assert!(db.find_in_text("paid to bc1qexamplewallet").iter().any(|m| m.actor.name == "Ghostline"));
+
+        let report = "The Sodinokibi operators resurfaced; Fancy Bear and APT28 were not involved.";
+        assert_eq!(
+            db.normalize_report(report),
+            "The Sodinokibi (REvil) operators resurfaced; Fancy Bear and APT28 were not involved."
+        );
+    }
+}
@@ -5,9 +5,13 @@
 //! - Threat actor information
 //! - Cryptocurrency addresses
 //! - Malware names and TTPs
+//! - Infrastructure fingerprints: X.509 certificates, SSH host keys, JA3/JA4
+//! - Tor relay fingerprints and bridge lines
 
+use base64::Engine;
 use regex::Regex;
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use std::collections::HashSet;
 use std::sync::LazyLock;
 
@@ -53,6 +57,20 @@ pub enum ArtifactType {
     Phone,
     /// Credit card number (redacted)
     CreditCard,
+    /// Perceptual (difference) hash of an image
+    ImageHash,
+    /// X.509 certificate fingerprint (SHA-1 or SHA-256, lowercase hex)
+    CertFingerprint,
+    /// SSH host key fingerprint (`SHA256:<base64>`, or legacy MD5 `aa:bb:...`)
+    SshHostKey,
+    /// JA3/JA3S TLS fingerprint
+    Ja3,
+    /// JA4+ fingerprint (JA4, JA4S, JA4H)
+    Ja4,
+    /// Tor relay fingerprint (40 uppercase hex digits)
+    TorRelay,
+    /// Tor bridge line: `[transport] address:port FINGERPRINT`, without its secrets
+    TorBridge,
     /// Custom/unknown
     Custom(String),
 }
@@ -152,6 +170,61 @@ static URL_REGEX: LazyLock<Regex> = LazyLock::new(|| {
     Regex::new(r#"https?://[^\s<>"']+"#).unwrap()
 });
 
+static PEM_CERT_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"-----BEGIN CERTIFICATE-----([A-Za-z0-9+/=\s]+?)-----END CERTIFICATE-----").unwrap()
+});
+
+/// Colon-separated hex runs: 16 bytes is an MD5 SSH fingerprint, 20/32 a certificate's
+static COLON_HEX_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\b(?:[0-9A-Fa-f]{2}:){15,}[0-9A-Fa-f]{2}\b").unwrap()
+});
+
+/// Plain hex only counts as a certificate fingerprint next to a label
+static CERT_LABELED_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"(?i)\b(?:x\.?509|ssl|tls|cert(?:ificate)?)[\w .-]{0,24}?(?:fingerprint|thumbprint|sha-?1|sha-?256)["']?\s*[:=]?\s*["']?([0-9a-f]{64}|[0-9a-f]{40})\b"#).unwrap()
+});
+
+static SSH_FINGERPRINT_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\bSHA256:[A-Za-z0-9+/]{43}").unwrap()
+});
+
+static SSH_KEY_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\b(?:ssh-(?:rsa|ed25519|dss)|ecdsa-sha2-nistp(?:256|384|521)|sk-ssh-ed25519@openssh\.com) (AAAA[A-Za-z0-9+/]+={0,3})").unwrap()
+});
+
+/// JA3 hashes are plain MD5, so they need a label too
+static JA3_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"(?i)\bja3s?(?:[ _-]?(?:hash|fingerprint|digest))?["']?\s*[:=]?\s*["']?([0-9a-f]{32})\b"#).unwrap()
+});
+
+/// JA4 (`t13d1516h2_8daaf6152771_b0da82dd1658`), JA4S and JA4H
+static JA4_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(concat!(
+        r"\b(?:[tqd]\d{2}[di]\d{4}[a-z0-9]{2}_[0-9a-f]{12}_[0-9a-f]{12}",
+        r"|[tq]\d{4}[a-z0-9]{2}_[0-9a-f]{4}_[0-9a-f]{12}",
+        r"|[a-z]{2}\d{2}[cn][rn]\d{2}[a-z0-9]{4}_[0-9a-f]{12}_[0-9a-f]{12}_[0-9a-f]{12})\b"
+    ))
+    .unwrap()
+});
+
+/// Relay fingerprints as Tor writes them: `$` prefixed, or in groups of four
+static RELAY_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\$([0-9A-Fa-f]{40})\b|\b((?:[0-9A-F]{4} ){9}[0-9A-F]{4})\b").unwrap()
+});
+
+static RELAY_LABELED_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)\b(?:relay|exit|guard)\b[\w ]{0,24}?[:=]?\s*([0-9a-f]{40})\b").unwrap()
+});
+
+/// `Bridge obfs4 192.0.2.1:443 <fingerprint> cert=... iat-mode=0` (the `Bridge` and transport optional)
+static BRIDGE_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(concat!(
+        r"(?:\b(obfs4|obfs3|meek_lite|meek|snowflake|webtunnel|scramblesuit|conjure)\s+)?",
+        r"(\b\d{1,3}(?:\.\d{1,3}){3}:\d{1,5}|\[[0-9A-Fa-f:]+\]:\d{1,5})\s+([0-9A-Fa-f]{40})\b"
+    ))
+    .unwrap()
+});
+
 /// Helper to add artifact if not already seen
 fn try_add_artifact(
     artifacts: &mut Vec<Artifact>,
@@ -172,11 +245,117 @@ fn try_add_artifact(
     }
 }
 
+/// Mark hex `value` as a fingerprint, so it isn't also taken as a file hash
+fn take_hash(seen: &mut HashSet<String>, value: &str) {
+    seen.insert(format!("fingerprint:{}", value.to_lowercase()));
+}
+
+/// Whether a hex hash was already taken as a fingerprint
+fn is_fingerprint(seen: &HashSet<String>, value: &str) -> bool {
+    seen.contains(&format!("fingerprint:{}", value.to_lowercase()))
+}
+
+/// `SHA256:<base64>` fingerprint of an SSH public key blob, as `ssh-keygen -l` prints it
+fn ssh_key_fingerprint(blob: &str) -> Option<String> {
+    let key = base64::engine::general_purpose::STANDARD.decode(blob).ok()?;
+    let digest = Sha256::digest(&key);
+    Some(format!("SHA256:{}", base64::engine::general_purpose::STANDARD_NO_PAD.encode(digest)))
+}
+
+/// SHA-256 fingerprint of a PEM certificate body
+fn pem_cert_fingerprint(body: &str) -> Option<String> {
+    let body: String = body.chars().filter(|c| !c.is_whitespace()).collect();
+    let der = base64::engine::general_purpose::STANDARD.decode(body).ok()?;
+    Some(Sha256::digest(&der).iter().map(|b| format!("{:02x}", b)).collect())
+}
+
+fn add_fingerprints(text: &str, source: Option<&str>, artifacts: &mut Vec<Artifact>, seen: &mut HashSet<String>) {
+    for cap in PEM_CERT_REGEX.captures_iter(text) {
+        if let Some(fingerprint) = pem_cert_fingerprint(&cap[1]) {
+            try_add_artifact(artifacts, seen, ArtifactType::CertFingerprint, &fingerprint, 1.0, source);
+        }
+    }
+    for cap in COLON_HEX_REGEX.find_iter(text) {
+        let value = cap.as_str().to_lowercase();
+        match value.len() {
+            47 => try_add_artifact(artifacts, seen, ArtifactType::SshHostKey, &value, 0.8, source),
+            59 | 95 => {
+                let hex = value.replace(':', "");
+                try_add_artifact(artifacts, seen, ArtifactType::CertFingerprint, &hex, 0.8, source);
+            }
+            _ => {}
+        }
+    }
+    for cap in CERT_LABELED_REGEX.captures_iter(text) {
+        take_hash(seen, &cap[1]);
+        try_add_artifact(artifacts, seen, ArtifactType::CertFingerprint, &cap[1].to_lowercase(), 0.9, source);
+    }
+    for cap in SSH_FINGERPRINT_REGEX.find_iter(text) {
+        // Longer base64 runs are something else
+        let next = text[cap.end()..].chars().next();
+        if !next.is_some_and(|c| c.is_ascii_alphanumeric() || c == '+' || c == '/') {
+            try_add_artifact(artifacts, seen, ArtifactType::SshHostKey, cap.as_str(), 0.95, source);
+        }
+    }
+    for cap in SSH_KEY_REGEX.captures_iter(text) {
+        if let Some(fingerprint) = ssh_key_fingerprint(&cap[1]) {
+            try_add_artifact(artifacts, seen, ArtifactType::SshHostKey, &fingerprint, 1.0, source);
+        }
+    }
+    for cap in JA3_REGEX.captures_iter(text) {
+        take_hash(seen, &cap[1]);
+        try_add_artifact(artifacts, seen, ArtifactType::Ja3, &cap[1].to_lowercase(), 0.9, source);
+    }
+    for cap in JA4_REGEX.find_iter(text) {
+        try_add_artifact(artifacts, seen, ArtifactType::Ja4, cap.as_str(), 0.9, source);
+    }
+
+    // Bridge lines first, so their fingerprints aren't also taken as relays
+    for cap in BRIDGE_REGEX.captures_iter(text) {
+        let fingerprint = cap[3].to_uppercase();
+        take_hash(seen, &fingerprint);
+        let line = match cap.get(1) {
+            Some(transport) => format!("{} {} {}", transport.as_str(), &cap[2], fingerprint),
+            None => format!("{} {}", &cap[2], fingerprint),
+        };
+        try_add_artifact(artifacts, seen, ArtifactType::TorBridge, &line, 0.9, source);
+    }
+    for cap in RELAY_REGEX.captures_iter(text) {
+        let fingerprint = cap.get(1).or(cap.get(2)).map(|m| m.as_str().replace(' ', "").to_uppercase());
+        if let Some(fingerprint) = fingerprint.filter(|fp| !is_fingerprint(seen, fp)) {
+            take_hash(seen, &fingerprint);
+            try_add_artifact(artifacts, seen, ArtifactType::TorRelay, &fingerprint, 0.95, source);
+        }
+    }
+    for cap in RELAY_LABELED_REGEX.captures_iter(text) {
+        if !is_fingerprint(seen, &cap[1]) {
+            take_hash(seen, &cap[1]);
+            try_add_artifact(artifacts, seen, ArtifactType::TorRelay, &cap[1].to_uppercase(), 0.85, source);
+        }
+    }
+}
+
+/// Extract certificate, SSH host key, JA3/JA4 and Tor relay fingerprints, and Tor bridge lines, from text
+///
+/// PEM certificates and SSH public keys are reduced to the fingerprints
+/// search engines index them by. Bare hex is only taken next to a label
+/// (`JA3:`, `SHA256 Fingerprint=`, `exit relay`), since it could be any
+/// hash. Bridge lines keep the transport, address and fingerprint but not
+/// their parameters (`cert=`), which grant access to the bridge.
+pub fn extract_fingerprints(text: &str, source: Option<&str>) -> Vec<Artifact> {
+    let mut artifacts = Vec::new();
+    add_fingerprints(text, source, &mut artifacts, &mut HashSet::new());
+    artifacts
+}
+
 /// Extract all artifacts from text content
 pub fn extract_artifacts(text: &str, source: Option<&str>) -> Vec<Artifact> {
     let mut artifacts = Vec::new();
     let mut seen: HashSet<String> = HashSet::new();
 
+    // Extract infrastructure fingerprints first, so their hashes aren't taken as file hashes
+    add_fingerprints(text, source, &mut artifacts, &mut seen);
+
     // Extract onion addresses (high priority for dark web)
     for cap in ONION_REGEX.find_iter(text) {
         try_add_artifact(&mut artifacts, &mut seen, ArtifactType::OnionAddress, cap.as_str(), 1.0, source);
@@ -200,17 +379,21 @@ pub fn extract_artifacts(text: &str, source: Option<&str>) -> Vec<Artifact> {
 
     // Extract hashes (order matters: SHA256 > SHA1 > MD5)
     for cap in SHA256_REGEX.find_iter(text) {
-        try_add_artifact(&mut artifacts, &mut seen, ArtifactType::Sha256, cap.as_str(), 0.9, source);
+        if !is_fingerprint(&seen, cap.as_str()) {
+            try_add_artifact(&mut artifacts, &mut seen, ArtifactType::Sha256, cap.as_str(), 0.9, source);
+        }
     }
     for cap in SHA1_REGEX.find_iter(text) {
         // Skip if already matched as SHA256
         let sha256_key = format!("{:?}:{}", ArtifactType::Sha256, cap.as_str().to_lowercase());
-        if !seen.contains(&sha256_key) {
+        if !seen.contains(&sha256_key) && !is_fingerprint(&seen, cap.as_str()) {
             try_add_artifact(&mut artifacts, &mut seen, ArtifactType::Sha1, cap.as_str(), 0.85, source);
         }
     }
     for cap in MD5_REGEX.find_iter(text) {
-        try_add_artifact(&mut artifacts, &mut seen, ArtifactType::Md5, cap.as_str(), 0.8, source);
+        if !is_fingerprint(&seen, cap.as_str()) {
+            try_add_artifact(&mut artifacts, &mut seen, ArtifactType::Md5, cap.as_str(), 0.8, source);
+        }
     }
 
     // Extract CVEs
@@ -282,6 +465,66 @@ mod tests {
         assert!(artifacts.iter().any(|a| a.artifact_type == ArtifactType::Cve));
     }
 
+    #[test]
+    fn test_extract_fingerprints() {
+        let text = "C2 cert SHA256 Fingerprint=0A1B2C3D4E5F60718293A4B5C6D7E8F90A1B2C3D4E5F60718293A4B5C6D7E8F9\n\
+                    ja3_hash: e7d705a3286e19ea42f587b344ee6865 ja4 t13d1516h2_8daaf6152771_b0da82dd1658\n\
+                    host key 16:27:ac:a5:76:28:2d:36:63:1b:56:4d:eb:df:a6:48\n\
+                    ssh-ed25519 AAAAC3NzaC1lZDI1NTE5AAAAIOMqqnkVzrm0SdG6UOoqKLsabgH5C9okWi0dh2l9GKJl root@c2\n\
+                    unrelated d41d8cd98f00b204e9800998ecf8427e";
+        let artifacts = extract_artifacts(text, None);
+        let of = |t: ArtifactType| -> Vec<&str> {
+            artifacts.iter().filter(|a| a.artifact_type == t).map(|a| a.value.as_str()).collect()
+        };
+        assert_eq!(of(ArtifactType::CertFingerprint), vec!["0a1b2c3d4e5f60718293a4b5c6d7e8f90a1b2c3d4e5f60718293a4b5c6d7e8f9"]);
+        assert_eq!(of(ArtifactType::Ja3), vec!["e7d705a3286e19ea42f587b344ee6865"]);
+        assert_eq!(of(ArtifactType::Ja4), vec!["t13d1516h2_8daaf6152771_b0da82dd1658"]);
+        let ssh = of(ArtifactType::SshHostKey);
+        assert_eq!(ssh[0], "16:27:ac:a5:76:28:2d:36:63:1b:56:4d:eb:df:a6:48");
+        assert!(ssh[1].starts_with("SHA256:") && ssh[1].len() == 50);
+        // Labeled hashes aren't also file hashes; unlabeled ones still are
+        assert_eq!(of(ArtifactType::Md5), vec!["d41d8cd98f00b204e9800998ecf8427e"]);
+        assert!(of(ArtifactType::Sha256).is_empty());
+
+        let pem = "-----BEGIN CERTIFICATE-----\nMIIBszCCAVmgAwIBAgIU\n-----END CERTIFICATE-----";
+        let certs = extract_fingerprints(pem, Some("https://crt.example/"));
+        assert_eq!(certs.len(), 1);
+        assert_eq!(certs[0].value.len(), 64);
+        assert_eq!(certs[0].source.as_deref(), Some("https://crt.example/"));
+    }
+
+    #[test]
+    fn test_extract_tor_relays_and_bridges() {
+        let fp = "9695DFC35FFEB861329B9F1AB04C46397020CE31";
+        let text = format!(
+            "exit relay {}\nuse $A4FA4A4B2C2AE7B9D6CFC1C3F0B5F2A9B1E5B3C7~nick\n\
+             fingerprint 7EA6 EAD6 FD83 083C 538F 4403 8BBF A077 587D D755\n\
+             Bridge obfs4 192.0.2.10:443 2B280B23E1107BB62ABFC40DDCC8824814F80A72 cert=abc iat-mode=0\n\
+             [2001:db8::1]:9001 C5B7CD6946FF10C5B3E89691A7D3F2C122D2117C\n\
+             unrelated sha1 da39a3ee5e6b4b0d3255bfef95601890afd80709",
+            fp.to_lowercase()
+        );
+        let artifacts = extract_artifacts(&text, None);
+        let of = |t: ArtifactType| -> Vec<&str> {
+            artifacts.iter().filter(|a| a.artifact_type == t).map(|a| a.value.as_str()).collect()
+        };
+        assert_eq!(
+            of(ArtifactType::TorBridge),
+            vec![
+                "obfs4 192.0.2.10:443 2B280B23E1107BB62ABFC40DDCC8824814F80A72",
+                "[2001:db8::1]:9001 C5B7CD6946FF10C5B3E89691A7D3F2C122D2117C"
+            ]
+        );
+        let mut relays = of(ArtifactType::TorRelay);
+        relays.sort();
+        assert_eq!(
+            relays,
+            vec!["7EA6EAD6FD83083C538F44038BBFA077587DD755", fp, "A4FA4A4B2C2AE7B9D6CFC1C3F0B5F2A9B1E5B3C7"]
+        );
+        assert_eq!(of(ArtifactType::Sha1), vec!["da39a3ee5e6b4b0d3255bfef95601890afd80709"]);
+        assert!(artifacts.iter().all(|a| !a.value.contains("cert=")));
+    }
+
     #[test]
     fn test_extract_email() {
         let text = "Contact admin@darkmarket.onion for support";
@@ -0,0 +1,275 @@
+//! Encryption at rest for local case data
+//!
+//! Case stores, checkpoints and archived content can be sealed with
+//! XChaCha20-Poly1305 under either a 32-byte keyfile or a passphrase
+//! (stretched with Argon2id, salt stored per file). Sealed data is
+//! self-describing, so readers can tell encrypted files from plain ones.
+//!
+//! Layout: `MAGIC | mode | salt (passphrase only) | nonce | ciphertext`,
+//! with everything before the ciphertext authenticated as associated data.
+
+use std::path::Path;
+
+use argon2::Argon2;
+use chacha20poly1305::aead::{rand_core::RngCore, Aead, AeadCore, KeyInit, OsRng, Payload};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+use thiserror::Error;
+
+/// Prefix identifying sealed data (format version 1)
+pub const SEALED_MAGIC: &[u8; 7] = b"RSMESH\x01";
+
+const MODE_KEYFILE: u8 = 0;
+const MODE_PASSPHRASE: u8 = 1;
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 24;
+const KEY_LEN: usize = 32;
+
+/// Errors from sealing and opening data
+#[derive(Debug, Error)]
+pub enum AtRestError {
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Invalid key: {0}")]
+    InvalidKey(String),
+
+    #[error("Data is not sealed (missing header)")]
+    NotSealed,
+
+    #[error("Sealed data is truncated or has an unknown format")]
+    Malformed,
+
+    #[error("Decryption failed (wrong key or tampered data)")]
+    Decrypt,
+
+    #[error("Key derivation failed: {0}")]
+    Kdf(String),
+}
+
+/// Secret used to seal and open data
+#[derive(Clone)]
+pub enum AtRestKey {
+    /// Raw 256-bit key (from a keyfile)
+    Key([u8; KEY_LEN]),
+    /// Passphrase, stretched per file with Argon2id
+    Passphrase(String),
+}
+
+impl std::fmt::Debug for AtRestKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AtRestKey::Key(_) => write!(f, "AtRestKey::Key(..)"),
+            AtRestKey::Passphrase(_) => write!(f, "AtRestKey::Passphrase(..)"),
+        }
+    }
+}
+
+impl AtRestKey {
+    /// Key from a passphrase
+    pub fn passphrase(passphrase: impl Into<String>) -> Self {
+        AtRestKey::Passphrase(passphrase.into())
+    }
+
+    /// Load a keyfile holding 32 raw bytes or 64 hex characters
+    ///
+    /// Create one with e.g. `head -c 32 /dev/urandom > case.key`.
+    pub fn from_keyfile(path: impl AsRef<Path>) -> Result<Self, AtRestError> {
+        let data = std::fs::read(path.as_ref())?;
+        if data.len() == KEY_LEN {
+            let mut key = [0u8; KEY_LEN];
+            key.copy_from_slice(&data);
+            return Ok(AtRestKey::Key(key));
+        }
+
+        let text = std::str::from_utf8(&data).map(str::trim).unwrap_or_default();
+        if text.len() == KEY_LEN * 2 && text.bytes().all(|b| b.is_ascii_hexdigit()) {
+            let mut key = [0u8; KEY_LEN];
+            for (i, byte) in key.iter_mut().enumerate() {
+                *byte = u8::from_str_radix(&text[i * 2..i * 2 + 2], 16)
+                    .map_err(|e| AtRestError::InvalidKey(e.to_string()))?;
+            }
+            return Ok(AtRestKey::Key(key));
+        }
+
+        Err(AtRestError::InvalidKey(format!(
+            "{} must hold 32 raw bytes or 64 hex characters",
+            path.as_ref().display()
+        )))
+    }
+}
+
+impl AtRestKey {
+    /// Key for sealing many small records under one `salt`
+    ///
+    /// A passphrase is stretched once here rather than per record.
+    pub fn stretched(&self, salt: &[u8]) -> Result<AtRestKey, AtRestError> {
+        match self {
+            AtRestKey::Key(raw) => Ok(AtRestKey::Key(*raw)),
+            AtRestKey::Passphrase(passphrase) => Ok(AtRestKey::Key(derive_key(passphrase, salt)?)),
+        }
+    }
+}
+
+/// Fresh random salt for [`AtRestKey::stretched`]
+pub fn random_salt() -> [u8; SALT_LEN] {
+    let mut salt = [0u8; SALT_LEN];
+    OsRng.fill_bytes(&mut salt);
+    salt
+}
+
+/// Whether `data` carries the sealed-data header
+pub fn is_sealed(data: &[u8]) -> bool {
+    data.starts_with(SEALED_MAGIC)
+}
+
+/// Encrypt and authenticate `plaintext`
+pub fn seal(key: &AtRestKey, plaintext: &[u8]) -> Result<Vec<u8>, AtRestError> {
+    let mut header = SEALED_MAGIC.to_vec();
+    let cipher_key = match key {
+        AtRestKey::Key(raw) => {
+            header.push(MODE_KEYFILE);
+            *raw
+        }
+        AtRestKey::Passphrase(passphrase) => {
+            let mut salt = [0u8; SALT_LEN];
+            OsRng.fill_bytes(&mut salt);
+            header.push(MODE_PASSPHRASE);
+            header.extend_from_slice(&salt);
+            derive_key(passphrase, &salt)?
+        }
+    };
+
+    let cipher = XChaCha20Poly1305::new(&cipher_key.into());
+    let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+    header.extend_from_slice(&nonce);
+
+    let ciphertext = cipher
+        .encrypt(&nonce, Payload { msg: plaintext, aad: &header })
+        .map_err(|_| AtRestError::Decrypt)?;
+    header.extend_from_slice(&ciphertext);
+    Ok(header)
+}
+
+/// Verify and decrypt data produced by [`seal`]
+pub fn open(key: &AtRestKey, data: &[u8]) -> Result<Vec<u8>, AtRestError> {
+    if !is_sealed(data) {
+        return Err(AtRestError::NotSealed);
+    }
+    let rest = &data[SEALED_MAGIC.len()..];
+    let (&mode, rest) = rest.split_first().ok_or(AtRestError::Malformed)?;
+
+    let salt_len = match mode {
+        MODE_KEYFILE => 0,
+        MODE_PASSPHRASE => SALT_LEN,
+        _ => return Err(AtRestError::Malformed),
+    };
+    if rest.len() < salt_len + NONCE_LEN {
+        return Err(AtRestError::Malformed);
+    }
+    let (salt, rest) = rest.split_at(salt_len);
+    let (nonce, ciphertext) = rest.split_at(NONCE_LEN);
+    let header = &data[..data.len() - ciphertext.len()];
+
+    let cipher_key = match (key, mode) {
+        (AtRestKey::Key(raw), MODE_KEYFILE) => *raw,
+        (AtRestKey::Passphrase(passphrase), MODE_PASSPHRASE) => derive_key(passphrase, salt)?,
+        (AtRestKey::Key(_), _) => {
+            return Err(AtRestError::InvalidKey("data was sealed with a passphrase".to_string()))
+        }
+        (AtRestKey::Passphrase(_), _) => {
+            return Err(AtRestError::InvalidKey("data was sealed with a keyfile".to_string()))
+        }
+    };
+
+    XChaCha20Poly1305::new(&cipher_key.into())
+        .decrypt(XNonce::from_slice(nonce), Payload { msg: ciphertext, aad: header })
+        .map_err(|_| AtRestError::Decrypt)
+}
+
+/// Seal `plaintext` and write it to `path`
+pub fn write_sealed(path: impl AsRef<Path>, key: &AtRestKey, plaintext: &[u8]) -> Result<(), AtRestError> {
+    std::fs::write(path, seal(key, plaintext)?)?;
+    Ok(())
+}
+
+/// Read `path`, opening it if sealed
+///
+/// Plain files are returned as-is, so stores can be migrated to encryption
+/// without rewriting existing data up front.
+pub fn read_maybe_sealed(path: impl AsRef<Path>, key: Option<&AtRestKey>) -> Result<Vec<u8>, AtRestError> {
+    let data = std::fs::read(path)?;
+    match key {
+        Some(key) if is_sealed(&data) => open(key, &data),
+        None if is_sealed(&data) => Err(AtRestError::InvalidKey(
+            "data is encrypted but no key was given".to_string(),
+        )),
+        _ => Ok(data),
+    }
+}
+
+fn derive_key(passphrase: &str, salt: &[u8]) -> Result<[u8; KEY_LEN], AtRestError> {
+    let mut key = [0u8; KEY_LEN];
+    Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|e| AtRestError::Kdf(e.to_string()))?;
+    Ok(key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keyfile_roundtrip_and_tamper() {
+        let key = AtRestKey::Key([7u8; 32]);
+        let sealed = seal(&key, b"case notes: 1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa").unwrap();
+
+        assert!(is_sealed(&sealed));
+        assert!(!sealed.windows(4).any(|w| w == b"case"));
+        assert_eq!(open(&key, &sealed).unwrap(), b"case notes: 1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa");
+
+        let mut tampered = sealed.clone();
+        *tampered.last_mut().unwrap() ^= 1;
+        assert!(matches!(open(&key, &tampered), Err(AtRestError::Decrypt)));
+        assert!(matches!(open(&AtRestKey::Key([8u8; 32]), &sealed), Err(AtRestError::Decrypt)));
+        assert!(matches!(open(&key, b"plain text"), Err(AtRestError::NotSealed)));
+    }
+
+    #[test]
+    fn test_passphrase_roundtrip() {
+        let key = AtRestKey::passphrase("correct horse battery staple");
+        let sealed = seal(&key, b"checkpoint").unwrap();
+
+        assert_eq!(open(&key, &sealed).unwrap(), b"checkpoint");
+        assert!(open(&AtRestKey::passphrase("wrong"), &sealed).is_err());
+        assert!(matches!(open(&AtRestKey::Key([0u8; 32]), &sealed), Err(AtRestError::InvalidKey(_))));
+    }
+
+    #[test]
+    fn test_keyfile_formats() {
+        let dir = std::env::temp_dir().join(format!("robin-at-rest-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let raw = dir.join("raw.key");
+        std::fs::write(&raw, [0xABu8; 32]).unwrap();
+        let hex = dir.join("hex.key");
+        std::fs::write(&hex, format!("{}\n", "ab".repeat(32))).unwrap();
+        let bad = dir.join("bad.key");
+        std::fs::write(&bad, "too short").unwrap();
+
+        assert!(matches!(AtRestKey::from_keyfile(&raw).unwrap(), AtRestKey::Key(k) if k == [0xAB; 32]));
+        assert!(matches!(AtRestKey::from_keyfile(&hex).unwrap(), AtRestKey::Key(k) if k == [0xAB; 32]));
+        assert!(AtRestKey::from_keyfile(&bad).is_err());
+
+        let key = AtRestKey::from_keyfile(&raw).unwrap();
+        let plain = dir.join("plain.json");
+        std::fs::write(&plain, "{}").unwrap();
+        let sealed = dir.join("sealed.json");
+        write_sealed(&sealed, &key, b"{\"case\":1}").unwrap();
+        assert_eq!(read_maybe_sealed(&plain, Some(&key)).unwrap(), b"{}");
+        assert_eq!(read_maybe_sealed(&sealed, Some(&key)).unwrap(), b"{\"case\":1}");
+        assert!(read_maybe_sealed(&sealed, None).is_err());
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
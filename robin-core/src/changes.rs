@@ -0,0 +1,251 @@
+//! Change detection for re-scraped pages
+//!
+//! Watching a leak site or marketplace over time means noticing when a
+//! page changes: a new victim listed, a price raised, a vendor gone. A
+//! [`ChangeStore`] keeps a hash of each scraped page's normalized text
+//! (and the text itself, to diff against) across runs. When a page comes
+//! back with a different hash, [`ChangeStore::check`] returns a
+//! [`ContentDiff`] summarizing which lines appeared and disappeared.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashSet};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use thiserror::Error;
+
+use crate::{read_maybe_sealed, write_sealed, AtRestError, AtRestKey};
+
+/// A change store shared by change detectors and whoever saves it
+pub type SharedChanges = Arc<Mutex<ChangeStore>>;
+
+/// Lines of a page kept to diff the next version against
+const MAX_STORED_LINES: usize = 2000;
+
+/// Added and removed lines quoted in a diff summary
+const SAMPLE_LINES: usize = 3;
+
+/// Characters of each quoted line
+const SAMPLE_CHARS: usize = 120;
+
+/// Errors loading or saving a change store
+#[derive(Debug, Error)]
+pub enum ChangeError {
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Invalid change store: {0}")]
+    Parse(#[from] serde_json::Error),
+
+    #[error("Encryption error: {0}")]
+    Sealed(#[from] AtRestError),
+}
+
+/// Page text reduced to what matters for change detection
+///
+/// Whitespace runs collapse to one space and blank lines go, so reflowed
+/// markup or a changed indentation doesn't count as a change.
+pub fn normalize_content(text: &str) -> Vec<String> {
+    text.lines()
+        .map(|line| line.split_whitespace().collect::<Vec<_>>().join(" "))
+        .filter(|line| !line.is_empty())
+        .collect()
+}
+
+/// Hex SHA-256 of the normalized text
+pub fn content_hash(text: &str) -> String {
+    hash_lines(&normalize_content(text))
+}
+
+fn hash_lines(lines: &[String]) -> String {
+    let mut hasher = Sha256::new();
+    for line in lines {
+        hasher.update(line.as_bytes());
+        hasher.update(b"\n");
+    }
+    hasher.finalize().iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// The last version seen of a page
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PageVersion {
+    /// Hash of the normalized text
+    pub hash: String,
+    /// Normalized lines (the first [`MAX_STORED_LINES`])
+    pub lines: Vec<String>,
+    pub first_seen: DateTime<Utc>,
+    /// When the current version was first seen
+    pub changed_at: DateTime<Utc>,
+}
+
+/// What changed between two versions of a page
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContentDiff {
+    pub previous_hash: String,
+    pub hash: String,
+    /// When the previous version was first seen
+    pub previous_at: DateTime<Utc>,
+    /// Lines in the new version only
+    pub added: Vec<String>,
+    /// Lines in the previous version only
+    pub removed: Vec<String>,
+}
+
+impl ContentDiff {
+    /// One-paragraph summary quoting a few added and removed lines
+    pub fn summary(&self) -> String {
+        let quote = |lines: &[String]| {
+            lines
+                .iter()
+                .take(SAMPLE_LINES)
+                .map(|line| {
+                    let short: String = line.chars().take(SAMPLE_CHARS).collect();
+                    if short.len() < line.len() {
+                        format!("\"{}…\"", short)
+                    } else {
+                        format!("\"{}\"", short)
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        let mut summary = format!("+{} / -{} lines since {}", self.added.len(), self.removed.len(), self.previous_at.format("%Y-%m-%d"));
+        if !self.added.is_empty() {
+            summary.push_str(&format!("; added {}", quote(&self.added)));
+        }
+        if !self.removed.is_empty() {
+            summary.push_str(&format!("; removed {}", quote(&self.removed)));
+        }
+        summary
+    }
+}
+
+/// Outcome of checking a scraped page against its last version
+#[derive(Debug, Clone, PartialEq)]
+pub enum PageCheck {
+    /// Not seen before
+    New,
+    Unchanged,
+    Changed(ContentDiff),
+}
+
+/// Last-seen versions of scraped pages, by URL
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ChangeStore {
+    #[serde(default)]
+    pages: BTreeMap<String, PageVersion>,
+}
+
+impl ChangeStore {
+    /// Load a store written by [`ChangeStore::save`]; a missing file is an empty store
+    pub fn load(path: impl AsRef<Path>, key: Option<&AtRestKey>) -> Result<Self, ChangeError> {
+        let path = path.as_ref();
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        Ok(serde_json::from_slice(&read_maybe_sealed(path, key)?)?)
+    }
+
+    /// Write the store as JSON, sealed when a key is given
+    pub fn save(&self, path: impl AsRef<Path>, key: Option<&AtRestKey>) -> Result<(), ChangeError> {
+        let json = serde_json::to_vec_pretty(self)?;
+        match key {
+            Some(key) => write_sealed(path, key, &json)?,
+            None => std::fs::write(path, json)?,
+        }
+        Ok(())
+    }
+
+    /// Compare `text` with the last version of `url` and remember it as the current one
+    pub fn check(&mut self, url: &str, text: &str) -> PageCheck {
+        let mut lines = normalize_content(text);
+        let hash = hash_lines(&lines);
+        lines.truncate(MAX_STORED_LINES);
+        let now = Utc::now();
+
+        let Some(page) = self.pages.get_mut(url) else {
+            self.pages.insert(
+                url.to_string(),
+                PageVersion {
+                    hash,
+                    lines,
+                    first_seen: now,
+                    changed_at: now,
+                },
+            );
+            return PageCheck::New;
+        };
+        if page.hash == hash {
+            return PageCheck::Unchanged;
+        }
+
+        let old: HashSet<&String> = page.lines.iter().collect();
+        let new: HashSet<&String> = lines.iter().collect();
+        let diff = ContentDiff {
+            previous_hash: page.hash.clone(),
+            hash: hash.clone(),
+            previous_at: page.changed_at,
+            added: lines.iter().filter(|l| !old.contains(l)).cloned().collect(),
+            removed: page.lines.iter().filter(|l| !new.contains(l)).cloned().collect(),
+        };
+        page.hash = hash;
+        page.lines = lines;
+        page.changed_at = now;
+        PageCheck::Changed(diff)
+    }
+
+    /// Last version seen of `url`
+    pub fn get(&self, url: &str) -> Option<&PageVersion> {
+        self.pages.get(url)
+    }
+
+    /// Forget pages unchanged since before `cutoff`; returns how many
+    pub fn prune(&mut self, cutoff: DateTime<Utc>) -> usize {
+        let before = self.pages.len();
+        self.pages.retain(|_, page| page.changed_at >= cutoff);
+        before - self.pages.len()
+    }
+
+    /// Number of pages tracked
+    pub fn len(&self) -> usize {
+        self.pages.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pages.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detects_changes_across_runs() {
+        assert_eq!(content_hash("Victims:\n  acme  corp\n\n"), content_hash("Victims:\nacme corp"));
+
+        let path = std::env::temp_dir().join(format!("robin-changes-{}.json", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let url = "http://leaks.onion/";
+
+        let mut store = ChangeStore::load(&path, None).unwrap();
+        assert_eq!(store.check(url, "Victims:\nacme corp\nglobex"), PageCheck::New);
+        store.save(&path, None).unwrap();
+
+        let mut next_run = ChangeStore::load(&path, None).unwrap();
+        assert_eq!(next_run.check(url, "Victims:\n acme corp \nglobex\n"), PageCheck::Unchanged);
+        let PageCheck::Changed(diff) = next_run.check(url, "Victims:\nacme corp\ninitech - 2 TB") else {
+            panic!("change not detected");
+        };
+        assert_eq!(diff.added, vec!["initech - 2 TB"]);
+        assert_eq!(diff.removed, vec!["globex"]);
+        assert_eq!(diff.hash, next_run.get(url).unwrap().hash);
+        assert!(diff.summary().starts_with("+1 / -1 lines since "));
+        assert!(diff.summary().ends_with("; added \"initech - 2 TB\"; removed \"globex\""));
+
+        assert_eq!(next_run.prune(Utc::now() + chrono::Duration::seconds(1)), 1);
+        assert!(next_run.is_empty());
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,171 @@
+//! Task claim arbitration
+//!
+//! Agents that could each take the same task (a refined query, a URL)
+//! claim it through the field before starting: [`Field::claim_task`] emits
+//! a `TaskClaim` signal and is only accepted if no other agent holds the
+//! task. Claims made without seeing each other (on replicated fields, or
+//! by agents sharing a field across processes) are settled by
+//! [`Field::task_holder`]: the highest affinity wins, then the earliest
+//! claim, then the lowest claimer ID, so every field picks the same
+//! holder. A claim lasts as long as its signal, so the task of an agent
+//! that died is free again once its claim expires. Tasks are claimed per
+//! investigation ([`investigation_task`]), so concurrent investigations
+//! reaching the same URL or query each work on it.
+
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+use crate::{Field, OsintPayload, PayloadKind, Signal};
+
+/// How long a claim holds its task, in seconds
+pub const CLAIM_TTL: f64 = 300.0;
+
+/// Claim ID of `task` (a URL, a query) within an investigation
+pub fn investigation_task(investigation_id: Option<&str>, task: &str) -> String {
+    match investigation_id {
+        Some(id) => format!("{}|{}", id, task),
+        None => task.to_string(),
+    }
+}
+
+/// Result of claiming a task
+#[derive(Debug, Clone, PartialEq)]
+pub enum ClaimOutcome {
+    /// The claimer holds the task and should work on it
+    Accepted,
+    /// Another agent holds the task
+    Held { holder: String },
+}
+
+impl ClaimOutcome {
+    pub fn is_accepted(&self) -> bool {
+        matches!(self, ClaimOutcome::Accepted)
+    }
+}
+
+/// Claim signal fields: task ID, claimer ID, affinity
+fn claim_of(signal: &Signal) -> Option<(&str, &str, f64)> {
+    match &signal.payload {
+        OsintPayload::TaskClaim { task_id, claimer_id, affinity } => Some((task_id, claimer_id, *affinity)),
+        _ => None,
+    }
+}
+
+/// Order of claims on one task, winner first
+fn arbitrate(a: &Signal, b: &Signal) -> Ordering {
+    let (_, a_claimer, a_affinity) = claim_of(a).unwrap_or_default();
+    let (_, b_claimer, b_affinity) = claim_of(b).unwrap_or_default();
+    b_affinity
+        .total_cmp(&a_affinity)
+        .then_with(|| a.created_at.cmp(&b.created_at))
+        .then_with(|| a_claimer.cmp(b_claimer))
+}
+
+impl Field {
+    /// Claim `task_id` for `claimer`
+    ///
+    /// Emits the claim unless another agent already holds the task. A
+    /// claimer holding the task renews its claim by claiming again.
+    pub fn claim_task(&mut self, task_id: &str, claimer: &str, affinity: f64) -> ClaimOutcome {
+        if let Some(holder) = self.task_holder(task_id).filter(|holder| *holder != claimer) {
+            return ClaimOutcome::Held { holder: holder.to_string() };
+        }
+        let signal = Signal::builder(OsintPayload::TaskClaim {
+            task_id: task_id.to_string(),
+            claimer_id: claimer.to_string(),
+            affinity,
+        })
+        .origin(claimer)
+        .ttl(CLAIM_TTL)
+        .build();
+        self.emit(signal);
+        match self.task_holder(task_id) {
+            Some(holder) if holder != claimer => ClaimOutcome::Held { holder: holder.to_string() },
+            _ => ClaimOutcome::Accepted,
+        }
+    }
+
+    /// Agent holding `task_id` after arbitration, if any claim on it is live
+    pub fn task_holder(&self, task_id: &str) -> Option<&str> {
+        self.sense_kinds(&[PayloadKind::TaskClaim], 0.0)
+            .into_iter()
+            .filter(|signal| claim_of(signal).is_some_and(|(task, ..)| task == task_id))
+            .min_by(|a, b| arbitrate(a, b))
+            .and_then(claim_of)
+            .map(|(_, claimer, _)| claimer)
+    }
+
+    /// Holder of every claimed task
+    pub fn task_holders(&self) -> HashMap<&str, &str> {
+        let mut winners: HashMap<&str, &Signal> = HashMap::new();
+        for signal in self.sense_kinds(&[PayloadKind::TaskClaim], 0.0) {
+            let Some((task, ..)) = claim_of(signal) else { continue };
+            let winner = winners.entry(task).or_insert(signal);
+            if arbitrate(signal, winner) == Ordering::Less {
+                *winner = signal;
+            }
+        }
+        winners
+            .into_iter()
+            .filter_map(|(task, signal)| claim_of(signal).map(|(_, claimer, _)| (task, claimer)))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn claim(field: &Field, task: &str, claimer: &str, affinity: f64, secs: i64) -> Signal {
+        let mut signal = Signal::builder(OsintPayload::TaskClaim {
+            task_id: task.to_string(),
+            claimer_id: claimer.to_string(),
+            affinity,
+        })
+        .origin(claimer)
+        .ttl(CLAIM_TTL)
+        .build();
+        signal.created_at = field.now() + chrono::Duration::seconds(secs);
+        signal
+    }
+
+    #[test]
+    fn test_claims_are_exclusive_arbitrated_and_expire() {
+        let mut field = Field::new();
+        assert!(field.claim_task("crawl:lockbit", "crawler-1", 0.5).is_accepted());
+        assert!(field.claim_task("crawl:lockbit", "crawler-1", 0.5).is_accepted());
+        // A held task can't be taken, even with a better affinity
+        assert_eq!(
+            field.claim_task("crawl:lockbit", "crawler-2", 1.0),
+            ClaimOutcome::Held { holder: "crawler-1".to_string() }
+        );
+        assert_eq!(field.task_holder("crawl:lockbit"), Some("crawler-1"));
+        assert_eq!(field.task_holder("crawl:other"), None);
+
+        // Concurrent claims (as merged from a replica): affinity, then time, then ID
+        let mut merged = Field::new();
+        for (task, claimer, affinity, secs) in [
+            ("http://a.onion/", "scraper-2", 0.5, 0),
+            ("http://a.onion/", "scraper-1", 0.9, 1),
+            ("http://b.onion/", "scraper-2", 0.5, 0),
+            ("http://b.onion/", "scraper-1", 0.5, 1),
+            ("http://c.onion/", "scraper-2", 0.5, 0),
+            ("http://c.onion/", "scraper-1", 0.5, 0),
+        ] {
+            let signal = claim(&merged, task, claimer, affinity, secs);
+            merged.emit(signal);
+        }
+        assert_eq!(merged.task_holder("http://a.onion/"), Some("scraper-1"));
+        assert_eq!(merged.task_holder("http://b.onion/"), Some("scraper-2"));
+        assert_eq!(merged.task_holder("http://c.onion/"), Some("scraper-1"));
+        assert!(!merged.claim_task("http://a.onion/", "scraper-2", 0.5).is_accepted());
+        let holders = merged.task_holders();
+        assert_eq!(holders.len(), 3);
+        assert_eq!(holders["http://b.onion/"], "scraper-2");
+
+        // The task is free again once the holder's claim expires
+        field.tick(CLAIM_TTL + 1.0);
+        assert_eq!(field.task_holder("crawl:lockbit"), None);
+        assert!(field.claim_task("crawl:lockbit", "crawler-2", 1.0).is_accepted());
+    }
+}
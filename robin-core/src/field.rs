@@ -3,39 +3,492 @@
 //! The field is the central coordination mechanism in SMESH:
 //! - Agents emit signals into the field
 //! - Signals decay over time
-//! - Agents sense signals matching their interests
+//! - Agents sense signals matching their interests, through a per-kind index
+//!   with intensities cached between ticks
 //! - Reinforcement from multiple agents builds consensus
+//! - An optional journal keeps the field on disk (see [`crate::field_store`])
+//! - Subscribers get a [`FieldEvent`] for every emission, reinforcement and expiry
+//! - Emission hooks can rewrite, veto or add to emitted signals (see [`crate::hooks`])
+//! - A [`SignalPolicy`] can override signal lifetimes per payload kind, and
+//!   cap the signals held, evicting the weakest beyond the caps
 
 use chrono::{DateTime, Utc};
-use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::path::Path;
+use tokio::sync::broadcast;
 
-use crate::{OsintPayload, Signal};
+use crate::field_store::{FieldRecord, FieldStore, Journal};
+use crate::hooks::Hooks;
+use crate::reinforcement::Reinforcement;
+use crate::{
+    add_tag, AtRestKey, EmitContext, EmitVetoed, FieldSnapshot, FieldStoreError, HookAction, OriginHashPolicy, OsintPayload, PayloadKind,
+    SharedHook, Signal, SignalPolicy, FIELD_SNAPSHOT_VERSION, MAX_HOOK_DEPTH, NEVER_EVICTED,
+};
+
+/// Number of buckets in [`FieldStats::intensity_histogram`]
+pub const INTENSITY_BUCKETS: usize = 10;
+
+/// Expired signals a field keeps by default
+pub const DEFAULT_MAX_HISTORY: usize = 10_000;
+
+/// Events buffered per subscriber before the slowest ones start missing events
+pub const FIELD_EVENT_CAPACITY: usize = 1024;
+
+/// A change in the field, delivered to [`Field::subscribe`] receivers
+#[derive(Debug, Clone)]
+pub enum FieldEvent {
+    /// A new signal entered the field
+    SignalEmitted(Box<Signal>),
+    /// An existing signal was reinforced by `agent`
+    SignalReinforced {
+        hash: String,
+        agent: String,
+        /// Reinforcements so far, this one included
+        count: u32,
+    },
+    /// A signal expired and moved to history
+    SignalExpired(Box<Signal>),
+    /// A signal was evicted to history to keep the field within its caps
+    SignalEvicted(Box<Signal>),
+}
+
+/// Event channel of a field, created by the first subscriber
+///
+/// Clones of a field are detached from it, like the journal.
+#[derive(Debug, Default)]
+struct Subscribers(Option<broadcast::Sender<FieldEvent>>);
+
+impl Clone for Subscribers {
+    fn clone(&self) -> Self {
+        Self(None)
+    }
+}
+
+/// Active signals keyed by origin hash, indexed by payload kind
+///
+/// Serializes as the plain map; the index is rebuilt on load, and
+/// intensities missing from the cache are computed when sensed.
+#[derive(Debug, Clone, Default)]
+struct SignalStore {
+    map: HashMap<String, Signal>,
+    by_kind: HashMap<PayloadKind, HashSet<String>>,
+    /// Effective intensity of each signal at the field's current time
+    intensity: HashMap<String, f64>,
+}
+
+impl SignalStore {
+    fn insert(&mut self, hash: String, signal: Signal, now: DateTime<Utc>) {
+        self.intensity.insert(hash.clone(), signal.effective_intensity(now));
+        self.by_kind.entry(signal.payload.kind()).or_default().insert(hash.clone());
+        if let Some(old) = self.map.insert(hash.clone(), signal) {
+            if old.payload.kind() != self.map[&hash].payload.kind() {
+                self.unindex(&hash, old.payload.kind());
+            }
+        }
+    }
+
+    fn remove(&mut self, hash: &str) -> Option<Signal> {
+        let signal = self.map.remove(hash)?;
+        self.intensity.remove(hash);
+        self.unindex(hash, signal.payload.kind());
+        Some(signal)
+    }
+
+    fn unindex(&mut self, hash: &str, kind: PayloadKind) {
+        if let Some(hashes) = self.by_kind.get_mut(&kind) {
+            hashes.remove(hash);
+            if hashes.is_empty() {
+                self.by_kind.remove(&kind);
+            }
+        }
+    }
+
+    fn clear(&mut self) {
+        self.map.clear();
+        self.by_kind.clear();
+        self.intensity.clear();
+    }
+
+    /// Recompute the cached intensity of `hash`
+    fn refresh(&mut self, hash: &str, now: DateTime<Utc>) {
+        if let Some(signal) = self.map.get(hash) {
+            self.intensity.insert(hash.to_string(), signal.effective_intensity(now));
+        }
+    }
+
+    fn intensity(&self, hash: &str, signal: &Signal, now: DateTime<Utc>) -> f64 {
+        self.intensity
+            .get(hash)
+            .copied()
+            .unwrap_or_else(|| signal.effective_intensity(now))
+    }
+
+    fn of_kind(&self, kind: PayloadKind) -> impl Iterator<Item = (&String, &Signal)> {
+        self.by_kind
+            .get(&kind)
+            .into_iter()
+            .flatten()
+            .filter_map(|hash| self.map.get_key_value(hash))
+    }
+}
+
+impl Serialize for SignalStore {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.map.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for SignalStore {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let map = HashMap::<String, Signal>::deserialize(deserializer)?;
+        let mut by_kind: HashMap<PayloadKind, HashSet<String>> = HashMap::new();
+        for (hash, signal) in &map {
+            by_kind.entry(signal.payload.kind()).or_default().insert(hash.clone());
+        }
+        Ok(Self {
+            map,
+            by_kind,
+            intensity: HashMap::new(),
+        })
+    }
+}
 
 /// The shared field where signals exist and propagate
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Field {
     /// Active signals (origin_hash -> Signal)
-    signals: HashMap<String, Signal>,
+    signals: SignalStore,
 
-    /// Signal history for analysis
-    history: Vec<Signal>,
+    /// Expired signals, oldest first
+    history: VecDeque<Signal>,
 
     /// Current simulation time
     current_time: DateTime<Utc>,
 
-    /// Maximum signals to store in history
+    /// Maximum signals to store in history; the oldest make way for new ones
     max_history: usize,
+
+    /// Expired signals pushed out of a full history
+    #[serde(default)]
+    history_dropped: u64,
+
+    /// Signals evicted over the policy's caps, per payload kind
+    #[serde(default)]
+    evicted: BTreeMap<PayloadKind, u64>,
+
+    /// How origin hashes are computed for emitted signals
+    #[serde(default)]
+    hash_policy: OriginHashPolicy,
+
+    /// TTL and decay applied to emitted signals
+    #[serde(default)]
+    signal_policy: SignalPolicy,
+
+    /// Change counter, bumped on every emission or reinforcement
+    #[serde(default)]
+    sequence: u64,
+
+    /// Sequence number of the last change per payload kind
+    #[serde(default)]
+    changed_at: HashMap<PayloadKind, u64>,
+
+    /// On-disk journal changes are appended to
+    #[serde(skip)]
+    journal: Journal,
+
+    /// Receivers of field events
+    #[serde(skip)]
+    subscribers: Subscribers,
+
+    /// Middleware run on every emitted signal
+    #[serde(skip)]
+    hooks: Hooks,
+
+    /// How reinforcements raise confidence
+    #[serde(skip)]
+    pub(crate) reinforcement: Reinforcement,
 }
 
 impl Field {
     /// Create a new field
     pub fn new() -> Self {
         Self {
-            signals: HashMap::new(),
-            history: Vec::new(),
+            signals: SignalStore::default(),
+            history: VecDeque::new(),
             current_time: Utc::now(),
-            max_history: 10000,
+            max_history: DEFAULT_MAX_HISTORY,
+            history_dropped: 0,
+            evicted: BTreeMap::new(),
+            hash_policy: OriginHashPolicy::default(),
+            signal_policy: SignalPolicy::default(),
+            sequence: 0,
+            changed_at: HashMap::new(),
+            journal: Journal::default(),
+            subscribers: Subscribers::default(),
+            hooks: Hooks::default(),
+            reinforcement: Reinforcement::default(),
+        }
+    }
+
+    /// Run `hook` on every signal emitted from now on, after the hooks already added
+    pub fn add_hook(&mut self, hook: SharedHook) {
+        self.hooks.0.push(hook);
+    }
+
+    /// Add an emission hook
+    pub fn with_hook(mut self, hook: SharedHook) -> Self {
+        self.add_hook(hook);
+        self
+    }
+
+    /// Emission hooks, in the order they run
+    ///
+    /// Clones of a field share its hooks; a field loaded or imported has none.
+    pub fn hooks(&self) -> &[SharedHook] {
+        &self.hooks.0
+    }
+
+    /// Receive an event for every signal emitted, reinforced or expired from now on
+    ///
+    /// Heartbeats are included. A receiver more than [`FIELD_EVENT_CAPACITY`]
+    /// events behind skips the oldest and gets `RecvError::Lagged`.
+    pub fn subscribe(&mut self) -> broadcast::Receiver<FieldEvent> {
+        match &self.subscribers.0 {
+            Some(sender) => sender.subscribe(),
+            None => {
+                let (sender, receiver) = broadcast::channel(FIELD_EVENT_CAPACITY);
+                self.subscribers.0 = Some(sender);
+                receiver
+            }
+        }
+    }
+
+    fn notify(&self, event: impl FnOnce() -> FieldEvent) {
+        if let Some(sender) = self.subscribers.0.as_ref().filter(|s| s.receiver_count() > 0) {
+            let _ = sender.send(event());
+        }
+    }
+
+    /// Reload a field journaled with [`Field::persist_to`]
+    ///
+    /// Reinforcements get the confidence they were journaled with, whatever
+    /// model decided it. The journal is replayed, compacted to a snapshot, and kept: the
+    /// loaded field goes on appending to it, sealed under `key` if given
+    /// (a plain journal loaded with a key is sealed from here on). The
+    /// journaled hash policy is kept too.
+    pub fn load(path: impl AsRef<Path>, key: Option<&AtRestKey>) -> Result<Self, FieldStoreError> {
+        let path = path.as_ref();
+        let mut field = Self::new();
+        for record in FieldStore::read(path, key)? {
+            match record {
+                FieldRecord::Snapshot { field: snapshot } => field = *snapshot,
+                // Journaled signals already went through the policy and hooks
+                FieldRecord::Emit { at, signal, confidence } => {
+                    field.current_time = at;
+                    let hash = field.insert(*signal);
+                    field.restore_confidence(&hash, confidence);
+                }
+                FieldRecord::Reinforce { at, hash, agent, confidence } => {
+                    field.current_time = at;
+                    field.reinforce(&hash, &agent);
+                    field.restore_confidence(&hash, confidence);
+                }
+                FieldRecord::Expire { at } => {
+                    field.current_time = at;
+                    field.expire();
+                }
+                FieldRecord::Evict { at, hash } => {
+                    field.current_time = at;
+                    field.evict(&hash);
+                }
+            }
+        }
+        field.refresh_intensities();
+        field.persist_to(path, key)?;
+        Ok(field)
+    }
+
+    /// Versioned copy of the active signals and history
+    pub fn export_snapshot(&self) -> FieldSnapshot {
+        let mut signals: Vec<Signal> = self.signals.map.values().cloned().collect();
+        signals.sort_by(|a, b| a.created_at.cmp(&b.created_at).then_with(|| a.origin_hash.cmp(&b.origin_hash)));
+        FieldSnapshot {
+            version: FIELD_SNAPSHOT_VERSION,
+            exported_at: Utc::now(),
+            field_time: self.current_time,
+            hash_policy: self.hash_policy.clone(),
+            signals,
+            history: self.history.iter().cloned().collect(),
+        }
+    }
+
+    /// Field holding the signals of a snapshot
+    ///
+    /// Every imported signal counts as a change, so agents sense them as new.
+    pub fn import_snapshot(snapshot: FieldSnapshot) -> Result<Self, FieldStoreError> {
+        if snapshot.version > FIELD_SNAPSHOT_VERSION {
+            return Err(FieldStoreError::Version(snapshot.version));
+        }
+        let mut field = Self::new().with_hash_policy(snapshot.hash_policy);
+        field.current_time = snapshot.field_time;
+        field.history = snapshot.history.into();
+        field.trim_history();
+        for signal in snapshot.signals {
+            field.mark_changed(signal.payload.kind());
+            field.signals.insert(signal.origin_hash.clone(), signal, field.current_time);
+        }
+        Ok(field)
+    }
+
+    /// Journal the field to `path` from now on, sealed under `key` if given
+    ///
+    /// The file is replaced with a snapshot of the field as it is now.
+    pub fn persist_to(&mut self, path: impl AsRef<Path>, key: Option<&AtRestKey>) -> Result<(), FieldStoreError> {
+        self.journal = Journal(Some(FieldStore::create(path.as_ref(), self, key)?));
+        Ok(())
+    }
+
+    /// Path of the field's journal, if it has one
+    pub fn store_path(&self) -> Option<&Path> {
+        self.journal.0.as_ref().map(|store| store.path())
+    }
+
+    /// The error that stopped journaling, once
+    ///
+    /// Writes to a journal stop at the first error so it never has gaps.
+    pub fn take_store_error(&mut self) -> Option<std::io::Error> {
+        self.journal.0.as_mut().and_then(|store| store.take_error())
+    }
+
+    fn journal(&mut self, record: impl FnOnce() -> FieldRecord) {
+        if let Some(store) = &mut self.journal.0 {
+            store.append(&record());
+        }
+    }
+
+    /// Use a custom origin-hash policy for emitted signals
+    pub fn with_hash_policy(mut self, policy: OriginHashPolicy) -> Self {
+        self.hash_policy = policy;
+        self
+    }
+
+    /// Origin-hash policy in effect
+    pub fn hash_policy(&self) -> &OriginHashPolicy {
+        &self.hash_policy
+    }
+
+    /// Apply `policy` to the lifetimes of signals emitted from now on
+    pub fn with_signal_policy(mut self, policy: SignalPolicy) -> Self {
+        self.set_signal_policy(policy);
+        self
+    }
+
+    /// Replace the signal policy; signals already in the field keep their lifetimes
+    ///
+    /// Signals beyond the new policy's caps are evicted right away.
+    pub fn set_signal_policy(&mut self, policy: SignalPolicy) {
+        self.signal_policy = policy;
+        self.evict_over_limits();
+    }
+
+    /// Signal policy in effect
+    pub fn signal_policy(&self) -> &SignalPolicy {
+        &self.signal_policy
+    }
+
+    /// Keep up to `max` expired signals in history
+    pub fn with_max_history(mut self, max: usize) -> Self {
+        self.max_history = max;
+        self.trim_history();
+        self
+    }
+
+    /// Expired signals, oldest first (see [`Field::history_query`])
+    pub fn history(&self) -> &VecDeque<Signal> {
+        &self.history
+    }
+
+    /// Expired signals pushed out of a full history so far
+    pub fn history_dropped(&self) -> u64 {
+        self.history_dropped
+    }
+
+    /// Signals evicted over the policy's caps so far
+    pub fn evicted(&self) -> u64 {
+        self.evicted.values().sum()
+    }
+
+    /// Evict the weakest signals of `kind`, then of any kind, beyond the caps
+    ///
+    /// Heartbeats don't count toward the total cap, and [`NEVER_EVICTED`] kinds are kept.
+    fn evict_over(&mut self, kind: PayloadKind) {
+        if let Some(max) = self.signal_policy.kind_limit(kind).filter(|_| !NEVER_EVICTED.contains(&kind)) {
+            while self.signals.by_kind.get(&kind).map_or(0, HashSet::len) > max {
+                match self.weakest(Some(kind)) {
+                    Some(hash) => self.evict(&hash),
+                    None => break,
+                }
+            }
+        }
+        if let Some(max) = self.signal_policy.max_signals {
+            let heartbeats = |field: &Self| field.signals.by_kind.get(&PayloadKind::Heartbeat).map_or(0, HashSet::len);
+            while self.signals.map.len() - heartbeats(self) > max {
+                match self.weakest(None) {
+                    Some(hash) => self.evict(&hash),
+                    None => break,
+                }
+            }
+        }
+    }
+
+    /// Apply every cap of the policy
+    fn evict_over_limits(&mut self) {
+        let kinds: Vec<PayloadKind> = self.signals.by_kind.keys().copied().collect();
+        for kind in kinds {
+            self.evict_over(kind);
+        }
+    }
+
+    /// Hash of the active signal with the lowest effective intensity, oldest first among equals
+    fn weakest(&self, kind: Option<PayloadKind>) -> Option<String> {
+        let now = self.current_time;
+        let rank = |(hash, signal): &(&String, &Signal)| (self.signals.intensity(hash, signal, now), signal.created_at);
+        let candidates: Box<dyn Iterator<Item = (&String, &Signal)>> = match kind {
+            Some(kind) => Box::new(self.signals.of_kind(kind)),
+            None => Box::new(self.signals.map.iter().filter(|(_, signal)| {
+                let kind = signal.payload.kind();
+                kind != PayloadKind::Heartbeat && !NEVER_EVICTED.contains(&kind)
+            })),
+        };
+        candidates
+            .min_by(|a, b| {
+                let (a_intensity, a_created) = rank(a);
+                let (b_intensity, b_created) = rank(b);
+                a_intensity.total_cmp(&b_intensity).then(a_created.cmp(&b_created)).then_with(|| a.0.cmp(b.0))
+            })
+            .map(|(hash, _)| hash.clone())
+    }
+
+    fn evict(&mut self, hash: &str) {
+        if let Some(signal) = self.signals.remove(hash) {
+            let kind = signal.payload.kind();
+            *self.evicted.entry(kind).or_insert(0) += 1;
+            if kind != PayloadKind::Heartbeat {
+                let at = self.current_time;
+                self.journal(|| FieldRecord::Evict { at, hash: hash.to_string() });
+            }
+            self.notify(|| FieldEvent::SignalEvicted(Box::new(signal.clone())));
+            self.history.push_back(signal);
+            self.trim_history();
+        }
+    }
+
+    /// Drop the oldest expired signals beyond the history limit
+    fn trim_history(&mut self) {
+        while self.history.len() > self.max_history {
+            self.history.pop_front();
+            self.history_dropped += 1;
         }
     }
 
@@ -46,61 +499,277 @@ impl Field {
 
     /// Emit a signal into the field
     /// Returns the signal hash (for tracking)
+    ///
+    /// A signal without an investigation joins its parents' (see
+    /// [`Field::sense_in`]). The field's [`SignalPolicy`] sets the
+    /// signal's lifetime, then the signal passes through the field's hooks,
+    /// then its origin hash is recomputed under the field's
+    /// [`OriginHashPolicy`]. A vetoed
+    /// signal's hash is still returned, but [`Field::get`] won't find it;
+    /// use [`Field::try_emit`] to tell.
     pub fn emit(&mut self, signal: Signal) -> String {
-        let hash = signal.origin_hash.clone();
+        self.try_emit(signal).unwrap_or_else(|vetoed| vetoed.hash)
+    }
+
+    /// Emit a signal, failing if a hook vetoes it
+    pub fn try_emit(&mut self, signal: Signal) -> Result<String, EmitVetoed> {
+        self.emit_at_depth(signal, 0)
+    }
+
+    fn emit_at_depth(&mut self, mut signal: Signal, depth: usize) -> Result<String, EmitVetoed> {
+        if signal.investigation_id.is_none() {
+            signal.investigation_id = self.parent_investigation(&signal);
+        }
+        if !self.signal_policy.is_identity() {
+            self.signal_policy.apply(&mut signal);
+        }
+        if self.hooks.0.is_empty() {
+            return Ok(self.insert(signal));
+        }
+
+        let hooks = self.hooks.clone();
+        let mut ctx = EmitContext::new(self, depth);
+        for hook in &hooks.0 {
+            if let HookAction::Veto(reason) = hook.on_emit(&mut signal, &mut ctx) {
+                return Err(EmitVetoed {
+                    hash: self.hash_policy.hash_signal(&signal),
+                    hook: hook.name().to_string(),
+                    reason,
+                });
+            }
+        }
+        let queued = ctx.into_queued();
+
+        let hash = self.insert(signal);
+        if depth < MAX_HOOK_DEPTH {
+            for next in queued {
+                let _ = self.emit_at_depth(next, depth + 1);
+            }
+        }
+        Ok(hash)
+    }
+
+    /// Put a signal that passed the hooks into the field
+    fn insert(&mut self, mut signal: Signal) -> String {
+        let hash = self.hash_policy.hash_signal(&signal);
+        signal.origin_hash = hash.clone();
+        let kind = signal.payload.kind();
+        self.mark_changed(kind);
 
         // Check for existing signal to reinforce
-        if let Some(existing) = self.signals.get_mut(&hash) {
-            existing.reinforce(&signal.origin_agent_id);
+        if let Some(existing) = self.signals.map.get_mut(&hash) {
+            existing.reinforce_with(&signal.origin_agent_id, Some(signal.confidence), self.reinforcement.0.as_ref());
+            // Another derivation of the same signal adds to its provenance
+            for parent in &signal.parent_hashes {
+                if !existing.parent_hashes.contains(parent) {
+                    existing.parent_hashes.push(parent.clone());
+                }
+            }
+            for tag in &signal.tags {
+                add_tag(&mut existing.tags, tag);
+            }
+            let (count, confidence) = (existing.reinforcement_count, existing.confidence);
+            self.signals.refresh(&hash, self.current_time);
+            if kind != PayloadKind::Heartbeat {
+                let at = self.current_time;
+                self.journal(|| FieldRecord::Emit {
+                    at,
+                    signal: Box::new(signal.clone()),
+                    confidence: Some(confidence),
+                });
+            }
+            self.notify(|| FieldEvent::SignalReinforced {
+                hash: hash.clone(),
+                agent: signal.origin_agent_id.clone(),
+                count,
+            });
             return hash;
         }
 
-        self.signals.insert(hash.clone(), signal);
+        if kind != PayloadKind::Heartbeat {
+            let at = self.current_time;
+            self.journal(|| FieldRecord::Emit {
+                at,
+                signal: Box::new(signal.clone()),
+                confidence: None,
+            });
+        }
+        self.notify(|| FieldEvent::SignalEmitted(Box::new(signal.clone())));
+        self.signals.insert(hash.clone(), signal, self.current_time);
+        self.evict_over(kind);
         hash
     }
 
+    /// Set a replayed signal's confidence to the journaled one
+    fn restore_confidence(&mut self, hash: &str, confidence: Option<f64>) {
+        if let (Some(signal), Some(confidence)) = (self.signals.map.get_mut(hash), confidence) {
+            signal.confidence = confidence;
+        }
+    }
+
+    /// Merge a signal replicated from another field
+    ///
+    /// Unlike [`Field::emit`] the signal keeps its origin hash, and neither
+    /// the signal policy nor hooks run: it passed them where it was emitted.
+    /// A signal already here gains the reinforcements, parents and tags it
+    /// lacks. Expired signals and signals hashed under another policy are
+    /// dropped. Returns whether the field changed.
+    pub fn merge_remote(&mut self, signal: Signal) -> bool {
+        if signal.is_expired(self.current_time) || self.hash_policy.hash_signal(&signal) != signal.origin_hash {
+            return false;
+        }
+        let hash = signal.origin_hash.clone();
+        let Some(existing) = self.signals.map.get_mut(&hash) else {
+            let kind = signal.payload.kind();
+            self.mark_changed(kind);
+            if kind != PayloadKind::Heartbeat {
+                let at = self.current_time;
+                self.journal(|| FieldRecord::Emit {
+                    at,
+                    signal: Box::new(signal.clone()),
+                    confidence: None,
+                });
+            }
+            self.notify(|| FieldEvent::SignalEmitted(Box::new(signal.clone())));
+            self.signals.insert(hash, signal, self.current_time);
+            self.evict_over(kind);
+            return true;
+        };
+
+        let mut changed = false;
+        for parent in signal.parent_hashes {
+            if !existing.parent_hashes.contains(&parent) {
+                existing.parent_hashes.push(parent);
+                changed = true;
+            }
+        }
+        for tag in &signal.tags {
+            if !existing.tags.contains(tag) {
+                add_tag(&mut existing.tags, tag);
+                changed = true;
+            }
+        }
+        let reinforcers: Vec<String> = signal
+            .reinforced_by
+            .into_iter()
+            .filter(|agent| !existing.reinforced_by.contains(agent))
+            .collect();
+        for agent in &reinforcers {
+            self.reinforce(&hash, agent);
+        }
+        changed || !reinforcers.is_empty()
+    }
+
+    fn mark_changed(&mut self, kind: PayloadKind) {
+        self.sequence += 1;
+        self.changed_at.insert(kind, self.sequence);
+    }
+
+    /// Current change sequence number
+    ///
+    /// Record it before a pass over the field and pass it to
+    /// [`Field::changed_since`] later to learn whether anything new arrived.
+    pub fn sequence(&self) -> u64 {
+        self.sequence
+    }
+
+    /// Whether a signal of any of `kinds` was emitted, reinforced or expired after `sequence`
+    pub fn changed_since(&self, kinds: &[PayloadKind], sequence: u64) -> bool {
+        kinds
+            .iter()
+            .any(|kind| self.changed_at.get(kind).is_some_and(|&at| at > sequence))
+    }
+
     /// Advance time and process decay/expiration
     pub fn tick(&mut self, dt_seconds: f64) -> FieldTickResult {
         self.current_time += chrono::Duration::milliseconds((dt_seconds * 1000.0) as i64);
+        let result = self.expire();
+        if result.expired_count > 0 {
+            let at = self.current_time;
+            self.journal(|| FieldRecord::Expire { at });
+        }
+        result
+    }
 
+    /// Update intensities at the current time and move expired signals to history
+    fn expire(&mut self) -> FieldTickResult {
         let mut expired = Vec::new();
 
-        for (hash, signal) in &mut self.signals {
-            signal.current_intensity = signal.compute_intensity(self.current_time);
-            if signal.is_expired(self.current_time) {
+        let now = self.current_time;
+        let store = &mut self.signals;
+        for (hash, signal) in &mut store.map {
+            signal.current_intensity = signal.compute_intensity(now);
+            store.intensity.insert(hash.clone(), signal.effective_intensity(now));
+            if signal.is_expired(now) {
                 expired.push(hash.clone());
             }
         }
 
-        // Move expired signals to history
+        // Move expired signals to history; an expiry is a change (a lapsed claim frees its task)
         for hash in &expired {
             if let Some(signal) = self.signals.remove(hash) {
-                if self.history.len() < self.max_history {
-                    self.history.push(signal);
-                }
+                self.mark_changed(signal.payload.kind());
+                self.notify(|| FieldEvent::SignalExpired(Box::new(signal.clone())));
+                self.history.push_back(signal);
             }
         }
+        self.trim_history();
 
         FieldTickResult {
             expired_count: expired.len(),
-            active_count: self.signals.len(),
+            active_count: self.signals.map.len(),
+        }
+    }
+
+    /// Recompute every cached intensity at the current time
+    fn refresh_intensities(&mut self) {
+        let now = self.current_time;
+        let store = &mut self.signals;
+        for (hash, signal) in &store.map {
+            store.intensity.insert(hash.clone(), signal.effective_intensity(now));
         }
     }
 
+    /// Effective intensity of an active signal now (None if it isn't in the field)
+    ///
+    /// Cached: recomputed on every tick and reinforcement, not per call.
+    pub fn intensity(&self, hash: &str) -> Option<f64> {
+        let signal = self.signals.map.get(hash)?;
+        Some(self.signals.intensity(hash, signal, self.current_time))
+    }
+
     /// Sense all signals above a threshold intensity
     pub fn sense(&self, min_intensity: f64) -> Vec<&Signal> {
         self.signals
-            .values()
-            .filter(|s| s.effective_intensity(self.current_time) >= min_intensity)
+            .map
+            .iter()
+            .filter(|(hash, s)| self.signals.intensity(hash, s, self.current_time) >= min_intensity)
+            .map(|(_, s)| s)
+            .collect()
+    }
+
+    /// Sense signals of the given kinds above a threshold intensity
+    ///
+    /// Only the signals of those kinds are visited, so this stays cheap on
+    /// large fields; prefer it to [`Field::sense_where`] when the kinds are known.
+    pub fn sense_kinds(&self, kinds: &[PayloadKind], min_intensity: f64) -> Vec<&Signal> {
+        kinds
+            .iter()
+            .flat_map(|&kind| self.signals.of_kind(kind))
+            .filter(|(hash, s)| self.signals.intensity(hash, s, self.current_time) >= min_intensity)
+            .map(|(_, s)| s)
             .collect()
     }
 
     /// Sense signals matching a predicate
+    ///
+    /// Visits every active signal; see [`Field::sense_kinds`], and
+    /// [`Field::query`] for the common filters.
     pub fn sense_where<F>(&self, predicate: F) -> Vec<&Signal>
     where
         F: Fn(&Signal) -> bool,
     {
-        self.signals.values().filter(|s| predicate(s)).collect()
+        self.signals.map.values().filter(|s| predicate(s)).collect()
     }
 
     /// Sense signals by payload type
@@ -118,6 +787,9 @@ impl Field {
                 OsintPayload::EnrichedArtifacts { .. } => result.enriched_artifacts.push(signal),
                 OsintPayload::BlockchainAnalysis { .. } => result.blockchain_analyses.push(signal),
                 OsintPayload::PasteContent { .. } => result.paste_content.push(signal),
+                OsintPayload::ContentChanged { .. } => result.content_changes.push(signal),
+                OsintPayload::ThreadMessages { .. } => result.thread_messages.push(signal),
+                OsintPayload::ActorProfile { .. } => result.actor_profiles.push(signal),
                 OsintPayload::Insight { .. } => result.insights.push(signal),
                 OsintPayload::Summary { .. } => result.summaries.push(signal),
                 OsintPayload::Heartbeat { .. } => result.heartbeats.push(signal),
@@ -130,18 +802,61 @@ impl Field {
 
     /// Get a specific signal by hash
     pub fn get(&self, hash: &str) -> Option<&Signal> {
-        self.signals.get(hash)
+        self.signals.map.get(hash)
+    }
+
+    /// A signal and everything it was derived from, nearest first
+    ///
+    /// Follows [`Signal::parent_hashes`] through active signals and history;
+    /// parents that left the history are skipped.
+    pub fn provenance(&self, hash: &str) -> Vec<&Signal> {
+        let expired: HashMap<&str, &Signal> = self.history.iter().map(|s| (s.origin_hash.as_str(), s)).collect();
+        let mut seen = HashSet::new();
+        let mut queue = std::collections::VecDeque::from([hash]);
+        let mut lineage = Vec::new();
+        while let Some(hash) = queue.pop_front() {
+            if !seen.insert(hash) {
+                continue;
+            }
+            let Some(signal) = self.get(hash).or_else(|| expired.get(hash).copied()) else {
+                continue;
+            };
+            queue.extend(signal.parent_hashes.iter().map(String::as_str));
+            lineage.push(signal);
+        }
+        lineage
     }
 
     /// Get mutable reference to a signal
+    ///
+    /// Its payload kind must not change, and a changed intensity or
+    /// confidence is sensed from the next tick on.
     pub fn get_mut(&mut self, hash: &str) -> Option<&mut Signal> {
-        self.signals.get_mut(hash)
+        self.signals.map.get_mut(hash)
     }
 
     /// Reinforce a signal from an external agent
     pub fn reinforce(&mut self, hash: &str, agent_id: &str) -> bool {
-        if let Some(signal) = self.signals.get_mut(hash) {
-            signal.reinforce(agent_id);
+        if let Some(signal) = self.signals.map.get_mut(hash) {
+            signal.reinforce_with(agent_id, None, self.reinforcement.0.as_ref());
+            let kind = signal.payload.kind();
+            let (count, confidence) = (signal.reinforcement_count, signal.confidence);
+            self.signals.refresh(hash, self.current_time);
+            self.mark_changed(kind);
+            self.notify(|| FieldEvent::SignalReinforced {
+                hash: hash.to_string(),
+                agent: agent_id.to_string(),
+                count,
+            });
+            if kind != PayloadKind::Heartbeat {
+                let at = self.current_time;
+                self.journal(|| FieldRecord::Reinforce {
+                    at,
+                    hash: hash.to_string(),
+                    agent: agent_id.to_string(),
+                    confidence: Some(confidence),
+                });
+            }
             true
         } else {
             false
@@ -150,27 +865,41 @@ impl Field {
 
     /// Count of active signals
     pub fn active_count(&self) -> usize {
-        self.signals.len()
+        self.signals.map.len()
     }
 
     /// Get field statistics
     pub fn stats(&self) -> FieldStats {
-        let total_intensity: f64 = self.signals.values().map(|s| s.current_intensity).sum();
-        let avg_intensity = if self.signals.is_empty() {
+        let signals = &self.signals.map;
+        let total_intensity: f64 = signals.values().map(|s| s.current_intensity).sum();
+        let avg_intensity = if signals.is_empty() {
             0.0
         } else {
-            total_intensity / self.signals.len() as f64
+            total_intensity / signals.len() as f64
         };
 
         let total_reinforcements: u32 =
-            self.signals.values().map(|s| s.reinforcement_count).sum();
+            signals.values().map(|s| s.reinforcement_count).sum();
+
+        let mut by_kind = BTreeMap::new();
+        let mut intensity_histogram = vec![0; INTENSITY_BUCKETS];
+        for signal in signals.values() {
+            *by_kind.entry(signal.payload.kind()).or_insert(0) += 1;
+            let bucket = (signal.current_intensity.clamp(0.0, 1.0) * INTENSITY_BUCKETS as f64) as usize;
+            intensity_histogram[bucket.min(INTENSITY_BUCKETS - 1)] += 1;
+        }
 
         FieldStats {
-            active_signals: self.signals.len(),
+            active_signals: signals.len(),
             total_intensity,
             avg_intensity,
             total_reinforcements,
             history_size: self.history.len(),
+            history_dropped: self.history_dropped,
+            evicted: self.evicted(),
+            evicted_by_kind: self.evicted.clone(),
+            by_kind,
+            intensity_histogram,
         }
     }
 
@@ -194,13 +923,28 @@ pub struct FieldTickResult {
 }
 
 /// Field statistics
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FieldStats {
     pub active_signals: usize,
     pub total_intensity: f64,
     pub avg_intensity: f64,
     pub total_reinforcements: u32,
     pub history_size: usize,
+    /// Expired signals pushed out of a full history
+    #[serde(default)]
+    pub history_dropped: u64,
+    /// Signals evicted to keep the field within its caps
+    #[serde(default)]
+    pub evicted: u64,
+    /// Evicted signals per payload kind
+    #[serde(default)]
+    pub evicted_by_kind: BTreeMap<PayloadKind, u64>,
+    /// Active signals per payload kind
+    #[serde(default)]
+    pub by_kind: BTreeMap<PayloadKind, usize>,
+    /// Active signals per intensity band (0.0-0.1, 0.1-0.2, ..., 0.9-1.0)
+    #[serde(default)]
+    pub intensity_histogram: Vec<usize>,
 }
 
 /// Signals grouped by payload type
@@ -215,6 +959,9 @@ pub struct SignalsByType<'a> {
     pub enriched_artifacts: Vec<&'a Signal>,
     pub blockchain_analyses: Vec<&'a Signal>,
     pub paste_content: Vec<&'a Signal>,
+    pub content_changes: Vec<&'a Signal>,
+    pub thread_messages: Vec<&'a Signal>,
+    pub actor_profiles: Vec<&'a Signal>,
     pub insights: Vec<&'a Signal>,
     pub summaries: Vec<&'a Signal>,
     pub heartbeats: Vec<&'a Signal>,
@@ -224,6 +971,33 @@ pub struct SignalsByType<'a> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::{AtRestError, Network};
+    use crate::HashScope;
+    use std::io::Write;
+
+    #[test]
+    fn test_stats_by_kind_and_intensity() {
+        let mut field = Field::new();
+        for query in ["a", "b"] {
+            field.emit(Signal::builder(OsintPayload::UserQuery {
+                query: query.to_string(),
+                priority: 1.0,
+            }).build());
+        }
+        field.emit(Signal::builder(OsintPayload::RawResult {
+            url: "http://a.onion/".to_string(),
+            title: "A".to_string(),
+            engine: "Ahmia".to_string(),
+            network: Network::Tor,
+            query: None,
+        }).build());
+
+        let stats = field.stats();
+        assert_eq!(stats.by_kind[&PayloadKind::UserQuery], 2);
+        assert_eq!(stats.by_kind[&PayloadKind::RawResult], 1);
+        assert_eq!(stats.intensity_histogram.len(), INTENSITY_BUCKETS);
+        assert_eq!(stats.intensity_histogram.iter().sum::<usize>(), 3);
+    }
 
     #[test]
     fn test_field_emit_and_sense() {
@@ -266,10 +1040,374 @@ mod tests {
         field.emit(signal2); // should reinforce
 
         assert_eq!(field.active_count(), 1);
-        let signal = field.signals.values().next().unwrap();
+        let signal = field.signals.map.values().next().unwrap();
         assert_eq!(signal.reinforcement_count, 1);
     }
 
+    #[test]
+    fn test_field_content_scoped_reinforcement() {
+        let policy = OriginHashPolicy::default().with_scope(PayloadKind::RawResult, HashScope::CONTENT);
+        let mut field = Field::new().with_hash_policy(policy);
+
+        for agent in ["crawler-1", "crawler-2"] {
+            let signal = Signal::builder(OsintPayload::RawResult {
+                url: "http://a.onion/".to_string(),
+                title: "A".to_string(),
+                engine: "Ahmia".to_string(),
+                network: Network::Tor,
+                query: None,
+            })
+            .origin(agent)
+            .build();
+            field.emit(signal);
+        }
+
+        // Same result from two crawlers reinforces instead of duplicating
+        assert_eq!(field.active_count(), 1);
+        let signal = field.signals.map.values().next().unwrap();
+        assert_eq!(signal.reinforcement_count, 1);
+        assert_eq!(signal.reinforced_by, vec!["crawler-2".to_string()]);
+    }
+
+    #[test]
+    fn test_changed_since() {
+        let mut field = Field::new();
+        let before = field.sequence();
+        let hash = field.emit(Signal::builder(OsintPayload::UserQuery {
+            query: "test".to_string(),
+            priority: 1.0,
+        }).build());
+
+        assert!(field.changed_since(&[PayloadKind::UserQuery], before));
+        assert!(!field.changed_since(&[PayloadKind::RawResult], before));
+
+        let seen = field.sequence();
+        assert!(!field.changed_since(&[PayloadKind::UserQuery], seen));
+        field.reinforce(&hash, "agent-2");
+        assert!(field.changed_since(&[PayloadKind::UserQuery, PayloadKind::RawResult], seen));
+    }
+
+    #[test]
+    fn test_journal_reloads_field() {
+        let path = std::env::temp_dir().join(format!("robin-field-{}.jsonl", std::process::id()));
+        let query = |query: &str, ttl: f64| {
+            Signal::builder(OsintPayload::UserQuery {
+                query: query.to_string(),
+                priority: 1.0,
+            })
+            .origin("user")
+            .ttl(ttl)
+            .build()
+        };
+
+        let mut field = Field::new();
+        field.emit(query("before", 60.0));
+        field.persist_to(&path, None).unwrap();
+        let kept = field.emit(query("kept", 60.0));
+        field.emit(query("expires", 1.0));
+        field.reinforce(&kept, "agent-2");
+        field.tick(2.0);
+        field.emit(Signal::builder(OsintPayload::Heartbeat {
+            agent_id: "crawler-1".to_string(),
+            agent_type: crate::AgentType::Crawler,
+            capacity: 1.0,
+        }).build());
+        assert_eq!(field.store_path(), Some(path.as_path()));
+
+        // A line cut short by a crash is ignored
+        std::fs::OpenOptions::new().append(true).open(&path).unwrap()
+            .write_all(br#"{"op":"emit","at":"#).unwrap();
+
+        let mut loaded = Field::load(&path, None).unwrap();
+        assert_eq!(loaded.active_count(), 2);
+        assert_eq!(loaded.get(&kept).unwrap().reinforced_by, vec!["agent-2".to_string()]);
+        assert_eq!(loaded.stats().history_size, 1);
+        assert_eq!(loaded.now(), field.now());
+        assert!(!loaded.stats().by_kind.contains_key(&PayloadKind::Heartbeat));
+
+        // The loaded field keeps journaling to the compacted file
+        assert_eq!(std::fs::read_to_string(&path).unwrap().lines().count(), 1);
+        loaded.emit(query("after", 60.0));
+        assert!(loaded.take_store_error().is_none());
+        assert_eq!(Field::load(&path, None).unwrap().active_count(), 3);
+
+        std::fs::write(&path, "not json\n").unwrap();
+        assert!(matches!(Field::load(&path, None), Err(FieldStoreError::Parse { line: 1, .. })));
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_sealed_journal() {
+        let path = std::env::temp_dir().join(format!("robin-field-sealed-{}.jsonl", std::process::id()));
+        let key = AtRestKey::passphrase("field journal");
+        let query = Signal::builder(OsintPayload::UserQuery {
+            query: "secret-target".to_string(),
+            priority: 1.0,
+        })
+        .origin("user")
+        .build();
+
+        let mut field = Field::new();
+        field.persist_to(&path, Some(&key)).unwrap();
+        let hash = field.emit(query);
+        field.reinforce(&hash, "agent-2");
+        let raw = std::fs::read_to_string(&path).unwrap();
+        assert!(!raw.contains("secret-target") && !raw.contains("agent-2"));
+        assert_eq!(raw.lines().count(), 4);
+
+        assert!(matches!(Field::load(&path, None), Err(FieldStoreError::Sealed(AtRestError::InvalidKey(_)))));
+        assert!(Field::load(&path, Some(&AtRestKey::passphrase("wrong"))).is_err());
+        let loaded = Field::load(&path, Some(&key)).unwrap();
+        assert_eq!(loaded.get(&hash).unwrap().reinforcement_count, 1);
+        // Compacted, and still sealed
+        let raw = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(raw.lines().count(), 2);
+        assert!(!raw.contains("secret-target"));
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_snapshot_roundtrip() {
+        let path = std::env::temp_dir().join(format!("robin-field-snapshot-{}.json", std::process::id()));
+        let mut field = Field::new();
+        for (query, ttl) in [("kept", 60.0), ("expires", 1.0)] {
+            field.emit(Signal::builder(OsintPayload::UserQuery {
+                query: query.to_string(),
+                priority: 1.0,
+            }).ttl(ttl).build());
+        }
+        field.tick(2.0);
+        let hash = field.emit(Signal::builder(OsintPayload::UserQuery {
+            query: "late".to_string(),
+            priority: 1.0,
+        }).build());
+        field.reinforce(&hash, "agent-2");
+
+        let key = crate::AtRestKey::passphrase("correct horse");
+        field.export_snapshot().save(&path, Some(&key)).unwrap();
+        let snapshot = FieldSnapshot::load(&path, Some(&key)).unwrap();
+        assert_eq!(snapshot.version, FIELD_SNAPSHOT_VERSION);
+        assert_eq!(snapshot.signals.len(), 2);
+        assert_eq!(snapshot.history.len(), 1);
+
+        let imported = Field::import_snapshot(snapshot.clone()).unwrap();
+        assert_eq!(imported.active_count(), 2);
+        assert_eq!(imported.now(), field.now());
+        assert_eq!(imported.get(&hash).unwrap().reinforcement_count, 1);
+        assert!(imported.changed_since(&[PayloadKind::UserQuery], 0));
+
+        let future = FieldSnapshot { version: FIELD_SNAPSHOT_VERSION + 1, ..snapshot };
+        assert!(matches!(Field::import_snapshot(future.clone()), Err(FieldStoreError::Version(_))));
+        future.save(&path, None).unwrap();
+        assert!(matches!(FieldSnapshot::load(&path, None), Err(FieldStoreError::Version(_))));
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_subscribers_see_field_events() {
+        let mut field = Field::new();
+        let mut events = field.subscribe();
+        let mut late = field.clone().subscribe();
+        let query = || Signal::builder(OsintPayload::UserQuery {
+            query: "test".to_string(),
+            priority: 1.0,
+        }).origin("agent-1").ttl(1.0).build();
+
+        let hash = field.emit(query());
+        field.emit(query());
+        field.reinforce(&hash, "agent-2");
+        field.tick(2.0);
+
+        assert!(matches!(events.try_recv(), Ok(FieldEvent::SignalEmitted(s)) if s.origin_hash == hash));
+        assert!(matches!(events.try_recv(), Ok(FieldEvent::SignalReinforced { agent, count: 1, .. }) if agent == "agent-1"));
+        assert!(matches!(events.try_recv(), Ok(FieldEvent::SignalReinforced { agent, count: 2, .. }) if agent == "agent-2"));
+        assert!(matches!(events.try_recv(), Ok(FieldEvent::SignalExpired(s)) if s.origin_hash == hash));
+        assert!(events.try_recv().is_err());
+        // A clone's subscribers don't hear the original
+        assert!(late.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_sense_kinds_uses_index_and_cached_intensity() {
+        let mut field = Field::new();
+        for i in 0..100 {
+            field.emit(Signal::builder(OsintPayload::UserQuery {
+                query: format!("query {}", i),
+                priority: 1.0,
+            }).build());
+        }
+        let weak = field.emit(Signal::builder(OsintPayload::RawResult {
+            url: "http://a.onion/".to_string(),
+            title: "A".to_string(),
+            engine: "Ahmia".to_string(),
+            network: Network::Tor,
+            query: None,
+        }).confidence(0.3).decay_function(crate::DecayFunction::Linear).ttl(10.0).build());
+
+        assert_eq!(field.sense_kinds(&[PayloadKind::UserQuery], 0.1).len(), 100);
+        assert_eq!(field.sense_kinds(&[PayloadKind::RawResult, PayloadKind::Summary], 0.1).len(), 1);
+        let intensity = field.intensity(&weak).unwrap();
+        assert!((intensity - 0.3).abs() < 1e-9);
+
+        // Reinforcement and ticks refresh the cache
+        field.reinforce(&weak, "crawler-2");
+        assert!(field.intensity(&weak).unwrap() > intensity);
+        field.tick(6.0);
+        assert!(field.sense_kinds(&[PayloadKind::RawResult], 0.2).is_empty());
+        assert_eq!(field.intensity(&weak), Some(field.get(&weak).unwrap().effective_intensity(field.now())));
+
+        // The index survives serialization and expiry
+        let restored: Field = serde_json::from_str(&serde_json::to_string(&field).unwrap()).unwrap();
+        assert_eq!(restored.sense_kinds(&[PayloadKind::RawResult], 0.1).len(), 1);
+        field.tick(5.0);
+        assert!(field.sense_kinds(&[PayloadKind::RawResult], 0.0).is_empty());
+        assert_eq!(field.intensity(&weak), None);
+    }
+
+    #[test]
+    fn test_provenance_follows_parents_into_history() {
+        let mut field = Field::new();
+        let query = field.emit(Signal::builder(OsintPayload::UserQuery {
+            query: "lockbit".to_string(),
+            priority: 1.0,
+        }).ttl(1.0).build());
+        let refined = field.emit(Signal::builder(OsintPayload::RefinedQuery {
+            original: "lockbit".to_string(),
+            refined: "lockbit leak site".to_string(),
+            confidence: 1.0,
+            raw: None,
+            variants: Vec::new(),
+        }).parent(&query).build());
+        field.tick(2.0);
+        let result = |parent: &str| {
+            Signal::builder(OsintPayload::RawResult {
+                url: "http://a.onion/".to_string(),
+                title: "A".to_string(),
+                engine: "Ahmia".to_string(),
+                network: Network::Tor,
+                query: None,
+            }).origin("crawler-1").parent(parent).build()
+        };
+        // A second derivation of the same result adds its parent
+        let raw = field.emit(result(&refined));
+        field.emit(result("gone"));
+
+        // The expired user query is found in history; unknown parents are skipped
+        let lineage: Vec<&str> = field.provenance(&raw).iter().map(|s| s.origin_hash.as_str()).collect();
+        assert_eq!(lineage, vec![raw.as_str(), refined.as_str(), query.as_str()]);
+        assert_eq!(field.get(&raw).unwrap().parent_hashes, vec![refined.clone(), "gone".to_string()]);
+        assert!(field.provenance("missing").is_empty());
+    }
+
+    #[test]
+    fn test_merge_remote_keeps_hash_and_unions_reinforcements() {
+        let result = || {
+            Signal::builder(OsintPayload::RawResult {
+                url: "http://a.onion/".to_string(),
+                title: "A".to_string(),
+                engine: "Ahmia".to_string(),
+                network: Network::Tor,
+                query: None,
+            })
+            .origin("crawler-1")
+            .build()
+        };
+        let mut here = Field::new();
+        let mut there = Field::new();
+        let hash = there.emit(result());
+        there.reinforce(&hash, "crawler-2");
+
+        let copy = there.get(&hash).unwrap().clone();
+        assert!(here.merge_remote(copy.clone()));
+        assert_eq!(here.get(&hash).unwrap().reinforcement_count, 1);
+        // Merging what is already known changes nothing, so replicas don't echo forever
+        assert!(!here.merge_remote(copy.clone()));
+
+        there.reinforce(&hash, "crawler-3");
+        here.reinforce(&hash, "crawler-4");
+        assert!(here.merge_remote(there.get(&hash).unwrap().clone()));
+        let merged = here.get(&hash).unwrap();
+        assert_eq!(merged.reinforcement_count, 3);
+        assert!(["crawler-2", "crawler-3", "crawler-4"].iter().all(|a| merged.reinforced_by.iter().any(|b| b == a)));
+
+        let mut tampered = copy;
+        tampered.origin_hash = "forged".to_string();
+        assert!(!here.merge_remote(tampered));
+        assert!(here.get("forged").is_none());
+    }
+
+    #[test]
+    fn test_caps_evict_weakest_signals() {
+        let result = |i: usize, intensity: f64| {
+            Signal::builder(OsintPayload::RawResult {
+                url: format!("http://{}.onion/", i),
+                title: "Result".to_string(),
+                engine: "Ahmia".to_string(),
+                network: Network::Tor,
+                query: None,
+            })
+            .intensity(intensity)
+            .build()
+        };
+        let policy = SignalPolicy::default().with_max_signals(4).with_kind_limit(PayloadKind::RawResult, 3);
+        let mut field = Field::new().with_signal_policy(policy);
+        let mut events = field.subscribe();
+        let query = field.emit(Signal::builder(OsintPayload::UserQuery {
+            query: "lockbit".to_string(),
+            priority: 1.0,
+        }).intensity(0.2).build());
+
+        let hashes: Vec<String> = [0.9, 0.3, 0.8, 0.7].iter().enumerate().map(|(i, &x)| field.emit(result(i, x))).collect();
+        // The weakest result made way for the fourth
+        assert!(field.get(&hashes[1]).is_none());
+        assert_eq!(field.stats().by_kind[&PayloadKind::RawResult], 3);
+        assert_eq!(field.history().back().unwrap().origin_hash, hashes[1]);
+
+        // Heartbeats don't count toward the total cap
+        field.emit(Signal::builder(OsintPayload::Heartbeat {
+            agent_id: "crawler-1".to_string(),
+            agent_type: crate::AgentType::Crawler,
+            capacity: 1.0,
+        }).build());
+        assert_eq!(field.active_count(), 5);
+
+        // Over the total cap, the weakest signal of any kind goes, but never the query
+        let path = std::env::temp_dir().join(format!("robin-field-evict-{}.jsonl", std::process::id()));
+        field.persist_to(&path, None).unwrap();
+        field.emit(Signal::builder(OsintPayload::Insight {
+            category: crate::InsightCategory::ThreatActor,
+            content: "LockBit affiliate".to_string(),
+            confidence: 0.9,
+            sources: Vec::new(),
+        }).build());
+        assert!(field.get(&query).is_some());
+        assert!(field.get(&hashes[3]).is_none());
+        assert_eq!(field.active_count(), 5);
+
+        let stats = field.stats();
+        assert_eq!(stats.evicted, 2);
+        assert_eq!(stats.evicted_by_kind[&PayloadKind::RawResult], 2);
+        let evicted: Vec<String> = std::iter::from_fn(|| events.try_recv().ok())
+            .filter_map(|event| match event {
+                FieldEvent::SignalEvicted(signal) => Some(signal.origin_hash),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(evicted, vec![hashes[1].clone(), hashes[3].clone()]);
+
+        // Evictions are journaled, so a reload holds the same signals
+        let loaded = Field::load(&path, None).unwrap();
+        assert!(loaded.get(&hashes[3]).is_none());
+        assert_eq!(loaded.active_count(), field.active_count());
+        std::fs::remove_file(&path).unwrap();
+
+        // Tightening the policy evicts right away
+        field.set_signal_policy(SignalPolicy::default().with_max_signals(2));
+        assert_eq!(field.active_count(), 3);
+        assert!(field.get(&query).is_some());
+        assert_eq!(field.evicted(), 4);
+    }
+
     #[test]
     fn test_field_expiration() {
         let mut field = Field::new();
@@ -0,0 +1,476 @@
+//! On-disk journal for the signal field
+//!
+//! A field given a store with [`Field::persist_to`] appends every emission,
+//! reinforcement and expiry to a JSON Lines journal as it happens, so a run
+//! that crashed or was interrupted can be reloaded with [`Field::load`].
+//! Loading replays the journal and compacts it into a single snapshot line
+//! before appending resumes.
+//!
+//! The journal is a plain append-only file rather than an embedded
+//! database (SQLite, sled): records are small, written in order and only
+//! ever read back whole, and a text file stays greppable and easy to seal.
+//!
+//! Heartbeats are not journaled; agents re-emit them every tick.
+//!
+//! Given an [`AtRestKey`], the journal is sealed line by line: a header
+//! line says how, and every record after it is sealed and base64-encoded,
+//! so appending stays one write per record. A passphrase is stretched once
+//! per journal, with the salt kept in the header.
+//!
+//! [`FieldSnapshot`] is the portable form of a field: a versioned document
+//! of its active signals and history, for analysis after a run or for
+//! moving field state to another machine.
+
+use base64::Engine;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+use crate::{open, random_salt, read_maybe_sealed, seal, write_sealed, AtRestError, AtRestKey, Field, OriginHashPolicy, Signal};
+
+/// Current [`FieldSnapshot::version`]
+pub const FIELD_SNAPSHOT_VERSION: u32 = 1;
+
+/// Errors loading or starting a field journal
+#[derive(Debug, Error)]
+pub enum FieldStoreError {
+    #[error("IO error: {0}")]
+    Io(#[from] io::Error),
+
+    #[error("Invalid field journal at line {line}: {source}")]
+    Parse {
+        line: usize,
+        #[source]
+        source: serde_json::Error,
+    },
+
+    #[error("Invalid field snapshot: {0}")]
+    Snapshot(#[from] serde_json::Error),
+
+    #[error("Unsupported field snapshot version {0} (this build reads up to {FIELD_SNAPSHOT_VERSION})")]
+    Version(u32),
+
+    #[error("Encryption error: {0}")]
+    Sealed(#[from] AtRestError),
+}
+
+/// Versioned export of a field's signals
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FieldSnapshot {
+    /// Format version ([`FIELD_SNAPSHOT_VERSION`] when written by this build)
+    pub version: u32,
+    /// When the snapshot was taken
+    pub exported_at: DateTime<Utc>,
+    /// Field time at export
+    pub field_time: DateTime<Utc>,
+    /// Origin-hash policy the signals were hashed with
+    #[serde(default)]
+    pub hash_policy: OriginHashPolicy,
+    /// Active signals, oldest first
+    pub signals: Vec<Signal>,
+    /// Expired signals kept for analysis, in expiry order
+    #[serde(default)]
+    pub history: Vec<Signal>,
+}
+
+impl FieldSnapshot {
+    /// Load a snapshot written by [`FieldSnapshot::save`]
+    pub fn load(path: impl AsRef<Path>, key: Option<&AtRestKey>) -> Result<Self, FieldStoreError> {
+        let snapshot: Self = serde_json::from_slice(&read_maybe_sealed(path, key)?)?;
+        if snapshot.version > FIELD_SNAPSHOT_VERSION {
+            return Err(FieldStoreError::Version(snapshot.version));
+        }
+        Ok(snapshot)
+    }
+
+    /// Write the snapshot as JSON, sealed when a key is given
+    pub fn save(&self, path: impl AsRef<Path>, key: Option<&AtRestKey>) -> Result<(), FieldStoreError> {
+        let json = serde_json::to_vec_pretty(self)?;
+        match key {
+            Some(key) => write_sealed(path, key, &json)?,
+            None => std::fs::write(path, json)?,
+        }
+        Ok(())
+    }
+}
+
+/// One journal line
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub(crate) enum FieldRecord {
+    /// The whole field; replaces everything before it
+    Snapshot { field: Box<Field> },
+    /// A signal emitted at field time `at`; `confidence` is what the signal
+    /// it reinforced reached (replay can't know the model that decided it)
+    Emit {
+        at: DateTime<Utc>,
+        signal: Box<Signal>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        confidence: Option<f64>,
+    },
+    /// A signal reinforced at field time `at`, reaching `confidence`
+    Reinforce {
+        at: DateTime<Utc>,
+        hash: String,
+        agent: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        confidence: Option<f64>,
+    },
+    /// Signals expired when field time reached `at`
+    Expire { at: DateTime<Utc> },
+    /// A signal evicted over the signal policy's caps at field time `at`
+    Evict { at: DateTime<Utc>, hash: String },
+}
+
+/// First line of a sealed journal
+#[derive(Debug, Serialize, Deserialize)]
+struct SealedHeader {
+    sealed: SealedWith,
+}
+
+/// Key a journal's lines are sealed with
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum SealedWith {
+    Keyfile,
+    /// Passphrase stretched with this salt (base64)
+    Passphrase { salt: String },
+}
+
+impl SealedWith {
+    /// Header for a new journal, and the key its lines are sealed with
+    fn new(key: &AtRestKey) -> Result<(Self, AtRestKey), AtRestError> {
+        match key {
+            AtRestKey::Key(_) => Ok((SealedWith::Keyfile, key.clone())),
+            AtRestKey::Passphrase(_) => {
+                let salt = random_salt();
+                let salt_b64 = base64::engine::general_purpose::STANDARD.encode(salt);
+                Ok((SealedWith::Passphrase { salt: salt_b64 }, key.stretched(&salt)?))
+            }
+        }
+    }
+
+    /// Key the lines of a journal with this header are sealed with
+    fn line_key(&self, key: Option<&AtRestKey>) -> Result<AtRestKey, AtRestError> {
+        match (self, key) {
+            (_, None) => Err(AtRestError::InvalidKey("field journal is encrypted but no key was given".to_string())),
+            (SealedWith::Keyfile, Some(key @ AtRestKey::Key(_))) => Ok(key.clone()),
+            (SealedWith::Passphrase { salt }, Some(key @ AtRestKey::Passphrase(_))) => {
+                let salt = base64::engine::general_purpose::STANDARD
+                    .decode(salt)
+                    .map_err(|_| AtRestError::Malformed)?;
+                key.stretched(&salt)
+            }
+            (SealedWith::Keyfile, Some(_)) => {
+                Err(AtRestError::InvalidKey("field journal was sealed with a keyfile".to_string()))
+            }
+            (SealedWith::Passphrase { .. }, Some(_)) => {
+                Err(AtRestError::InvalidKey("field journal was sealed with a passphrase".to_string()))
+            }
+        }
+    }
+}
+
+/// A record as one journal line, sealed under `key` if given
+fn encode_line(record: &FieldRecord, key: Option<&AtRestKey>) -> Result<Vec<u8>, FieldStoreError> {
+    let json = serde_json::to_vec(record)?;
+    let mut line = match key {
+        Some(key) => base64::engine::general_purpose::STANDARD.encode(seal(key, &json)?).into_bytes(),
+        None => json,
+    };
+    line.push(b'\n');
+    Ok(line)
+}
+
+/// Append handle of a field journal
+#[derive(Debug)]
+pub(crate) struct FieldStore {
+    path: PathBuf,
+    file: File,
+    /// Key records are sealed with (already stretched), if the journal is sealed
+    key: Option<AtRestKey>,
+    /// Set by the first write error; nothing more is written after one
+    failed: bool,
+    /// That error, until taken
+    error: Option<io::Error>,
+}
+
+impl FieldStore {
+    /// Replace the journal at `path` with a snapshot of `field` and open it for appending
+    ///
+    /// The snapshot goes to a temporary file first, so a crash leaves
+    /// either the old journal or the new one. With a key, the journal is sealed.
+    pub(crate) fn create(path: &Path, field: &Field, key: Option<&AtRestKey>) -> Result<Self, FieldStoreError> {
+        let snapshot = FieldRecord::Snapshot {
+            field: Box::new(field.clone()),
+        };
+        let key = write_journal(path, &[snapshot], key)?;
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            file: OpenOptions::new().append(true).open(path)?,
+            key,
+            failed: false,
+            error: None,
+        })
+    }
+
+    /// Records of the journal at `path`, in order
+    ///
+    /// A sealed journal needs its key; a plain one is read with or without
+    /// one. A final line cut short by a crash is dropped.
+    pub(crate) fn read(path: &Path, key: Option<&AtRestKey>) -> Result<Vec<FieldRecord>, FieldStoreError> {
+        let data = std::fs::read_to_string(path)?;
+        let complete = data.ends_with('\n');
+        let mut lines: Vec<&str> = data.lines().collect();
+
+        let line_key = match lines.first().and_then(|line| serde_json::from_str::<SealedHeader>(line).ok()) {
+            Some(header) => Some(header.sealed.line_key(key)?),
+            None => None,
+        };
+        let skipped = usize::from(line_key.is_some());
+        lines.drain(..skipped);
+
+        let mut records = Vec::with_capacity(lines.len());
+        for (i, line) in lines.iter().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let last = i + 1 == lines.len() && !complete;
+            let json = match &line_key {
+                Some(key) => {
+                    let opened = base64::engine::general_purpose::STANDARD
+                        .decode(line.trim())
+                        .map_err(|_| AtRestError::Malformed)
+                        .and_then(|sealed| open(key, &sealed));
+                    match opened {
+                        Ok(json) => json,
+                        Err(_) if last => break,
+                        Err(e) => return Err(e.into()),
+                    }
+                }
+                None => line.as_bytes().to_vec(),
+            };
+            match serde_json::from_slice(&json) {
+                Ok(record) => records.push(record),
+                Err(_) if last => break,
+                Err(source) => return Err(FieldStoreError::Parse { line: i + skipped + 1, source }),
+            }
+        }
+        Ok(records)
+    }
+
+    pub(crate) fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Append a record (one write per line, so a crash loses at most the last one)
+    pub(crate) fn append(&mut self, record: &FieldRecord) {
+        if self.failed {
+            return;
+        }
+        let result = encode_line(record, self.key.as_ref())
+            .map_err(io::Error::other)
+            .and_then(|line| self.file.write_all(&line));
+        if let Err(e) = result {
+            self.failed = true;
+            self.error = Some(e);
+        }
+    }
+
+    pub(crate) fn take_error(&mut self) -> Option<io::Error> {
+        self.error.take()
+    }
+}
+
+/// Replace the journal at `path` with `records`; returns the key lines are sealed with
+///
+/// The records go to a temporary file first, so a crash leaves either the
+/// old journal or the new one.
+fn write_journal(path: &Path, records: &[FieldRecord], key: Option<&AtRestKey>) -> Result<Option<AtRestKey>, FieldStoreError> {
+    let mut data = Vec::new();
+    let key = match key {
+        Some(key) => {
+            let (sealed, line_key) = SealedWith::new(key)?;
+            data.extend(serde_json::to_vec(&SealedHeader { sealed })?);
+            data.push(b'\n');
+            Some(line_key)
+        }
+        None => None,
+    };
+    for record in records {
+        data.extend(encode_line(record, key.as_ref())?);
+    }
+
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    std::fs::write(&tmp, data)?;
+    std::fs::rename(&tmp, path)?;
+    Ok(key)
+}
+
+/// Records of the field journal at `path` as plain JSON lines
+///
+/// For tools that work on the stored text, such as subject data requests.
+/// `Ok(None)` if the journal is sealed and no key was given.
+pub fn journal_lines(path: impl AsRef<Path>, key: Option<&AtRestKey>) -> Result<Option<Vec<String>>, FieldStoreError> {
+    let path = path.as_ref();
+    if key.is_none() && journal_is_sealed(path)? {
+        return Ok(None);
+    }
+    let lines = FieldStore::read(path, key)?
+        .iter()
+        .map(serde_json::to_string)
+        .collect::<Result<_, _>>()?;
+    Ok(Some(lines))
+}
+
+/// Replace the field journal at `path` with `lines` (as from [`journal_lines`])
+///
+/// Every line must still be a journal record. With a key, the journal is
+/// sealed again.
+pub fn rewrite_journal(path: impl AsRef<Path>, lines: &[String], key: Option<&AtRestKey>) -> Result<(), FieldStoreError> {
+    let records = lines
+        .iter()
+        .enumerate()
+        .map(|(i, line)| serde_json::from_str(line).map_err(|source| FieldStoreError::Parse { line: i + 1, source }))
+        .collect::<Result<Vec<FieldRecord>, _>>()?;
+    write_journal(path.as_ref(), &records, key)?;
+    Ok(())
+}
+
+/// Whether the journal at `path` starts with a sealed header
+pub fn journal_is_sealed(path: impl AsRef<Path>) -> Result<bool, FieldStoreError> {
+    let data = std::fs::read(path)?;
+    let first = data.split(|&b| b == b'\n').next().unwrap_or_default();
+    Ok(serde_json::from_slice::<SealedHeader>(first).is_ok())
+}
+
+/// A field's journal, if it has one
+///
+/// Clones of a field are detached from the journal, so only the original
+/// writes to it.
+#[derive(Debug, Default)]
+pub(crate) struct Journal(pub(crate) Option<FieldStore>);
+
+impl Clone for Journal {
+    fn clone(&self) -> Self {
+        Self(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::OsintPayload;
+
+    fn journal_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("robin-field-store-{}-{}.jsonl", name, std::process::id()))
+    }
+
+    fn emit(query: &str) -> FieldRecord {
+        let signal = Signal::builder(OsintPayload::UserQuery {
+            query: query.to_string(),
+            priority: 1.0,
+        })
+        .origin("user")
+        .build();
+        FieldRecord::Emit {
+            at: Utc::now(),
+            signal: Box::new(signal),
+            confidence: None,
+        }
+    }
+
+    fn ops(records: &[FieldRecord]) -> Vec<&'static str> {
+        records
+            .iter()
+            .map(|record| match record {
+                FieldRecord::Snapshot { .. } => "snapshot",
+                FieldRecord::Emit { .. } => "emit",
+                FieldRecord::Reinforce { .. } => "reinforce",
+                FieldRecord::Expire { .. } => "expire",
+                FieldRecord::Evict { .. } => "evict",
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_read_replays_in_order() {
+        let path = journal_path("replay");
+        let mut store = FieldStore::create(&path, &Field::new(), None).unwrap();
+        store.append(&emit("first"));
+        store.append(&FieldRecord::Reinforce {
+            at: Utc::now(),
+            hash: "abc".to_string(),
+            agent: "agent-2".to_string(),
+            confidence: Some(0.75),
+        });
+        store.append(&FieldRecord::Expire { at: Utc::now() });
+        assert!(store.take_error().is_none());
+
+        let records = FieldStore::read(&path, None).unwrap();
+        assert_eq!(ops(&records), vec!["snapshot", "emit", "reinforce", "expire"]);
+        assert!(matches!(&records[2], FieldRecord::Reinforce { confidence: Some(c), .. } if *c == 0.75));
+
+        // Rewriting the plain lines keeps the same records
+        let lines = journal_lines(&path, None).unwrap().unwrap();
+        rewrite_journal(&path, &lines, None).unwrap();
+        assert_eq!(ops(&FieldStore::read(&path, None).unwrap()), vec!["snapshot", "emit", "reinforce", "expire"]);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_read_truncated_lines() {
+        let path = journal_path("truncated");
+        let mut store = FieldStore::create(&path, &Field::new(), None).unwrap();
+        store.append(&emit("kept"));
+        let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+
+        // A final line cut short by a crash is dropped
+        file.write_all(br#"{"op":"emit","at":"#).unwrap();
+        assert_eq!(ops(&FieldStore::read(&path, None).unwrap()), vec!["snapshot", "emit"]);
+
+        // Once more lines follow, it is corruption and names the line
+        file.write_all(b"\n").unwrap();
+        file.write_all(&encode_line(&emit("after"), None).unwrap()).unwrap();
+        assert!(matches!(FieldStore::read(&path, None), Err(FieldStoreError::Parse { line: 3, .. })));
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_sealed_journal_lines() {
+        let path = journal_path("sealed");
+        let key = AtRestKey::Key([7; 32]);
+        let mut store = FieldStore::create(&path, &Field::new(), Some(&key)).unwrap();
+        store.append(&emit("secret-target"));
+
+        let raw = std::fs::read_to_string(&path).unwrap();
+        assert!(raw.lines().next().unwrap().contains("keyfile"));
+        assert!(!raw.contains("secret-target"));
+        assert!(journal_is_sealed(&path).unwrap());
+        assert!(journal_lines(&path, None).unwrap().is_none());
+
+        // The key must be the kind the header names
+        assert!(matches!(FieldStore::read(&path, None), Err(FieldStoreError::Sealed(AtRestError::InvalidKey(_)))));
+        assert!(matches!(
+            FieldStore::read(&path, Some(&AtRestKey::passphrase("keyfile"))),
+            Err(FieldStoreError::Sealed(AtRestError::InvalidKey(_)))
+        ));
+        assert!(FieldStore::read(&path, Some(&AtRestKey::Key([8; 32]))).is_err());
+
+        // A sealed line cut short is dropped like a plain one
+        OpenOptions::new().append(true).open(&path).unwrap().write_all(b"UlNNRVNI").unwrap();
+        assert_eq!(ops(&FieldStore::read(&path, Some(&key)).unwrap()), vec!["snapshot", "emit"]);
+
+        let lines = journal_lines(&path, Some(&key)).unwrap().unwrap();
+        assert!(lines[1].contains("secret-target"));
+        rewrite_journal(&path, &lines, Some(&key)).unwrap();
+        assert!(journal_is_sealed(&path).unwrap());
+        assert_eq!(ops(&FieldStore::read(&path, Some(&key)).unwrap()), vec!["snapshot", "emit"]);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
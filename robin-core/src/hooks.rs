@@ -0,0 +1,187 @@
+//! Emission hooks on the signal field
+//!
+//! Hooks added with [`Field::add_hook`] see every signal passed to
+//! [`Field::emit`] before it enters the field, in the order they were
+//! added. A hook can inspect the signal, rewrite it (the origin hash is
+//! computed afterwards), veto it, or queue further signals to emit after
+//! it. Cross-cutting policy such as scope enforcement, redaction or metrics
+//! then lives in one place instead of in every agent.
+//!
+//! Signals queued by a hook go through the hooks too, up to
+//! [`MAX_HOOK_DEPTH`] levels deep, so hooks that feed each other can't loop
+//! forever. Journal replay and snapshot import don't run hooks: those
+//! signals already passed them.
+
+use std::fmt;
+use std::sync::Arc;
+use thiserror::Error;
+
+use crate::{Field, Signal};
+
+/// Levels of hook-queued signals emitted below the original one
+pub const MAX_HOOK_DEPTH: usize = 4;
+
+/// A hook shared between fields
+pub type SharedHook = Arc<dyn EmitHook>;
+
+/// What a hook decided about a signal
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HookAction {
+    /// Let the signal through (possibly rewritten)
+    Pass,
+    /// Drop the signal, with the reason
+    Veto(String),
+}
+
+/// A signal dropped by a hook
+#[derive(Debug, Clone, Error)]
+#[error("Signal {hash} vetoed by {hook}: {reason}")]
+pub struct EmitVetoed {
+    /// Origin hash the signal would have had
+    pub hash: String,
+    /// Name of the vetoing hook
+    pub hook: String,
+    pub reason: String,
+}
+
+/// What a hook can see and do besides rewriting the signal
+pub struct EmitContext<'a> {
+    field: &'a Field,
+    depth: usize,
+    queued: Vec<Signal>,
+}
+
+impl<'a> EmitContext<'a> {
+    pub(crate) fn new(field: &'a Field, depth: usize) -> Self {
+        Self {
+            field,
+            depth,
+            queued: Vec::new(),
+        }
+    }
+
+    /// The field as it is before the signal enters it
+    pub fn field(&self) -> &Field {
+        self.field
+    }
+
+    /// How many hook-queued signals deep this emission is (0 = emitted by an agent)
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// Emit `signal` after the current one, if it is let through
+    pub fn also_emit(&mut self, signal: Signal) {
+        self.queued.push(signal);
+    }
+
+    pub(crate) fn into_queued(self) -> Vec<Signal> {
+        self.queued
+    }
+}
+
+/// Middleware run on every emitted signal
+pub trait EmitHook: Send + Sync {
+    /// Name used in logs and [`EmitVetoed`]
+    fn name(&self) -> &str;
+
+    /// Inspect or rewrite `signal`; queue more through `ctx`
+    fn on_emit(&self, signal: &mut Signal, ctx: &mut EmitContext<'_>) -> HookAction;
+}
+
+/// Hook from a closure
+pub struct FnHook<F> {
+    name: String,
+    f: F,
+}
+
+impl<F> FnHook<F>
+where
+    F: Fn(&mut Signal, &mut EmitContext<'_>) -> HookAction + Send + Sync + 'static,
+{
+    pub fn new(name: &str, f: F) -> Self {
+        Self { name: name.to_string(), f }
+    }
+
+    /// Wrap for adding to fields
+    pub fn shared(self) -> SharedHook {
+        Arc::new(self)
+    }
+}
+
+impl<F> EmitHook for FnHook<F>
+where
+    F: Fn(&mut Signal, &mut EmitContext<'_>) -> HookAction + Send + Sync,
+{
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn on_emit(&self, signal: &mut Signal, ctx: &mut EmitContext<'_>) -> HookAction {
+        (self.f)(signal, ctx)
+    }
+}
+
+/// Hooks of a field, in order
+#[derive(Clone, Default)]
+pub(crate) struct Hooks(pub(crate) Vec<SharedHook>);
+
+impl fmt::Debug for Hooks {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.0.iter().map(|hook| hook.name())).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{OsintPayload, PayloadKind};
+
+    fn query(text: &str) -> Signal {
+        Signal::builder(OsintPayload::UserQuery {
+            query: text.to_string(),
+            priority: 1.0,
+        })
+        .build()
+    }
+
+    #[test]
+    fn test_hooks_rewrite_veto_and_fan_out() {
+        let redact = FnHook::new("redact", |signal: &mut Signal, _: &mut EmitContext<'_>| {
+            if let OsintPayload::UserQuery { query, .. } = &mut signal.payload {
+                *query = query.replace("alice@example.com", "[email]");
+            }
+            HookAction::Pass
+        });
+        let scope = FnHook::new("scope", |signal: &mut Signal, _: &mut EmitContext<'_>| match &signal.payload {
+            OsintPayload::UserQuery { query, .. } if query.contains("forbidden") => {
+                HookAction::Veto("out of scope".to_string())
+            }
+            _ => HookAction::Pass,
+        });
+        // Echoes every query, including its own echoes
+        let echo = FnHook::new("echo", |signal: &mut Signal, ctx: &mut EmitContext<'_>| {
+            if let OsintPayload::UserQuery { query, .. } = &signal.payload {
+                ctx.also_emit(self::query(&format!("{} again", query)));
+            }
+            HookAction::Pass
+        });
+        let mut field = Field::new().with_hook(redact.shared()).with_hook(scope.shared());
+        assert_eq!(field.hooks().len(), 2);
+
+        let hash = field.emit(query("leaks of alice@example.com"));
+        assert!(matches!(&field.get(&hash).unwrap().payload,
+            OsintPayload::UserQuery { query, .. } if query == "leaks of [email]"));
+
+        let vetoed = field.try_emit(query("forbidden topic")).unwrap_err();
+        assert_eq!(vetoed.hook, "scope");
+        assert_eq!(field.emit(query("forbidden topic")), vetoed.hash);
+        assert!(field.get(&vetoed.hash).is_none());
+        assert_eq!(field.active_count(), 1);
+
+        field.add_hook(echo.shared());
+        field.emit(query("ransomware"));
+        let queries = field.sense_kinds(&[PayloadKind::UserQuery], 0.0).len();
+        assert_eq!(queries, 1 + 1 + MAX_HOOK_DEPTH);
+    }
+}
@@ -0,0 +1,96 @@
+//! Investigation namespaces
+//!
+//! Several queries can run in one swarm without their pipelines mixing:
+//! each query's [`Signal::investigation_id`] is set when it's submitted,
+//! and every signal derived from it inherits the ID when emitted
+//! ([`Field::emit`]), so refined queries, results, pages and artifacts
+//! all carry it without agents passing it along. The ID is part of the
+//! origin hash, so the same result found by two investigations is two
+//! signals. Agents that relate signals to a query sense with
+//! [`Field::sense_in`]. Signals without an ID form one namespace of their
+//! own, which is all a single-query run uses.
+
+use std::collections::BTreeSet;
+
+use crate::{Field, PayloadKind, Signal};
+
+impl Field {
+    /// Signals of `kinds` in `investigation` (`None` = unscoped signals) above `min_intensity`
+    pub fn sense_in(&self, investigation: Option<&str>, kinds: &[PayloadKind], min_intensity: f64) -> Vec<&Signal> {
+        self.sense_kinds(kinds, min_intensity)
+            .into_iter()
+            .filter(|signal| signal.investigation_id.as_deref() == investigation)
+            .collect()
+    }
+
+    /// Investigations with active signals of `kinds`, in ID order (`None` first)
+    pub fn investigations(&self, kinds: &[PayloadKind]) -> Vec<Option<&str>> {
+        self.sense_kinds(kinds, 0.0)
+            .into_iter()
+            .map(|signal| signal.investigation_id.as_deref())
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect()
+    }
+
+    /// Investigation of the first of `signal`'s parents that has one
+    pub(crate) fn parent_investigation(&self, signal: &Signal) -> Option<String> {
+        signal.parent_hashes.iter().find_map(|hash| {
+            self.get(hash)
+                .or_else(|| self.history().iter().find(|s| s.origin_hash == *hash))
+                .and_then(|parent| parent.investigation_id.clone())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Network, OsintPayload};
+
+    fn result(url: &str) -> Signal {
+        Signal::builder(OsintPayload::RawResult {
+            url: url.to_string(),
+            title: "Leak site".to_string(),
+            engine: "Ahmia".to_string(),
+            network: Network::Tor,
+            query: None,
+        })
+        .origin("crawler-1")
+        .build()
+    }
+
+    #[test]
+    fn test_investigations_stay_apart() {
+        let mut field = Field::new();
+        let mut queries = Vec::new();
+        for (id, query) in [("inv-a", "lockbit"), ("inv-b", "blackcat")] {
+            let signal = Signal::builder(OsintPayload::UserQuery { query: query.to_string(), priority: 1.0 })
+                .origin("user")
+                .investigation(id)
+                .build();
+            queries.push(field.emit(signal));
+        }
+        field.emit(result("http://unscoped.onion/"));
+
+        // The same result in both investigations: derived signals inherit the ID, and don't merge
+        let a = field.emit(Signal::builder(result("http://shared.onion/").payload).origin("crawler-1").parent(&queries[0]).build());
+        let b = field.emit(Signal::builder(result("http://shared.onion/").payload).origin("crawler-1").parent(&queries[1]).build());
+        assert_ne!(a, b);
+        assert_eq!(field.get(&a).unwrap().investigation_id.as_deref(), Some("inv-a"));
+        assert_eq!(field.get(&b).unwrap().reinforcement_count, 0);
+
+        assert_eq!(field.sense_in(Some("inv-a"), &[PayloadKind::RawResult], 0.0).len(), 1);
+        assert_eq!(field.sense_in(Some("inv-b"), &[PayloadKind::RawResult, PayloadKind::UserQuery], 0.0).len(), 2);
+        assert_eq!(field.sense_in(None, &[PayloadKind::RawResult], 0.0).len(), 1);
+        assert_eq!(field.investigations(&[PayloadKind::RawResult]), vec![None, Some("inv-a"), Some("inv-b")]);
+
+        // An explicit ID wins over the parent's, and the ID survives a snapshot
+        let moved = field.emit(
+            Signal::builder(result("http://moved.onion/").payload).parent(&queries[0]).investigation("inv-b").build(),
+        );
+        assert_eq!(field.get(&moved).unwrap().investigation_id.as_deref(), Some("inv-b"));
+        let restored = Field::import_snapshot(field.export_snapshot()).unwrap();
+        assert_eq!(restored.sense_in(Some("inv-b"), &[PayloadKind::RawResult], 0.0).len(), 2);
+    }
+}
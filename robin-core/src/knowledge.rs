@@ -0,0 +1,300 @@
+//! Organizational memory across investigations
+//!
+//! When a run produces a report, its confident artifacts, the threat actors
+//! they were seen alongside and the report's key insights are kept as a
+//! case in a [`KnowledgeStore`]. Later investigations that touch the same
+//! entities get those cases back ("this wallet appeared in case #12,
+//! attributed to LockBit") before the analyst writes its report.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use thiserror::Error;
+
+use crate::{read_maybe_sealed, write_sealed, Artifact, ArtifactType, AtRestError, AtRestKey};
+
+/// Artifacts below this confidence are not remembered
+pub const MIN_KNOWLEDGE_CONFIDENCE: f64 = 0.6;
+
+/// Most key insights kept per case
+const MAX_CASE_INSIGHTS: usize = 5;
+
+/// A knowledge store shared between the analyst and whoever saves it
+pub type SharedKnowledge = Arc<Mutex<KnowledgeStore>>;
+
+/// Errors loading or saving a knowledge store
+#[derive(Debug, Error)]
+pub enum KnowledgeError {
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Invalid knowledge store: {0}")]
+    Parse(#[from] serde_json::Error),
+
+    #[error("Encryption error: {0}")]
+    Sealed(#[from] AtRestError),
+}
+
+/// An entity seen in a past case
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CaseEntity {
+    pub artifact_type: ArtifactType,
+    pub value: String,
+    /// Threat actors found on the same pages
+    #[serde(default)]
+    pub attributed_to: Vec<String>,
+}
+
+/// A finished investigation
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CaseRecord {
+    /// Sequential case number, starting at 1
+    pub id: u64,
+    pub query: String,
+    pub recorded_at: DateTime<Utc>,
+    pub entities: Vec<CaseEntity>,
+    /// Key insights from the case report
+    #[serde(default)]
+    pub insights: Vec<String>,
+    /// Tags of the signals the case was built from (see [`crate::Tagger`])
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+}
+
+/// A current artifact that appeared in a past case
+#[derive(Debug, Clone, PartialEq)]
+pub struct PriorSighting<'a> {
+    /// Current artifact value
+    pub value: &'a str,
+    pub case: &'a CaseRecord,
+    pub entity: &'a CaseEntity,
+}
+
+impl PriorSighting<'_> {
+    /// One line for the analyst, e.g. `bc1q… appeared in case #12 ("…", 2026-03-01), attributed to LockBit`
+    pub fn describe(&self) -> String {
+        let mut line = format!(
+            "{} appeared in case #{} (\"{}\", {})",
+            self.value,
+            self.case.id,
+            self.case.query,
+            self.case.recorded_at.format("%Y-%m-%d")
+        );
+        if !self.entity.attributed_to.is_empty() {
+            line.push_str(&format!(", attributed to {}", self.entity.attributed_to.join(", ")));
+        }
+        line
+    }
+}
+
+/// Entities, attributions and insights retained from past cases
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct KnowledgeStore {
+    cases: Vec<CaseRecord>,
+}
+
+impl KnowledgeStore {
+    /// Load a store written by [`KnowledgeStore::save`]; a missing file is an empty store
+    pub fn load(path: impl AsRef<Path>, key: Option<&AtRestKey>) -> Result<Self, KnowledgeError> {
+        let path = path.as_ref();
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        Ok(serde_json::from_slice(&read_maybe_sealed(path, key)?)?)
+    }
+
+    /// Write the store as JSON, sealed when a key is given
+    pub fn save(&self, path: impl AsRef<Path>, key: Option<&AtRestKey>) -> Result<(), KnowledgeError> {
+        let json = serde_json::to_vec_pretty(self)?;
+        match key {
+            Some(key) => write_sealed(path, key, &json)?,
+            None => std::fs::write(path, json)?,
+        }
+        Ok(())
+    }
+
+    pub fn cases(&self) -> &[CaseRecord] {
+        &self.cases
+    }
+
+    /// Forget cases recorded before `cutoff`; returns how many
+    pub fn prune(&mut self, cutoff: DateTime<Utc>) -> usize {
+        let before = self.cases.len();
+        self.cases.retain(|case| case.recorded_at >= cutoff);
+        before - self.cases.len()
+    }
+
+    /// Cases carrying `tag`
+    pub fn cases_tagged<'a>(&'a self, tag: &'a str) -> impl Iterator<Item = &'a CaseRecord> + 'a {
+        self.cases.iter().filter(move |case| case.tags.iter().any(|t| t == tag))
+    }
+
+    /// Remember a finished case; returns its case number
+    ///
+    /// Entities are the confident artifacts (URLs and ATT&CK IDs are too
+    /// generic to link cases), each attributed to the threat actors
+    /// extracted from the same source pages.
+    pub fn record_case(&mut self, query: &str, artifacts: &[Artifact], insights: Vec<String>, tags: Vec<String>) -> u64 {
+        let mut actors_by_source: HashMap<&str, BTreeSet<&str>> = HashMap::new();
+        for artifact in artifacts.iter().filter(|a| a.artifact_type == ArtifactType::ThreatActor) {
+            if let Some(source) = &artifact.source {
+                actors_by_source.entry(source).or_default().insert(&artifact.value);
+            }
+        }
+
+        let mut entities: Vec<CaseEntity> = Vec::new();
+        for artifact in artifacts.iter().filter(|a| is_memorable(a)) {
+            let actors = artifact
+                .source
+                .as_deref()
+                .and_then(|s| actors_by_source.get(s))
+                .into_iter()
+                .flatten()
+                .filter(|actor| **actor != artifact.value)
+                .map(|actor| actor.to_string());
+            match entities
+                .iter_mut()
+                .find(|e| e.artifact_type == artifact.artifact_type && e.value == artifact.value)
+            {
+                Some(entity) => {
+                    for actor in actors {
+                        if !entity.attributed_to.contains(&actor) {
+                            entity.attributed_to.push(actor);
+                        }
+                    }
+                }
+                None => entities.push(CaseEntity {
+                    artifact_type: artifact.artifact_type.clone(),
+                    value: artifact.value.clone(),
+                    attributed_to: actors.collect(),
+                }),
+            }
+        }
+
+        let id = self.cases.last().map_or(1, |c| c.id + 1);
+        self.cases.push(CaseRecord {
+            id,
+            query: query.to_string(),
+            recorded_at: Utc::now(),
+            entities,
+            insights: insights.into_iter().take(MAX_CASE_INSIGHTS).collect(),
+            tags,
+        });
+        id
+    }
+
+    /// Past cases that share an entity with `artifacts`, oldest case first
+    pub fn recall<'a>(&'a self, artifacts: &'a [Artifact]) -> Vec<PriorSighting<'a>> {
+        let mut seen = BTreeSet::new();
+        let mut sightings = Vec::new();
+        for case in &self.cases {
+            for artifact in artifacts.iter().filter(|a| is_memorable(a)) {
+                let Some(entity) = case
+                    .entities
+                    .iter()
+                    .find(|e| e.artifact_type == artifact.artifact_type && e.value == artifact.value)
+                else {
+                    continue;
+                };
+                if seen.insert((case.id, artifact.value.as_str())) {
+                    sightings.push(PriorSighting {
+                        value: &artifact.value,
+                        case,
+                        entity,
+                    });
+                }
+            }
+        }
+        sightings
+    }
+}
+
+/// Bullet points under a report's "Key Insights" heading
+pub fn key_insights(markdown: &str) -> Vec<String> {
+    let mut in_section = false;
+    let mut insights = Vec::new();
+    for line in markdown.lines().map(str::trim) {
+        if line.starts_with('#') {
+            in_section = line.to_lowercase().contains("key insights");
+            continue;
+        }
+        if !in_section {
+            continue;
+        }
+        let item = line
+            .strip_prefix("- ")
+            .or_else(|| line.strip_prefix("* "))
+            .or_else(|| line.split_once(". ").filter(|(n, _)| n.parse::<u32>().is_ok()).map(|(_, rest)| rest));
+        if let Some(item) = item.map(str::trim).filter(|i| !i.is_empty()) {
+            insights.push(item.to_string());
+        }
+    }
+    insights
+}
+
+fn is_memorable(artifact: &Artifact) -> bool {
+    artifact.confidence >= MIN_KNOWLEDGE_CONFIDENCE
+        && !matches!(artifact.artifact_type, ArtifactType::Url | ArtifactType::MitreAttack)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WALLET: &str = "bc1qxy2kgdygjrsqtzq2n0yrf2493p83kkfjhx0wlh";
+
+    fn found(artifact_type: ArtifactType, value: &str, source: &str) -> Artifact {
+        Artifact::new(artifact_type, value.to_string()).with_source(source)
+    }
+
+    #[test]
+    fn test_record_and_recall() {
+        let mut store = KnowledgeStore::default();
+        let first = vec![
+            found(ArtifactType::Bitcoin, WALLET, "http://leak.onion/a"),
+            found(ArtifactType::ThreatActor, "LockBit", "http://leak.onion/a"),
+            found(ArtifactType::Email, "ops@example.com", "http://forum.onion/b"),
+            found(ArtifactType::Domain, "weak.example", "http://forum.onion/b").with_confidence(0.3),
+        ];
+        assert_eq!(store.record_case("lockbit payments", &first, vec!["Payments go to one wallet".into()], vec!["engagement:acme".into()]), 1);
+        assert_eq!(store.record_case("unrelated", &[], Vec::new(), Vec::new()), 2);
+        assert_eq!(store.cases_tagged("engagement:acme").map(|c| c.id).collect::<Vec<_>>(), vec![1]);
+
+        let current = vec![
+            found(ArtifactType::Bitcoin, WALLET, "http://other.onion/"),
+            found(ArtifactType::Domain, "weak.example", "http://other.onion/"),
+        ];
+        let sightings = store.recall(&current);
+        assert_eq!(sightings.len(), 1);
+        assert_eq!(sightings[0].case.id, 1);
+        assert_eq!(sightings[0].entity.attributed_to, vec!["LockBit"]);
+        assert!(sightings[0].describe().contains("appeared in case #1 (\"lockbit payments\""));
+        assert!(sightings[0].describe().ends_with("attributed to LockBit"));
+
+        assert_eq!(store.prune(Utc::now() - chrono::Duration::days(1)), 0);
+        assert_eq!(store.prune(Utc::now() + chrono::Duration::seconds(1)), 2);
+        assert!(store.recall(&current).is_empty());
+    }
+
+    #[test]
+    fn test_key_insights_and_persistence() {
+        let report = "# Report\n## Executive Summary\n- not this\n## Key Insights\n1. Wallet reused\n- Same operator\n\n## Threat Assessment\n- High";
+        assert_eq!(key_insights(report), vec!["Wallet reused", "Same operator"]);
+
+        let dir = std::env::temp_dir().join(format!("robin-knowledge-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("knowledge.json");
+        assert!(KnowledgeStore::load(&path, None).unwrap().cases().is_empty());
+
+        let mut store = KnowledgeStore::default();
+        store.record_case("q", &[found(ArtifactType::Email, "a@b.example", "http://x.onion/")], key_insights(report), Vec::new());
+        let key = AtRestKey::passphrase("correct horse");
+        store.save(&path, Some(&key)).unwrap();
+        let loaded = KnowledgeStore::load(&path, Some(&key)).unwrap();
+        assert_eq!(loaded.cases(), store.cases());
+        assert!(KnowledgeStore::load(&path, None).is_err());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,73 @@
+//! Language detection for scraped text
+//!
+//! Much dark web content is Russian or Chinese, and the filter and analyst
+//! work best on English. [`detect_language`] identifies a text's language
+//! (with `whatlang`) so agents can decide what to translate. Codes are
+//! ISO 639-1 like [`query_language`] and engine language lists; languages
+//! without a two-letter code keep their ISO 639-3 code.
+
+use crate::query_language;
+
+/// Characters of a text looked at (detection settles long before this)
+const SAMPLE_CHARS: usize = 2000;
+
+/// Confidence below which a statistical guess is not trusted
+///
+/// `whatlang`'s own reliability flag rejects most paragraphs of ordinary
+/// prose; half confidence keeps mistakes rare on page-length text.
+const MIN_CONFIDENCE: f64 = 0.5;
+
+/// ISO 639-3 codes `whatlang` reports, with their ISO 639-1 equivalent
+const ISO_639_1: &[(&str, &str)] = &[
+    ("eng", "en"), ("rus", "ru"), ("cmn", "zh"), ("spa", "es"), ("por", "pt"), ("ita", "it"),
+    ("ben", "bn"), ("fra", "fr"), ("deu", "de"), ("ukr", "uk"), ("kat", "ka"), ("ara", "ar"),
+    ("hin", "hi"), ("jpn", "ja"), ("heb", "he"), ("yid", "yi"), ("pol", "pl"), ("amh", "am"),
+    ("jav", "jv"), ("kor", "ko"), ("nob", "nb"), ("dan", "da"), ("swe", "sv"), ("fin", "fi"),
+    ("tur", "tr"), ("nld", "nl"), ("hun", "hu"), ("ces", "cs"), ("ell", "el"), ("bul", "bg"),
+    ("bel", "be"), ("ron", "ro"), ("slv", "sl"), ("hrv", "hr"), ("srp", "sr"), ("mkd", "mk"),
+    ("lit", "lt"), ("lav", "lv"), ("est", "et"), ("vie", "vi"), ("urd", "ur"), ("tha", "th"),
+    ("uzb", "uz"), ("aze", "az"), ("ind", "id"), ("pes", "fa"), ("khm", "km"), ("afr", "af"),
+    ("lat", "la"), ("slk", "sk"), ("cat", "ca"), ("tgl", "tl"), ("hye", "hy"), ("cym", "cy"),
+];
+
+/// Language of `text` (ISO 639-1), or `None` when it can't be told
+///
+/// Short texts such as result titles rarely give a reliable statistical
+/// answer; for those, a non-Latin script still identifies the language
+/// (Cyrillic as Russian, Han as Chinese, ...).
+pub fn detect_language(text: &str) -> Option<&'static str> {
+    let sample: String = text.chars().take(SAMPLE_CHARS).collect();
+    let info = whatlang::detect(&sample)?;
+    if info.confidence() >= MIN_CONFIDENCE {
+        let code = info.lang().code();
+        return Some(ISO_639_1.iter().find(|(long, _)| *long == code).map_or(code, |(_, short)| short));
+    }
+    query_language(&sample).filter(|l| *l != "en")
+}
+
+/// Whether `language` is English, or unknown (nothing to translate)
+pub fn is_english(language: Option<&str>) -> bool {
+    language.is_none_or(|l| l == "en")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_language() {
+        assert_eq!(
+            detect_language("Продаю свежие базы данных клиентов банков, оплата только в биткоинах через гаранта."),
+            Some("ru")
+        );
+        assert_eq!(
+            detect_language("Selling fresh bank customer databases, payment in bitcoin through escrow only."),
+            Some("en")
+        );
+        assert_eq!(detect_language("出售银行客户数据库，仅接受比特币付款，可走担保交易。"), Some("zh"));
+        // Too short to judge statistically, but the script gives it away
+        assert_eq!(detect_language("Базы"), Some("ru"));
+        assert_eq!(detect_language("12345"), None);
+        assert!(is_english(None) && is_english(Some("en")) && !is_english(Some("ru")));
+    }
+}
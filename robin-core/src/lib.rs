@@ -4,17 +4,73 @@
 //! - SMESH-inspired signals with decay and reinforcement
 //! - OSINT-specific signal payloads
 //! - Artifact extraction types (IOCs, TTPs, threat actors)
+//! - Threat actor alias knowledge base with fuzzy matching
 //! - Search engine registry
+//! - Language detection for scraped text
+//! - Configurable origin-hash policy for deduplication/reinforcement
+//! - Encryption at rest for local case data
+//! - Knowledge store that carries entities and insights across investigations
+//! - Vector index for searching past collections by content
+//! - Per-target finding trends with spike detection for monitoring
+//! - Cross-run record of crawled and scraped URLs
+//! - Content hashing and change detection for re-scraped pages
+//! - On-disk journal for resuming an interrupted field, and versioned field snapshots
+//! - Emission hooks for policy, redaction and metrics applied to every signal
+//! - Per-payload-kind signal lifetime (TTL and decay) policy
+//! - Rule-based signal tagging by engagement, query and severity
+//! - Declarative signal queries over the field
+//! - Task claims arbitrated through the field, so agents don't duplicate work
+//! - Chat log and forum thread parsing into per-author message records
+//! - Investigation namespaces, so concurrent queries in one field stay apart
+//! - Actor and vendor profiles aggregated from the signals that reference them
+//! - Pluggable confidence aggregation for reinforced signals
 
 pub mod signals;
 pub mod artifacts;
+pub mod actors;
 pub mod search_engines;
+pub mod language;
 pub mod field;
+pub mod origin_hash;
+pub mod at_rest;
+pub mod knowledge;
+pub mod vectors;
+pub mod trends;
+pub mod seen;
+pub mod changes;
+pub mod field_store;
+pub mod hooks;
+pub mod signal_policy;
+pub mod tagging;
+pub mod query;
+pub mod claims;
+pub mod threads;
+pub mod investigations;
+pub mod profiles;
+pub mod reinforcement;
 
 pub use signals::*;
 pub use artifacts::*;
+pub use actors::*;
 pub use search_engines::*;
+pub use language::*;
 pub use field::*;
+pub use origin_hash::*;
+pub use at_rest::*;
+pub use knowledge::*;
+pub use vectors::*;
+pub use trends::*;
+pub use seen::*;
+pub use changes::*;
+pub use field_store::*;
+pub use hooks::*;
+pub use signal_policy::*;
+pub use tagging::*;
+pub use query::*;
+pub use claims::*;
+pub use threads::*;
+pub use profiles::*;
+pub use reinforcement::*;
 
 /// Default signal TTL in seconds
 pub const DEFAULT_TTL: f64 = 60.0;
@@ -0,0 +1,260 @@
+//! Origin-hash policy
+//!
+//! The origin hash is the key a signal is stored under in the field: two
+//! signals with the same hash reinforce instead of coexisting. What goes into
+//! that hash decides what counts as "the same observation":
+//! - Content only: identical findings from different agents reinforce
+//! - Content + agent: each agent's findings stay separate (the default)
+//! - Content + timestamp: repeated emissions never merge
+//!
+//! The scope can be set per payload kind, and the digest algorithm and
+//! truncation length are configurable.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256, Sha512};
+use std::collections::HashMap;
+
+use crate::{OsintPayload, PayloadKind, Signal};
+
+/// Default origin-hash length in hex characters
+pub const DEFAULT_ORIGIN_HASH_LEN: usize = 16;
+
+/// Minimum origin-hash length in hex characters
+pub const MIN_ORIGIN_HASH_LEN: usize = 8;
+
+/// Digest algorithm used for origin hashes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum HashAlgorithm {
+    /// SHA-256 (64 hex characters)
+    #[default]
+    Sha256,
+    /// SHA-512 (128 hex characters)
+    Sha512,
+}
+
+impl HashAlgorithm {
+    /// Full digest length in hex characters
+    pub fn hex_len(&self) -> usize {
+        match self {
+            HashAlgorithm::Sha256 => 64,
+            HashAlgorithm::Sha512 => 128,
+        }
+    }
+
+    /// Hex digest of the given parts
+    fn digest_hex(&self, parts: &[&[u8]]) -> String {
+        match self {
+            HashAlgorithm::Sha256 => to_hex(&digest::<Sha256>(parts)),
+            HashAlgorithm::Sha512 => to_hex(&digest::<Sha512>(parts)),
+        }
+    }
+}
+
+fn digest<D: Digest>(parts: &[&[u8]]) -> Vec<u8> {
+    let mut hasher = D::new();
+    for part in parts {
+        // Length-prefix each part so boundaries can't be shifted between them
+        hasher.update((part.len() as u64).to_be_bytes());
+        hasher.update(part);
+    }
+    hasher.finalize().to_vec()
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Which signal properties participate in the origin hash
+///
+/// Payload content always participates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HashScope {
+    /// Include the originating agent ID
+    pub agent: bool,
+    /// Include the creation timestamp (millisecond precision)
+    pub timestamp: bool,
+}
+
+impl HashScope {
+    /// Content only: identical payloads reinforce across agents
+    pub const CONTENT: Self = Self { agent: false, timestamp: false };
+    /// Content and agent ID
+    pub const CONTENT_AND_AGENT: Self = Self { agent: true, timestamp: false };
+    /// Content and timestamp: every emission is distinct
+    pub const CONTENT_AND_TIMESTAMP: Self = Self { agent: false, timestamp: true };
+}
+
+impl Default for HashScope {
+    fn default() -> Self {
+        Self::CONTENT_AND_AGENT
+    }
+}
+
+/// How origin hashes are computed
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OriginHashPolicy {
+    /// Digest algorithm
+    pub algorithm: HashAlgorithm,
+    /// Hex characters kept from the digest
+    pub hex_len: usize,
+    /// Scope for payload kinds without an override
+    pub default_scope: HashScope,
+    /// Per-payload-kind scope overrides
+    #[serde(default)]
+    pub overrides: HashMap<PayloadKind, HashScope>,
+}
+
+impl Default for OriginHashPolicy {
+    fn default() -> Self {
+        Self {
+            algorithm: HashAlgorithm::default(),
+            hex_len: DEFAULT_ORIGIN_HASH_LEN,
+            default_scope: HashScope::default(),
+            overrides: HashMap::new(),
+        }
+    }
+}
+
+impl OriginHashPolicy {
+    /// Use a different digest algorithm
+    pub fn with_algorithm(mut self, algorithm: HashAlgorithm) -> Self {
+        self.algorithm = algorithm;
+        self
+    }
+
+    /// Keep `hex_len` hex characters of the digest
+    ///
+    /// Clamped to [`MIN_ORIGIN_HASH_LEN`] and the algorithm's full length.
+    pub fn with_hex_len(mut self, hex_len: usize) -> Self {
+        self.hex_len = hex_len;
+        self
+    }
+
+    /// Set the scope used when a payload kind has no override
+    pub fn with_default_scope(mut self, scope: HashScope) -> Self {
+        self.default_scope = scope;
+        self
+    }
+
+    /// Override the scope for one payload kind
+    pub fn with_scope(mut self, kind: PayloadKind, scope: HashScope) -> Self {
+        self.overrides.insert(kind, scope);
+        self
+    }
+
+    /// Scope applied to a payload kind
+    pub fn scope_for(&self, kind: PayloadKind) -> HashScope {
+        self.overrides.get(&kind).copied().unwrap_or(self.default_scope)
+    }
+
+    /// Compute the origin hash for a payload emitted by `agent_id` at `created_at`
+    pub fn compute(&self, payload: &OsintPayload, agent_id: &str, created_at: DateTime<Utc>) -> String {
+        self.compute_in(None, payload, agent_id, created_at)
+    }
+
+    /// Compute the origin hash for a payload emitted in `investigation`
+    ///
+    /// The same payload in two investigations hashes differently, so one
+    /// investigation's signals never reinforce another's.
+    pub fn compute_in(
+        &self,
+        investigation: Option<&str>,
+        payload: &OsintPayload,
+        agent_id: &str,
+        created_at: DateTime<Utc>,
+    ) -> String {
+        let scope = self.scope_for(payload.kind());
+        let payload_json = serde_json::to_string(payload).unwrap_or_default();
+        let timestamp = created_at.timestamp_millis().to_be_bytes();
+
+        let mut parts: Vec<&[u8]> = vec![payload_json.as_bytes()];
+        if let Some(investigation) = investigation {
+            parts.push(investigation.as_bytes());
+        }
+        if scope.agent {
+            parts.push(agent_id.as_bytes());
+        }
+        if scope.timestamp {
+            parts.push(&timestamp);
+        }
+
+        let len = self
+            .hex_len
+            .clamp(MIN_ORIGIN_HASH_LEN, self.algorithm.hex_len());
+        let mut hash = self.algorithm.digest_hex(&parts);
+        hash.truncate(len);
+        hash
+    }
+
+    /// Compute the origin hash for an existing signal
+    pub fn hash_signal(&self, signal: &Signal) -> String {
+        self.compute_in(
+            signal.investigation_id.as_deref(),
+            &signal.payload,
+            &signal.origin_agent_id,
+            signal.created_at,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn raw_result() -> OsintPayload {
+        OsintPayload::RawResult {
+            url: "http://a.onion/".to_string(),
+            title: "A".to_string(),
+            engine: "Ahmia".to_string(),
+            network: crate::Network::Tor,
+            query: None,
+        }
+    }
+
+    #[test]
+    fn test_default_scope_separates_agents() {
+        let policy = OriginHashPolicy::default();
+        let now = Utc::now();
+
+        let a = policy.compute(&raw_result(), "crawler-1", now);
+        let b = policy.compute(&raw_result(), "crawler-2", now);
+        assert_ne!(a, b);
+        assert_eq!(a.len(), DEFAULT_ORIGIN_HASH_LEN);
+    }
+
+    #[test]
+    fn test_per_kind_scope_override() {
+        let policy = OriginHashPolicy::default()
+            .with_scope(PayloadKind::RawResult, HashScope::CONTENT)
+            .with_scope(PayloadKind::UserQuery, HashScope::CONTENT_AND_TIMESTAMP);
+        let now = Utc::now();
+
+        // Content-only: identical results from different crawlers collapse
+        assert_eq!(
+            policy.compute(&raw_result(), "crawler-1", now),
+            policy.compute(&raw_result(), "crawler-2", now)
+        );
+
+        // Timestamped: the same query submitted twice stays distinct
+        let query = OsintPayload::UserQuery {
+            query: "test".to_string(),
+            priority: 1.0,
+        };
+        let later = now + chrono::Duration::milliseconds(1);
+        assert_ne!(policy.compute(&query, "cli", now), policy.compute(&query, "cli", later));
+    }
+
+    #[test]
+    fn test_algorithm_and_length() {
+        let now = Utc::now();
+        let policy = OriginHashPolicy::default()
+            .with_algorithm(HashAlgorithm::Sha512)
+            .with_hex_len(1000);
+        assert_eq!(policy.compute(&raw_result(), "a", now).len(), 128);
+
+        let short = OriginHashPolicy::default().with_hex_len(1);
+        assert_eq!(short.compute(&raw_result(), "a", now).len(), MIN_ORIGIN_HASH_LEN);
+    }
+}
@@ -0,0 +1,409 @@
+//! Actor and vendor profiles
+//!
+//! A [`ProfileBook`] folds the signals that reference a handle or actor
+//! into one [`ActorProfile`] each: the sites it was seen on, wallets, PGP
+//! keys, contact channels and a timeline. Handles are resolved through the
+//! [`ActorDatabase`], so a known alias lands on the actor's canonical
+//! profile.
+//!
+//! Evidence is only attributed where the signal says who it belongs to: a
+//! handle's own chat or forum messages, a paste's author, a page's author
+//! meta tag, or a page naming exactly one known actor. Pages naming several
+//! actors only add the page to each one's sites and timeline. Pastes are
+//! often dumps of other people's data, so only the contact channels and PGP
+//! keys an author advertises in one are attributed to them.
+
+use chrono::{DateTime, Utc};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use sha1::{Digest, Sha1};
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::LazyLock;
+
+use base64::Engine;
+
+use crate::{extract_artifacts, ActorDatabase, Artifact, ArtifactType, OsintPayload, Signal};
+
+/// Most timeline entries kept per profile (the latest)
+pub const MAX_TIMELINE: usize = 50;
+
+/// Something an actor did, or was seen doing
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct ActivityEvent {
+    pub at: DateTime<Utc>,
+    /// Page or paste it happened on
+    pub source: String,
+    pub activity: String,
+}
+
+/// Everything the field holds about one actor or handle
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ActorProfile {
+    /// Canonical actor name, or the handle as first seen
+    pub name: String,
+    /// Other names and handles it appeared under
+    #[serde(default)]
+    pub aliases: BTreeSet<String>,
+    /// Pages and pastes it was seen on
+    #[serde(default)]
+    pub sites: BTreeSet<String>,
+    /// Cryptocurrency addresses
+    #[serde(default)]
+    pub wallets: BTreeSet<String>,
+    /// PGP key fingerprints
+    #[serde(default)]
+    pub pgp_keys: BTreeSet<String>,
+    /// Contact channels, as `channel: address`
+    #[serde(default)]
+    pub contacts: BTreeSet<String>,
+    /// Activity, oldest first
+    #[serde(default)]
+    pub timeline: Vec<ActivityEvent>,
+    /// Signals the profile was built from
+    #[serde(default)]
+    pub evidence: usize,
+}
+
+impl ActorProfile {
+    fn add(&mut self, evidence: Evidence) {
+        self.wallets.extend(evidence.wallets);
+        self.pgp_keys.extend(evidence.pgp_keys);
+        self.contacts.extend(evidence.contacts);
+    }
+
+    fn record(&mut self, at: DateTime<Utc>, source: &str, activity: String) {
+        self.sites.insert(source.to_string());
+        let event = ActivityEvent { at, source: source.to_string(), activity };
+        if let Err(i) = self.timeline.binary_search(&event) {
+            self.timeline.insert(i, event);
+        }
+        if self.timeline.len() > MAX_TIMELINE {
+            self.timeline.remove(0);
+        }
+        self.evidence += 1;
+    }
+}
+
+/// Wallets, keys and contacts attributable to one author
+#[derive(Default)]
+struct Evidence {
+    wallets: BTreeSet<String>,
+    pgp_keys: BTreeSet<String>,
+    contacts: BTreeSet<String>,
+}
+
+impl Evidence {
+    fn from_artifacts(artifacts: &[Artifact]) -> Self {
+        let mut evidence = Evidence::default();
+        for artifact in artifacts {
+            match artifact.artifact_type {
+                ArtifactType::Bitcoin | ArtifactType::Ethereum | ArtifactType::Monero => {
+                    evidence.wallets.insert(artifact.value.clone());
+                }
+                ArtifactType::Email => {
+                    evidence.contacts.insert(format!("email: {}", artifact.value.to_lowercase()));
+                }
+                ArtifactType::Phone => {
+                    evidence.contacts.insert(format!("phone: {}", artifact.value));
+                }
+                _ => {}
+            }
+        }
+        evidence
+    }
+
+    fn from_text(text: &str) -> Self {
+        let mut evidence = Self::advertised(text);
+        let artifacts = Self::from_artifacts(&extract_artifacts(text, None));
+        evidence.wallets.extend(artifacts.wallets);
+        evidence.contacts.extend(artifacts.contacts);
+        evidence
+    }
+
+    /// Only the labelled contact channels and PGP keys in `text`
+    fn advertised(text: &str) -> Self {
+        let mut evidence = Evidence::default();
+        for cap in CONTACT_REGEX.captures_iter(text) {
+            let channel = match cap[1].to_lowercase().as_str() {
+                "tg" => "telegram".to_string(),
+                "xmpp" | "jid" => "jabber".to_string(),
+                channel => channel.to_string(),
+            };
+            evidence.contacts.insert(format!("{}: {}", channel, &cap[2]));
+        }
+        for block in PGP_BLOCK_REGEX.find_iter(text) {
+            evidence.pgp_keys.extend(pgp_fingerprint(block.as_str()));
+        }
+        evidence
+    }
+}
+
+static CONTACT_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)\b(jabber|xmpp|jid|telegram|tg|tox|session|wickr|threema|icq)\s*:\s*(@?[\w.\-]+(?:@[\w.\-]+)?)")
+        .unwrap()
+});
+
+static PGP_BLOCK_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"-----BEGIN PGP PUBLIC KEY BLOCK-----[\s\S]*?-----END PGP PUBLIC KEY BLOCK-----").unwrap()
+});
+
+/// V4 fingerprint (40 uppercase hex digits) of the primary key in an armored public key block
+pub fn pgp_fingerprint(armored: &str) -> Option<String> {
+    let mut lines = armored.lines().map(str::trim).skip_while(|line| !line.starts_with("-----BEGIN"));
+    lines.next()?;
+    // Armor headers end at the first blank line
+    let body: String = lines
+        .skip_while(|line| line.contains(": "))
+        .take_while(|line| !line.starts_with("-----END"))
+        .filter(|line| !line.starts_with('='))
+        .collect();
+    let data = base64::engine::general_purpose::STANDARD.decode(body).ok()?;
+
+    // The first packet must be a public key (tag 6)
+    let header = *data.first()?;
+    let (tag, len, start) = if header & 0x40 != 0 {
+        let tag = header & 0x3f;
+        match *data.get(1)? {
+            len @ 0..=191 => (tag, len as usize, 2),
+            first @ 192..=223 => (tag, ((first as usize - 192) << 8) + *data.get(2)? as usize + 192, 3),
+            255 => (tag, u32::from_be_bytes(data.get(2..6)?.try_into().ok()?) as usize, 6),
+            _ => return None,
+        }
+    } else {
+        let tag = (header >> 2) & 0x0f;
+        match header & 0x03 {
+            0 => (tag, *data.get(1)? as usize, 2),
+            1 => (tag, u16::from_be_bytes(data.get(1..3)?.try_into().ok()?) as usize, 3),
+            2 => (tag, u32::from_be_bytes(data.get(1..5)?.try_into().ok()?) as usize, 5),
+            _ => return None,
+        }
+    };
+    let packet = data.get(start..start + len)?;
+    if tag != 6 || packet.first() != Some(&4) {
+        return None;
+    }
+    let mut hasher = Sha1::new();
+    hasher.update([0x99]);
+    hasher.update((len as u16).to_be_bytes());
+    hasher.update(packet);
+    Some(hasher.finalize().iter().map(|b| format!("{:02X}", b)).collect())
+}
+
+/// Profiles of the actors and handles in a set of signals
+#[derive(Debug, Clone, Default)]
+pub struct ProfileBook {
+    actors: ActorDatabase,
+    /// Profiles by lowercased name
+    profiles: BTreeMap<String, ActorProfile>,
+}
+
+impl ProfileBook {
+    /// Resolve handles against `actors`
+    pub fn new(actors: ActorDatabase) -> Self {
+        Self { actors, profiles: BTreeMap::new() }
+    }
+
+    /// Profile of `name` (a canonical name or handle, ignoring case)
+    pub fn get(&self, name: &str) -> Option<&ActorProfile> {
+        self.profiles.get(&name.to_lowercase())
+    }
+
+    pub fn profiles(&self) -> impl Iterator<Item = &ActorProfile> {
+        self.profiles.values()
+    }
+
+    /// The profile `handle` belongs to, recording the handle as an alias
+    fn profile(&mut self, handle: &str) -> &mut ActorProfile {
+        let name = self.actors.lookup(handle).map_or(handle, |actor| actor.name.as_str()).to_string();
+        let profile = self.profiles.entry(name.to_lowercase()).or_insert_with(|| ActorProfile {
+            name: name.clone(),
+            ..Default::default()
+        });
+        if !handle.eq_ignore_ascii_case(&profile.name) {
+            profile.aliases.insert(handle.to_string());
+        }
+        profile
+    }
+
+    /// Add the evidence in `signal`; returns the profiles it changed
+    pub fn absorb(&mut self, signal: &Signal) -> Vec<&ActorProfile> {
+        let at = signal.created_at;
+        let mut changed = Vec::new();
+        match &signal.payload {
+            OsintPayload::ThreadMessages { source_url, format, author, messages, .. } => {
+                let text: Vec<&str> = messages.iter().map(|message| message.text.as_str()).collect();
+                let at = messages.iter().find_map(|message| message.sent_at).map_or(at, |sent| sent.and_utc());
+                let profile = self.profile(author);
+                profile.add(Evidence::from_text(&text.join("\n")));
+                let format = serde_json::to_value(format).ok().and_then(|v| v.as_str().map(str::to_string));
+                profile.record(
+                    at,
+                    source_url,
+                    format!("{} message(s) in {} conversation", messages.len(), format.unwrap_or_default()),
+                );
+                changed.push(author.clone());
+            }
+            OsintPayload::PasteContent { url, site, author: Some(author), content, created_at, .. } => {
+                let at = created_at
+                    .as_deref()
+                    .and_then(|posted| DateTime::parse_from_rfc3339(posted).ok())
+                    .map_or(at, |posted| posted.with_timezone(&Utc));
+                let profile = self.profile(author);
+                profile.add(Evidence::advertised(content));
+                profile.record(at, url, format!("posted paste on {}", site));
+                changed.push(author.clone());
+            }
+            OsintPayload::ScrapedContent { url, title, metadata: Some(metadata), .. } => {
+                if let Some(author) = metadata.meta.get("author").map(|a| a.trim()).filter(|a| !a.is_empty()) {
+                    let mut evidence = Evidence::from_text(&metadata.render());
+                    evidence.pgp_keys.extend(metadata.pgp_keys.iter().filter_map(|key| pgp_fingerprint(key)));
+                    let profile = self.profile(author);
+                    profile.add(evidence);
+                    profile.record(at, url, format!("author of page \"{}\"", title));
+                    changed.push(author.to_string());
+                }
+            }
+            OsintPayload::ExtractedArtifacts { source_url, artifacts } => {
+                let named: BTreeSet<&str> = artifacts
+                    .iter()
+                    .filter(|a| matches!(a.artifact_type, ArtifactType::ThreatActor | ArtifactType::Username))
+                    .map(|a| a.value.as_str())
+                    .collect();
+                let sole = named.len() == 1;
+                for name in named {
+                    let profile = self.profile(name);
+                    if sole {
+                        profile.add(Evidence::from_artifacts(artifacts));
+                    }
+                    profile.record(at, source_url, "named on page".to_string());
+                    changed.push(name.to_string());
+                }
+            }
+            _ => {}
+        }
+        let mut keys: Vec<String> = changed
+            .iter()
+            .map(|handle| self.actors.lookup(handle).map_or(handle.as_str(), |a| a.name.as_str()).to_lowercase())
+            .collect();
+        keys.sort();
+        keys.dedup();
+        keys.iter().filter_map(|key| self.profiles.get(key)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{MessageRecord, PageMetadata, ThreadFormat};
+
+    const PGP_KEY: &str = "-----BEGIN PGP PUBLIC KEY BLOCK-----
+
+mDMEatBQCxYJKwYBBAHaRw8BAQdAPXh0NTeeDqPYymToVkYqeYhmf61/JxU0i0tF
+FBQIxry0H3ZlbmRvcl94IDx2ZW5kb3JfeEBleGFtcGxlLm9yZz6IkAQTFggAOBYh
+BLoebQWTrHcFmdXOXXe16QllBq/OBQJq0FALAhsDBQsJCAcCBhUKCQgLAgQWAgMB
+Ah4BAheAAAoJEHe16QllBq/OKqwBAO26C7ggdxhoaUmM9IWT8VRAPmkR9UbRbmhz
+Kwbtf7BhAQDT5JEafaig7+35zR/sgya4yS4SgN+jKR3FYMLoXjGqCw==
+=atBZ
+-----END PGP PUBLIC KEY BLOCK-----";
+
+    fn message(text: &str) -> MessageRecord {
+        MessageRecord {
+            author: "vendor_x".to_string(),
+            timestamp: Some("2024-03-01 21:14".to_string()),
+            sent_at: chrono::NaiveDateTime::parse_from_str("2024-03-01 21:14", "%Y-%m-%d %H:%M").ok(),
+            text: text.to_string(),
+            quotes: Vec::new(),
+            index: 0,
+        }
+    }
+
+    #[test]
+    fn test_pgp_fingerprint() {
+        assert_eq!(pgp_fingerprint(PGP_KEY).as_deref(), Some("BA1E6D0593AC770599D5CE5D77B5E9096506AFCE"));
+        let headed = PGP_KEY.replace("BLOCK-----\n\n", "BLOCK-----\nComment: vendor key\n\n");
+        assert_eq!(pgp_fingerprint(&headed), pgp_fingerprint(PGP_KEY));
+        assert_eq!(pgp_fingerprint("-----BEGIN PGP PUBLIC KEY BLOCK-----\n\nAAAA\n-----END PGP PUBLIC KEY BLOCK-----"), None);
+    }
+
+    #[test]
+    fn test_profiles_merge_evidence_per_actor() {
+        let mut book = ProfileBook::new(ActorDatabase::embedded());
+
+        let chat = Signal::builder(OsintPayload::ThreadMessages {
+            source_url: "http://forum.onion/t/1".to_string(),
+            format: ThreadFormat::Forum,
+            author: "vendor_x".to_string(),
+            messages: vec![
+                message("escrow only, btc 1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa"),
+                message(&format!("jabber: vx@jabber.example\n{}", PGP_KEY)),
+            ],
+            thread_messages: 5,
+        })
+        .build();
+        let changed = book.absorb(&chat);
+        assert_eq!(changed.len(), 1);
+        let profile = book.get("VENDOR_X").unwrap();
+        assert!(profile.wallets.contains("1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa"));
+        assert!(profile.contacts.contains("jabber: vx@jabber.example"));
+        assert!(profile.pgp_keys.contains("BA1E6D0593AC770599D5CE5D77B5E9096506AFCE"));
+        assert_eq!(profile.timeline[0].at.to_rfc3339(), "2024-03-01T21:14:00+00:00");
+        assert_eq!(profile.timeline[0].activity, "2 message(s) in forum conversation");
+
+        // The same handle as a page's author adds its contacts
+        let page = Signal::builder(OsintPayload::ScrapedContent {
+            url: "http://shop.onion/".to_string(),
+            title: "Shop".to_string(),
+            text: String::new(),
+            char_count: 0,
+            screenshot: None,
+            language: None,
+            original_text: None,
+            chunk: None,
+            metadata: Some(PageMetadata {
+                meta: [("author".to_string(), "vendor_x".to_string())].into(),
+                emails: vec!["Sales@Shop.example".to_string()],
+                ..Default::default()
+            }),
+        })
+        .build();
+        book.absorb(&page);
+        let profile = book.get("vendor_x").unwrap();
+        assert!(profile.contacts.contains("email: sales@shop.example"));
+        assert_eq!(profile.sites.len(), 2);
+        assert_eq!(profile.evidence, 2);
+
+        // A page naming one actor attributes its wallets; aliases land on the canonical profile
+        let artifacts = |names: &[&str]| {
+            let mut artifacts: Vec<Artifact> =
+                names.iter().map(|n| Artifact::new(ArtifactType::ThreatActor, n.to_string())).collect();
+            artifacts.push(Artifact::new(ArtifactType::Monero, "4AdUndXHHZ6cfufTMvppY6JwXNouMBzSkbLYfpAV5Usx3skxNgYeYTRj5UzqtReoS44qo9mtmXCqY45DJ852K5Jv2684Rge".to_string()));
+            Signal::builder(OsintPayload::ExtractedArtifacts {
+                source_url: format!("http://leaks.onion/{}", names.len()),
+                artifacts,
+            })
+            .build()
+        };
+        let lockbit = book.absorb(&artifacts(&["LockBit"]))[0].name.clone();
+        assert_eq!(book.get(&lockbit).unwrap().wallets.len(), 1);
+        assert_eq!(book.absorb(&artifacts(&["LockBit", "vendor_x"])).len(), 2);
+        assert_eq!(book.get(&lockbit).unwrap().sites.len(), 2);
+        assert!(book.get("vendor_x").unwrap().wallets.len() == 1, "shared pages attribute nothing");
+
+        // A paste's author gets the channels it advertises, not the data it dumps
+        let paste = Signal::builder(OsintPayload::PasteContent {
+            url: "https://pastebin.com/abc".to_string(),
+            site: "pastebin".to_string(),
+            title: None,
+            content: "combo list\nalice@corp.example:hunter2\nbtc 1BoatSLRHtKNngkdXEeobR76b53LETtpyT\ncontact tg: @dumper".to_string(),
+            created_at: None,
+            author: Some("dumper".to_string()),
+            language: None,
+            score: 0.0,
+        })
+        .build();
+        book.absorb(&paste);
+        let profile = book.get("dumper").unwrap();
+        assert_eq!(profile.contacts, BTreeSet::from(["telegram: @dumper".to_string()]));
+        assert!(profile.wallets.is_empty());
+    }
+}
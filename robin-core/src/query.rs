@@ -0,0 +1,323 @@
+//! Declarative signal queries
+//!
+//! A [`SignalQuery`] combines the usual filters on field signals (payload
+//! kind, intensity, origin agent, age, text, tags, investigation) so
+//! callers don't write their own [`Field::sense_where`] closures, and so
+//! queries can come from config files or the command line. Run one with [`Field::query`], or
+//! over expired signals with [`Field::history_query`].
+//!
+//! ```toml
+//! kinds = ["raw_result", "filtered_result"]
+//! min_intensity = 0.3
+//! origin = "crawler-*"
+//! contains = "lockbit"
+//! ```
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::Path;
+use thiserror::Error;
+
+use crate::{Field, PayloadKind, Signal};
+
+/// Errors loading a signal query
+#[derive(Debug, Error)]
+pub enum SignalQueryError {
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Invalid signal query: {0}")]
+    Parse(#[from] toml::de::Error),
+}
+
+/// Filters on field signals; every filter set must match
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SignalQuery {
+    /// Payload kinds (empty = all)
+    #[serde(default)]
+    pub kinds: Vec<PayloadKind>,
+    /// Lowest current intensity
+    #[serde(default)]
+    pub min_intensity: f64,
+    /// Origin agent ID, or an ID prefix ending in `*`
+    #[serde(default)]
+    pub origin: Option<String>,
+    /// Only signals created after this time
+    #[serde(default)]
+    pub created_after: Option<DateTime<Utc>>,
+    /// Text appearing in any value of the payload, ignoring case (field names don't count)
+    #[serde(default)]
+    pub contains: Option<String>,
+    /// Tags the signal must all carry
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Investigation the signal belongs to
+    #[serde(default)]
+    pub investigation: Option<String>,
+    /// Most signals returned
+    #[serde(default)]
+    pub limit: Option<usize>,
+}
+
+impl SignalQuery {
+    /// Query matching every active signal
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parse a TOML query
+    pub fn from_toml(toml: &str) -> Result<Self, SignalQueryError> {
+        Ok(toml::from_str(toml)?)
+    }
+
+    /// Load a TOML query file
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, SignalQueryError> {
+        Self::from_toml(&std::fs::read_to_string(path)?)
+    }
+
+    pub fn kind(mut self, kind: PayloadKind) -> Self {
+        if !self.kinds.contains(&kind) {
+            self.kinds.push(kind);
+        }
+        self
+    }
+
+    pub fn kinds(self, kinds: &[PayloadKind]) -> Self {
+        kinds.iter().fold(self, |query, &kind| query.kind(kind))
+    }
+
+    pub fn min_intensity(mut self, intensity: f64) -> Self {
+        self.min_intensity = intensity;
+        self
+    }
+
+    /// Signals from `agent_id` (or agents whose ID starts with it, with a trailing `*`)
+    pub fn origin(mut self, agent_id: &str) -> Self {
+        self.origin = Some(agent_id.to_string());
+        self
+    }
+
+    pub fn created_after(mut self, time: DateTime<Utc>) -> Self {
+        self.created_after = Some(time);
+        self
+    }
+
+    pub fn contains(mut self, text: &str) -> Self {
+        self.contains = Some(text.to_string());
+        self
+    }
+
+    pub fn tag(mut self, tag: &str) -> Self {
+        self.tags.push(tag.to_string());
+        self
+    }
+
+    pub fn investigation(mut self, id: &str) -> Self {
+        self.investigation = Some(id.to_string());
+        self
+    }
+
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Whether `signal`, at `intensity`, passes the filters
+    pub fn matches(&self, signal: &Signal, intensity: f64) -> bool {
+        if !self.kinds.is_empty() && !self.kinds.contains(&signal.payload.kind()) {
+            return false;
+        }
+        if intensity < self.min_intensity {
+            return false;
+        }
+        if let Some(origin) = &self.origin {
+            let matched = match origin.strip_suffix('*') {
+                Some(prefix) => signal.origin_agent_id.starts_with(prefix),
+                None => signal.origin_agent_id == *origin,
+            };
+            if !matched {
+                return false;
+            }
+        }
+        if self.created_after.is_some_and(|after| signal.created_at <= after) {
+            return false;
+        }
+        if !self.tags.iter().all(|tag| signal.tags.contains(tag)) {
+            return false;
+        }
+        if self.investigation.as_ref().is_some_and(|id| signal.investigation_id.as_ref() != Some(id)) {
+            return false;
+        }
+        match &self.contains {
+            Some(text) => signal.payload.searchable_text().contains(&text.to_lowercase()),
+            None => true,
+        }
+    }
+}
+
+impl Field {
+    /// Active signals matching `query`, oldest first
+    pub fn query(&self, query: &SignalQuery) -> Vec<&Signal> {
+        let candidates = if query.kinds.is_empty() {
+            self.sense(query.min_intensity)
+        } else {
+            self.sense_kinds(&query.kinds, query.min_intensity)
+        };
+        let mut found: Vec<&Signal> = candidates
+            .into_iter()
+            .filter(|signal| {
+                let intensity = self.intensity(&signal.origin_hash).unwrap_or(0.0);
+                query.matches(signal, intensity)
+            })
+            .collect();
+        sort_and_limit(&mut found, query.limit);
+        found
+    }
+
+    /// Expired signals in history matching `query`, oldest first
+    ///
+    /// `min_intensity` is compared with the intensity signals started with,
+    /// since every expired signal has decayed to nothing.
+    pub fn history_query(&self, query: &SignalQuery) -> Vec<&Signal> {
+        let mut found: Vec<&Signal> = self
+            .history()
+            .iter()
+            .filter(|signal| query.matches(signal, signal.intensity))
+            .collect();
+        sort_and_limit(&mut found, query.limit);
+        found
+    }
+
+    /// Write the history signals matching `query` to `out` as JSON Lines
+    ///
+    /// Returns how many were written.
+    pub fn export_history(&self, query: &SignalQuery, mut out: impl Write) -> std::io::Result<usize> {
+        let found = self.history_query(query);
+        for signal in &found {
+            serde_json::to_writer(&mut out, signal)?;
+            out.write_all(b"\n")?;
+        }
+        out.flush()?;
+        Ok(found.len())
+    }
+}
+
+fn sort_and_limit(found: &mut Vec<&Signal>, limit: Option<usize>) {
+    found.sort_by(|a, b| a.created_at.cmp(&b.created_at).then_with(|| a.origin_hash.cmp(&b.origin_hash)));
+    if let Some(limit) = limit {
+        found.truncate(limit);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Network, OsintPayload};
+
+    fn result(url: &str, title: &str, agent: &str) -> Signal {
+        Signal::builder(OsintPayload::RawResult {
+            url: url.to_string(),
+            title: title.to_string(),
+            engine: "Ahmia".to_string(),
+            network: Network::Tor,
+            query: None,
+        })
+        .origin(agent)
+        .build()
+    }
+
+    #[test]
+    fn test_query_combines_filters() {
+        let mut field = Field::new();
+        let start = field.now();
+        field.emit(result("http://a.onion/", "LockBit leak site", "crawler-1"));
+        field.emit(result("http://b.onion/", "Forum index", "crawler-2"));
+        field.emit(result("http://c.onion/", "lockbit mirror", "other-1"));
+        field.emit(
+            Signal::builder(OsintPayload::UserQuery {
+                query: "lockbit".to_string(),
+                priority: 1.0,
+            })
+            .tag("engagement:acme")
+            .build(),
+        );
+
+        let urls = |query: &SignalQuery| -> Vec<String> {
+            field
+                .query(query)
+                .iter()
+                .filter_map(|s| match &s.payload {
+                    OsintPayload::RawResult { url, .. } => Some(url.clone()),
+                    _ => None,
+                })
+                .collect()
+        };
+        let lockbit = SignalQuery::new().kind(PayloadKind::RawResult).contains("LOCKBIT");
+        let mut found = urls(&lockbit);
+        found.sort();
+        assert_eq!(found, vec!["http://a.onion/", "http://c.onion/"]);
+        assert_eq!(urls(&lockbit.clone().origin("crawler-*")), vec!["http://a.onion/"]);
+        assert_eq!(urls(&lockbit.clone().origin("crawler")), Vec::<String>::new());
+        assert_eq!(urls(&lockbit.limit(1)).len(), 1);
+        // Only values are searched: every result has a title, none is titled "title"
+        assert_eq!(urls(&SignalQuery::new().contains("ahmia")).len(), 3);
+        assert!(urls(&SignalQuery::new().contains("title")).is_empty());
+        assert!(urls(&SignalQuery::new().contains("raw_result")).is_empty());
+
+        assert_eq!(field.query(&SignalQuery::new()).len(), 4);
+        assert_eq!(field.query(&SignalQuery::new().tag("engagement:acme")).len(), 1);
+        assert_eq!(field.query(&SignalQuery::new().investigation("inv-a")).len(), 0);
+        assert_eq!(field.query(&SignalQuery::new().min_intensity(1.1)).len(), 0);
+        assert_eq!(field.query(&SignalQuery::new().created_after(start + chrono::Duration::hours(1))).len(), 0);
+
+        let from_config =
+            SignalQuery::from_toml("kinds = [\"raw_result\"]\norigin = \"crawler-*\"\ncontains = \"lockbit\"").unwrap();
+        assert_eq!(field.query(&from_config).len(), 1);
+        assert!(matches!(SignalQuery::from_toml("kind = \"raw_result\""), Err(SignalQueryError::Parse(_))));
+    }
+
+    #[test]
+    fn test_history_is_bounded_and_queryable() {
+        let mut field = Field::new().with_max_history(2);
+        for (i, agent) in ["crawler-1", "crawler-2", "other-1"].iter().enumerate() {
+            field.emit(
+                Signal::builder(OsintPayload::RawResult {
+                    url: format!("http://{}.onion/", i),
+                    title: "LockBit".to_string(),
+                    engine: "Ahmia".to_string(),
+                    network: Network::Tor,
+                    query: None,
+                })
+                .origin(agent)
+                .ttl(1.0)
+                .build(),
+            );
+            field.tick(2.0);
+        }
+        assert_eq!(field.active_count(), 0);
+        // The oldest expired signal made way, and the drop is counted
+        assert_eq!(field.history().len(), 2);
+        assert_eq!(field.history_dropped(), 1);
+        assert_eq!(field.stats().history_dropped, 1);
+        assert!(field.query(&SignalQuery::new()).is_empty());
+
+        let crawlers = SignalQuery::new().origin("crawler-*").min_intensity(0.5);
+        assert_eq!(field.history_query(&crawlers).len(), 1);
+        assert_eq!(field.history_query(&SignalQuery::new().contains("lockbit")).len(), 2);
+
+        let mut jsonl = Vec::new();
+        assert_eq!(field.export_history(&SignalQuery::new(), &mut jsonl).unwrap(), 2);
+        let lines: Vec<Signal> = String::from_utf8(jsonl)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines[1].origin_agent_id, "other-1");
+
+        // History survives a snapshot round trip, within the limit
+        let restored = Field::import_snapshot(field.export_snapshot()).unwrap();
+        assert_eq!(restored.history().len(), 2);
+    }
+}
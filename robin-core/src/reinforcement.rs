@@ -0,0 +1,266 @@
+//! Confidence aggregation for reinforced signals
+//!
+//! When another agent re-emits a signal already in the field (or a replica
+//! reports one), the field reinforces it and its [`ReinforcementModel`]
+//! decides the new confidence. Models are set on the field with
+//! [`Field::set_reinforcement_model`]:
+//!
+//! - [`AdditiveModel`] (the default): a fixed boost that shrinks with each
+//!   reinforcement
+//! - [`BayesianModel`]: each reinforcing agent is evidence weighed by how
+//!   much it's trusted; agents trusted below 0.5 lower confidence
+//! - [`LogOddsModel`]: the reinforcing signal's own confidence is pooled
+//!   with the signal's, in log-odds, scaled by a fixed weight
+//!
+//! The model isn't journaled or snapshotted: set it again on a loaded
+//! field. Journaled reinforcements carry the confidence they reached, so
+//! replay restores it whatever the model was.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::Arc;
+
+use crate::{Field, Signal};
+
+/// A reinforcement model shared between fields
+pub type SharedReinforcement = Arc<dyn ReinforcementModel>;
+
+/// How reinforcements combine into a signal's confidence
+pub trait ReinforcementModel: Send + Sync {
+    /// Name for logs and debugging
+    fn name(&self) -> &str;
+
+    /// Confidence of `signal` once `agent` reinforces it
+    ///
+    /// `signal` is as it was before: `agent` isn't in `reinforced_by` yet.
+    /// `opinion` is the confidence of the re-emitted signal, if the
+    /// reinforcement came from an emission.
+    fn reinforce(&self, signal: &Signal, agent: &str, opinion: Option<f64>) -> f64;
+}
+
+/// Fixed boost with diminishing returns: `boost / (1 + 0.5 n)` for the n-th reinforcement
+#[derive(Debug, Clone, PartialEq)]
+pub struct AdditiveModel {
+    pub boost: f64,
+}
+
+impl Default for AdditiveModel {
+    fn default() -> Self {
+        Self { boost: 0.1 }
+    }
+}
+
+impl ReinforcementModel for AdditiveModel {
+    fn name(&self) -> &str {
+        "additive"
+    }
+
+    fn reinforce(&self, signal: &Signal, _agent: &str, _opinion: Option<f64>) -> f64 {
+        let count = signal.reinforcement_count as f64 + 1.0;
+        (signal.confidence + self.boost / (1.0 + count * 0.5)).min(1.0)
+    }
+}
+
+/// Bayesian update with per-agent trust
+///
+/// An agent's trust is the chance its reinforcement is right, so it
+/// multiplies the signal's odds by `trust / (1 - trust)`. Trust is looked
+/// up by agent ID, then by agent type (`crawler` for `crawler-2`).
+#[derive(Debug, Clone, PartialEq)]
+pub struct BayesianModel {
+    /// Trust of agents not listed
+    pub default_trust: f64,
+    trust: BTreeMap<String, f64>,
+}
+
+impl Default for BayesianModel {
+    fn default() -> Self {
+        Self { default_trust: 0.6, trust: BTreeMap::new() }
+    }
+}
+
+impl BayesianModel {
+    /// Trust `agent` (an agent ID or type) this much, from 0 to 1
+    pub fn with_trust(mut self, agent: &str, trust: f64) -> Self {
+        self.trust.insert(agent.to_string(), trust);
+        self
+    }
+
+    pub fn with_default_trust(mut self, trust: f64) -> Self {
+        self.default_trust = trust;
+        self
+    }
+
+    /// Trust of `agent`, clamped away from certainty
+    pub fn trust(&self, agent: &str) -> f64 {
+        let agent_type = agent
+            .rsplit_once('-')
+            .filter(|(_, n)| !n.is_empty() && n.bytes().all(|b| b.is_ascii_digit()))
+            .map(|(agent_type, _)| agent_type);
+        let trust = self
+            .trust
+            .get(agent)
+            .or_else(|| agent_type.and_then(|t| self.trust.get(t)))
+            .copied()
+            .unwrap_or(self.default_trust);
+        trust.clamp(0.01, 0.99)
+    }
+}
+
+impl ReinforcementModel for BayesianModel {
+    fn name(&self) -> &str {
+        "bayesian"
+    }
+
+    fn reinforce(&self, signal: &Signal, agent: &str, _opinion: Option<f64>) -> f64 {
+        let trust = self.trust(agent);
+        from_log_odds(log_odds(signal.confidence) + log_odds(trust))
+    }
+}
+
+/// Logarithmic opinion pooling
+///
+/// The reinforcing signal's confidence (`default_opinion` for bare
+/// reinforcements) is added to the signal's in log-odds, scaled by
+/// `weight`: 1.0 treats agents as independent, lower values assume every
+/// agent partly repeats what the others saw. Agents aren't told apart by
+/// their sources; the same weight applies to all.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LogOddsModel {
+    pub weight: f64,
+    pub default_opinion: f64,
+}
+
+impl Default for LogOddsModel {
+    fn default() -> Self {
+        Self { weight: 0.5, default_opinion: 0.6 }
+    }
+}
+
+impl ReinforcementModel for LogOddsModel {
+    fn name(&self) -> &str {
+        "log_odds"
+    }
+
+    fn reinforce(&self, signal: &Signal, _agent: &str, opinion: Option<f64>) -> f64 {
+        let opinion = opinion.unwrap_or(self.default_opinion).clamp(0.01, 0.99);
+        from_log_odds(log_odds(signal.confidence) + self.weight * log_odds(opinion))
+    }
+}
+
+/// Log-odds of a probability, clamped so certainty stays finite
+fn log_odds(p: f64) -> f64 {
+    let p = p.clamp(0.001, 0.999);
+    (p / (1.0 - p)).ln()
+}
+
+fn from_log_odds(x: f64) -> f64 {
+    1.0 / (1.0 + (-x).exp())
+}
+
+/// Reinforcement model of a field
+#[derive(Clone)]
+pub(crate) struct Reinforcement(pub(crate) SharedReinforcement);
+
+impl Default for Reinforcement {
+    fn default() -> Self {
+        Self(Arc::new(AdditiveModel::default()))
+    }
+}
+
+impl fmt::Debug for Reinforcement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.0.name())
+    }
+}
+
+impl Field {
+    /// Aggregate reinforcements with `model` from now on
+    pub fn with_reinforcement_model(mut self, model: SharedReinforcement) -> Self {
+        self.set_reinforcement_model(model);
+        self
+    }
+
+    /// Replace the reinforcement model; confidences already reached are kept
+    pub fn set_reinforcement_model(&mut self, model: SharedReinforcement) {
+        self.reinforcement = Reinforcement(model);
+    }
+
+    /// Reinforcement model in effect
+    pub fn reinforcement_model(&self) -> &SharedReinforcement {
+        &self.reinforcement.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{HashScope, OriginHashPolicy, OsintPayload, PayloadKind};
+
+    fn signal(confidence: f64) -> Signal {
+        Signal::builder(OsintPayload::UserQuery { query: "lockbit".to_string(), priority: 1.0 })
+            .origin("crawler-1")
+            .confidence(confidence)
+            .build()
+    }
+
+    /// Confidence after `reinforcers` each re-emit `signal` with confidence 0.7
+    fn consensus(model: SharedReinforcement, reinforcers: &[&str]) -> f64 {
+        let policy = OriginHashPolicy::default().with_scope(PayloadKind::UserQuery, HashScope::CONTENT);
+        let mut field = Field::new().with_hash_policy(policy).with_reinforcement_model(model);
+        let hash = field.emit(signal(0.5));
+        for agent in reinforcers {
+            let mut again = signal(0.7);
+            again.origin_agent_id = agent.to_string();
+            field.emit(again);
+        }
+        field.get(&hash).unwrap().confidence
+    }
+
+    #[test]
+    fn test_models_aggregate_differently() {
+        // The default reproduces the fixed diminishing boost
+        let additive = consensus(Arc::new(AdditiveModel::default()), &["crawler-2", "crawler-3"]);
+        assert!((additive - (0.5 + 0.1 / 1.5 + 0.1 / 2.0)).abs() < 1e-9);
+        assert_eq!(Field::new().reinforcement_model().name(), "additive");
+
+        // Trusted agents move confidence further; distrusted ones lower it
+        let bayesian = BayesianModel::default().with_trust("crawler", 0.9).with_trust("crawler-3", 0.2);
+        assert_eq!(bayesian.trust("crawler-2"), 0.9);
+        assert_eq!(bayesian.trust("scraper-1"), 0.6);
+        let trusted = consensus(Arc::new(bayesian.clone()), &["crawler-2"]);
+        assert!((trusted - 0.9).abs() < 1e-9);
+        assert!(consensus(Arc::new(bayesian), &["crawler-3"]) < 0.5);
+
+        // Pooled opinions: more agreement, more confidence, never certainty
+        let pooled = |n: usize| {
+            let agents: Vec<String> = (0..n).map(|i| format!("crawler-{}", i + 2)).collect();
+            consensus(Arc::new(LogOddsModel::default()), &agents.iter().map(String::as_str).collect::<Vec<_>>())
+        };
+        assert!(pooled(1) > 0.5 && pooled(3) > pooled(1) && pooled(20) < 1.0);
+
+        // A repeat reinforcement by the same agent changes nothing
+        assert_eq!(consensus(Arc::new(LogOddsModel::default()), &["crawler-2", "crawler-2"]), pooled(1));
+    }
+
+    #[test]
+    fn test_journal_keeps_model_confidence() {
+        let path = std::env::temp_dir().join(format!("robin-reinforcement-{}.jsonl", std::process::id()));
+        let policy = OriginHashPolicy::default().with_scope(PayloadKind::UserQuery, HashScope::CONTENT);
+        let model = BayesianModel::default().with_trust("crawler", 0.9);
+        let mut field = Field::new().with_hash_policy(policy).with_reinforcement_model(Arc::new(model));
+        field.persist_to(&path, None).unwrap();
+        let hash = field.emit(signal(0.5));
+        let mut again = signal(0.7);
+        again.origin_agent_id = "crawler-2".to_string();
+        field.emit(again);
+        field.reinforce(&hash, "crawler-3");
+
+        // Loaded with the default model, the journaled confidence still wins
+        let loaded = Field::load(&path, None).unwrap();
+        let (before, after) = (field.get(&hash).unwrap(), loaded.get(&hash).unwrap());
+        assert_eq!(after.reinforcement_count, 2);
+        assert!((after.confidence - before.confidence).abs() < 1e-9);
+        let _ = std::fs::remove_file(&path);
+    }
+}
@@ -1,167 +1,790 @@
 //! Dark web search engine registry
 //!
-//! Provides a list of known dark web search engines with their URL templates.
+//! Provides lists of known dark web search engines with their URL templates:
+//! Tor onion services and I2P eepsites, plus opt-in clearnet engines for
+//! surface-web mentions. Engines are tagged with the languages
+//! they index and the kinds of content they cover, so a query can be routed
+//! to the engines that suit it (see [`select_engines`]). Engines whose result
+//! markup a generic link scan misses can carry CSS selectors
+//! ([`ResultSelectors`]), configured per engine name in TOML. Engines that
+//! split results over several pages describe how to ask for the next one
+//! ([`Pagination`]). The engines themselves are data: a bundled list that
+//! files loaded at startup extend or override ([`SearchEngineRegistry`]).
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
 
 use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::QueryVariant;
+
+/// Bundled result selectors for engines with known markup
+const EMBEDDED_ENGINE_PARSERS: &str = include_str!("../data/engine_parsers.toml");
+
+/// Bundled search engines
+const EMBEDDED_SEARCH_ENGINES: &str = include_str!("../data/search_engines.toml");
+
+/// Network a site lives on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Network {
+    /// Tor onion services (`.onion`)
+    #[default]
+    Tor,
+    /// I2P eepsites (`.i2p`)
+    I2p,
+    /// The surface web (still reached through Tor)
+    Clearnet,
+}
+
+impl Network {
+    /// Network of any URL: hidden services as [`Network::of_url`], everything else clearnet
+    pub fn classify(url: &str) -> Self {
+        Self::of_url(url).unwrap_or(Network::Clearnet)
+    }
+
+    /// Hidden-service network of a URL, judged by its host suffix (None = not a hidden service)
+    pub fn of_url(url: &str) -> Option<Self> {
+        let rest = url.split_once("://").map_or(url, |(_, rest)| rest);
+        let authority = rest.split(['/', '?', '#']).next().unwrap_or_default();
+        let host = authority.rsplit('@').next().unwrap_or_default();
+        let host = host.split(':').next().unwrap_or_default().to_ascii_lowercase();
+        let host = host.trim_end_matches('.');
+
+        if host.ends_with(".onion") {
+            Some(Network::Tor)
+        } else if host.ends_with(".i2p") {
+            Some(Network::I2p)
+        } else {
+            None
+        }
+    }
+}
+
+impl fmt::Display for Network {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Network::Tor => write!(f, "tor"),
+            Network::I2p => write!(f, "i2p"),
+            Network::Clearnet => write!(f, "clearnet"),
+        }
+    }
+}
+
+/// HTTP method a search engine expects
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum HttpMethod {
+    #[default]
+    Get,
+    Post,
+}
+
+impl HttpMethod {
+    /// Method name as sent on the wire
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            HttpMethod::Get => "GET",
+            HttpMethod::Post => "POST",
+        }
+    }
+}
+
+impl fmt::Display for HttpMethod {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Kind of content a search engine is good at finding
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EngineCategory {
+    /// General index of onion services
+    Index,
+    /// Forums and discussion boards
+    Forums,
+    /// Marketplaces and vendor shops
+    Markets,
+    /// Leak sites and data dumps
+    Leaks,
+}
+
+impl EngineCategory {
+    /// Category name as used in configuration
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EngineCategory::Index => "index",
+            EngineCategory::Forums => "forums",
+            EngineCategory::Markets => "markets",
+            EngineCategory::Leaks => "leaks",
+        }
+    }
+}
+
+impl fmt::Display for EngineCategory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for EngineCategory {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "index" | "indexes" => Ok(EngineCategory::Index),
+            "forums" | "forum" => Ok(EngineCategory::Forums),
+            "markets" | "market" => Ok(EngineCategory::Markets),
+            "leaks" | "leak" => Ok(EngineCategory::Leaks),
+            other => Err(format!(
+                "unknown engine category '{}' (expected index, forums, markets or leaks)",
+                other
+            )),
+        }
+    }
+}
 
 /// A dark web search engine
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SearchEngine {
     /// Human-readable name
-    pub name: &'static str,
+    pub name: String,
     /// URL template with {query} placeholder
-    pub url_template: &'static str,
+    pub url_template: String,
     /// Whether this engine is currently active/reliable
+    #[serde(default = "default_active")]
     pub active: bool,
     /// Estimated reliability (0.0 - 1.0)
+    #[serde(default = "default_reliability")]
     pub reliability: f64,
+    /// Request method (POST engines take the query in `body_template`)
+    #[serde(default)]
+    pub method: HttpMethod,
+    /// Form-encoded request body with {query} placeholder, e.g. `q={query}&lang=any`
+    #[serde(default)]
+    pub body_template: Option<String>,
+    /// Extra request headers the engine requires (e.g. a `Referer`)
+    #[serde(default)]
+    pub headers: Vec<(String, String)>,
+    /// ISO 639-1 codes of the languages the engine indexes (empty = multilingual)
+    #[serde(default)]
+    pub languages: Vec<String>,
+    /// Kinds of content the engine covers
+    #[serde(default)]
+    pub categories: Vec<EngineCategory>,
+    /// How to find results in the engine's pages (None = generic link scan)
+    #[serde(default)]
+    pub selectors: Option<ResultSelectors>,
+    /// How to request later result pages (None = single page)
+    #[serde(default)]
+    pub pagination: Option<Pagination>,
+}
+
+fn default_active() -> bool {
+    true
+}
+
+fn default_reliability() -> f64 {
+    0.5
+}
+
+/// How a search engine selects a result page
+///
+/// The parameter is added to the query string of GET engines and to the
+/// form body of POST engines.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "style", rename_all = "snake_case")]
+pub enum Pagination {
+    /// `param` is the page number, counting from `first`
+    Page { param: String, first: u32 },
+    /// `param` is the index of the first result on the page
+    Offset { param: String, per_page: u32 },
+}
+
+impl Pagination {
+    /// `param=value` selecting page `page` (0 = the first page)
+    fn pair(&self, page: u32) -> String {
+        match self {
+            Pagination::Page { param, first } => format!("{}={}", param, first + page),
+            Pagination::Offset { param, per_page } => format!("{}={}", param, page * per_page),
+        }
+    }
+}
+
+/// CSS selectors locating results on a search engine's result page
+///
+/// `link`, `title` and `snippet` are matched inside each `container`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ResultSelectors {
+    /// One element per result
+    pub container: String,
+    /// Result link (its `href` is the result URL)
+    #[serde(default = "default_link_selector")]
+    pub link: String,
+    /// Result title (None = the link text)
+    #[serde(default)]
+    pub title: Option<String>,
+    /// Result description
+    #[serde(default)]
+    pub snippet: Option<String>,
+}
+
+fn default_link_selector() -> String {
+    "a".to_string()
+}
+
+/// Errors loading engine parser configuration
+#[derive(Debug, Error)]
+pub enum EngineParserError {
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Invalid engine parser file: {0}")]
+    Parse(#[from] toml::de::Error),
+}
+
+/// Result selectors by engine name, loaded from TOML
+///
+/// ```toml
+/// [Ahmia]
+/// container = "li.result"
+/// link = "h4 a"
+/// snippet = "p"
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct EngineParsers {
+    engines: BTreeMap<String, ResultSelectors>,
+}
+
+impl EngineParsers {
+    /// The bundled selectors
+    pub fn embedded() -> Self {
+        Self::from_toml(EMBEDDED_ENGINE_PARSERS).expect("bundled engine_parsers.toml is valid")
+    }
+
+    /// Parse a TOML table of engine name → selectors
+    pub fn from_toml(toml: &str) -> Result<Self, EngineParserError> {
+        Ok(toml::from_str(toml)?)
+    }
+
+    /// Load a TOML parser file
+    pub fn load_file(path: impl AsRef<Path>) -> Result<Self, EngineParserError> {
+        Self::from_toml(&std::fs::read_to_string(path)?)
+    }
+
+    /// Add `other`'s engines, replacing selectors for engines already present
+    pub fn merge(mut self, other: EngineParsers) -> Self {
+        self.engines.extend(other.engines);
+        self
+    }
+
+    /// Selectors for the engine called `name` (case-insensitive)
+    pub fn get(&self, name: &str) -> Option<&ResultSelectors> {
+        self.engines
+            .iter()
+            .find(|(engine, _)| engine.eq_ignore_ascii_case(name))
+            .map(|(_, selectors)| selectors)
+    }
+
+    pub fn len(&self) -> usize {
+        self.engines.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.engines.is_empty()
+    }
 }
 
 impl SearchEngine {
+    /// An active GET engine with default reliability and no metadata
+    pub fn new(name: &str, url_template: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            url_template: url_template.to_string(),
+            active: default_active(),
+            reliability: default_reliability(),
+            method: HttpMethod::Get,
+            body_template: None,
+            headers: Vec::new(),
+            languages: Vec::new(),
+            categories: Vec::new(),
+            selectors: None,
+            pagination: None,
+        }
+    }
+
+    /// A SearXNG instance at `base_url` (e.g. `https://searx.example.org`), read from its HTML results
+    pub fn searxng(base_url: &str) -> Self {
+        let base = base_url.trim_end_matches('/');
+        let host = base.split_once("://").map_or(base, |(_, rest)| rest);
+        Self {
+            reliability: 0.7,
+            categories: vec![EngineCategory::Index],
+            selectors: Some(ResultSelectors {
+                container: "article.result".to_string(),
+                link: "h3 a".to_string(),
+                title: None,
+                snippet: Some("p.content".to_string()),
+            }),
+            pagination: Some(Pagination::Page {
+                param: "pageno".to_string(),
+                first: 1,
+            }),
+            ..Self::new(&format!("SearXNG {}", host), &format!("{}/search?q={{query}}", base))
+        }
+    }
+
     /// Build search URL for a query
     pub fn build_url(&self, query: &str) -> String {
         self.url_template.replace("{query}", &urlencoded(query))
     }
+
+    /// Build the form-encoded request body for a query, if the engine takes one
+    pub fn build_body(&self, query: &str) -> Option<String> {
+        self.body_template
+            .as_ref()
+            .map(|template| template.replace("{query}", &urlencoded(query)))
+    }
+
+    /// Search URL for result page `page` (0 = first); None if the engine has no such page
+    pub fn build_page_url(&self, query: &str, page: u32) -> Option<String> {
+        let url = self.build_url(query);
+        match (page, &self.pagination, self.method) {
+            (0, _, _) | (_, Some(_), HttpMethod::Post) => Some(url),
+            (_, Some(pagination), HttpMethod::Get) => {
+                let separator = if url.contains('?') { '&' } else { '?' };
+                Some(format!("{}{}{}", url, separator, pagination.pair(page)))
+            }
+            (_, None, _) => None,
+        }
+    }
+
+    /// Request body for result page `page`, if the engine takes one
+    pub fn build_page_body(&self, query: &str, page: u32) -> Option<String> {
+        let body = self.build_body(query)?;
+        match &self.pagination {
+            Some(pagination) if page > 0 && self.method == HttpMethod::Post => {
+                Some(format!("{}&{}", body, pagination.pair(page)))
+            }
+            _ => Some(body),
+        }
+    }
+
+    /// Network the engine is reached over
+    pub fn network(&self) -> Network {
+        Network::classify(&self.url_template)
+    }
+
+    /// Whether the engine indexes `language` (ISO 639-1)
+    pub fn supports_language(&self, language: &str) -> bool {
+        self.languages.is_empty() || self.languages.iter().any(|l| l == language)
+    }
+
+    /// Whether the engine covers any of `focus` (an empty focus matches everything)
+    pub fn covers(&self, focus: &[EngineCategory]) -> bool {
+        focus.is_empty() || focus.iter().any(|c| self.categories.contains(c))
+    }
 }
 
-/// URL-encode a query string
+/// Language of a query, judged by its script (ISO 639-1)
+///
+/// Latin-script queries count as English; `None` means the query has no letters.
+pub fn query_language(query: &str) -> Option<&'static str> {
+    let mut counts: Vec<(&'static str, usize)> = Vec::new();
+    for c in query.chars().filter(|c| c.is_alphabetic()) {
+        let language = match c as u32 {
+            0x0400..=0x04FF => "ru",
+            0x0370..=0x03FF => "el",
+            0x0590..=0x05FF => "he",
+            0x0600..=0x06FF => "ar",
+            0x3040..=0x30FF => "ja",
+            0xAC00..=0xD7AF => "ko",
+            0x4E00..=0x9FFF => "zh",
+            _ => "en",
+        };
+        match counts.iter_mut().find(|(l, _)| *l == language) {
+            Some((_, n)) => *n += 1,
+            None => counts.push((language, 1)),
+        }
+    }
+    // Kana anywhere means Japanese, even if kanji dominate
+    if counts.iter().any(|(l, _)| *l == "ja") && counts.iter().any(|(l, _)| *l == "zh") {
+        return Some("ja");
+    }
+    counts.into_iter().max_by_key(|(_, n)| *n).map(|(l, _)| l)
+}
+
+/// Engines from `engines` suited to `query` and `focus`
+///
+/// Keeps engines that index the query's language and cover one of the focus
+/// categories. When nothing matches, every engine is returned so a query is
+/// never left uncrawled.
+pub fn select_engines<'a>(
+    engines: impl IntoIterator<Item = &'a SearchEngine>,
+    query: &str,
+    focus: &[EngineCategory],
+) -> Vec<&'a SearchEngine> {
+    let engines: Vec<_> = engines.into_iter().collect();
+    let language = query_language(query);
+    let selected: Vec<_> = engines
+        .iter()
+        .copied()
+        .filter(|e| language.is_none_or(|l| e.supports_language(l)) && e.covers(focus))
+        .collect();
+    if selected.is_empty() {
+        engines
+    } else {
+        selected
+    }
+}
+
+/// The query to search `engine` with: the first variant for a category it covers, else `query`
+///
+/// Variants for general indexes come last, so an engine covering both an
+/// index and forums gets the forum phrasing.
+pub fn query_for<'a>(engine: &SearchEngine, query: &'a str, variants: &'a [QueryVariant]) -> &'a str {
+    variants
+        .iter()
+        .filter(|v| engine.categories.contains(&v.category))
+        .min_by_key(|v| v.category == EngineCategory::Index)
+        .map_or(query, |v| v.query.as_str())
+}
+
+/// URL-encode a query string (form encoding over UTF-8 bytes)
 fn urlencoded(s: &str) -> String {
-    s.chars()
-        .map(|c| match c {
-            'a'..='z' | 'A'..='Z' | '0'..='9' | '-' | '_' | '.' | '~' => c.to_string(),
-            ' ' => "+".to_string(),
-            _ => format!("%{:02X}", c as u8),
+    s.bytes()
+        .map(|b| match b {
+            b'a'..=b'z' | b'A'..=b'Z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => (b as char).to_string(),
+            b' ' => "+".to_string(),
+            _ => format!("%{:02X}", b),
         })
         .collect()
 }
 
-/// Default list of dark web search engines
-pub static DEFAULT_SEARCH_ENGINES: &[SearchEngine] = &[
-    SearchEngine {
-        name: "Ahmia",
-        url_template: "http://juhanurmihxlp77nkq76byazcldy2hlmovfu2epvl5ankdibsot4csyd.onion/search/?q={query}",
-        active: true,
-        reliability: 0.9,
-    },
-    SearchEngine {
-        name: "OnionLand",
-        url_template: "http://3bbad7fauom4d6sgppalyqddsqbf5u5p56b5k5uk2zxsy3d6ey2jobad.onion/search?q={query}",
-        active: true,
-        reliability: 0.8,
-    },
-    SearchEngine {
-        name: "Torgle",
-        url_template: "http://iy3544gmoeclh5de6gez2256v6pjh4omhpqdh2wpeeppjtvqmjhkfwad.onion/torgle/?query={query}",
-        active: true,
-        reliability: 0.7,
-    },
-    SearchEngine {
-        name: "Amnesia",
-        url_template: "http://amnesia7u5odx5xbwtpnqk3edybgud5bmiagu75bnqx2crntw5kry7ad.onion/search?query={query}",
-        active: true,
-        reliability: 0.75,
-    },
-    SearchEngine {
-        name: "Kaizer",
-        url_template: "http://kaizerwfvp5gxu6cppibp7jhcqptavq3iqef66wbxenh6a2fklibdvid.onion/search?q={query}",
-        active: true,
-        reliability: 0.7,
-    },
-    SearchEngine {
-        name: "Anima",
-        url_template: "http://anima4ffe27xmakwnseih3ic2y7y3l6e7fucwk4oerdn4odf7k74tbid.onion/search?q={query}",
-        active: true,
-        reliability: 0.65,
-    },
-    SearchEngine {
-        name: "Tornado",
-        url_template: "http://tornadoxn3viscgz647shlysdy7ea5zqzwda7hierekeuokh5eh5b3qd.onion/search?q={query}",
-        active: true,
-        reliability: 0.7,
-    },
-    SearchEngine {
-        name: "TorNet",
-        url_template: "http://tornetupfu7gcgidt33ftnungxzyfq2pygui5qdoyss34xbgx2qruzid.onion/search?q={query}",
-        active: true,
-        reliability: 0.65,
-    },
-    SearchEngine {
-        name: "Torland",
-        url_template: "http://torlbmqwtudkorme6prgfpmsnile7ug2zm4u3ejpcncxuhpu4k2j4kyd.onion/index.php?a=search&q={query}",
-        active: true,
-        reliability: 0.6,
-    },
-    SearchEngine {
-        name: "FindTor",
-        url_template: "http://findtorroveq5wdnipkaojfpqulxnkhblymc7aramjzajcvpptd4rjqd.onion/search?q={query}",
-        active: true,
-        reliability: 0.7,
-    },
-    SearchEngine {
-        name: "Excavator",
-        url_template: "http://2fd6cemt4gmccflhm6imvdfvli3nf7zn6rfrwpsy7uhxrgbypvwf5fad.onion/search?query={query}",
-        active: true,
-        reliability: 0.65,
-    },
-    SearchEngine {
-        name: "Onionway",
-        url_template: "http://oniwayzz74cv2puhsgx4dpjwieww4wdphsydqvf5q7eyz4myjvyw26ad.onion/search.php?s={query}",
-        active: true,
-        reliability: 0.6,
-    },
-    SearchEngine {
-        name: "Tor66",
-        url_template: "http://tor66sewebgixwhcqfnp5inzp5x5uohhdy3kvtnyfxc2e5mxiuh34iid.onion/search?q={query}",
-        active: true,
-        reliability: 0.75,
-    },
-    SearchEngine {
-        name: "OSS",
-        url_template: "http://3fzh7yuupdfyjhwt3ugzqqof6ulbcl27ecev33knxe3u7goi3vfn2qqd.onion/oss/index.php?search={query}",
-        active: true,
-        reliability: 0.5,
-    },
-    SearchEngine {
-        name: "Torgol",
-        url_template: "http://torgolnpeouim56dykfob6jh5r2ps2j73enc42s2um4ufob3ny4fcdyd.onion/?q={query}",
-        active: true,
-        reliability: 0.6,
-    },
-    SearchEngine {
-        name: "TheDeepSearches",
-        url_template: "http://searchgf7gdtauh7bhnbyed4ivxqmuoat3nm6zfrg3ymkq6mtnpye3ad.onion/search?q={query}",
-        active: true,
-        reliability: 0.7,
-    },
-];
-
-/// Get all active search engines
-pub fn active_engines() -> impl Iterator<Item = &'static SearchEngine> {
-    DEFAULT_SEARCH_ENGINES.iter().filter(|e| e.active)
-}
-
-/// Get engines sorted by reliability (highest first)
-pub fn engines_by_reliability() -> Vec<&'static SearchEngine> {
-    let mut engines: Vec<_> = active_engines().collect();
-    engines.sort_by(|a, b| b.reliability.partial_cmp(&a.reliability).unwrap());
-    engines
+/// Errors loading a search engine registry
+#[derive(Debug, Error)]
+pub enum EngineRegistryError {
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Invalid engine file: {0}")]
+    Toml(#[from] toml::de::Error),
+
+    #[error("Invalid engine file: {0}")]
+    Json(#[from] serde_json::Error),
+
+    #[error("Cannot write engine file: {0}")]
+    TomlWrite(#[from] toml::ser::Error),
+
+    #[error("Engine '{0}' has no {{query}} placeholder in its URL or body template")]
+    MissingPlaceholder(String),
+}
+
+/// Search engines the crawler can use
+///
+/// Starts from the bundled list; files loaded at startup add engines or
+/// replace bundled ones of the same name, so a moved or dead engine can be
+/// fixed (or set `active = false`) without recompiling. TOML files hold
+/// `[[engine]]` tables, JSON files an array of engines:
+///
+/// ```toml
+/// [[engine]]
+/// name = "Ahmia"
+/// url_template = "http://newmirror.onion/search/?q={query}"
+/// ```
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SearchEngineRegistry {
+    #[serde(default, rename = "engine")]
+    engines: Vec<SearchEngine>,
+}
+
+impl SearchEngineRegistry {
+    /// The bundled engines
+    pub fn embedded() -> Self {
+        Self::from_toml(EMBEDDED_SEARCH_ENGINES).expect("bundled search_engines.toml is valid")
+    }
+
+    /// A registry of exactly these engines
+    pub fn from_engines(engines: Vec<SearchEngine>) -> Self {
+        Self { engines }
+    }
+
+    /// Parse `[[engine]]` tables
+    pub fn from_toml(toml: &str) -> Result<Self, EngineRegistryError> {
+        Self::validated(toml::from_str::<Self>(toml)?.engines)
+    }
+
+    /// Parse a JSON array of engines
+    pub fn from_json(json: &str) -> Result<Self, EngineRegistryError> {
+        Self::validated(serde_json::from_str(json)?)
+    }
+
+    /// Load an engine file, JSON if it ends in `.json` and TOML otherwise
+    pub fn load_file(path: impl AsRef<Path>) -> Result<Self, EngineRegistryError> {
+        let path = path.as_ref();
+        let contents = std::fs::read_to_string(path)?;
+        if path.extension().is_some_and(|e| e.eq_ignore_ascii_case("json")) {
+            Self::from_json(&contents)
+        } else {
+            Self::from_toml(&contents)
+        }
+    }
+
+    /// Write the registry as an engine file [`SearchEngineRegistry::load_file`] reads back
+    pub fn save_file(&self, path: impl AsRef<Path>) -> Result<(), EngineRegistryError> {
+        let path = path.as_ref();
+        let contents = if path.extension().is_some_and(|e| e.eq_ignore_ascii_case("json")) {
+            serde_json::to_string_pretty(&self.engines)?
+        } else {
+            toml::to_string(self)?
+        };
+        std::fs::write(path, contents)?;
+        Ok(())
+    }
+
+    fn validated(engines: Vec<SearchEngine>) -> Result<Self, EngineRegistryError> {
+        for engine in &engines {
+            let in_body = engine.body_template.as_ref().is_some_and(|b| b.contains("{query}"));
+            if !engine.url_template.contains("{query}") && !in_body {
+                return Err(EngineRegistryError::MissingPlaceholder(engine.name.clone()));
+            }
+        }
+        Ok(Self { engines })
+    }
+
+    /// Add `other`'s engines, replacing engines of the same name (case-insensitive)
+    pub fn merge(mut self, other: SearchEngineRegistry) -> Self {
+        for engine in other.engines {
+            match self.engines.iter_mut().find(|e| e.name.eq_ignore_ascii_case(&engine.name)) {
+                Some(existing) => *existing = engine,
+                None => self.engines.push(engine),
+            }
+        }
+        self
+    }
+
+    /// The engine called `name` (case-insensitive)
+    pub fn get(&self, name: &str) -> Option<&SearchEngine> {
+        self.engines.iter().find(|e| e.name.eq_ignore_ascii_case(name))
+    }
+
+    /// Set the reliability of the engine called `name`, clamped to 0.0 - 1.0
+    ///
+    /// Returns false when there is no such engine.
+    pub fn set_reliability(&mut self, name: &str, reliability: f64) -> bool {
+        match self.engines.iter_mut().find(|e| e.name.eq_ignore_ascii_case(name)) {
+            Some(engine) => {
+                engine.reliability = reliability.clamp(0.0, 1.0);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Every engine, active or not
+    pub fn engines(&self) -> &[SearchEngine] {
+        &self.engines
+    }
+
+    /// Active engines reached over `network`
+    pub fn active(&self, network: Network) -> impl Iterator<Item = &SearchEngine> {
+        self.engines.iter().filter(move |e| e.active && e.network() == network)
+    }
+
+    /// Active engines on `network`, sorted by reliability (highest first)
+    pub fn by_reliability(&self, network: Network) -> Vec<&SearchEngine> {
+        let mut engines: Vec<_> = self.active(network).collect();
+        engines.sort_by(|a, b| b.reliability.total_cmp(&a.reliability));
+        engines
+    }
+
+    pub fn len(&self) -> usize {
+        self.engines.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.engines.is_empty()
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn bundled(name: &str) -> SearchEngine {
+        SearchEngineRegistry::embedded().get(name).unwrap().clone()
+    }
+
     #[test]
     fn test_build_url() {
-        let engine = &DEFAULT_SEARCH_ENGINES[0];
+        let engine = bundled("Ahmia");
         let url = engine.build_url("ransomware payments");
         assert!(url.contains("ransomware+payments"));
         assert!(url.ends_with(".onion/search/?q=ransomware+payments"));
     }
 
+    #[test]
+    fn test_build_post_body() {
+        let engine = SearchEngine {
+            method: HttpMethod::Post,
+            body_template: Some("q={query}&page=1".to_string()),
+            headers: vec![("Referer".to_string(), "http://form.onion/".to_string())],
+            ..SearchEngine::new("FormEngine", "http://form.onion/search")
+        };
+        assert_eq!(engine.build_url("lockbit leaks"), "http://form.onion/search");
+        assert_eq!(engine.build_body("lockbit leaks").unwrap(), "q=lockbit+leaks&page=1");
+        assert!(bundled("Ahmia").build_body("x").is_none());
+    }
+
+    #[test]
+    fn test_page_requests() {
+        let onionland = bundled("OnionLand");
+        assert_eq!(onionland.build_page_url("x", 0).unwrap(), onionland.build_url("x"));
+        assert!(onionland.build_page_url("x", 2).unwrap().ends_with("search?q=x&page=3"));
+        let legwork = bundled("Legwork");
+        assert!(legwork.build_page_url("x", 1).unwrap().ends_with("&startRecord=50"));
+        // Single-page engines have no second page
+        assert!(bundled("Ahmia").build_page_url("x", 1).is_none());
+
+        let form = SearchEngine {
+            method: HttpMethod::Post,
+            body_template: Some("q={query}".to_string()),
+            pagination: Some(Pagination::Offset { param: "start".to_string(), per_page: 20 }),
+            ..SearchEngine::new("FormEngine", "http://form.onion/search")
+        };
+        assert_eq!(form.build_page_url("x", 1).unwrap(), "http://form.onion/search");
+        assert_eq!(form.build_page_body("x", 1).unwrap(), "q=x&start=20");
+    }
+
+    #[test]
+    fn test_engine_parsers() {
+        let parsers = EngineParsers::embedded();
+        let ahmia = parsers.get("ahmia").unwrap();
+        assert_eq!(ahmia.container, "li.result");
+
+        let custom = EngineParsers::from_toml("[Torgle]\ncontainer = \"div.hit\"\n").unwrap();
+        assert_eq!(custom.get("Torgle").unwrap().link, "a");
+        let merged = parsers.merge(custom);
+        assert!(merged.get("Torgle").is_some() && merged.get("Ahmia").is_some());
+        assert!(EngineParsers::from_toml("[Torgle]\nlink = \"a\"\n").is_err());
+    }
+
+    #[test]
+    fn test_urlencoded_utf8() {
+        assert_eq!(urlencoded("a&b"), "a%26b");
+        // Multi-byte characters encode every UTF-8 byte
+        assert_eq!(urlencoded("é"), "%C3%A9");
+    }
+
     #[test]
     fn test_active_engines() {
-        let count = active_engines().count();
-        assert!(count >= 10);
+        let registry = SearchEngineRegistry::embedded();
+        assert!(registry.active(Network::Tor).count() >= 10);
+        assert_eq!(registry.active(Network::I2p).count(), 3);
+        assert!(registry.active(Network::Clearnet).all(|e| e.selectors.is_some()));
+
+        let searx = SearchEngine::searxng("https://searx.example.org/");
+        assert_eq!(searx.name, "SearXNG searx.example.org");
+        assert_eq!(searx.network(), Network::Clearnet);
+        assert!(searx.build_page_url("a b", 1).unwrap().ends_with("/search?q=a+b&pageno=2"));
+        assert_eq!(registry.by_reliability(Network::Tor)[0].name, "Ahmia");
+    }
+
+    #[test]
+    fn test_registry_overrides() {
+        let custom = SearchEngineRegistry::from_toml(
+            "[[engine]]\nname = \"ahmia\"\nurl_template = \"http://newmirror.onion/?q={query}\"\n\n\
+             [[engine]]\nname = \"Torgle\"\nurl_template = \"http://x.onion/?q={query}\"\nactive = false\n",
+        )
+        .unwrap();
+        let json = SearchEngineRegistry::from_json(
+            r#"[{"name": "NewEngine", "url_template": "http://new.onion/s", "method": "POST",
+                 "body_template": "q={query}", "categories": ["leaks"]}]"#,
+        )
+        .unwrap();
+        let registry = SearchEngineRegistry::embedded().merge(custom).merge(json);
+
+        assert_eq!(registry.get("Ahmia").unwrap().build_url("x"), "http://newmirror.onion/?q=x");
+        assert!(registry.active(Network::Tor).all(|e| e.name != "Torgle"));
+        let new = registry.get("NewEngine").unwrap();
+        assert_eq!(new.build_body("a b").unwrap(), "q=a+b");
+        assert_eq!(new.categories, vec![EngineCategory::Leaks]);
+        assert_eq!(registry.len(), SearchEngineRegistry::embedded().len() + 1);
+
+        let missing = SearchEngineRegistry::from_toml("[[engine]]\nname = \"Bad\"\nurl_template = \"http://bad.onion/\"\n");
+        assert!(matches!(missing, Err(EngineRegistryError::MissingPlaceholder(name)) if name == "Bad"));
+    }
+
+    #[test]
+    fn test_registry_round_trip() {
+        let mut registry = SearchEngineRegistry::embedded();
+        assert!(registry.set_reliability("ahmia", 1.7));
+        assert!(!registry.set_reliability("NoSuchEngine", 0.1));
+
+        for name in ["toml", "json"] {
+            let path = std::env::temp_dir().join(format!("robin-engines-{}.{}", std::process::id(), name));
+            registry.save_file(&path).unwrap();
+            let loaded = SearchEngineRegistry::load_file(&path).unwrap();
+            std::fs::remove_file(&path).unwrap();
+
+            assert_eq!(loaded.len(), registry.len());
+            assert_eq!(loaded.get("Ahmia").unwrap().reliability, 1.0);
+            assert_eq!(loaded.get("OnionLand").unwrap().pagination, bundled("OnionLand").pagination);
+        }
+    }
+
+    #[test]
+    fn test_query_language() {
+        assert_eq!(query_language("lockbit affiliates"), Some("en"));
+        assert_eq!(query_language("продажа баз данных"), Some("ru"));
+        assert_eq!(query_language("ランサムウェア 攻撃"), Some("ja"));
+        assert_eq!(query_language("1234 !!"), None);
+    }
+
+    #[test]
+    fn test_select_engines() {
+        let registry = SearchEngineRegistry::embedded();
+        let active_engines = || registry.active(Network::Tor);
+        let forums = select_engines(active_engines(), "carding", &[EngineCategory::Forums]);
+        assert!(!forums.is_empty() && forums.len() < active_engines().count());
+        assert!(forums.iter().all(|e| e.categories.contains(&EngineCategory::Forums)));
+
+        // Russian queries only go to multilingual engines
+        let russian = select_engines(active_engines(), "утечка", &[]);
+        assert!(russian.iter().all(|e| e.languages.is_empty()));
+
+        // Nothing matches: fall back to every engine
+        let none = select_engines(active_engines(), "утечка", &[EngineCategory::Leaks]);
+        assert_eq!(none.len(), active_engines().count());
+    }
+
+    #[test]
+    fn test_query_for_engine() {
+        let variant = |category, query: &str| QueryVariant { category, query: query.to_string() };
+        let variants = vec![
+            variant(EngineCategory::Index, "lockbit ransomware group"),
+            variant(EngineCategory::Leaks, "lockbit victim data leak"),
+        ];
+        let mut engine = SearchEngine::new("E", "http://e.onion/?q={query}");
+        engine.categories = vec![EngineCategory::Index, EngineCategory::Leaks];
+        assert_eq!(query_for(&engine, "lockbit ransomware", &variants), "lockbit victim data leak");
+        engine.categories = vec![EngineCategory::Forums];
+        assert_eq!(query_for(&engine, "lockbit ransomware", &variants), "lockbit ransomware");
+        assert_eq!(query_for(&engine, "lockbit ransomware", &[]), "lockbit ransomware");
+    }
+
+    #[test]
+    fn test_network_of_url() {
+        assert_eq!(Network::of_url("http://legwork.i2p/search?q=x"), Some(Network::I2p));
+        assert_eq!(Network::of_url("http://abc.b32.i2p:8080/"), Some(Network::I2p));
+        assert_eq!(Network::of_url("http://user@abc.onion/"), Some(Network::Tor));
+        assert_eq!(Network::of_url("https://example.com/?u=abc.onion"), None);
+        assert_eq!(Network::classify("https://example.com/?u=abc.onion"), Network::Clearnet);
+        assert_eq!(Network::classify("http://legwork.i2p/"), Network::I2p);
     }
 }
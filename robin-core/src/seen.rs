@@ -0,0 +1,163 @@
+//! Cross-run record of crawled and scraped URLs
+//!
+//! Repeated investigations of the same topic tend to turn up the same
+//! search results. A [`SeenStore`] remembers every result URL crawlers
+//! emitted and every page scrapers fetched, with when it was first seen,
+//! so later runs can skip them. Only URLs from earlier runs are skipped:
+//! within a run, duplicates still reinforce as usual. Scrapers record a
+//! page once it was fetched, so pages that were skipped or failed are
+//! tried again next run.
+//!
+//! The store is one JSON file (sealed with the run's at-rest key), loaded
+//! whole and saved at the end of a run, rather than an embedded database:
+//! it holds URLs only, and stays small next to the other stores.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use thiserror::Error;
+
+use crate::{read_maybe_sealed, write_sealed, AtRestError, AtRestKey};
+
+/// A seen-URL store shared by crawlers, scrapers and whoever saves it
+pub type SharedSeen = Arc<Mutex<SeenStore>>;
+
+/// Errors loading or saving a seen-URL store
+#[derive(Debug, Error)]
+pub enum SeenError {
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Invalid seen-URL store: {0}")]
+    Parse(#[from] serde_json::Error),
+
+    #[error("Encryption error: {0}")]
+    Sealed(#[from] AtRestError),
+}
+
+/// Which stage saw a URL
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SeenKind {
+    /// Returned by a search engine
+    Result,
+    /// Fetched by a scraper
+    Scraped,
+}
+
+/// URLs seen by past runs, with first-seen times
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SeenStore {
+    #[serde(default)]
+    results: BTreeMap<String, DateTime<Utc>>,
+    #[serde(default)]
+    scraped: BTreeMap<String, DateTime<Utc>>,
+    /// Start of the current run; entries from before it count as seen
+    #[serde(skip, default = "Utc::now")]
+    opened_at: DateTime<Utc>,
+}
+
+impl Default for SeenStore {
+    fn default() -> Self {
+        Self {
+            results: BTreeMap::new(),
+            scraped: BTreeMap::new(),
+            opened_at: Utc::now(),
+        }
+    }
+}
+
+impl SeenStore {
+    /// Load a store written by [`SeenStore::save`]; a missing file is an empty store
+    pub fn load(path: impl AsRef<Path>, key: Option<&AtRestKey>) -> Result<Self, SeenError> {
+        let path = path.as_ref();
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        Ok(serde_json::from_slice(&read_maybe_sealed(path, key)?)?)
+    }
+
+    /// Write the store as JSON, sealed when a key is given
+    pub fn save(&self, path: impl AsRef<Path>, key: Option<&AtRestKey>) -> Result<(), SeenError> {
+        let json = serde_json::to_vec_pretty(self)?;
+        match key {
+            Some(key) => write_sealed(path, key, &json)?,
+            None => std::fs::write(path, json)?,
+        }
+        Ok(())
+    }
+
+    fn urls(&self, kind: SeenKind) -> &BTreeMap<String, DateTime<Utc>> {
+        match kind {
+            SeenKind::Result => &self.results,
+            SeenKind::Scraped => &self.scraped,
+        }
+    }
+
+    /// Whether an earlier run already saw `url`
+    pub fn seen_before(&self, kind: SeenKind, url: &str) -> bool {
+        self.urls(kind).get(url).is_some_and(|&at| at < self.opened_at)
+    }
+
+    /// Remember `url` (the first-seen time of known URLs is kept)
+    pub fn record(&mut self, kind: SeenKind, url: &str) {
+        let urls = match kind {
+            SeenKind::Result => &mut self.results,
+            SeenKind::Scraped => &mut self.scraped,
+        };
+        urls.entry(url.to_string()).or_insert_with(Utc::now);
+    }
+
+    /// Treat URLs from earlier runs as unseen for this run (they are still remembered)
+    pub fn ignore_earlier_runs(&mut self) {
+        self.opened_at = DateTime::<Utc>::MIN_UTC;
+    }
+
+    /// Forget URLs first seen before `cutoff`, so they get crawled again; returns how many
+    pub fn prune(&mut self, cutoff: DateTime<Utc>) -> usize {
+        let before = self.results.len() + self.scraped.len();
+        self.results.retain(|_, at| *at >= cutoff);
+        self.scraped.retain(|_, at| *at >= cutoff);
+        before - self.results.len() - self.scraped.len()
+    }
+
+    /// Number of URLs of `kind` remembered
+    pub fn len(&self, kind: SeenKind) -> usize {
+        self.urls(kind).len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.results.is_empty() && self.scraped.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_only_earlier_runs_count_as_seen() {
+        let path = std::env::temp_dir().join(format!("robin-seen-{}.json", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let mut store = SeenStore::load(&path, None).unwrap();
+        store.record(SeenKind::Result, "http://a.onion/");
+        assert!(!store.seen_before(SeenKind::Result, "http://a.onion/"));
+        store.save(&path, None).unwrap();
+
+        let mut next_run = SeenStore::load(&path, None).unwrap();
+        assert!(next_run.seen_before(SeenKind::Result, "http://a.onion/"));
+        assert!(!next_run.seen_before(SeenKind::Scraped, "http://a.onion/"));
+        assert_eq!(next_run.len(SeenKind::Result), 1);
+
+        let mut fresh = next_run.clone();
+        fresh.ignore_earlier_runs();
+        assert!(!fresh.seen_before(SeenKind::Result, "http://a.onion/"));
+
+        assert_eq!(next_run.prune(Utc::now()), 1);
+        assert!(next_run.is_empty());
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,254 @@
+//! Per-payload-kind signal lifetimes
+//!
+//! Agents give each signal a TTL and decay suited to a responsive network
+//! (results live 90s, pages 180s, ...). Over a slow Tor circuit a scrape
+//! can outlast the result it was started from, so a field can carry a
+//! [`SignalPolicy`] that overrides lifetimes per payload kind and stretches
+//! all of them by a common factor. It is applied on [`crate::Field::emit`],
+//! before emission hooks run.
+//!
+//! Policies load from TOML:
+//!
+//! ```toml
+//! ttl_scale = 2.0          # everything lives twice as long and decays half as fast
+//!
+//! [kinds.raw_result]
+//! ttl = 300
+//! decay_function = "Linear"
+//!
+//! [kinds.heartbeat]
+//! decay_function = { HalfLife = { half_life = 5.0 } }
+//! ```
+//!
+//! A policy can also cap how many signals the field holds, in total and per
+//! payload kind. Beyond a cap the weakest signals (lowest effective
+//! intensity, then oldest) are evicted to history:
+//!
+//! ```toml
+//! max_signals = 20000
+//!
+//! [max_per_kind]
+//! raw_result = 5000
+//! ```
+//!
+//! Queries and summaries ([`NEVER_EVICTED`]) drive and end a run, so no cap
+//! evicts them. Heartbeats don't count toward `max_signals`.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+use thiserror::Error;
+
+use crate::{DecayFunction, PayloadKind, Signal};
+
+/// Payload kinds no cap evicts
+pub const NEVER_EVICTED: [PayloadKind; 3] = [PayloadKind::UserQuery, PayloadKind::RefinedQuery, PayloadKind::Summary];
+
+/// Errors loading a signal policy
+#[derive(Debug, Error)]
+pub enum SignalPolicyError {
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Invalid signal policy: {0}")]
+    Parse(#[from] toml::de::Error),
+
+    #[error("Invalid signal policy: {0}")]
+    Invalid(String),
+}
+
+/// Lifetime overrides for one payload kind (None = keep what the agent chose)
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Lifetime {
+    /// Time to live in seconds
+    #[serde(default)]
+    pub ttl: Option<f64>,
+    /// Decay rate parameter
+    #[serde(default)]
+    pub decay_rate: Option<f64>,
+    #[serde(default)]
+    pub decay_function: Option<DecayFunction>,
+}
+
+/// TTL and decay applied to emitted signals
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SignalPolicy {
+    /// Stretches every lifetime: TTLs are multiplied and decay rates divided by it
+    #[serde(default = "default_ttl_scale")]
+    pub ttl_scale: f64,
+    /// Per-payload-kind overrides, applied before scaling
+    #[serde(default)]
+    pub kinds: HashMap<PayloadKind, Lifetime>,
+    /// Most active signals in the field (None = unbounded)
+    #[serde(default)]
+    pub max_signals: Option<usize>,
+    /// Most active signals of each payload kind
+    #[serde(default)]
+    pub max_per_kind: HashMap<PayloadKind, usize>,
+}
+
+fn default_ttl_scale() -> f64 {
+    1.0
+}
+
+impl Default for SignalPolicy {
+    fn default() -> Self {
+        Self {
+            ttl_scale: default_ttl_scale(),
+            kinds: HashMap::new(),
+            max_signals: None,
+            max_per_kind: HashMap::new(),
+        }
+    }
+}
+
+impl SignalPolicy {
+    /// Parse a TOML policy
+    pub fn from_toml(toml: &str) -> Result<Self, SignalPolicyError> {
+        let policy: Self = toml::from_str(toml)?;
+        policy.validate()?;
+        Ok(policy)
+    }
+
+    /// Load a TOML policy file
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, SignalPolicyError> {
+        Self::from_toml(&std::fs::read_to_string(path)?)
+    }
+
+    /// Stretch every lifetime by `scale`
+    pub fn with_ttl_scale(mut self, scale: f64) -> Self {
+        self.ttl_scale = scale;
+        self
+    }
+
+    /// Override the lifetime of one payload kind
+    pub fn with_lifetime(mut self, kind: PayloadKind, lifetime: Lifetime) -> Self {
+        self.kinds.insert(kind, lifetime);
+        self
+    }
+
+    /// Hold at most `max` active signals
+    pub fn with_max_signals(mut self, max: usize) -> Self {
+        self.max_signals = Some(max);
+        self
+    }
+
+    /// Hold at most `max` active signals of `kind`
+    pub fn with_kind_limit(mut self, kind: PayloadKind, max: usize) -> Self {
+        self.max_per_kind.insert(kind, max);
+        self
+    }
+
+    /// Cap on active signals of `kind`, if any
+    pub fn kind_limit(&self, kind: PayloadKind) -> Option<usize> {
+        self.max_per_kind.get(&kind).copied()
+    }
+
+    fn validate(&self) -> Result<(), SignalPolicyError> {
+        if !(self.ttl_scale.is_finite() && self.ttl_scale > 0.0) {
+            return Err(SignalPolicyError::Invalid(format!("ttl_scale must be positive, got {}", self.ttl_scale)));
+        }
+        for (kind, lifetime) in &self.kinds {
+            if lifetime.ttl.is_some_and(|ttl| !(ttl.is_finite() && ttl > 0.0)) {
+                return Err(SignalPolicyError::Invalid(format!("{} ttl must be positive", kind)));
+            }
+            if lifetime.decay_rate.is_some_and(|rate| !(rate.is_finite() && rate >= 0.0)) {
+                return Err(SignalPolicyError::Invalid(format!("{} decay_rate must not be negative", kind)));
+            }
+            if lifetime.decay_function.as_ref().is_some_and(|f| !f.is_valid()) {
+                return Err(SignalPolicyError::Invalid(format!("{} decay_function parameters must be positive", kind)));
+            }
+        }
+        if self.max_signals == Some(0) {
+            return Err(SignalPolicyError::Invalid("max_signals must be positive".to_string()));
+        }
+        if let Some(kind) = self.max_per_kind.iter().find(|(_, &max)| max == 0).map(|(kind, _)| kind) {
+            return Err(SignalPolicyError::Invalid(format!("max_per_kind.{} must be positive", kind)));
+        }
+        Ok(())
+    }
+
+    /// Whether the policy leaves signals as agents built them (caps aside)
+    pub fn is_identity(&self) -> bool {
+        self.ttl_scale == 1.0 && self.kinds.is_empty()
+    }
+
+    /// Set the signal's TTL and decay from the policy
+    pub fn apply(&self, signal: &mut Signal) {
+        if let Some(lifetime) = self.kinds.get(&signal.payload.kind()) {
+            signal.ttl = lifetime.ttl.unwrap_or(signal.ttl);
+            signal.decay_rate = lifetime.decay_rate.unwrap_or(signal.decay_rate);
+            if let Some(decay_function) = &lifetime.decay_function {
+                signal.decay_function = decay_function.clone();
+            }
+        }
+        signal.ttl *= self.ttl_scale;
+        signal.decay_rate /= self.ttl_scale;
+        signal.decay_function = signal.decay_function.stretched(self.ttl_scale);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::OsintPayload;
+
+    #[test]
+    fn test_policy_overrides_and_scales_lifetimes() {
+        let policy = SignalPolicy::from_toml(
+            "ttl_scale = 2.0\n\n[kinds.raw_result]\nttl = 300\ndecay_function = \"Linear\"\n",
+        )
+        .unwrap();
+        let result = |ttl| {
+            Signal::builder(OsintPayload::RawResult {
+                url: "http://a.onion/".to_string(),
+                title: "A".to_string(),
+                engine: "Ahmia".to_string(),
+                network: crate::Network::Tor,
+                query: None,
+            })
+            .ttl(ttl)
+            .decay_rate(0.1)
+            .build()
+        };
+
+        let mut raw = result(90.0);
+        policy.apply(&mut raw);
+        assert_eq!((raw.ttl, raw.decay_rate, raw.decay_function), (600.0, 0.05, DecayFunction::Linear));
+
+        // Kinds without an override are only scaled
+        let mut query = Signal::builder(OsintPayload::UserQuery {
+            query: "lockbit".to_string(),
+            priority: 1.0,
+        })
+        .ttl(60.0)
+        .build();
+        policy.apply(&mut query);
+        assert_eq!(query.ttl, 120.0);
+        assert_eq!(query.decay_function, DecayFunction::Exponential);
+
+        // Parameterized curves stretch with the scale too
+        let halving = SignalPolicy::from_toml(
+            "ttl_scale = 2.0\n\n[kinds.user_query]\ndecay_function = { HalfLife = { half_life = 5.0 } }\n",
+        )
+        .unwrap();
+        halving.apply(&mut query);
+        assert_eq!(query.decay_function, DecayFunction::HalfLife { half_life: 10.0 });
+        assert!(matches!(
+            SignalPolicy::from_toml("[kinds.user_query]\ndecay_function = { HalfLife = { half_life = 0.0 } }"),
+            Err(SignalPolicyError::Invalid(_))
+        ));
+
+        assert!(SignalPolicy::default().is_identity());
+        assert!(matches!(SignalPolicy::from_toml("ttl_scale = 0"), Err(SignalPolicyError::Invalid(_))));
+        assert!(matches!(SignalPolicy::from_toml("[kinds.raw_result]\nttl = -1"), Err(SignalPolicyError::Invalid(_))));
+        assert!(matches!(SignalPolicy::from_toml("[kinds.raw_result]\ntll = 5"), Err(SignalPolicyError::Parse(_))));
+
+        let capped = SignalPolicy::from_toml("max_signals = 100\n\n[max_per_kind]\nraw_result = 10\n").unwrap();
+        assert_eq!((capped.max_signals, capped.kind_limit(PayloadKind::RawResult)), (Some(100), Some(10)));
+        assert!(capped.is_identity());
+        assert!(matches!(SignalPolicy::from_toml("[max_per_kind]\nraw_result = 0"), Err(SignalPolicyError::Invalid(_))));
+    }
+}
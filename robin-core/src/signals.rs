@@ -8,13 +8,112 @@
 
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, LazyLock, RwLock};
 use uuid::Uuid;
 
-use crate::{Artifact, DEFAULT_DECAY_RATE, DEFAULT_TTL};
+use crate::{AdditiveModel, Artifact, EngineCategory, Network, OriginHashPolicy, ReinforcementModel, DEFAULT_DECAY_RATE, DEFAULT_TTL};
+
+/// A refined query phrased for one kind of search engine
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QueryVariant {
+    pub category: EngineCategory,
+    pub query: String,
+}
+
+/// Where a chunk of a long scraped page sits in the page
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ContentChunk {
+    /// Position among the page's chunks, from 0
+    pub index: usize,
+    /// Number of chunks the page was split into
+    pub count: usize,
+    /// Byte offset of the chunk in the page text
+    pub offset: usize,
+}
+
+/// Structured metadata of a scraped page, kept apart from its flattened text
+///
+/// These are the attribution leads plain text loses: who the page says wrote
+/// it, how it describes itself when shared, whom to contact and pay, the
+/// operator's PGP key, and when things were posted.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PageMetadata {
+    /// `<meta>` tags by name (author, description, generator, ...)
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub meta: BTreeMap<String, String>,
+    /// OpenGraph properties, without the `og:` prefix
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub open_graph: BTreeMap<String, String>,
+    /// Addresses of `mailto:` links
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub emails: Vec<String>,
+    /// `bitcoin:`, `monero:`, `ethereum:` and `litecoin:` payment URIs
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub payment_uris: Vec<String>,
+    /// ASCII-armored PGP public key blocks
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub pgp_keys: Vec<String>,
+    /// Timestamps shown on the page, as written
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub timestamps: Vec<String>,
+    /// Hashes of the page's images, when image hashing is on
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub images: Vec<ImageDigest>,
+}
+
+/// Hashes of an image downloaded from a page
+///
+/// The SHA-256 matches byte-identical files; the difference hash also
+/// matches the same picture re-encoded, resized or lightly edited (compare
+/// with a Hamming distance of a few bits).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ImageDigest {
+    /// Image URL, or `data:` and the MIME type for an inline image
+    pub url: String,
+    /// Size in bytes
+    pub bytes: usize,
+    /// Hex SHA-256 of the file
+    pub sha256: String,
+    /// 64-bit difference hash as 16 hex digits (None = not a decodable raster image)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dhash: Option<String>,
+}
+
+impl PageMetadata {
+    pub fn is_empty(&self) -> bool {
+        self.meta.is_empty()
+            && self.open_graph.is_empty()
+            && self.emails.is_empty()
+            && self.payment_uris.is_empty()
+            && self.pgp_keys.is_empty()
+            && self.timestamps.is_empty()
+            && self.images.is_empty()
+    }
+
+    /// One `key: value` line per field, for artifact extraction and reports
+    ///
+    /// PGP keys and images are counted rather than quoted.
+    pub fn render(&self) -> String {
+        let mut lines: Vec<String> = self.meta.iter().map(|(k, v)| format!("{}: {}", k, v)).collect();
+        lines.extend(self.open_graph.iter().map(|(k, v)| format!("og:{}: {}", k, v)));
+        lines.extend(self.emails.iter().map(|e| format!("mailto: {}", e)));
+        lines.extend(self.payment_uris.iter().map(|u| format!("payment: {}", u)));
+        if !self.pgp_keys.is_empty() {
+            lines.push(format!("pgp public keys: {}", self.pgp_keys.len()));
+        }
+        if !self.timestamps.is_empty() {
+            lines.push(format!("timestamps: {}", self.timestamps.join(", ")));
+        }
+        if !self.images.is_empty() {
+            lines.push(format!("images hashed: {}", self.images.len()));
+        }
+        lines.join("\n")
+    }
+}
 
 /// Decay functions for signal intensity over time
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
 pub enum DecayFunction {
     /// Exponential decay: I(t) = I₀ * e^(-λt)
     #[default]
@@ -23,6 +122,80 @@ pub enum DecayFunction {
     Linear,
     /// Step function: full intensity until TTL, then zero
     Step,
+    /// Exponential decay by half-life in seconds: I(t) = I₀ * 0.5^(t/h)
+    HalfLife { half_life: f64 },
+    /// Sigmoid: near full intensity until `midpoint` seconds, then a drop
+    /// whose sharpness is `steepness` (per second)
+    Logistic { midpoint: f64, steepness: f64 },
+    /// A curve registered with [`register_decay`] under this name
+    /// (exponential if none is)
+    Custom(String),
+}
+
+/// Fraction of its initial intensity a signal keeps, from its age and TTL in seconds
+pub type DecayCurve = Arc<dyn Fn(f64, f64) -> f64 + Send + Sync>;
+
+/// Curves of [`DecayFunction::Custom`] by name
+static DECAY_CURVES: LazyLock<RwLock<HashMap<String, DecayCurve>>> = LazyLock::new(Default::default);
+
+/// Register the curve used by `DecayFunction::Custom(name)`, replacing any
+/// curve registered under that name before
+///
+/// Results are clamped to 0..=1.
+pub fn register_decay<F>(name: &str, curve: F)
+where
+    F: Fn(f64, f64) -> f64 + Send + Sync + 'static,
+{
+    DECAY_CURVES.write().unwrap().insert(name.to_string(), Arc::new(curve));
+}
+
+/// Curve registered under `name`, if any
+pub fn decay_curve(name: &str) -> Option<DecayCurve> {
+    DECAY_CURVES.read().unwrap().get(name).cloned()
+}
+
+impl DecayFunction {
+    /// Whether the function's parameters are usable (positive and finite)
+    pub fn is_valid(&self) -> bool {
+        match self {
+            DecayFunction::HalfLife { half_life } => half_life.is_finite() && *half_life > 0.0,
+            DecayFunction::Logistic { midpoint, steepness } => {
+                midpoint.is_finite() && *midpoint >= 0.0 && steepness.is_finite() && *steepness > 0.0
+            }
+            DecayFunction::Custom(name) => !name.is_empty(),
+            _ => true,
+        }
+    }
+
+    /// The same shape over a lifetime `scale` times as long
+    pub fn stretched(&self, scale: f64) -> Self {
+        match self {
+            DecayFunction::HalfLife { half_life } => DecayFunction::HalfLife { half_life: half_life * scale },
+            DecayFunction::Logistic { midpoint, steepness } => DecayFunction::Logistic {
+                midpoint: midpoint * scale,
+                steepness: steepness / scale,
+            },
+            other => other.clone(),
+        }
+    }
+
+    /// Fraction of the initial intensity left after `age` seconds (before the TTL cut-off)
+    pub fn remaining(&self, age: f64, ttl: f64, decay_rate: f64) -> f64 {
+        match self {
+            DecayFunction::Exponential => (-decay_rate * age).exp(),
+            DecayFunction::Linear => (1.0 - age / ttl).max(0.0),
+            DecayFunction::Step => 1.0,
+            DecayFunction::HalfLife { half_life } => 0.5f64.powf(age / half_life),
+            // Scaled so a fresh signal starts at full intensity
+            DecayFunction::Logistic { midpoint, steepness } => {
+                (1.0 + (-steepness * midpoint).exp()) / (1.0 + (steepness * (age - midpoint)).exp())
+            }
+            DecayFunction::Custom(name) => match decay_curve(name) {
+                Some(curve) => curve(age, ttl).clamp(0.0, 1.0),
+                None => (-decay_rate * age).exp(),
+            },
+        }
+    }
 }
 
 /// OSINT-specific signal payload types
@@ -38,8 +211,15 @@ pub enum OsintPayload {
     /// Query refined for dark web search engines
     RefinedQuery {
         original: String,
+        /// Sanitized search terms, safe to put in a search URL
         refined: String,
         confidence: f64,
+        /// What the LLM answered, when sanitizing changed it
+        #[serde(default)]
+        raw: Option<String>,
+        /// Phrasings tailored to engine categories (engines without one get `refined`)
+        #[serde(default)]
+        variants: Vec<QueryVariant>,
     },
 
     /// Raw result from a search engine
@@ -47,6 +227,12 @@ pub enum OsintPayload {
         url: String,
         title: String,
         engine: String,
+        /// Network the result lives on
+        #[serde(default)]
+        network: Network,
+        /// Query the engine was searched with, when a variant rather than the refined query
+        #[serde(default)]
+        query: Option<String>,
     },
 
     /// Result filtered as relevant by an agent
@@ -63,6 +249,22 @@ pub enum OsintPayload {
         title: String,
         text: String,
         char_count: usize,
+        /// PNG of the rendered page, if screenshots were taken
+        #[serde(default)]
+        screenshot: Option<String>,
+        /// Language of the page (ISO 639-1), if detected
+        #[serde(default)]
+        language: Option<String>,
+        /// The page's own text, when `text` is an English translation of it
+        #[serde(default)]
+        original_text: Option<String>,
+        /// Which part of the page `text` is, for pages split into chunks (None = the whole page)
+        #[serde(default)]
+        chunk: Option<ContentChunk>,
+        /// Meta tags, OpenGraph, contact/payment URIs, PGP keys and timestamps
+        /// (on the first chunk only; None = nothing found)
+        #[serde(default)]
+        metadata: Option<PageMetadata>,
     },
 
     /// Artifacts extracted from scraped content
@@ -120,6 +322,42 @@ pub enum OsintPayload {
         created_at: Option<String>,
         /// Paste author if available
         author: Option<String>,
+        /// Language of the paste (ISO 639-1), if detected
+        #[serde(default)]
+        language: Option<String>,
+        /// Density of secrets and IOCs, 0.0 - 1.0 (see `robin_agents::score_paste`)
+        #[serde(default)]
+        score: f64,
+    },
+
+    /// A page scraped by an earlier run changed since
+    ContentChanged {
+        url: String,
+        title: String,
+        /// Content hashes of the previous and current version
+        previous_hash: String,
+        hash: String,
+        added_lines: usize,
+        removed_lines: usize,
+        /// Line counts and a few quoted added and removed lines
+        summary: String,
+    },
+
+    /// Messages one author wrote in a chat log or forum thread
+    ThreadMessages {
+        /// Page or paste the conversation was found in
+        source_url: String,
+        format: crate::ThreadFormat,
+        author: String,
+        /// The author's messages, in conversation order
+        messages: Vec<crate::MessageRecord>,
+        /// Messages in the whole conversation
+        thread_messages: usize,
+    },
+
+    /// What the field holds about one actor or handle, refreshed as evidence arrives
+    ActorProfile {
+        profile: crate::ActorProfile,
     },
 
     /// Heartbeat signal for agent liveness
@@ -137,6 +375,144 @@ pub enum OsintPayload {
     },
 }
 
+/// Payload type discriminant, without the payload data
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PayloadKind {
+    UserQuery,
+    RefinedQuery,
+    RawResult,
+    FilteredResult,
+    ScrapedContent,
+    ExtractedArtifacts,
+    Insight,
+    Summary,
+    EnrichedArtifacts,
+    BlockchainAnalysis,
+    PasteContent,
+    ContentChanged,
+    ThreadMessages,
+    ActorProfile,
+    Heartbeat,
+    TaskClaim,
+}
+
+impl PayloadKind {
+    /// All payload kinds
+    pub const ALL: &'static [PayloadKind] = &[
+        PayloadKind::UserQuery,
+        PayloadKind::RefinedQuery,
+        PayloadKind::RawResult,
+        PayloadKind::FilteredResult,
+        PayloadKind::ScrapedContent,
+        PayloadKind::ExtractedArtifacts,
+        PayloadKind::Insight,
+        PayloadKind::Summary,
+        PayloadKind::EnrichedArtifacts,
+        PayloadKind::BlockchainAnalysis,
+        PayloadKind::PasteContent,
+        PayloadKind::ContentChanged,
+        PayloadKind::ThreadMessages,
+        PayloadKind::ActorProfile,
+        PayloadKind::Heartbeat,
+        PayloadKind::TaskClaim,
+    ];
+
+    /// Snake-case name, matching the payload's serialized `type` tag
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PayloadKind::UserQuery => "user_query",
+            PayloadKind::RefinedQuery => "refined_query",
+            PayloadKind::RawResult => "raw_result",
+            PayloadKind::FilteredResult => "filtered_result",
+            PayloadKind::ScrapedContent => "scraped_content",
+            PayloadKind::ExtractedArtifacts => "extracted_artifacts",
+            PayloadKind::Insight => "insight",
+            PayloadKind::Summary => "summary",
+            PayloadKind::EnrichedArtifacts => "enriched_artifacts",
+            PayloadKind::BlockchainAnalysis => "blockchain_analysis",
+            PayloadKind::PasteContent => "paste_content",
+            PayloadKind::ContentChanged => "content_changed",
+            PayloadKind::ThreadMessages => "thread_messages",
+            PayloadKind::ActorProfile => "actor_profile",
+            PayloadKind::Heartbeat => "heartbeat",
+            PayloadKind::TaskClaim => "task_claim",
+        }
+    }
+}
+
+impl std::fmt::Display for PayloadKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for PayloadKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        PayloadKind::ALL
+            .iter()
+            .copied()
+            .find(|kind| kind.as_str() == s)
+            .ok_or_else(|| format!("unknown payload kind: {}", s))
+    }
+}
+
+impl OsintPayload {
+    /// Lowercased values of every field, one per line, for case-insensitive matching
+    ///
+    /// Field names and the payload type are left out, so searching for
+    /// `url` doesn't match every payload that has a URL.
+    pub fn searchable_text(&self) -> String {
+        fn push_values(value: &serde_json::Value, text: &mut String) {
+            match value {
+                serde_json::Value::String(s) => {
+                    text.push_str(s);
+                    text.push('\n');
+                }
+                serde_json::Value::Number(n) => {
+                    text.push_str(&n.to_string());
+                    text.push('\n');
+                }
+                serde_json::Value::Array(items) => items.iter().for_each(|item| push_values(item, text)),
+                serde_json::Value::Object(fields) => fields.values().for_each(|field| push_values(field, text)),
+                serde_json::Value::Bool(_) | serde_json::Value::Null => {}
+            }
+        }
+
+        let mut value = serde_json::to_value(self).unwrap_or_default();
+        if let Some(fields) = value.as_object_mut() {
+            fields.remove("type");
+        }
+        let mut text = String::new();
+        push_values(&value, &mut text);
+        text.to_lowercase()
+    }
+
+    /// Payload type discriminant
+    pub fn kind(&self) -> PayloadKind {
+        match self {
+            OsintPayload::UserQuery { .. } => PayloadKind::UserQuery,
+            OsintPayload::RefinedQuery { .. } => PayloadKind::RefinedQuery,
+            OsintPayload::RawResult { .. } => PayloadKind::RawResult,
+            OsintPayload::FilteredResult { .. } => PayloadKind::FilteredResult,
+            OsintPayload::ScrapedContent { .. } => PayloadKind::ScrapedContent,
+            OsintPayload::ExtractedArtifacts { .. } => PayloadKind::ExtractedArtifacts,
+            OsintPayload::Insight { .. } => PayloadKind::Insight,
+            OsintPayload::Summary { .. } => PayloadKind::Summary,
+            OsintPayload::EnrichedArtifacts { .. } => PayloadKind::EnrichedArtifacts,
+            OsintPayload::BlockchainAnalysis { .. } => PayloadKind::BlockchainAnalysis,
+            OsintPayload::PasteContent { .. } => PayloadKind::PasteContent,
+            OsintPayload::ContentChanged { .. } => PayloadKind::ContentChanged,
+            OsintPayload::ThreadMessages { .. } => PayloadKind::ThreadMessages,
+            OsintPayload::ActorProfile { .. } => PayloadKind::ActorProfile,
+            OsintPayload::Heartbeat { .. } => PayloadKind::Heartbeat,
+            OsintPayload::TaskClaim { .. } => PayloadKind::TaskClaim,
+        }
+    }
+}
+
 /// Categories of intelligence insights
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
@@ -206,12 +582,19 @@ pub struct EnrichmentFinding {
 pub enum AgentType {
     Refiner,
     Crawler,
+    Directory,
     Filter,
     Scraper,
     Extractor,
     Enricher,
     BlockchainAnalyst,
     PasteMonitor,
+    FeedWatcher,
+    GistWatcher,
+    TargetMonitor,
+    ChangeDetector,
+    ThreadParser,
+    Profiler,
     Analyst,
 }
 
@@ -221,7 +604,7 @@ pub struct Signal {
     /// Unique signal instance ID
     pub id: Uuid,
 
-    /// Content-based hash for deduplication/reinforcement
+    /// Content-based hash for deduplication/reinforcement (see [`OriginHashPolicy`])
     pub origin_hash: String,
 
     /// The payload (OSINT-specific data)
@@ -256,6 +639,19 @@ pub struct Signal {
 
     /// IDs of agents that reinforced this signal
     pub reinforced_by: Vec<String>,
+
+    /// Origin hashes of the signals this one was derived from
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub parent_hashes: Vec<String>,
+
+    /// Free-form labels (engagement, campaign, severity, ...) for slicing findings
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+
+    /// Investigation the signal belongs to, when several share the field
+    /// (inherited from parents on emit; see [`Field::sense_in`](crate::Field::sense_in))
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub investigation_id: Option<String>,
 }
 
 impl Signal {
@@ -276,17 +672,7 @@ impl Signal {
             return 0.0;
         }
 
-        match self.decay_function {
-            DecayFunction::Exponential => self.intensity * (-self.decay_rate * age).exp(),
-            DecayFunction::Linear => (self.intensity * (1.0 - age / self.ttl)).max(0.0),
-            DecayFunction::Step => {
-                if age < self.ttl {
-                    self.intensity
-                } else {
-                    0.0
-                }
-            }
-        }
+        self.intensity * self.decay_function.remaining(age, self.ttl, self.decay_rate)
     }
 
     /// Effective intensity = base * confidence * reinforcement boost
@@ -304,22 +690,28 @@ impl Signal {
 
     /// Reinforce this signal (agreement from another agent)
     pub fn reinforce(&mut self, reinforcer_id: &str) {
-        if !self.reinforced_by.contains(&reinforcer_id.to_string()) {
-            self.reinforced_by.push(reinforcer_id.to_string());
-            self.reinforcement_count += 1;
+        self.reinforce_with(reinforcer_id, None, &AdditiveModel::default());
+    }
 
-            // Boost confidence with diminishing returns
-            let boost = 0.1 / (1.0 + self.reinforcement_count as f64 * 0.5);
-            self.confidence = (self.confidence + boost).min(1.0);
+    /// Reinforce this signal, with `model` deciding the new confidence
+    ///
+    /// Returns false if `reinforcer_id` already reinforced it.
+    pub fn reinforce_with(&mut self, reinforcer_id: &str, opinion: Option<f64>, model: &dyn ReinforcementModel) -> bool {
+        if self.reinforced_by.iter().any(|id| id == reinforcer_id) {
+            return false;
         }
+        self.confidence = model.reinforce(self, reinforcer_id, opinion).clamp(0.0, 1.0);
+        self.reinforced_by.push(reinforcer_id.to_string());
+        self.reinforcement_count += 1;
+        true
     }
 
-    fn compute_origin_hash(payload: &OsintPayload, origin_agent_id: &str) -> String {
-        let mut hasher = Sha256::new();
-        let payload_json = serde_json::to_string(payload).unwrap_or_default();
-        hasher.update(payload_json.as_bytes());
-        hasher.update(origin_agent_id.as_bytes());
-        format!("{:x}", hasher.finalize())[..16].to_string()
+}
+
+/// Add `tag` to `tags` unless it is already there
+pub fn add_tag(tags: &mut Vec<String>, tag: &str) {
+    if !tags.iter().any(|t| t == tag) {
+        tags.push(tag.to_string());
     }
 }
 
@@ -332,6 +724,9 @@ pub struct SignalBuilder {
     decay_function: DecayFunction,
     confidence: f64,
     origin_agent_id: String,
+    parent_hashes: Vec<String>,
+    tags: Vec<String>,
+    investigation_id: Option<String>,
 }
 
 impl SignalBuilder {
@@ -344,6 +739,9 @@ impl SignalBuilder {
             decay_function: DecayFunction::default(),
             confidence: 1.0,
             origin_agent_id: String::new(),
+            parent_hashes: Vec::new(),
+            tags: Vec::new(),
+            investigation_id: None,
         }
     }
 
@@ -367,6 +765,24 @@ impl SignalBuilder {
         self
     }
 
+    /// Decay exponentially, halving every `secs` seconds
+    pub fn half_life(self, secs: f64) -> Self {
+        self.decay_function(DecayFunction::HalfLife { half_life: secs.max(f64::MIN_POSITIVE) })
+    }
+
+    /// Stay near full intensity until `midpoint` seconds, then drop off
+    pub fn logistic(self, midpoint: f64, steepness: f64) -> Self {
+        self.decay_function(DecayFunction::Logistic {
+            midpoint: midpoint.max(0.0),
+            steepness: steepness.max(f64::MIN_POSITIVE),
+        })
+    }
+
+    /// Decay along the curve registered as `name` with [`register_decay`]
+    pub fn custom_decay(self, name: &str) -> Self {
+        self.decay_function(DecayFunction::Custom(name.to_string()))
+    }
+
     pub fn confidence(mut self, confidence: f64) -> Self {
         self.confidence = confidence.clamp(0.0, 1.0);
         self
@@ -377,9 +793,43 @@ impl SignalBuilder {
         self
     }
 
+    /// Record a signal this one was derived from
+    pub fn parent(mut self, hash: &str) -> Self {
+        if !self.parent_hashes.iter().any(|h| h == hash) {
+            self.parent_hashes.push(hash.to_string());
+        }
+        self
+    }
+
+    /// Record several signals this one was derived from
+    pub fn parents<I, S>(self, hashes: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        hashes.into_iter().fold(self, |builder, hash| builder.parent(hash.as_ref()))
+    }
+
+    /// Label the signal
+    pub fn tag(mut self, tag: &str) -> Self {
+        add_tag(&mut self.tags, tag);
+        self
+    }
+
+    /// Put the signal in an investigation
+    pub fn investigation(mut self, id: &str) -> Self {
+        self.investigation_id = Some(id.to_string());
+        self
+    }
+
     pub fn build(self) -> Signal {
         let now = Utc::now();
-        let origin_hash = Signal::compute_origin_hash(&self.payload, &self.origin_agent_id);
+        let origin_hash = OriginHashPolicy::default().compute_in(
+            self.investigation_id.as_deref(),
+            &self.payload,
+            &self.origin_agent_id,
+            now,
+        );
 
         Signal {
             id: Uuid::new_v4(),
@@ -395,6 +845,9 @@ impl SignalBuilder {
             created_at: now,
             reinforcement_count: 0,
             reinforced_by: Vec::new(),
+            parent_hashes: self.parent_hashes,
+            tags: self.tags,
+            investigation_id: self.investigation_id,
         }
     }
 }
@@ -435,6 +888,45 @@ mod tests {
         assert!((intensity - 0.368).abs() < 0.01);
     }
 
+    #[test]
+    fn test_half_life_logistic_and_custom_decay() {
+        let query = || {
+            Signal::builder(OsintPayload::UserQuery {
+                query: "test".to_string(),
+                priority: 1.0,
+            })
+            .ttl(600.0)
+        };
+        let at = |signal: &Signal, secs| signal.compute_intensity(signal.created_at + chrono::Duration::seconds(secs));
+
+        let halving = query().half_life(30.0).build();
+        assert!((at(&halving, 30) - 0.5).abs() < 1e-9);
+        assert!((at(&halving, 60) - 0.25).abs() < 1e-9);
+
+        // Holds near full strength, then falls through half at the midpoint
+        let finding = query().logistic(300.0, 0.05).build();
+        assert!((at(&finding, 0) - 1.0).abs() < 1e-9);
+        assert!(at(&finding, 120) > 0.99);
+        assert!((at(&finding, 300) - 0.5).abs() < 0.01);
+        assert!(at(&finding, 450) < 0.01);
+
+        register_decay("test-cliff", |age, ttl| if age < ttl / 2.0 { 1.0 } else { 0.2 });
+        let cliff = query().custom_decay("test-cliff").build();
+        assert_eq!(at(&cliff, 299), 1.0);
+        assert_eq!(at(&cliff, 301), 0.2);
+        // Unregistered curves fall back to exponential decay
+        let unknown = query().custom_decay("test-unregistered").decay_rate(0.1).build();
+        assert!((at(&unknown, 10) - 0.368).abs() < 0.01);
+
+        assert_eq!(
+            DecayFunction::Logistic { midpoint: 300.0, steepness: 0.05 }.stretched(2.0),
+            DecayFunction::Logistic { midpoint: 600.0, steepness: 0.025 }
+        );
+        assert!(!DecayFunction::HalfLife { half_life: 0.0 }.is_valid());
+        let json = serde_json::to_string(&DecayFunction::HalfLife { half_life: 30.0 }).unwrap();
+        assert_eq!(serde_json::from_str::<DecayFunction>(&json).unwrap(), DecayFunction::HalfLife { half_life: 30.0 });
+    }
+
     #[test]
     fn test_reinforcement() {
         let mut signal = Signal::builder(OsintPayload::UserQuery {
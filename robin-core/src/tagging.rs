@@ -0,0 +1,306 @@
+//! Rule-based signal tagging
+//!
+//! Deployments that run many engagements through one swarm need to slice
+//! findings by engagement, campaign or severity afterwards. A [`Tagger`] is
+//! an emission hook that labels each signal as it enters the field with:
+//!
+//! - the fixed tags of the run (`engagement:acme`, `playbook:ransomware`)
+//! - `query:<id>` on each user query, from its origin hash
+//! - the tags of the signals it was derived from, so everything found for
+//!   a tagged query carries that query's tags
+//! - the tags of every [`TagRule`] it matches
+//!
+//! Parents that already left the field pass nothing on. Tags are kept in
+//! field snapshots and on the cases of the knowledge store.
+//!
+//! Rules load from TOML:
+//!
+//! ```toml
+//! tags = ["engagement:acme"]
+//!
+//! [[rules]]
+//! tags = ["severity:high"]
+//! artifact_types = ["credit_card", "bitcoin"]
+//!
+//! [[rules]]
+//! tags = ["org:acme"]
+//! contains = ["acme.com", "Acme Corp"]
+//! ```
+
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::sync::Arc;
+use thiserror::Error;
+
+use crate::{add_tag, ArtifactType, EmitContext, EmitHook, HookAction, OsintPayload, PayloadKind, SharedHook, Signal};
+
+/// Hex digits of the origin hash used in `query:` tags
+const QUERY_ID_LEN: usize = 8;
+
+/// Errors loading tag rules
+#[derive(Debug, Error)]
+pub enum TaggingError {
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Invalid tag rules: {0}")]
+    Parse(#[from] toml::de::Error),
+
+    #[error("Invalid tag rules: {0}")]
+    Invalid(String),
+}
+
+/// Tags given to signals that match every condition set
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TagRule {
+    pub tags: Vec<String>,
+    /// Payload kinds the rule applies to (empty = all)
+    #[serde(default)]
+    pub kinds: Vec<PayloadKind>,
+    /// Any of these appears in a value of the payload, ignoring case (field names don't count)
+    #[serde(default)]
+    pub contains: Vec<String>,
+    /// The payload carries an artifact of one of these types
+    #[serde(default)]
+    pub artifact_types: Vec<ArtifactType>,
+    #[serde(default)]
+    pub min_confidence: Option<f64>,
+}
+
+impl TagRule {
+    /// Rule adding `tags` to every signal
+    pub fn new<I, S>(tags: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self {
+            tags: tags.into_iter().map(Into::into).collect(),
+            ..Default::default()
+        }
+    }
+
+    pub fn with_kinds(mut self, kinds: &[PayloadKind]) -> Self {
+        self.kinds = kinds.to_vec();
+        self
+    }
+
+    pub fn with_contains(mut self, text: &str) -> Self {
+        self.contains.push(text.to_string());
+        self
+    }
+
+    pub fn with_artifact_type(mut self, artifact_type: ArtifactType) -> Self {
+        self.artifact_types.push(artifact_type);
+        self
+    }
+
+    pub fn with_min_confidence(mut self, confidence: f64) -> Self {
+        self.min_confidence = Some(confidence);
+        self
+    }
+
+    /// Whether `signal` meets the rule's conditions
+    pub fn matches(&self, signal: &Signal) -> bool {
+        if !self.kinds.is_empty() && !self.kinds.contains(&signal.payload.kind()) {
+            return false;
+        }
+        if self.min_confidence.is_some_and(|min| signal.confidence < min) {
+            return false;
+        }
+        if !self.artifact_types.is_empty() {
+            let artifacts = match &signal.payload {
+                OsintPayload::ExtractedArtifacts { artifacts, .. } => artifacts.as_slice(),
+                OsintPayload::EnrichedArtifacts { artifact, .. } => std::slice::from_ref(artifact),
+                _ => &[],
+            };
+            if !artifacts.iter().any(|a| self.artifact_types.contains(&a.artifact_type)) {
+                return false;
+            }
+        }
+        if !self.contains.is_empty() {
+            let text = signal.payload.searchable_text();
+            if !self.contains.iter().any(|term| text.contains(&term.to_lowercase())) {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// What a [`Tagger`] tags
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TagRules {
+    /// Tags for every signal
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Tag user queries with `query:<id>`
+    #[serde(default = "default_true")]
+    pub query_ids: bool,
+    /// Copy the tags of parent signals
+    #[serde(default = "default_true")]
+    pub inherit: bool,
+    #[serde(default)]
+    pub rules: Vec<TagRule>,
+}
+
+impl Default for TagRules {
+    fn default() -> Self {
+        Self {
+            tags: Vec::new(),
+            query_ids: true,
+            inherit: true,
+            rules: Vec::new(),
+        }
+    }
+}
+
+impl TagRules {
+    /// Parse TOML rules
+    pub fn from_toml(toml: &str) -> Result<Self, TaggingError> {
+        let rules: Self = toml::from_str(toml)?;
+        rules.validate()?;
+        Ok(rules)
+    }
+
+    /// Load a TOML rules file
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, TaggingError> {
+        Self::from_toml(&std::fs::read_to_string(path)?)
+    }
+
+    /// Tag every signal with `tag`
+    pub fn with_tag(mut self, tag: &str) -> Self {
+        add_tag(&mut self.tags, tag);
+        self
+    }
+
+    pub fn with_rule(mut self, rule: TagRule) -> Self {
+        self.rules.push(rule);
+        self
+    }
+
+    fn validate(&self) -> Result<(), TaggingError> {
+        if self.tags.iter().chain(self.rules.iter().flat_map(|r| &r.tags)).any(|t| t.trim().is_empty()) {
+            return Err(TaggingError::Invalid("tags must not be empty".to_string()));
+        }
+        if let Some(i) = self.rules.iter().position(|r| r.tags.is_empty()) {
+            return Err(TaggingError::Invalid(format!("rule {} has no tags", i + 1)));
+        }
+        Ok(())
+    }
+}
+
+/// Emission hook applying [`TagRules`]
+#[derive(Debug, Clone, Default)]
+pub struct Tagger {
+    rules: TagRules,
+}
+
+impl Tagger {
+    pub fn new(rules: TagRules) -> Self {
+        Self { rules }
+    }
+
+    pub fn rules(&self) -> &TagRules {
+        &self.rules
+    }
+
+    /// Wrap for adding to fields
+    pub fn shared(self) -> SharedHook {
+        Arc::new(self)
+    }
+}
+
+impl EmitHook for Tagger {
+    fn name(&self) -> &str {
+        "tagger"
+    }
+
+    fn on_emit(&self, signal: &mut Signal, ctx: &mut EmitContext<'_>) -> HookAction {
+        let mut tags = std::mem::take(&mut signal.tags);
+        for tag in &self.rules.tags {
+            add_tag(&mut tags, tag);
+        }
+        if self.rules.query_ids && matches!(signal.payload, OsintPayload::UserQuery { .. }) {
+            let mut id = ctx.field().hash_policy().hash_signal(signal);
+            id.truncate(QUERY_ID_LEN);
+            add_tag(&mut tags, &format!("query:{}", id));
+        }
+        if self.rules.inherit {
+            for parent in signal.parent_hashes.iter().filter_map(|hash| ctx.field().get(hash)) {
+                for tag in &parent.tags {
+                    add_tag(&mut tags, tag);
+                }
+            }
+        }
+        for rule in self.rules.rules.iter().filter(|rule| rule.matches(signal)) {
+            for tag in &rule.tags {
+                add_tag(&mut tags, tag);
+            }
+        }
+        signal.tags = tags;
+        HookAction::Pass
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Artifact, Field};
+
+    #[test]
+    fn test_tagger_labels_and_inherits() {
+        let rules = TagRules::from_toml(
+            "tags = [\"engagement:acme\"]\n\n[[rules]]\ntags = [\"severity:high\"]\nartifact_types = [\"credit_card\"]\n\n\
+             [[rules]]\ntags = [\"org:acme\"]\ncontains = [\"ACME.com\"]\nkinds = [\"extracted_artifacts\"]\n",
+        )
+        .unwrap();
+        let mut field = Field::new().with_hook(Tagger::new(rules).shared());
+
+        let query = field.emit(
+            Signal::builder(OsintPayload::UserQuery {
+                query: "acme leak".to_string(),
+                priority: 1.0,
+            })
+            .build(),
+        );
+        let query_tags = field.get(&query).unwrap().tags.clone();
+        assert_eq!(query_tags[0], "engagement:acme");
+        assert_eq!(query_tags[1], format!("query:{}", &query[..QUERY_ID_LEN]));
+
+        let found = field.emit(
+            Signal::builder(OsintPayload::ExtractedArtifacts {
+                source_url: "http://a.onion/".to_string(),
+                artifacts: vec![
+                    Artifact::new(ArtifactType::CreditCard, "4111-XXXX-XXXX-1111".to_string()),
+                    Artifact::new(ArtifactType::Email, "ops@acme.com".to_string()),
+                ],
+            })
+            .origin("extractor-01")
+            .parent(&query)
+            .tag("manual")
+            .build(),
+        );
+        let tags = &field.get(&found).unwrap().tags;
+        for tag in ["manual", "engagement:acme", &query_tags[1], "severity:high", "org:acme"] {
+            assert!(tags.iter().any(|t| t == tag), "missing {} in {:?}", tag, tags);
+        }
+        assert_eq!(tags.len(), 5);
+
+        // Terms are matched against payload values, not field names or the payload type
+        for term in ["source_url", "artifact_type", "extracted_artifacts"] {
+            let rule = TagRule::new(["x"]).with_contains(term);
+            assert!(!rule.matches(field.get(&found).unwrap()), "{} matched", term);
+        }
+        assert!(TagRule::new(["x"]).with_contains("A.ONION").matches(field.get(&found).unwrap()));
+
+        assert!(matches!(TagRules::from_toml("[[rules]]\ntags = []"), Err(TaggingError::Invalid(_))));
+        assert!(matches!(TagRules::from_toml("[[rules]]\ntag = [\"x\"]"), Err(TaggingError::Parse(_))));
+    }
+}
@@ -0,0 +1,297 @@
+//! Chat log and forum thread parsing
+//!
+//! Scraped pages and pastes are often conversations: IRC or Jabber logs,
+//! WhatsApp and Discord/Telegram exports, forum threads. [`parse_thread`]
+//! recognizes these layouts and splits them into [`MessageRecord`]s (author
+//! handle, timestamp, text, quoted replies), so findings can be attributed
+//! to, and aggregated per, the handle that wrote them.
+//!
+//! Lines that don't start a message continue the one before; lines quoted
+//! with `>`, and blocks under a forum "X wrote:" or "Quote from: X" header,
+//! become the message's quotes rather than its text.
+
+use chrono::NaiveDateTime;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::LazyLock;
+
+/// Fewest messages for text to count as a conversation
+pub const MIN_THREAD_MESSAGES: usize = 2;
+
+/// Conversation layouts recognized by [`parse_thread`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ThreadFormat {
+    /// `[12:34] <nick> message`
+    Irc,
+    /// `[2024-01-02 12:34] handle: message` (Jabber, Discord and Telegram exports)
+    BracketedChat,
+    /// `1/2/24, 12:34 - Name: message`
+    WhatsApp,
+    /// Posts under `Posted by handle on date` headers
+    Forum,
+}
+
+/// Text a message quotes from an earlier one
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct QuotedReply {
+    /// Handle quoted, if the quote names it
+    pub author: Option<String>,
+    pub text: String,
+}
+
+/// One message of a conversation
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MessageRecord {
+    /// Handle of the author
+    pub author: String,
+    /// Timestamp as written
+    pub timestamp: Option<String>,
+    /// Timestamp, when it gives an unambiguous date
+    #[serde(default)]
+    pub sent_at: Option<NaiveDateTime>,
+    pub text: String,
+    #[serde(default)]
+    pub quotes: Vec<QuotedReply>,
+    /// Position in the conversation, from 0
+    pub index: usize,
+}
+
+/// A conversation split into messages
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParsedThread {
+    pub format: ThreadFormat,
+    pub messages: Vec<MessageRecord>,
+}
+
+impl ParsedThread {
+    /// Messages by author handle, each in conversation order
+    pub fn by_author(&self) -> BTreeMap<&str, Vec<&MessageRecord>> {
+        let mut authors: BTreeMap<&str, Vec<&MessageRecord>> = BTreeMap::new();
+        for message in &self.messages {
+            authors.entry(message.author.as_str()).or_default().push(message);
+        }
+        authors
+    }
+}
+
+const TIME: &str = r"\d{4}-\d{2}-\d{2}[ T]\d{1,2}:\d{2}(?::\d{2})?|\d{1,2}:\d{2}(?::\d{2})?";
+
+static IRC_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(&format!(r"^\[?({TIME})\]?\s+<[@+%~&]?([^>\s]+)>\s?(.*)$")).unwrap()
+});
+
+static BRACKETED_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^\[([^\]]*\d:\d\d[^\]]*)\]\s+([^\s:<>\[\]][^:<>\[\]]{0,39}?):\s+(.*)$").unwrap()
+});
+
+static WHATSAPP_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"^\[?(\d{1,2}/\d{1,2}/\d{2,4},?\s\d{1,2}:\d{2}(?::\d{2})?(?:\s?[AaPp][Mm])?)\]?\s(?:-\s)?([^:\[\]]{1,40}):\s(.*)$",
+    )
+    .unwrap()
+});
+
+static FORUM_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)^(?:posted by|post by|author:)\s*@?([^\s,»]+)(?:\s*(?:on|»|-|,)\s*(.+?))?\s*$").unwrap()
+});
+
+static QUOTE_HEADER_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)^(?:quote from:\s*@?([^\s,]+).*|@?([^\s,]+) wrote:|quote:)\s*$").unwrap()
+});
+
+/// Author, timestamp and first line of text of a line starting a message
+fn message_start(format: ThreadFormat, line: &str) -> Option<(String, String, String)> {
+    let (author, time, text) = match format {
+        ThreadFormat::Forum => {
+            let caps = FORUM_REGEX.captures(line)?;
+            (caps.get(1)?, caps.get(2).map_or("", |m| m.as_str()), "")
+        }
+        _ => {
+            let regex = match format {
+                ThreadFormat::Irc => &IRC_REGEX,
+                ThreadFormat::BracketedChat => &BRACKETED_REGEX,
+                _ => &WHATSAPP_REGEX,
+            };
+            let caps = regex.captures(line)?;
+            (caps.get(2)?, caps.get(1)?.as_str(), caps.get(3)?.as_str())
+        }
+    };
+    let author = author.as_str().trim().trim_start_matches('@');
+    if author.is_empty() {
+        return None;
+    }
+    Some((author.to_string(), time.to_string(), text.to_string()))
+}
+
+/// Timestamps in formats that say which number is the day
+fn parse_time(raw: &str) -> Option<NaiveDateTime> {
+    [
+        "%Y-%m-%d %H:%M:%S",
+        "%Y-%m-%d %H:%M",
+        "%Y-%m-%dT%H:%M:%S",
+        "%Y-%m-%dT%H:%M",
+        "%B %d, %Y, %I:%M:%S %p",
+        "%B %d, %Y, %I:%M %p",
+        "%b %d, %Y, %I:%M:%S %p",
+        "%b %d, %Y, %I:%M %p",
+    ]
+    .iter()
+    .find_map(|format| NaiveDateTime::parse_from_str(raw.trim(), format).ok())
+}
+
+/// Split `text` into messages if it's a chat log or forum thread
+///
+/// The layout most lines follow is used; text with fewer than
+/// [`MIN_THREAD_MESSAGES`] messages in every layout isn't a conversation.
+pub fn parse_thread(text: &str) -> Option<ParsedThread> {
+    let lines: Vec<&str> = text.lines().map(str::trim).collect();
+    let format = [ThreadFormat::Irc, ThreadFormat::WhatsApp, ThreadFormat::BracketedChat, ThreadFormat::Forum]
+        .into_iter()
+        .map(|format| (format, lines.iter().filter(|line| message_start(format, line).is_some()).count()))
+        .filter(|&(_, count)| count >= MIN_THREAD_MESSAGES)
+        .max_by_key(|&(_, count)| count)?
+        .0;
+
+    let mut messages: Vec<MessageRecord> = Vec::new();
+    let mut quoting = Quoting::No;
+    for line in lines {
+        let body = match message_start(format, line) {
+            Some((author, timestamp, first)) => {
+                quoting = Quoting::No;
+                messages.push(MessageRecord {
+                    author,
+                    sent_at: parse_time(&timestamp),
+                    timestamp: Some(timestamp).filter(|t| !t.is_empty()),
+                    text: String::new(),
+                    quotes: Vec::new(),
+                    index: messages.len(),
+                });
+                first
+            }
+            None => line.to_string(),
+        };
+        if let Some(message) = messages.last_mut() {
+            quoting = add_line(message, body.trim(), quoting);
+        }
+    }
+    Some(ParsedThread { format, messages })
+}
+
+/// Whether the lines being read are quoted
+#[derive(Clone, Copy, PartialEq)]
+enum Quoting {
+    No,
+    /// `>` lines, ending at the first line without one
+    Marked,
+    /// Lines under a quote header, ending at a blank line
+    Block,
+}
+
+/// Add a line of a message's body to its text or its last quote
+fn add_line(message: &mut MessageRecord, line: &str, quoting: Quoting) -> Quoting {
+    fn append(text: &mut String, line: &str) {
+        if !text.is_empty() {
+            text.push('\n');
+        }
+        text.push_str(line);
+    }
+
+    if let Some(caps) = QUOTE_HEADER_REGEX.captures(line) {
+        let author = caps.get(1).or_else(|| caps.get(2));
+        message.quotes.push(QuotedReply {
+            author: author
+                .map(|m| m.as_str().trim_start_matches('@').to_string())
+                .filter(|author| !author.is_empty()),
+            text: String::new(),
+        });
+        return Quoting::Block;
+    }
+    if let Some(quoted) = line.strip_prefix('>') {
+        if quoting == Quoting::No {
+            message.quotes.push(QuotedReply { author: None, text: String::new() });
+        }
+        if let Some(quote) = message.quotes.last_mut() {
+            append(&mut quote.text, quoted.trim_start());
+        }
+        return if quoting == Quoting::Block { Quoting::Block } else { Quoting::Marked };
+    }
+    if line.is_empty() {
+        return Quoting::No;
+    }
+    match (quoting, message.quotes.last_mut()) {
+        (Quoting::Block, Some(quote)) => {
+            append(&mut quote.text, line);
+            Quoting::Block
+        }
+        _ => {
+            append(&mut message.text, line);
+            Quoting::No
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parses_chat_logs() {
+        let irc = "\
+[2024-03-01 21:14:02] <@darkseller> fresh combo lists, 40k lines
+[2024-03-01 21:15:40] <buyer_77> price?
+> fresh combo lists
+how fresh
+[2024-03-01 21:16:01] <darkseller> 0.01 btc, escrow only";
+        let thread = parse_thread(irc).unwrap();
+        assert_eq!(thread.format, ThreadFormat::Irc);
+        assert_eq!(thread.messages.len(), 3);
+        let reply = &thread.messages[1];
+        assert_eq!(reply.author, "buyer_77");
+        assert_eq!(reply.text, "price?\nhow fresh");
+        assert_eq!(reply.quotes, vec![QuotedReply { author: None, text: "fresh combo lists".to_string() }]);
+        assert_eq!(reply.sent_at.unwrap().to_string(), "2024-03-01 21:15:40");
+        let authors = thread.by_author();
+        assert_eq!(authors["darkseller"].len(), 2);
+        assert_eq!(authors["darkseller"][1].index, 2);
+
+        let whatsapp = "12/03/2024, 09:01 - Vlad: new panel is up\n12/03/2024, 09:02 - Ivan M: link?";
+        let thread = parse_thread(whatsapp).unwrap();
+        assert_eq!(thread.format, ThreadFormat::WhatsApp);
+        assert_eq!(thread.messages[1].author, "Ivan M");
+        // Day and month order is ambiguous, so only the raw timestamp is kept
+        assert_eq!(thread.messages[1].timestamp.as_deref(), Some("12/03/2024, 09:02"));
+        assert_eq!(thread.messages[1].sent_at, None);
+
+        let discord = "[2024-05-02 10:00] ghost#0420: anyone selling rdp?\n[2024-05-02 10:03] kr0n: dm me";
+        assert_eq!(parse_thread(discord).unwrap().format, ThreadFormat::BracketedChat);
+
+        assert_eq!(parse_thread("Welcome to our shop.\nPrices at 12:00 daily."), None);
+        assert_eq!(parse_thread("[12:00] <solo> one line"), None);
+    }
+
+    #[test]
+    fn test_parses_forum_threads() {
+        let forum = "\
+Re: Selling access to EU bank
+Posted by vendor_x on March 02, 2024, 04:12:33 PM
+Access to 3 panels, ask for proof.
+Posted by skeptic on March 02, 2024, 05:00:01 PM
+vendor_x wrote:
+Access to 3 panels
+ask for proof.
+
+Proof or scam.";
+        let thread = parse_thread(forum).unwrap();
+        assert_eq!(thread.format, ThreadFormat::Forum);
+        assert_eq!(thread.messages.len(), 2);
+        assert_eq!(thread.messages[0].author, "vendor_x");
+        assert_eq!(thread.messages[0].text, "Access to 3 panels, ask for proof.");
+        assert_eq!(thread.messages[0].sent_at.unwrap().to_string(), "2024-03-02 16:12:33");
+        let reply = &thread.messages[1];
+        assert_eq!(reply.text, "Proof or scam.");
+        assert_eq!(reply.quotes[0].author.as_deref(), Some("vendor_x"));
+        assert_eq!(reply.quotes[0].text, "Access to 3 panels\nask for proof.");
+    }
+}
@@ -0,0 +1,315 @@
+//! Rate-of-discovery tracking for monitored targets
+//!
+//! When the same targets (brands, products, executives) are watched run
+//! after run, each run adds one [`Observation`] per target: pages mentioning
+//! it, wallets seen alongside it for the first time, and pages about it not
+//! seen before. A value far above the target's rolling baseline is an
+//! [`Anomaly`], the "chatter suddenly increased" alert.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use thiserror::Error;
+
+use crate::{read_maybe_sealed, write_sealed, AtRestError, AtRestKey};
+
+/// Observations kept per target (older ones are dropped)
+const MAX_OBSERVATIONS: usize = 365;
+
+/// Per-run tallies shared between the monitor agent and whoever records them
+pub type SharedTally = Arc<Mutex<RunTally>>;
+
+/// Errors loading or saving a trend store
+#[derive(Debug, Error)]
+pub enum TrendError {
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Invalid trend store: {0}")]
+    Parse(#[from] serde_json::Error),
+
+    #[error("Encryption error: {0}")]
+    Sealed(#[from] AtRestError),
+}
+
+/// A tracked quantity
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TrendMetric {
+    /// Pages and pastes mentioning the target
+    Mentions,
+    /// Wallets on those pages not seen in earlier runs
+    NewWallets,
+    /// Pages mentioning the target not seen in earlier runs
+    NewPages,
+}
+
+impl TrendMetric {
+    pub const ALL: [TrendMetric; 3] = [TrendMetric::Mentions, TrendMetric::NewWallets, TrendMetric::NewPages];
+}
+
+impl fmt::Display for TrendMetric {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TrendMetric::Mentions => write!(f, "mentions"),
+            TrendMetric::NewWallets => write!(f, "new wallets"),
+            TrendMetric::NewPages => write!(f, "new pages"),
+        }
+    }
+}
+
+/// One run's findings for a target
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Observation {
+    pub at: DateTime<Utc>,
+    pub mentions: u64,
+    pub new_wallets: u64,
+    pub new_pages: u64,
+}
+
+impl Observation {
+    pub fn get(&self, metric: TrendMetric) -> u64 {
+        match metric {
+            TrendMetric::Mentions => self.mentions,
+            TrendMetric::NewWallets => self.new_wallets,
+            TrendMetric::NewPages => self.new_pages,
+        }
+    }
+}
+
+/// When a value counts as a spike
+#[derive(Debug, Clone, Copy)]
+pub struct AnomalyPolicy {
+    /// Earlier observations forming the baseline
+    pub window: usize,
+    /// Fewer earlier observations than this never alert
+    pub min_history: usize,
+    /// Standard deviations above the baseline mean (the deviation is floored at 1)
+    pub threshold_sigmas: f64,
+    /// Smallest absolute increase over the mean that alerts
+    pub min_increase: f64,
+}
+
+impl Default for AnomalyPolicy {
+    fn default() -> Self {
+        Self {
+            window: 14,
+            min_history: 3,
+            threshold_sigmas: 3.0,
+            min_increase: 3.0,
+        }
+    }
+}
+
+/// A value far above its baseline
+#[derive(Debug, Clone, PartialEq)]
+pub struct Anomaly {
+    pub target: String,
+    pub metric: TrendMetric,
+    pub observed: u64,
+    /// Mean of the baseline window
+    pub baseline: f64,
+    /// Standard deviations above the baseline
+    pub sigmas: f64,
+}
+
+impl Anomaly {
+    /// e.g. `"Acme Corp": 14 mentions vs. baseline 2.3 (5.1σ)`
+    pub fn describe(&self) -> String {
+        format!(
+            "\"{}\": {} {} vs. baseline {:.1} ({:.1}σ)",
+            self.target, self.observed, self.metric, self.baseline, self.sigmas
+        )
+    }
+}
+
+/// What one run found about each monitored target
+#[derive(Debug, Clone, Default)]
+pub struct RunTally {
+    targets: BTreeMap<String, TargetTally>,
+}
+
+/// Pages and wallets tied to one target in a run
+#[derive(Debug, Clone, Default)]
+pub struct TargetTally {
+    pub pages: BTreeSet<String>,
+    pub wallets: BTreeSet<String>,
+}
+
+impl RunTally {
+    /// An empty tally for `targets`
+    pub fn new(targets: &[String]) -> Self {
+        Self {
+            targets: targets.iter().map(|t| (t.clone(), TargetTally::default())).collect(),
+        }
+    }
+
+    pub fn targets(&self) -> impl Iterator<Item = &str> {
+        self.targets.keys().map(String::as_str)
+    }
+
+    pub fn get(&self, target: &str) -> Option<&TargetTally> {
+        self.targets.get(target)
+    }
+
+    /// Count `url` for every target `text` mentions (case-insensitive); returns those targets
+    pub fn add_page(&mut self, url: &str, text: &str) -> Vec<String> {
+        let text = text.to_lowercase();
+        let mut matched = Vec::new();
+        for (target, tally) in &mut self.targets {
+            if text.contains(&target.to_lowercase()) {
+                tally.pages.insert(url.to_string());
+                matched.push(target.clone());
+            }
+        }
+        matched
+    }
+
+    /// Attribute a wallet found on `url` to the targets that page mentions
+    pub fn add_wallet(&mut self, url: &str, wallet: &str) {
+        for tally in self.targets.values_mut().filter(|t| t.pages.contains(url)) {
+            tally.wallets.insert(wallet.to_string());
+        }
+    }
+}
+
+/// Observation history of one target
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct TargetHistory {
+    observations: Vec<Observation>,
+    seen_pages: BTreeSet<String>,
+    seen_wallets: BTreeSet<String>,
+}
+
+/// Per-target finding history across runs
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TrendStore {
+    targets: BTreeMap<String, TargetHistory>,
+}
+
+impl TrendStore {
+    /// Load a store written by [`TrendStore::save`]; a missing file is an empty store
+    pub fn load(path: impl AsRef<Path>, key: Option<&AtRestKey>) -> Result<Self, TrendError> {
+        let path = path.as_ref();
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        Ok(serde_json::from_slice(&read_maybe_sealed(path, key)?)?)
+    }
+
+    /// Write the store as JSON, sealed when a key is given
+    pub fn save(&self, path: impl AsRef<Path>, key: Option<&AtRestKey>) -> Result<(), TrendError> {
+        let json = serde_json::to_vec_pretty(self)?;
+        match key {
+            Some(key) => write_sealed(path, key, &json)?,
+            None => std::fs::write(path, json)?,
+        }
+        Ok(())
+    }
+
+    /// Observations of `target`, oldest first
+    pub fn history(&self, target: &str) -> &[Observation] {
+        self.targets.get(target).map_or(&[], |h| &h.observations)
+    }
+
+    /// Add one run's tally and return the anomalies it shows
+    pub fn record(&mut self, tally: &RunTally, at: DateTime<Utc>, policy: &AnomalyPolicy) -> Vec<Anomaly> {
+        let mut anomalies = Vec::new();
+        for (target, found) in &tally.targets {
+            let history = self.targets.entry(target.clone()).or_default();
+            let observation = Observation {
+                at,
+                mentions: found.pages.len() as u64,
+                new_wallets: found.wallets.difference(&history.seen_wallets).count() as u64,
+                new_pages: found.pages.difference(&history.seen_pages).count() as u64,
+            };
+            let baseline = &history.observations[history.observations.len().saturating_sub(policy.window)..];
+            for metric in TrendMetric::ALL {
+                let values: Vec<f64> = baseline.iter().map(|o| o.get(metric) as f64).collect();
+                if let Some((mean, sigmas)) = spike(&values, observation.get(metric) as f64, policy) {
+                    anomalies.push(Anomaly {
+                        target: target.clone(),
+                        metric,
+                        observed: observation.get(metric),
+                        baseline: mean,
+                        sigmas,
+                    });
+                }
+            }
+
+            history.seen_pages.extend(found.pages.iter().cloned());
+            history.seen_wallets.extend(found.wallets.iter().cloned());
+            history.observations.push(observation);
+            let excess = history.observations.len().saturating_sub(MAX_OBSERVATIONS);
+            history.observations.drain(..excess);
+        }
+        anomalies
+    }
+}
+
+/// Mean and sigmas of `observed` against `baseline`, if it is a spike
+fn spike(baseline: &[f64], observed: f64, policy: &AnomalyPolicy) -> Option<(f64, f64)> {
+    if baseline.len() < policy.min_history.max(1) {
+        return None;
+    }
+    let mean = baseline.iter().sum::<f64>() / baseline.len() as f64;
+    let variance = baseline.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / baseline.len() as f64;
+    // Quiet targets (all zeros) would otherwise alert on a single mention
+    let sigmas = (observed - mean) / variance.sqrt().max(1.0);
+    (observed - mean >= policy.min_increase && sigmas >= policy.threshold_sigmas).then_some((mean, sigmas))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(store: &mut TrendStore, pages: &[(&str, &str)], wallets: &[(&str, &str)]) -> Vec<Anomaly> {
+        let mut tally = RunTally::new(&["Acme Corp".to_string()]);
+        for (url, text) in pages {
+            tally.add_page(url, text);
+        }
+        for (url, wallet) in wallets {
+            tally.add_wallet(url, wallet);
+        }
+        store.record(&tally, Utc::now(), &AnomalyPolicy::default())
+    }
+
+    #[test]
+    fn test_spike_over_baseline_alerts() {
+        let mut store = TrendStore::default();
+        // A steady trickle: the same one or two pages each run
+        for _ in 0..5 {
+            let quiet = run(&mut store, &[("http://a.onion/", "acme corp vpn for sale"), ("http://b.onion/", "nothing")], &[]);
+            assert!(quiet.is_empty());
+        }
+        let pages: Vec<(String, String)> =
+            (0..12).map(|i| (format!("http://leak{}.onion/", i), format!("ACME CORP dump part {}", i))).collect();
+        let pages: Vec<(&str, &str)> = pages.iter().map(|(u, t)| (u.as_str(), t.as_str())).collect();
+        let anomalies = run(&mut store, &pages, &[("http://leak1.onion/", "bc1qacmewallet")]);
+
+        let metrics: Vec<TrendMetric> = anomalies.iter().map(|a| a.metric).collect();
+        assert_eq!(metrics, vec![TrendMetric::Mentions, TrendMetric::NewPages]);
+        assert_eq!(anomalies[0].observed, 12);
+        assert!(anomalies[0].describe().starts_with("\"Acme Corp\": 12 mentions vs. baseline 1.0"));
+        assert_eq!(store.history("Acme Corp").len(), 6);
+        assert_eq!(store.history("Acme Corp")[5].new_wallets, 1);
+    }
+
+    #[test]
+    fn test_short_history_and_persistence() {
+        let mut store = TrendStore::default();
+        // No baseline yet: even a burst does not alert
+        let pages: Vec<(String, String)> = (0..20).map(|i| (format!("http://x{}.onion/", i), "acme corp".to_string())).collect();
+        let pages: Vec<(&str, &str)> = pages.iter().map(|(u, t)| (u.as_str(), t.as_str())).collect();
+        assert!(run(&mut store, &pages, &[]).is_empty());
+
+        let path = std::env::temp_dir().join(format!("robin-trends-{}.json", std::process::id()));
+        store.save(&path, None).unwrap();
+        let loaded = TrendStore::load(&path, None).unwrap();
+        assert_eq!(loaded.history("Acme Corp"), store.history("Acme Corp"));
+        std::fs::remove_file(&path).unwrap();
+    }
+}
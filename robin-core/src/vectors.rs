@@ -0,0 +1,268 @@
+//! Vector index over collected content
+//!
+//! Scraped pages and pastes are kept with an embedding so later
+//! investigations can search everything collected before by meaning,
+//! without refetching over Tor. The index is a flat file of normalized
+//! vectors searched by cosine similarity, which is plenty for a single
+//! analyst's archive (tens of thousands of documents).
+//!
+//! Embeddings from different models are not comparable, so a store records
+//! the model that produced it and refuses vectors from any other.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use thiserror::Error;
+
+use crate::{read_maybe_sealed, write_sealed, AtRestError, AtRestKey};
+
+/// Model name of [`hash_embedding`] vectors
+pub const HASHING_MODEL: &str = "hashing-512";
+
+/// Dimensions of [`hash_embedding`] vectors
+const HASHING_DIMS: usize = 512;
+
+/// Longest text kept per document, in characters
+pub const MAX_VECTOR_TEXT_CHARS: usize = 4000;
+
+/// A vector store shared between the analyst and whoever saves it
+pub type SharedVectors = Arc<Mutex<VectorStore>>;
+
+/// Errors from the vector store
+#[derive(Debug, Error)]
+pub enum VectorError {
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Invalid vector store: {0}")]
+    Parse(#[from] serde_json::Error),
+
+    #[error("Encryption error: {0}")]
+    Sealed(#[from] AtRestError),
+
+    #[error("store was embedded with {store}, not {model}")]
+    ModelMismatch { store: String, model: String },
+}
+
+/// Where a document was collected
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CollectionKind {
+    Scraped,
+    Paste,
+}
+
+/// A collected document and its embedding
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VectorEntry {
+    /// Hash of URL and text, so re-collected unchanged pages are stored once
+    pub id: String,
+    pub url: String,
+    pub kind: CollectionKind,
+    pub title: String,
+    pub text: String,
+    pub collected_at: DateTime<Utc>,
+    /// Unit-length embedding
+    pub embedding: Vec<f32>,
+}
+
+impl VectorEntry {
+    pub fn new(url: &str, kind: CollectionKind, title: &str, text: &str, embedding: Vec<f32>) -> Self {
+        let digest = Sha256::digest(format!("{}\n{}", url, text).as_bytes());
+        Self {
+            id: digest.iter().take(16).map(|b| format!("{:02x}", b)).collect(),
+            url: url.to_string(),
+            kind,
+            title: title.to_string(),
+            text: text.chars().take(MAX_VECTOR_TEXT_CHARS).collect(),
+            collected_at: Utc::now(),
+            embedding: normalized(embedding),
+        }
+    }
+}
+
+/// Embedded documents from past collections
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct VectorStore {
+    /// Embedding model of every entry (None until the first insert)
+    model: Option<String>,
+    entries: Vec<VectorEntry>,
+}
+
+impl VectorStore {
+    /// Load a store written by [`VectorStore::save`]; a missing file is an empty store
+    pub fn load(path: impl AsRef<Path>, key: Option<&AtRestKey>) -> Result<Self, VectorError> {
+        let path = path.as_ref();
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        Ok(serde_json::from_slice(&read_maybe_sealed(path, key)?)?)
+    }
+
+    /// Write the store as JSON, sealed when a key is given
+    pub fn save(&self, path: impl AsRef<Path>, key: Option<&AtRestKey>) -> Result<(), VectorError> {
+        let json = serde_json::to_vec(self)?;
+        match key {
+            Some(key) => write_sealed(path, key, &json)?,
+            None => std::fs::write(path, json)?,
+        }
+        Ok(())
+    }
+
+    pub fn model(&self) -> Option<&str> {
+        self.model.as_deref()
+    }
+
+    pub fn entries(&self) -> &[VectorEntry] {
+        &self.entries
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Drop documents collected before `cutoff`; returns how many
+    pub fn prune(&mut self, cutoff: DateTime<Utc>) -> usize {
+        let before = self.entries.len();
+        self.entries.retain(|e| e.collected_at >= cutoff);
+        before - self.entries.len()
+    }
+
+    /// Whether the store already holds this exact document
+    pub fn contains(&self, id: &str) -> bool {
+        self.entries.iter().any(|e| e.id == id)
+    }
+
+    /// Add a document embedded with `model`; returns false if it was already stored
+    pub fn insert(&mut self, model: &str, entry: VectorEntry) -> Result<bool, VectorError> {
+        self.check_model(model)?;
+        if self.contains(&entry.id) {
+            return Ok(false);
+        }
+        self.model = Some(model.to_string());
+        self.entries.push(entry);
+        Ok(true)
+    }
+
+    /// The `k` documents most similar to `query` (embedded with `model`), best first
+    pub fn search(&self, model: &str, query: &[f32], k: usize) -> Result<Vec<(f32, &VectorEntry)>, VectorError> {
+        self.check_model(model)?;
+        let query = normalized(query.to_vec());
+        let mut scored: Vec<(f32, &VectorEntry)> = self
+            .entries
+            .iter()
+            .filter(|e| e.embedding.len() == query.len())
+            .map(|e| (dot(&e.embedding, &query), e))
+            .collect();
+        scored.sort_by(|a, b| b.0.total_cmp(&a.0));
+        scored.truncate(k);
+        Ok(scored)
+    }
+
+    fn check_model(&self, model: &str) -> Result<(), VectorError> {
+        match &self.model {
+            Some(store) if store != model => Err(VectorError::ModelMismatch {
+                store: store.clone(),
+                model: model.to_string(),
+            }),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Offline embedding: hashed word unigrams and bigrams, unit length
+///
+/// Needs no model or API key but only captures shared vocabulary, not
+/// meaning; an embedding model finds paraphrases this misses.
+pub fn hash_embedding(text: &str) -> Vec<f32> {
+    let words: Vec<String> = text
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| w.chars().count() >= 2)
+        .map(str::to_lowercase)
+        .collect();
+    let mut vector = vec![0.0f32; HASHING_DIMS];
+    let mut add = |feature: &str, weight: f32| {
+        let digest = Sha256::digest(feature.as_bytes());
+        let bucket = u32::from_le_bytes([digest[0], digest[1], digest[2], digest[3]]) as usize % HASHING_DIMS;
+        // A second hash bit picks the sign, so collisions cancel out on average
+        let sign = if digest[4] & 1 == 0 { 1.0 } else { -1.0 };
+        vector[bucket] += sign * weight;
+    };
+    for word in &words {
+        add(word, 1.0);
+    }
+    for pair in words.windows(2) {
+        add(&format!("{} {}", pair[0], pair[1]), 0.5);
+    }
+    normalized(vector)
+}
+
+fn normalized(mut vector: Vec<f32>) -> Vec<f32> {
+    let norm = dot(&vector, &vector).sqrt();
+    if norm > 0.0 {
+        vector.iter_mut().for_each(|x| *x /= norm);
+    }
+    vector
+}
+
+fn dot(a: &[f32], b: &[f32]) -> f32 {
+    a.iter().zip(b).map(|(x, y)| x * y).sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(url: &str, text: &str) -> VectorEntry {
+        VectorEntry::new(url, CollectionKind::Scraped, "page", text, hash_embedding(text))
+    }
+
+    #[test]
+    fn test_search_ranks_similar_documents() {
+        let mut store = VectorStore::default();
+        let docs = [
+            ("http://a.onion/", "LockBit affiliates publish stolen data on the leak site"),
+            ("http://b.onion/", "Fresh carding dumps with CVV for sale"),
+            ("http://c.onion/", "Monero mixer with low fees"),
+        ];
+        for (url, text) in docs {
+            assert!(store.insert(HASHING_MODEL, entry(url, text)).unwrap());
+        }
+        // The same document is stored once
+        assert!(!store.insert(HASHING_MODEL, entry(docs[0].0, docs[0].1)).unwrap());
+
+        let hits = store.search(HASHING_MODEL, &hash_embedding("lockbit leak site data"), 2).unwrap();
+        assert_eq!(hits.len(), 2);
+        assert_eq!(hits[0].1.url, "http://a.onion/");
+        assert!(hits[0].0 > hits[1].0);
+
+        assert!(matches!(
+            store.search("text-embedding-3-small", &[1.0, 0.0], 1),
+            Err(VectorError::ModelMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn test_store_persists() {
+        let path = std::env::temp_dir().join(format!("robin-vectors-{}.json", std::process::id()));
+        let mut store = VectorStore::default();
+        store.insert(HASHING_MODEL, entry("http://a.onion/", "ransomware negotiation chat")).unwrap();
+        store.save(&path, None).unwrap();
+
+        let loaded = VectorStore::load(&path, None).unwrap();
+        assert_eq!(loaded.model(), Some(HASHING_MODEL));
+        assert_eq!(loaded.entries(), store.entries());
+        std::fs::remove_file(&path).unwrap();
+
+        let mut store = loaded;
+        assert_eq!(store.prune(Utc::now() - chrono::Duration::days(1)), 0);
+        assert_eq!(store.prune(Utc::now() + chrono::Duration::seconds(1)), 1);
+        assert!(store.is_empty());
+    }
+}
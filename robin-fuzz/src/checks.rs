@@ -0,0 +1,320 @@
+//! Invariant checks over parser entry points
+//!
+//! Every check takes arbitrary input and panics if an invariant is broken.
+
+use std::collections::HashSet;
+
+use robin_core::{extract_artifacts, parse_thread, OsintPayload, Signal, SearchEngine, MIN_THREAD_MESSAGES};
+use robin_tor::{extract_content, parse_search_results, truncate_text};
+
+/// Source label attached to fuzzed artifacts
+const FUZZ_SOURCE: &str = "fuzz://input";
+
+/// Extracted artifacts are unique substrings of the input with bounded confidence
+pub fn check_extract_artifacts(text: &str) {
+    let artifacts = extract_artifacts(text, Some(FUZZ_SOURCE));
+    let mut seen = HashSet::new();
+
+    for artifact in &artifacts {
+        assert!(
+            text.contains(&artifact.value),
+            "artifact {:?} is not a substring of the input",
+            artifact.value
+        );
+        assert!((0.0..=1.0).contains(&artifact.confidence));
+        assert_eq!(artifact.source.as_deref(), Some(FUZZ_SOURCE));
+        assert!(
+            seen.insert((format!("{:?}", artifact.artifact_type), artifact.value.to_lowercase())),
+            "duplicate artifact {:?}",
+            artifact.value
+        );
+    }
+
+    let json = serde_json::to_string(&artifacts).expect("artifacts serialize");
+    let back: Vec<robin_core::Artifact> = serde_json::from_str(&json).expect("artifacts deserialize");
+    assert_eq!(back.len(), artifacts.len());
+}
+
+/// Parsed conversations have enough messages, in order, each with an author from the input
+pub fn check_parse_thread(text: &str) {
+    let Some(thread) = parse_thread(text) else { return };
+    assert!(thread.messages.len() >= MIN_THREAD_MESSAGES);
+    for (i, message) in thread.messages.iter().enumerate() {
+        assert_eq!(message.index, i);
+        assert!(!message.author.is_empty());
+        assert!(text.contains(&message.author), "author {:?} is not in the input", message.author);
+    }
+    let authored: usize = thread.by_author().values().map(Vec::len).sum();
+    assert_eq!(authored, thread.messages.len());
+}
+
+/// Scraped text is whitespace-normalized, and page metadata round-trips through JSON
+pub fn check_extract_content(html: &str) {
+    let (_title, text, metadata) = extract_content(html);
+
+    assert_eq!(text, text.split_whitespace().collect::<Vec<_>>().join(" "));
+    let json = serde_json::to_string(&metadata).expect("metadata serializes");
+    assert_eq!(serde_json::from_str::<robin_core::PageMetadata>(&json).expect("metadata deserializes"), metadata);
+}
+
+/// Search results are onion links with a usable title and no engine self-links
+pub fn check_parse_search_results(html: &str) {
+    for result in parse_search_results(html, "Fuzz") {
+        assert!(result.url.contains(".onion"), "non-onion url {:?}", result.url);
+        assert!(!result.url.contains("search") && !result.url.contains("query"));
+        assert!(result.title.len() >= 3);
+        assert_eq!(result.engine, "Fuzz");
+    }
+}
+
+/// Truncation stays on a char boundary and within the limit
+pub fn check_truncate_text(text: &str, max_len: usize) {
+    let (truncated, was_truncated) = truncate_text(text, max_len);
+
+    assert_eq!(was_truncated, text.len() > max_len);
+    if was_truncated {
+        let kept = truncated.trim_end_matches("...(truncated)");
+        assert!(kept.len() <= max_len);
+        assert!(text.starts_with(kept));
+    } else {
+        assert_eq!(truncated, text);
+    }
+}
+
+/// Engine URLs stay ASCII and the encoded query decodes back to the input
+pub fn check_build_url(engine: &SearchEngine, query: &str) {
+    let url = engine.build_url(query);
+    assert!(url.is_ascii(), "non-ASCII url {:?}", url);
+    assert!(!url.contains(char::is_whitespace));
+
+    let (prefix, suffix) = engine
+        .url_template
+        .split_once("{query}")
+        .expect("template has a {query} placeholder");
+    let encoded = &url[prefix.len()..url.len() - suffix.len()];
+    assert_eq!(form_decode(encoded).as_deref(), Some(query), "query did not round-trip");
+}
+
+/// Decode `application/x-www-form-urlencoded` text
+fn form_decode(encoded: &str) -> Option<String> {
+    let bytes = encoded.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+
+    while i < bytes.len() {
+        match bytes[i] {
+            b'+' => decoded.push(b' '),
+            b'%' => {
+                let hex = encoded.get(i + 1..i + 3)?;
+                decoded.push(u8::from_str_radix(hex, 16).ok()?);
+                i += 2;
+            }
+            b => decoded.push(b),
+        }
+        i += 1;
+    }
+
+    String::from_utf8(decoded).ok()
+}
+
+/// A signal survives a JSON round-trip unchanged
+pub fn check_signal_roundtrip(signal: &Signal) {
+    let json = serde_json::to_string(signal).expect("signal serializes");
+    let back: Signal = serde_json::from_str(&json).expect("signal deserializes");
+
+    assert_eq!(back.id, signal.id);
+    assert_eq!(back.origin_hash, signal.origin_hash);
+    assert_eq!(back.origin_agent_id, signal.origin_agent_id);
+    assert_eq!(back.created_at, signal.created_at);
+    assert!((back.intensity - signal.intensity).abs() < 1e-12);
+    assert!((back.confidence - signal.confidence).abs() < 1e-12);
+    assert!(
+        json_approx_eq(
+            &serde_json::to_value(&back.payload).unwrap(),
+            &serde_json::to_value(&signal.payload).unwrap()
+        ),
+        "payload changed across round-trip"
+    );
+}
+
+/// Structural JSON equality, allowing the last-bit drift of serde_json's
+/// default (non-`float_roundtrip`) float parser
+fn json_approx_eq(a: &serde_json::Value, b: &serde_json::Value) -> bool {
+    use serde_json::Value;
+
+    match (a, b) {
+        (Value::Number(x), Value::Number(y)) => match (x.as_f64(), y.as_f64()) {
+            (Some(x), Some(y)) => (x - y).abs() <= 1e-12 * x.abs().max(1.0),
+            _ => x == y,
+        },
+        (Value::Array(x), Value::Array(y)) => {
+            x.len() == y.len() && x.iter().zip(y).all(|(x, y)| json_approx_eq(x, y))
+        }
+        (Value::Object(x), Value::Object(y)) => {
+            x.len() == y.len() && x.iter().all(|(k, v)| y.get(k).is_some_and(|w| json_approx_eq(v, w)))
+        }
+        _ => a == b,
+    }
+}
+
+/// Arbitrary bytes never panic the signal/payload decoders
+pub fn check_signal_json(data: &[u8]) {
+    if let Ok(signal) = serde_json::from_slice::<Signal>(data) {
+        // Anything we accept, we must be able to write back out
+        serde_json::to_string(&signal).expect("accepted signal serializes");
+    }
+    let _ = serde_json::from_slice::<OsintPayload>(data);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+    use robin_core::{Network, SearchEngineRegistry};
+    use std::sync::LazyLock;
+
+    static ENGINES: LazyLock<SearchEngineRegistry> = LazyLock::new(SearchEngineRegistry::embedded);
+
+    /// Text biased towards artifact-shaped tokens mixed with noise
+    fn messy_text() -> impl Strategy<Value = String> {
+        let token = prop_oneof![
+            any::<String>(),
+            "[a-z2-7]{56}\\.onion",
+            "[a-fA-F0-9]{32,64}",
+            "(bc1|1|3)[a-zA-HJ-NP-Z0-9]{25,39}",
+            "0x[a-fA-F0-9]{40}",
+            "CVE-[0-9]{4}-[0-9]{4,6}",
+            "[a-z.]{1,20}@[a-z]{1,10}\\.[a-z]{2,4}",
+            "https?://[^ ]{0,40}",
+            "[0-9]{1,3}(\\.[0-9]{1,3}){3}",
+            "[ \\n\\t\u{a0}\u{200b}]{0,4}",
+            "[а-яё]{1,10}|[一-龥]{1,6}|[😀-🙏]{1,3}",
+        ];
+        prop::collection::vec(token, 0..24).prop_map(|tokens| tokens.join(" "))
+    }
+
+    /// Lines shaped like chat log and forum messages, mixed with noise
+    fn chat_text() -> impl Strategy<Value = String> {
+        let line = prop_oneof![
+            any::<String>(),
+            "\\[[0-9]{1,2}:[0-9]{2}\\] <[@+]?[a-z_0-9]{0,12}> .{0,30}",
+            "\\[2024-0[1-9]-[0-2][0-9] [0-9]{2}:[0-9]{2}\\] [a-z#0-9 ]{0,20}: .{0,30}",
+            "[0-9]{1,2}/[0-9]{1,2}/[0-9]{2}, [0-9]{1,2}:[0-9]{2} - [A-Za-z ]{0,20}: .{0,30}",
+            "(Posted by|Author:) @?[a-z_]{0,12}( on .{0,25})?",
+            "(> ?|Quote from: [a-z]{0,8}|[a-z]{0,8} wrote:).{0,20}",
+            "",
+        ];
+        prop::collection::vec(line, 0..16).prop_map(|lines| lines.join("\n"))
+    }
+
+    /// HTML fragments with unbalanced tags, entities and odd hrefs
+    fn messy_html() -> impl Strategy<Value = String> {
+        let fragment = prop_oneof![
+            any::<String>(),
+            Just("<html><head><title>".to_string()),
+            Just("</title></head><body>".to_string()),
+            Just("<script>var x = '</a>';</script>".to_string()),
+            Just("<style>.x{}</style><noscript>js</noscript>".to_string()),
+            "<a href=\"https?://[a-z2-7]{16,56}\\.onion[/a-z?=]{0,12}\">[^<]{0,12}</a>",
+            "<a href='[^']{0,30}'>[^<]{0,8}",
+            "<(p|div|span|td)>",
+            "</(p|div|span|td|body|html)>",
+            "&(amp|lt|gt|nbsp|#x[0-9a-f]{1,6});",
+        ];
+        prop::collection::vec(fragment, 0..32).prop_map(|parts| parts.concat())
+    }
+
+    fn payload() -> impl Strategy<Value = OsintPayload> {
+        prop_oneof![
+            (any::<String>(), 0.0..=1.0f64)
+                .prop_map(|(query, priority)| OsintPayload::UserQuery { query, priority }),
+            (any::<String>(), any::<String>(), any::<String>()).prop_map(|(url, title, engine)| {
+                OsintPayload::RawResult { network: Network::classify(&url), url, title, engine, query: None }
+            }),
+            (any::<String>(), any::<Option<String>>(), messy_text()).prop_map(|(url, title, text)| {
+                OsintPayload::ScrapedContent {
+                    url,
+                    title: title.unwrap_or_default(),
+                    char_count: text.len(),
+                    text,
+                    screenshot: None,
+                    language: None,
+                    original_text: None,
+                    chunk: None,
+                    metadata: None,
+                }
+            }),
+            messy_text().prop_map(|text| OsintPayload::ExtractedArtifacts {
+                source_url: "http://example.onion/".to_string(),
+                artifacts: extract_artifacts(&text, None),
+            }),
+        ]
+    }
+
+    proptest! {
+        #[test]
+        fn prop_extract_artifacts(text in messy_text()) {
+            check_extract_artifacts(&text);
+        }
+
+        #[test]
+        fn prop_parse_thread(text in chat_text()) {
+            check_parse_thread(&text);
+        }
+
+        #[test]
+        fn prop_html_parsing(html in messy_html()) {
+            check_extract_content(&html);
+            check_parse_search_results(&html);
+        }
+
+        #[test]
+        fn prop_scripts_excluded(
+            before in "[^<&]{0,40}",
+            script in "[^<]{1,40}",
+            after in "[^<&]{0,40}",
+        ) {
+            let html = format!(
+                "<html><body><p>{}</p><script>ROBIN_SCRIPT {}</script><p>{}</p></body></html>",
+                before, script, after
+            );
+            let (_, text, _) = extract_content(&html);
+            prop_assert!(!text.contains("ROBIN_SCRIPT"));
+        }
+
+        #[test]
+        fn prop_truncate_text(text in messy_text(), max_len in 0usize..256) {
+            check_truncate_text(&text, max_len);
+        }
+
+        #[test]
+        fn prop_build_url(query in any::<String>(), idx in 0..ENGINES.len()) {
+            check_build_url(&ENGINES.engines()[idx], &query);
+        }
+
+        #[test]
+        fn prop_signal_roundtrip(
+            payload in payload(),
+            confidence in 0.0..=1.0f64,
+            ttl in 0.0..3600.0f64,
+            agent in "[a-z]{1,12}-[0-9]{1,4}",
+        ) {
+            let signal = Signal::builder(payload)
+                .origin(&agent)
+                .confidence(confidence)
+                .ttl(ttl)
+                .build();
+            check_signal_roundtrip(&signal);
+        }
+
+        #[test]
+        fn prop_signal_json_bytes(data in prop::collection::vec(any::<u8>(), 0..512)) {
+            check_signal_json(&data);
+        }
+    }
+
+    #[test]
+    fn test_non_ascii_query_url() {
+        check_build_url(&ENGINES.engines()[0], "вымогатель 勒索软件 💰");
+    }
+}
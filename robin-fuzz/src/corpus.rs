@@ -0,0 +1,89 @@
+//! Seed corpus of real-world messy content
+//!
+//! Seeds live under `corpus/<target>/`, one directory per fuzz target, and
+//! double as the starting corpus for `cargo fuzz run <target> ../corpus/<target>`.
+
+use std::path::{Path, PathBuf};
+
+use crate::checks;
+
+/// Fuzz targets, matching the corpus directory names and `fuzz/fuzz_targets/*.rs`
+pub const TARGETS: &[&str] = &["extract_artifacts", "parse_html", "signal_json"];
+
+/// A single corpus seed
+#[derive(Debug, Clone)]
+pub struct Seed {
+    /// Path of the seed file
+    pub path: PathBuf,
+    /// Raw seed bytes
+    pub data: Vec<u8>,
+}
+
+/// Root of the checked-in seed corpus
+pub fn corpus_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("corpus")
+}
+
+/// Load all seeds for a target, sorted by path
+pub fn seeds(target: &str) -> std::io::Result<Vec<Seed>> {
+    let mut paths: Vec<PathBuf> = std::fs::read_dir(corpus_dir().join(target))?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.is_file())
+        .collect();
+    paths.sort();
+
+    paths
+        .into_iter()
+        .map(|path| Ok(Seed { data: std::fs::read(&path)?, path }))
+        .collect()
+}
+
+/// Run every check belonging to `target` against one input
+///
+/// Panics on an invariant violation or an unknown target.
+pub fn check_target(target: &str, data: &[u8]) {
+    match target {
+        "extract_artifacts" => {
+            let text = String::from_utf8_lossy(data);
+            checks::check_extract_artifacts(&text);
+            checks::check_truncate_text(&text, data.len() / 2);
+        }
+        "parse_html" => {
+            let html = String::from_utf8_lossy(data);
+            checks::check_extract_content(&html);
+            checks::check_parse_search_results(&html);
+        }
+        "signal_json" => checks::check_signal_json(data),
+        other => panic!("unknown fuzz target {:?}", other),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_every_target_has_seeds() {
+        for target in TARGETS {
+            assert!(!seeds(target).unwrap().is_empty(), "no seeds for {}", target);
+        }
+    }
+
+    #[test]
+    fn test_seed_corpus_passes_checks() {
+        for target in TARGETS {
+            for seed in seeds(target).unwrap() {
+                if let Err(e) = std::panic::catch_unwind(|| check_target(target, &seed.data)) {
+                    panic!("{} failed on {}: {:?}", target, seed.path.display(), e);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_valid_signal_seed_decodes() {
+        let seed = std::fs::read(corpus_dir().join("signal_json/scraped_content.json")).unwrap();
+        let signal: robin_core::Signal = serde_json::from_slice(&seed).unwrap();
+        checks::check_signal_roundtrip(&signal);
+    }
+}
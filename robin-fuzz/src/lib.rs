@@ -0,0 +1,22 @@
+//! Robin×SMESH Fuzz Harness
+//!
+//! Property-based and fuzz testing for the code that touches untrusted
+//! dark web content:
+//! - Artifact extraction (`robin_core::extract_artifacts`)
+//! - HTML parsing (search result pages and scraped content)
+//! - Signal JSON (de)serialization
+//!
+//! Invariants live in [`checks`] and panic on violation, so the same checks
+//! back the proptest suite, the seed corpus replay, and the libFuzzer
+//! targets under `fuzz/`:
+//!
+//! ```text
+//! cd robin-fuzz/fuzz
+//! cargo +nightly fuzz run extract_artifacts ../corpus/extract_artifacts
+//! ```
+
+pub mod checks;
+pub mod corpus;
+
+pub use checks::*;
+pub use corpus::*;
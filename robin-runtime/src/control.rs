@@ -0,0 +1,319 @@
+//! Local control channel for a running swarm
+//!
+//! Local GUIs and scripts can submit queries, check status and pause or
+//! resume a run over a Unix domain socket, without opening a network port.
+//! The socket is created mode `0600`, and connections from any user other
+//! than the socket's owner are dropped. Windows named pipes aren't
+//! supported: there the control channel fails with
+//! [`std::io::ErrorKind::Unsupported`].
+//!
+//! The protocol is one JSON [`ControlRequest`] per line, each answered by
+//! one JSON [`ControlResponse`] line. Requests are handled by the swarm
+//! between ticks.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+use tokio::sync::{mpsc, oneshot};
+
+use robin_core::FieldStats;
+use robin_tor::BandwidthSnapshot;
+
+use crate::pipeline::Pipeline;
+
+/// A command for the swarm
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "command", rename_all = "snake_case")]
+pub enum ControlRequest {
+    /// Emit an additional user query
+    Submit {
+        query: String,
+        #[serde(default = "default_priority")]
+        priority: f64,
+        /// Run the query as its own investigation, rather than part of the current one
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        investigation: Option<String>,
+    },
+    /// Report run state and field statistics
+    Status,
+    /// Stop ticking the field and running agents
+    Pause,
+    /// Continue after a pause
+    Resume,
+}
+
+fn default_priority() -> f64 {
+    1.0
+}
+
+/// The swarm's answer to a [`ControlRequest`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ControlResponse {
+    /// Whether the request was carried out
+    pub ok: bool,
+    /// Human-readable result
+    pub message: String,
+    /// Whether the swarm is paused after the request
+    pub paused: bool,
+    /// Seconds since the run started
+    pub elapsed_secs: u64,
+    /// Field statistics (status requests only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stats: Option<FieldStats>,
+    /// Signals emitted per agent over recent ticks, oldest first (status requests only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub activity: Option<BTreeMap<String, Vec<u32>>>,
+    /// Agents' declared inputs and outputs (status requests only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pipeline: Option<Pipeline>,
+    /// Bytes transferred over Tor by host and agent (status requests only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bandwidth: Option<BandwidthSnapshot>,
+}
+
+pub(crate) type ControlMessage = (ControlRequest, oneshot::Sender<ControlResponse>);
+
+/// Sends requests to a running swarm; see [`crate::Swarm::control_handle`]
+#[derive(Debug, Clone)]
+pub struct ControlHandle {
+    pub(crate) tx: mpsc::Sender<ControlMessage>,
+}
+
+impl ControlHandle {
+    /// Send a request and wait for the swarm to handle it
+    pub async fn request(&self, request: ControlRequest) -> Option<ControlResponse> {
+        let (reply, response) = oneshot::channel();
+        self.tx.send((request, reply)).await.ok()?;
+        response.await.ok()
+    }
+}
+
+/// Serve `handle` on a Unix socket at `path` until the swarm stops
+///
+/// A stale socket left by an earlier run (one that refuses connections) is
+/// replaced. The socket of a run still listening fails with
+/// [`std::io::ErrorKind::AddrInUse`], and any other file at `path` is left
+/// alone and fails with [`std::io::ErrorKind::AlreadyExists`].
+#[cfg(unix)]
+pub async fn serve_control_socket(path: &Path, handle: ControlHandle) -> std::io::Result<()> {
+    use std::os::unix::fs::{FileTypeExt, MetadataExt, PermissionsExt};
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+    use tokio::net::UnixListener;
+    use tracing::{debug, info, warn};
+
+    match std::fs::symlink_metadata(path) {
+        Ok(meta) if meta.file_type().is_socket() => match tokio::net::UnixStream::connect(path).await {
+            Ok(_) => {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::AddrInUse,
+                    format!("{} is the control socket of a running swarm", path.display()),
+                ))
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::ConnectionRefused => std::fs::remove_file(path)?,
+            Err(e) => return Err(e),
+        },
+        Ok(_) => {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::AlreadyExists,
+                format!("{} exists and is not a socket", path.display()),
+            ))
+        }
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+        Err(e) => return Err(e),
+    }
+    let listener = UnixListener::bind(path)?;
+    std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))?;
+    let owner = std::fs::metadata(path)?.uid();
+    info!("Control socket listening on {}", path.display());
+
+    loop {
+        let (stream, _) = listener.accept().await?;
+        // Covers the window between bind and chmod
+        match stream.peer_cred() {
+            Ok(cred) if cred.uid() == owner => {}
+            _ => {
+                warn!("Rejected control connection from another user");
+                continue;
+            }
+        }
+        let handle = handle.clone();
+        tokio::spawn(async move {
+            let (read, mut write) = stream.into_split();
+            let mut lines = BufReader::new(read).lines();
+            while let Ok(Some(line)) = lines.next_line().await {
+                let response = match serde_json::from_str::<ControlRequest>(&line) {
+                    Ok(request) => match handle.request(request).await {
+                        Some(response) => response,
+                        None => return, // swarm finished
+                    },
+                    Err(e) => ControlResponse {
+                        ok: false,
+                        message: format!("invalid request: {}", e),
+                        paused: false,
+                        elapsed_secs: 0,
+                        stats: None,
+                        activity: None,
+                        pipeline: None,
+                        bandwidth: None,
+                    },
+                };
+                let Ok(mut json) = serde_json::to_string(&response) else {
+                    return;
+                };
+                json.push('\n');
+                if write.write_all(json.as_bytes()).await.is_err() {
+                    return;
+                }
+            }
+            debug!("Control client disconnected");
+        });
+    }
+}
+
+/// Unix sockets are required; Windows named pipes are not supported yet
+#[cfg(not(unix))]
+pub async fn serve_control_socket(_path: &Path, _handle: ControlHandle) -> std::io::Result<()> {
+    Err(std::io::Error::new(
+        std::io::ErrorKind::Unsupported,
+        "the control socket requires a Unix platform",
+    ))
+}
+
+/// Send one request to the control socket at `path`
+#[cfg(unix)]
+pub async fn send_control_request(path: &Path, request: &ControlRequest) -> std::io::Result<ControlResponse> {
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+    let stream = tokio::net::UnixStream::connect(path).await?;
+    let (read, mut write) = stream.into_split();
+    let mut json = serde_json::to_string(request)?;
+    json.push('\n');
+    write.write_all(json.as_bytes()).await?;
+
+    let line = BufReader::new(read).lines().next_line().await?.ok_or_else(|| {
+        std::io::Error::new(std::io::ErrorKind::UnexpectedEof, "swarm closed the connection")
+    })?;
+    Ok(serde_json::from_str(&line)?)
+}
+
+/// Unix sockets are required; Windows named pipes are not supported yet
+#[cfg(not(unix))]
+pub async fn send_control_request(_path: &Path, _request: &ControlRequest) -> std::io::Result<ControlResponse> {
+    Err(std::io::Error::new(
+        std::io::ErrorKind::Unsupported,
+        "the control socket requires a Unix platform",
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_request_wire_format() {
+        let submit: ControlRequest = serde_json::from_str(r#"{"command":"submit","query":"lockbit"}"#).unwrap();
+        assert_eq!(
+            submit,
+            ControlRequest::Submit {
+                query: "lockbit".to_string(),
+                priority: 1.0,
+                investigation: None,
+            }
+        );
+        assert_eq!(serde_json::to_string(&ControlRequest::Pause).unwrap(), r#"{"command":"pause"}"#);
+        assert!(serde_json::from_str::<ControlRequest>(r#"{"command":"shutdown"}"#).is_err());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_socket_round_trip() {
+        let path = std::env::temp_dir().join(format!("robin-control-{}.sock", std::process::id()));
+        let (tx, mut rx) = mpsc::channel::<ControlMessage>(4);
+        tokio::spawn(async move {
+            while let Some((request, reply)) = rx.recv().await {
+                let paused = request == ControlRequest::Pause;
+                let _ = reply.send(ControlResponse {
+                    ok: true,
+                    message: format!("{:?}", request),
+                    paused,
+                    elapsed_secs: 0,
+                    stats: None,
+                    activity: None,
+                    pipeline: None,
+                    bandwidth: None,
+                });
+            }
+        });
+        let server_path = path.clone();
+        tokio::spawn(async move { serve_control_socket(&server_path, ControlHandle { tx }).await });
+
+        // Wait for the listener
+        for _ in 0..50 {
+            if path.exists() {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        let response = send_control_request(&path, &ControlRequest::Pause).await.unwrap();
+        assert!(response.ok && response.paused);
+
+        // A second run can't take over the live socket
+        let (other, _rx) = mpsc::channel::<ControlMessage>(1);
+        let err = serve_control_socket(&path, ControlHandle { tx: other }).await.unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::AddrInUse);
+        assert!(send_control_request(&path, &ControlRequest::Status).await.unwrap().ok);
+
+        use std::os::unix::fs::PermissionsExt;
+        assert_eq!(std::fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o600);
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_socket_never_replaces_other_files() {
+        let path = std::env::temp_dir().join(format!("robin-control-{}.txt", std::process::id()));
+        std::fs::write(&path, "keep me").unwrap();
+        let (tx, _rx) = mpsc::channel::<ControlMessage>(1);
+
+        let err = serve_control_socket(&path, ControlHandle { tx }).await.unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::AlreadyExists);
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "keep me");
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_stale_socket_is_replaced() {
+        let path = std::env::temp_dir().join(format!("robin-control-stale-{}.sock", std::process::id()));
+        std::fs::remove_file(&path).ok();
+        // Bound and dropped: the file stays but nothing listens
+        drop(std::os::unix::net::UnixListener::bind(&path).unwrap());
+        let (tx, mut rx) = mpsc::channel::<ControlMessage>(1);
+        let server_path = path.clone();
+        tokio::spawn(async move { serve_control_socket(&server_path, ControlHandle { tx }).await });
+
+        let client_path = path.clone();
+        let client = tokio::spawn(async move {
+            for _ in 0..50 {
+                if let Ok(response) = send_control_request(&client_path, &ControlRequest::Status).await {
+                    return response;
+                }
+                tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+            }
+            panic!("control socket never came up");
+        });
+        let (_, reply) = rx.recv().await.unwrap();
+        let _ = reply.send(ControlResponse {
+            ok: true,
+            message: String::new(),
+            paused: false,
+            elapsed_secs: 0,
+            stats: None,
+            activity: None,
+            pipeline: None,
+            bandwidth: None,
+        });
+        assert!(client.await.unwrap().ok);
+        std::fs::remove_file(&path).ok();
+    }
+}
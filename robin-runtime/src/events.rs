@@ -0,0 +1,262 @@
+//! Run event log
+//!
+//! While a swarm runs, newly emitted artifacts, insights and the final
+//! summary are appended to an NDJSON event log, one [`RunEvent`] per line.
+//! [`EventReader`] reads the log incrementally, so `robin-smesh tail` can
+//! follow a run that is still in progress or replay a finished one.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs::{File, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+use robin_core::{Field, OsintPayload, PayloadKind};
+
+/// Payload kinds written to the event log
+pub const EVENT_KINDS: &[PayloadKind] = &[
+    PayloadKind::ExtractedArtifacts,
+    PayloadKind::EnrichedArtifacts,
+    PayloadKind::BlockchainAnalysis,
+    PayloadKind::Insight,
+    PayloadKind::Summary,
+];
+
+/// One discovery during a run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunEvent {
+    /// When the event was logged
+    pub timestamp: DateTime<Utc>,
+    /// Origin hash of the signal
+    pub hash: String,
+    /// Emitting agent
+    pub origin: String,
+    /// Sender's confidence
+    pub confidence: f64,
+    /// Tags of the signal (engagement, query, rule tags)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// Signal payload
+    pub payload: OsintPayload,
+}
+
+impl RunEvent {
+    /// Payload kind of the event
+    pub fn kind(&self) -> PayloadKind {
+        self.payload.kind()
+    }
+
+    /// Whether this event ends the run (the summary)
+    pub fn is_final(&self) -> bool {
+        self.kind() == PayloadKind::Summary
+    }
+
+    /// One-line description for tabular output
+    pub fn describe(&self) -> String {
+        match &self.payload {
+            OsintPayload::ExtractedArtifacts { source_url, artifacts } => {
+                let sample: Vec<String> = artifacts
+                    .iter()
+                    .take(3)
+                    .map(|a| format!("{:?}={}", a.artifact_type, a.value))
+                    .collect();
+                let more = if artifacts.len() > 3 { ", ..." } else { "" };
+                format!("{} from {}: {}{}", artifacts.len(), source_url, sample.join(", "), more)
+            }
+            OsintPayload::EnrichedArtifacts { artifact, source, findings } => {
+                format!("{} via {}: {} finding(s)", artifact.value, source, findings.len())
+            }
+            OsintPayload::BlockchainAnalysis { address, chain, analysis } => {
+                format!("{} {}: {} tx, balance {}", chain, address, analysis.tx_count, analysis.balance)
+            }
+            OsintPayload::Insight { category, content, .. } => {
+                let content: String = content.chars().take(100).collect();
+                format!("{:?}: {}", category, content.replace('\n', " "))
+            }
+            OsintPayload::Summary { artifact_count, source_count, .. } => {
+                format!("report ready: {} artifacts from {} sources", artifact_count, source_count)
+            }
+            other => other.kind().to_string(),
+        }
+    }
+}
+
+/// Appends new discoveries from the field to an event log file
+#[derive(Debug)]
+pub struct EventLog {
+    path: PathBuf,
+    file: File,
+    logged: HashSet<String>,
+}
+
+impl EventLog {
+    /// Create (or truncate) the log at `path`
+    pub fn create<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let file = File::create(&path)?;
+        Ok(Self {
+            path,
+            file,
+            logged: HashSet::new(),
+        })
+    }
+
+    /// Log file path
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Append every event-kind signal not logged yet; returns how many were written
+    pub fn record(&mut self, field: &Field) -> io::Result<usize> {
+        let now = Utc::now();
+        let mut lines = String::new();
+        let mut written = 0;
+
+        let mut signals = field.sense_kinds(EVENT_KINDS, 0.0);
+        signals.sort_by_key(|s| s.created_at);
+        for signal in signals {
+            if !self.logged.insert(signal.origin_hash.clone()) {
+                continue;
+            }
+            let event = RunEvent {
+                timestamp: now,
+                hash: signal.origin_hash.clone(),
+                origin: signal.origin_agent_id.clone(),
+                confidence: signal.confidence,
+                tags: signal.tags.clone(),
+                payload: signal.payload.clone(),
+            };
+            lines.push_str(&serde_json::to_string(&event)?);
+            lines.push('\n');
+            written += 1;
+        }
+
+        if written > 0 {
+            self.file.write_all(lines.as_bytes())?;
+            self.file.flush()?;
+        }
+        Ok(written)
+    }
+}
+
+/// Incremental reader for an event log that may still be growing
+#[derive(Debug)]
+pub struct EventReader {
+    path: PathBuf,
+    offset: u64,
+    /// Bytes of an unfinished trailing line
+    partial: Vec<u8>,
+}
+
+impl EventReader {
+    /// Reader starting at the beginning of the log at `path`
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        // Fail early on a missing log
+        OpenOptions::new().read(true).open(&path)?;
+        Ok(Self {
+            path,
+            offset: 0,
+            partial: Vec::new(),
+        })
+    }
+
+    /// Complete events appended since the last call
+    ///
+    /// A trailing line without a newline is held back until it is finished.
+    pub fn read_new(&mut self) -> io::Result<Vec<RunEvent>> {
+        let mut file = File::open(&self.path)?;
+        if file.metadata()?.len() < self.offset {
+            // Truncated by a new run: start over
+            self.offset = 0;
+            self.partial.clear();
+        }
+        file.seek(SeekFrom::Start(self.offset))?;
+        self.offset += file.read_to_end(&mut self.partial)? as u64;
+
+        let Some(end) = self.partial.iter().rposition(|&b| b == b'\n') else {
+            return Ok(Vec::new());
+        };
+        let complete: Vec<u8> = self.partial.drain(..=end).collect();
+        String::from_utf8_lossy(&complete)
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| serde_json::from_str(line).map_err(io::Error::from))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use robin_core::{Artifact, ArtifactType, Signal};
+
+    fn artifacts_signal(url: &str) -> Signal {
+        Signal::builder(OsintPayload::ExtractedArtifacts {
+            source_url: url.to_string(),
+            artifacts: vec![Artifact::new(ArtifactType::Email, "ops@evil.example".to_string())],
+        })
+        .origin("extractor-1")
+        .build()
+    }
+
+    #[test]
+    fn test_log_records_each_signal_once() {
+        let path = std::env::temp_dir().join(format!("robin-events-{}.ndjson", std::process::id()));
+        let mut field = Field::new();
+        let mut log = EventLog::create(&path).unwrap();
+        let mut reader = EventReader::open(&path).unwrap();
+
+        field.emit(artifacts_signal("http://a.onion/"));
+        field.emit(Signal::builder(OsintPayload::UserQuery {
+            query: "q".to_string(),
+            priority: 1.0,
+        }).build());
+        assert_eq!(log.record(&field).unwrap(), 1);
+        assert_eq!(log.record(&field).unwrap(), 0);
+
+        let events = reader.read_new().unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].origin, "extractor-1");
+        assert!(events[0].describe().contains("Email=ops@evil.example"));
+
+        let mut tagged = artifacts_signal("http://b.onion/");
+        tagged.tags.push("engagement:acme".to_string());
+        field.emit(tagged);
+        log.record(&field).unwrap();
+        assert_eq!(reader.read_new().unwrap()[0].tags, vec!["engagement:acme"]);
+        assert!(reader.read_new().unwrap().is_empty());
+
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn test_reader_holds_back_partial_lines() {
+        let path = std::env::temp_dir().join(format!("robin-events-partial-{}.ndjson", std::process::id()));
+        let event = RunEvent {
+            timestamp: Utc::now(),
+            hash: "h".to_string(),
+            origin: "analyst-1".to_string(),
+            confidence: 1.0,
+            tags: Vec::new(),
+            payload: OsintPayload::Summary {
+                markdown: "# Report".to_string(),
+                artifact_count: 2,
+                source_count: 1,
+            },
+        };
+        let line = serde_json::to_string(&event).unwrap();
+        let (head, tail) = line.split_at(10);
+
+        std::fs::write(&path, head).unwrap();
+        let mut reader = EventReader::open(&path).unwrap();
+        assert!(reader.read_new().unwrap().is_empty());
+
+        std::fs::write(&path, format!("{}{}\n", head, tail)).unwrap();
+        let events = reader.read_new().unwrap();
+        assert!(events[0].is_final());
+
+        std::fs::remove_file(&path).ok();
+    }
+}
@@ -1,7 +1,20 @@
 //! Robin×SMESH Runtime
 //!
-//! Provides the swarm coordinator that orchestrates agents via signal diffusion.
+//! Provides the swarm coordinator that orchestrates agents via signal diffusion,
+//! a local control socket for driving a running swarm, the run event log,
+//! adaptive tick pacing, the pipeline graph checked from agents' declared
+//! inputs and outputs, and field replication between swarm nodes.
 
 pub mod swarm;
+pub mod control;
+pub mod events;
+pub mod pipeline;
+pub mod pacing;
+pub mod replication;
 
 pub use swarm::*;
+pub use control::*;
+pub use events::*;
+pub use pipeline::*;
+pub use pacing::*;
+pub use replication::*;
@@ -0,0 +1,67 @@
+//! Adaptive tick pacing
+//!
+//! A busy swarm ticks at its base interval; each quiet tick (no signals
+//! besides heartbeats) stretches the interval by half, up to a ceiling. The
+//! first new signal snaps it back to the base, so bursts are handled at full
+//! speed while a long-running, idle swarm wakes only every few seconds.
+
+use std::time::Duration;
+
+/// Tick interval that backs off while the field is quiet
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TickPacer {
+    min_ms: u64,
+    max_ms: u64,
+    current_ms: u64,
+}
+
+impl TickPacer {
+    /// Pace between `min_ms` (during activity) and `max_ms` (when quiet)
+    ///
+    /// A ceiling at or below the floor gives a fixed interval.
+    pub fn new(min_ms: u64, max_ms: u64) -> Self {
+        let min_ms = min_ms.max(1);
+        Self {
+            min_ms,
+            max_ms: max_ms.max(min_ms),
+            current_ms: min_ms,
+        }
+    }
+
+    /// Interval until the next tick
+    pub fn interval(&self) -> Duration {
+        Duration::from_millis(self.current_ms)
+    }
+
+    /// Adjust after a tick, given whether it produced new signals
+    pub fn observe(&mut self, active: bool) {
+        self.current_ms = if active {
+            self.min_ms
+        } else {
+            (self.current_ms + self.current_ms.div_ceil(2)).min(self.max_ms)
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backs_off_when_quiet_and_snaps_back() {
+        let mut pacer = TickPacer::new(500, 2000);
+        assert_eq!(pacer.interval(), Duration::from_millis(500));
+
+        for expected in [750, 1125, 1688, 2000, 2000] {
+            pacer.observe(false);
+            assert_eq!(pacer.interval(), Duration::from_millis(expected));
+        }
+
+        pacer.observe(true);
+        assert_eq!(pacer.interval(), Duration::from_millis(500));
+
+        let mut fixed = TickPacer::new(500, 0);
+        fixed.observe(false);
+        assert_eq!(fixed.interval(), Duration::from_millis(500));
+    }
+}
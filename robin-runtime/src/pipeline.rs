@@ -0,0 +1,182 @@
+//! Pipeline graph from declared agent inputs and outputs
+//!
+//! Built from each agent's [`OsintAgent::senses`] / [`OsintAgent::emits`], so
+//! it shows the pipeline as configured rather than as documented. Checks
+//! flag payloads nobody consumes and agents nobody feeds.
+
+use serde::{Deserialize, Serialize};
+use std::fmt::Write;
+
+use robin_agents::OsintAgent;
+use robin_core::PayloadKind;
+
+/// Kinds the swarm itself emits (user queries) or consumes (the summary ends a run)
+const EXTERNAL: &[PayloadKind] = &[PayloadKind::UserQuery, PayloadKind::Summary];
+
+/// One agent's declared inputs and outputs
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PipelineStage {
+    pub agent_id: String,
+    pub agent_type: String,
+    pub senses: Vec<PayloadKind>,
+    pub emits: Vec<PayloadKind>,
+}
+
+/// A swarm's agents as a producer/consumer graph over payload kinds
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Pipeline {
+    pub stages: Vec<PipelineStage>,
+}
+
+impl Pipeline {
+    pub fn from_agents(agents: &[Box<dyn OsintAgent>]) -> Self {
+        Self {
+            stages: agents
+                .iter()
+                .map(|agent| PipelineStage {
+                    agent_id: agent.id().to_string(),
+                    agent_type: agent.agent_type().to_string(),
+                    senses: agent.senses().to_vec(),
+                    emits: agent.emits().to_vec(),
+                })
+                .collect(),
+        }
+    }
+
+    fn produced(&self, kind: PayloadKind) -> bool {
+        EXTERNAL.contains(&kind) || self.stages.iter().any(|s| s.emits.contains(&kind))
+    }
+
+    fn consumed(&self, kind: PayloadKind) -> bool {
+        EXTERNAL.contains(&kind) || self.stages.iter().any(|s| s.senses.contains(&kind))
+    }
+
+    /// Kinds some agent emits but no agent senses (wasted work)
+    pub fn unconsumed(&self) -> Vec<PayloadKind> {
+        PayloadKind::ALL
+            .iter()
+            .copied()
+            .filter(|&kind| self.stages.iter().any(|s| s.emits.contains(&kind)) && !self.consumed(kind))
+            .collect()
+    }
+
+    /// Agents none of whose inputs any agent emits (they can never run)
+    ///
+    /// Inputs are alternatives: the analyst only folds in enrichment when an
+    /// enricher runs, so a missing producer for one input is not an error.
+    pub fn unfed(&self) -> Vec<&str> {
+        self.stages
+            .iter()
+            .filter(|stage| !stage.senses.is_empty() && !stage.senses.iter().any(|&kind| self.produced(kind)))
+            .map(|stage| stage.agent_id.as_str())
+            .collect()
+    }
+
+    /// Human-readable completeness problems (empty = every link is connected)
+    pub fn warnings(&self) -> Vec<String> {
+        let mut warnings: Vec<String> = self
+            .unconsumed()
+            .into_iter()
+            .map(|kind| format!("nothing consumes {} (emitted by {})", kind, self.emitters(kind).join(", ")))
+            .collect();
+        warnings.extend(
+            self.unfed()
+                .into_iter()
+                .map(|agent| format!("{} will never run: nothing emits what it senses", agent)),
+        );
+        warnings
+    }
+
+    fn emitters(&self, kind: PayloadKind) -> Vec<&str> {
+        self.stages
+            .iter()
+            .filter(|s| s.emits.contains(&kind))
+            .map(|s| s.agent_id.as_str())
+            .collect()
+    }
+
+    /// One line per stage: `crawler-1  refined_query → raw_result`
+    pub fn render(&self) -> String {
+        let width = self.stages.iter().map(|s| s.agent_id.len()).max().unwrap_or(0);
+        let mut out = String::new();
+        for stage in &self.stages {
+            let _ = writeln!(
+                out,
+                "{:<width$}  {} → {}",
+                stage.agent_id,
+                kinds(&stage.senses),
+                kinds(&stage.emits),
+                width = width
+            );
+        }
+        out
+    }
+
+    /// Mermaid flowchart with payload kinds as nodes and agent types as edge labels
+    ///
+    /// Agents of the same type with the same inputs and outputs are drawn
+    /// once; agents that emit nothing end in a round node of their own.
+    pub fn to_mermaid(&self) -> String {
+        let mut edges: Vec<String> = Vec::new();
+        for stage in &self.stages {
+            for input in &stage.senses {
+                let targets = if stage.emits.is_empty() {
+                    vec![format!("{}((\"{}\"))", stage.agent_type, stage.agent_type)]
+                } else {
+                    stage.emits.iter().map(|kind| kind.to_string()).collect()
+                };
+                for target in targets {
+                    let edge = format!("    {} -->|{}| {}", input, stage.agent_type, target);
+                    if !edges.contains(&edge) {
+                        edges.push(edge);
+                    }
+                }
+            }
+        }
+        format!("flowchart LR\n{}\n", edges.join("\n"))
+    }
+}
+
+fn kinds(kinds: &[PayloadKind]) -> String {
+    if kinds.is_empty() {
+        return "(nothing)".to_string();
+    }
+    kinds.iter().map(PayloadKind::as_str).collect::<Vec<_>>().join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stage(id: &str, senses: &[PayloadKind], emits: &[PayloadKind]) -> PipelineStage {
+        PipelineStage {
+            agent_id: id.to_string(),
+            agent_type: id.trim_end_matches("-1").to_string(),
+            senses: senses.to_vec(),
+            emits: emits.to_vec(),
+        }
+    }
+
+    #[test]
+    fn test_warns_on_unconsumed_and_unfed_kinds() {
+        let pipeline = Pipeline {
+            stages: vec![
+                stage("refiner-1", &[PayloadKind::UserQuery], &[PayloadKind::RefinedQuery]),
+                stage("paste-1", &[PayloadKind::RefinedQuery], &[PayloadKind::PasteContent]),
+                stage("analyst-1", &[PayloadKind::ScrapedContent], &[PayloadKind::Summary]),
+            ],
+        };
+
+        assert_eq!(pipeline.unconsumed(), vec![PayloadKind::PasteContent]);
+        assert_eq!(pipeline.unfed(), vec!["analyst-1"]);
+        assert_eq!(
+            pipeline.warnings(),
+            vec![
+                "nothing consumes paste_content (emitted by paste-1)",
+                "analyst-1 will never run: nothing emits what it senses",
+            ]
+        );
+        assert!(pipeline.render().contains("paste-1    refined_query → paste_content"));
+        assert!(pipeline.to_mermaid().contains("    refined_query -->|paste| paste_content"));
+    }
+}
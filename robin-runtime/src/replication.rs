@@ -0,0 +1,580 @@
+//! Field replication between swarm nodes
+//!
+//! Several swarms (on different machines or behind different Tor exits)
+//! can share one logical field: each node gossips the signals emitted into
+//! its field, and the reinforcements they get, to its peers over TCP, and
+//! merges what its peers send with [`Field::merge_remote`]. Merging is
+//! idempotent (signals keep their origin hash, reinforcements are a union
+//! of agents), so a message passed on by several peers is applied once and
+//! gossip dies out where it reaches nodes that already have it.
+//!
+//! Messages lost while a peer is disconnected or busy are repaired by
+//! anti-entropy: on connecting, and then periodically, each node sends its
+//! peers a digest of its active signals with a hash of the agents that
+//! reinforced each, and a node answers with the signals the digest lacks
+//! or has a different set of reinforcers for.
+//!
+//! Every frame is sealed with a shared 32-byte key (see
+//! [`robin_core::at_rest`]), so only nodes holding the keyfile can join.
+//! Nodes with different origin-hash policies are refused: their hashes
+//! would not agree. Heartbeats stay local. Until the hello is exchanged a
+//! peer's frames are capped at [`MAX_HELLO_LEN`], so an unauthenticated
+//! connection can't make a node allocate a full frame.
+//!
+//! Peers are dialed directly unless [`ReplicationConfig::proxy`] is set;
+//! `.onion` peers need it pointed at Tor's SOCKS port.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use thiserror::Error;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::{broadcast, mpsc};
+use sha2::{Digest as _, Sha256};
+use tracing::{debug, info, warn};
+
+use robin_core::{AtRestError, AtRestKey, Field, FieldEvent, OriginHashPolicy, PayloadKind, Signal};
+use robin_tor::UpstreamProxy;
+
+/// Largest frame accepted from a peer, in bytes
+pub const MAX_FRAME_LEN: usize = 16 * 1024 * 1024;
+
+/// Largest frame accepted before the hello exchange completes, in bytes
+pub const MAX_HELLO_LEN: usize = 64 * 1024;
+
+/// Default time between anti-entropy digests
+pub const DEFAULT_ANTI_ENTROPY_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Wait before redialing a peer that dropped or refused the connection
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+/// Time allowed for the hello exchange
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Messages queued per peer before new ones are dropped (anti-entropy repairs them)
+const PEER_QUEUE: usize = 1024;
+
+/// Errors from replication
+#[derive(Debug, Error)]
+pub enum ReplicationError {
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Replication requires a 32-byte keyfile, not a passphrase")]
+    PassphraseKey,
+
+    #[error("Frame of {0} bytes exceeds the limit")]
+    FrameTooLarge(usize),
+
+    #[error("Sealing error: {0}")]
+    Seal(#[from] AtRestError),
+
+    #[error("Invalid message: {0}")]
+    Message(#[from] serde_json::Error),
+
+    #[error("Handshake failed: {0}")]
+    Handshake(String),
+}
+
+/// Where and with whom a node replicates its field
+#[derive(Debug, Clone)]
+pub struct ReplicationConfig {
+    /// Name of this node in peers' logs
+    pub node_id: String,
+    /// Address to accept peers on (None = only dial out)
+    pub listen: Option<SocketAddr>,
+    /// Peers to dial (`host:port`), redialed when the connection drops
+    pub peers: Vec<String>,
+    /// Proxy peers are dialed through, e.g. Tor's SOCKS port (None = direct)
+    pub proxy: Option<UpstreamProxy>,
+    /// Shared key sealing every frame
+    pub key: AtRestKey,
+    /// Time between anti-entropy digests
+    pub anti_entropy_interval: Duration,
+}
+
+impl ReplicationConfig {
+    pub fn new(node_id: &str, key: AtRestKey) -> Self {
+        Self {
+            node_id: node_id.to_string(),
+            listen: None,
+            peers: Vec::new(),
+            proxy: None,
+            key,
+            anti_entropy_interval: DEFAULT_ANTI_ENTROPY_INTERVAL,
+        }
+    }
+
+    pub fn with_listen(mut self, addr: SocketAddr) -> Self {
+        self.listen = Some(addr);
+        self
+    }
+
+    pub fn with_peer(mut self, addr: &str) -> Self {
+        self.peers.push(addr.to_string());
+        self
+    }
+
+    pub fn with_proxy(mut self, proxy: UpstreamProxy) -> Self {
+        self.proxy = Some(proxy);
+        self
+    }
+
+    pub fn with_anti_entropy_interval(mut self, interval: Duration) -> Self {
+        self.anti_entropy_interval = interval;
+        self
+    }
+}
+
+/// What nodes send each other
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ReplicaMessage {
+    /// First message on a connection
+    Hello { node: String, hash_policy: OriginHashPolicy },
+    /// A signal, new or with more reinforcements
+    Signal { signal: Box<Signal> },
+    /// `agent` reinforced the signal `hash`
+    Reinforce { hash: String, agent: String },
+    /// Active signals of the sender with the hash of their reinforcers
+    Digest { entries: Vec<(String, String)> },
+}
+
+/// Connection-local peer number
+type PeerId = u64;
+
+/// What connection tasks pass to the replica
+enum Inbound {
+    Connected(PeerId),
+    Message(PeerId, ReplicaMessage),
+}
+
+/// Connected peers: node name and message queue
+type Peers = Arc<Mutex<HashMap<PeerId, (String, mpsc::Sender<ReplicaMessage>)>>>;
+
+/// State shared by connection tasks
+struct Link {
+    node_id: String,
+    hash_policy: OriginHashPolicy,
+    key: AtRestKey,
+    peers: Peers,
+    inbound: mpsc::UnboundedSender<Inbound>,
+    next_peer: AtomicU64,
+}
+
+/// Replication endpoint of one field
+///
+/// Connections run on background tasks; [`Replica::sync`] exchanges
+/// signals with them and is called by the swarm between ticks.
+pub struct Replica {
+    node_id: String,
+    local_addr: Option<SocketAddr>,
+    events: broadcast::Receiver<FieldEvent>,
+    inbound: mpsc::UnboundedReceiver<Inbound>,
+    peers: Peers,
+    anti_entropy_interval: Duration,
+    last_digest: Instant,
+}
+
+impl Replica {
+    /// Start listening and dialing peers for `field`
+    ///
+    /// Subscribes to `field`, so call it again if the field is replaced.
+    pub async fn start(config: ReplicationConfig, field: &mut Field) -> Result<Self, ReplicationError> {
+        if matches!(config.key, AtRestKey::Passphrase(_)) {
+            return Err(ReplicationError::PassphraseKey);
+        }
+        let (inbound_tx, inbound) = mpsc::unbounded_channel();
+        let peers: Peers = Arc::default();
+        let link = Arc::new(Link {
+            node_id: config.node_id.clone(),
+            hash_policy: field.hash_policy().clone(),
+            key: config.key,
+            peers: peers.clone(),
+            inbound: inbound_tx,
+            next_peer: AtomicU64::new(0),
+        });
+
+        let mut local_addr = None;
+        if let Some(addr) = config.listen {
+            let listener = TcpListener::bind(addr).await?;
+            local_addr = Some(listener.local_addr()?);
+            info!("Replication listening on {}", listener.local_addr()?);
+            tokio::spawn(accept_peers(listener, link.clone()));
+        }
+        for peer in config.peers {
+            tokio::spawn(dial_peer(peer, config.proxy.clone(), link.clone()));
+        }
+
+        Ok(Self {
+            node_id: config.node_id,
+            local_addr,
+            events: field.subscribe(),
+            inbound,
+            peers,
+            anti_entropy_interval: config.anti_entropy_interval,
+            last_digest: Instant::now(),
+        })
+    }
+
+    pub fn node_id(&self) -> &str {
+        &self.node_id
+    }
+
+    /// Address peers can reach this node on, if it listens
+    pub fn local_addr(&self) -> Option<SocketAddr> {
+        self.local_addr
+    }
+
+    /// Names of the connected peers
+    pub fn peers(&self) -> Vec<String> {
+        self.peers.lock().unwrap().values().map(|(node, _)| node.clone()).collect()
+    }
+
+    /// Send local changes to peers and merge theirs into `field`
+    ///
+    /// Returns how many peer messages changed the field.
+    pub fn sync(&mut self, field: &mut Field) -> usize {
+        self.forward(field, None);
+        let mut merged = 0;
+        while let Ok(inbound) = self.inbound.try_recv() {
+            match inbound {
+                Inbound::Connected(peer) => self.send(Some(peer), None, digest(field)),
+                Inbound::Message(peer, message) => {
+                    if self.apply(field, peer, message) {
+                        merged += 1;
+                    }
+                    // Pass what changed on to the other peers
+                    self.forward(field, Some(peer));
+                }
+            }
+        }
+        if self.last_digest.elapsed() >= self.anti_entropy_interval {
+            self.last_digest = Instant::now();
+            self.send(None, None, digest(field));
+        }
+        merged
+    }
+
+    /// Merge one message from `peer`; whether the field changed
+    fn apply(&self, field: &mut Field, peer: PeerId, message: ReplicaMessage) -> bool {
+        match message {
+            ReplicaMessage::Signal { signal } => field.merge_remote(*signal),
+            ReplicaMessage::Reinforce { hash, agent } => {
+                let known = field.get(&hash).is_some_and(|s| !s.reinforced_by.contains(&agent));
+                known && field.reinforce(&hash, &agent)
+            }
+            ReplicaMessage::Digest { entries } => {
+                let theirs: HashMap<String, String> = entries.into_iter().collect();
+                for signal in field.sense(0.0) {
+                    // Either side may hold reinforcers the other lacks: both send, merging is a union
+                    let differs = theirs
+                        .get(&signal.origin_hash)
+                        .is_none_or(|hash| *hash != reinforcers_hash(signal));
+                    if differs && signal.payload.kind() != PayloadKind::Heartbeat {
+                        self.send(
+                            Some(peer),
+                            None,
+                            ReplicaMessage::Signal {
+                                signal: Box::new(signal.clone()),
+                            },
+                        );
+                    }
+                }
+                false
+            }
+            ReplicaMessage::Hello { .. } => false,
+        }
+    }
+
+    /// Send field changes since the last call to every peer but `except`
+    fn forward(&mut self, field: &Field, except: Option<PeerId>) {
+        loop {
+            let message = match self.events.try_recv() {
+                Ok(FieldEvent::SignalEmitted(signal)) if signal.payload.kind() != PayloadKind::Heartbeat => {
+                    ReplicaMessage::Signal { signal }
+                }
+                Ok(FieldEvent::SignalReinforced { hash, agent, .. }) => {
+                    if field.get(&hash).is_none_or(|s| s.payload.kind() == PayloadKind::Heartbeat) {
+                        continue;
+                    }
+                    ReplicaMessage::Reinforce { hash, agent }
+                }
+                Ok(_) => continue,
+                Err(broadcast::error::TryRecvError::Lagged(missed)) => {
+                    debug!("Replication missed {} field events; anti-entropy will catch up", missed);
+                    continue;
+                }
+                Err(_) => break,
+            };
+            self.send(None, except, message);
+        }
+    }
+
+    /// Queue `message` for `to` (None = every peer but `except`)
+    fn send(&self, to: Option<PeerId>, except: Option<PeerId>, message: ReplicaMessage) {
+        let peers = self.peers.lock().unwrap();
+        for (id, (node, tx)) in peers.iter() {
+            if to.is_some_and(|to| to != *id) || except == Some(*id) {
+                continue;
+            }
+            if tx.try_send(message.clone()).is_err() {
+                debug!("Replication queue to {} full; dropped a message", node);
+            }
+        }
+    }
+}
+
+/// Digest of the field's active signals
+fn digest(field: &Field) -> ReplicaMessage {
+    ReplicaMessage::Digest {
+        entries: field
+            .sense(0.0)
+            .into_iter()
+            .filter(|signal| signal.payload.kind() != PayloadKind::Heartbeat)
+            .map(|signal| (signal.origin_hash.clone(), reinforcers_hash(signal)))
+            .collect(),
+    }
+}
+
+/// Hash of the set of agents that reinforced `signal`
+fn reinforcers_hash(signal: &Signal) -> String {
+    let mut agents: Vec<&str> = signal.reinforced_by.iter().map(String::as_str).collect();
+    agents.sort_unstable();
+    let mut hasher = Sha256::new();
+    for agent in agents {
+        hasher.update(agent.as_bytes());
+        hasher.update([0]);
+    }
+    hasher.finalize()[..16].iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+async fn accept_peers(listener: TcpListener, link: Arc<Link>) {
+    loop {
+        match listener.accept().await {
+            Ok((stream, addr)) => {
+                let link = link.clone();
+                tokio::spawn(async move {
+                    if let Err(e) = run_connection(stream, &link).await {
+                        warn!("Replication peer {} dropped: {}", addr, e);
+                    }
+                });
+            }
+            Err(e) => warn!("Replication accept failed: {}", e),
+        }
+        if link.inbound.is_closed() {
+            return;
+        }
+    }
+}
+
+async fn dial_peer(addr: String, proxy: Option<UpstreamProxy>, link: Arc<Link>) {
+    while !link.inbound.is_closed() {
+        let stream = match &proxy {
+            Some(proxy) => proxy.tunnel(&addr).await,
+            None => TcpStream::connect(&addr).await,
+        };
+        match stream {
+            Ok(stream) => {
+                if let Err(e) = run_connection(stream, &link).await {
+                    warn!("Replication peer {} dropped: {}", addr, e);
+                }
+            }
+            Err(e) => debug!("Replication peer {} unreachable: {}", addr, e),
+        }
+        tokio::time::sleep(RECONNECT_DELAY).await;
+    }
+}
+
+/// Exchange hellos, then relay messages until either side closes
+async fn run_connection(stream: TcpStream, link: &Link) -> Result<(), ReplicationError> {
+    stream.set_nodelay(true)?;
+    let (mut read, mut write) = stream.into_split();
+    let hello = ReplicaMessage::Hello {
+        node: link.node_id.clone(),
+        hash_policy: link.hash_policy.clone(),
+    };
+    write_frame(&mut write, &link.key, &hello).await?;
+    let node = match tokio::time::timeout(HANDSHAKE_TIMEOUT, read_frame(&mut read, &link.key, MAX_HELLO_LEN)).await {
+        Err(_) => return Err(ReplicationError::Handshake("timed out".to_string())),
+        Ok(Ok(ReplicaMessage::Hello { node, hash_policy })) => {
+            if node == link.node_id {
+                return Err(ReplicationError::Handshake(format!("peer has this node's ID {}", node)));
+            }
+            if hash_policy != link.hash_policy {
+                return Err(ReplicationError::Handshake(format!("{} uses another origin-hash policy", node)));
+            }
+            node
+        }
+        Ok(Ok(_)) => return Err(ReplicationError::Handshake("expected hello".to_string())),
+        Ok(Err(e)) => return Err(e),
+    };
+
+    let id = link.next_peer.fetch_add(1, Ordering::Relaxed);
+    let (tx, mut rx) = mpsc::channel(PEER_QUEUE);
+    link.peers.lock().unwrap().insert(id, (node.clone(), tx));
+    info!("Replicating with {}", node);
+    let _ = link.inbound.send(Inbound::Connected(id));
+
+    let key = link.key.clone();
+    let writer = tokio::spawn(async move {
+        while let Some(message) = rx.recv().await {
+            if let Err(e) = write_frame(&mut write, &key, &message).await {
+                debug!("Replication write failed: {}", e);
+                break;
+            }
+        }
+    });
+    let result = loop {
+        match read_frame(&mut read, &link.key, MAX_FRAME_LEN).await {
+            Ok(message) => {
+                if link.inbound.send(Inbound::Message(id, message)).is_err() {
+                    break Ok(());
+                }
+            }
+            Err(ReplicationError::Io(e)) if e.kind() == std::io::ErrorKind::UnexpectedEof => break Ok(()),
+            Err(e) => break Err(e),
+        }
+    };
+    link.peers.lock().unwrap().remove(&id);
+    writer.abort();
+    info!("Stopped replicating with {}", node);
+    result
+}
+
+/// Write one sealed, length-prefixed message
+async fn write_frame<W: AsyncWriteExt + Unpin>(
+    write: &mut W,
+    key: &AtRestKey,
+    message: &ReplicaMessage,
+) -> Result<(), ReplicationError> {
+    let sealed = robin_core::seal(key, &serde_json::to_vec(message)?)?;
+    if sealed.len() > MAX_FRAME_LEN {
+        return Err(ReplicationError::FrameTooLarge(sealed.len()));
+    }
+    write.write_all(&(sealed.len() as u32).to_be_bytes()).await?;
+    write.write_all(&sealed).await?;
+    Ok(())
+}
+
+/// Read and open one message of at most `max_len` sealed bytes
+async fn read_frame<R: AsyncReadExt + Unpin>(
+    read: &mut R,
+    key: &AtRestKey,
+    max_len: usize,
+) -> Result<ReplicaMessage, ReplicationError> {
+    let len = read.read_u32().await? as usize;
+    if len > max_len {
+        return Err(ReplicationError::FrameTooLarge(len));
+    }
+    let mut sealed = vec![0u8; len];
+    read.read_exact(&mut sealed).await?;
+    Ok(serde_json::from_slice(&robin_core::open(key, &sealed)?)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use robin_core::{Network, OsintPayload};
+
+    fn result(url: &str) -> Signal {
+        Signal::builder(OsintPayload::RawResult {
+            url: url.to_string(),
+            title: "Leak".to_string(),
+            engine: "Ahmia".to_string(),
+            network: Network::Tor,
+            query: None,
+        })
+        .origin("crawler-1")
+        .ttl(600.0)
+        .build()
+    }
+
+    /// Sync both nodes until `done` holds
+    async fn sync_until(nodes: &mut [(&mut Replica, &mut Field)], done: impl Fn(&[(&mut Replica, &mut Field)]) -> bool) {
+        for _ in 0..200 {
+            for (replica, field) in nodes.iter_mut() {
+                replica.sync(field);
+            }
+            if done(nodes) {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        panic!("replicas did not converge");
+    }
+
+    #[tokio::test]
+    async fn test_two_nodes_converge() {
+        let key = AtRestKey::Key([3u8; 32]);
+        let localhost: SocketAddr = "127.0.0.1:0".parse().unwrap();
+
+        // A signal emitted before the second node joins reaches it by anti-entropy
+        let mut field_a = Field::new();
+        let early = field_a.emit(result("http://early.onion/"));
+        let mut a = Replica::start(ReplicationConfig::new("a", key.clone()).with_listen(localhost), &mut field_a)
+            .await
+            .unwrap();
+        let addr = a.local_addr().unwrap().to_string();
+        let mut field_b = Field::new();
+        let mut b = Replica::start(ReplicationConfig::new("b", key.clone()).with_peer(&addr), &mut field_b)
+            .await
+            .unwrap();
+        let mut nodes = [(&mut a, &mut field_a), (&mut b, &mut field_b)];
+        sync_until(&mut nodes, |n| n[1].1.get(&early).is_some()).await;
+
+        // New signals and reinforcements flow both ways
+        let late = nodes[1].1.emit(result("http://late.onion/"));
+        nodes[0].1.reinforce(&early, "crawler-2");
+        sync_until(&mut nodes, |n| {
+            n[0].1.get(&late).is_some() && n[1].1.get(&early).unwrap().reinforcement_count == 1
+        })
+        .await;
+        nodes[0].1.reinforce(&late, "crawler-3");
+        sync_until(&mut nodes, |n| n[1].1.get(&late).unwrap().reinforcement_count == 1).await;
+        assert_eq!(nodes[0].1.get(&late).unwrap().reinforcement_count, 1);
+        assert_eq!(nodes[0].0.peers(), vec!["b".to_string()]);
+
+        // Nodes without the key can't join
+        let mut field_c = Field::new();
+        let c = Replica::start(ReplicationConfig::new("c", AtRestKey::Key([4u8; 32])).with_peer(&addr), &mut field_c)
+            .await
+            .unwrap();
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert!(c.peers().is_empty());
+        assert_eq!(nodes[0].0.peers().len(), 1);
+
+        assert!(matches!(
+            Replica::start(ReplicationConfig::new("d", AtRestKey::passphrase("secret")), &mut field_c).await,
+            Err(ReplicationError::PassphraseKey)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_hello_frame_is_capped() {
+        let key = AtRestKey::Key([3u8; 32]);
+        let (mut client, mut server) = tokio::io::duplex(64);
+        client.write_all(&((MAX_HELLO_LEN + 1) as u32).to_be_bytes()).await.unwrap();
+        assert!(matches!(
+            read_frame(&mut server, &key, MAX_HELLO_LEN).await,
+            Err(ReplicationError::FrameTooLarge(_))
+        ));
+    }
+
+    #[test]
+    fn test_digest_compares_reinforcers() {
+        let mut a = result("http://x.onion/");
+        let mut b = a.clone();
+        a.reinforced_by = vec!["crawler-2".to_string(), "crawler-3".to_string()];
+        b.reinforced_by = vec!["crawler-3".to_string(), "crawler-2".to_string()];
+        assert_eq!(reinforcers_hash(&a), reinforcers_hash(&b));
+
+        // Same count, different agents
+        b.reinforced_by = vec!["crawler-3".to_string(), "crawler-4".to_string()];
+        assert_ne!(reinforcers_hash(&a), reinforcers_hash(&b));
+    }
+}
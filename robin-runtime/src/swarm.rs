@@ -4,19 +4,38 @@
 //! - No central orchestration
 //! - Agents sense and emit signals independently
 //! - Coordination emerges from signal reinforcement
-//! - Field ticks advance time and decay signals
-
-use std::time::Duration;
-use tokio::time::interval;
+//! - Field ticks advance time and decay signals, less often while nothing happens
+//! - An optional control channel submits queries and pauses the run
+//! - An optional event log records discoveries as they happen
+//! - An optional field journal lets an interrupted run resume
+//! - Agents idle with `NoWork` are not woken until their sensed kinds change
+//! - An optional replica shares the field with swarms on other nodes
+
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
 use tracing::{debug, error, info, warn};
 
 use robin_agents::{
-    AgentConfig, AgentError, AnalystAgent, BlockchainAgent, BlockchainConfig, CrawlerAgent,
-    EnrichmentAgent, EnrichmentConfig, ExtractorAgent, FilterAgent, OsintAgent,
-    PasteMonitorAgent, PasteMonitorConfig, RefinerAgent, ScraperAgent, SharedBackend,
+    default_paste_sites, AgentConfig, AgentError, AnalystAgent, BlockchainAgent, BlockchainConfig, ChangeDetectorAgent, CrawlerAgent,
+    DirectoryAgent, EnrichmentAgent, EnrichmentConfig, ExtractorAgent, FeedWatcherAgent, FeedWatcherConfig, FilterAgent, FollowLimits, GistWatcherAgent, GistWatcherConfig, LinkFrontier, OsintAgent,
+    PasteMonitorAgent, PasteMonitorConfig, PasteScoring, RefinerAgent, ScraperAgent, SharedBackend, SharedEmbedder, SharedPasteSite, SharedTranslator,
+    ProfilerAgent, TargetMonitorAgent, ThreadParserAgent, WorkQueue,
+};
+use robin_core::{
+    ActorDatabase, AdditiveModel, AtRestKey, EngineCategory, Field, FieldEvent, FieldStoreError, OriginHashPolicy, OsintPayload, PayloadKind, SharedChanges, SharedHook, SharedKnowledge, SharedReinforcement, SharedSeen, SharedTally, SharedVectors, Signal, SignalPolicy,
 };
-use robin_core::{Field, OsintPayload, Signal};
-use robin_tor::TorConfig;
+use robin_tor::{BandwidthSnapshot, MetricsSnapshot, TorConfig, TorError};
+
+use crate::control::{ControlHandle, ControlMessage, ControlRequest, ControlResponse};
+use crate::events::EventLog;
+use crate::pacing::TickPacer;
+use crate::pipeline::Pipeline;
+use crate::replication::{Replica, ReplicationConfig, ReplicationError};
+
+/// Ticks of per-agent activity kept for status requests
+pub const ACTIVITY_WINDOW: usize = 40;
 
 /// Swarm configuration
 pub struct SwarmConfig {
@@ -24,8 +43,11 @@ pub struct SwarmConfig {
     pub backend: SharedBackend,
     /// Tor proxy configuration
     pub tor_config: TorConfig,
-    /// Field tick interval in milliseconds
+    /// Field tick interval in milliseconds (while signals are flowing)
     pub tick_interval_ms: u64,
+    /// Longest tick interval in milliseconds, reached while the field is quiet
+    /// (at or below `tick_interval_ms` = fixed interval)
+    pub max_tick_interval_ms: u64,
     /// Maximum runtime in seconds (0 = unlimited)
     pub max_runtime_secs: u64,
     /// Number of crawler agents
@@ -40,6 +62,46 @@ pub struct SwarmConfig {
     pub enable_blockchain: bool,
     /// Enable paste site monitoring
     pub enable_pastes: bool,
+    /// Which pastes the paste monitor emits
+    pub paste_scoring: PasteScoring,
+    /// Paste sites searched besides the built-in ones
+    pub paste_sites: Vec<SharedPasteSite>,
+    /// Origin-hash policy for the field (controls what reinforces)
+    pub hash_policy: OriginHashPolicy,
+    /// Signal lifetimes (TTL and decay) per payload kind
+    pub signal_policy: SignalPolicy,
+    /// How reinforcements raise a signal's confidence (None = additive)
+    pub reinforcement: Option<SharedReinforcement>,
+    /// Engine categories crawlers focus on (empty = all engines)
+    pub engine_focus: Vec<EngineCategory>,
+    /// Have the refiner phrase the query per engine category (forums, markets, leaks)
+    pub query_variants: bool,
+    /// Result pages crawled per engine, for engines that paginate
+    pub max_pages: u32,
+    /// Longest text in one ScrapedContent signal; longer pages are split into chunks
+    pub chunk_chars: usize,
+    /// Follow links on scraped pages within these limits (None = only scrape search results)
+    pub follow_links: Option<FollowLimits>,
+    /// Onion link directories (URLs or files) to seed results from
+    pub seed_directories: Vec<String>,
+    /// RSS/Atom feeds to watch for query-relevant items (empty = no feed watcher)
+    pub feeds: Vec<String>,
+    /// Public gists (and push events) to watch for the query and a watchlist (None = no gist watcher)
+    pub gists: Option<GistWatcherConfig>,
+    /// Threat actor knowledge base for extraction and alias normalization
+    pub actors: ActorDatabase,
+    /// Past cases the analyst consults and adds this run to (None = no memory)
+    pub knowledge: Option<SharedKnowledge>,
+    /// Past collections the analyst searches and adds this run's content to (None = no index)
+    pub vectors: Option<(SharedVectors, SharedEmbedder)>,
+    /// Findings tallied per monitored target (None = no target monitor)
+    pub monitor: Option<SharedTally>,
+    /// URLs crawled and scraped by earlier runs, skipped in this one (None = no cross-run dedup)
+    pub seen: Option<SharedSeen>,
+    /// Page versions from earlier runs; scraped pages that changed since emit ContentChanged (None = no change detection)
+    pub changes: Option<SharedChanges>,
+    /// Translates non-English titles and pages for the filter and analyst (None = no translation)
+    pub translator: Option<SharedTranslator>,
 }
 
 /// The OSINT swarm coordinator
@@ -47,13 +109,32 @@ pub struct Swarm {
     backend: SharedBackend,
     tor_config: TorConfig,
     tick_interval_ms: u64,
+    max_tick_interval_ms: u64,
     max_runtime_secs: u64,
     use_specialists: bool,
     enable_enrichment: bool,
     enable_blockchain: bool,
     enable_pastes: bool,
+    paste_scoring: PasteScoring,
+    paste_sites: Vec<SharedPasteSite>,
+    query_variants: bool,
+    max_pages: u32,
+    chunk_chars: usize,
+    follow_links: Option<FollowLimits>,
+    seen: Option<SharedSeen>,
+    translator: Option<SharedTranslator>,
     field: Field,
     agents: Vec<Box<dyn OsintAgent>>,
+    control: Option<mpsc::Receiver<ControlMessage>>,
+    paused: bool,
+    event_log: Option<EventLog>,
+    replica: Option<Replica>,
+    /// Signals emitted per agent in recent ticks
+    activity: BTreeMap<String, VecDeque<u32>>,
+    /// Field sequence at which each idle agent last reported `NoWork`
+    idle: HashMap<String, u64>,
+    /// Investigations queries were submitted to, in order (`None` = unscoped)
+    investigations: Vec<Option<String>>,
 }
 
 impl Swarm {
@@ -67,90 +148,215 @@ impl Swarm {
             backend: config.backend,
             tor_config: config.tor_config,
             tick_interval_ms: config.tick_interval_ms,
+            max_tick_interval_ms: config.max_tick_interval_ms,
             max_runtime_secs: config.max_runtime_secs,
             use_specialists,
             enable_enrichment,
             enable_blockchain,
             enable_pastes,
-            field: Field::new(),
+            paste_scoring: config.paste_scoring,
+            paste_sites: config.paste_sites,
+            query_variants: config.query_variants,
+            max_pages: config.max_pages,
+            chunk_chars: config.chunk_chars,
+            follow_links: config.follow_links,
+            seen: config.seen,
+            translator: config.translator,
+            field: Field::new()
+                .with_hash_policy(config.hash_policy)
+                .with_signal_policy(config.signal_policy)
+                .with_reinforcement_model(config.reinforcement.unwrap_or_else(|| Arc::new(AdditiveModel::default()))),
             agents: Vec::new(),
+            control: None,
+            paused: false,
+            event_log: None,
+            replica: None,
+            activity: BTreeMap::new(),
+            idle: HashMap::new(),
+            investigations: Vec::new(),
         };
 
         // Initialize agents
-        swarm.init_agents(config.num_crawlers, config.num_scrapers);
+        swarm.init_agents(
+            config.num_crawlers,
+            config.num_scrapers,
+            &config.engine_focus,
+            &config.actors,
+            config.knowledge,
+            config.vectors,
+        )?;
+        if !config.seed_directories.is_empty() {
+            info!("Seeding from {} link directories", config.seed_directories.len());
+            let directory = DirectoryAgent::new(
+                AgentConfig::default().with_id("directory-1"),
+                swarm.tor_config.for_agent("directory-1"),
+                config.seed_directories,
+            );
+            swarm.agents.push(Box::new(directory));
+        }
+        if !config.feeds.is_empty() {
+            info!("Watching {} RSS/Atom feeds", config.feeds.len());
+            let feeds = FeedWatcherAgent::new(
+                AgentConfig::default().with_id("feed-watcher-1"),
+                FeedWatcherConfig {
+                    feeds: config.feeds,
+                    ..Default::default()
+                },
+            );
+            swarm.agents.push(Box::new(feeds));
+        }
+        if let Some(gists) = config.gists {
+            let egress = &swarm.tor_config.egress;
+            info!("Watching public gists{}, egress {}", if gists.push_events { " and push events" } else { "" }, egress.pastes);
+            let proxy = egress.pastes.proxy_url(&swarm.tor_config)?;
+            let watcher = GistWatcherAgent::new(
+                AgentConfig::default().with_id("gist-watcher-1"),
+                GistWatcherConfig { proxy, ..gists },
+            );
+            swarm.agents.push(Box::new(watcher));
+        }
+        if let Some(tally) = config.monitor {
+            let monitor = TargetMonitorAgent::new(AgentConfig::default().with_id("target-monitor-1"), tally);
+            swarm.agents.push(Box::new(monitor));
+        }
+        if let Some(changes) = config.changes {
+            let detector = ChangeDetectorAgent::new(AgentConfig::default().with_id("change-detector-1"), changes);
+            swarm.agents.push(Box::new(detector));
+        }
+
+        for warning in swarm.pipeline().warnings() {
+            warn!("Pipeline: {}", warning);
+        }
 
         Ok(swarm)
     }
 
-    fn init_agents(&mut self, num_crawlers: usize, num_scrapers: usize) {
+    fn init_agents(
+        &mut self,
+        num_crawlers: usize,
+        num_scrapers: usize,
+        engine_focus: &[EngineCategory],
+        actors: &ActorDatabase,
+        knowledge: Option<SharedKnowledge>,
+        vectors: Option<(SharedVectors, SharedEmbedder)>,
+    ) -> Result<(), TorError> {
+        let egress = &self.tor_config.egress;
+
         // Refiner agent (1)
         let refiner = RefinerAgent::new(
             AgentConfig::default().with_id("refiner-1"),
             self.backend.clone(),
-        );
+        )
+        .with_variants(self.query_variants);
         self.agents.push(Box::new(refiner));
 
         // Crawler agents
         for i in 0..num_crawlers {
-            let crawler = CrawlerAgent::new(
-                AgentConfig::default().with_id(&format!("crawler-{}", i + 1)),
-                self.tor_config.clone(),
-            );
+            let id = format!("crawler-{}", i + 1);
+            let crawler = CrawlerAgent::new(AgentConfig::default().with_id(&id), self.tor_config.for_agent(&id))
+            .with_focus(engine_focus.to_vec())
+            .with_max_pages(self.max_pages)
+            .with_partition(i, num_crawlers);
+            let crawler = match &self.seen {
+                Some(seen) => crawler.with_seen(seen.clone()),
+                None => crawler,
+            };
             self.agents.push(Box::new(crawler));
         }
 
         // Filter agent (1)
-        let filter = FilterAgent::new(
+        let mut filter = FilterAgent::new(
             AgentConfig::default().with_id("filter-1"),
             self.backend.clone(),
         );
+        if let Some(translator) = &self.translator {
+            filter = filter.with_translator(translator.clone());
+        }
         self.agents.push(Box::new(filter));
 
-        // Scraper agents
-        for i in 0..num_scrapers {
-            let scraper = ScraperAgent::new(
-                AgentConfig::default().with_id(&format!("scraper-{}", i + 1)),
-                self.tor_config.clone(),
+        // Scraper agents, sharing one link frontier when following links
+        let frontier = self.follow_links.map(|limits| {
+            info!(
+                "Following links up to depth {} ({} pages max)",
+                limits.max_depth, limits.max_pages
             );
+            LinkFrontier::new(limits).shared()
+        });
+        // One queue, so each URL is scraped by exactly one scraper
+        let scrape_queue = WorkQueue::new();
+        for i in 0..num_scrapers {
+            let id = format!("scraper-{}", i + 1);
+            let mut scraper = ScraperAgent::new(AgentConfig::default().with_id(&id), self.tor_config.for_agent(&id))
+            .with_chunk_chars(self.chunk_chars)
+            .with_work_queue(scrape_queue.clone());
+            if let Some(frontier) = &frontier {
+                scraper = scraper.with_frontier(frontier.clone());
+            }
+            if let Some(seen) = &self.seen {
+                scraper = scraper.with_seen(seen.clone());
+            }
+            if let Some(translator) = &self.translator {
+                scraper = scraper.with_translator(translator.clone());
+            }
             self.agents.push(Box::new(scraper));
         }
 
         // Extractor agent (1)
-        let extractor = ExtractorAgent::new(AgentConfig::default().with_id("extractor-1"));
+        let extractor =
+            ExtractorAgent::new(AgentConfig::default().with_id("extractor-1")).with_actors(actors.clone());
         self.agents.push(Box::new(extractor));
 
+        // Thread parser (1) - per-author messages from chat logs and forum threads
+        self.agents.push(Box::new(ThreadParserAgent::new(AgentConfig::default().with_id("thread-parser-1"))));
+
+        // Profiler (1) - per-actor profiles, refreshed as evidence accumulates
+        let profiler = ProfilerAgent::new(AgentConfig::default().with_id("profiler-1")).with_actors(actors.clone());
+        self.agents.push(Box::new(profiler));
+
         // Enrichment agent (optional) - queries external OSINT sources
         if self.enable_enrichment {
-            info!("Enabling external OSINT enrichment (GitHub, Brave)");
+            info!("Enabling external OSINT enrichment (GitHub, Brave), egress {}", egress.enrichment);
             let enricher = EnrichmentAgent::new(
                 AgentConfig::default().with_id("enricher-1"),
-                EnrichmentConfig::default(),
+                EnrichmentConfig {
+                    proxy: egress.enrichment.proxy_url(&self.tor_config)?,
+                    ..Default::default()
+                },
             );
             self.agents.push(Box::new(enricher));
         }
 
         // Blockchain agent (optional) - temporal analysis of crypto wallets
         if self.enable_blockchain {
-            info!("Enabling blockchain temporal analysis (Blockstream, Etherscan)");
+            info!("Enabling blockchain temporal analysis (Blockstream, Etherscan), egress {}", egress.blockchain);
             let blockchain = BlockchainAgent::new(
                 AgentConfig::default().with_id("blockchain-1"),
-                BlockchainConfig::default(),
+                BlockchainConfig {
+                    proxy: egress.blockchain.proxy_url(&self.tor_config)?,
+                    ..Default::default()
+                },
             );
             self.agents.push(Box::new(blockchain));
         }
 
         // Paste monitor agent (optional) - search public paste sites
         if self.enable_pastes {
-            info!("Enabling paste site monitoring (Pastebin, Rentry, dpaste, etc.)");
+            info!("Enabling paste site monitoring (Pastebin, Rentry, dpaste, etc.), egress {}", egress.pastes);
             let paste_monitor = PasteMonitorAgent::new(
                 AgentConfig::default().with_id("paste-monitor-1"),
-                PasteMonitorConfig::default(),
-            );
+                PasteMonitorConfig {
+                    proxy: egress.pastes.proxy_url(&self.tor_config)?,
+                    scoring: self.paste_scoring.clone(),
+                    sites: default_paste_sites().into_iter().chain(self.paste_sites.iter().cloned()).collect(),
+                    ..Default::default()
+                },
+            )
+            .with_archive(self.tor_config.archive.clone());
             self.agents.push(Box::new(paste_monitor));
         }
 
         // Analyst agent (1) - with or without specialists
-        let analyst = if self.use_specialists {
+        let mut analyst = if self.use_specialists {
             info!("Using multi-specialist analyst mode");
             AnalystAgent::new_with_specialists(
                 AgentConfig::default().with_id("analyst-1"),
@@ -161,34 +367,203 @@ impl Swarm {
                 AgentConfig::default().with_id("analyst-1"),
                 self.backend.clone(),
             )
-        };
+        }
+        .with_actors(actors.clone());
+        if let Some(knowledge) = knowledge {
+            analyst = analyst.with_knowledge(knowledge);
+        }
+        if let Some((store, embedder)) = vectors {
+            analyst = analyst.with_vectors(store, embedder);
+        }
         self.agents.push(Box::new(analyst));
 
         info!("Initialized {} agents", self.agents.len());
+        Ok(())
     }
 
     /// Submit a query to the swarm
     pub fn submit_query(&mut self, query: &str, priority: f64) -> String {
-        let signal = Signal::builder(OsintPayload::UserQuery {
+        self.submit(None, query, priority)
+    }
+
+    /// Submit a query as its own investigation, alongside any others
+    ///
+    /// Its signals are kept apart from other investigations' (see
+    /// [`Field::sense_in`]), and [`Swarm::run`] waits for a summary of each.
+    pub fn submit_investigation(&mut self, investigation: &str, query: &str, priority: f64) -> String {
+        self.submit(Some(investigation), query, priority)
+    }
+
+    fn submit(&mut self, investigation: Option<&str>, query: &str, priority: f64) -> String {
+        let mut builder = Signal::builder(OsintPayload::UserQuery {
             query: query.to_string(),
             priority,
         })
         .origin("user")
         .confidence(1.0)
-        .ttl(300.0) // Query lives for 5 minutes
-        .build();
+        .ttl(300.0); // Query lives for 5 minutes
+        if let Some(investigation) = investigation {
+            builder = builder.investigation(investigation);
+        }
 
-        let hash = self.field.emit(signal);
+        let hash = self.field.emit(builder.build());
+        let investigation = investigation.map(str::to_string);
+        if !self.investigations.contains(&investigation) {
+            self.investigations.push(investigation);
+        }
         info!("Submitted query: {} (hash: {})", query, hash);
         hash
     }
 
+    /// Summaries of every submitted investigation, once all are written
+    ///
+    /// Several are joined in submission order. Without submitted queries
+    /// (a resumed field), any summary will do.
+    fn report(&self) -> Option<String> {
+        let markdown = |signal: &Signal| match &signal.payload {
+            OsintPayload::Summary { markdown, .. } => Some(markdown.clone()),
+            _ => None,
+        };
+        if self.investigations.is_empty() {
+            return self.field.sense_kinds(&[PayloadKind::Summary], 0.0).into_iter().find_map(markdown);
+        }
+        let summaries = self
+            .investigations
+            .iter()
+            .map(|id| self.field.sense_in(id.as_deref(), &[PayloadKind::Summary], 0.0).into_iter().find_map(markdown))
+            .collect::<Option<Vec<_>>>()?;
+        Some(summaries.join("\n\n---\n\n"))
+    }
+
+    /// Channel for controlling the swarm while it runs
+    ///
+    /// Requests are handled between ticks. Calling this again replaces the
+    /// previous channel.
+    pub fn control_handle(&mut self) -> ControlHandle {
+        let (tx, rx) = mpsc::channel(16);
+        self.control = Some(rx);
+        ControlHandle { tx }
+    }
+
+    /// Append artifacts, insights and the summary to an NDJSON log at `path`
+    ///
+    /// See [`crate::EventReader`] for following the log.
+    pub fn log_events_to(&mut self, path: &std::path::Path) -> std::io::Result<()> {
+        self.event_log = Some(EventLog::create(path)?);
+        Ok(())
+    }
+
+    /// Journal the field to `path`, resuming from it if it exists
+    ///
+    /// Returns whether an earlier field was resumed. Restored signals are
+    /// new to this run's agents, so they pick them up as work again.
+    /// The swarm's emission hooks, signal policy and reinforcement model carry
+    /// over to a resumed field. With `key`, the journal is sealed.
+    pub fn store_field_at(&mut self, path: &std::path::Path, key: Option<&AtRestKey>) -> Result<bool, FieldStoreError> {
+        if path.exists() {
+            let hooks = self.field.hooks().to_vec();
+            let policy = self.field.signal_policy().clone();
+            let reinforcement = self.field.reinforcement_model().clone();
+            self.field = Field::load(path, key)?;
+            self.field.set_signal_policy(policy);
+            self.field.set_reinforcement_model(reinforcement);
+            for hook in hooks {
+                self.field.add_hook(hook);
+            }
+            info!("Resumed field from {}: {} active signals", path.display(), self.field.active_count());
+            Ok(true)
+        } else {
+            self.field.persist_to(path, key)?;
+            Ok(false)
+        }
+    }
+
+    /// Share the field with the swarms of other nodes (see [`crate::replication`])
+    ///
+    /// Call after [`Swarm::store_field_at`]: resuming replaces the field.
+    /// Returns the address peers can reach this node on, if it listens.
+    pub async fn replicate(&mut self, config: ReplicationConfig) -> Result<Option<std::net::SocketAddr>, ReplicationError> {
+        let replica = Replica::start(config, &mut self.field).await?;
+        let addr = replica.local_addr();
+        self.replica = Some(replica);
+        Ok(addr)
+    }
+
+    /// Whether the swarm is paused by a control request
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    /// Handle pending control requests
+    fn handle_control(&mut self, start: Instant) {
+        let Some(mut control) = self.control.take() else {
+            return;
+        };
+        while let Ok((request, reply)) = control.try_recv() {
+            let mut stats = None;
+            let mut activity = None;
+            let mut pipeline = None;
+            let mut bandwidth = None;
+            let message = match request {
+                ControlRequest::Submit { query, priority, investigation } => {
+                    let hash = match &investigation {
+                        Some(investigation) => self.submit_investigation(investigation, &query, priority),
+                        None => self.submit_query(&query, priority),
+                    };
+                    format!("submitted query (hash: {})", hash)
+                }
+                ControlRequest::Status => {
+                    stats = Some(self.field.stats());
+                    activity = Some(
+                        self.activity
+                            .iter()
+                            .map(|(id, ticks)| (id.clone(), ticks.iter().copied().collect()))
+                            .collect(),
+                    );
+                    pipeline = Some(self.pipeline());
+                    bandwidth = Some(self.tor_bandwidth());
+                    if self.paused { "paused" } else { "running" }.to_string()
+                }
+                ControlRequest::Pause => {
+                    self.paused = true;
+                    info!("Swarm paused by control request");
+                    "paused".to_string()
+                }
+                ControlRequest::Resume => {
+                    self.paused = false;
+                    info!("Swarm resumed by control request");
+                    "resumed".to_string()
+                }
+            };
+            let _ = reply.send(ControlResponse {
+                ok: true,
+                message,
+                paused: self.paused,
+                elapsed_secs: start.elapsed().as_secs(),
+                stats,
+                activity,
+                pipeline,
+                bandwidth,
+            });
+        }
+        self.control = Some(control);
+    }
+
     /// Run the swarm until completion or timeout
+    ///
+    /// Time spent paused counts towards the maximum runtime.
     pub async fn run(&mut self) -> Result<Option<String>, anyhow::Error> {
-        let tick_duration = Duration::from_millis(self.tick_interval_ms);
-        let mut ticker = interval(tick_duration);
-
-        let start = std::time::Instant::now();
+        let mut pacer = TickPacer::new(self.tick_interval_ms, self.max_tick_interval_ms);
+        let mut next_tick = tokio::time::Instant::now();
+        // Heartbeats arrive every tick, and task claims only accompany
+        // other work; neither counts as activity
+        let active_kinds: Vec<PayloadKind> = PayloadKind::ALL
+            .iter()
+            .copied()
+            .filter(|&kind| !matches!(kind, PayloadKind::Heartbeat | PayloadKind::TaskClaim))
+            .collect();
+
+        let start = Instant::now();
         let max_runtime = if self.max_runtime_secs > 0 {
             Duration::from_secs(self.max_runtime_secs)
         } else {
@@ -198,7 +573,10 @@ impl Swarm {
         info!("Swarm starting with {} agents", self.agents.len());
 
         loop {
-            ticker.tick().await;
+            tokio::time::sleep_until(next_tick).await;
+            let tick_duration = pacer.interval();
+            next_tick = tokio::time::Instant::now() + tick_duration;
+            let sequence = self.field.sequence();
 
             // Check timeout
             if start.elapsed() >= max_runtime {
@@ -206,8 +584,20 @@ impl Swarm {
                 break;
             }
 
+            self.handle_control(start);
+            if self.paused {
+                continue;
+            }
+
+            if let Some(replica) = &mut self.replica {
+                let merged = replica.sync(&mut self.field);
+                if merged > 0 {
+                    debug!("Merged {} updates from peers", merged);
+                }
+            }
+
             // Tick the field (decay signals)
-            let tick_result = self.field.tick(self.tick_interval_ms as f64 / 1000.0);
+            let tick_result = self.field.tick(tick_duration.as_secs_f64());
             debug!(
                 "Field tick: {} active, {} expired",
                 tick_result.active_count, tick_result.expired_count
@@ -218,8 +608,27 @@ impl Swarm {
                 // Emit heartbeat
                 agent.heartbeat(&mut self.field);
 
+                // Skip agents whose inputs haven't changed since they last had nothing to do
+                let ticks = self.activity.entry(agent.id().to_string()).or_default();
+                if ticks.len() == ACTIVITY_WINDOW {
+                    ticks.pop_front();
+                }
+                if still_idle(&self.idle, &self.field, agent.as_ref()) {
+                    ticks.push_back(0);
+                    continue;
+                }
+
                 // Process signals
-                match agent.process(&mut self.field).await {
+                let sequence = self.field.sequence();
+                let result = agent.process(&mut self.field).await;
+                ticks.push_back(result.as_ref().map_or(0, |hashes| hashes.len() as u32));
+                if matches!(result, Err(AgentError::NoWork)) {
+                    self.idle.insert(agent.id().to_string(), sequence);
+                } else {
+                    self.idle.remove(agent.id());
+                }
+
+                match result {
                     Ok(hashes) => {
                         if !hashes.is_empty() {
                             debug!(
@@ -241,20 +650,23 @@ impl Swarm {
                 }
             }
 
-            // Check for summary signal (completion)
-            let summaries: Vec<_> = self
-                .field
-                .sense_where(|s| matches!(&s.payload, OsintPayload::Summary { .. }))
-                .into_iter()
-                .cloned()
-                .collect();
-
-            if let Some(summary_signal) = summaries.first() {
-                if let OsintPayload::Summary { markdown, .. } = &summary_signal.payload {
-                    info!("Swarm completed - summary generated");
-                    return Ok(Some(markdown.clone()));
+            pacer.observe(self.field.changed_since(&active_kinds, sequence));
+
+            if let Some(log) = &mut self.event_log {
+                if let Err(e) = log.record(&self.field) {
+                    warn!("Failed to write event log {}: {}", log.path().display(), e);
                 }
             }
+            if let Some(e) = self.field.take_store_error() {
+                let path = self.field.store_path().map(|p| p.display().to_string()).unwrap_or_default();
+                warn!("Field journal {} stopped: {}", path, e);
+            }
+
+            // Check for summary signals (completion)
+            if let Some(report) = self.report() {
+                info!("Swarm completed - summary generated");
+                return Ok(Some(report));
+            }
 
             // Print field stats periodically
             let stats = self.field.stats();
@@ -269,16 +681,57 @@ impl Swarm {
         Ok(None)
     }
 
+    /// The agents' declared inputs and outputs
+    pub fn pipeline(&self) -> Pipeline {
+        Pipeline::from_agents(&self.agents)
+    }
+
+    /// The signal field
+    pub fn field(&self) -> &Field {
+        &self.field
+    }
+
+    /// Run `hook` on every signal agents emit (see [`robin_core::hooks`])
+    pub fn add_emit_hook(&mut self, hook: SharedHook) {
+        self.field.add_hook(hook);
+    }
+
+    /// Receive field events (emissions, reinforcements, expiries) while the swarm runs
+    ///
+    /// Subscribe after [`Swarm::store_field_at`]: resuming replaces the field.
+    pub fn subscribe(&mut self) -> tokio::sync::broadcast::Receiver<FieldEvent> {
+        self.field.subscribe()
+    }
+
     /// Get field statistics
     pub fn stats(&self) -> robin_core::FieldStats {
         self.field.stats()
     }
+
+    /// Tor request metrics so far, by host and by engine
+    pub fn tor_metrics(&self) -> MetricsSnapshot {
+        self.tor_config.metrics.snapshot()
+    }
+
+    /// Bytes transferred so far, by host and by agent
+    pub fn tor_bandwidth(&self) -> BandwidthSnapshot {
+        self.tor_config.bandwidth.snapshot()
+    }
+}
+
+/// Whether `agent` reported `NoWork` and none of its sensed kinds changed since
+fn still_idle(idle: &HashMap<String, u64>, field: &Field, agent: &dyn OsintAgent) -> bool {
+    if agent.wakes_itself() {
+        return false;
+    }
+    idle.get(agent.id())
+        .is_some_and(|&sequence| !field.changed_since(agent.senses(), sequence))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use robin_agents::{create_backend, OpenAIBackendConfig};
+    use robin_agents::{create_backend, OpenAIBackendConfig, DEFAULT_CHUNK_CHARS};
 
     fn mock_backend() -> SharedBackend {
         create_backend(OpenAIBackendConfig {
@@ -295,6 +748,7 @@ mod tests {
             backend: mock_backend(),
             tor_config: TorConfig::default(),
             tick_interval_ms: 500,
+            max_tick_interval_ms: 500,
             max_runtime_secs: 300,
             num_crawlers: 2,
             num_scrapers: 3,
@@ -302,6 +756,26 @@ mod tests {
             enable_enrichment: false,
             enable_blockchain: false,
             enable_pastes: false,
+            paste_scoring: Default::default(),
+            paste_sites: Vec::new(),
+            hash_policy: OriginHashPolicy::default(),
+            signal_policy: SignalPolicy::default(),
+            reinforcement: None,
+            engine_focus: Vec::new(),
+            query_variants: false,
+            max_pages: 1,
+            chunk_chars: DEFAULT_CHUNK_CHARS,
+            follow_links: None,
+            seed_directories: Vec::new(),
+            feeds: Vec::new(),
+            gists: None,
+            actors: ActorDatabase::embedded(),
+            knowledge: None,
+            vectors: None,
+            monitor: None,
+            seen: None,
+            changes: None,
+            translator: None,
         };
 
         let swarm = Swarm::new(config);
@@ -309,6 +783,8 @@ mod tests {
 
         let swarm = swarm.unwrap();
         assert!(swarm.agents.len() >= 5);
+        assert_eq!(swarm.pipeline().stages.len(), swarm.agents.len());
+        assert!(swarm.pipeline().warnings().is_empty(), "{:?}", swarm.pipeline().warnings());
     }
 
     #[test]
@@ -317,6 +793,7 @@ mod tests {
             backend: mock_backend(),
             tor_config: TorConfig::default(),
             tick_interval_ms: 500,
+            max_tick_interval_ms: 500,
             max_runtime_secs: 300,
             num_crawlers: 2,
             num_scrapers: 3,
@@ -324,11 +801,176 @@ mod tests {
             enable_enrichment: false,
             enable_blockchain: false,
             enable_pastes: false,
+            paste_scoring: Default::default(),
+            paste_sites: Vec::new(),
+            hash_policy: OriginHashPolicy::default(),
+            signal_policy: SignalPolicy::default(),
+            reinforcement: None,
+            engine_focus: Vec::new(),
+            query_variants: false,
+            max_pages: 1,
+            chunk_chars: DEFAULT_CHUNK_CHARS,
+            follow_links: None,
+            seed_directories: Vec::new(),
+            feeds: Vec::new(),
+            gists: None,
+            actors: ActorDatabase::embedded(),
+            knowledge: None,
+            vectors: None,
+            monitor: None,
+            seen: None,
+            changes: None,
+            translator: None,
         };
 
         let mut swarm = Swarm::new(config).unwrap();
         let hash = swarm.submit_query("ransomware payments", 0.8);
         assert!(!hash.is_empty());
         assert_eq!(swarm.field.active_count(), 1);
+
+        // A second investigation: the run ends once both have a summary
+        let second = swarm.submit_investigation("carding", "carding forums", 1.0);
+        assert_eq!(swarm.field.get(&second).unwrap().investigation_id.as_deref(), Some("carding"));
+        let summarize = |swarm: &mut Swarm, query: &str, markdown: &str| {
+            let query = swarm.field.sense_kinds(&[PayloadKind::UserQuery], 0.0).into_iter().find(|s| {
+                matches!(&s.payload, OsintPayload::UserQuery { query: q, .. } if q == query)
+            }).unwrap().origin_hash.clone();
+            swarm.field.emit(
+                Signal::builder(OsintPayload::Summary { markdown: markdown.to_string(), artifact_count: 0, source_count: 0 })
+                    .origin("analyst-1")
+                    .parent(&query)
+                    .build(),
+            );
+        };
+        summarize(&mut swarm, "carding forums", "# Carding");
+        assert_eq!(swarm.report(), None);
+        summarize(&mut swarm, "ransomware payments", "# Ransomware");
+        assert_eq!(swarm.report().unwrap(), "# Ransomware\n\n---\n\n# Carding");
+    }
+
+    #[test]
+    fn test_idle_agents_wait_for_input() {
+        let config = SwarmConfig {
+            backend: mock_backend(),
+            tor_config: TorConfig::default(),
+            tick_interval_ms: 500,
+            max_tick_interval_ms: 500,
+            max_runtime_secs: 300,
+            num_crawlers: 1,
+            num_scrapers: 1,
+            use_specialists: false,
+            enable_enrichment: false,
+            enable_blockchain: false,
+            enable_pastes: false,
+            paste_scoring: Default::default(),
+            paste_sites: Vec::new(),
+            hash_policy: OriginHashPolicy::default(),
+            signal_policy: SignalPolicy::default(),
+            reinforcement: None,
+            engine_focus: Vec::new(),
+            query_variants: false,
+            max_pages: 1,
+            chunk_chars: DEFAULT_CHUNK_CHARS,
+            follow_links: None,
+            seed_directories: Vec::new(),
+            feeds: vec!["https://example.com/feed.xml".to_string()],
+            gists: None,
+            actors: ActorDatabase::embedded(),
+            knowledge: None,
+            vectors: None,
+            monitor: None,
+            seen: None,
+            changes: None,
+            translator: None,
+        };
+
+        let mut swarm = Swarm::new(config).unwrap();
+        let sequence = swarm.field.sequence();
+        for agent in &swarm.agents {
+            swarm.idle.insert(agent.id().to_string(), sequence);
+        }
+        let idle = |swarm: &Swarm, id: &str| {
+            let agent = swarm.agents.iter().find(|a| a.id() == id).unwrap();
+            still_idle(&swarm.idle, &swarm.field, agent.as_ref())
+        };
+        assert!(idle(&swarm, "refiner-1"));
+        assert!(idle(&swarm, "crawler-1"));
+        assert!(!idle(&swarm, "feed-watcher-1"));
+
+        swarm.submit_query("lockbit", 1.0);
+        assert!(!idle(&swarm, "refiner-1"));
+        assert!(idle(&swarm, "crawler-1"));
+
+        // A claim held by a scraper that died wakes the scraper when it lapses
+        swarm.field.claim_task("http://a.onion/", "scraper-9", 1.0);
+        let sequence = swarm.field.sequence();
+        swarm.idle.insert("scraper-1".to_string(), sequence);
+        assert!(idle(&swarm, "scraper-1"));
+        swarm.field.tick(robin_core::CLAIM_TTL + 1.0);
+        assert!(!idle(&swarm, "scraper-1"));
+    }
+
+    #[tokio::test]
+    async fn test_control_requests() {
+        let config = SwarmConfig {
+            backend: mock_backend(),
+            tor_config: TorConfig::default(),
+            tick_interval_ms: 500,
+            max_tick_interval_ms: 500,
+            max_runtime_secs: 300,
+            num_crawlers: 1,
+            num_scrapers: 1,
+            use_specialists: false,
+            enable_enrichment: false,
+            enable_blockchain: false,
+            enable_pastes: false,
+            paste_scoring: Default::default(),
+            paste_sites: Vec::new(),
+            hash_policy: OriginHashPolicy::default(),
+            signal_policy: SignalPolicy::default(),
+            reinforcement: None,
+            engine_focus: Vec::new(),
+            query_variants: false,
+            max_pages: 1,
+            chunk_chars: DEFAULT_CHUNK_CHARS,
+            follow_links: None,
+            seed_directories: Vec::new(),
+            feeds: Vec::new(),
+            gists: None,
+            actors: ActorDatabase::embedded(),
+            knowledge: None,
+            vectors: None,
+            monitor: None,
+            seen: None,
+            changes: None,
+            translator: None,
+        };
+
+        let mut swarm = Swarm::new(config).unwrap();
+        let handle = swarm.control_handle();
+        let requests = tokio::spawn(async move {
+            let submit = ControlRequest::Submit {
+                query: "lockbit".to_string(),
+                priority: 1.0,
+                investigation: None,
+            };
+            (
+                handle.request(submit).await.unwrap(),
+                handle.request(ControlRequest::Pause).await.unwrap(),
+                handle.request(ControlRequest::Status).await.unwrap(),
+            )
+        });
+
+        while !requests.is_finished() {
+            swarm.handle_control(Instant::now());
+            tokio::task::yield_now().await;
+        }
+        let (submit, pause, status) = requests.await.unwrap();
+        assert!(submit.ok && !submit.paused);
+        assert!(pause.paused && swarm.is_paused());
+        assert_eq!(status.stats.unwrap().active_signals, 1);
+        assert!(status.activity.is_some());
+        assert!(status.bandwidth.is_some());
+        assert!(status.pipeline.is_some_and(|p| p.stages.iter().any(|s| s.agent_id == "refiner-1")));
     }
 }
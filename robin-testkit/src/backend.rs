@@ -0,0 +1,120 @@
+//! Scripted LLM backend
+//!
+//! Replies come from a queue, in order; once it runs dry every call gets the
+//! fallback reply. Each call's prompts are recorded for assertions.
+
+use async_trait::async_trait;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+
+use robin_agents::{LlmBackend, LlmError, SharedBackend};
+
+/// One recorded backend call
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecordedCall {
+    pub system: String,
+    pub user: String,
+}
+
+#[derive(Debug, Default)]
+struct Script {
+    replies: VecDeque<Result<String, String>>,
+    calls: Vec<RecordedCall>,
+}
+
+/// LLM backend answering from a script
+///
+/// Clones share the script, so a test can keep one handle while the agent
+/// owns another.
+#[derive(Debug, Clone)]
+pub struct ScriptedBackend {
+    script: Arc<Mutex<Script>>,
+    fallback: String,
+}
+
+impl ScriptedBackend {
+    /// A backend with no queued replies (every call gets the fallback)
+    pub fn new() -> Self {
+        Self {
+            script: Arc::default(),
+            fallback: String::new(),
+        }
+    }
+
+    /// Queue a reply
+    pub fn reply(self, reply: impl Into<String>) -> Self {
+        self.script.lock().unwrap().replies.push_back(Ok(reply.into()));
+        self
+    }
+
+    /// Queue a failure (`LlmError::Api` with this message)
+    pub fn fail(self, message: impl Into<String>) -> Self {
+        self.script.lock().unwrap().replies.push_back(Err(message.into()));
+        self
+    }
+
+    /// Reply once the queue is empty (default: empty string)
+    pub fn with_fallback(mut self, reply: impl Into<String>) -> Self {
+        self.fallback = reply.into();
+        self
+    }
+
+    /// This backend as an agent's shared backend
+    pub fn shared(&self) -> SharedBackend {
+        Arc::new(self.clone())
+    }
+
+    /// Calls made so far, oldest first
+    pub fn calls(&self) -> Vec<RecordedCall> {
+        self.script.lock().unwrap().calls.clone()
+    }
+
+    /// Queued replies not yet consumed
+    pub fn remaining(&self) -> usize {
+        self.script.lock().unwrap().replies.len()
+    }
+}
+
+impl Default for ScriptedBackend {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl LlmBackend for ScriptedBackend {
+    async fn generate(&self, system: &str, user: &str) -> Result<String, LlmError> {
+        let mut script = self.script.lock().unwrap();
+        script.calls.push(RecordedCall {
+            system: system.to_string(),
+            user: user.to_string(),
+        });
+        match script.replies.pop_front() {
+            Some(Ok(reply)) => Ok(reply),
+            Some(Err(message)) => Err(LlmError::Api(message)),
+            None => Ok(self.fallback.clone()),
+        }
+    }
+
+    fn model_name(&self) -> &str {
+        "scripted"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_replies_in_order_then_fallback() {
+        let backend = ScriptedBackend::new().reply("first").fail("overloaded").with_fallback("later");
+        let shared = backend.shared();
+
+        assert_eq!(shared.generate("sys", "a").await.unwrap(), "first");
+        assert!(matches!(shared.generate("sys", "b").await, Err(LlmError::Api(m)) if m == "overloaded"));
+        assert_eq!(shared.generate("sys", "c").await.unwrap(), "later");
+        assert_eq!(backend.remaining(), 0);
+        let users: Vec<String> = backend.calls().into_iter().map(|c| c.user).collect();
+        assert_eq!(users, vec!["a", "b", "c"]);
+    }
+}
@@ -0,0 +1,93 @@
+//! Canned signals
+//!
+//! Signals with the payloads agents usually sense, built with the same
+//! defaults the built-in agents use, originating from `"testkit"`.
+
+use robin_core::{Artifact, ArtifactType, Network, OsintPayload, Signal};
+
+/// Origin agent of canned signals
+pub const ORIGIN: &str = "testkit";
+
+fn signal(payload: OsintPayload) -> Signal {
+    Signal::builder(payload).origin(ORIGIN).build()
+}
+
+/// A user query at full priority
+pub fn user_query(query: &str) -> Signal {
+    signal(OsintPayload::UserQuery {
+        query: query.to_string(),
+        priority: 1.0,
+    })
+}
+
+/// A refined query (the original is the refined text)
+pub fn refined_query(refined: &str) -> Signal {
+    signal(OsintPayload::RefinedQuery {
+        original: refined.to_string(),
+        refined: refined.to_string(),
+        confidence: 0.9,
+        raw: None,
+        variants: Vec::new(),
+    })
+}
+
+/// A search engine result, on the network its URL implies
+pub fn raw_result(url: &str, title: &str, engine: &str) -> Signal {
+    signal(OsintPayload::RawResult {
+        url: url.to_string(),
+        title: title.to_string(),
+        engine: engine.to_string(),
+        network: Network::classify(url),
+        query: None,
+    })
+}
+
+/// A result that passed filtering
+pub fn filtered_result(url: &str, title: &str, relevance: f64) -> Signal {
+    signal(OsintPayload::FilteredResult {
+        url: url.to_string(),
+        title: title.to_string(),
+        relevance,
+        reason: "scripted".to_string(),
+    })
+}
+
+/// A scraped page
+pub fn scraped(url: &str, title: &str, text: &str) -> Signal {
+    signal(OsintPayload::ScrapedContent {
+        url: url.to_string(),
+        title: title.to_string(),
+        text: text.to_string(),
+        char_count: text.chars().count(),
+        screenshot: None,
+        language: None,
+        original_text: None,
+        chunk: None,
+        metadata: None,
+    })
+}
+
+/// A paste with no author or date
+pub fn paste(url: &str, site: &str, content: &str) -> Signal {
+    signal(OsintPayload::PasteContent {
+        url: url.to_string(),
+        site: site.to_string(),
+        title: None,
+        content: content.to_string(),
+        created_at: None,
+        author: None,
+        language: None,
+        score: 1.0,
+    })
+}
+
+/// Artifacts found on `source_url`, each tagged with it as source
+pub fn artifacts(source_url: &str, found: &[(ArtifactType, &str)]) -> Signal {
+    signal(OsintPayload::ExtractedArtifacts {
+        source_url: source_url.to_string(),
+        artifacts: found
+            .iter()
+            .map(|(kind, value)| Artifact::new(kind.clone(), value.to_string()).with_source(source_url))
+            .collect(),
+    })
+}
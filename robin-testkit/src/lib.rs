@@ -0,0 +1,27 @@
+//! Robin×SMESH Test Kit
+//!
+//! Deterministic harness for unit-testing agents without Tor or an LLM:
+//! - **Scenarios**: a field scripted tick by tick, run against one agent ([`Scenario`])
+//! - **Canned signals**: one-line constructors for common payloads ([`canned`])
+//! - **Scripted backend**: an [`LlmBackend`](robin_agents::LlmBackend) with queued replies that records prompts
+//! - **Assertions**: helpers over what an agent sensed and emitted ([`ScenarioReport`])
+//!
+//! ```no_run
+//! # async fn demo() {
+//! use robin_agents::{AgentConfig, RefinerAgent};
+//! use robin_core::PayloadKind;
+//! use robin_testkit::{canned, Scenario, ScriptedBackend};
+//!
+//! let backend = ScriptedBackend::new().reply("lockbit leak site");
+//! let mut agent = RefinerAgent::new(AgentConfig::default().with_id("refiner-1"), backend.shared());
+//! let report = Scenario::new().at(0, canned::user_query("lockbit")).run(&mut agent, 3).await;
+//! report.assert_emitted(PayloadKind::RefinedQuery, 1);
+//! # }
+//! ```
+
+pub mod backend;
+pub mod canned;
+pub mod scenario;
+
+pub use backend::*;
+pub use scenario::*;
@@ -0,0 +1,211 @@
+//! Scripted field scenarios
+//!
+//! A [`Scenario`] schedules signals at tick numbers and runs one agent over
+//! a fresh field. Each tick it emits the signals due, lets the agent sense
+//! and process once, then advances field time by a fixed step, so runs are
+//! deterministic (no wall clock, no swarm). Heartbeats are not emitted.
+
+use std::collections::BTreeMap;
+
+use robin_agents::{AgentError, OsintAgent};
+use robin_core::{Field, OsintPayload, PayloadKind, Signal};
+
+/// What one `process` call returned
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TickOutcome {
+    /// Hashes of the signals emitted
+    Emitted(Vec<String>),
+    /// `AgentError::NoWork`
+    NoWork,
+    /// Any other error, as text
+    Failed(String),
+}
+
+/// A scripted sequence of signals
+#[derive(Debug)]
+pub struct Scenario {
+    steps: BTreeMap<u32, Vec<Signal>>,
+    tick_secs: f64,
+    field: Field,
+}
+
+impl Scenario {
+    /// An empty scenario on a fresh field, advancing 0.5 s per tick (the swarm default)
+    pub fn new() -> Self {
+        Self {
+            steps: BTreeMap::new(),
+            tick_secs: 0.5,
+            field: Field::new(),
+        }
+    }
+
+    /// Emit `signal` at the start of tick `tick` (0 = first)
+    pub fn at(mut self, tick: u32, signal: Signal) -> Self {
+        self.steps.entry(tick).or_default().push(signal);
+        self
+    }
+
+    /// Emit every signal in `signals` at the start of tick `tick`
+    pub fn at_all(mut self, tick: u32, signals: impl IntoIterator<Item = Signal>) -> Self {
+        self.steps.entry(tick).or_default().extend(signals);
+        self
+    }
+
+    /// Seconds of field time per tick (lets TTLs and decay play out)
+    pub fn with_tick_secs(mut self, secs: f64) -> Self {
+        self.tick_secs = secs;
+        self
+    }
+
+    /// Start from this field instead of a fresh one (e.g. a custom hash policy)
+    pub fn with_field(mut self, field: Field) -> Self {
+        self.field = field;
+        self
+    }
+
+    /// Run `agent` for `ticks` ticks
+    pub async fn run<A: OsintAgent + ?Sized>(mut self, agent: &mut A, ticks: u32) -> ScenarioReport {
+        let mut report = ScenarioReport::default();
+        for tick in 0..ticks {
+            for signal in self.steps.remove(&tick).unwrap_or_default() {
+                self.field.emit(signal);
+            }
+            report.sensed.push(agent.sense(&self.field).len());
+            let outcome = match agent.process(&mut self.field).await {
+                Ok(hashes) => {
+                    report
+                        .emitted
+                        .extend(hashes.iter().filter_map(|h| self.field.get(h)).cloned());
+                    TickOutcome::Emitted(hashes)
+                }
+                Err(AgentError::NoWork) => TickOutcome::NoWork,
+                Err(e) => TickOutcome::Failed(e.to_string()),
+            };
+            report.outcomes.push(outcome);
+            self.field.tick(self.tick_secs);
+        }
+        report.field = self.field;
+        report
+    }
+}
+
+impl Default for Scenario {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// What happened during a scenario run
+#[derive(Debug, Default)]
+pub struct ScenarioReport {
+    /// The field after the last tick
+    pub field: Field,
+    /// One outcome per tick
+    pub outcomes: Vec<TickOutcome>,
+    /// Signals the agent sensed at each tick
+    pub sensed: Vec<usize>,
+    /// Signals the agent emitted, in order (as they were right after emission)
+    pub emitted: Vec<Signal>,
+}
+
+impl ScenarioReport {
+    /// Payloads the agent emitted of `kind`
+    pub fn emitted_of(&self, kind: PayloadKind) -> Vec<&OsintPayload> {
+        self.emitted
+            .iter()
+            .map(|s| &s.payload)
+            .filter(|p| p.kind() == kind)
+            .collect()
+    }
+
+    /// Error messages of failed ticks
+    pub fn errors(&self) -> Vec<&str> {
+        self.outcomes
+            .iter()
+            .filter_map(|o| match o {
+                TickOutcome::Failed(e) => Some(e.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Panic unless exactly `count` signals of `kind` were emitted
+    pub fn assert_emitted(&self, kind: PayloadKind, count: usize) {
+        let found = self.emitted_of(kind).len();
+        assert_eq!(
+            found,
+            count,
+            "expected {} {} signal(s), agent emitted {}; all emitted kinds: {:?}",
+            count,
+            kind,
+            found,
+            self.emitted.iter().map(|s| s.payload.kind()).collect::<Vec<_>>()
+        );
+    }
+
+    /// Panic unless the agent sensed `count` signals at `tick`
+    pub fn assert_sensed(&self, tick: u32, count: usize) {
+        assert_eq!(self.sensed.get(tick as usize), Some(&count), "signals sensed per tick: {:?}", self.sensed);
+    }
+
+    /// Panic unless every tick from `tick` on returned `NoWork`
+    pub fn assert_idle_from(&self, tick: u32) {
+        for (i, outcome) in self.outcomes.iter().enumerate().skip(tick as usize) {
+            assert_eq!(outcome, &TickOutcome::NoWork, "tick {} was not idle", i);
+        }
+    }
+
+    /// Panic if any tick failed
+    pub fn assert_no_errors(&self) {
+        assert!(self.errors().is_empty(), "agent failed: {:?}", self.errors());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{canned, ScriptedBackend};
+    use robin_agents::{AgentConfig, ExtractorAgent, RefinerAgent};
+    use robin_core::ArtifactType;
+
+    #[tokio::test]
+    async fn test_refiner_scenario() {
+        let backend = ScriptedBackend::new().reply("lockbit leak site").fail("rate limited");
+        let mut agent = RefinerAgent::new(AgentConfig::default().with_id("refiner-1"), backend.shared());
+
+        let report = Scenario::new()
+            .at(0, canned::user_query("lockbit"))
+            .at(3, canned::user_query("conti"))
+            .run(&mut agent, 4)
+            .await;
+
+        report.assert_emitted(PayloadKind::RefinedQuery, 1);
+        assert!(matches!(report.emitted_of(PayloadKind::RefinedQuery)[0],
+            OsintPayload::RefinedQuery { refined, .. } if refined == "lockbit leak site"));
+        report.assert_sensed(0, 1);
+        assert_eq!(report.outcomes[1..3], [TickOutcome::NoWork, TickOutcome::NoWork]);
+        assert_eq!(report.errors(), vec!["LLM error: API error: rate limited"]);
+        assert_eq!(backend.calls()[1].user, "conti");
+    }
+
+    #[tokio::test]
+    async fn test_extractor_scenario() {
+        let mut agent = ExtractorAgent::new(AgentConfig::default().with_id("extractor-1"));
+        let page = "Send 0.5 BTC to bc1qxy2kgdygjrsqtzq2n0yrf2493p83kkfjhx0wlh or mail leaks@example.com";
+
+        let report = Scenario::new()
+            .at(0, canned::scraped("http://leak.onion/", "Leak", page))
+            .run(&mut agent, 3)
+            .await;
+
+        report.assert_no_errors();
+        report.assert_emitted(PayloadKind::ExtractedArtifacts, 1);
+        report.assert_idle_from(1);
+        let OsintPayload::ExtractedArtifacts { artifacts, .. } = report.emitted_of(PayloadKind::ExtractedArtifacts)[0]
+        else {
+            unreachable!()
+        };
+        assert!(artifacts.iter().any(|a| a.artifact_type == ArtifactType::Bitcoin));
+        assert!(artifacts.iter().all(|a| a.source.as_deref() == Some("http://leak.onion/")));
+    }
+}
@@ -0,0 +1,317 @@
+//! Site-specific page adapters
+//!
+//! Generic extraction flattens a page into one run of text. For well-known
+//! layouts (Dread-style forum threads, ransomware leak site indexes) an
+//! adapter instead pulls out each post with its author and timestamp. The
+//! scraper picks the first adapter whose URL pattern matches and that finds
+//! posts, and falls back to [`crate::extract_content`] otherwise.
+
+use scraper::{ElementRef, Html, Selector};
+use std::fmt;
+use std::sync::Arc;
+
+/// One post, reply or listing entry on a structured page
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SitePost {
+    /// Username of the poster (None on leak sites)
+    pub author: Option<String>,
+    /// When it was posted, as the site shows it (`datetime` attribute preferred)
+    pub timestamp: Option<String>,
+    /// Post or victim title
+    pub title: Option<String>,
+    /// Post body or listing description
+    pub body: String,
+}
+
+/// A page parsed by a [`SiteAdapter`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StructuredPage {
+    /// Name of the adapter that parsed it
+    pub adapter: String,
+    /// Contents of `<title>`
+    pub title: Option<String>,
+    /// Thread subject or index heading
+    pub heading: Option<String>,
+    pub posts: Vec<SitePost>,
+}
+
+impl StructuredPage {
+    /// Plain text with one line per post, keeping author and timestamp
+    ///
+    /// ```text
+    /// Thread: Selling RDP access
+    /// - alice (2024-05-01T10:00:00Z): Fresh batch, escrow only
+    /// ```
+    pub fn render(&self) -> String {
+        let mut lines = Vec::with_capacity(self.posts.len() + 1);
+        if let Some(heading) = &self.heading {
+            lines.push(format!("Thread: {}", heading));
+        }
+        for post in &self.posts {
+            let who = post
+                .author
+                .as_deref()
+                .or(post.title.as_deref())
+                .unwrap_or("unknown");
+            let mut line = format!("- {}", who);
+            if let Some(timestamp) = &post.timestamp {
+                line.push_str(&format!(" ({})", timestamp));
+            }
+            line.push(':');
+            if let (Some(_), Some(title)) = (&post.author, &post.title) {
+                line.push_str(&format!(" {} -", title));
+            }
+            if !post.body.is_empty() {
+                line.push(' ');
+                line.push_str(&post.body);
+            }
+            lines.push(line);
+        }
+        lines.join("\n")
+    }
+}
+
+/// Structured parser for one family of sites
+pub trait SiteAdapter: fmt::Debug + Send + Sync {
+    /// Short name, recorded on parsed pages
+    fn name(&self) -> &str;
+    /// Whether this adapter handles `url`
+    fn matches(&self, url: &str) -> bool;
+    /// Parse `html`; None when the page doesn't have the expected layout
+    fn parse(&self, html: &str) -> Option<StructuredPage>;
+}
+
+/// Adapters tried in order by the scraper
+#[derive(Debug, Clone)]
+pub struct SiteAdapters {
+    adapters: Vec<Arc<dyn SiteAdapter>>,
+}
+
+impl SiteAdapters {
+    /// The built-in forum and leak site adapters
+    pub fn builtin() -> Self {
+        Self {
+            adapters: vec![Arc::new(ForumAdapter), Arc::new(LeakSiteAdapter)],
+        }
+    }
+
+    /// No adapters (always generic extraction)
+    pub fn none() -> Self {
+        Self { adapters: Vec::new() }
+    }
+
+    /// Add an adapter, tried before the ones already present
+    pub fn with_adapter(mut self, adapter: impl SiteAdapter + 'static) -> Self {
+        self.adapters.insert(0, Arc::new(adapter));
+        self
+    }
+
+    /// Adapters whose URL pattern matches `url`, in order
+    pub fn for_url<'a>(&'a self, url: &'a str) -> impl Iterator<Item = &'a dyn SiteAdapter> + 'a {
+        self.adapters.iter().map(|a| a.as_ref()).filter(move |a| a.matches(url))
+    }
+
+    /// First structured parse of `html` by an adapter matching `url`
+    pub fn parse(&self, url: &str, html: &str) -> Option<StructuredPage> {
+        self.for_url(url).find_map(|adapter| adapter.parse(html))
+    }
+
+    pub fn len(&self) -> usize {
+        self.adapters.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.adapters.is_empty()
+    }
+}
+
+impl Default for SiteAdapters {
+    fn default() -> Self {
+        Self::builtin()
+    }
+}
+
+/// Dread-style forum threads: `/post/`, `/d/` and `/thread/` paths
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ForumAdapter;
+
+impl SiteAdapter for ForumAdapter {
+    fn name(&self) -> &str {
+        "forum"
+    }
+
+    fn matches(&self, url: &str) -> bool {
+        let path = url_path(url);
+        ["/post/", "/d/", "/thread/", "/threads/", "/topic/"]
+            .iter()
+            .any(|p| path.starts_with(p))
+    }
+
+    fn parse(&self, html: &str) -> Option<StructuredPage> {
+        parse_blocks(
+            self.name(),
+            html,
+            &Layout {
+                heading: "h1, .post-title, .postTitle, .thread-title",
+                block: ".post, .comment, .message, .reply",
+                title: ".title, .postTitle",
+                author: ".author, .username, .user, .poster",
+                timestamp: ".timestamp, .date, .time",
+                body: ".content, .body, .postContent, .comment-body, .message-body",
+            },
+        )
+    }
+}
+
+/// Ransomware data leak site indexes: `/`, `/leaks`, `/victims`, `/blog`
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LeakSiteAdapter;
+
+impl SiteAdapter for LeakSiteAdapter {
+    fn name(&self) -> &str {
+        "leak-site"
+    }
+
+    fn matches(&self, url: &str) -> bool {
+        let path = url_path(url);
+        url.contains(".onion")
+            && (path == "/"
+                || ["/leaks", "/victims", "/blog", "/news", "/companies"]
+                    .iter()
+                    .any(|p| path.starts_with(p)))
+    }
+
+    fn parse(&self, html: &str) -> Option<StructuredPage> {
+        parse_blocks(
+            self.name(),
+            html,
+            &Layout {
+                heading: "h1",
+                block: ".post-block, .victim, .leak, .leak-card, .company",
+                title: ".post-title, .victim-name, .company-name, .title, h2, h3",
+                author: ".author",
+                timestamp: ".post-timer, .date, .timer, .updated, .deadline",
+                body: ".post-block-text, .desc, .description, .text, p",
+            },
+        )
+    }
+}
+
+/// CSS selectors (comma-separated alternatives) for one page layout
+struct Layout {
+    heading: &'static str,
+    block: &'static str,
+    title: &'static str,
+    author: &'static str,
+    timestamp: &'static str,
+    body: &'static str,
+}
+
+fn parse_blocks(adapter: &str, html: &str, layout: &Layout) -> Option<StructuredPage> {
+    let selector = |s: &str| Selector::parse(s).expect("built-in adapter selector");
+    let document = Html::parse_document(html);
+    let block = selector(layout.block);
+    let title = selector(layout.title);
+    let author = selector(layout.author);
+    let timestamp = selector(layout.timestamp);
+    let body = selector(layout.body);
+    let time = selector("time");
+
+    let posts: Vec<SitePost> = document
+        .select(&block)
+        // Skip wrappers whose own children are blocks (e.g. a comment tree)
+        .filter(|el| el.select(&block).next().is_none())
+        .map(|el| SitePost {
+            author: first_text(el, &author),
+            timestamp: el
+                .select(&time)
+                .next()
+                .and_then(|t| t.value().attr("datetime").map(str::to_string))
+                .or_else(|| first_text(el, &time))
+                .or_else(|| first_text(el, &timestamp)),
+            title: first_text(el, &title),
+            body: first_text(el, &body).unwrap_or_default(),
+        })
+        .filter(|post| !post.body.is_empty() || post.title.is_some())
+        .collect();
+
+    if posts.is_empty() {
+        return None;
+    }
+
+    Some(StructuredPage {
+        adapter: adapter.to_string(),
+        title: first_text(document.root_element(), &selector("title")),
+        heading: first_text(document.root_element(), &selector(layout.heading)),
+        posts,
+    })
+}
+
+fn first_text(el: ElementRef<'_>, selector: &Selector) -> Option<String> {
+    el.select(selector)
+        .next()
+        .map(|found| found.text().collect::<Vec<_>>().join(" "))
+        .map(|text| text.split_whitespace().collect::<Vec<_>>().join(" "))
+        .filter(|text| !text.is_empty())
+}
+
+fn url_path(url: &str) -> String {
+    reqwest::Url::parse(url)
+        .map(|u| u.path().to_string())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DREAD: &str = "http://dreadytofatroptsdj6io7l3xptbet6onoyno2yv7jicoxknyazubrad.onion";
+
+    #[test]
+    fn test_forum_thread_keeps_authors_and_timestamps() {
+        let html = r#"<html><head><title>Dread</title></head><body>
+            <h1>Selling RDP access</h1>
+            <div class="post"><span class="author">alice</span>
+                <time datetime="2024-05-01T10:00:00Z">1 day ago</time>
+                <div class="content">Fresh batch, escrow only</div></div>
+            <div class="comment"><a class="username">bob</a><span class="date">2024-05-02</span>
+                <div class="comment-body">Vouch</div></div>
+        </body></html>"#;
+
+        let page = SiteAdapters::builtin()
+            .parse(&format!("{}/post/abc123", DREAD), html)
+            .unwrap();
+
+        assert_eq!(page.adapter, "forum");
+        assert_eq!(page.heading.as_deref(), Some("Selling RDP access"));
+        assert_eq!(page.posts.len(), 2);
+        assert_eq!(page.posts[1].timestamp.as_deref(), Some("2024-05-02"));
+        assert_eq!(
+            page.render(),
+            "Thread: Selling RDP access\n- alice (2024-05-01T10:00:00Z): Fresh batch, escrow only\n- bob (2024-05-02): Vouch"
+        );
+    }
+
+    #[test]
+    fn test_leak_site_index_lists_victims() {
+        let html = r#"<body><div class="post-block">
+            <div class="post-title">Acme Corp</div>
+            <div class="post-block-text">500 GB of contracts</div>
+            <div class="post-timer">2024-06-01 12:00</div></div></body>"#;
+
+        let page = SiteAdapters::builtin().parse(&format!("{}/", DREAD), html).unwrap();
+
+        assert_eq!(page.adapter, "leak-site");
+        assert_eq!(page.render(), "- Acme Corp (2024-06-01 12:00): 500 GB of contracts");
+    }
+
+    #[test]
+    fn test_unmatched_or_unstructured_pages_fall_through() {
+        let adapters = SiteAdapters::builtin();
+        let plain = "<body><p>Nothing structured here</p></body>";
+
+        assert_eq!(adapters.for_url(&format!("{}/search?q=x", DREAD)).count(), 0);
+        assert!(adapters.parse(&format!("{}/post/abc", DREAD), plain).is_none());
+        assert!(SiteAdapters::none().parse(&format!("{}/", DREAD), plain).is_none());
+    }
+}
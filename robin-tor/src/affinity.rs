@@ -0,0 +1,107 @@
+//! Per-host session affinity
+//!
+//! Login-gated hidden services often tie a session to the circuit it was
+//! opened on and drop it when a request arrives over another one. With
+//! [`crate::TorConfig::affinity`] set, every request to a host carries the
+//! same SOCKS username and password, which Tor and arti use as a stream
+//! isolation key: pages of one host share a circuit, and different hosts
+//! never do. With a proxy pool, a host also stays on the endpoint it was
+//! first sent through for as long as that endpoint is healthy.
+//!
+//! The credentials include a per-run token, so separate runs don't share
+//! circuits either.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use crate::{ProxyLease, ProxyPool, TorError};
+
+/// Shared per-host circuit isolation and proxy pinning
+///
+/// Cloning is cheap and all clones share the same pins.
+#[derive(Debug, Clone)]
+pub struct SessionAffinity {
+    token: String,
+    /// Pool endpoint each host was last sent through
+    pins: Arc<Mutex<HashMap<String, String>>>,
+}
+
+impl Default for SessionAffinity {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SessionAffinity {
+    /// Affinity with a fresh run token
+    pub fn new() -> Self {
+        let token = uuid::Uuid::new_v4().simple().to_string();
+        Self {
+            token: token[..12].to_string(),
+            pins: Arc::default(),
+        }
+    }
+
+    /// SOCKS username and password isolating `host`'s streams
+    pub fn isolation(&self, host: &str) -> (String, String) {
+        (format!("robin-{}", self.token), host.to_string())
+    }
+
+    /// `proxy_url` carrying `host`'s isolation credentials
+    ///
+    /// Only SOCKS5 proxies take a password; others are returned unchanged.
+    pub fn isolate(&self, proxy_url: &str, host: &str) -> Result<String, TorError> {
+        let mut url = reqwest::Url::parse(proxy_url)
+            .map_err(|e| TorError::ClientBuild(format!("invalid proxy '{}': {}", proxy_url, e)))?;
+        if !matches!(url.scheme(), "socks5" | "socks5h") {
+            return Ok(proxy_url.to_string());
+        }
+        let (username, password) = self.isolation(host);
+        url.set_username(&username)
+            .and_then(|_| url.set_password(Some(&password)))
+            .map_err(|_| TorError::ClientBuild(format!("proxy '{}' cannot carry credentials", proxy_url)))?;
+        Ok(url.as_str().trim_end_matches('/').to_string())
+    }
+
+    /// Lease `host`'s pinned endpoint from `pool`, pinning a new one if it has none or it is down
+    pub fn lease(&self, pool: &ProxyPool, host: &str) -> ProxyLease {
+        let mut pins = self.pins.lock().unwrap();
+        let lease = pool.acquire_preferring(pins.get(host).map(String::as_str));
+        pins.insert(host.to_string(), lease.addr().to_string());
+        lease
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_isolates_per_host_and_pins_endpoints() {
+        let affinity = SessionAffinity::new();
+        let proxy = "socks5h://127.0.0.1:9050";
+        let forum = affinity.isolate(proxy, "forum.onion").unwrap();
+        assert!(forum.starts_with("socks5h://robin-"));
+        assert!(forum.ends_with(":forum.onion@127.0.0.1:9050"));
+        assert_eq!(affinity.clone().isolate(proxy, "forum.onion").unwrap(), forum);
+        assert_ne!(affinity.isolate(proxy, "market.onion").unwrap(), forum);
+        assert_ne!(SessionAffinity::new().isolate(proxy, "forum.onion").unwrap(), forum);
+        assert_eq!(affinity.isolate("http://127.0.0.1:4444", "x.i2p").unwrap(), "http://127.0.0.1:4444");
+        reqwest::Proxy::all(forum).unwrap();
+
+        let pool = ProxyPool::new(["socks5h://127.0.0.1:9050", "socks5h://127.0.0.1:9052"])
+            .unwrap()
+            .with_failure_threshold(1);
+        let first = affinity.lease(&pool, "forum.onion").addr().to_string();
+        for _ in 0..3 {
+            assert_eq!(affinity.lease(&pool, "forum.onion").addr(), first);
+        }
+
+        // A host moves only when its endpoint goes down
+        pool.record_probe(&first, false);
+        let moved = affinity.lease(&pool, "forum.onion").addr().to_string();
+        assert_ne!(moved, first);
+        pool.record_probe(&first, true);
+        assert_eq!(affinity.lease(&pool, "forum.onion").addr(), moved);
+    }
+}
@@ -0,0 +1,631 @@
+//! Opsec self-check of the proxy setup
+//!
+//! The leak guard refuses configurations it knows are unsafe; the audit
+//! checks what actually happens. It stands up a recording proxy on
+//! localhost in place of each configured Tor endpoint (keeping the
+//! endpoint's scheme) and sends requests through clients built exactly as
+//! the crawlers build them. The recorder sees whether a hostname arrived
+//! for Tor to resolve or an address the system resolver had already
+//! looked up; a separate local sink catches connections that bypassed the
+//! proxy. An upstream proxy is replaced by a local stand-in too, and the
+//! screenshot browser's flags are checked. Nothing leaves the machine, so
+//! the audit runs without Tor.
+
+use serde::Serialize;
+use std::net::IpAddr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::task::JoinHandle;
+
+use crate::{
+    browser_args, ScreenshotConfig, browser_proxy, create_tor_client_for, TlsPolicy, TorConfig, TorError, UpstreamKind, UpstreamProxy,
+};
+
+/// Onion the audit requests (never contacted: the recorder answers for it)
+pub const AUDIT_ONION: &str = "2gzyxa5ihm7nsggfxnu52rck2vv4rvmdlkiu3zzui5du4xyclen53wid.onion";
+
+/// Give up on an audit request after this long
+const AUDIT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Largest handshake or request header the recorder reads
+const MAX_HEADER: usize = 16 * 1024;
+
+const CANNED_RESPONSE: &[u8] = b"HTTP/1.1 200 OK\r\nContent-Length: 5\r\nConnection: close\r\n\r\naudit";
+
+/// Verdict of one audit check
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditOutcome {
+    Pass,
+    /// Not a leak, but traffic or a setting the operator should know about
+    Flag,
+    Fail,
+}
+
+/// One audit check and what it found
+#[derive(Debug, Clone, Serialize)]
+pub struct AuditCheck {
+    pub name: String,
+    pub outcome: AuditOutcome,
+    pub detail: String,
+}
+
+/// All checks of an audit
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct AuditReport {
+    pub checks: Vec<AuditCheck>,
+}
+
+impl AuditReport {
+    pub fn push(&mut self, name: impl Into<String>, outcome: AuditOutcome, detail: impl Into<String>) {
+        self.checks.push(AuditCheck {
+            name: name.into(),
+            outcome,
+            detail: detail.into(),
+        });
+    }
+
+    /// Note clearnet traffic that doesn't go through Tor
+    pub fn flag(&mut self, name: impl Into<String>, detail: impl Into<String>) {
+        self.push(name, AuditOutcome::Flag, detail);
+    }
+
+    /// Checks with the given outcome
+    pub fn count(&self, outcome: AuditOutcome) -> usize {
+        self.checks.iter().filter(|c| c.outcome == outcome).count()
+    }
+
+    /// No check failed
+    pub fn passed(&self) -> bool {
+        self.count(AuditOutcome::Fail) == 0
+    }
+}
+
+/// A connection request the recording proxy received
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ProxyConnect {
+    /// Destination as the client sent it
+    host: String,
+}
+
+impl ProxyConnect {
+    /// The client resolved the destination itself and sent an address
+    fn resolved_locally(&self) -> bool {
+        self.host.parse::<IpAddr>().is_ok()
+    }
+}
+
+/// Local listener that records what it is asked to connect to and answers itself
+struct Recorder {
+    addr: String,
+    connects: Arc<Mutex<Vec<ProxyConnect>>>,
+    task: JoinHandle<()>,
+}
+
+impl Recorder {
+    async fn start() -> Result<Self, TorError> {
+        let listener = TcpListener::bind("127.0.0.1:0").await.map_err(harness_error)?;
+        let addr = listener.local_addr().map_err(harness_error)?.to_string();
+        let connects = Arc::new(Mutex::new(Vec::new()));
+        let recorded = connects.clone();
+        let task = tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let recorded = recorded.clone();
+                tokio::spawn(async move {
+                    let _ = serve_proxy(stream, &recorded).await;
+                });
+            }
+        });
+        Ok(Self { addr, connects, task })
+    }
+
+    fn take(&self) -> Vec<ProxyConnect> {
+        std::mem::take(&mut *self.connects.lock().unwrap())
+    }
+}
+
+impl Drop for Recorder {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+/// Local listener counting connections made to it directly
+struct Sink {
+    port: u16,
+    hits: Arc<Mutex<usize>>,
+    task: JoinHandle<()>,
+}
+
+impl Sink {
+    async fn start() -> Result<Self, TorError> {
+        let listener = TcpListener::bind("127.0.0.1:0").await.map_err(harness_error)?;
+        let port = listener.local_addr().map_err(harness_error)?.port();
+        let hits = Arc::new(Mutex::new(0));
+        let counted = hits.clone();
+        let task = tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                *counted.lock().unwrap() += 1;
+                tokio::spawn(async move {
+                    let _ = read_header(&mut stream).await;
+                    let _ = stream.write_all(CANNED_RESPONSE).await;
+                });
+            }
+        });
+        Ok(Self { port, hits, task })
+    }
+
+    fn hits(&self) -> usize {
+        *self.hits.lock().unwrap()
+    }
+}
+
+impl Drop for Sink {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+/// Local stand-in for the upstream proxy: records what it is asked to tunnel to and tunnels there
+struct Upstream {
+    proxy: UpstreamProxy,
+    targets: Arc<Mutex<Vec<String>>>,
+    task: JoinHandle<()>,
+}
+
+impl Upstream {
+    async fn start(kind: UpstreamKind) -> Result<Self, TorError> {
+        let listener = TcpListener::bind("127.0.0.1:0").await.map_err(harness_error)?;
+        let addr = listener.local_addr().map_err(harness_error)?;
+        let scheme = match kind {
+            UpstreamKind::Http => "http",
+            UpstreamKind::Socks5 => "socks5",
+        };
+        let proxy = format!("{}://{}", scheme, addr).parse().map_err(TorError::Audit)?;
+        let targets = Arc::new(Mutex::new(Vec::new()));
+        let recorded = targets.clone();
+        let task = tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let recorded = recorded.clone();
+                tokio::spawn(async move {
+                    let _ = serve_upstream(stream, kind, &recorded).await;
+                });
+            }
+        });
+        Ok(Self { proxy, targets, task })
+    }
+
+    fn take(&self) -> Vec<String> {
+        std::mem::take(&mut *self.targets.lock().unwrap())
+    }
+}
+
+impl Drop for Upstream {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+fn harness_error(e: std::io::Error) -> TorError {
+    TorError::Audit(e.to_string())
+}
+
+/// Speak SOCKS5, SOCKS4(a) or HTTP proxy to one client and record the destination it asks for
+///
+/// The destination is recorded before the client gets its answer, so the
+/// audit never looks before the recorder has.
+async fn serve_proxy(mut stream: TcpStream, recorded: &Mutex<Vec<ProxyConnect>>) -> std::io::Result<()> {
+    let version = stream.read_u8().await?;
+    let host = match version {
+        5 => {
+            let Some((host, _)) = read_socks5_connect(&mut stream).await? else {
+                return Ok(());
+            };
+            stream.write_all(&[5, 0, 0, 1, 0, 0, 0, 0, 0, 0]).await?;
+            read_header(&mut stream).await?;
+            host
+        }
+        4 => {
+            let mut request = [0u8; 7];
+            stream.read_exact(&mut request).await?;
+            read_until_nul(&mut stream).await?;
+            let ip = [request[3], request[4], request[5], request[6]];
+            // 0.0.0.x marks SOCKS4a: the hostname follows the user ID
+            // (some clients send an extra NUL before it)
+            let host = if ip[..3] == [0, 0, 0] && ip[3] != 0 {
+                match read_until_nul(&mut stream).await? {
+                    empty if empty.is_empty() => read_until_nul(&mut stream).await?,
+                    host => host,
+                }
+            } else {
+                IpAddr::from(ip).to_string()
+            };
+            stream.write_all(&[0, 0x5a, 0, 0, 0, 0, 0, 0]).await?;
+            read_header(&mut stream).await?;
+            host
+        }
+        first => {
+            let mut header = vec![first];
+            header.extend(read_header(&mut stream).await?);
+            let header = String::from_utf8_lossy(&header);
+            let target = header.split_whitespace().nth(1).unwrap_or_default();
+            let authority = target.split_once("://").map_or(target, |(_, rest)| rest);
+            let authority = authority.split('/').next().unwrap_or_default();
+            let host = authority.rsplit_once(':').map_or(authority, |(host, _)| host);
+            host.trim_matches(['[', ']']).to_string()
+        }
+    };
+    recorded.lock().unwrap().push(ProxyConnect { host });
+    stream.write_all(CANNED_RESPONSE).await
+}
+
+/// Speak `kind` of upstream proxy to one client: record the target it asks for, then tunnel to it
+async fn serve_upstream(mut stream: TcpStream, kind: UpstreamKind, recorded: &Mutex<Vec<String>>) -> std::io::Result<()> {
+    let target = match kind {
+        UpstreamKind::Socks5 => {
+            if stream.read_u8().await? != 5 {
+                return Ok(());
+            }
+            let Some((host, port)) = read_socks5_connect(&mut stream).await? else {
+                return Ok(());
+            };
+            format!("{}:{}", host, port)
+        }
+        UpstreamKind::Http => {
+            let header = read_header(&mut stream).await?;
+            let header = String::from_utf8_lossy(&header);
+            header.split_whitespace().nth(1).unwrap_or_default().to_string()
+        }
+    };
+    recorded.lock().unwrap().push(target.clone());
+    let mut tunnel = TcpStream::connect(&target).await?;
+    match kind {
+        UpstreamKind::Socks5 => stream.write_all(&[5, 0, 0, 1, 0, 0, 0, 0, 0, 0]).await?,
+        UpstreamKind::Http => stream.write_all(b"HTTP/1.1 200 Connection established\r\n\r\n").await?,
+    }
+    tokio::io::copy_bidirectional(&mut stream, &mut tunnel).await?;
+    Ok(())
+}
+
+/// Rest of a SOCKS5 greeting (after the version byte) and the CONNECT request: its host and port
+async fn read_socks5_connect(stream: &mut TcpStream) -> std::io::Result<Option<(String, u16)>> {
+    let methods = stream.read_u8().await?;
+    let mut skip = vec![0u8; methods as usize];
+    stream.read_exact(&mut skip).await?;
+    stream.write_all(&[5, 0]).await?;
+    let mut request = [0u8; 4];
+    stream.read_exact(&mut request).await?;
+    let host = match request[3] {
+        1 => {
+            let mut ip = [0u8; 4];
+            stream.read_exact(&mut ip).await?;
+            IpAddr::from(ip).to_string()
+        }
+        4 => {
+            let mut ip = [0u8; 16];
+            stream.read_exact(&mut ip).await?;
+            IpAddr::from(ip).to_string()
+        }
+        3 => {
+            let len = stream.read_u8().await?;
+            let mut name = vec![0u8; len as usize];
+            stream.read_exact(&mut name).await?;
+            String::from_utf8_lossy(&name).into_owned()
+        }
+        _ => return Ok(None),
+    };
+    let port = stream.read_u16().await?;
+    Ok(Some((host, port)))
+}
+
+/// Bytes up to and including the blank line ending an HTTP header
+async fn read_header<S: AsyncRead + Unpin>(stream: &mut S) -> std::io::Result<Vec<u8>> {
+    let mut header = Vec::new();
+    while !header.ends_with(b"\r\n\r\n") && header.len() < MAX_HEADER {
+        header.push(stream.read_u8().await?);
+    }
+    Ok(header)
+}
+
+async fn read_until_nul<S: AsyncRead + Unpin>(stream: &mut S) -> std::io::Result<String> {
+    let mut bytes = Vec::new();
+    loop {
+        match stream.read_u8().await? {
+            0 => return Ok(String::from_utf8_lossy(&bytes).into_owned()),
+            byte if bytes.len() < MAX_HEADER => bytes.push(byte),
+            _ => return Ok(String::from_utf8_lossy(&bytes).into_owned()),
+        }
+    }
+}
+
+/// `config` with `proxy`'s scheme pointed at the recorder, reached through `upstream` if given
+fn harness_config(proxy: &str, recorder: &Recorder, upstream: Option<&Upstream>, config: &TorConfig) -> TorConfig {
+    let scheme = proxy.split_once("://").map_or("socks5h", |(scheme, _)| scheme);
+    TorConfig {
+        socks_addr: format!("{}://{}", scheme, recorder.addr),
+        proxy_pool: None,
+        upstream_proxy: upstream.map(|u| u.proxy.clone()),
+        timeout_secs: AUDIT_TIMEOUT.as_secs(),
+        ..config.clone()
+    }
+}
+
+/// GET `url` with a client built for it; the error text if it fails
+async fn probe(url: &str, config: &TorConfig) -> Result<Result<(), String>, TorError> {
+    let client = create_tor_client_for(url, config)?;
+    Ok(match tokio::time::timeout(AUDIT_TIMEOUT, client.get(url).send()).await {
+        Ok(Ok(_)) => Ok(()),
+        Ok(Err(e)) => Err(e.to_string()),
+        Err(_) => Err("timed out".to_string()),
+    })
+}
+
+/// Verdict on an onion request, from what reached the proxy
+fn judge_onion(host: &str, built: &Result<Result<(), String>, TorError>, connects: &[ProxyConnect]) -> (AuditOutcome, String) {
+    if let Err(e) = built {
+        return match e {
+            TorError::Leak(reason) => (AuditOutcome::Pass, format!("refused by the leak guard: {}", reason)),
+            other => (AuditOutcome::Fail, format!("client could not be built: {}", other)),
+        };
+    }
+    match connects.first() {
+        Some(c) if c.host == host => (AuditOutcome::Pass, "hostname handed to the proxy for Tor to resolve".to_string()),
+        Some(c) if c.resolved_locally() => (
+            AuditOutcome::Fail,
+            format!("resolved locally to {} before reaching the proxy (DNS leak)", c.host),
+        ),
+        Some(c) => (AuditOutcome::Fail, format!("proxy was asked for {} instead", c.host)),
+        None => {
+            let error = built.as_ref().ok().and_then(|r| r.as_ref().err()).cloned().unwrap_or_default();
+            (
+                AuditOutcome::Fail,
+                format!("request never reached the proxy, so it was resolved or sent outside Tor ({})", error),
+            )
+        }
+    }
+}
+
+/// Verdict on a clearnet request, from what reached the proxy and the sink
+fn judge_clearnet(
+    built: &Result<Result<(), String>, TorError>,
+    connects: &[ProxyConnect],
+    direct: usize,
+) -> (AuditOutcome, String) {
+    if direct > 0 {
+        return (AuditOutcome::Fail, "connected to the destination directly, bypassing the proxy".to_string());
+    }
+    match (built, connects.first()) {
+        (Err(TorError::Leak(reason)), _) => (AuditOutcome::Pass, format!("refused by the leak guard: {}", reason)),
+        (Err(other), _) => (AuditOutcome::Fail, format!("client could not be built: {}", other)),
+        (Ok(_), Some(c)) if c.resolved_locally() => (
+            AuditOutcome::Flag,
+            format!("hostname resolved by the system resolver ({}); use socks5h:// to resolve through Tor", c.host),
+        ),
+        (Ok(_), Some(_)) => (AuditOutcome::Pass, "sent through the proxy with remote DNS".to_string()),
+        (Ok(result), None) => (
+            AuditOutcome::Fail,
+            format!("request never reached the proxy ({})", result.as_ref().err().cloned().unwrap_or_default()),
+        ),
+    }
+}
+
+/// Verdict on the upstream proxy, from what it was asked to tunnel to and how many requests reached the Tor endpoint
+fn judge_upstream(endpoint: &str, targets: &[String], reached: usize) -> (AuditOutcome, String) {
+    if let Some(other) = targets.iter().find(|t| t.as_str() != endpoint) {
+        return (AuditOutcome::Fail, format!("asked to tunnel to {} instead of the Tor endpoint", other));
+    }
+    if targets.len() < reached {
+        return (AuditOutcome::Fail, "requests reached the Tor endpoint without going through it".to_string());
+    }
+    (AuditOutcome::Pass, "only asked to tunnel to the Tor endpoint".to_string())
+}
+
+/// Verdict on the screenshot browser's flags: its proxy, local DNS and WebRTC
+///
+/// Chromium takes the last of repeated flags, so extra arguments that
+/// override these are caught too.
+fn judge_screenshots(shots: &ScreenshotConfig, config: &TorConfig) -> (AuditOutcome, String) {
+    let url = format!("http://{}/", AUDIT_ONION);
+    let proxy = match browser_proxy(&url, config) {
+        Ok(proxy) => proxy,
+        Err(e) => return (AuditOutcome::Pass, format!("the browser is never started: {}", e)),
+    };
+    let args = browser_args(&url, &shots.output_dir.join("audit.png"), &proxy, shots);
+    let last = |flag: &str| args.iter().rev().find_map(|a| a.strip_prefix(flag)).unwrap_or_default();
+    if last("--proxy-server=") != proxy {
+        return (AuditOutcome::Fail, format!("the browser is not sent through {}", proxy));
+    }
+    let bypass = ["--no-proxy-server", "--proxy-bypass-list", "--proxy-pac-url", "--proxy-auto-detect"];
+    if let Some(flag) = args.iter().find(|a| bypass.iter().any(|b| a.starts_with(b))) {
+        return (AuditOutcome::Fail, format!("{} lets the browser around the proxy", flag));
+    }
+    if !last("--host-resolver-rules=").starts_with("MAP * ~NOTFOUND") {
+        return (AuditOutcome::Fail, "the browser can resolve hostnames locally (DNS leak)".to_string());
+    }
+    if last("--force-webrtc-ip-handling-policy=") != "disable_non_proxied_udp" {
+        return (AuditOutcome::Fail, "WebRTC can connect around the proxy and reveal the real address".to_string());
+    }
+    (
+        AuditOutcome::Pass,
+        format!("sent through {} with local DNS and unproxied WebRTC disabled", proxy),
+    )
+}
+
+/// Check that every proxy in `config` keeps onion traffic inside Tor
+///
+/// Per endpoint, an onion request must reach the proxy as a hostname (or
+/// be refused), and a clearnet request must reach the proxy rather than
+/// its destination. With an upstream proxy both go through a stand-in for
+/// it, which may only be asked to tunnel to the Tor endpoint. The
+/// screenshot browser's flags are checked, and settings that weaken the
+/// setup are flagged.
+pub async fn audit_routing(config: &TorConfig) -> Result<AuditReport, TorError> {
+    let mut report = AuditReport::default();
+    let proxies = match &config.proxy_pool {
+        Some(pool) => pool.addrs(),
+        None => vec![config.socks_addr.clone()],
+    };
+
+    let recorder = Recorder::start().await?;
+    let sink = Sink::start().await?;
+    let upstream = match &config.upstream_proxy {
+        Some(upstream) => Some(Upstream::start(upstream.kind()).await?),
+        None => None,
+    };
+    for proxy in &proxies {
+        let harness = harness_config(proxy, &recorder, upstream.as_ref(), config);
+
+        let onion = format!("http://{}/", AUDIT_ONION);
+        let built = probe(&onion, &harness).await;
+        let connects = recorder.take();
+        let mut reached = connects.len();
+        let (outcome, detail) = judge_onion(AUDIT_ONION, &built, &connects);
+        let detail = detail.replace(&harness.socks_addr, proxy);
+        report.push(format!("onion requests via {}", proxy), outcome, detail);
+
+        let clearnet = format!("http://localhost:{}/", sink.port);
+        let before = sink.hits();
+        let built = probe(&clearnet, &harness).await;
+        let connects = recorder.take();
+        reached += connects.len();
+        let (outcome, detail) = judge_clearnet(&built, &connects, sink.hits() - before);
+        let detail = detail.replace(&harness.socks_addr, proxy);
+        report.push(format!("clearnet requests via {}", proxy), outcome, detail);
+
+        if let Some(upstream) = &upstream {
+            let (outcome, detail) = judge_upstream(&recorder.addr, &upstream.take(), reached);
+            report.push(format!("upstream proxy for {}", proxy), outcome, detail);
+        }
+    }
+    if let Some(upstream) = &config.upstream_proxy {
+        report.flag(
+            "upstream proxy",
+            format!(
+                "the {:?} proxy carries the SOCKS handshake with Tor unencrypted, so it sees which hosts are requested",
+                upstream.kind()
+            ),
+        );
+    }
+    if let Some(shots) = &config.screenshots {
+        let (outcome, detail) = judge_screenshots(shots, config);
+        report.push("screenshot browser", outcome, detail);
+    }
+
+    if config.strict {
+        report.push("strict mode", AuditOutcome::Pass, "only socks5h:// proxies and Tor traffic allowed");
+    } else {
+        report.flag("strict mode", "off: other proxy schemes and I2P traffic are allowed (--strict fails closed)");
+    }
+    if let Some(i2p) = &config.i2p {
+        report.flag("I2P", format!("eepsites go through the I2P HTTP proxy at {}, not Tor", i2p.proxy_addr));
+    }
+    if config.clearnet_search {
+        report.flag("clearnet search engines", "queried through Tor exit nodes, which see the query");
+    }
+    if config.tls_policy == TlsPolicy::AcceptAll {
+        report.flag("TLS", "certificates are not validated anywhere, so exit nodes can intercept clearnet HTTPS");
+    }
+    let unverified: Vec<&str> = config
+        .tls_overrides
+        .iter()
+        .filter(|o| o.accept_invalid && !o.host.ends_with(".onion"))
+        .map(|o| o.host.as_str())
+        .collect();
+    if !unverified.is_empty() && config.tls_policy != TlsPolicy::AcceptAll {
+        report.flag("TLS", format!("certificates are not validated on clearnet hosts {}", unverified.join(", ")));
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_audit_routing() {
+        let report = audit_routing(&TorConfig::default()).await.unwrap();
+        let outcomes: Vec<_> = report.checks.iter().map(|c| (c.name.as_str(), c.outcome)).collect();
+        assert_eq!(
+            outcomes,
+            vec![
+                ("onion requests via socks5h://127.0.0.1:9050", AuditOutcome::Pass),
+                ("clearnet requests via socks5h://127.0.0.1:9050", AuditOutcome::Pass),
+                ("strict mode", AuditOutcome::Flag),
+            ]
+        );
+        assert!(report.checks[0].detail.contains("hostname handed to the proxy"));
+
+        // socks5:// resolves locally: the guard refuses onions, clearnet names are looked up locally
+        let local_dns = TorConfig {
+            socks_addr: "socks5://127.0.0.1:9050".to_string(),
+            ..Default::default()
+        };
+        let report = audit_routing(&local_dns).await.unwrap();
+        assert_eq!(report.checks[0].outcome, AuditOutcome::Pass);
+        assert!(report.checks[0].detail.starts_with("refused by the leak guard"));
+        assert_eq!(report.checks[1].outcome, AuditOutcome::Flag);
+        assert!(report.passed());
+
+        // What a leaking client would look like to the recorder
+        let leaked = vec![ProxyConnect { host: "203.0.113.7".to_string() }];
+        assert_eq!(judge_onion(AUDIT_ONION, &Ok(Ok(())), &leaked).0, AuditOutcome::Fail);
+        assert_eq!(judge_onion(AUDIT_ONION, &Ok(Err("dns error".to_string())), &[]).0, AuditOutcome::Fail);
+        assert_eq!(judge_clearnet(&Ok(Ok(())), &[], 1).0, AuditOutcome::Fail);
+    }
+
+    #[tokio::test]
+    async fn test_audit_upstream_proxy() {
+        for upstream in ["http://10.0.0.1:3128", "socks5://10.0.0.1:1080"] {
+            let config = TorConfig {
+                upstream_proxy: Some(upstream.parse().unwrap()),
+                ..Default::default()
+            };
+            let report = audit_routing(&config).await.unwrap();
+            let outcomes: Vec<_> = report.checks.iter().map(|c| (c.name.as_str(), c.outcome)).collect();
+            assert_eq!(
+                outcomes,
+                vec![
+                    ("onion requests via socks5h://127.0.0.1:9050", AuditOutcome::Pass),
+                    ("clearnet requests via socks5h://127.0.0.1:9050", AuditOutcome::Pass),
+                    ("upstream proxy for socks5h://127.0.0.1:9050", AuditOutcome::Pass),
+                    ("upstream proxy", AuditOutcome::Flag),
+                    ("strict mode", AuditOutcome::Flag),
+                ],
+                "{}",
+                upstream
+            );
+        }
+
+        let endpoint = "127.0.0.1:9050";
+        assert_eq!(judge_upstream(endpoint, &[endpoint.to_string()], 1).0, AuditOutcome::Pass);
+        assert_eq!(judge_upstream(endpoint, &["example.com:80".to_string()], 1).0, AuditOutcome::Fail);
+        assert_eq!(judge_upstream(endpoint, &[], 1).0, AuditOutcome::Fail);
+    }
+
+    #[test]
+    fn test_audit_screenshot_browser() {
+        let shots = ScreenshotConfig::new("/tmp/shots");
+        assert_eq!(judge_screenshots(&shots, &TorConfig::default()).0, AuditOutcome::Pass);
+
+        // Extra flags that undo the defaults
+        for extra in [
+            "--force-webrtc-ip-handling-policy=default",
+            "--host-resolver-rules=",
+            "--proxy-server=direct://",
+            "--proxy-bypass-list=*",
+        ] {
+            let mut shots = ScreenshotConfig::new("/tmp/shots");
+            shots.extra_args.push(extra.to_string());
+            assert_eq!(judge_screenshots(&shots, &TorConfig::default()).0, AuditOutcome::Fail, "{}", extra);
+        }
+
+        // The browser can't chain through an upstream proxy, so it never runs
+        let config = TorConfig {
+            upstream_proxy: Some("http://10.0.0.1:3128".parse().unwrap()),
+            ..Default::default()
+        };
+        assert!(judge_screenshots(&shots, &config).1.starts_with("the browser is never started"));
+    }
+}
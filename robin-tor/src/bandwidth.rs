@@ -0,0 +1,165 @@
+//! Bandwidth accounting and per-run transfer caps
+//!
+//! Tor's capacity is shared community infrastructure, and a runaway
+//! recursive crawl can pull gigabytes through volunteer relays. Every live
+//! request through [`crate::fetch_request`] is charged to a
+//! [`BandwidthMeter`] by destination host and by the agent that sent it.
+//! With a cap set, requests fail with [`TorError::BandwidthExceeded`] once
+//! the run has transferred that much.
+//!
+//! Counts are HTTP bytes (request line, headers and bodies); TLS and Tor
+//! cell overhead is not included, so the wire total is somewhat higher.
+//! Onion probes are charged too. Not counted: the screenshot browser's
+//! traffic (screenshots stop once the cap is reached) and the API calls of
+//! enrichment, blockchain, paste, gist and feed agents, even over Tor.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use crate::TorError;
+
+/// Bytes sent and received
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Transfer {
+    pub sent: u64,
+    pub received: u64,
+}
+
+impl Transfer {
+    /// Bytes in both directions
+    pub fn total(&self) -> u64 {
+        self.sent + self.received
+    }
+
+    fn add(&mut self, other: Transfer) {
+        self.sent += other.sent;
+        self.received += other.received;
+    }
+}
+
+/// Point-in-time copy of a [`BandwidthMeter`]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BandwidthSnapshot {
+    /// Per destination host
+    pub hosts: BTreeMap<String, Transfer>,
+    /// Per agent (requests made outside an agent are only counted by host)
+    pub agents: BTreeMap<String, Transfer>,
+    /// Across the run
+    pub total: Transfer,
+    /// Cap on `total`, in bytes (None = unlimited)
+    pub limit: Option<u64>,
+}
+
+#[derive(Debug, Default)]
+struct MeterState {
+    hosts: HashMap<String, Transfer>,
+    agents: HashMap<String, Transfer>,
+    total: Transfer,
+}
+
+/// Shared transfer counters with an optional cap
+///
+/// Cloning is cheap and all clones share the same counters.
+#[derive(Debug, Clone, Default)]
+pub struct BandwidthMeter {
+    limit: Option<u64>,
+    used: Arc<AtomicU64>,
+    state: Arc<Mutex<MeterState>>,
+}
+
+impl BandwidthMeter {
+    /// A meter refusing requests once `limit` bytes have been transferred
+    pub fn with_limit(limit: u64) -> Self {
+        Self {
+            limit: Some(limit),
+            ..Default::default()
+        }
+    }
+
+    /// Bytes transferred so far
+    pub fn used(&self) -> u64 {
+        self.used.load(Ordering::Relaxed)
+    }
+
+    /// Err once the cap has been reached
+    ///
+    /// The request that crosses the cap still completes; the ones after it
+    /// are refused.
+    pub fn check(&self) -> Result<(), TorError> {
+        match self.limit {
+            Some(limit) if self.used() >= limit => Err(TorError::BandwidthExceeded(limit)),
+            _ => Ok(()),
+        }
+    }
+
+    /// Record a request to `host` made by `agent`
+    pub fn charge(&self, host: &str, agent: Option<&str>, transfer: Transfer) {
+        self.used.fetch_add(transfer.total(), Ordering::Relaxed);
+        let mut state = self.state.lock().unwrap();
+        state.total.add(transfer);
+        state.hosts.entry(host.to_string()).or_default().add(transfer);
+        if let Some(agent) = agent {
+            state.agents.entry(agent.to_string()).or_default().add(transfer);
+        }
+    }
+
+    /// Copy of the current counters
+    pub fn snapshot(&self) -> BandwidthSnapshot {
+        let state = self.state.lock().unwrap();
+        BandwidthSnapshot {
+            hosts: state.hosts.iter().map(|(k, v)| (k.clone(), *v)).collect(),
+            agents: state.agents.iter().map(|(k, v)| (k.clone(), *v)).collect(),
+            total: state.total,
+            limit: self.limit,
+        }
+    }
+}
+
+/// Parse a byte size such as `500MB`, `2 GiB` or `1048576`
+///
+/// Units are binary (`KB` and `KiB` are both 1024 bytes).
+pub fn parse_byte_size(s: &str) -> Result<u64, String> {
+    let s = s.trim();
+    let split = s.find(|c: char| !c.is_ascii_digit() && c != '.').unwrap_or(s.len());
+    let (number, unit) = s.split_at(split);
+    let number: f64 = number.parse().map_err(|_| format!("invalid size '{}'", s))?;
+    let scale: u64 = match unit.trim().to_ascii_lowercase().as_str() {
+        "" | "b" => 1,
+        "k" | "kb" | "kib" => 1 << 10,
+        "m" | "mb" | "mib" => 1 << 20,
+        "g" | "gb" | "gib" => 1 << 30,
+        "t" | "tb" | "tib" => 1 << 40,
+        other => return Err(format!("unknown size unit '{}' (expected B, KB, MB, GB or TB)", other)),
+    };
+    Ok((number * scale as f64) as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_meter_counts_and_caps() {
+        let meter = BandwidthMeter::with_limit(1000);
+        let shared = meter.clone();
+        shared.charge("a.onion", Some("crawler-1"), Transfer { sent: 100, received: 500 });
+        assert!(meter.check().is_ok());
+        shared.charge("b.onion", None, Transfer { sent: 50, received: 400 });
+        assert!(matches!(meter.check(), Err(TorError::BandwidthExceeded(1000))));
+
+        let snapshot = meter.snapshot();
+        assert_eq!(snapshot.total, Transfer { sent: 150, received: 900 });
+        assert_eq!(snapshot.hosts["b.onion"].total(), 450);
+        assert_eq!(snapshot.agents.len(), 1);
+        assert_eq!(snapshot.agents["crawler-1"].received, 500);
+        assert!(BandwidthMeter::default().check().is_ok());
+
+        assert_eq!(parse_byte_size("1048576"), Ok(1 << 20));
+        assert_eq!(parse_byte_size("500MB"), Ok(500 << 20));
+        assert_eq!(parse_byte_size("1.5 GiB"), Ok(3 << 29));
+        assert!(parse_byte_size("5 parsecs").is_err());
+        assert!(parse_byte_size("MB").is_err());
+    }
+}
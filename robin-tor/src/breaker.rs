@@ -0,0 +1,254 @@
+//! Per-host circuit breaker
+//!
+//! A hidden service that went offline fails every request with a timeout
+//! or connection error, each costing retries, Tor circuits and run time.
+//! After [`BreakerPolicy::failure_threshold`] consecutive connection-level
+//! failures a host's circuit opens and requests to it fail at once with
+//! [`TorError::CircuitOpen`]. Once the cooldown passes one request is let
+//! through as a probe: success closes the circuit, failure reopens it with
+//! the cooldown doubled. A probe that ends any other way (an HTTP error,
+//! an unreachable proxy, a cancelled request) reopens it with the same
+//! cooldown, so the host is probed again later.
+//!
+//! The breaker is shared by every clone of a [`crate::TorConfig`], so
+//! crawlers, scrapers and onion probes all learn from each other. HTTP
+//! error statuses don't count (the service is up), and neither does an
+//! unreachable SOCKS proxy (that is the local Tor daemon's fault).
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+
+use crate::TorError;
+
+/// When a host's circuit opens
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BreakerPolicy {
+    /// Consecutive failures that open the circuit (0 = never)
+    pub failure_threshold: u32,
+    /// First cooldown before a probe; doubled for every failed probe
+    pub cooldown: Duration,
+    /// Longest cooldown
+    pub max_cooldown: Duration,
+}
+
+impl Default for BreakerPolicy {
+    fn default() -> Self {
+        Self {
+            failure_threshold: 5,
+            cooldown: Duration::from_secs(5 * 60),
+            max_cooldown: Duration::from_secs(60 * 60),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Circuit {
+    /// Requests go through
+    Closed { consecutive_failures: u32 },
+    /// Requests fail fast until `until`
+    Open { until: Instant, cooldown: Duration },
+    /// One probe is in flight; everything else fails fast
+    HalfOpen { cooldown: Duration },
+}
+
+/// One request admitted by a [`HostBreaker`]
+///
+/// Report whether the host answered with [`BreakerPermit::record`]. A
+/// probe dropped without an outcome reopens the circuit for its cooldown.
+#[must_use = "report the outcome with record(), or drop the permit if it says nothing about the host"]
+#[derive(Debug)]
+pub struct BreakerPermit {
+    breaker: HostBreaker,
+    host: String,
+    /// Admitted as the probe of a half-open circuit
+    probe: bool,
+}
+
+impl BreakerPermit {
+    /// Record the outcome (`ok` = it connected and answered)
+    pub fn record(mut self, ok: bool) {
+        self.probe = false;
+        self.breaker.record(&self.host, ok);
+    }
+}
+
+impl Drop for BreakerPermit {
+    fn drop(&mut self) {
+        if self.probe {
+            self.breaker.abandon_probe(&self.host);
+        }
+    }
+}
+
+/// Shared per-host circuit breaker
+///
+/// Cloning is cheap and all clones share the same circuits.
+#[derive(Debug, Clone, Default)]
+pub struct HostBreaker {
+    policy: BreakerPolicy,
+    circuits: Arc<Mutex<HashMap<String, Circuit>>>,
+    /// Times each host's circuit opened
+    trips: Arc<Mutex<HashMap<String, u32>>>,
+}
+
+impl HostBreaker {
+    pub fn new(policy: BreakerPolicy) -> Self {
+        Self {
+            policy,
+            ..Default::default()
+        }
+    }
+
+    /// Err while `host`'s circuit is open
+    ///
+    /// After the cooldown the first caller is admitted as the probe. Report
+    /// the outcome through the returned permit.
+    pub fn admit(&self, host: &str) -> Result<BreakerPermit, TorError> {
+        let mut permit = BreakerPermit {
+            breaker: self.clone(),
+            host: host.to_string(),
+            probe: false,
+        };
+        if self.policy.failure_threshold == 0 {
+            return Ok(permit);
+        }
+        let mut circuits = self.circuits.lock().unwrap();
+        let Some(circuit) = circuits.get_mut(host) else {
+            return Ok(permit);
+        };
+        match *circuit {
+            Circuit::Closed { .. } => Ok(permit),
+            Circuit::Open { until, cooldown } if Instant::now() >= until => {
+                *circuit = Circuit::HalfOpen { cooldown };
+                permit.probe = true;
+                Ok(permit)
+            }
+            Circuit::Open { .. } | Circuit::HalfOpen { .. } => Err(TorError::CircuitOpen(host.to_string())),
+        }
+    }
+
+    /// Reopen `host`'s circuit for the same cooldown if its probe ended without an outcome
+    fn abandon_probe(&self, host: &str) {
+        let mut circuits = self.circuits.lock().unwrap();
+        if let Some(circuit) = circuits.get_mut(host) {
+            if let Circuit::HalfOpen { cooldown } = *circuit {
+                *circuit = Circuit::Open {
+                    until: Instant::now() + cooldown,
+                    cooldown,
+                };
+            }
+        }
+    }
+
+    /// Record the outcome of a request to `host` (`ok` = it connected and answered)
+    pub fn record(&self, host: &str, ok: bool) {
+        if self.policy.failure_threshold == 0 {
+            return;
+        }
+        let mut circuits = self.circuits.lock().unwrap();
+        if ok {
+            if let Some(Circuit::HalfOpen { .. }) = circuits.remove(host) {
+                info!("Circuit for {} closed: probe succeeded", host);
+            }
+            return;
+        }
+
+        let circuit = circuits
+            .entry(host.to_string())
+            .or_insert(Circuit::Closed { consecutive_failures: 0 });
+        let cooldown = match *circuit {
+            Circuit::Closed { consecutive_failures } if consecutive_failures + 1 < self.policy.failure_threshold => {
+                *circuit = Circuit::Closed {
+                    consecutive_failures: consecutive_failures + 1,
+                };
+                return;
+            }
+            Circuit::Closed { .. } => self.policy.cooldown,
+            Circuit::HalfOpen { cooldown } => (cooldown * 2).min(self.policy.max_cooldown),
+            // Late failures of requests admitted before the circuit opened
+            Circuit::Open { .. } => return,
+        };
+        *circuit = Circuit::Open {
+            until: Instant::now() + cooldown,
+            cooldown,
+        };
+        *self.trips.lock().unwrap().entry(host.to_string()).or_default() += 1;
+        warn!("Circuit for {} opened for {:?} after repeated failures", host, cooldown);
+    }
+
+    /// How many times each host's circuit opened this run
+    pub fn trips(&self) -> BTreeMap<String, u32> {
+        self.trips.lock().unwrap().iter().map(|(k, v)| (k.clone(), *v)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_opens_probes_and_closes() {
+        let breaker = HostBreaker::new(BreakerPolicy {
+            failure_threshold: 2,
+            cooldown: Duration::from_millis(100),
+            max_cooldown: Duration::from_millis(150),
+        });
+        let shared = breaker.clone();
+        let host = "dead.onion";
+
+        // A success resets the count
+        breaker.record(host, false);
+        breaker.record(host, true);
+        breaker.record(host, false);
+        breaker.admit(host).unwrap().record(false);
+        assert!(matches!(breaker.admit(host), Err(TorError::CircuitOpen(h)) if h == host));
+        assert!(breaker.admit("other.onion").is_ok());
+
+        // One probe after the cooldown; a failed probe doubles it (up to the max)
+        std::thread::sleep(Duration::from_millis(110));
+        let probe = breaker.admit(host).unwrap();
+        assert!(shared.admit(host).is_err());
+        probe.record(false);
+        std::thread::sleep(Duration::from_millis(110));
+        assert!(breaker.admit(host).is_err());
+        std::thread::sleep(Duration::from_millis(50));
+
+        breaker.admit(host).unwrap().record(true);
+        assert!(breaker.admit(host).is_ok());
+        assert_eq!(breaker.trips()[host], 2);
+
+        let off = HostBreaker::new(BreakerPolicy {
+            failure_threshold: 0,
+            ..Default::default()
+        });
+        for _ in 0..10 {
+            off.record(host, false);
+        }
+        assert!(off.admit(host).is_ok());
+    }
+
+    #[test]
+    fn test_probe_without_outcome_is_retried() {
+        let cooldown = Duration::from_millis(50);
+        let breaker = HostBreaker::new(BreakerPolicy {
+            failure_threshold: 1,
+            cooldown,
+            max_cooldown: Duration::from_secs(1),
+        });
+        let host = "flaky.onion";
+        breaker.record(host, false);
+        std::thread::sleep(cooldown + Duration::from_millis(10));
+
+        // The probe errors in a way that says nothing about the host
+        drop(breaker.admit(host).unwrap());
+        assert!(breaker.admit(host).is_err());
+
+        // After the same cooldown it is probed again, not wedged half-open
+        std::thread::sleep(cooldown + Duration::from_millis(10));
+        breaker.admit(host).unwrap().record(true);
+        assert!(breaker.admit(host).is_ok());
+        assert_eq!(breaker.trips()[host], 1);
+    }
+}
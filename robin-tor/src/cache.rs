@@ -0,0 +1,535 @@
+//! Disk-backed response cache
+//!
+//! Successful responses are stored under a cache directory, keyed by
+//! method, URL and request-body hash, so re-running an investigation or
+//! several scrapers hitting the same page don't go back over Tor. Entries
+//! expire after a TTL, and the oldest entries are evicted once the cache
+//! grows past its size limit.
+//!
+//! Servers can shorten an entry's lifetime with `Cache-Control: max-age` or
+//! `Expires` (never past the cache's own TTL) and keep a response out of the
+//! cache with `no-store`, `no-cache` or an already-past expiry. Many onion
+//! services send `no-store` on every page by default, so the TTL can also be
+//! made to override the headers.
+//!
+//! With [`ResponseCache::with_key`] entries are sealed at rest. A passphrase
+//! is stretched once per cache directory, with the salt kept beside the
+//! entries.
+
+use chrono::DateTime;
+use reqwest::header::{HeaderMap, CACHE_CONTROL, DATE, EXPIRES};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tracing::debug;
+
+use robin_core::{is_sealed, open, random_salt, seal, AtRestKey};
+
+use crate::{FetchResponse, TorError};
+
+/// Default entry lifetime
+pub const DEFAULT_CACHE_TTL: Duration = Duration::from_secs(60 * 60);
+
+/// Default size limit (256 MiB)
+pub const DEFAULT_CACHE_MAX_BYTES: u64 = 256 * 1024 * 1024;
+
+/// File in a sealed cache directory holding the passphrase salt
+const SALT_FILE: &str = "cache.salt";
+
+/// On-disk cache entry
+#[derive(Debug, Serialize, Deserialize)]
+struct CacheEntry {
+    url: String,
+    status: u16,
+    body: String,
+    /// Unix seconds
+    stored_at: u64,
+    /// Lifetime the server allowed, in seconds (None = the cache's TTL)
+    #[serde(default)]
+    max_age: Option<u64>,
+}
+
+/// Caching a server allowed for a response, from `Cache-Control` and `Expires`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Cacheability {
+    /// No caching headers: the cache's TTL applies
+    #[default]
+    Unspecified,
+    /// Fresh for at most this long
+    MaxAge(Duration),
+    /// Not to be stored (`no-store`, `no-cache`, or already expired)
+    NoStore,
+}
+
+impl Cacheability {
+    /// Read `Cache-Control` (preferred) or `Expires` from response headers
+    ///
+    /// `private` is ignored, since this is not a shared cache.
+    pub fn from_headers(headers: &HeaderMap) -> Self {
+        let directives = headers
+            .get_all(CACHE_CONTROL)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .map(|directive| directive.trim().to_ascii_lowercase())
+            .collect::<Vec<_>>();
+        if directives.iter().any(|d| d == "no-store" || d == "no-cache") {
+            return Self::NoStore;
+        }
+        let max_age = directives
+            .iter()
+            .find_map(|d| d.strip_prefix("max-age=")?.trim_matches('"').parse::<u64>().ok());
+        if let Some(secs) = max_age {
+            return Self::lifetime(secs as i64);
+        }
+
+        let date = |name| {
+            let value = headers.get(name)?.to_str().ok()?;
+            DateTime::parse_from_rfc2822(value).ok().map(|date| date.timestamp())
+        };
+        match headers.get(EXPIRES) {
+            // An unparseable Expires (often "0" or "-1") means already expired
+            Some(_) => match date(EXPIRES) {
+                Some(expires) => Self::lifetime(expires - date(DATE).unwrap_or(now_secs() as i64)),
+                None => Self::NoStore,
+            },
+            None => Self::Unspecified,
+        }
+    }
+
+    fn lifetime(secs: i64) -> Self {
+        if secs <= 0 {
+            Self::NoStore
+        } else {
+            Self::MaxAge(Duration::from_secs(secs as u64))
+        }
+    }
+}
+
+/// Cache counters since the cache was opened
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    /// Lookups served from the cache
+    pub hits: u64,
+    /// Lookups that went to the network (absent or expired)
+    pub misses: u64,
+    /// Responses written to the cache
+    pub stores: u64,
+    /// Entries removed to stay under the size limit
+    pub evictions: u64,
+}
+
+impl CacheStats {
+    /// Fraction of lookups served from the cache
+    pub fn hit_rate(&self) -> f64 {
+        let lookups = self.hits + self.misses;
+        if lookups == 0 {
+            0.0
+        } else {
+            self.hits as f64 / lookups as f64
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct Counters {
+    hits: AtomicU64,
+    misses: AtomicU64,
+    stores: AtomicU64,
+    evictions: AtomicU64,
+}
+
+/// Shared handle to a response cache directory
+///
+/// Cloning is cheap and all clones share the same directory and counters,
+/// so one cache can be handed to every agent through [`crate::TorConfig`].
+#[derive(Debug, Clone)]
+pub struct ResponseCache {
+    dir: PathBuf,
+    ttl: Duration,
+    max_bytes: u64,
+    /// Apply the TTL to every response, whatever its caching headers say
+    ttl_override: bool,
+    /// Key entries are sealed with (already stretched), if any
+    key: Option<AtRestKey>,
+    counters: Arc<Counters>,
+    /// Serializes eviction passes
+    evicting: Arc<Mutex<()>>,
+    /// Removes a per-run cache directory once the last clone is dropped
+    _run_dir: Option<Arc<RunDir>>,
+}
+
+/// A cache directory that only lives for this run
+#[derive(Debug)]
+struct RunDir(PathBuf);
+
+impl Drop for RunDir {
+    fn drop(&mut self) {
+        std::fs::remove_dir_all(&self.0).ok();
+    }
+}
+
+impl ResponseCache {
+    /// Open (creating if needed) a cache in `dir`
+    pub fn open<P: AsRef<Path>>(dir: P) -> Result<Self, TorError> {
+        let dir = dir.as_ref().to_path_buf();
+        std::fs::create_dir_all(&dir)
+            .map_err(|e| TorError::Cache(format!("failed to create {}: {}", dir.display(), e)))?;
+        Ok(Self {
+            dir,
+            ttl: DEFAULT_CACHE_TTL,
+            max_bytes: DEFAULT_CACHE_MAX_BYTES,
+            ttl_override: false,
+            key: None,
+            counters: Arc::default(),
+            evicting: Arc::default(),
+            _run_dir: None,
+        })
+    }
+
+    /// Open a cache in a fresh temporary directory, removed when the last clone is dropped
+    ///
+    /// Without a persistent cache, this still lets retries, parallel
+    /// crawlers and the recursive crawler share responses within a run.
+    pub fn for_run() -> Result<Self, TorError> {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.subsec_nanos())
+            .unwrap_or_default();
+        let dir = std::env::temp_dir().join(format!("robin-run-cache-{}-{}", std::process::id(), nanos));
+        let mut cache = Self::open(&dir)?;
+        cache._run_dir = Some(Arc::new(RunDir(dir)));
+        Ok(cache)
+    }
+
+    /// Set how long entries stay fresh
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// Apply the TTL to every response, ignoring `Cache-Control` and `Expires`
+    pub fn with_ttl_override(mut self, ttl_override: bool) -> Self {
+        self.ttl_override = ttl_override;
+        self
+    }
+
+    /// Seal entries under `key`; entries that can't be opened with it are misses
+    pub fn with_key(mut self, key: &AtRestKey) -> Result<Self, TorError> {
+        if let AtRestKey::Passphrase(_) = key {
+            let path = self.dir.join(SALT_FILE);
+            if !path.exists() {
+                std::fs::write(&path, random_salt())
+                    .map_err(|e| TorError::Cache(format!("failed to write {}: {}", path.display(), e)))?;
+            }
+        }
+        self.key = Self::entry_key(&self.dir, key)?;
+        Ok(self)
+    }
+
+    /// Key the entries of the cache in `dir` are sealed with under `key`
+    ///
+    /// None if `key` is a passphrase and the cache has no salt, so no entry
+    /// was ever sealed with one.
+    pub fn entry_key(dir: &Path, key: &AtRestKey) -> Result<Option<AtRestKey>, TorError> {
+        let salt = match key {
+            AtRestKey::Key(_) => Vec::new(),
+            AtRestKey::Passphrase(_) => match std::fs::read(dir.join(SALT_FILE)) {
+                Ok(salt) => salt,
+                Err(_) => return Ok(None),
+            },
+        };
+        key.stretched(&salt).map(Some).map_err(|e| TorError::Cache(e.to_string()))
+    }
+
+    /// Set the size limit in bytes
+    pub fn with_max_bytes(mut self, max_bytes: u64) -> Self {
+        self.max_bytes = max_bytes;
+        self
+    }
+
+    /// Cache directory
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Counters since the cache was opened
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            hits: self.counters.hits.load(Ordering::Relaxed),
+            misses: self.counters.misses.load(Ordering::Relaxed),
+            stores: self.counters.stores.load(Ordering::Relaxed),
+            evictions: self.counters.evictions.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Fresh cached response for the request, if any
+    pub fn get(&self, method: &str, url: &str, body: &[u8]) -> Option<FetchResponse> {
+        let path = self.entry_path(method, url, body);
+        let entry = std::fs::read(&path)
+            .ok()
+            .and_then(|data| match &self.key {
+                Some(key) if is_sealed(&data) => open(key, &data).ok(),
+                None if is_sealed(&data) => None,
+                _ => Some(data),
+            })
+            .and_then(|json| serde_json::from_slice::<CacheEntry>(&json).ok())
+            .filter(|entry| entry.url == url);
+
+        match entry {
+            Some(entry) if now_secs().saturating_sub(entry.stored_at) <= self.lifetime(&entry) => {
+                self.counters.hits.fetch_add(1, Ordering::Relaxed);
+                debug!("Cache hit for {}", url);
+                Some(FetchResponse {
+                    url: entry.url,
+                    status: entry.status,
+                    body: entry.body,
+                    cacheability: Cacheability::Unspecified,
+                })
+            }
+            expired => {
+                if expired.is_some() {
+                    std::fs::remove_file(&path).ok();
+                }
+                self.counters.misses.fetch_add(1, Ordering::Relaxed);
+                None
+            }
+        }
+    }
+
+    /// Store a response for the request, unless its caching headers forbid it
+    pub fn put(&self, method: &str, body: &[u8], response: &FetchResponse) -> Result<(), TorError> {
+        let max_age = match response.cacheability {
+            _ if self.ttl_override => None,
+            Cacheability::Unspecified => None,
+            Cacheability::MaxAge(max_age) => Some(max_age.as_secs()),
+            Cacheability::NoStore => {
+                debug!("Not caching {} (no-store)", response.url);
+                return Ok(());
+            }
+        };
+        let entry = CacheEntry {
+            url: response.url.clone(),
+            status: response.status,
+            body: response.body.clone(),
+            stored_at: now_secs(),
+            max_age,
+        };
+        let mut json = serde_json::to_vec(&entry)
+            .map_err(|e| TorError::Cache(format!("failed to serialize entry: {}", e)))?;
+        if let Some(key) = &self.key {
+            json = seal(key, &json).map_err(|e| TorError::Cache(e.to_string()))?;
+        }
+
+        // Write then rename, so concurrent readers never see a partial entry
+        let path = self.entry_path(method, &response.url, body);
+        let tmp = path.with_extension(format!("tmp{}", std::process::id()));
+        std::fs::write(&tmp, json)
+            .and_then(|_| std::fs::rename(&tmp, &path))
+            .map_err(|e| TorError::Cache(format!("failed to write {}: {}", path.display(), e)))?;
+        self.counters.stores.fetch_add(1, Ordering::Relaxed);
+
+        self.evict_to_limit();
+        Ok(())
+    }
+
+    /// Seconds an entry stays fresh
+    fn lifetime(&self, entry: &CacheEntry) -> u64 {
+        match entry.max_age {
+            Some(max_age) if !self.ttl_override => max_age.min(self.ttl.as_secs()),
+            _ => self.ttl.as_secs(),
+        }
+    }
+
+    /// Remove the oldest entries until the cache fits its size limit
+    fn evict_to_limit(&self) {
+        let Ok(_guard) = self.evicting.try_lock() else {
+            // Another thread is already evicting
+            return;
+        };
+
+        let mut entries: Vec<(SystemTime, u64, PathBuf)> = match std::fs::read_dir(&self.dir) {
+            Ok(dir) => dir
+                .filter_map(|e| e.ok())
+                .filter(|e| e.path().extension().is_some_and(|ext| ext == "json"))
+                .filter_map(|e| {
+                    let meta = e.metadata().ok()?;
+                    Some((meta.modified().ok()?, meta.len(), e.path()))
+                })
+                .collect(),
+            Err(_) => return,
+        };
+
+        let mut total: u64 = entries.iter().map(|(_, len, _)| len).sum();
+        if total <= self.max_bytes {
+            return;
+        }
+
+        entries.sort();
+        for (_, len, path) in entries {
+            if total <= self.max_bytes {
+                break;
+            }
+            if std::fs::remove_file(&path).is_ok() {
+                total -= len;
+                self.counters.evictions.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+    }
+
+    fn entry_path(&self, method: &str, url: &str, body: &[u8]) -> PathBuf {
+        let body_hash = Sha256::digest(body);
+        let mut hasher = Sha256::new();
+        hasher.update(method.to_ascii_uppercase().as_bytes());
+        hasher.update([0]);
+        hasher.update(url.as_bytes());
+        hasher.update([0]);
+        hasher.update(body_hash);
+        let key: String = hasher.finalize().iter().map(|b| format!("{:02x}", b)).collect();
+        self.dir.join(format!("{}.json", key))
+    }
+}
+
+pub(crate) fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_cache(name: &str) -> ResponseCache {
+        let dir = std::env::temp_dir().join(format!("robin-cache-{}-{}", name, std::process::id()));
+        std::fs::remove_dir_all(&dir).ok();
+        ResponseCache::open(dir).unwrap()
+    }
+
+    fn response(url: &str, body: &str) -> FetchResponse {
+        FetchResponse {
+            url: url.to_string(),
+            status: 200,
+            body: body.to_string(),
+            cacheability: Cacheability::Unspecified,
+        }
+    }
+
+    #[test]
+    fn test_hit_miss_and_body_key() {
+        let cache = temp_cache("hits");
+
+        assert!(cache.get("GET", "http://a.onion/", b"").is_none());
+        cache.put("GET", b"", &response("http://a.onion/", "page")).unwrap();
+        assert_eq!(cache.get("GET", "http://a.onion/", b"").unwrap().body, "page");
+
+        // Same URL with a different request body is a different entry
+        assert!(cache.get("POST", "http://a.onion/", b"q=x").is_none());
+
+        let stats = cache.stats();
+        assert_eq!((stats.hits, stats.misses, stats.stores), (1, 2, 1));
+        assert!((stats.hit_rate() - 1.0 / 3.0).abs() < 1e-9);
+        std::fs::remove_dir_all(cache.dir()).ok();
+    }
+
+    #[test]
+    fn test_sealed_entries() {
+        let key = AtRestKey::passphrase("cache");
+        let cache = temp_cache("sealed").with_key(&key).unwrap();
+        cache.put("GET", b"", &response("http://a.onion/", "secret page")).unwrap();
+        assert_eq!(cache.get("GET", "http://a.onion/", b"").unwrap().body, "secret page");
+
+        let on_disk: Vec<Vec<u8>> = std::fs::read_dir(cache.dir())
+            .unwrap()
+            .filter_map(|e| e.ok())
+            .filter(|e| e.path().extension().is_some_and(|ext| ext == "json"))
+            .map(|e| std::fs::read(e.path()).unwrap())
+            .collect();
+        assert_eq!(on_disk.len(), 1);
+        assert!(is_sealed(&on_disk[0]));
+
+        // Reopened with the same passphrase the entry is still readable; without it, it's a miss
+        let reopened = ResponseCache::open(cache.dir()).unwrap();
+        assert!(reopened.get("GET", "http://a.onion/", b"").is_none());
+        let reopened = reopened.with_key(&key).unwrap();
+        assert_eq!(reopened.get("GET", "http://a.onion/", b"").unwrap().body, "secret page");
+        std::fs::remove_dir_all(cache.dir()).ok();
+    }
+
+    #[test]
+    fn test_expired_entries_miss() {
+        let cache = temp_cache("ttl").with_ttl(Duration::ZERO);
+        cache.put("GET", b"", &response("http://a.onion/", "page")).unwrap();
+
+        std::thread::sleep(Duration::from_millis(1100));
+        assert!(cache.get("GET", "http://a.onion/", b"").is_none());
+        assert_eq!(std::fs::read_dir(cache.dir()).unwrap().count(), 0);
+        std::fs::remove_dir_all(cache.dir()).ok();
+    }
+
+    #[test]
+    fn test_evicts_oldest_over_limit() {
+        let cache = temp_cache("evict").with_max_bytes(300);
+        for i in 0..5 {
+            let url = format!("http://{}.onion/", i);
+            cache.put("GET", b"", &response(&url, &"x".repeat(100))).unwrap();
+            std::thread::sleep(Duration::from_millis(10));
+        }
+
+        assert!(cache.stats().evictions >= 3);
+        assert!(cache.get("GET", "http://4.onion/", b"").is_some());
+        assert!(cache.get("GET", "http://0.onion/", b"").is_none());
+        std::fs::remove_dir_all(cache.dir()).ok();
+    }
+
+    #[test]
+    fn test_respects_cache_headers() {
+        let headers = |pairs: &[(&'static str, &str)]| {
+            let mut map = HeaderMap::new();
+            for (name, value) in pairs {
+                map.append(*name, value.parse().unwrap());
+            }
+            Cacheability::from_headers(&map)
+        };
+        assert_eq!(headers(&[]), Cacheability::Unspecified);
+        assert_eq!(headers(&[("cache-control", "private, max-age=60")]), Cacheability::MaxAge(Duration::from_secs(60)));
+        assert_eq!(headers(&[("cache-control", "no-store, no-cache, must-revalidate")]), Cacheability::NoStore);
+        assert_eq!(headers(&[("cache-control", "max-age=0")]), Cacheability::NoStore);
+        assert_eq!(
+            headers(&[("date", "Wed, 21 Oct 2026 07:28:00 GMT"), ("expires", "Wed, 21 Oct 2026 07:38:00 GMT")]),
+            Cacheability::MaxAge(Duration::from_secs(600))
+        );
+        assert_eq!(headers(&[("expires", "Thu, 19 Nov 1981 08:52:00 GMT")]), Cacheability::NoStore);
+        assert_eq!(headers(&[("expires", "-1")]), Cacheability::NoStore);
+
+        let cache = temp_cache("headers");
+        let mut uncacheable = response("http://a.onion/", "page");
+        uncacheable.cacheability = Cacheability::NoStore;
+        cache.put("GET", b"", &uncacheable).unwrap();
+        assert!(cache.get("GET", "http://a.onion/", b"").is_none());
+
+        // max-age can shorten the TTL, never extend it
+        let mut short = response("http://b.onion/", "page");
+        short.cacheability = Cacheability::MaxAge(Duration::ZERO);
+        cache.put("GET", b"", &short).unwrap();
+        std::thread::sleep(Duration::from_millis(1100));
+        assert!(cache.get("GET", "http://b.onion/", b"").is_none());
+
+        let cache = cache.with_ttl_override(true);
+        cache.put("GET", b"", &uncacheable).unwrap();
+        assert!(cache.get("GET", "http://a.onion/", b"").is_some());
+        std::fs::remove_dir_all(cache.dir()).ok();
+
+        let run = ResponseCache::for_run().unwrap();
+        let dir = run.dir().to_path_buf();
+        let clone = run.clone();
+        drop(run);
+        assert!(dir.exists());
+        drop(clone);
+        assert!(!dir.exists());
+    }
+}
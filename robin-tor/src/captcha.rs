@@ -0,0 +1,215 @@
+//! Captcha and anti-bot interstitial detection
+//!
+//! Many onion services answer automated traffic with a "prove you're human"
+//! page instead of content. [`crate::fetch_request`] recognises these pages
+//! and hands them to the configured [`CaptchaSolver`]; if there is none, or
+//! it gives up, the fetch fails with [`crate::TorError::CaptchaRequired`] so callers
+//! can skip the site instead of parsing the challenge as content.
+
+use async_trait::async_trait;
+use std::fmt;
+use tokio::sync::Mutex;
+
+use crate::FetchResponse;
+
+/// Pages larger than this are only flagged on vendor markup, not on wording
+const GENERIC_MAX_BODY: usize = 16 * 1024;
+
+/// Pages larger than this are only flagged on vendor markup inside a form
+///
+/// Challenge interstitials are small; a large page embedding a vendor
+/// widget (a comment box, a newsletter signup) is still content.
+const VENDOR_MAX_BODY: usize = 64 * 1024;
+
+/// Vendor-specific markup, matched on small pages or inside a form
+const VENDOR_MARKERS: &[(&str, CaptchaKind)] = &[
+    ("cf-challenge", CaptchaKind::Cloudflare),
+    ("cf_chl_", CaptchaKind::Cloudflare),
+    ("challenge-platform", CaptchaKind::Cloudflare),
+    ("g-recaptcha", CaptchaKind::Recaptcha),
+    ("google.com/recaptcha", CaptchaKind::Recaptcha),
+    ("h-captcha", CaptchaKind::Hcaptcha),
+    ("hcaptcha.com", CaptchaKind::Hcaptcha),
+    ("ddos-guard", CaptchaKind::DdosGuard),
+];
+
+/// Interstitial wording, matched on small pages only
+const GENERIC_PHRASES: &[&str] = &[
+    "prove you're human",
+    "prove you are human",
+    "verify you are human",
+    "are you a robot",
+    "i am not a robot",
+    "enter the captcha",
+    "solve the captcha",
+    "ddos protection",
+    "checking your browser",
+];
+
+/// Kind of challenge a site presented
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CaptchaKind {
+    Cloudflare,
+    Recaptcha,
+    Hcaptcha,
+    DdosGuard,
+    /// Site-specific challenge (typical of onion markets and forums)
+    Generic,
+}
+
+impl fmt::Display for CaptchaKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CaptchaKind::Cloudflare => write!(f, "cloudflare"),
+            CaptchaKind::Recaptcha => write!(f, "recaptcha"),
+            CaptchaKind::Hcaptcha => write!(f, "hcaptcha"),
+            CaptchaKind::DdosGuard => write!(f, "ddos-guard"),
+            CaptchaKind::Generic => write!(f, "captcha"),
+        }
+    }
+}
+
+/// Detect a captcha or anti-bot interstitial in a response
+pub fn detect_captcha(response: &FetchResponse) -> Option<CaptchaKind> {
+    let body = response.body.to_ascii_lowercase();
+    let vendor = VENDOR_MARKERS.iter().find(|(marker, _)| {
+        if body.len() <= VENDOR_MAX_BODY {
+            body.contains(marker)
+        } else {
+            inside_form(&body, marker)
+        }
+    });
+    if let Some((_, kind)) = vendor {
+        return Some(*kind);
+    }
+    if body.len() <= GENERIC_MAX_BODY && GENERIC_PHRASES.iter().any(|phrase| body.contains(phrase)) {
+        return Some(CaptchaKind::Generic);
+    }
+    None
+}
+
+/// Whether `marker` appears between a `<form` and its `</form`
+fn inside_form(body: &str, marker: &str) -> bool {
+    body.match_indices(marker).any(|(at, _)| {
+        let before = &body[..at];
+        before
+            .rfind("<form")
+            .is_some_and(|open| before.rfind("</form").is_none_or(|close| close < open))
+    })
+}
+
+/// A challenge handed to a [`CaptchaSolver`]
+#[derive(Debug, Clone)]
+pub struct CaptchaChallenge {
+    /// Requested URL
+    pub url: String,
+    /// Detected challenge kind
+    pub kind: CaptchaKind,
+    /// The challenge page
+    pub response: FetchResponse,
+}
+
+/// What to send with the retried request once a challenge is solved
+#[derive(Debug, Clone, Default)]
+pub struct CaptchaSolution {
+    /// Extra headers, typically a clearance `Cookie`
+    pub headers: Vec<(String, String)>,
+}
+
+/// Gets past captcha interstitials (manual prompt, solving service, ...)
+///
+/// Returning `None` gives up; the fetch then fails with
+/// [`crate::TorError::CaptchaRequired`].
+#[async_trait]
+pub trait CaptchaSolver: fmt::Debug + Send + Sync {
+    async fn solve(&self, challenge: &CaptchaChallenge) -> Option<CaptchaSolution>;
+}
+
+/// Asks the operator on the terminal for a clearance cookie
+///
+/// The operator opens the URL in Tor Browser, solves the challenge and
+/// pastes the site's `Cookie` header value; an empty line skips the site.
+/// Prompts are serialised so concurrent fetches don't interleave.
+#[derive(Debug, Default)]
+pub struct PromptSolver {
+    lock: Mutex<()>,
+}
+
+impl PromptSolver {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl CaptchaSolver for PromptSolver {
+    async fn solve(&self, challenge: &CaptchaChallenge) -> Option<CaptchaSolution> {
+        let _guard = self.lock.lock().await;
+        let prompt = format!(
+            "\n{} challenge at {}\nSolve it in Tor Browser and paste the Cookie header (empty to skip): ",
+            challenge.kind, challenge.url
+        );
+        let line = tokio::task::spawn_blocking(move || {
+            use std::io::Write;
+            eprint!("{}", prompt);
+            std::io::stderr().flush().ok();
+            let mut line = String::new();
+            std::io::stdin().read_line(&mut line).ok().map(|_| line)
+        })
+        .await
+        .ok()??;
+
+        let cookie = line.trim();
+        if cookie.is_empty() {
+            return None;
+        }
+        Some(CaptchaSolution {
+            headers: vec![("Cookie".to_string(), cookie.to_string())],
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Cacheability;
+
+    fn page(body: &str) -> FetchResponse {
+        FetchResponse {
+            url: "http://a.onion/".to_string(),
+            status: 200,
+            body: body.to_string(),
+            cacheability: Cacheability::Unspecified,
+        }
+    }
+
+    #[test]
+    fn test_detects_vendor_challenges() {
+        let cloudflare = page(r#"<form id="challenge-form" action="/?__cf_chl_jschl_tk__=x">"#);
+        assert_eq!(detect_captcha(&cloudflare), Some(CaptchaKind::Cloudflare));
+        let hcaptcha = page(r#"<div class="h-captcha" data-sitekey="k"></div>"#);
+        assert_eq!(detect_captcha(&hcaptcha), Some(CaptchaKind::Hcaptcha));
+        let market = page("<h1>Prove you're human</h1><img src='/captcha.png'>");
+        assert_eq!(detect_captcha(&market), Some(CaptchaKind::Generic));
+    }
+
+    #[test]
+    fn test_ignores_ordinary_pages() {
+        assert_eq!(detect_captcha(&page("<h1>Leaked databases</h1>")), None);
+        // A long forum thread that merely discusses captchas is content
+        let thread = format!("<p>how do I prove you are human checks?</p>{}", "<p>reply</p>".repeat(2000));
+        assert_eq!(detect_captcha(&page(&thread)), None);
+        // So is a large page that only mentions or loads a vendor widget outside a form
+        let article = format!(
+            "<script src='https://hcaptcha.com/1/api.js'></script><p>g-recaptcha bypass</p>{}",
+            "<p>post</p>".repeat(10_000)
+        );
+        assert_eq!(detect_captcha(&page(&article)), None);
+        // ...but a vendor widget inside a form on a large page is a challenge
+        let gated = format!(
+            "{}<form action='/login'><div class='g-recaptcha'></div></form>",
+            "<p>post</p>".repeat(10_000)
+        );
+        assert_eq!(detect_captcha(&page(&gated)), Some(CaptchaKind::Recaptcha));
+    }
+}
@@ -0,0 +1,256 @@
+//! VCR-style record/replay for Tor traffic
+//!
+//! A cassette is a JSON file of recorded request/response pairs. In
+//! [`CassetteMode::Record`] every live response is appended to the cassette;
+//! in [`CassetteMode::Replay`] responses are served from it and Tor is never
+//! touched, so parsing changes can be validated against real engine HTML in
+//! tests and CI.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use crate::{Cacheability, FetchResponse, TorError};
+
+/// Current cassette file format version
+pub const CASSETTE_VERSION: u32 = 1;
+
+/// Whether a cassette is being written or read
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CassetteMode {
+    /// Perform live requests and append responses to the cassette
+    Record,
+    /// Serve responses from the cassette without network access
+    Replay,
+}
+
+/// A single recorded request/response pair
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Interaction {
+    /// HTTP method (e.g. "GET")
+    pub method: String,
+    /// Request URL
+    pub url: String,
+    /// Request body, for POST requests
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub request_body: Option<String>,
+    /// HTTP status code
+    pub status: u16,
+    /// Response body
+    pub body: String,
+}
+
+/// On-disk cassette document
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CassetteFile {
+    version: u32,
+    interactions: Vec<Interaction>,
+}
+
+#[derive(Debug, Default)]
+struct CassetteState {
+    interactions: Vec<Interaction>,
+    /// Next replay index per request key, so repeated requests walk the recording
+    cursors: HashMap<String, usize>,
+}
+
+/// Shared handle to a cassette
+///
+/// Cloning is cheap and all clones share the same recording, so one cassette
+/// can be handed to every agent through [`crate::TorConfig`].
+#[derive(Debug, Clone)]
+pub struct Cassette {
+    mode: CassetteMode,
+    path: Option<PathBuf>,
+    state: Arc<Mutex<CassetteState>>,
+}
+
+impl Cassette {
+    /// Start a new recording that is written to `path` after every interaction
+    pub fn record<P: AsRef<Path>>(path: P) -> Self {
+        Self {
+            mode: CassetteMode::Record,
+            path: Some(path.as_ref().to_path_buf()),
+            state: Arc::default(),
+        }
+    }
+
+    /// Load a cassette from `path` for replay
+    pub fn replay<P: AsRef<Path>>(path: P) -> Result<Self, TorError> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| TorError::Cassette(format!("failed to read {}: {}", path.display(), e)))?;
+        let mut cassette = Self::from_json(&content)?;
+        cassette.path = Some(path.to_path_buf());
+        Ok(cassette)
+    }
+
+    /// Parse a replay cassette from its JSON representation
+    pub fn from_json(json: &str) -> Result<Self, TorError> {
+        let file: CassetteFile =
+            serde_json::from_str(json).map_err(|e| TorError::Cassette(e.to_string()))?;
+
+        if file.version != CASSETTE_VERSION {
+            return Err(TorError::Cassette(format!(
+                "unsupported cassette version {} (expected {})",
+                file.version, CASSETTE_VERSION
+            )));
+        }
+
+        Ok(Self::from_interactions(file.interactions))
+    }
+
+    /// Build an in-memory replay cassette (handy for tests)
+    pub fn from_interactions(interactions: Vec<Interaction>) -> Self {
+        Self {
+            mode: CassetteMode::Replay,
+            path: None,
+            state: Arc::new(Mutex::new(CassetteState {
+                interactions,
+                cursors: HashMap::new(),
+            })),
+        }
+    }
+
+    /// Cassette mode
+    pub fn mode(&self) -> CassetteMode {
+        self.mode
+    }
+
+    /// Snapshot of all interactions
+    pub fn interactions(&self) -> Vec<Interaction> {
+        self.state.lock().unwrap().interactions.clone()
+    }
+
+    /// Serve a recorded response
+    ///
+    /// Repeated requests for the same URL (and body) replay successive
+    /// recordings and stick on the last one once exhausted.
+    pub(crate) fn lookup(&self, method: &str, url: &str, body: Option<&str>) -> Result<FetchResponse, TorError> {
+        let mut state = self.state.lock().unwrap();
+
+        let matches: Vec<usize> = state
+            .interactions
+            .iter()
+            .enumerate()
+            .filter(|(_, i)| {
+                i.method.eq_ignore_ascii_case(method) && i.url == url && i.request_body.as_deref() == body
+            })
+            .map(|(idx, _)| idx)
+            .collect();
+
+        if matches.is_empty() {
+            return Err(TorError::Cassette(format!("no recorded response for {} {}", method, url)));
+        }
+
+        let key = format!("{} {} {}", method, url, body.unwrap_or_default());
+        let cursor = state.cursors.entry(key).or_insert(0);
+        let idx = matches[(*cursor).min(matches.len() - 1)];
+        *cursor += 1;
+
+        let interaction = &state.interactions[idx];
+        Ok(FetchResponse {
+            url: interaction.url.clone(),
+            status: interaction.status,
+            body: interaction.body.clone(),
+            cacheability: Cacheability::Unspecified,
+        })
+    }
+
+    /// Append a live response and persist the cassette
+    pub(crate) fn append(
+        &self,
+        method: &str,
+        request_body: Option<&str>,
+        response: &FetchResponse,
+    ) -> Result<(), TorError> {
+        self.state.lock().unwrap().interactions.push(Interaction {
+            method: method.to_string(),
+            url: response.url.clone(),
+            request_body: request_body.map(str::to_string),
+            status: response.status,
+            body: response.body.clone(),
+        });
+        self.save()
+    }
+
+    /// Write the cassette to its path (no-op for in-memory cassettes)
+    pub fn save(&self) -> Result<(), TorError> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+
+        let file = CassetteFile {
+            version: CASSETTE_VERSION,
+            interactions: self.interactions(),
+        };
+        let json = serde_json::to_string_pretty(&file).map_err(|e| TorError::Cassette(e.to_string()))?;
+        std::fs::write(path, json)
+            .map_err(|e| TorError::Cassette(format!("failed to write {}: {}", path.display(), e)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn interaction(url: &str, body: &str) -> Interaction {
+        Interaction {
+            method: "GET".to_string(),
+            url: url.to_string(),
+            request_body: None,
+            status: 200,
+            body: body.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_replay_sequence() {
+        let cassette = Cassette::from_interactions(vec![
+            interaction("http://a.onion/", "first"),
+            interaction("http://a.onion/", "second"),
+        ]);
+
+        assert_eq!(cassette.lookup("GET", "http://a.onion/", None).unwrap().body, "first");
+        assert_eq!(cassette.lookup("GET", "http://a.onion/", None).unwrap().body, "second");
+        // Sticks on the last recording once exhausted
+        assert_eq!(cassette.lookup("GET", "http://a.onion/", None).unwrap().body, "second");
+        assert!(matches!(
+            cassette.lookup("GET", "http://b.onion/", None),
+            Err(TorError::Cassette(_))
+        ));
+    }
+
+    #[test]
+    fn test_record_and_reload() {
+        let path = std::env::temp_dir().join(format!("robin-cassette-{}.json", std::process::id()));
+        let recorder = Cassette::record(&path);
+
+        recorder
+            .append(
+                "GET",
+                None,
+                &FetchResponse {
+                    url: "http://a.onion/".to_string(),
+                    status: 200,
+                    body: "<html>hi</html>".to_string(),
+                    cacheability: Cacheability::Unspecified,
+                },
+            )
+            .unwrap();
+
+        let replay = Cassette::replay(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+
+        assert_eq!(replay.mode(), CassetteMode::Replay);
+        assert_eq!(replay.interactions().len(), 1);
+        assert_eq!(replay.lookup("GET", "http://a.onion/", None).unwrap().body, "<html>hi</html>");
+    }
+
+    #[test]
+    fn test_rejects_unknown_version() {
+        let json = r#"{"version": 99, "interactions": []}"#;
+        assert!(Cassette::from_json(json).is_err());
+    }
+}
@@ -0,0 +1,221 @@
+//! Standalone Tor HTTP client
+//!
+//! [`TorHttp`] wraps a [`TorConfig`] in the request operations code outside
+//! the crawler and scraper needs: GET, form POST and size-capped binary
+//! downloads. Every request gets the shared request path's behaviour
+//! (leak guard, retries with backoff, per-host rate limits and circuit
+//! breaker, session affinity, bandwidth accounting, WARC archiving), and
+//! none of the OSINT parts of the crate are involved, so other projects
+//! can depend on `robin-tor` for this alone:
+//!
+//! ```no_run
+//! # async fn demo() -> Result<(), robin_tor::TorError> {
+//! use robin_tor::{TorConfig, TorHttp};
+//!
+//! let http = TorHttp::new(TorConfig::default());
+//! let page = http.get("http://example.onion/").await?;
+//! let logo = http.download("http://example.onion/logo.png", 1 << 20).await?;
+//! println!("{} {} bytes, logo {} bytes", page.status, page.body.len(), logo.len());
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`api_client`] builds the plain clients agents use for clearnet APIs,
+//! routed per their [`crate::Egress`].
+
+use reqwest::{Client, Proxy};
+use std::time::Duration;
+use tracing::debug;
+
+use robin_core::Network;
+
+use crate::{
+    check_request, classify_error, create_i2p_client, create_pooled_client, fetch, fetch::read_body, fetch_request,
+    rate_limit::host_key, retry_delay, FailureKind, FetchRequest, FetchResponse, TorConfig, TorError, Transfer,
+};
+
+/// HTTP client for requests through Tor (and I2P for `.i2p` hosts)
+///
+/// Cloning copies the configuration; rate limits, the circuit breaker,
+/// metrics, bandwidth counters and the archive stay shared between clones.
+#[derive(Debug, Clone, Default)]
+pub struct TorHttp {
+    config: TorConfig,
+}
+
+impl From<TorConfig> for TorHttp {
+    fn from(config: TorConfig) -> Self {
+        Self::new(config)
+    }
+}
+
+impl TorHttp {
+    pub fn new(config: TorConfig) -> Self {
+        Self { config }
+    }
+
+    /// Configuration requests are made with
+    pub fn config(&self) -> &TorConfig {
+        &self.config
+    }
+
+    /// A clone whose requests are charged to the agent `id`
+    pub fn for_agent(&self, id: &str) -> Self {
+        Self::new(self.config.for_agent(id))
+    }
+
+    /// GET `url`; see [`fetch_request`] for caching, retries and errors
+    pub async fn get(&self, url: &str) -> Result<FetchResponse, TorError> {
+        fetch(url, &self.config).await
+    }
+
+    /// POST a form-encoded `body` to `url`
+    pub async fn post_form(&self, url: &str, body: &str) -> Result<FetchResponse, TorError> {
+        fetch_request(&FetchRequest::post_form(url, body), &self.config).await
+    }
+
+    /// Send an arbitrary request
+    pub async fn send(&self, request: &FetchRequest) -> Result<FetchResponse, TorError> {
+        fetch_request(request, &self.config).await
+    }
+
+    /// GET binary content of at most `max_bytes`; see [`download`]
+    pub async fn download(&self, url: &str, max_bytes: usize) -> Result<Vec<u8>, TorError> {
+        download(url, max_bytes, &self.config).await
+    }
+}
+
+/// GET binary content, giving up once it grows past `max_bytes`
+///
+/// Goes through the leak guard, crawl policy, rate limit, circuit breaker
+/// and bandwidth cap like [`fetch_request`], and retries timeouts and
+/// connection failures the same way. Downloads are not cached, recorded
+/// or archived, and non-2xx statuses are errors.
+pub async fn download(url: &str, max_bytes: usize, config: &TorConfig) -> Result<Vec<u8>, TorError> {
+    check_request(url, config)?;
+    config.crawl_policy.check(url).map_err(TorError::OutOfScope)?;
+    let host = host_key(url).ok_or_else(|| TorError::InvalidUrl(url.to_string()))?;
+
+    let mut attempt = 0;
+    loop {
+        let permit = config.breaker.admit(&host)?;
+        let result = download_once(url, &host, max_bytes, config).await;
+        let failure = result.as_ref().err().and_then(classify_error);
+        match &result {
+            Ok(_) => permit.record(true),
+            Err(_) if matches!(failure, Some(FailureKind::Timeout | FailureKind::Connect)) => permit.record(false),
+            Err(_) => drop(permit),
+        }
+        let Some(kind) = failure.filter(|_| attempt < config.max_retries) else {
+            return result;
+        };
+
+        let delay = retry_delay(config, kind, attempt);
+        attempt += 1;
+        debug!("Download of {} failed ({}); retry {}/{} in {:?}", url, kind, attempt, config.max_retries, delay);
+        tokio::time::sleep(delay).await;
+    }
+}
+
+async fn download_once(url: &str, host: &str, max_bytes: usize, config: &TorConfig) -> Result<Vec<u8>, TorError> {
+    config.bandwidth.check()?;
+    if let Some(per_minute) = config.requests_per_host_per_minute {
+        config.rate_limiter.acquire(host, per_minute).await;
+    }
+    let client = match Network::of_url(url) {
+        Some(Network::I2p) => {
+            let i2p = config.i2p.as_ref().ok_or_else(|| TorError::I2pNotConfigured(url.to_string()))?;
+            create_i2p_client(i2p)?
+        }
+        _ => create_pooled_client(config, Some(url))?.0,
+    };
+
+    let response = client.get(url).send().await.map_err(|e| match e.is_timeout() {
+        true => TorError::Timeout(config.timeout_secs),
+        false => e.into(),
+    })?;
+    if !response.status().is_success() {
+        return Err(TorError::Status {
+            url: url.to_string(),
+            status: response.status().as_u16(),
+        });
+    }
+    let (bytes, result) = read_body(response, url, max_bytes).await;
+    config.bandwidth.charge(
+        host,
+        config.agent.as_deref(),
+        Transfer {
+            sent: url.len() as u64,
+            received: bytes.len() as u64,
+        },
+    );
+    result.map(|_| bytes)
+}
+
+/// Plain client for clearnet API calls, through `proxy` when given (None = direct)
+///
+/// Use [`crate::Egress::proxy_url`] to pick the proxy.
+pub fn api_client(proxy: Option<&str>, timeout: Duration, user_agent: Option<&str>) -> Result<Client, TorError> {
+    let mut builder = Client::builder().timeout(timeout);
+    if let Some(user_agent) = user_agent {
+        builder = builder.user_agent(user_agent);
+    }
+    if let Some(proxy) = proxy {
+        let proxy = Proxy::all(proxy).map_err(|e| TorError::ClientBuild(format!("invalid proxy '{}': {}", proxy, e)))?;
+        builder = builder.proxy(proxy);
+    }
+    builder.build().map_err(|e| TorError::ClientBuild(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    #[tokio::test]
+    async fn test_download_through_proxy() {
+        // A plain HTTP "proxy" that answers every request itself
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let mut request = [0u8; 1024];
+                let n = stream.read(&mut request).await.unwrap_or(0);
+                let response = if String::from_utf8_lossy(&request[..n]).contains("/missing") {
+                    "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\n\r\n"
+                } else {
+                    "HTTP/1.1 200 OK\r\nContent-Length: 4\r\n\r\nPNG!"
+                };
+                let _ = stream.write_all(response.as_bytes()).await;
+            }
+        });
+
+        let http = TorHttp::new(TorConfig {
+            socks_addr: format!("http://{}", addr),
+            max_retries: 0,
+            ..Default::default()
+        })
+        .for_agent("scraper-1");
+        assert_eq!(http.download("http://example.test/logo.png", 1024).await.unwrap(), b"PNG!");
+        assert!(matches!(
+            http.download("http://example.test/logo.png", 2).await,
+            Err(TorError::BodyTooLarge { limit: 2, .. })
+        ));
+        assert!(matches!(
+            http.download("http://example.test/missing.png", 1024).await,
+            Err(TorError::Status { status: 404, .. })
+        ));
+        let bandwidth = http.config().bandwidth.snapshot();
+        assert_eq!(bandwidth.agents["scraper-1"].received, 4);
+    }
+
+    #[test]
+    fn test_api_client_validates_proxy() {
+        assert!(api_client(None, Duration::from_secs(5), Some("Robin-SMESH/1.0")).is_ok());
+        assert!(api_client(Some("socks5h://127.0.0.1:9050"), Duration::from_secs(5), None).is_ok());
+        assert!(matches!(
+            api_client(Some("not a proxy"), Duration::from_secs(5), None),
+            Err(TorError::ClientBuild(_))
+        ));
+    }
+}
@@ -1,23 +1,56 @@
 //! Dark web search engine crawler
 //!
-//! Queries .onion search engines and extracts result links.
+//! Queries .onion (and .i2p) search engines and extracts result links.
+//! Engines with configured [`ResultSelectors`] are parsed with them; the
+//! generic link scan is the fallback. Clearnet engines are parsed with their
+//! selectors only, keeping surface-web links (unwrapping redirect links). Engines with [`robin_core::Pagination`] can be
+//! crawled several pages deep.
 
-use scraper::{Html, Selector};
+use scraper::{ElementRef, Html, Selector};
 use std::collections::HashSet;
+use std::sync::LazyLock;
+use std::time::Instant;
 use tracing::{debug, warn};
 
-use crate::{create_tor_client, TorConfig, TorError};
-use robin_core::SearchEngine;
+use crate::{fetch_request, FetchRequest, TorConfig, TorError};
+use robin_core::{HttpMethod, Network, ResultSelectors, SearchEngine};
+
+/// Hidden service URLs, also when wrapped in an engine's redirect link
+static HIDDEN_SERVICE_URL: LazyLock<regex::Regex> = LazyLock::new(|| {
+    regex::Regex::new(r#"https?://(?:[a-z0-9\.]+\.onion|[a-z0-9\.\-]+\.i2p)[^\s"'<>]*"#).unwrap()
+});
 
 /// A search result from a dark web search engine
 #[derive(Debug, Clone)]
 pub struct SearchResult {
     /// Result title
     pub title: String,
-    /// Result URL (typically .onion)
+    /// Result URL (.onion or .i2p; any http(s) URL for clearnet engines)
     pub url: String,
     /// Source search engine
     pub engine: String,
+    /// Result description, when the engine's selectors find one
+    pub snippet: Option<String>,
+}
+
+/// The HTTP request that searches `engine` for `query`
+///
+/// GET engines take the query in the URL; POST engines in the form body.
+pub fn engine_request(engine: &SearchEngine, query: &str) -> FetchRequest {
+    engine_page_request(engine, query, 0).expect("every engine has a first page")
+}
+
+/// The request for result page `page` (0 = first), if the engine paginates that far
+pub fn engine_page_request(engine: &SearchEngine, query: &str, page: u32) -> Option<FetchRequest> {
+    let url = engine.build_page_url(query, page)?;
+    let mut request = match (engine.method, engine.build_page_body(query, page)) {
+        (HttpMethod::Post, body) => FetchRequest::post_form(&url, &body.unwrap_or_default()),
+        (HttpMethod::Get, _) => FetchRequest::get(&url),
+    };
+    for (name, value) in &engine.headers {
+        request = request.with_header(name, value);
+    }
+    Some(request)
 }
 
 /// Crawl a search engine for results
@@ -26,70 +59,123 @@ pub async fn crawl_engine(
     query: &str,
     config: &TorConfig,
 ) -> Result<Vec<SearchResult>, TorError> {
-    let client = create_tor_client(config)?;
-    let url = engine.build_url(query);
-
     debug!("Crawling {} with query: {}", engine.name, query);
+    let selectors = engine_selectors(engine, config);
+    crawl_url(&engine_request(engine, query), &engine.name, engine.network(), selectors, config).await
+}
 
-    let response = client.get(&url).send().await?;
+/// The engine's own selectors, else those configured for its name
+pub(crate) fn engine_selectors<'a>(engine: &'a SearchEngine, config: &'a TorConfig) -> Option<&'a ResultSelectors> {
+    engine.selectors.as_ref().or_else(|| config.engine_parsers.get(&engine.name))
+}
 
-    if !response.status().is_success() {
-        warn!("Engine {} returned status: {}", engine.name, response.status());
-        return Ok(Vec::new());
+/// Crawl up to `max_pages` result pages of an engine, one after another
+///
+/// Stops early when a page fails or brings no new results (engines that
+/// ignore the page parameter serve the first page again).
+async fn crawl_pages(
+    requests: &[FetchRequest],
+    engine_name: &str,
+    network: Network,
+    selectors: Option<&ResultSelectors>,
+    config: &TorConfig,
+) -> Result<Vec<SearchResult>, TorError> {
+    let mut results: Vec<SearchResult> = Vec::new();
+    for (page, request) in requests.iter().enumerate() {
+        let page_results = match crawl_url(request, engine_name, network, selectors, config).await {
+            Ok(page_results) => page_results,
+            Err(e) if page == 0 => return Err(e),
+            Err(e) => {
+                debug!("Engine {} page {} failed: {}", engine_name, page + 1, e);
+                break;
+            }
+        };
+        let before = results.len();
+        for result in page_results {
+            if !results.iter().any(|r| r.url == result.url) {
+                results.push(result);
+            }
+        }
+        if results.len() == before {
+            break;
+        }
     }
-
-    let html = response.text().await?;
-    let results = parse_search_results(&html, engine.name);
-
-    debug!("Engine {} returned {} results", engine.name, results.len());
     Ok(results)
 }
 
-/// Crawl a URL directly (for use in async contexts)
+/// Crawl a prepared engine request, recording it in the engine's metrics
 async fn crawl_url(
-    url: &str,
+    request: &FetchRequest,
     engine_name: &str,
+    network: Network,
+    selectors: Option<&ResultSelectors>,
     config: &TorConfig,
 ) -> Result<Vec<SearchResult>, TorError> {
-    let client = create_tor_client(config)?;
-
-    debug!("Crawling URL: {}", url);
-
-    let response = client.get(url).send().await?;
+    debug!("Crawling {} {}", request.method, request.url);
+
+    let started = Instant::now();
+    let result = fetch_request(request, config).await;
+    let (bytes, ok) = match &result {
+        Ok(response) => (response.body.len(), response.is_success()),
+        Err(_) => (0, false),
+    };
+    if !config.is_replay() {
+        config.metrics.record_engine(engine_name, started.elapsed(), bytes, ok);
+        config.engine_health.record(engine_name, started.elapsed(), ok);
+    }
+    let response = result?;
 
-    if !response.status().is_success() {
-        warn!("URL {} returned status: {}", url, response.status());
+    if !response.is_success() {
+        warn!("Engine {} returned status: {}", engine_name, response.status);
         return Ok(Vec::new());
     }
 
-    let html = response.text().await?;
-    let results = parse_search_results(&html, engine_name);
+    let mut results = parse_engine_results(&response.body, engine_name, network, selectors);
+    results.retain(|r| config.crawl_policy.permits_result(&r.url, &r.title));
 
-    debug!("URL returned {} results", results.len());
+    debug!("Engine {} returned {} results", engine_name, results.len());
     Ok(results)
 }
 
-/// Crawl multiple search engines concurrently
+/// Crawl multiple search engines concurrently, up to `max_pages` result pages each
+///
+/// Engines benched by `config.engine_health` are skipped.
 pub async fn crawl_engines(
     engines: &[&SearchEngine],
     query: &str,
     config: &TorConfig,
     max_concurrent: usize,
+    max_pages: u32,
 ) -> Vec<SearchResult> {
     use futures::stream::{self, StreamExt};
 
     // Clone engine data to avoid lifetime issues with async closures
-    let engine_data: Vec<(String, String)> = engines
+    let (engines, benched): (Vec<&SearchEngine>, Vec<&SearchEngine>) =
+        engines.iter().partition(|e| config.is_replay() || config.engine_health.is_available(&e.name));
+    if !benched.is_empty() {
+        debug!(
+            "Skipping benched engines: {}",
+            benched.iter().map(|e| e.name.as_str()).collect::<Vec<_>>().join(", ")
+        );
+    }
+    let engine_data: Vec<(String, Network, Vec<FetchRequest>, Option<ResultSelectors>)> = engines
         .iter()
-        .map(|e| (e.name.to_string(), e.build_url(query)))
+        .map(|e| {
+            let pages = (0..max_pages.max(1)).map_while(|page| engine_page_request(e, query, page)).collect();
+            (e.name.clone(), e.network(), pages, engine_selectors(e, config).cloned())
+        })
         .collect();
 
     let results: Vec<_> = stream::iter(engine_data)
-        .map(|(name, url)| {
+        .map(|(name, network, requests, selectors)| {
             let config = config.clone();
             async move {
-                match crawl_url(&url, &name, &config).await {
+                match crawl_pages(&requests, &name, network, selectors.as_ref(), &config).await {
                     Ok(results) => results,
+                    Err(e @ TorError::CaptchaRequired { .. }) => {
+                        warn!("Engine {} skipped: {}", name, e);
+                        Vec::new()
+                    }
                     Err(e) => {
                         warn!("Engine {} failed: {}", name, e);
                         Vec::new()
@@ -116,13 +202,111 @@ pub async fn crawl_engines(
     deduped
 }
 
+/// Parse a result page with the engine's selectors, falling back to [`parse_search_results`]
+///
+/// The fallback also covers invalid selectors and selectors that match
+/// nothing (e.g. after the engine changed its markup). Clearnet engines have
+/// no fallback: a generic scan of a surface-web page finds only navigation.
+pub fn parse_engine_results(
+    html: &str,
+    engine_name: &str,
+    network: Network,
+    selectors: Option<&ResultSelectors>,
+) -> Vec<SearchResult> {
+    if let Some(selectors) = selectors {
+        match parse_with_selectors(html, engine_name, network, selectors) {
+            Ok(results) if !results.is_empty() => return results,
+            Ok(_) => debug!("Selectors for {} matched no results; using generic parser", engine_name),
+            Err(e) => warn!("Invalid selectors for {}: {}", engine_name, e),
+        }
+    }
+    if network == Network::Clearnet {
+        debug!("No usable selectors for clearnet engine {}", engine_name);
+        return Vec::new();
+    }
+    parse_search_results(html, engine_name)
+}
+
+/// Parse results located by `selectors`
+pub fn parse_with_selectors(
+    html: &str,
+    engine_name: &str,
+    network: Network,
+    selectors: &ResultSelectors,
+) -> Result<Vec<SearchResult>, String> {
+    let link_url = match network {
+        Network::Clearnet => clearnet_result_url,
+        Network::Tor | Network::I2p => result_url,
+    };
+    let parse = |css: &str| Selector::parse(css).map_err(|e| format!("'{}': {}", css, e));
+    let container = parse(&selectors.container)?;
+    let link = parse(&selectors.link)?;
+    let title = selectors.title.as_deref().map(parse).transpose()?;
+    let snippet = selectors.snippet.as_deref().map(parse).transpose()?;
+
+    let document = Html::parse_document(html);
+    let mut results = Vec::new();
+    for item in document.select(&container) {
+        let Some(anchor) = item.select(&link).next() else {
+            continue;
+        };
+        let Some(url) = anchor.value().attr("href").and_then(link_url) else {
+            continue;
+        };
+        let title = match &title {
+            Some(selector) => item.select(selector).next().map(element_text),
+            None => Some(element_text(anchor)),
+        }
+        .unwrap_or_default();
+        if title.len() < 3 {
+            continue;
+        }
+        results.push(SearchResult {
+            title,
+            url,
+            engine: engine_name.to_string(),
+            snippet: snippet
+                .as_ref()
+                .and_then(|selector| item.select(selector).next())
+                .map(element_text)
+                .filter(|s| !s.is_empty()),
+        });
+    }
+    Ok(results)
+}
+
+/// The .onion / .i2p URL in a result link, unwrapping engine redirects
+fn result_url(href: &str) -> Option<String> {
+    let url = HIDDEN_SERVICE_URL.find(href)?.as_str();
+    // The suffix must end the host (not e.g. `x.i2pfake.com`)
+    Network::of_url(url).map(|_| url.to_string())
+}
+
+/// The absolute http(s) URL in a clearnet result link, unwrapping `uddg=` redirects (DuckDuckGo)
+fn clearnet_result_url(href: &str) -> Option<String> {
+    let href = match href.strip_prefix("//") {
+        Some(rest) => format!("https://{}", rest),
+        None => href.to_string(),
+    };
+    let url = reqwest::Url::parse(&href).ok()?;
+    if let Some((_, target)) = url.query_pairs().find(|(k, _)| k == "uddg") {
+        return clearnet_result_url(&target);
+    }
+    matches!(url.scheme(), "http" | "https").then(|| url.to_string())
+}
+
+fn element_text(element: ElementRef) -> String {
+    element.text().collect::<Vec<_>>().join(" ").split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
 /// Parse search results from HTML
-fn parse_search_results(html: &str, engine_name: &str) -> Vec<SearchResult> {
+///
+/// Keeps `.onion` and `.i2p` links with a meaningful title, skipping engine self-links.
+pub fn parse_search_results(html: &str, engine_name: &str) -> Vec<SearchResult> {
     let document = Html::parse_document(html);
     let link_selector = Selector::parse("a").unwrap();
 
     let mut results = Vec::new();
-    let onion_regex = regex::Regex::new(r#"https?://[a-z0-9\.]+\.onion[^\s"'<>]*"#).unwrap();
 
     for element in document.select(&link_selector) {
         let href = match element.value().attr("href") {
@@ -130,10 +314,8 @@ fn parse_search_results(html: &str, engine_name: &str) -> Vec<SearchResult> {
             None => continue,
         };
 
-        // Extract .onion URLs
-        if let Some(m) = onion_regex.find(href) {
-            let url = m.as_str().to_string();
-
+        // Extract .onion / .i2p URLs
+        if let Some(url) = result_url(href) {
             // Skip search engine self-links
             if url.contains("search") || url.contains("query") {
                 continue;
@@ -154,6 +336,7 @@ fn parse_search_results(html: &str, engine_name: &str) -> Vec<SearchResult> {
                 title,
                 url,
                 engine: engine_name.to_string(),
+                snippet: None,
             });
         }
     }
@@ -180,7 +363,144 @@ mod tests {
         let results = parse_search_results(html, "TestEngine");
         
         // Should include the first and third, skip the search link
-        assert!(results.len() >= 1);
+        assert!(!results.is_empty());
         assert!(results.iter().all(|r| !r.url.contains("search")));
     }
+
+    #[test]
+    fn test_parse_eepsite_results() {
+        let html = r#"
+            <a href="http://forum-dev.i2p/thread/42">I2P Forum Thread</a>
+            <a href="http://ukeu3k5oycgaauneqgtnvselmt4yemvoilkln7jpvamvfx7dnkdq.b32.i2p/">B32 Site</a>
+            <a href="https://example.i2pfake.com/">Clearnet</a>
+        "#;
+
+        let results = parse_search_results(html, "Legwork");
+        assert_eq!(results.len(), 2);
+        assert!(results.iter().all(|r| r.url.contains(".i2p/")));
+    }
+
+    #[tokio::test]
+    async fn test_crawl_engine_from_cassette() {
+        let cassette = crate::Cassette::replay(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/cassettes/ahmia_ransomware.json"
+        ))
+        .unwrap();
+        let config = TorConfig {
+            cassette: Some(cassette),
+            ..Default::default()
+        };
+
+        let ahmia = config.search_engines.get("Ahmia").unwrap();
+        let results = crawl_engine(ahmia, "ransomware payments", &config).await.unwrap();
+
+        // Redirect links are unwrapped; short titles and pagination links are skipped
+        assert_eq!(results.len(), 3);
+        assert!(results.iter().all(|r| r.engine == "Ahmia"));
+        assert!(results[0].title.contains("payment instructions"));
+        assert!(results[1].url.ends_with(".onion/blog"));
+        // Parsed with Ahmia's bundled selectors
+        assert_eq!(results[1].snippet.as_deref(), Some("Data of companies that refused to pay."));
+    }
+
+    #[test]
+    fn test_selector_fallback() {
+        let html = r#"<div class="hit"><a href="http://leaksite1234567890abcdef.onion/">LockBit leak site</a>
+            <span class="desc">Victim list</span></div>
+            <a href="http://other1234567890abcdef.onion/">Unlisted result</a>"#;
+        let selectors = ResultSelectors {
+            container: "div.hit".to_string(),
+            link: "a".to_string(),
+            title: None,
+            snippet: Some("span.desc".to_string()),
+        };
+        let results = parse_engine_results(html, "Custom", Network::Tor, Some(&selectors));
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].snippet.as_deref(), Some("Victim list"));
+
+        // Selectors that match nothing, or don't parse, use the generic scan
+        let stale = ResultSelectors { container: "li.result".to_string(), ..selectors.clone() };
+        assert_eq!(parse_engine_results(html, "Custom", Network::Tor, Some(&stale)).len(), 2);
+        let invalid = ResultSelectors { container: "div[".to_string(), ..selectors };
+        assert_eq!(parse_engine_results(html, "Custom", Network::Tor, Some(&invalid)).len(), 2);
+        // ... except on clearnet engines
+        assert!(parse_engine_results(html, "Custom", Network::Clearnet, Some(&stale)).is_empty());
+    }
+
+    #[test]
+    fn test_clearnet_results() {
+        let html = r#"<div class="result"><a class="result__a"
+                href="//duckduckgo.com/l/?uddg=https%3A%2F%2Fnews.example.com%2Facme%2Dbreach%3Fa%3D1&amp;rut=x">Acme breach reported</a>
+                <a class="result__snippet">Acme Corp confirms</a></div>
+            <div class="result"><a class="result__a" href="/settings">Settings</a></div>
+            <div class="result"><a class="result__a" href="http://leaksite1234567890abcdef.onion/">Acme on a leak site</a></div>"#;
+        let ddg = robin_core::SearchEngineRegistry::embedded().get("DuckDuckGo").unwrap().clone();
+        assert_eq!(ddg.network(), Network::Clearnet);
+
+        let results = parse_engine_results(html, &ddg.name, ddg.network(), ddg.selectors.as_ref());
+        let urls: Vec<&str> = results.iter().map(|r| r.url.as_str()).collect();
+        assert_eq!(urls, vec!["https://news.example.com/acme-breach?a=1", "http://leaksite1234567890abcdef.onion/"]);
+        assert_eq!(results[0].snippet.as_deref(), Some("Acme Corp confirms"));
+    }
+
+    #[tokio::test]
+    async fn test_crawl_post_engine() {
+        let engine = SearchEngine {
+            method: HttpMethod::Post,
+            body_template: Some("q={query}&lang=any".to_string()),
+            headers: vec![("Referer".to_string(), "http://form.onion/".to_string())],
+            ..SearchEngine::new("FormEngine", "http://form.onion/search")
+        };
+        let request = engine_request(&engine, "lockbit");
+        assert_eq!(request.body.as_deref(), Some("q=lockbit&lang=any"));
+        assert_eq!(request.headers, vec![("Referer".to_string(), "http://form.onion/".to_string())]);
+
+        let config = TorConfig {
+            cassette: Some(crate::Cassette::from_interactions(vec![crate::Interaction {
+                method: "POST".to_string(),
+                url: "http://form.onion/search".to_string(),
+                request_body: Some("q=lockbit&lang=any".to_string()),
+                status: 200,
+                body: r#"<a href="http://leaksite1234567890abcdef.onion/">LockBit leak site</a>"#.to_string(),
+            }])),
+            ..Default::default()
+        };
+        let results = crawl_engine(&engine, "lockbit", &config).await.unwrap();
+        assert_eq!(results.len(), 1);
+
+        // A different query is a different recorded request
+        assert!(crawl_engine(&engine, "conti", &config).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_crawl_engines_follows_pages() {
+        let engine = SearchEngine {
+            pagination: Some(robin_core::Pagination::Page { param: "page".to_string(), first: 1 }),
+            ..SearchEngine::new("PagedEngine", "http://paged.onion/search?q={query}")
+        };
+        let page = |url: &str, body: &str| crate::Interaction {
+            method: "GET".to_string(),
+            url: url.to_string(),
+            request_body: None,
+            status: 200,
+            body: body.to_string(),
+        };
+        let first = r#"<a href="http://leaksite1234567890abcdef.onion/">LockBit leak site</a>"#;
+        let config = TorConfig {
+            cassette: Some(crate::Cassette::from_interactions(vec![
+                page("http://paged.onion/search?q=lockbit", first),
+                page(
+                    "http://paged.onion/search?q=lockbit&page=2",
+                    r#"<a href="http://mirror1234567890abcdef.onion/">LockBit mirror</a>"#,
+                ),
+                // Past the last page the engine serves the first one again, which ends the crawl
+                page("http://paged.onion/search?q=lockbit&page=3", first),
+            ])),
+            ..Default::default()
+        };
+
+        assert_eq!(crawl_engines(&[&engine], "lockbit", &config, 1, 1).await.len(), 1);
+        assert_eq!(crawl_engines(&[&engine], "lockbit", &config, 1, 5).await.len(), 2);
+    }
 }
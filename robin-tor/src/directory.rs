@@ -0,0 +1,219 @@
+//! Onion link directory ingestion
+//!
+//! Hidden Wiki style directories list onion services under category
+//! headings, usually with a one-line description, and surface sites that
+//! search engines never index. [`load_directory`] reads a directory from an
+//! onion URL or a local file, and [`match_directory`] ranks its entries
+//! against a query without any further requests.
+
+use scraper::{ElementRef, Html, Selector};
+use std::collections::HashSet;
+
+use crate::{fetch, TorConfig, TorError};
+use robin_core::Network;
+
+/// Longest description kept per entry, in characters
+const MAX_DESCRIPTION: usize = 200;
+
+/// A site listed in a link directory
+#[derive(Debug, Clone, PartialEq)]
+pub struct DirectoryEntry {
+    /// Listed URL (.onion or .i2p)
+    pub url: String,
+    /// Link text
+    pub title: String,
+    /// Text next to the link
+    pub description: String,
+    /// Heading the link is listed under
+    pub category: Option<String>,
+    /// Directory the entry came from
+    pub source: String,
+}
+
+impl DirectoryEntry {
+    /// Title and description as one line, for result lists
+    pub fn label(&self) -> String {
+        if self.description.is_empty() {
+            self.title.clone()
+        } else {
+            format!("{} - {}", self.title, self.description)
+        }
+    }
+}
+
+/// Load a directory from an `http(s)://` URL (fetched through Tor) or a local file
+///
+/// Files may be HTML or plain text with one link per line and `#` headings.
+pub async fn load_directory(source: &str, config: &TorConfig) -> Result<Vec<DirectoryEntry>, TorError> {
+    let body = if source.starts_with("http://") || source.starts_with("https://") {
+        fetch(source, config).await?.body
+    } else {
+        std::fs::read_to_string(source)
+            .map_err(|e| TorError::InvalidUrl(format!("cannot read directory {}: {}", source, e)))?
+    };
+
+    let mut entries = if body.contains("<a ") || body.contains("<A ") {
+        parse_directory(&body, source)
+    } else {
+        parse_link_list(&body, source)
+    };
+    entries.retain(|e| config.crawl_policy.permits_result(&e.url, &e.label()));
+    Ok(entries)
+}
+
+/// Parse an HTML directory page
+pub fn parse_directory(html: &str, source: &str) -> Vec<DirectoryEntry> {
+    let document = Html::parse_document(html);
+    let selector = Selector::parse("h1, h2, h3, h4, a").unwrap();
+    let mut seen = HashSet::new();
+    let mut category = None;
+    let mut entries = Vec::new();
+
+    for element in document.select(&selector) {
+        let text = collapse(&element.text().collect::<String>());
+        if element.value().name() != "a" {
+            category = Some(text).filter(|t| !t.is_empty());
+            continue;
+        }
+        let Some(url) = element.value().attr("href").and_then(hidden_service_url) else {
+            continue;
+        };
+        if !seen.insert(normalize(&url)) {
+            continue;
+        }
+
+        // Description: the rest of the list item / paragraph around the link
+        let context = element
+            .parent()
+            .and_then(ElementRef::wrap)
+            .map(|parent| collapse(&parent.text().collect::<String>()))
+            .unwrap_or_default();
+        let description = context.replacen(&text, "", 1);
+        let description = description.trim_matches(|c: char| c.is_whitespace() || "-–—:|".contains(c));
+
+        entries.push(DirectoryEntry {
+            title: if text.is_empty() { url.clone() } else { text },
+            url,
+            description: description.chars().take(MAX_DESCRIPTION).collect(),
+            category: category.clone(),
+            source: source.to_string(),
+        });
+    }
+    entries
+}
+
+/// Parse a plain-text link list (`# Heading` lines start a category)
+pub fn parse_link_list(text: &str, source: &str) -> Vec<DirectoryEntry> {
+    let mut seen = HashSet::new();
+    let mut category = None;
+    let mut entries = Vec::new();
+
+    for line in text.lines().map(str::trim) {
+        if let Some(heading) = line.strip_prefix('#') {
+            category = Some(heading.trim_start_matches('#').trim().to_string()).filter(|h| !h.is_empty());
+            continue;
+        }
+        let Some((url, rest)) = line
+            .split_whitespace()
+            .find_map(hidden_service_url)
+            .map(|url| (url.clone(), line.replacen(&url, "", 1)))
+        else {
+            continue;
+        };
+        if !seen.insert(normalize(&url)) {
+            continue;
+        }
+        let rest = collapse(rest.trim_matches(|c: char| c.is_whitespace() || "-–—:|*".contains(c)));
+        entries.push(DirectoryEntry {
+            title: if rest.is_empty() { url.clone() } else { rest.chars().take(80).collect() },
+            url,
+            description: String::new(),
+            category: category.clone(),
+            source: source.to_string(),
+        });
+    }
+    entries
+}
+
+/// Up to `limit` entries relevant to `query`, best first
+///
+/// Entries score by the query terms found in their title, description and
+/// category (title hits count double); entries without a hit are dropped.
+pub fn match_directory<'a>(entries: &'a [DirectoryEntry], query: &str, limit: usize) -> Vec<&'a DirectoryEntry> {
+    let terms: HashSet<String> = query
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|t| t.chars().count() >= 3)
+        .map(str::to_lowercase)
+        .collect();
+
+    let mut scored: Vec<(usize, &DirectoryEntry)> = entries
+        .iter()
+        .filter_map(|entry| {
+            let title = entry.title.to_lowercase();
+            let rest = format!("{} {}", entry.description, entry.category.as_deref().unwrap_or_default()).to_lowercase();
+            let score: usize = terms
+                .iter()
+                .map(|t| if title.contains(t.as_str()) { 2 } else { usize::from(rest.contains(t.as_str())) })
+                .sum();
+            (score > 0).then_some((score, entry))
+        })
+        .collect();
+    scored.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+    scored.into_iter().take(limit).map(|(_, entry)| entry).collect()
+}
+
+/// `candidate` if it is an absolute .onion or .i2p URL
+fn hidden_service_url(candidate: &str) -> Option<String> {
+    let candidate = candidate.trim_matches(|c: char| "<>()[]\"'".contains(c));
+    (candidate.starts_with("http") && Network::of_url(candidate).is_some()).then(|| candidate.to_string())
+}
+
+fn normalize(url: &str) -> String {
+    url.trim_end_matches('/').to_lowercase()
+}
+
+fn collapse(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WIKI: &str = r#"<html><body>
+        <h2>Marketplaces</h2>
+        <ul>
+          <li><a href="http://marketabc.onion/">Abc Market</a> - Drugs, fraud and carding vendors</li>
+          <li><a href="http://marketabc.onion">Abc Market mirror</a></li>
+        </ul>
+        <h2>Forums</h2>
+        <p><a href="http://dreadxyz.onion/">Dread</a>: Reddit-style forum</p>
+        <a href="https://example.com/">clearnet</a>
+    </body></html>"#;
+
+    #[test]
+    fn test_parse_directory_formats() {
+        let entries = parse_directory(WIKI, "wiki");
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].title, "Abc Market");
+        assert_eq!(entries[0].description, "Drugs, fraud and carding vendors");
+        assert_eq!(entries[0].category.as_deref(), Some("Marketplaces"));
+        assert_eq!(entries[1].category.as_deref(), Some("Forums"));
+
+        let list = parse_link_list("# Leaks\nhttp://leaksite.onion/ - ransomware leak blog\n\nnot a link\n", "list.txt");
+        assert_eq!(list.len(), 1);
+        assert_eq!(list[0].title, "ransomware leak blog");
+        assert_eq!(list[0].category.as_deref(), Some("Leaks"));
+    }
+
+    #[test]
+    fn test_match_directory() {
+        let entries = parse_directory(WIKI, "wiki");
+        // Description hits on both entries
+        assert_eq!(match_directory(&entries, "carding forum", 10).len(), 2);
+        // Title hits rank first
+        let matches = match_directory(&entries, "forum market", 10);
+        assert_eq!(matches[0].title, "Abc Market");
+        assert!(match_directory(&entries, "ransomware", 10).is_empty());
+    }
+}
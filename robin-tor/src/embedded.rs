@@ -0,0 +1,256 @@
+//! Embedded Tor client (arti)
+//!
+//! Bootstraps an in-process Tor client and exposes it through a local
+//! SOCKS5 listener, so the rest of the crate keeps talking SOCKS5h and no
+//! external Tor daemon is required.
+//!
+//! Only available with the `embedded-tor` feature; without it,
+//! [`start_tor`] returns [`TorError::Embedded`] for [`TorMode::Embedded`].
+
+use crate::{TorConfig, TorError, TorMode};
+
+/// Handle to a running embedded Tor client
+///
+/// The local SOCKS listener is shut down when the handle is dropped, so keep
+/// it alive for as long as clients built from the config are in use.
+pub struct EmbeddedTor {
+    socks_addr: String,
+    #[cfg(feature = "embedded-tor")]
+    _client: std::sync::Arc<arti_client::TorClient<tor_rtcompat::PreferredRuntime>>,
+    #[cfg(feature = "embedded-tor")]
+    task: tokio::task::JoinHandle<()>,
+}
+
+impl EmbeddedTor {
+    /// SOCKS5h address of the local listener (e.g. `socks5h://127.0.0.1:41234`)
+    pub fn socks_addr(&self) -> &str {
+        &self.socks_addr
+    }
+
+    /// Bootstrap progress of the arti client
+    pub fn bootstrap(&self) -> Result<crate::Bootstrap, TorError> {
+        #[cfg(feature = "embedded-tor")]
+        {
+            let status = self._client.bootstrap_status();
+            Ok(crate::Bootstrap {
+                percent: (status.as_frac() * 100.0).round() as u8,
+                summary: status.to_string(),
+            })
+        }
+        #[cfg(not(feature = "embedded-tor"))]
+        Err(TorError::Embedded(
+            "robin-tor was built without the `embedded-tor` feature".to_string(),
+        ))
+    }
+}
+
+impl Drop for EmbeddedTor {
+    fn drop(&mut self) {
+        #[cfg(feature = "embedded-tor")]
+        self.task.abort();
+    }
+}
+
+/// Prepare Tor according to `config.mode`
+///
+/// For [`TorMode::External`] this is a no-op. For [`TorMode::Embedded`] it
+/// bootstraps arti, starts the local SOCKS listener and rewrites
+/// `config.socks_addr` to point at it.
+pub async fn start_tor(config: &mut TorConfig) -> Result<Option<EmbeddedTor>, TorError> {
+    match config.mode {
+        TorMode::External => Ok(None),
+        TorMode::Embedded if config.upstream_proxy.is_some() => Err(TorError::Embedded(
+            "the embedded client cannot use an upstream proxy; run a Tor daemon with HTTPSProxy/Socks5Proxy instead"
+                .to_string(),
+        )),
+        TorMode::Embedded => {
+            let embedded = bootstrap_embedded(config).await?;
+            config.socks_addr = embedded.socks_addr.clone();
+            Ok(Some(embedded))
+        }
+    }
+}
+
+#[cfg(not(feature = "embedded-tor"))]
+async fn bootstrap_embedded(_config: &TorConfig) -> Result<EmbeddedTor, TorError> {
+    Err(TorError::Embedded(
+        "robin-tor was built without the `embedded-tor` feature".to_string(),
+    ))
+}
+
+#[cfg(feature = "embedded-tor")]
+async fn bootstrap_embedded(config: &TorConfig) -> Result<EmbeddedTor, TorError> {
+    use arti_client::{config::TorClientConfigBuilder, TorClient, TorClientConfig};
+    use tokio::net::TcpListener;
+    use tracing::info;
+
+    let tor_config = match &config.embedded_data_dir {
+        Some(dir) => TorClientConfigBuilder::from_directories(dir.join("state"), dir.join("cache"))
+            .build()
+            .map_err(|e| TorError::Embedded(e.to_string()))?,
+        None => TorClientConfig::default(),
+    };
+
+    info!("Bootstrapping embedded Tor client (arti)...");
+    let client = TorClient::create_bootstrapped(tor_config)
+        .await
+        .map_err(|e| TorError::Embedded(e.to_string()))?;
+    info!("Embedded Tor client bootstrapped");
+
+    let listener = TcpListener::bind("127.0.0.1:0")
+        .await
+        .map_err(|e| TorError::Embedded(format!("failed to bind SOCKS listener: {}", e)))?;
+    let local_addr = listener
+        .local_addr()
+        .map_err(|e| TorError::Embedded(e.to_string()))?;
+
+    let task = tokio::spawn(socks::serve(listener, client.clone()));
+
+    Ok(EmbeddedTor {
+        socks_addr: format!("socks5h://{}", local_addr),
+        _client: client,
+        task,
+    })
+}
+
+/// Minimal SOCKS5 front-end (no auth, CONNECT only) that forwards streams to arti
+#[cfg(feature = "embedded-tor")]
+mod socks {
+    use arti_client::{StreamPrefs, TorClient};
+    use std::io;
+    use std::net::{Ipv4Addr, Ipv6Addr};
+    use std::sync::Arc;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::{TcpListener, TcpStream};
+    use tor_rtcompat::PreferredRuntime;
+    use tracing::{debug, warn};
+
+    const VERSION: u8 = 0x05;
+    const NO_AUTH: u8 = 0x00;
+    const NO_ACCEPTABLE_METHOD: u8 = 0xFF;
+    const CMD_CONNECT: u8 = 0x01;
+    const REPLY_SUCCEEDED: u8 = 0x00;
+    const REPLY_HOST_UNREACHABLE: u8 = 0x04;
+    const REPLY_COMMAND_NOT_SUPPORTED: u8 = 0x07;
+    const REPLY_ADDRESS_NOT_SUPPORTED: u8 = 0x08;
+
+    pub(super) async fn serve(listener: TcpListener, client: Arc<TorClient<PreferredRuntime>>) {
+        loop {
+            let (stream, peer) = match listener.accept().await {
+                Ok(conn) => conn,
+                Err(e) => {
+                    warn!("Embedded SOCKS accept failed: {}", e);
+                    continue;
+                }
+            };
+
+            let client = client.clone();
+            tokio::spawn(async move {
+                if let Err(e) = handle(stream, &client).await {
+                    debug!("Embedded SOCKS connection from {} closed: {}", peer, e);
+                }
+            });
+        }
+    }
+
+    async fn handle(mut stream: TcpStream, client: &TorClient<PreferredRuntime>) -> io::Result<()> {
+        // Method negotiation
+        let mut greeting = [0u8; 2];
+        stream.read_exact(&mut greeting).await?;
+        if greeting[0] != VERSION {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "not a SOCKS5 client"));
+        }
+        let mut methods = vec![0u8; greeting[1] as usize];
+        stream.read_exact(&mut methods).await?;
+        if !methods.contains(&NO_AUTH) {
+            stream.write_all(&[VERSION, NO_ACCEPTABLE_METHOD]).await?;
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "no acceptable auth method"));
+        }
+        stream.write_all(&[VERSION, NO_AUTH]).await?;
+
+        // Request: VER CMD RSV ATYP
+        let mut request = [0u8; 4];
+        stream.read_exact(&mut request).await?;
+        if request[1] != CMD_CONNECT {
+            reply(&mut stream, REPLY_COMMAND_NOT_SUPPORTED).await?;
+            return Err(io::Error::new(io::ErrorKind::Unsupported, "only CONNECT is supported"));
+        }
+
+        let host = match request[3] {
+            0x01 => {
+                let mut octets = [0u8; 4];
+                stream.read_exact(&mut octets).await?;
+                Ipv4Addr::from(octets).to_string()
+            }
+            0x03 => {
+                let mut len = [0u8; 1];
+                stream.read_exact(&mut len).await?;
+                let mut name = vec![0u8; len[0] as usize];
+                stream.read_exact(&mut name).await?;
+                String::from_utf8(name)
+                    .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "non-UTF-8 hostname"))?
+            }
+            0x04 => {
+                let mut octets = [0u8; 16];
+                stream.read_exact(&mut octets).await?;
+                Ipv6Addr::from(octets).to_string()
+            }
+            _ => {
+                reply(&mut stream, REPLY_ADDRESS_NOT_SUPPORTED).await?;
+                return Err(io::Error::new(io::ErrorKind::InvalidData, "unknown address type"));
+            }
+        };
+
+        let mut port = [0u8; 2];
+        stream.read_exact(&mut port).await?;
+        let port = u16::from_be_bytes(port);
+
+        let mut prefs = StreamPrefs::new();
+        prefs.connect_to_onion_services(arti_client::config::BoolOrAuto::Explicit(true));
+
+        match client.connect_with_prefs((host.as_str(), port), &prefs).await {
+            Ok(mut tor_stream) => {
+                reply(&mut stream, REPLY_SUCCEEDED).await?;
+                tokio::io::copy_bidirectional(&mut stream, &mut tor_stream).await?;
+                Ok(())
+            }
+            Err(e) => {
+                reply(&mut stream, REPLY_HOST_UNREACHABLE).await?;
+                Err(io::Error::other(format!("Tor connect to {}:{} failed: {}", host, port, e)))
+            }
+        }
+    }
+
+    async fn reply(stream: &mut TcpStream, code: u8) -> io::Result<()> {
+        // Bound address is not meaningful here; report 0.0.0.0:0
+        stream
+            .write_all(&[VERSION, code, 0x00, 0x01, 0, 0, 0, 0, 0, 0])
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_start_tor_external_is_noop() {
+        let mut config = TorConfig::default();
+        let original = config.socks_addr.clone();
+
+        let handle = start_tor(&mut config).await.unwrap();
+        assert!(handle.is_none());
+        assert_eq!(config.socks_addr, original);
+    }
+
+    #[cfg(not(feature = "embedded-tor"))]
+    #[tokio::test]
+    async fn test_embedded_requires_feature() {
+        let mut config = TorConfig {
+            mode: TorMode::Embedded,
+            ..Default::default()
+        };
+
+        assert!(matches!(start_tor(&mut config).await, Err(TorError::Embedded(_))));
+    }
+}
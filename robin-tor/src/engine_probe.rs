@@ -0,0 +1,184 @@
+//! Search engine benchmarking
+//!
+//! The reliability figures bundled with the engine list go stale quickly:
+//! engines move, slow down or die. A probe fires one canary query at each
+//! engine, records whether it answered, how fast and with how many results,
+//! and turns that into a fresh reliability score that can be written back
+//! into the registry (and so into an engine file loaded with `--engines`).
+
+use serde::Serialize;
+use std::time::Instant;
+use tracing::debug;
+
+use crate::crawler::engine_selectors;
+use crate::{engine_request, fetch_request, parse_engine_results, TorConfig};
+use robin_core::{Network, SearchEngine, SearchEngineRegistry};
+
+/// Query used when none is given: common enough that every index has hits
+pub const DEFAULT_CANARY_QUERY: &str = "market";
+
+/// Result count that earns the full result share of the score
+const FULL_RESULTS: usize = 20;
+
+/// Latency (ms) at which the speed share of the score reaches zero
+const SLOW_MS: u64 = 30_000;
+
+/// Weight of a probe against the engine's previous reliability
+///
+/// One probe is a noisy sample (a circuit can be slow by chance), so the
+/// new score moves the old one halfway rather than replacing it.
+const PROBE_WEIGHT: f64 = 0.5;
+
+/// How one engine answered the canary query
+#[derive(Debug, Clone, Serialize)]
+pub struct EngineProbe {
+    pub name: String,
+    pub network: Network,
+    /// The engine answered with a success status
+    pub reachable: bool,
+    /// Time to the engine's answer (None when unreachable)
+    pub latency_ms: Option<u64>,
+    /// Results parsed from the first page
+    pub results: usize,
+    /// Why the engine was unreachable
+    pub error: Option<String>,
+    /// Reliability before the probe
+    pub previous_reliability: f64,
+    /// Reliability after blending in this probe
+    pub reliability: f64,
+}
+
+impl EngineProbe {
+    /// This probe alone as a 0.0 - 1.0 score
+    ///
+    /// Unreachable engines score 0; an answer without results 0.2; an answer
+    /// with results 0.4, plus up to 0.3 for the result count and up to 0.3
+    /// for speed.
+    pub fn score(&self) -> f64 {
+        if !self.reachable {
+            return 0.0;
+        }
+        if self.results == 0 {
+            return 0.2;
+        }
+        let results = self.results.min(FULL_RESULTS) as f64 / FULL_RESULTS as f64;
+        let latency = self.latency_ms.unwrap_or(SLOW_MS).min(SLOW_MS) as f64;
+        let speed = 1.0 - latency / SLOW_MS as f64;
+        0.4 + 0.3 * results + 0.3 * speed
+    }
+}
+
+/// Search `engine` for `query` and measure the answer
+///
+/// Benched engines are probed too (that is how they get a second chance),
+/// and the outcome is recorded in `config.engine_health` like any crawl.
+pub async fn probe_engine(engine: &SearchEngine, query: &str, config: &TorConfig) -> EngineProbe {
+    debug!("Probing {} with query: {}", engine.name, query);
+    let started = Instant::now();
+    let result = fetch_request(&engine_request(engine, query), config).await;
+    let elapsed = started.elapsed();
+
+    let (reachable, results, error, bytes) = match result {
+        Ok(response) if response.is_success() => {
+            let selectors = engine_selectors(engine, config);
+            let results = parse_engine_results(&response.body, &engine.name, engine.network(), selectors);
+            (true, results.len(), None, response.body.len())
+        }
+        Ok(response) => (false, 0, Some(format!("HTTP {}", response.status)), response.body.len()),
+        Err(e) => (false, 0, Some(e.to_string()), 0),
+    };
+    if !config.is_replay() {
+        config.metrics.record_engine(&engine.name, elapsed, bytes, reachable);
+        config.engine_health.record(&engine.name, elapsed, reachable);
+    }
+
+    let mut probe = EngineProbe {
+        name: engine.name.clone(),
+        network: engine.network(),
+        reachable,
+        latency_ms: reachable.then_some(elapsed.as_millis() as u64),
+        results,
+        error,
+        previous_reliability: engine.reliability,
+        reliability: engine.reliability,
+    };
+    probe.reliability = (1.0 - PROBE_WEIGHT) * engine.reliability + PROBE_WEIGHT * probe.score();
+    probe
+}
+
+/// Probe several engines concurrently, in the order given
+pub async fn probe_engines(
+    engines: &[&SearchEngine],
+    query: &str,
+    config: &TorConfig,
+    max_concurrent: usize,
+) -> Vec<EngineProbe> {
+    use futures::stream::{self, StreamExt};
+
+    stream::iter(engines.iter().copied())
+        .map(|engine| probe_engine(engine, query, config))
+        .buffered(max_concurrent.max(1))
+        .collect()
+        .await
+}
+
+/// Write the probes' reliability scores into `registry`; returns how many engines were updated
+pub fn apply_probes(registry: &mut SearchEngineRegistry, probes: &[EngineProbe]) -> usize {
+    probes
+        .iter()
+        .filter(|probe| registry.set_reliability(&probe.name, probe.reliability))
+        .count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Cassette, Interaction};
+
+    fn page(url: &str, status: u16, body: &str) -> Interaction {
+        Interaction {
+            method: "GET".to_string(),
+            url: url.to_string(),
+            request_body: None,
+            status,
+            body: body.to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_probe_scores_engines() {
+        let good = SearchEngine::new("Good", "http://good.onion/search?q={query}");
+        let empty = SearchEngine::new("Empty", "http://empty.onion/search?q={query}");
+        let down = SearchEngine::new("Down", "http://down.onion/search?q={query}");
+        let gone = SearchEngine::new("Gone", "http://gone.onion/search?q={query}");
+        let config = TorConfig {
+            cassette: Some(Cassette::from_interactions(vec![
+                page(
+                    "http://good.onion/search?q=market",
+                    200,
+                    r#"<a href="http://leaksite1234567890abcdef.onion/">Leak site</a>"#,
+                ),
+                page("http://empty.onion/search?q=market", 200, "<p>No results</p>"),
+                page("http://down.onion/search?q=market", 404, ""),
+            ])),
+            ..Default::default()
+        };
+
+        let probes = probe_engines(&[&good, &empty, &down, &gone], DEFAULT_CANARY_QUERY, &config, 2).await;
+        let names: Vec<&str> = probes.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, vec!["Good", "Empty", "Down", "Gone"]);
+
+        assert!(probes[0].reachable && probes[0].results == 1 && probes[0].latency_ms.is_some());
+        assert!(probes[0].score() > 0.4);
+        assert_eq!(probes[1].score(), 0.2);
+        assert_eq!(probes[2].error.as_deref(), Some("HTTP 404"));
+        // Not in the cassette: a fetch error, not a panic
+        assert!(!probes[3].reachable && probes[3].error.is_some());
+        assert_eq!(probes[3].reliability, 0.25);
+
+        let mut registry = SearchEngineRegistry::from_engines(vec![good, down]);
+        assert_eq!(apply_probes(&mut registry, &probes), 2);
+        assert!(registry.get("Good").unwrap().reliability > 0.5);
+        assert_eq!(registry.get("Down").unwrap().reliability, 0.25);
+    }
+}
@@ -0,0 +1,601 @@
+//! Shared request path for the Tor layer
+//!
+//! Crawler and scraper traffic goes through [`fetch`], so behaviour that
+//! applies to every request (caching, retries, per-host rate limits, proxy
+//! pool health, sessions, captcha detection, cassette record/replay, WARC
+//! archival, ...)
+//! lives in one place.
+
+use reqwest::header::{CONTENT_LENGTH, CONTENT_TYPE};
+use std::time::Instant;
+use tracing::{debug, warn};
+
+use robin_core::{HttpMethod, Network};
+
+use crate::{
+    check_request, classify_error, classify_response, FailureKind, create_i2p_client, detect_captcha, create_login_client, create_pooled_client,
+    rate_limit::host_key, retry_delay, header_pairs, Cacheability, CaptchaChallenge, CassetteMode, TorConfig, TorError, Transfer,
+};
+
+/// Default for [`TorConfig::max_body_bytes`] (16 MiB)
+///
+/// Far above any real page, but well short of what a hostile service
+/// streaming gigabytes could make the swarm buffer.
+pub const DEFAULT_MAX_BODY_BYTES: usize = 16 * 1024 * 1024;
+
+/// A fetched HTTP response with its body read into memory
+#[derive(Debug, Clone)]
+pub struct FetchResponse {
+    /// Requested URL
+    pub url: String,
+    /// HTTP status code
+    pub status: u16,
+    /// Response body
+    pub body: String,
+    /// Caching allowed by the response headers
+    pub cacheability: Cacheability,
+}
+
+impl FetchResponse {
+    /// Whether the status is 2xx
+    pub fn is_success(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+}
+
+/// An HTTP request for [`fetch_request`]
+#[derive(Debug, Clone, Default)]
+pub struct FetchRequest {
+    /// Request method
+    pub method: HttpMethod,
+    /// Request URL
+    pub url: String,
+    /// Form-encoded body (sent as `application/x-www-form-urlencoded`)
+    pub body: Option<String>,
+    /// Extra request headers
+    pub headers: Vec<(String, String)>,
+}
+
+impl FetchRequest {
+    /// GET `url`
+    pub fn get(url: &str) -> Self {
+        Self {
+            url: url.to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// POST a form-encoded `body` to `url`
+    pub fn post_form(url: &str, body: &str) -> Self {
+        Self {
+            method: HttpMethod::Post,
+            url: url.to_string(),
+            body: Some(body.to_string()),
+            ..Default::default()
+        }
+    }
+
+    /// Add a request header
+    pub fn with_header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+}
+
+/// GET a URL; see [`fetch_request`]
+pub async fn fetch(url: &str, config: &TorConfig) -> Result<FetchResponse, TorError> {
+    fetch_request(&FetchRequest::get(url), config).await
+}
+
+/// Send a request through Tor, I2P for `.i2p` hosts, or the configured cassette
+///
+/// Timeouts, connection failures and 5xx/429 responses are retried up to
+/// `config.max_retries` times with exponential backoff; once retries are
+/// exhausted the result is [`TorError::MaxRetries`]. With `max_retries = 0`
+/// the first outcome is returned as-is.
+///
+/// With a response cache configured (and no cassette), fresh cached
+/// responses are returned without a request and successful ones are stored
+/// (unless their `Cache-Control` or `Expires` headers forbid it).
+/// Hosts with session credentials are never cached, since their pages
+/// depend on who is logged in.
+///
+/// A captcha interstitial goes to `config.captcha_solver`, and the request
+/// is sent once more with the solution; an unsolved challenge is
+/// [`TorError::CaptchaRequired`] and is never cached.
+///
+/// Bodies larger than `config.max_body_bytes` are abandoned mid-download
+/// with [`TorError::BodyTooLarge`] (not retried).
+///
+/// Hosts whose circuit in `config.breaker` is open fail at once with
+/// [`TorError::CircuitOpen`], without a request or retries.
+///
+/// Requests are charged to `config.bandwidth`, and refused with
+/// [`TorError::BandwidthExceeded`] once its cap is reached.
+///
+/// With `config.archive` set, every response received (including failed
+/// attempts and cache hits) is written to the WARC archive.
+pub async fn fetch_request(request: &FetchRequest, config: &TorConfig) -> Result<FetchResponse, TorError> {
+    let url = request.url.as_str();
+    let method = request.method.as_str();
+    let body = request.body.as_deref().unwrap_or_default().as_bytes();
+    let authenticated = config.sessions.as_ref().is_some_and(|sessions| {
+        host_key(url).is_some_and(|host| sessions.credentials_for(&host).is_some())
+    });
+    let cache = config
+        .cache
+        .as_ref()
+        .filter(|_| config.cassette.is_none() && !authenticated);
+    if let Some(hit) = cache.and_then(|cache| cache.get(method, url, body)) {
+        archive(request, &hit, &[], config);
+        return Ok(hit);
+    }
+
+    let mut attempt = 0;
+
+    let host = host_key(url).filter(|_| !config.is_replay());
+
+    loop {
+        let permit = match &host {
+            Some(host) => Some(config.breaker.admit(host)?),
+            None => None,
+        };
+        let started = Instant::now();
+        let result = fetch_once(request, config).await;
+        if let (Some(host), Some(permit)) = (&host, permit) {
+            let (bytes, ok) = match &result {
+                Ok(response) => (response.body.len(), response.is_success()),
+                Err(_) => (0, false),
+            };
+            config.metrics.record_host(host, started.elapsed(), body.len() + bytes, ok);
+            match &result {
+                Ok(_) => permit.record(true),
+                Err(e) if matches!(classify_error(e), Some(FailureKind::Timeout | FailureKind::Connect)) => {
+                    permit.record(false)
+                }
+                Err(_) => drop(permit),
+            }
+        }
+        let failure = match &result {
+            Ok(response) => classify_response(response),
+            Err(e) => classify_error(e),
+        };
+        let Some(kind) = failure else {
+            let result = match result {
+                Ok(response) => past_captcha(request, response, config).await,
+                Err(e) => Err(e),
+            };
+            if let (Some(cache), Ok(response)) = (cache, &result) {
+                if response.is_success() {
+                    if let Err(e) = cache.put(method, body, response) {
+                        warn!("Failed to cache {}: {}", url, e);
+                    }
+                }
+            }
+            return result;
+        };
+
+        if attempt >= config.max_retries {
+            if config.max_retries == 0 {
+                return result;
+            }
+            warn!("Giving up on {} after {} retries ({})", url, config.max_retries, kind);
+            return Err(TorError::MaxRetries(config.max_retries));
+        }
+
+        let delay = retry_delay(config, kind, attempt);
+        attempt += 1;
+        debug!(
+            "Fetch of {} failed ({}); retry {}/{} in {:?}",
+            url, kind, attempt, config.max_retries, delay
+        );
+        tokio::time::sleep(delay).await;
+    }
+}
+
+/// `response`, or the response after solving the captcha it presents
+async fn past_captcha(
+    request: &FetchRequest,
+    response: FetchResponse,
+    config: &TorConfig,
+) -> Result<FetchResponse, TorError> {
+    let Some(kind) = detect_captcha(&response) else {
+        return Ok(response);
+    };
+    let url = request.url.clone();
+    debug!("{} challenge at {}", kind, url);
+
+    if let Some(solver) = &config.captcha_solver {
+        let challenge = CaptchaChallenge {
+            url: url.clone(),
+            kind,
+            response,
+        };
+        if let Some(solution) = solver.solve(&challenge).await {
+            let mut solved = request.clone();
+            solved.headers.extend(solution.headers);
+            let response = fetch_once(&solved, config).await?;
+            if detect_captcha(&response).is_none() {
+                return Ok(response);
+            }
+            warn!("Captcha solution for {} was not accepted", url);
+        }
+    }
+    Err(TorError::CaptchaRequired { url, kind })
+}
+
+/// A single request attempt
+async fn fetch_once(request: &FetchRequest, config: &TorConfig) -> Result<FetchResponse, TorError> {
+    let url = request.url.as_str();
+    let method = request.method.as_str();
+    if let Some(cassette) = &config.cassette {
+        if cassette.mode() == CassetteMode::Replay {
+            debug!("Replaying {} {} from cassette", method, url);
+            return cassette.lookup(method, url, request.body.as_deref());
+        }
+    }
+    check_request(url, config)?;
+    config.bandwidth.check()?;
+
+    let i2p = match Network::of_url(url) {
+        Some(Network::I2p) => Some(
+            config
+                .i2p
+                .as_ref()
+                .ok_or_else(|| TorError::I2pNotConfigured(url.to_string()))?,
+        ),
+        _ => None,
+    };
+
+    let host = host_key(url).ok_or_else(|| TorError::InvalidUrl(url.to_string()))?;
+    if let Some(per_minute) = config.requests_per_host_per_minute {
+        config.rate_limiter.acquire(&host, per_minute).await;
+    }
+
+    let (client, lease) = match i2p {
+        Some(i2p) => (create_i2p_client(i2p)?, None),
+        None => create_pooled_client(config, Some(url))?,
+    };
+    let mut builder = match request.method {
+        HttpMethod::Get => client.get(url),
+        HttpMethod::Post => client.post(url),
+    };
+    if let Some(body) = &request.body {
+        builder = builder
+            .header(CONTENT_TYPE, "application/x-www-form-urlencoded")
+            .body(body.clone());
+    }
+    for (name, value) in &request.headers {
+        builder = builder.header(name.as_str(), value.as_str());
+    }
+    if let Some(sessions) = &config.sessions {
+        let proxy_addr = match (i2p, &lease) {
+            (Some(i2p), _) => i2p.proxy_addr.as_str(),
+            (None, Some(lease)) => lease.addr(),
+            (None, None) => config.socks_addr.as_str(),
+        };
+        builder = sessions
+            .authorize(&host, builder, || create_login_client(proxy_addr, config, url))
+            .await?;
+    }
+    let response = match builder.send().await {
+        Ok(response) => response,
+        Err(e) => {
+            // Only an unreachable SOCKS port is the proxy's fault; an
+            // unreachable onion says nothing about the endpoint
+            if let Some(lease) = &lease {
+                if is_proxy_unreachable(&e) {
+                    lease.report_failure();
+                }
+            }
+            if e.is_timeout() {
+                return Err(TorError::Timeout(config.timeout_secs));
+            }
+            return Err(e.into());
+        }
+    };
+    if let Some(lease) = &lease {
+        lease.report_success();
+    }
+    let status = response.status().as_u16();
+    let headers = config.archive.as_ref().map(|_| header_pairs(response.headers())).unwrap_or_default();
+    let cacheability = Cacheability::from_headers(response.headers());
+    let header_bytes: usize = response.headers().iter().map(|(name, value)| name.as_str().len() + value.len() + 4).sum();
+    if let Some(sessions) = &config.sessions {
+        sessions.store_cookies(&host, response.headers());
+        if status == 401 || status == 403 {
+            sessions.invalidate(&host).await;
+        }
+    }
+    let (body, read) = read_body(response, url, config.max_body_bytes).await;
+    config.bandwidth.charge(
+        &host,
+        config.agent.as_deref(),
+        Transfer {
+            sent: request_bytes(request) as u64,
+            received: (header_bytes + body.len()) as u64,
+        },
+    );
+    read?;
+    let body = String::from_utf8_lossy(&body).into_owned();
+
+    let fetched = FetchResponse {
+        url: url.to_string(),
+        status,
+        body,
+        cacheability,
+    };
+
+    if let Some(cassette) = &config.cassette {
+        if cassette.mode() == CassetteMode::Record {
+            cassette.append(method, request.body.as_deref(), &fetched)?;
+        }
+    }
+    archive(request, &fetched, &headers, config);
+
+    Ok(fetched)
+}
+
+/// Stream a response body, giving up once it grows past `max_bytes`
+///
+/// Returns the bytes read, complete or up to where reading stopped, and
+/// whether the body was read in full. A `Content-Length` over the limit is
+/// refused before reading anything.
+pub async fn read_body(mut response: reqwest::Response, url: &str, max_bytes: usize) -> (Vec<u8>, Result<(), TorError>) {
+    let too_large = || TorError::BodyTooLarge {
+        url: url.to_string(),
+        limit: max_bytes,
+    };
+    let declared = response
+        .headers()
+        .get(CONTENT_LENGTH)
+        .and_then(|value| value.to_str().ok()?.parse::<usize>().ok());
+    if declared.is_some_and(|len| len > max_bytes) {
+        return (Vec::new(), Err(too_large()));
+    }
+
+    let mut body = Vec::with_capacity(declared.unwrap_or_default());
+    loop {
+        match response.chunk().await {
+            Ok(Some(chunk)) if body.len() + chunk.len() > max_bytes => return (body, Err(too_large())),
+            Ok(Some(chunk)) => body.extend_from_slice(&chunk),
+            Ok(None) => return (body, Ok(())),
+            Err(e) => return (body, Err(e.into())),
+        }
+    }
+}
+
+/// Bytes of the request line, extra headers and body
+///
+/// Headers the client adds itself (user agent, cookies, ...) are not
+/// included, so this slightly undercounts.
+fn request_bytes(request: &FetchRequest) -> usize {
+    let headers: usize = request.headers.iter().map(|(name, value)| name.len() + value.len() + 4).sum();
+    request.method.as_str().len() + request.url.len() + 11 + headers + request.body.as_ref().map_or(0, |body| body.len())
+}
+
+/// Write an exchange to the configured WARC archive
+///
+/// A failing archive is logged rather than failing the fetch: the page is
+/// still usable, and the error repeats in the log for every request.
+fn archive(request: &FetchRequest, response: &FetchResponse, headers: &[(String, String)], config: &TorConfig) {
+    if let Some(warc) = &config.archive {
+        if let Err(e) = warc.write_exchange(request, response, headers) {
+            warn!("Failed to archive {}: {}", response.url, e);
+        }
+    }
+}
+
+/// Whether a request failed before reaching the SOCKS proxy (e.g. dead Tor daemon)
+pub(crate) fn is_proxy_unreachable(err: &reqwest::Error) -> bool {
+    let mut source: Option<&(dyn std::error::Error + 'static)> = Some(err);
+    while let Some(e) = source {
+        if let Some(io) = e.downcast_ref::<std::io::Error>() {
+            if io.kind() == std::io::ErrorKind::ConnectionRefused {
+                return true;
+            }
+        }
+        // reqwest's SOCKS connector ends the chain here without the io error
+        if e.to_string().contains("failed to create underlying connection") {
+            return true;
+        }
+        source = e.source();
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Cassette, Interaction};
+
+    #[tokio::test]
+    async fn test_fetch_replays_without_network() {
+        let config = TorConfig {
+            // Unroutable proxy: a live request would fail
+            socks_addr: "socks5h://127.0.0.1:1".to_string(),
+            max_retries: 0,
+            cassette: Some(Cassette::from_interactions(vec![Interaction {
+                method: "GET".to_string(),
+                url: "http://a.onion/".to_string(),
+                request_body: None,
+                status: 503,
+                body: "busy".to_string(),
+            }])),
+            ..Default::default()
+        };
+
+        let response = fetch("http://a.onion/", &config).await.unwrap();
+        assert_eq!(response.status, 503);
+        assert!(!response.is_success());
+        assert_eq!(response.body, "busy");
+    }
+
+    #[tokio::test]
+    async fn test_fetch_serves_from_cache() {
+        let dir = std::env::temp_dir().join(format!("robin-fetch-cache-{}", std::process::id()));
+        let cache = crate::ResponseCache::open(&dir).unwrap();
+        cache
+            .put("GET", b"", &FetchResponse {
+                url: "http://a.onion/".to_string(),
+                status: 200,
+                body: "cached".to_string(),
+                cacheability: Cacheability::Unspecified,
+            })
+            .unwrap();
+
+        let warc = crate::WarcWriter::open(dir.join("run.warc")).unwrap();
+        let config = TorConfig {
+            // Unroutable proxy: only a cache hit can succeed
+            socks_addr: "socks5h://127.0.0.1:1".to_string(),
+            max_retries: 0,
+            cache: Some(cache.clone()),
+            archive: Some(warc.clone()),
+            ..Default::default()
+        };
+        assert_eq!(fetch("http://a.onion/", &config).await.unwrap().body, "cached");
+        assert!(fetch("http://b.onion/", &config).await.is_err());
+        assert_eq!((cache.stats().hits, cache.stats().misses), (1, 1));
+        // Cache hits are evidence too
+        let archived = std::fs::read_to_string(warc.path()).unwrap();
+        assert!(archived.contains("WARC-Target-URI: http://a.onion/\r\n"));
+        assert!(archived.contains("\r\n\r\ncached\r\n\r\n"));
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[tokio::test]
+    async fn test_oversized_bodies_are_abandoned() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let mut request = [0u8; 1024];
+                let n = stream.read(&mut request).await.unwrap_or(0);
+                let head = if request[..n].starts_with(b"GET /declared") {
+                    "HTTP/1.1 200 OK\r\nContent-Length: 1000000\r\n\r\n"
+                } else {
+                    "HTTP/1.1 200 OK\r\nConnection: close\r\n\r\n"
+                };
+                let _ = stream.write_all(head.as_bytes()).await;
+                let _ = stream.write_all(&[b'x'; 64 * 1024]).await;
+            }
+        });
+
+        let client = reqwest::Client::new();
+        let get = |path: &str| client.get(format!("http://{}/{}", addr, path)).send();
+
+        let (body, result) = read_body(get("small").await.unwrap(), "small", 1 << 20).await;
+        assert!(result.is_ok());
+        assert_eq!(body.len(), 64 * 1024);
+
+        // Streamed past the cap: stops at the cap
+        let (body, result) = read_body(get("stream").await.unwrap(), "stream", 1000).await;
+        assert!(matches!(result, Err(TorError::BodyTooLarge { limit: 1000, .. })));
+        assert!(body.len() <= 1000);
+
+        // Declared too large: nothing is read
+        let (body, result) = read_body(get("declared").await.unwrap(), "declared", 1 << 16).await;
+        assert!(matches!(result, Err(TorError::BodyTooLarge { .. })));
+        assert!(body.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_refused_proxy_marks_pool_endpoint() {
+        let pool = crate::ProxyPool::new(["socks5h://127.0.0.1:1"]).unwrap();
+        let config = TorConfig {
+            proxy_pool: Some(pool.clone()),
+            timeout_secs: 5,
+            max_retries: 2,
+            retry_base_delay_ms: 0,
+            ..Default::default()
+        };
+
+        assert!(matches!(
+            fetch("http://a.onion/", &config).await,
+            Err(TorError::MaxRetries(2))
+        ));
+        let stats = pool.stats();
+        assert_eq!(stats[0].failures, 3);
+        assert_eq!(stats[0].in_flight, 0);
+    }
+
+    #[tokio::test]
+    async fn test_retries_server_errors() {
+        let interaction = |status: u16, body: &str| Interaction {
+            method: "GET".to_string(),
+            url: "http://a.onion/".to_string(),
+            request_body: None,
+            status,
+            body: body.to_string(),
+        };
+        let config = |interactions| TorConfig {
+            max_retries: 2,
+            retry_base_delay_ms: 0,
+            cassette: Some(Cassette::from_interactions(interactions)),
+            ..Default::default()
+        };
+
+        // 503 then 200: the retry succeeds
+        let recovering = config(vec![interaction(503, "busy"), interaction(200, "ok")]);
+        assert_eq!(fetch("http://a.onion/", &recovering).await.unwrap().body, "ok");
+
+        // Persistent 502: retries are exhausted
+        let failing = config(vec![interaction(502, "bad gateway")]);
+        assert!(matches!(
+            fetch("http://a.onion/", &failing).await,
+            Err(TorError::MaxRetries(2))
+        ));
+
+        // 404 is final and returned as-is
+        let missing = config(vec![interaction(404, "missing")]);
+        assert_eq!(fetch("http://a.onion/", &missing).await.unwrap().status, 404);
+    }
+
+    #[derive(Debug)]
+    struct CookieSolver;
+
+    #[async_trait::async_trait]
+    impl crate::CaptchaSolver for CookieSolver {
+        async fn solve(&self, challenge: &crate::CaptchaChallenge) -> Option<crate::CaptchaSolution> {
+            assert_eq!(challenge.kind, crate::CaptchaKind::Generic);
+            Some(crate::CaptchaSolution {
+                headers: vec![("Cookie".to_string(), "clearance=1".to_string())],
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_captcha_pages_go_to_solver() {
+        let interaction = |body: &str| Interaction {
+            method: "GET".to_string(),
+            url: "http://a.onion/".to_string(),
+            request_body: None,
+            status: 200,
+            body: body.to_string(),
+        };
+        let challenge = "<h1>Prove you're human</h1>";
+
+        let unsolved = TorConfig {
+            max_retries: 0,
+            cassette: Some(Cassette::from_interactions(vec![interaction(challenge)])),
+            ..Default::default()
+        };
+        assert!(matches!(
+            fetch("http://a.onion/", &unsolved).await,
+            Err(TorError::CaptchaRequired { .. })
+        ));
+
+        let solved = TorConfig {
+            captcha_solver: Some(std::sync::Arc::new(CookieSolver)),
+            cassette: Some(Cassette::from_interactions(vec![
+                interaction(challenge),
+                interaction("market index"),
+            ])),
+            ..unsolved
+        };
+        assert_eq!(fetch("http://a.onion/", &solved).await.unwrap().body, "market index");
+    }
+}
@@ -0,0 +1,119 @@
+//! Clearnet-leak guard
+//!
+//! Every client in this crate goes through a proxy, but a proxy address can
+//! still leak: `socks5://` makes reqwest resolve hostnames itself, so an
+//! `.onion` lookup reaches the system resolver (and whoever watches it)
+//! before Tor ever sees the request. [`check_proxy`] refuses that case
+//! always. With [`TorConfig::strict`] set, the crate fails closed: anything
+//! that would not go through Tor's SOCKS port with remote DNS, including
+//! eepsites through the I2P HTTP proxy, is refused before a connection is
+//! opened.
+
+use reqwest::Url;
+
+use crate::{is_onion_url, TorConfig, TorError};
+
+/// Refuse proxy addresses that would leak requests to `url` (None = any destination)
+pub fn check_proxy(proxy_addr: &str, url: Option<&str>, config: &TorConfig) -> Result<(), TorError> {
+    let scheme = Url::parse(proxy_addr)
+        .map(|u| u.scheme().to_string())
+        .map_err(|e| TorError::ClientBuild(format!("invalid proxy '{}': {}", proxy_addr, e)))?;
+    let remote_dns = matches!(scheme.as_str(), "socks5h" | "socks4a");
+    let socks = remote_dns || matches!(scheme.as_str(), "socks5" | "socks4");
+
+    if socks && !remote_dns && url.is_some_and(is_onion_url) {
+        return Err(TorError::Leak(format!(
+            "{} resolves hostnames locally, which would look up the onion outside Tor; use socks5h://",
+            proxy_addr
+        )));
+    }
+    if config.strict && scheme != "socks5h" {
+        return Err(TorError::Leak(format!(
+            "strict mode only allows socks5h:// proxies, not {}",
+            proxy_addr
+        )));
+    }
+    Ok(())
+}
+
+/// Refuse a request to `url` that strict mode would not send through Tor
+pub fn check_request(url: &str, config: &TorConfig) -> Result<(), TorError> {
+    if !config.strict {
+        return Ok(());
+    }
+    let parsed = Url::parse(url).map_err(|_| TorError::InvalidUrl(url.to_string()))?;
+    if !matches!(parsed.scheme(), "http" | "https") {
+        return Err(TorError::Leak(format!("strict mode refuses non-HTTP URL {}", url)));
+    }
+    if parsed.host_str().is_some_and(|h| h.ends_with(".i2p")) {
+        return Err(TorError::Leak(format!("strict mode refuses {}: I2P traffic bypasses Tor", url)));
+    }
+    Ok(())
+}
+
+/// Check every proxy `config` can use, so a strict run fails before it starts
+pub fn check_config(config: &TorConfig) -> Result<(), TorError> {
+    let addrs = match &config.proxy_pool {
+        Some(pool) => pool.addrs(),
+        None => vec![config.socks_addr.clone()],
+    };
+    for addr in &addrs {
+        check_proxy(addr, None, config)?;
+    }
+    if config.strict && config.i2p.is_some() {
+        return Err(TorError::Leak("strict mode cannot be combined with I2P".to_string()));
+    }
+    if config.strict && config.screenshots.is_some() {
+        return Err(TorError::Leak(
+            "strict mode cannot be combined with screenshots: the browser's traffic isn't checked".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ONION: &str = "http://2gzyxa5ihm7nsggfxnu52rck2vv4rvmdlkiu3zzui5du4xyclen53wid.onion/";
+
+    #[test]
+    fn test_local_dns_proxy_blocked_for_onions() {
+        let config = TorConfig::default();
+        assert!(check_proxy("socks5h://127.0.0.1:9050", Some(ONION), &config).is_ok());
+        assert!(matches!(
+            check_proxy("socks5://127.0.0.1:9050", Some(ONION), &config),
+            Err(TorError::Leak(_))
+        ));
+        // Resolving clearnet hosts locally is tolerated outside strict mode
+        assert!(check_proxy("socks5://127.0.0.1:9050", Some("https://example.com/"), &config).is_ok());
+        assert!(check_request("http://legwork.i2p/", &config).is_ok());
+    }
+
+    #[test]
+    fn test_strict_mode_fails_closed() {
+        let config = TorConfig {
+            strict: true,
+            ..Default::default()
+        };
+        assert!(check_config(&config).is_ok());
+        assert!(check_proxy("socks5://127.0.0.1:9050", Some("https://example.com/"), &config).is_err());
+        assert!(check_proxy("http://127.0.0.1:8118", None, &config).is_err());
+        assert!(check_request("http://legwork.i2p/", &config).is_err());
+        assert!(check_request("ftp://files.onion/", &config).is_err());
+        assert!(check_request(ONION, &config).is_ok());
+
+        let leaky = TorConfig {
+            socks_addr: "socks5://127.0.0.1:9050".to_string(),
+            ..config
+        };
+        assert!(check_config(&leaky).is_err());
+
+        let screenshots = TorConfig {
+            strict: true,
+            screenshots: Some(crate::ScreenshotConfig::new("/tmp/shots")),
+            ..Default::default()
+        };
+        assert!(check_config(&screenshots).is_err());
+    }
+}
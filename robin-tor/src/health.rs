@@ -0,0 +1,235 @@
+//! Search engine health tracking
+//!
+//! Every live engine crawl updates the engine's success count, mean latency
+//! and last success. After [`EngineHealthPolicy::failure_threshold`]
+//! consecutive failures (typically timeouts of an engine that went offline)
+//! the engine is benched for a cooldown instead of costing every crawl its
+//! full timeout; once the cooldown passes it gets one retry, and each
+//! further failure doubles the cooldown. Health is saved as JSON so a run
+//! starts from what earlier runs learned.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tracing::warn;
+
+use crate::cache::now_secs;
+use crate::TorError;
+
+/// When engines are benched
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EngineHealthPolicy {
+    /// Consecutive failures before an engine is benched
+    pub failure_threshold: u32,
+    /// First cooldown; doubled for every failed retry
+    pub cooldown: Duration,
+    /// Longest cooldown
+    pub max_cooldown: Duration,
+}
+
+impl Default for EngineHealthPolicy {
+    fn default() -> Self {
+        Self {
+            failure_threshold: 3,
+            cooldown: Duration::from_secs(15 * 60),
+            max_cooldown: Duration::from_secs(24 * 60 * 60),
+        }
+    }
+}
+
+/// What is known about one engine
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EngineRecord {
+    pub successes: u64,
+    pub failures: u64,
+    /// Failures since the last success
+    pub consecutive_failures: u32,
+    /// Summed crawl time in milliseconds
+    pub total_ms: u64,
+    /// Unix time of the last successful crawl
+    pub last_success: Option<u64>,
+    /// Unix time until which the engine is skipped
+    pub benched_until: Option<u64>,
+}
+
+impl EngineRecord {
+    /// Fraction of crawls that succeeded (1.0 before the first crawl)
+    pub fn success_rate(&self) -> f64 {
+        let total = self.successes + self.failures;
+        if total == 0 {
+            1.0
+        } else {
+            self.successes as f64 / total as f64
+        }
+    }
+
+    /// Mean crawl time in milliseconds
+    pub fn avg_ms(&self) -> u64 {
+        self.total_ms.checked_div(self.successes + self.failures).unwrap_or(0)
+    }
+
+    /// Whether the engine is benched at Unix time `now`
+    pub fn is_benched(&self, now: u64) -> bool {
+        self.benched_until.is_some_and(|until| now < until)
+    }
+}
+
+/// Shared per-engine health
+///
+/// Cloning is cheap and all clones share the same records.
+#[derive(Debug, Clone, Default)]
+pub struct EngineHealth {
+    policy: EngineHealthPolicy,
+    records: Arc<Mutex<HashMap<String, EngineRecord>>>,
+}
+
+impl EngineHealth {
+    pub fn new(policy: EngineHealthPolicy) -> Self {
+        Self {
+            policy,
+            records: Arc::default(),
+        }
+    }
+
+    /// Load records saved by [`EngineHealth::save`]; a missing file starts empty
+    pub fn load(path: impl AsRef<Path>, policy: EngineHealthPolicy) -> Result<Self, TorError> {
+        let health = Self::new(policy);
+        let path = path.as_ref();
+        if path.exists() {
+            let json = std::fs::read_to_string(path).map_err(|e| TorError::Health(e.to_string()))?;
+            let records: HashMap<String, EngineRecord> =
+                serde_json::from_str(&json).map_err(|e| TorError::Health(e.to_string()))?;
+            *health.records.lock().unwrap() = records;
+        }
+        Ok(health)
+    }
+
+    /// Write every record as JSON
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), TorError> {
+        let json = serde_json::to_string_pretty(&self.snapshot()).map_err(|e| TorError::Health(e.to_string()))?;
+        std::fs::write(path, json).map_err(|e| TorError::Health(e.to_string()))
+    }
+
+    /// Record one crawl of the engine `name`
+    pub fn record(&self, name: &str, elapsed: Duration, ok: bool) {
+        self.record_at(name, elapsed, ok, now_secs());
+    }
+
+    fn record_at(&self, name: &str, elapsed: Duration, ok: bool, now: u64) {
+        let mut records = self.records.lock().unwrap();
+        let record = records.entry(name.to_string()).or_default();
+        record.total_ms += elapsed.as_millis() as u64;
+        if ok {
+            record.successes += 1;
+            record.consecutive_failures = 0;
+            record.last_success = Some(now);
+            record.benched_until = None;
+            return;
+        }
+
+        record.failures += 1;
+        record.consecutive_failures += 1;
+        if let Some(retries) = record.consecutive_failures.checked_sub(self.policy.failure_threshold) {
+            let cooldown = self
+                .policy
+                .cooldown
+                .saturating_mul(2u32.saturating_pow(retries))
+                .min(self.policy.max_cooldown);
+            record.benched_until = Some(now + cooldown.as_secs());
+            if retries == 0 {
+                warn!(
+                    "Engine {} failed {} times in a row; benched for {:?}",
+                    name,
+                    record.consecutive_failures,
+                    cooldown
+                );
+            }
+        }
+    }
+
+    /// Whether the engine `name` should be crawled now
+    pub fn is_available(&self, name: &str) -> bool {
+        self.is_available_at(name, now_secs())
+    }
+
+    fn is_available_at(&self, name: &str, now: u64) -> bool {
+        self.records
+            .lock()
+            .unwrap()
+            .get(name)
+            .is_none_or(|record| !record.is_benched(now))
+    }
+
+    /// Engines benched right now, with the Unix time they return
+    pub fn benched(&self) -> Vec<(String, u64)> {
+        let now = now_secs();
+        self.snapshot()
+            .into_iter()
+            .filter(|(_, record)| record.is_benched(now))
+            .filter_map(|(name, record)| Some((name, record.benched_until?)))
+            .collect()
+    }
+
+    /// Copy of every record, by engine name
+    pub fn snapshot(&self) -> BTreeMap<String, EngineRecord> {
+        self.records
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(name, record)| (name.clone(), *record))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SLOW: Duration = Duration::from_secs(45);
+
+    #[test]
+    fn test_bench_and_retry() {
+        let health = EngineHealth::default();
+        let shared = health.clone();
+        let now = 1_000_000;
+        shared.record_at("Torgle", SLOW, false, now);
+        shared.record_at("Torgle", SLOW, false, now);
+        assert!(health.is_available_at("Torgle", now));
+        shared.record_at("Torgle", SLOW, false, now);
+        assert!(!health.is_available_at("Torgle", now + 60));
+
+        // Retried after the cooldown; another failure doubles it
+        assert!(health.is_available_at("Torgle", now + 15 * 60));
+        shared.record_at("Torgle", SLOW, false, now + 15 * 60);
+        assert!(!health.is_available_at("Torgle", now + 15 * 60 + 29 * 60));
+        assert!(health.is_available_at("Torgle", now + 15 * 60 + 30 * 60));
+
+        // A success restores the engine
+        shared.record_at("Torgle", Duration::from_secs(5), true, now + 50 * 60);
+        let record = health.snapshot()["Torgle"];
+        assert_eq!((record.successes, record.failures, record.consecutive_failures), (1, 4, 0));
+        assert_eq!(record.last_success, Some(now + 50 * 60));
+        assert_eq!(record.avg_ms(), 37_000);
+        assert!((record.success_rate() - 0.2).abs() < 1e-9);
+        assert!(health.is_available("Ahmia"));
+    }
+
+    #[test]
+    fn test_health_persists() {
+        let path = std::env::temp_dir().join(format!("robin-engine-health-{}.json", std::process::id()));
+        let health = EngineHealth::default();
+        for _ in 0..3 {
+            health.record("Torgle", SLOW, false);
+        }
+        health.record("Ahmia", Duration::from_millis(800), true);
+        health.save(&path).unwrap();
+
+        let loaded = EngineHealth::load(&path, EngineHealthPolicy::default()).unwrap();
+        assert_eq!(loaded.snapshot(), health.snapshot());
+        assert!(!loaded.is_available("Torgle"));
+        assert_eq!(loaded.benched().len(), 1);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,74 @@
+//! I2P HTTP proxy client
+//!
+//! Eepsites are reached through the I2P router's HTTP proxy (port 4444 by
+//! default). Set `TorConfig::i2p` to route `.i2p` URLs there; every other
+//! URL keeps going through Tor, so one investigation can cover both networks.
+
+use reqwest::{Client, Proxy};
+use std::time::Duration;
+
+use crate::{random_user_agent, TorError};
+
+/// I2P router proxy configuration
+#[derive(Debug, Clone)]
+pub struct I2pConfig {
+    /// HTTP proxy address of the I2P router (default: 127.0.0.1:4444)
+    pub proxy_addr: String,
+    /// Request timeout in seconds (tunnels build slowly, so longer than Tor's)
+    pub timeout_secs: u64,
+}
+
+impl Default for I2pConfig {
+    fn default() -> Self {
+        Self {
+            proxy_addr: "http://127.0.0.1:4444".to_string(),
+            timeout_secs: 120,
+        }
+    }
+}
+
+/// Create an HTTP client that routes through the I2P proxy
+pub fn create_i2p_client(config: &I2pConfig) -> Result<Client, TorError> {
+    let proxy = Proxy::all(&config.proxy_addr)
+        .map_err(|e| TorError::ClientBuild(e.to_string()))?;
+
+    Client::builder()
+        .proxy(proxy)
+        .timeout(Duration::from_secs(config.timeout_secs))
+        .user_agent(random_user_agent())
+        .build()
+        .map_err(|e| TorError::ClientBuild(e.to_string()))
+}
+
+/// Eepsite used for connectivity checks (I2P project site)
+const CHECK_URL: &str = "http://i2p-projekt.i2p/";
+
+/// Check if the I2P router proxy can reach the network
+pub async fn check_i2p_connection(config: &I2pConfig) -> Result<bool, TorError> {
+    let client = create_i2p_client(config)?;
+    Ok(match client.get(CHECK_URL).send().await {
+        Ok(resp) => resp.status().is_success() || resp.status().is_redirection(),
+        Err(_) => false,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_config() {
+        let config = I2pConfig::default();
+        assert!(config.proxy_addr.ends_with(":4444"));
+        assert!(create_i2p_client(&config).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_eepsite_without_i2p_config() {
+        let config = crate::TorConfig::default();
+        assert!(matches!(
+            crate::fetch("http://legwork.i2p/", &config).await,
+            Err(TorError::I2pNotConfigured(_))
+        ));
+    }
+}
@@ -0,0 +1,191 @@
+//! Image download and hashing
+//!
+//! The same marketplace banner, vendor logo or leaked document scan turns
+//! up across sites under different URLs. With [`TorConfig::images`] set,
+//! the scraper downloads each page's images (over the same proxy, capped in
+//! size and number) and records a SHA-256 and a difference hash of each in
+//! the page metadata, so they can be correlated as artifacts.
+//!
+//! Images are fetched with [`crate::download`], so they go through the leak
+//! guard, crawl policy, per-host rate limit and bandwidth cap like any
+//! request, but are not cached or archived.
+//! Inline `data:` images are hashed without a request.
+
+use base64::Engine;
+use image::imageops::FilterType;
+use scraper::{Html, Selector};
+use sha2::{Digest, Sha256};
+use tracing::debug;
+
+use robin_core::ImageDigest;
+
+use crate::{download, TorConfig};
+
+/// Default largest image downloaded (2 MiB)
+pub const DEFAULT_IMAGE_MAX_BYTES: usize = 2 * 1024 * 1024;
+
+/// Default images hashed per page
+pub const DEFAULT_IMAGES_PER_PAGE: usize = 10;
+
+/// Limits on image downloads
+#[derive(Debug, Clone)]
+pub struct ImageConfig {
+    /// Images larger than this are skipped
+    pub max_bytes: usize,
+    /// Images hashed per page, in document order
+    pub max_per_page: usize,
+}
+
+impl Default for ImageConfig {
+    fn default() -> Self {
+        Self {
+            max_bytes: DEFAULT_IMAGE_MAX_BYTES,
+            max_per_page: DEFAULT_IMAGES_PER_PAGE,
+        }
+    }
+}
+
+/// Where an image comes from
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum ImageSource {
+    Remote(String),
+    /// MIME type and decoded bytes of a `data:` URI
+    Inline(String, Vec<u8>),
+}
+
+/// `<img>` sources of a page, resolved against `base_url`, without duplicates
+fn image_sources(html: &str, base_url: &str, limit: usize) -> Vec<ImageSource> {
+    let Ok(base) = reqwest::Url::parse(base_url) else {
+        return Vec::new();
+    };
+    let document = Html::parse_document(html);
+    let img = Selector::parse("img[src]").unwrap();
+
+    let mut sources = Vec::new();
+    for element in document.select(&img) {
+        let src = element.value().attr("src").unwrap_or_default().trim();
+        let source = match src.strip_prefix("data:") {
+            Some(data) => {
+                let Some((mime, payload)) = data.split_once(";base64,") else {
+                    continue;
+                };
+                let payload: String = payload.split_whitespace().collect();
+                match base64::engine::general_purpose::STANDARD.decode(payload) {
+                    Ok(bytes) => ImageSource::Inline(mime.to_string(), bytes),
+                    Err(_) => continue,
+                }
+            }
+            None => match base.join(src) {
+                Ok(url) if matches!(url.scheme(), "http" | "https") => ImageSource::Remote(url.to_string()),
+                _ => continue,
+            },
+        };
+        if !sources.contains(&source) {
+            sources.push(source);
+        }
+        if sources.len() == limit {
+            break;
+        }
+    }
+    sources
+}
+
+/// Hex SHA-256 and difference hash of image bytes
+pub fn hash_image(url: &str, bytes: &[u8]) -> ImageDigest {
+    ImageDigest {
+        url: url.to_string(),
+        bytes: bytes.len(),
+        sha256: Sha256::digest(bytes).iter().map(|b| format!("{:02x}", b)).collect(),
+        dhash: difference_hash(bytes).map(|hash| format!("{:016x}", hash)),
+    }
+}
+
+/// 64-bit difference hash: one bit per horizontally adjacent pixel pair of
+/// a 9×8 grayscale thumbnail, set where brightness falls to the right
+///
+/// None when the bytes are not a decodable raster image (SVG, truncated, ...).
+pub fn difference_hash(bytes: &[u8]) -> Option<u64> {
+    let image = image::load_from_memory(bytes).ok()?;
+    let thumb = image.resize_exact(9, 8, FilterType::Triangle).to_luma8();
+    let mut hash = 0u64;
+    for y in 0..8 {
+        for x in 0..8 {
+            hash <<= 1;
+            if thumb.get_pixel(x, y)[0] > thumb.get_pixel(x + 1, y)[0] {
+                hash |= 1;
+            }
+        }
+    }
+    Some(hash)
+}
+
+/// Bits differing between two hex difference hashes (None if either is malformed)
+pub fn dhash_distance(a: &str, b: &str) -> Option<u32> {
+    let a = u64::from_str_radix(a, 16).ok()?;
+    let b = u64::from_str_radix(b, 16).ok()?;
+    Some((a ^ b).count_ones())
+}
+
+/// Download and hash the images of a page
+///
+/// Images that fail to download, are out of scope or exceed the size cap
+/// are skipped (and logged at debug level).
+pub async fn hash_page_images(html: &str, page_url: &str, config: &TorConfig) -> Vec<ImageDigest> {
+    let Some(images) = &config.images else {
+        return Vec::new();
+    };
+    let mut digests = Vec::new();
+    for source in image_sources(html, page_url, images.max_per_page) {
+        match source {
+            ImageSource::Inline(mime, bytes) if bytes.len() <= images.max_bytes => {
+                digests.push(hash_image(&format!("data:{}", mime), &bytes));
+            }
+            ImageSource::Inline(..) => {}
+            ImageSource::Remote(url) => match download(&url, images.max_bytes, config).await {
+                Ok(bytes) => digests.push(hash_image(&url, &bytes)),
+                Err(e) => debug!("Skipping image {}: {}", url, e),
+            },
+        }
+    }
+    digests
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// PNG of a horizontal gradient, `flip` for right-to-left
+    fn gradient(width: u32, flip: bool) -> Vec<u8> {
+        let image = image::GrayImage::from_fn(width, width / 2, |x, _| {
+            let x = if flip { width - 1 - x } else { x };
+            image::Luma([(x * 255 / (width - 1)) as u8])
+        });
+        let mut png = std::io::Cursor::new(Vec::new());
+        image.write_to(&mut png, image::ImageFormat::Png).unwrap();
+        png.into_inner()
+    }
+
+    #[test]
+    fn test_hashes_and_sources() {
+        let small = hash_image("http://a.onion/banner.png", &gradient(64, false));
+        let large = hash_image("http://b.onion/banner.png", &gradient(256, false));
+        let flipped = hash_image("http://c.onion/banner.png", &gradient(64, true));
+        assert_ne!(small.sha256, large.sha256);
+        // The same picture at another size keeps its difference hash
+        assert!(dhash_distance(small.dhash.as_deref().unwrap(), large.dhash.as_deref().unwrap()).unwrap() <= 4);
+        assert!(dhash_distance(small.dhash.as_deref().unwrap(), flipped.dhash.as_deref().unwrap()).unwrap() > 32);
+        assert_eq!(hash_image("x.svg", b"<svg/>").dhash, None);
+
+        let inline = base64::engine::general_purpose::STANDARD.encode(gradient(16, false));
+        let html = format!(
+            r#"<img src="/logo.png"><img src="logo.png"><img src="javascript:x"><img src="data:image/png;base64,{}">"#,
+            inline
+        );
+        let sources = image_sources(&html, "http://a.onion/shop/", 10);
+        assert_eq!(sources.len(), 3);
+        assert_eq!(sources[0], ImageSource::Remote("http://a.onion/logo.png".to_string()));
+        assert_eq!(sources[1], ImageSource::Remote("http://a.onion/shop/logo.png".to_string()));
+        assert!(matches!(&sources[2], ImageSource::Inline(mime, _) if mime == "image/png"));
+        assert_eq!(image_sources(&html, "http://a.onion/shop/", 1).len(), 1);
+    }
+}
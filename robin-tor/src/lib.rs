@@ -1,14 +1,97 @@
 //! Robin×SMESH Tor Layer
 //!
 //! Provides Tor-based networking for dark web crawling:
+//! - `TorHttp`, a standalone Tor HTTP client (GET, POST, capped downloads) usable outside Robin
 //! - SOCKS5h proxy client (DNS resolution via Tor)
-//! - Search engine querying
-//! - Content scraping with retry logic
+//! - Proxy pool across multiple Tor instances with health tracking
+//! - Chaining through an upstream HTTP/SOCKS5 (corporate) proxy
+//! - Clearnet-leak guard with a fail-closed strict mode, and an opsec audit of the proxy setup
+//! - Per-agent egress policy (direct, Tor or a proxy) for clearnet API calls
+//! - I2P HTTP proxy client for `.i2p` eepsites
+//! - Onion v3 address validation and reachability probes
+//! - Search engine querying and onion link directory ingestion
+//! - Crawl scope policy: host allow/deny lists, URL exclusions, per-host page budgets, robots.txt
+//! - Search engine health tracking that benches failing engines
+//! - Engine benchmarking with a canary query that refreshes reliability scores
+//! - Content scraping with retry logic (exponential backoff + jitter)
+//! - Headless-browser screenshots of scraped pages over the same proxy
+//! - Image download with SHA-256 and perceptual hashes for cross-site correlation
+//! - Site adapters that parse forum threads and leak site indexes into posts
+//! - Per-host politeness rate limiting shared across agents
+//! - Per-host circuit breaker that stops retrying dead hidden services
+//! - Optional embedded Tor client (arti, `embedded-tor` feature)
+//! - Cassette record/replay for offline testing
+//! - WARC archival of every request and response, for evidence preservation
+//! - Disk-backed response cache with TTL and size limit
+//! - Cookie jar and per-domain authenticated sessions
+//! - Per-host session affinity: one isolated circuit and pool endpoint per site
+//! - Captcha interstitial detection with a pluggable solver
+//! - Per-host and per-engine timing, traffic and failure metrics
+//! - Bandwidth accounting per host and agent, with a per-run transfer cap
+//! - Control port status (version, bootstrap, circuits) and onion latency probes
 
 pub mod proxy;
+pub mod pool;
+pub mod upstream;
+pub mod guard;
+pub mod audit;
+pub mod egress;
+pub mod i2p;
+pub mod fetch;
+pub mod client;
+pub mod retry;
+pub mod onion;
+pub mod rate_limit;
+pub mod breaker;
+pub mod cassette;
+pub mod warc;
+pub mod cache;
+pub mod session;
+pub mod affinity;
+pub mod captcha;
+pub mod metrics;
+pub mod bandwidth;
+pub mod status;
 pub mod crawler;
+pub mod policy;
+pub mod health;
+pub mod engine_probe;
+pub mod directory;
 pub mod scraper;
+pub mod screenshot;
+pub mod images;
+pub mod adapters;
+pub mod embedded;
 
 pub use proxy::*;
+pub use pool::*;
+pub use upstream::*;
+pub use guard::*;
+pub use audit::*;
+pub use egress::*;
+pub use i2p::*;
+pub use fetch::*;
+pub use client::*;
+pub use retry::*;
+pub use onion::*;
+pub use rate_limit::*;
+pub use breaker::*;
+pub use cassette::*;
+pub use warc::*;
+pub use cache::*;
+pub use session::*;
+pub use affinity::*;
+pub use captcha::*;
+pub use metrics::*;
+pub use bandwidth::*;
+pub use status::*;
 pub use crawler::*;
+pub use policy::*;
+pub use health::*;
+pub use engine_probe::*;
+pub use directory::*;
 pub use scraper::*;
+pub use screenshot::*;
+pub use images::*;
+pub use adapters::*;
+pub use embedded::*;
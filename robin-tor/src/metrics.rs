@@ -0,0 +1,140 @@
+//! Request timing, traffic and failure counters
+//!
+//! Every live request through [`crate::fetch_request`] is recorded against
+//! its destination host, and every engine crawl against the engine's name, so
+//! slow or flaky engines and onion services stand out at the end of a run.
+//! Cassette replays are not recorded.
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Counters for one host or engine
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct EndpointStats {
+    /// Requests made (each retry counts)
+    pub requests: u64,
+    /// Requests that errored or got a non-2xx status
+    pub failures: u64,
+    /// Request and response body bytes
+    pub bytes: u64,
+    /// Summed request time in milliseconds
+    pub total_ms: u64,
+    /// Slowest request in milliseconds
+    pub max_ms: u64,
+}
+
+impl EndpointStats {
+    /// Mean request time in milliseconds
+    pub fn avg_ms(&self) -> u64 {
+        self.total_ms.checked_div(self.requests).unwrap_or(0)
+    }
+
+    /// Fraction of requests that failed
+    pub fn failure_rate(&self) -> f64 {
+        if self.requests == 0 {
+            0.0
+        } else {
+            self.failures as f64 / self.requests as f64
+        }
+    }
+
+    fn record(&mut self, elapsed: Duration, bytes: usize, ok: bool) {
+        let ms = elapsed.as_millis() as u64;
+        self.requests += 1;
+        self.failures += u64::from(!ok);
+        self.bytes += bytes as u64;
+        self.total_ms += ms;
+        self.max_ms = self.max_ms.max(ms);
+    }
+}
+
+/// Point-in-time copy of [`TorMetrics`]
+#[derive(Debug, Clone, Default)]
+pub struct MetricsSnapshot {
+    /// Per destination host
+    pub hosts: BTreeMap<String, EndpointStats>,
+    /// Per search engine (one entry per crawl, including its retries)
+    pub engines: BTreeMap<String, EndpointStats>,
+}
+
+impl MetricsSnapshot {
+    /// Totals across all hosts
+    pub fn total(&self) -> EndpointStats {
+        self.hosts.values().fold(EndpointStats::default(), |mut total, s| {
+            total.requests += s.requests;
+            total.failures += s.failures;
+            total.bytes += s.bytes;
+            total.total_ms += s.total_ms;
+            total.max_ms = total.max_ms.max(s.max_ms);
+            total
+        })
+    }
+
+    /// Engines, slowest (by mean request time) first
+    pub fn slowest_engines(&self) -> Vec<(&str, &EndpointStats)> {
+        let mut engines: Vec<_> = self.engines.iter().map(|(name, s)| (name.as_str(), s)).collect();
+        engines.sort_by_key(|(_, s)| std::cmp::Reverse(s.avg_ms()));
+        engines
+    }
+}
+
+#[derive(Debug, Default)]
+struct MetricsState {
+    hosts: HashMap<String, EndpointStats>,
+    engines: HashMap<String, EndpointStats>,
+}
+
+/// Shared request metrics
+///
+/// Cloning is cheap and all clones share the same counters.
+#[derive(Debug, Clone, Default)]
+pub struct TorMetrics {
+    state: Arc<Mutex<MetricsState>>,
+}
+
+impl TorMetrics {
+    /// Record one request to `host`
+    pub fn record_host(&self, host: &str, elapsed: Duration, bytes: usize, ok: bool) {
+        let mut state = self.state.lock().unwrap();
+        state.hosts.entry(host.to_string()).or_default().record(elapsed, bytes, ok);
+    }
+
+    /// Record one crawl of the engine `name`
+    pub fn record_engine(&self, name: &str, elapsed: Duration, bytes: usize, ok: bool) {
+        let mut state = self.state.lock().unwrap();
+        state.engines.entry(name.to_string()).or_default().record(elapsed, bytes, ok);
+    }
+
+    /// Copy of the current counters
+    pub fn snapshot(&self) -> MetricsSnapshot {
+        let state = self.state.lock().unwrap();
+        MetricsSnapshot {
+            hosts: state.hosts.iter().map(|(k, v)| (k.clone(), *v)).collect(),
+            engines: state.engines.iter().map(|(k, v)| (k.clone(), *v)).collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_metrics_aggregate_per_endpoint() {
+        let metrics = TorMetrics::default();
+        let shared = metrics.clone();
+        shared.record_host("a.onion", Duration::from_millis(100), 10, true);
+        shared.record_host("a.onion", Duration::from_millis(300), 0, false);
+        shared.record_host("b.onion", Duration::from_millis(50), 5, true);
+        metrics.record_engine("Ahmia", Duration::from_millis(900), 15, true);
+        metrics.record_engine("Torgle", Duration::from_millis(4000), 0, false);
+
+        let snapshot = metrics.snapshot();
+        let a = snapshot.hosts["a.onion"];
+        assert_eq!((a.requests, a.failures, a.bytes, a.avg_ms(), a.max_ms), (2, 1, 10, 200, 300));
+        assert_eq!(a.failure_rate(), 0.5);
+        assert_eq!(snapshot.total().requests, 3);
+        assert_eq!(snapshot.slowest_engines()[0].0, "Torgle");
+    }
+}
@@ -0,0 +1,234 @@
+//! Onion address validation and reachability pre-checks
+//!
+//! A v3 onion address is the base32 encoding of
+//! `PUBKEY (32 bytes) | CHECKSUM (2 bytes) | VERSION (1 byte)`, where
+//! `CHECKSUM = SHA3-256(".onion checksum" | PUBKEY | VERSION)[..2]`.
+//! Validating it locally catches mangled links (truncated, mistyped, v2)
+//! before a Tor circuit is spent on them.
+
+use std::time::Duration;
+
+use sha3::{Digest, Sha3_256};
+use thiserror::Error;
+use tracing::debug;
+
+use crate::{create_tor_client_for, fetch::is_proxy_unreachable, rate_limit::host_key, TorConfig, Transfer};
+
+/// Length of a v3 onion label in base32 characters
+pub const ONION_V3_LEN: usize = 56;
+
+const ONION_V3_VERSION: u8 = 3;
+
+/// Why an onion address was rejected
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum OnionError {
+    #[error("not an onion address: {0}")]
+    NotOnion(String),
+
+    #[error("not a v3 onion address (label is {0} characters, expected 56)")]
+    WrongLength(usize),
+
+    #[error("invalid base32 in onion address")]
+    InvalidBase32,
+
+    #[error("unsupported onion address version {0}")]
+    BadVersion(u8),
+
+    #[error("onion address checksum mismatch")]
+    BadChecksum,
+}
+
+/// Validate the v3 onion address in `url` (a URL or bare host)
+///
+/// Returns the normalized `<56 chars>.onion` host. Subdomains
+/// (`www.<addr>.onion`) are accepted and stripped.
+pub fn validate_onion(url: &str) -> Result<String, OnionError> {
+    let rest = url.split_once("://").map_or(url, |(_, rest)| rest);
+    let authority = rest.split(['/', '?', '#']).next().unwrap_or_default();
+    let host = authority.rsplit('@').next().unwrap_or_default();
+    let host = host.split(':').next().unwrap_or_default().to_ascii_lowercase();
+
+    let label = host
+        .trim_end_matches('.')
+        .strip_suffix(".onion")
+        .and_then(|h| h.rsplit('.').next())
+        .ok_or_else(|| OnionError::NotOnion(url.to_string()))?;
+
+    if label.len() != ONION_V3_LEN {
+        return Err(OnionError::WrongLength(label.len()));
+    }
+
+    let bytes = base32_decode(label).ok_or(OnionError::InvalidBase32)?;
+    let (pubkey, rest) = bytes.split_at(32);
+    let (checksum, version) = rest.split_at(2);
+
+    if version[0] != ONION_V3_VERSION {
+        return Err(OnionError::BadVersion(version[0]));
+    }
+
+    let mut hasher = Sha3_256::new();
+    hasher.update(b".onion checksum");
+    hasher.update(pubkey);
+    hasher.update(version);
+    if hasher.finalize()[..2] != *checksum {
+        return Err(OnionError::BadChecksum);
+    }
+
+    Ok(format!("{}.onion", label))
+}
+
+/// Whether `url` holds a valid v3 onion address
+pub fn is_valid_onion(url: &str) -> bool {
+    validate_onion(url).is_ok()
+}
+
+/// Probe whether an onion service answers at all, within `timeout`
+///
+/// Any HTTP response counts as reachable; a missing descriptor or dead
+/// service shows up as a connection failure or timeout. Outcomes feed the
+/// host's circuit breaker, and a host with an open circuit is unreachable
+/// without a probe. URLs outside the crawl policy, or past the bandwidth
+/// cap, are never probed; probes are charged to `config.bandwidth`.
+/// Always true when replaying a cassette.
+pub async fn probe_onion(url: &str, config: &TorConfig, timeout: Duration) -> bool {
+    if config.is_replay() {
+        return true;
+    }
+    if let Err(reason) = config.crawl_policy.check(url) {
+        debug!("Onion probe of {} skipped: {}", url, reason);
+        return false;
+    }
+    if let Err(e) = config.bandwidth.check() {
+        debug!("Onion probe of {} skipped: {}", url, e);
+        return false;
+    }
+
+    let host = host_key(url).unwrap_or_default();
+    let Ok(permit) = config.breaker.admit(&host) else {
+        debug!("Onion probe of {} skipped: circuit open", url);
+        return false;
+    };
+
+    let probe_config = TorConfig {
+        timeout_secs: timeout.as_secs().max(1),
+        ..config.clone()
+    };
+    let client = match create_tor_client_for(url, &probe_config) {
+        Ok(client) => client,
+        Err(_) => return false,
+    };
+
+    match client.head(url).send().await {
+        Ok(response) => {
+            let headers: usize = response.headers().iter().map(|(name, value)| name.as_str().len() + value.len() + 4).sum();
+            let transfer = Transfer {
+                sent: ("HEAD".len() + url.len() + 11) as u64,
+                received: headers as u64,
+            };
+            config.bandwidth.charge(&host, config.agent.as_deref(), transfer);
+            permit.record(true);
+            true
+        }
+        Err(e) => {
+            debug!("Onion probe of {} failed: {}", url, e);
+            if !is_proxy_unreachable(&e) {
+                permit.record(false);
+            }
+            false
+        }
+    }
+}
+
+/// Decode unpadded RFC 4648 base32 (lowercase) into exactly 35 bytes
+fn base32_decode(label: &str) -> Option<Vec<u8>> {
+    let mut out = Vec::with_capacity(35);
+    let mut buffer: u64 = 0;
+    let mut bits = 0;
+
+    for c in label.bytes() {
+        let value = match c {
+            b'a'..=b'z' => c - b'a',
+            b'2'..=b'7' => c - b'2' + 26,
+            _ => return None,
+        };
+        buffer = (buffer << 5) | value as u64;
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((buffer >> bits) as u8);
+        }
+    }
+
+    (out.len() == 35).then_some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TOR_PROJECT: &str = "2gzyxa5ihm7nsggfxnu52rck2vv4rvmdlkiu3zzui5du4xyclen53wid.onion";
+
+    #[test]
+    fn test_valid_v3_addresses() {
+        assert_eq!(validate_onion(TOR_PROJECT).unwrap(), TOR_PROJECT);
+        assert_eq!(
+            validate_onion(&format!("http://www.{}:80/index.html", TOR_PROJECT.to_uppercase())).unwrap(),
+            TOR_PROJECT
+        );
+        for engine in robin_core::SearchEngineRegistry::embedded().active(robin_core::Network::Tor) {
+            assert!(is_valid_onion(&engine.url_template), "{}", engine.name);
+        }
+    }
+
+    #[test]
+    fn test_invalid_addresses() {
+        // One character changed: checksum no longer matches
+        let mangled = TOR_PROJECT.replacen('2', "3", 1);
+        assert_eq!(validate_onion(&mangled), Err(OnionError::BadChecksum));
+
+        assert_eq!(validate_onion("http://expyuzz4wqqyqhjn.onion/"), Err(OnionError::WrongLength(16)));
+        assert_eq!(
+            validate_onion(&format!("{}1.onion", &TOR_PROJECT[..55])),
+            Err(OnionError::InvalidBase32)
+        );
+        assert!(matches!(validate_onion("http://example.com/"), Err(OnionError::NotOnion(_))));
+    }
+
+    #[tokio::test]
+    async fn test_probe_skipped_on_replay() {
+        let config = TorConfig {
+            socks_addr: "socks5h://127.0.0.1:1".to_string(),
+            cassette: Some(crate::Cassette::from_interactions(Vec::new())),
+            ..Default::default()
+        };
+        assert!(probe_onion(TOR_PROJECT, &config, Duration::from_secs(1)).await);
+
+        let live = TorConfig {
+            socks_addr: "socks5h://127.0.0.1:1".to_string(),
+            ..Default::default()
+        };
+        assert!(!probe_onion(&format!("http://{}/", TOR_PROJECT), &live, Duration::from_secs(1)).await);
+
+        // Out-of-scope hosts aren't contacted, so they can't trip the breaker
+        let denied = TorConfig {
+            socks_addr: "socks5h://127.0.0.1:1".to_string(),
+            crawl_policy: crate::CrawlPolicy::default().with_deny(TOR_PROJECT),
+            breaker: crate::HostBreaker::new(crate::BreakerPolicy {
+                failure_threshold: 1,
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        assert!(!probe_onion(&format!("http://{}/", TOR_PROJECT), &denied, Duration::from_secs(1)).await);
+        assert!(denied.breaker.trips().is_empty());
+
+        // Nor are hosts once the bandwidth cap is reached
+        let capped = TorConfig {
+            crawl_policy: crate::CrawlPolicy::default(),
+            bandwidth: crate::BandwidthMeter::with_limit(0),
+            ..denied
+        };
+        assert!(!probe_onion(&format!("http://{}/", TOR_PROJECT), &capped, Duration::from_secs(1)).await);
+        assert!(capped.breaker.trips().is_empty());
+    }
+}
@@ -0,0 +1,375 @@
+//! Crawl scope policy
+//!
+//! Organizations running investigations need hard scope boundaries. A
+//! [`CrawlPolicy`] decides which pages may be scraped: host allowlists and
+//! denylists (a host covers its subdomains), URL patterns to exclude (a
+//! built-in set screens out CSAM-risk URLs), a page budget per host and,
+//! for clearnet targets, robots.txt. Search results and directory entries
+//! outside the policy are dropped, and [`crate::scrape_url`] refuses such
+//! URLs with [`TorError::OutOfScope`].
+//!
+//! Policies load from TOML:
+//!
+//! ```toml
+//! allow = ["dreadytofatroptsdj6io7l3xptbet6onoyno2yv7jicoxknyazubrad.onion"]
+//! deny = ["example-market.onion"]
+//! exclude = ["(?i)/(porn|gore)/"]
+//! max_pages_per_host = 50
+//! respect_robots = true
+//! ```
+
+use regex::Regex;
+use robin_core::Network;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::{Arc, LazyLock, Mutex};
+use thiserror::Error;
+use tracing::debug;
+
+use crate::{fetch, host_key, TorConfig, TorError};
+
+/// URL patterns excluded unless a policy turns them off: CSAM-risk terms
+static BUILTIN_EXCLUSIONS: LazyLock<Vec<Regex>> = LazyLock::new(|| {
+    [r"(?i)\b(pthc|jailbait|hurtcore|lolita|preteens?|pedo(philia|phile)?)\b", r"(?i)child[-_ ]?(porn|abuse|sex)"]
+        .iter()
+        .map(|p| Regex::new(p).unwrap())
+        .collect()
+});
+
+/// Errors loading a crawl policy
+#[derive(Debug, Error)]
+pub enum PolicyError {
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Invalid crawl policy: {0}")]
+    Parse(#[from] toml::de::Error),
+
+    #[error("Invalid exclude pattern: {0}")]
+    Pattern(#[from] regex::Error),
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct PolicyFile {
+    allow: Vec<String>,
+    deny: Vec<String>,
+    exclude: Vec<String>,
+    max_pages_per_host: Option<usize>,
+    respect_robots: bool,
+    builtin_exclusions: bool,
+}
+
+impl Default for PolicyFile {
+    fn default() -> Self {
+        Self {
+            allow: Vec::new(),
+            deny: Vec::new(),
+            exclude: Vec::new(),
+            max_pages_per_host: None,
+            respect_robots: false,
+            builtin_exclusions: true,
+        }
+    }
+}
+
+/// Which URLs a run may scrape
+///
+/// The default policy only applies the built-in exclusions. Clones share
+/// their per-host page counts and robots.txt cache.
+#[derive(Debug, Clone)]
+pub struct CrawlPolicy {
+    /// Hosts in scope (empty = every host not denied)
+    allow: Vec<String>,
+    deny: Vec<String>,
+    exclude: Vec<Regex>,
+    builtin_exclusions: bool,
+    max_pages_per_host: Option<usize>,
+    respect_robots: bool,
+    /// Pages admitted per host
+    pages: Arc<Mutex<HashMap<String, usize>>>,
+    /// robots.txt rules per clearnet host (None = no usable robots.txt)
+    robots: Arc<Mutex<HashMap<String, Option<RobotsRules>>>>,
+}
+
+impl Default for CrawlPolicy {
+    fn default() -> Self {
+        Self {
+            allow: Vec::new(),
+            deny: Vec::new(),
+            exclude: Vec::new(),
+            builtin_exclusions: true,
+            max_pages_per_host: None,
+            respect_robots: false,
+            pages: Arc::default(),
+            robots: Arc::default(),
+        }
+    }
+}
+
+impl CrawlPolicy {
+    /// Parse a TOML policy
+    pub fn from_toml(toml: &str) -> Result<Self, PolicyError> {
+        let file: PolicyFile = toml::from_str(toml)?;
+        let mut policy = Self {
+            builtin_exclusions: file.builtin_exclusions,
+            max_pages_per_host: file.max_pages_per_host,
+            respect_robots: file.respect_robots,
+            ..Default::default()
+        };
+        for host in &file.allow {
+            policy = policy.with_allow(host);
+        }
+        for host in &file.deny {
+            policy = policy.with_deny(host);
+        }
+        for pattern in &file.exclude {
+            policy = policy.with_exclude(pattern)?;
+        }
+        Ok(policy)
+    }
+
+    /// Load a TOML policy file
+    pub fn load_file(path: impl AsRef<Path>) -> Result<Self, PolicyError> {
+        Self::from_toml(&std::fs::read_to_string(path)?)
+    }
+
+    /// Only scrape `host` (and its subdomains), plus other allowed hosts
+    pub fn with_allow(mut self, host: &str) -> Self {
+        self.allow.push(normalize_host(host));
+        self
+    }
+
+    /// Never scrape `host` or its subdomains
+    pub fn with_deny(mut self, host: &str) -> Self {
+        self.deny.push(normalize_host(host));
+        self
+    }
+
+    /// Never scrape URLs matching the regex `pattern`
+    pub fn with_exclude(mut self, pattern: &str) -> Result<Self, PolicyError> {
+        self.exclude.push(Regex::new(pattern)?);
+        Ok(self)
+    }
+
+    /// Scrape at most `max` pages of any one host
+    pub fn with_max_pages_per_host(mut self, max: usize) -> Self {
+        self.max_pages_per_host = Some(max);
+        self
+    }
+
+    /// Honour robots.txt on clearnet hosts
+    pub fn with_robots(mut self, respect: bool) -> Self {
+        self.respect_robots = respect;
+        self
+    }
+
+    /// Whether `url` is in scope by host and pattern; the reason when it isn't
+    ///
+    /// Doesn't count against the page budget or consult robots.txt.
+    pub fn check(&self, url: &str) -> Result<(), String> {
+        let Some(host) = host_key(url) else {
+            return Err("no host".to_string());
+        };
+        if self.deny.iter().any(|d| covers(d, &host)) {
+            return Err(format!("{} is denied", host));
+        }
+        if !self.allow.is_empty() && !self.allow.iter().any(|a| covers(a, &host)) {
+            return Err(format!("{} is not on the allowlist", host));
+        }
+        if self.excludes(url) {
+            return Err("URL matches an exclusion pattern".to_string());
+        }
+        Ok(())
+    }
+
+    /// Whether `text` (a URL or a result title) matches an exclusion pattern
+    pub fn excludes(&self, text: &str) -> bool {
+        let builtin = self.builtin_exclusions.then(|| BUILTIN_EXCLUSIONS.iter()).into_iter().flatten();
+        builtin.chain(&self.exclude).any(|pattern| pattern.is_match(text))
+    }
+
+    /// Whether a search result or directory entry is in scope
+    pub fn permits_result(&self, url: &str, title: &str) -> bool {
+        self.check(url).is_ok() && !self.excludes(title)
+    }
+
+    /// Admit `url` for scraping: in scope, allowed by robots.txt (when
+    /// respected) and within its host's page budget
+    ///
+    /// The budget is only used once the page is fetched ([`CrawlPolicy::charge`]).
+    pub async fn admit(&self, url: &str, config: &TorConfig) -> Result<(), TorError> {
+        self.check(url).map_err(|reason| TorError::OutOfScope(format!("{}: {}", url, reason)))?;
+        if self.respect_robots && Network::classify(url) == Network::Clearnet && !self.robots_allow(url, config).await {
+            return Err(TorError::OutOfScope(format!("{}: disallowed by robots.txt", url)));
+        }
+        let host = host_key(url).unwrap_or_default();
+        let count = self.pages.lock().unwrap().get(&host).copied().unwrap_or_default();
+        if self.max_pages_per_host.is_some_and(|max| count >= max) {
+            return Err(TorError::OutOfScope(format!("{}: page budget for {} used up", url, host)));
+        }
+        Ok(())
+    }
+
+    /// Count a fetched page of `url` against its host's page budget
+    pub fn charge(&self, url: &str) {
+        *self.pages.lock().unwrap().entry(host_key(url).unwrap_or_default()).or_default() += 1;
+    }
+
+    async fn robots_allow(&self, url: &str, config: &TorConfig) -> bool {
+        let Ok(parsed) = reqwest::Url::parse(url) else {
+            return false;
+        };
+        let host = host_key(url).unwrap_or_default();
+        let cached = self.robots.lock().unwrap().get(&host).cloned();
+        let rules = match cached {
+            Some(rules) => rules,
+            None => {
+                let robots_url = format!("{}/robots.txt", parsed.origin().ascii_serialization());
+                let rules = match fetch(&robots_url, config).await {
+                    Ok(response) if response.is_success() => Some(RobotsRules::parse(&response.body)),
+                    Ok(_) => None,
+                    Err(e) => {
+                        debug!("No robots.txt for {}: {}", host, e);
+                        None
+                    }
+                };
+                self.robots.lock().unwrap().insert(host, rules.clone());
+                rules
+            }
+        };
+        let path = match parsed.query() {
+            Some(query) => format!("{}?{}", parsed.path(), query),
+            None => parsed.path().to_string(),
+        };
+        rules.is_none_or(|rules| rules.allows(&path))
+    }
+}
+
+fn normalize_host(host: &str) -> String {
+    let host = host.trim().trim_start_matches("*.");
+    let host = host.split_once("://").map_or(host, |(_, rest)| rest);
+    host.trim_end_matches('/').to_ascii_lowercase()
+}
+
+/// Whether the policy entry `entry` covers `host` (itself or a subdomain)
+fn covers(entry: &str, host: &str) -> bool {
+    host == entry || host.strip_suffix(entry).is_some_and(|prefix| prefix.ends_with('.'))
+}
+
+/// The `User-agent: *` rules of a robots.txt
+#[derive(Debug, Clone, Default, PartialEq)]
+struct RobotsRules {
+    /// (allow, path pattern)
+    rules: Vec<(bool, String)>,
+}
+
+impl RobotsRules {
+    /// Rules for every user agent; requests carry rotating browser user
+    /// agents, so only `*` groups apply
+    fn parse(text: &str) -> Self {
+        let mut rules = Vec::new();
+        let mut agents: Vec<String> = Vec::new();
+        let mut in_rules = false;
+        for line in text.lines() {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            let Some((key, value)) = line.split_once(':') else {
+                continue;
+            };
+            let value = value.trim();
+            match key.trim().to_ascii_lowercase().as_str() {
+                "user-agent" => {
+                    if in_rules {
+                        agents.clear();
+                        in_rules = false;
+                    }
+                    agents.push(value.to_string());
+                }
+                directive @ ("allow" | "disallow") => {
+                    in_rules = true;
+                    // An empty Disallow allows everything
+                    if agents.iter().any(|a| a == "*") && !value.is_empty() {
+                        rules.push((directive == "allow", value.to_string()));
+                    }
+                }
+                _ => {}
+            }
+        }
+        Self { rules }
+    }
+
+    /// Whether `path` may be fetched: the longest matching rule wins, Allow on a tie
+    fn allows(&self, path: &str) -> bool {
+        self.rules
+            .iter()
+            .filter(|(_, pattern)| robots_match(pattern, path))
+            .max_by_key(|(allow, pattern)| (pattern.len(), *allow))
+            .is_none_or(|(allow, _)| *allow)
+    }
+}
+
+/// robots.txt path matching: a prefix match, with `*` wildcards and a `$` end anchor
+fn robots_match(pattern: &str, path: &str) -> bool {
+    let (pattern, anchored) = match pattern.strip_suffix('$') {
+        Some(pattern) => (pattern, true),
+        None => (pattern, false),
+    };
+    let regex = pattern.split('*').map(regex::escape).collect::<Vec<_>>().join(".*");
+    let regex = format!("^{}{}", regex, if anchored { "$" } else { "" });
+    Regex::new(&regex).is_ok_and(|r| r.is_match(path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ONION: &str = "http://2gzyxa5ihm7nsggfxnu52rck2vv4rvmdlkiu3zzui5du4xyclen53wid.onion";
+
+    #[tokio::test]
+    async fn test_policy_scope() {
+        let policy = CrawlPolicy::from_toml(
+            r#"
+            allow = ["2gzyxa5ihm7nsggfxnu52rck2vv4rvmdlkiu3zzui5du4xyclen53wid.onion", "example.com"]
+            deny = ["ads.example.com"]
+            exclude = ["(?i)/gore/"]
+            max_pages_per_host = 2
+            "#,
+        )
+        .unwrap();
+        assert!(policy.check(&format!("{}/forum", ONION)).is_ok());
+        assert!(policy.check("https://news.example.com/").is_ok());
+        assert!(policy.check("https://ads.example.com/x").unwrap_err().contains("denied"));
+        assert!(policy.check("https://other.org/").unwrap_err().contains("allowlist"));
+        assert!(policy.check(&format!("{}/GORE/1", ONION)).is_err());
+        // Built-in exclusions apply to URLs and titles
+        assert!(policy.check(&format!("{}/pthc/", ONION)).is_err());
+        assert!(!policy.permits_result(&format!("{}/x", ONION), "Jailbait gallery"));
+        assert!(CrawlPolicy::from_toml("builtin_exclusions = false").unwrap().check("http://a.onion/pthc").is_ok());
+        assert!(CrawlPolicy::from_toml("exclude = [\"(\"]").is_err());
+        assert!(CrawlPolicy::from_toml("alow = []").is_err());
+
+        // The page budget is shared by clones, and only fetched pages use it
+        let config = TorConfig::default();
+        let clone = policy.clone();
+        assert!(policy.admit(&format!("{}/1", ONION), &config).await.is_ok());
+        assert!(policy.admit(&format!("{}/1", ONION), &config).await.is_ok());
+        policy.charge(&format!("{}/1", ONION));
+        assert!(clone.admit(&format!("{}/2", ONION), &config).await.is_ok());
+        clone.charge(&format!("{}/2", ONION));
+        assert!(matches!(policy.admit(&format!("{}/3", ONION), &config).await, Err(TorError::OutOfScope(_))));
+    }
+
+    #[test]
+    fn test_robots_rules() {
+        let rules = RobotsRules::parse(
+            "User-agent: Googlebot\nDisallow: /\n\nUser-agent: *\nDisallow: /private/\nAllow: /private/public*\nDisallow: /*.pdf$\nDisallow:\n",
+        );
+        assert_eq!(rules.rules.len(), 3);
+        assert!(rules.allows("/"));
+        assert!(!rules.allows("/private/x"));
+        assert!(rules.allows("/private/public/page"));
+        assert!(!rules.allows("/files/report.pdf"));
+        assert!(rules.allows("/files/report.pdf?download=1"));
+    }
+}
@@ -0,0 +1,384 @@
+//! SOCKS proxy pool
+//!
+//! Spreads requests over several Tor SOCKS endpoints (e.g. multiple Tor
+//! daemons) with round-robin or least-loaded selection. Each endpoint tracks
+//! its health passively: repeated connection failures take it out of rotation
+//! for a cooldown period, and any successful request brings it back.
+
+use std::fmt;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use tracing::warn;
+
+use crate::TorError;
+
+/// Consecutive failures before an endpoint is taken out of rotation
+pub const DEFAULT_FAILURE_THRESHOLD: u32 = 3;
+
+/// How long an unhealthy endpoint stays out of rotation
+pub const DEFAULT_COOLDOWN: Duration = Duration::from_secs(30);
+
+/// How the next endpoint is chosen
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SelectionStrategy {
+    /// Rotate through healthy endpoints in order
+    #[default]
+    RoundRobin,
+    /// Pick the healthy endpoint with the fewest in-flight requests
+    LeastLoaded,
+}
+
+impl fmt::Display for SelectionStrategy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SelectionStrategy::RoundRobin => write!(f, "round-robin"),
+            SelectionStrategy::LeastLoaded => write!(f, "least-loaded"),
+        }
+    }
+}
+
+impl FromStr for SelectionStrategy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "round-robin" => Ok(SelectionStrategy::RoundRobin),
+            "least-loaded" => Ok(SelectionStrategy::LeastLoaded),
+            other => Err(format!(
+                "unknown proxy strategy '{}' (expected 'round-robin' or 'least-loaded')",
+                other
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct EndpointHealth {
+    consecutive_failures: u32,
+    successes: u64,
+    failures: u64,
+    down_until: Option<Instant>,
+}
+
+#[derive(Debug)]
+struct Endpoint {
+    addr: String,
+    in_flight: AtomicUsize,
+    health: Mutex<EndpointHealth>,
+}
+
+impl Endpoint {
+    fn is_healthy(&self, now: Instant) -> bool {
+        match self.health.lock().unwrap().down_until {
+            Some(until) => now >= until,
+            None => true,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct PoolState {
+    endpoints: Vec<Endpoint>,
+    next: AtomicUsize,
+}
+
+/// Point-in-time health of one endpoint
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProxyStats {
+    /// SOCKS address
+    pub addr: String,
+    /// Whether the endpoint is currently in rotation
+    pub healthy: bool,
+    /// Requests currently using this endpoint
+    pub in_flight: usize,
+    /// Successful requests
+    pub successes: u64,
+    /// Failed requests
+    pub failures: u64,
+    /// Failures since the last success
+    pub consecutive_failures: u32,
+}
+
+/// Shared pool of SOCKS endpoints
+///
+/// Cloning is cheap and all clones share health and load state, so one pool
+/// can be handed to every agent through [`crate::TorConfig`].
+#[derive(Debug, Clone)]
+pub struct ProxyPool {
+    strategy: SelectionStrategy,
+    failure_threshold: u32,
+    cooldown: Duration,
+    state: Arc<PoolState>,
+}
+
+impl ProxyPool {
+    /// Create a pool over the given SOCKS addresses
+    pub fn new<I, S>(addrs: I) -> Result<Self, TorError>
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let endpoints: Vec<Endpoint> = addrs
+            .into_iter()
+            .map(|addr| Endpoint {
+                addr: addr.into(),
+                in_flight: AtomicUsize::new(0),
+                health: Mutex::default(),
+            })
+            .collect();
+
+        if endpoints.is_empty() {
+            return Err(TorError::ClientBuild("proxy pool needs at least one SOCKS address".to_string()));
+        }
+
+        Ok(Self {
+            strategy: SelectionStrategy::default(),
+            failure_threshold: DEFAULT_FAILURE_THRESHOLD,
+            cooldown: DEFAULT_COOLDOWN,
+            state: Arc::new(PoolState {
+                endpoints,
+                next: AtomicUsize::new(0),
+            }),
+        })
+    }
+
+    /// Set the selection strategy
+    pub fn with_strategy(mut self, strategy: SelectionStrategy) -> Self {
+        self.strategy = strategy;
+        self
+    }
+
+    /// Set how many consecutive failures take an endpoint out of rotation
+    pub fn with_failure_threshold(mut self, threshold: u32) -> Self {
+        self.failure_threshold = threshold.max(1);
+        self
+    }
+
+    /// Set how long an unhealthy endpoint stays out of rotation
+    pub fn with_cooldown(mut self, cooldown: Duration) -> Self {
+        self.cooldown = cooldown;
+        self
+    }
+
+    /// Selection strategy
+    pub fn strategy(&self) -> SelectionStrategy {
+        self.strategy
+    }
+
+    /// Number of endpoints
+    pub fn len(&self) -> usize {
+        self.state.endpoints.len()
+    }
+
+    /// Whether the pool has no endpoints (never true for a constructed pool)
+    pub fn is_empty(&self) -> bool {
+        self.state.endpoints.is_empty()
+    }
+
+    /// All endpoint addresses, in configuration order
+    pub fn addrs(&self) -> Vec<String> {
+        self.state.endpoints.iter().map(|e| e.addr.clone()).collect()
+    }
+
+    /// Pick an endpoint and mark a request in flight on it
+    ///
+    /// If every endpoint is out of rotation, the one whose cooldown ends
+    /// first is used rather than failing outright.
+    pub fn acquire(&self) -> ProxyLease {
+        let idx = self.select();
+        self.state.endpoints[idx].in_flight.fetch_add(1, Ordering::SeqCst);
+        ProxyLease {
+            pool: self.clone(),
+            idx,
+        }
+    }
+
+    /// Like [`ProxyPool::acquire`], but use the endpoint at `addr` while it is healthy
+    pub fn acquire_preferring(&self, addr: Option<&str>) -> ProxyLease {
+        let now = Instant::now();
+        let preferred = addr.and_then(|addr| {
+            self.state
+                .endpoints
+                .iter()
+                .position(|e| e.addr == addr && e.is_healthy(now))
+        });
+        let idx = preferred.unwrap_or_else(|| self.select());
+        self.state.endpoints[idx].in_flight.fetch_add(1, Ordering::SeqCst);
+        ProxyLease {
+            pool: self.clone(),
+            idx,
+        }
+    }
+
+    fn select(&self) -> usize {
+        let endpoints = &self.state.endpoints;
+        let start = self.state.next.fetch_add(1, Ordering::Relaxed) % endpoints.len();
+        let now = Instant::now();
+
+        // Rotate the scan start so ties are spread across endpoints
+        let mut healthy = (0..endpoints.len())
+            .map(|offset| (start + offset) % endpoints.len())
+            .filter(|&idx| endpoints[idx].is_healthy(now));
+
+        let chosen = match self.strategy {
+            SelectionStrategy::RoundRobin => healthy.next(),
+            SelectionStrategy::LeastLoaded => {
+                healthy.min_by_key(|&idx| endpoints[idx].in_flight.load(Ordering::SeqCst))
+            }
+        };
+
+        chosen.unwrap_or_else(|| {
+            (0..endpoints.len())
+                .min_by_key(|&idx| endpoints[idx].health.lock().unwrap().down_until)
+                .unwrap_or(0)
+        })
+    }
+
+    /// Health snapshot of every endpoint
+    pub fn stats(&self) -> Vec<ProxyStats> {
+        let now = Instant::now();
+        self.state
+            .endpoints
+            .iter()
+            .map(|endpoint| {
+                let health = endpoint.health.lock().unwrap();
+                ProxyStats {
+                    addr: endpoint.addr.clone(),
+                    healthy: health.down_until.is_none_or(|until| now >= until),
+                    in_flight: endpoint.in_flight.load(Ordering::SeqCst),
+                    successes: health.successes,
+                    failures: health.failures,
+                    consecutive_failures: health.consecutive_failures,
+                }
+            })
+            .collect()
+    }
+
+    /// Feed an active health-check result into the pool
+    pub fn record_probe(&self, addr: &str, success: bool) {
+        if let Some(idx) = self.state.endpoints.iter().position(|e| e.addr == addr) {
+            self.record(idx, success);
+        }
+    }
+
+    fn record(&self, idx: usize, success: bool) {
+        let endpoint = &self.state.endpoints[idx];
+        let mut health = endpoint.health.lock().unwrap();
+
+        if success {
+            health.successes += 1;
+            health.consecutive_failures = 0;
+            health.down_until = None;
+        } else {
+            health.failures += 1;
+            health.consecutive_failures += 1;
+            if health.consecutive_failures >= self.failure_threshold {
+                if health.down_until.is_none() {
+                    warn!(
+                        "SOCKS proxy {} failed {} times in a row; out of rotation for {:?}",
+                        endpoint.addr, health.consecutive_failures, self.cooldown
+                    );
+                }
+                health.down_until = Some(Instant::now() + self.cooldown);
+            }
+        }
+    }
+}
+
+/// An endpoint checked out for one request
+///
+/// Dropping the lease releases its in-flight slot; report the outcome first
+/// so health tracking sees it.
+#[derive(Debug)]
+pub struct ProxyLease {
+    pool: ProxyPool,
+    idx: usize,
+}
+
+impl ProxyLease {
+    /// SOCKS address of the leased endpoint
+    pub fn addr(&self) -> &str {
+        &self.pool.state.endpoints[self.idx].addr
+    }
+
+    /// The endpoint carried a request successfully
+    pub fn report_success(&self) {
+        self.pool.record(self.idx, true);
+    }
+
+    /// The endpoint itself failed (e.g. SOCKS port refused the connection)
+    pub fn report_failure(&self) {
+        self.pool.record(self.idx, false);
+    }
+}
+
+impl Drop for ProxyLease {
+    fn drop(&mut self) {
+        self.pool.state.endpoints[self.idx]
+            .in_flight
+            .fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pool() -> ProxyPool {
+        ProxyPool::new(["socks5h://127.0.0.1:9050", "socks5h://127.0.0.1:9052"]).unwrap()
+    }
+
+    #[test]
+    fn test_round_robin_rotates() {
+        let pool = pool();
+        let first = pool.acquire().addr().to_string();
+        let second = pool.acquire().addr().to_string();
+        let third = pool.acquire().addr().to_string();
+
+        assert_ne!(first, second);
+        assert_eq!(first, third);
+        assert!(ProxyPool::new(Vec::<String>::new()).is_err());
+    }
+
+    #[test]
+    fn test_least_loaded_avoids_busy_endpoint() {
+        let pool = pool().with_strategy(SelectionStrategy::LeastLoaded);
+
+        let busy = pool.acquire();
+        for _ in 0..4 {
+            assert_ne!(pool.acquire().addr(), busy.addr());
+        }
+
+        drop(busy);
+        assert!(pool.stats().iter().all(|s| s.in_flight == 0));
+    }
+
+    #[test]
+    fn test_failures_take_endpoint_out_of_rotation() {
+        let pool = pool().with_failure_threshold(2);
+
+        let lease = pool.acquire();
+        let bad = lease.addr().to_string();
+        lease.report_failure();
+        lease.report_failure();
+        drop(lease);
+
+        for _ in 0..4 {
+            assert_ne!(pool.acquire().addr(), bad);
+        }
+        let stats = pool.stats();
+        let bad_stats = stats.iter().find(|s| s.addr == bad).unwrap();
+        assert!(!bad_stats.healthy);
+        assert_eq!(bad_stats.failures, 2);
+    }
+
+    #[test]
+    fn test_strategy_parse() {
+        assert_eq!("least-loaded".parse::<SelectionStrategy>(), Ok(SelectionStrategy::LeastLoaded));
+        assert!("random".parse::<SelectionStrategy>().is_err());
+        assert_eq!(SelectionStrategy::RoundRobin.to_string(), "round-robin");
+    }
+}
@@ -2,10 +2,165 @@
 //!
 //! Creates HTTP clients that route through Tor for .onion access.
 
-use reqwest::{Client, Proxy};
+use reqwest::{redirect::Policy, Client, Proxy};
+use std::fmt;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::Arc;
 use std::time::Duration;
 use thiserror::Error;
 
+use crate::{check_proxy, rate_limit::host_key, BandwidthMeter, CaptchaKind, CaptchaSolver, Cassette, CassetteMode, CrawlPolicy, EgressPolicy, EngineHealth, HostBreaker, HostRateLimiter, I2pConfig, ImageConfig, ProxyLease, ProxyPool, ResponseCache, ScreenshotConfig, SessionAffinity, SessionStore, SiteAdapters, TorMetrics, UpstreamProxy, WarcWriter};
+use robin_core::{EngineParsers, SearchEngineRegistry};
+
+/// How the Tor connection is provided
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TorMode {
+    /// Use an external Tor daemon's SOCKS port (`socks_addr`)
+    #[default]
+    External,
+    /// Bootstrap an in-process Tor client via arti (requires the `embedded-tor` feature)
+    Embedded,
+}
+
+impl fmt::Display for TorMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TorMode::External => write!(f, "external"),
+            TorMode::Embedded => write!(f, "embedded"),
+        }
+    }
+}
+
+impl FromStr for TorMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "external" => Ok(TorMode::External),
+            "embedded" => Ok(TorMode::Embedded),
+            other => Err(format!("unknown Tor mode '{}' (expected 'external' or 'embedded')", other)),
+        }
+    }
+}
+
+/// Which TLS certificates Tor clients accept
+///
+/// A v3 onion address already authenticates the service, so self-signed
+/// certificates on onions are harmless; on clearnet sites reached through
+/// Tor they leave the connection open to a malicious exit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TlsPolicy {
+    /// Validate every certificate
+    Strict,
+    /// Accept invalid certificates on .onion hosts only, and don't follow
+    /// their redirects off the onion network
+    #[default]
+    AcceptSelfSignedOnionOnly,
+    /// Accept any certificate (the old behaviour)
+    AcceptAll,
+}
+
+impl TlsPolicy {
+    /// Whether a client for `url` (None = any destination) skips certificate validation
+    pub fn accepts_invalid_certs(&self, url: Option<&str>) -> bool {
+        match self {
+            TlsPolicy::Strict => false,
+            TlsPolicy::AcceptSelfSignedOnionOnly => url.is_some_and(is_onion_url),
+            TlsPolicy::AcceptAll => true,
+        }
+    }
+}
+
+impl fmt::Display for TlsPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TlsPolicy::Strict => write!(f, "strict"),
+            TlsPolicy::AcceptSelfSignedOnionOnly => write!(f, "onion-only"),
+            TlsPolicy::AcceptAll => write!(f, "accept-all"),
+        }
+    }
+}
+
+impl FromStr for TlsPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "strict" => Ok(TlsPolicy::Strict),
+            "onion-only" => Ok(TlsPolicy::AcceptSelfSignedOnionOnly),
+            "accept-all" => Ok(TlsPolicy::AcceptAll),
+            other => Err(format!(
+                "unknown TLS policy '{}' (expected 'strict', 'onion-only' or 'accept-all')",
+                other
+            )),
+        }
+    }
+}
+
+/// Certificate validation for one host (and its subdomains), overriding the [`TlsPolicy`]
+///
+/// Parsed from `host=accept` (skip validation, e.g. a lab server with a
+/// self-signed certificate) or `host=verify` (validate, e.g. an onion known
+/// to have a real certificate).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TlsOverride {
+    pub host: String,
+    /// Whether invalid certificates are accepted on this host
+    pub accept_invalid: bool,
+}
+
+impl TlsOverride {
+    /// Whether this override covers `host`
+    pub fn matches(&self, host: &str) -> bool {
+        host == self.host || host.strip_suffix(&self.host).is_some_and(|prefix| prefix.ends_with('.'))
+    }
+}
+
+impl FromStr for TlsOverride {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (host, mode) = s
+            .split_once('=')
+            .ok_or_else(|| format!("invalid TLS override '{}' (expected host=accept or host=verify)", s))?;
+        let accept_invalid = match mode.to_ascii_lowercase().as_str() {
+            "accept" => true,
+            "verify" => false,
+            other => return Err(format!("unknown TLS override mode '{}' (expected 'accept' or 'verify')", other)),
+        };
+        let host = host.trim().trim_start_matches("*.").to_ascii_lowercase();
+        if host.is_empty() {
+            return Err(format!("TLS override '{}' has no host", s));
+        }
+        Ok(Self { host, accept_invalid })
+    }
+}
+
+/// Whether a client for `url` skips certificate validation under `policy` and `overrides`
+///
+/// The most specific override matching the host wins; without one, the policy decides.
+fn accepts_invalid_certs(policy: TlsPolicy, overrides: &[TlsOverride], url: Option<&str>) -> bool {
+    let host = url.and_then(|u| reqwest::Url::parse(u).ok()).and_then(|u| u.host_str().map(str::to_ascii_lowercase));
+    let chosen = host.as_deref().and_then(|host| {
+        overrides
+            .iter()
+            .filter(|o| o.matches(host))
+            .max_by_key(|o| o.host.len())
+    });
+    match chosen {
+        Some(o) => o.accept_invalid,
+        None => policy.accepts_invalid_certs(url),
+    }
+}
+
+pub(crate) fn is_onion_url(url: &str) -> bool {
+    reqwest::Url::parse(url)
+        .ok()
+        .and_then(|u| u.host_str().map(|h| h.ends_with(".onion")))
+        .unwrap_or(false)
+}
+
 /// Tor proxy configuration
 #[derive(Debug, Clone)]
 pub struct TorConfig {
@@ -15,6 +170,75 @@ pub struct TorConfig {
     pub timeout_secs: u64,
     /// Maximum retries per request
     pub max_retries: u32,
+    /// Initial retry backoff in milliseconds (doubles per attempt)
+    pub retry_base_delay_ms: u64,
+    /// Upper bound on a single retry backoff in milliseconds
+    pub retry_max_delay_ms: u64,
+    /// Certificate validation for HTTPS destinations
+    pub tls_policy: TlsPolicy,
+    /// Per-host exceptions to `tls_policy`
+    pub tls_overrides: Vec<TlsOverride>,
+    /// Fail closed: refuse anything not sent through Tor's SOCKS port with remote DNS
+    /// (see [`crate::check_request`])
+    pub strict: bool,
+    /// External daemon or embedded arti client
+    pub mode: TorMode,
+    /// State/cache directory for the embedded client (default: arti's per-user directories)
+    pub embedded_data_dir: Option<PathBuf>,
+    /// Record/replay cassette (None = plain live traffic)
+    pub cassette: Option<Cassette>,
+    /// Pool of SOCKS endpoints; takes precedence over `socks_addr` when set
+    pub proxy_pool: Option<ProxyPool>,
+    /// Proxy that connections to the Tor SOCKS endpoint(s) are tunnelled through (None = direct)
+    pub upstream_proxy: Option<UpstreamProxy>,
+    /// Politeness limit per destination host (None = unlimited)
+    pub requests_per_host_per_minute: Option<u32>,
+    /// Per-host token buckets, shared by every clone of this config
+    pub rate_limiter: HostRateLimiter,
+    /// I2P router proxy for `.i2p` URLs (None = eepsites are not fetched)
+    pub i2p: Option<I2pConfig>,
+    /// Also query clearnet search engines, through Tor like everything else
+    pub clearnet_search: bool,
+    /// Probe onion services for reachability before scraping, with this timeout (None = no probe)
+    pub onion_probe_timeout_secs: Option<u64>,
+    /// Disk-backed response cache (None = always fetch; ignored with a cassette)
+    pub cache: Option<ResponseCache>,
+    /// Cookie jar and per-domain credentials (None = no cookies or auth)
+    pub sessions: Option<SessionStore>,
+    /// Keep each host on its own circuit and pool endpoint (None = any circuit per request)
+    pub affinity: Option<SessionAffinity>,
+    /// Gets past captcha interstitials (None = fail with `TorError::CaptchaRequired`)
+    pub captcha_solver: Option<Arc<dyn CaptchaSolver>>,
+    /// Request timing and failure counters, shared by every clone of this config
+    pub metrics: TorMetrics,
+    /// Fails requests fast to hosts that keep failing (shared by clones)
+    pub breaker: HostBreaker,
+    /// Bytes transferred per host and agent, and the run's cap (shared by clones)
+    pub bandwidth: BandwidthMeter,
+    /// Agent whose requests this config makes, for bandwidth accounting
+    pub agent: Option<String>,
+    /// Per-engine success and latency, used to bench failing engines (shared by clones)
+    pub engine_health: EngineHealth,
+    /// Result selectors for engines by name (engines carrying their own take precedence)
+    pub engine_parsers: EngineParsers,
+    /// Search engines crawlers query (bundled list plus any loaded overrides)
+    pub search_engines: SearchEngineRegistry,
+    /// Structured parsers the scraper tries before generic extraction
+    pub site_adapters: SiteAdapters,
+    /// Render scraped pages to PNGs with a headless browser (None = no screenshots)
+    pub screenshots: Option<ScreenshotConfig>,
+    /// Download and hash scraped pages' images (None = images are not fetched)
+    pub images: Option<ImageConfig>,
+    /// Longest page text kept by the scraper, in bytes; the rest is cut
+    pub max_page_chars: usize,
+    /// Largest response body downloaded, in bytes; bigger downloads are abandoned
+    pub max_body_bytes: usize,
+    /// WARC file every request and response is archived to (None = no archive)
+    pub archive: Option<WarcWriter>,
+    /// How agents that call clearnet APIs reach them
+    pub egress: EgressPolicy,
+    /// Which pages may be scraped (hosts, URL exclusions, page budgets, robots.txt)
+    pub crawl_policy: CrawlPolicy,
 }
 
 impl Default for TorConfig {
@@ -23,6 +247,61 @@ impl Default for TorConfig {
             socks_addr: "socks5h://127.0.0.1:9050".to_string(),
             timeout_secs: 45,
             max_retries: 3,
+            retry_base_delay_ms: 1000,
+            retry_max_delay_ms: 15000,
+            tls_policy: TlsPolicy::default(),
+            tls_overrides: Vec::new(),
+            strict: false,
+            mode: TorMode::External,
+            embedded_data_dir: None,
+            cassette: None,
+            proxy_pool: None,
+            upstream_proxy: None,
+            requests_per_host_per_minute: None,
+            rate_limiter: HostRateLimiter::default(),
+            i2p: None,
+            clearnet_search: false,
+            onion_probe_timeout_secs: None,
+            cache: None,
+            sessions: None,
+            affinity: None,
+            captcha_solver: None,
+            metrics: TorMetrics::default(),
+            breaker: HostBreaker::default(),
+            bandwidth: BandwidthMeter::default(),
+            agent: None,
+            engine_health: EngineHealth::default(),
+            engine_parsers: EngineParsers::embedded(),
+            search_engines: SearchEngineRegistry::embedded(),
+            site_adapters: SiteAdapters::builtin(),
+            screenshots: None,
+            images: None,
+            max_page_chars: crate::DEFAULT_MAX_PAGE_CHARS,
+            max_body_bytes: crate::DEFAULT_MAX_BODY_BYTES,
+            archive: None,
+            egress: EgressPolicy::default(),
+            crawl_policy: CrawlPolicy::default(),
+        }
+    }
+}
+
+impl TorConfig {
+    /// Whether responses are served from a replay cassette instead of the network
+    pub fn is_replay(&self) -> bool {
+        self.cassette.as_ref().is_some_and(|c| c.mode() == CassetteMode::Replay)
+    }
+
+    /// Whether clients for `url` (None = any destination) skip certificate validation,
+    /// per `tls_policy` and `tls_overrides`
+    pub fn accepts_invalid_certs(&self, url: Option<&str>) -> bool {
+        accepts_invalid_certs(self.tls_policy, &self.tls_overrides, url)
+    }
+
+    /// A clone whose requests are charged to the agent `id`
+    pub fn for_agent(&self, id: &str) -> Self {
+        Self {
+            agent: Some(id.to_string()),
+            ..self.clone()
         }
     }
 }
@@ -44,6 +323,57 @@ pub enum TorError {
 
     #[error("Invalid URL: {0}")]
     InvalidUrl(String),
+
+    #[error("Embedded Tor error: {0}")]
+    Embedded(String),
+
+    #[error("Cassette error: {0}")]
+    Cassette(String),
+
+    #[error("Cache error: {0}")]
+    Cache(String),
+
+    #[error("I2P is not configured; cannot fetch {0}")]
+    I2pNotConfigured(String),
+
+    #[error("Session error: {0}")]
+    Session(String),
+
+    #[error("Tor control port error: {0}")]
+    Control(String),
+
+    #[error("Blocked possible clearnet leak: {0}")]
+    Leak(String),
+
+    #[error("Engine health error: {0}")]
+    Health(String),
+
+    #[error("Archive error: {0}")]
+    Archive(String),
+
+    #[error("Screenshot failed: {0}")]
+    Screenshot(String),
+
+    #[error("HTTP {status} from {url}")]
+    Status { url: String, status: u16 },
+
+    #[error("Audit harness error: {0}")]
+    Audit(String),
+
+    #[error("Out of crawl scope: {0}")]
+    OutOfScope(String),
+
+    #[error("Response from {url} exceeded {limit} bytes")]
+    BodyTooLarge { url: String, limit: usize },
+
+    #[error("Circuit open for {0} after repeated failures")]
+    CircuitOpen(String),
+
+    #[error("Bandwidth cap of {0} bytes reached for this run")]
+    BandwidthExceeded(u64),
+
+    #[error("{kind} challenge at {url}")]
+    CaptchaRequired { url: String, kind: CaptchaKind },
 }
 
 /// User agents for rotation
@@ -63,32 +393,124 @@ pub fn random_user_agent() -> &'static str {
 }
 
 /// Create a Tor-enabled HTTP client
+///
+/// With a proxy pool configured, the client uses the next endpoint from the
+/// pool; use [`create_pooled_client`] to also report its health. The client
+/// is not tied to a destination, so it validates certificates unless the
+/// policy is [`TlsPolicy::AcceptAll`]; see [`create_tor_client_for`].
 pub fn create_tor_client(config: &TorConfig) -> Result<Client, TorError> {
-    let proxy = Proxy::all(&config.socks_addr)
+    create_pooled_client(config, None).map(|(client, _)| client)
+}
+
+/// Create a Tor-enabled HTTP client for requests to `url`, per `config.tls_policy`
+pub fn create_tor_client_for(url: &str, config: &TorConfig) -> Result<Client, TorError> {
+    create_pooled_client(config, Some(url)).map(|(client, _)| client)
+}
+
+/// Create a Tor-enabled HTTP client along with its pool lease, if any
+pub fn create_pooled_client(config: &TorConfig, url: Option<&str>) -> Result<(Client, Option<ProxyLease>), TorError> {
+    match &config.proxy_pool {
+        Some(pool) => {
+            let lease = match (&config.affinity, url.and_then(host_key)) {
+                (Some(affinity), Some(host)) => affinity.lease(pool, &host),
+                _ => pool.acquire(),
+            };
+            let client = build_client(lease.addr(), config, url)?;
+            Ok((client, Some(lease)))
+        }
+        None => Ok((build_client(&config.socks_addr, config, url)?, None)),
+    }
+}
+
+fn build_client(socks_addr: &str, config: &TorConfig, url: Option<&str>) -> Result<Client, TorError> {
+    build_client_with(socks_addr, config, url, Policy::default())
+}
+
+/// Client through `proxy_addr` that doesn't follow redirects, for form logins to `url`'s site
+pub(crate) fn create_login_client(proxy_addr: &str, config: &TorConfig, url: &str) -> Result<Client, TorError> {
+    build_client_with(proxy_addr, config, Some(url), Policy::none())
+}
+
+fn build_client_with(
+    socks_addr: &str,
+    config: &TorConfig,
+    url: Option<&str>,
+    redirects: Policy,
+) -> Result<Client, TorError> {
+    check_proxy(socks_addr, url, config)?;
+    let socks_addr = match &config.upstream_proxy {
+        Some(upstream) => upstream.relay_for(socks_addr)?,
+        None => socks_addr.to_string(),
+    };
+    let socks_addr = match (&config.affinity, url.and_then(host_key)) {
+        (Some(affinity), Some(host)) => affinity.isolate(&socks_addr, &host)?,
+        _ => socks_addr,
+    };
+    let proxy = Proxy::all(socks_addr)
         .map_err(|e| TorError::ClientBuild(e.to_string()))?;
 
+    // Many .onion sites have self-signed certs; a client that accepts them
+    // only for some hosts must not follow a redirect to one that validates
+    let accept_invalid = config.accepts_invalid_certs(url);
+    let redirects = if accept_invalid && config.tls_policy != TlsPolicy::AcceptAll {
+        unverified_only_redirects(redirects, config.tls_policy, config.tls_overrides.clone())
+    } else {
+        redirects
+    };
+
     Client::builder()
         .proxy(proxy)
+        .redirect(redirects)
         .timeout(Duration::from_secs(config.timeout_secs))
         .user_agent(random_user_agent())
-        .danger_accept_invalid_certs(true) // Many .onion sites have self-signed certs
+        .danger_accept_invalid_certs(accept_invalid)
         .build()
         .map_err(|e| TorError::ClientBuild(e.to_string()))
 }
 
+/// `base` restricted to redirects to hosts allowed unverified TLS (onions, by default)
+fn unverified_only_redirects(base: Policy, policy: TlsPolicy, overrides: Vec<TlsOverride>) -> Policy {
+    Policy::custom(move |attempt| {
+        if !accepts_invalid_certs(policy, &overrides, Some(attempt.url().as_str())) {
+            let target = attempt.url().to_string();
+            let reason = if is_onion_url(&target) { "requires a valid certificate" } else { "leaves the onion network" };
+            attempt.error(format!("redirect to {} {}", target, reason))
+        } else {
+            base.redirect(attempt)
+        }
+    })
+}
+
+/// Known-good onion used for connectivity checks (Tor Project)
+const CHECK_URL: &str = "http://2gzyxa5ihm7nsggfxnu52rck2vv4rvmdlkiu3zzui5du4xyclen53wid.onion/";
+
 /// Check if Tor proxy is reachable
 pub async fn check_tor_connection(config: &TorConfig) -> Result<bool, TorError> {
     let client = create_tor_client(config)?;
-    
-    // Try to reach a known .onion address (Tor Project's)
-    let result = client
-        .get("http://2gzyxa5ihm7nsggfxnu52rck2vv4rvmdlkiu3ber7fzs2xqxczfebsid.onion/")
-        .send()
-        .await;
+    Ok(probe(&client).await)
+}
+
+/// Check every endpoint of the proxy pool (or `socks_addr` without one)
+///
+/// Results feed the pool's health tracking and are returned per address.
+pub async fn check_proxies(config: &TorConfig) -> Result<Vec<(String, bool)>, TorError> {
+    let Some(pool) = &config.proxy_pool else {
+        return Ok(vec![(config.socks_addr.clone(), check_tor_connection(config).await?)]);
+    };
+
+    let mut results = Vec::with_capacity(pool.len());
+    for addr in pool.addrs() {
+        let ok = probe(&build_client(&addr, config, Some(CHECK_URL))?).await;
+        pool.record_probe(&addr, ok);
+        results.push((addr, ok));
+    }
+    Ok(results)
+}
 
-    match result {
-        Ok(resp) => Ok(resp.status().is_success() || resp.status().is_redirection()),
-        Err(_) => Ok(false),
+async fn probe(client: &Client) -> bool {
+    match client.get(CHECK_URL).send().await {
+        Ok(resp) => resp.status().is_success() || resp.status().is_redirection(),
+        Err(_) => false,
     }
 }
 
@@ -101,6 +523,46 @@ mod tests {
         let config = TorConfig::default();
         assert!(config.socks_addr.contains("9050"));
         assert_eq!(config.timeout_secs, 45);
+        assert_eq!(config.mode, TorMode::External);
+    }
+
+    #[test]
+    fn test_tor_mode_parse() {
+        assert_eq!("embedded".parse::<TorMode>(), Ok(TorMode::Embedded));
+        assert_eq!("External".parse::<TorMode>(), Ok(TorMode::External));
+        assert!("bridge".parse::<TorMode>().is_err());
+        assert_eq!(TorMode::Embedded.to_string(), "embedded");
+    }
+
+    #[test]
+    fn test_tls_policy() {
+        let onion = "https://2gzyxa5ihm7nsggfxnu52rck2vv4rvmdlkiu3zzui5du4xyclen53wid.onion/";
+        let policy = TlsPolicy::default();
+        assert!(policy.accepts_invalid_certs(Some(onion)));
+        assert!(!policy.accepts_invalid_certs(Some("https://api.github.com/")));
+        assert!(!policy.accepts_invalid_certs(None));
+        assert!(!TlsPolicy::Strict.accepts_invalid_certs(Some(onion)));
+        assert!(TlsPolicy::AcceptAll.accepts_invalid_certs(None));
+        assert_eq!("onion-only".parse::<TlsPolicy>(), Ok(policy));
+        assert!("lax".parse::<TlsPolicy>().is_err());
+
+        // Per-host overrides win over the policy, the most specific first
+        let config = TorConfig {
+            tls_overrides: vec![
+                "*.lab.example=accept".parse().unwrap(),
+                "api.lab.example=verify".parse().unwrap(),
+                "2gzyxa5ihm7nsggfxnu52rck2vv4rvmdlkiu3zzui5du4xyclen53wid.onion=verify".parse().unwrap(),
+            ],
+            ..Default::default()
+        };
+        assert!(config.accepts_invalid_certs(Some("https://lab.example/")));
+        assert!(config.accepts_invalid_certs(Some("https://files.lab.example/")));
+        assert!(!config.accepts_invalid_certs(Some("https://api.lab.example/")));
+        assert!(!config.accepts_invalid_certs(Some("https://notlab.example/")));
+        assert!(!config.accepts_invalid_certs(Some(onion)));
+        assert!(config.accepts_invalid_certs(Some("https://other7lqsaj2xuid5zzzzg6zdmen4khvdgajgxbenyjqwx6hh7566tfjd.onion/")));
+        assert!("lab.example".parse::<TlsOverride>().is_err());
+        assert!("lab.example=maybe".parse::<TlsOverride>().is_err());
     }
 
     #[test]
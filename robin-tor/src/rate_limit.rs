@@ -0,0 +1,138 @@
+//! Per-host politeness rate limiting
+//!
+//! A token bucket per host, shared by every clone of a [`crate::TorConfig`],
+//! so several crawler and scraper agents together stay under
+//! `TorConfig::requests_per_host_per_minute` for any one onion service.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use tokio::time::Instant;
+
+/// Default bucket size: requests to one host are evenly spaced
+pub const DEFAULT_BURST: u32 = 1;
+
+#[derive(Debug)]
+struct Bucket {
+    /// Available tokens; negative when callers are queued for future tokens
+    tokens: f64,
+    last_refill: Instant,
+}
+
+/// Shared token-bucket rate limiter keyed by host
+///
+/// Cloning is cheap and all clones share the same buckets.
+#[derive(Debug, Clone)]
+pub struct HostRateLimiter {
+    burst: u32,
+    buckets: Arc<Mutex<HashMap<String, Bucket>>>,
+}
+
+impl Default for HostRateLimiter {
+    fn default() -> Self {
+        Self::with_burst(DEFAULT_BURST)
+    }
+}
+
+impl HostRateLimiter {
+    /// Limiter allowing bursts of up to `burst` requests per host
+    pub fn with_burst(burst: u32) -> Self {
+        Self {
+            burst: burst.max(1),
+            buckets: Arc::default(),
+        }
+    }
+
+    /// Wait until a request to `host` is allowed at `per_minute` requests per minute
+    pub async fn acquire(&self, host: &str, per_minute: u32) {
+        let wait = self.reserve(host, per_minute);
+        if !wait.is_zero() {
+            tokio::time::sleep(wait).await;
+        }
+    }
+
+    /// Take a token for `host`, returning how long the caller must wait for it
+    ///
+    /// Tokens are reserved up front, so concurrent callers queue in order
+    /// instead of racing for the next refill.
+    fn reserve(&self, host: &str, per_minute: u32) -> Duration {
+        if per_minute == 0 {
+            return Duration::ZERO;
+        }
+
+        let rate_per_sec = per_minute as f64 / 60.0;
+        let burst = self.burst as f64;
+        let now = Instant::now();
+
+        let mut buckets = self.buckets.lock().unwrap();
+        let bucket = buckets.entry(host.to_ascii_lowercase()).or_insert(Bucket {
+            tokens: burst,
+            last_refill: now,
+        });
+
+        let elapsed = now.duration_since(bucket.last_refill).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * rate_per_sec).min(burst);
+        bucket.last_refill = now;
+        bucket.tokens -= 1.0;
+
+        if bucket.tokens >= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(-bucket.tokens / rate_per_sec)
+        }
+    }
+}
+
+/// Host component of a URL, used as the rate-limit key
+pub fn host_key(url: &str) -> Option<String> {
+    reqwest::Url::parse(url)
+        .ok()?
+        .host_str()
+        .map(|h| h.to_ascii_lowercase())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reservations_queue_per_host() {
+        let limiter = HostRateLimiter::default();
+
+        // 60/min = one token per second; the first request is free
+        assert!(limiter.reserve("a.onion", 60).is_zero());
+        let second = limiter.reserve("a.onion", 60);
+        let third = limiter.reserve("A.onion", 60);
+        assert!(second > Duration::from_millis(900) && second <= Duration::from_secs(1));
+        assert!(third > Duration::from_millis(1900) && third <= Duration::from_secs(2));
+
+        // Other hosts have their own bucket; zero disables limiting
+        assert!(limiter.reserve("b.onion", 60).is_zero());
+        assert!(limiter.reserve("a.onion", 0).is_zero());
+    }
+
+    #[test]
+    fn test_burst_and_host_key() {
+        let limiter = HostRateLimiter::with_burst(3);
+        for _ in 0..3 {
+            assert!(limiter.reserve("a.onion", 60).is_zero());
+        }
+        assert!(!limiter.reserve("a.onion", 60).is_zero());
+
+        assert_eq!(host_key("http://ABC.onion:8080/path?q=1").as_deref(), Some("abc.onion"));
+        assert_eq!(host_key("not a url"), None);
+    }
+
+    #[tokio::test]
+    async fn test_acquire_spaces_requests() {
+        let limiter = HostRateLimiter::default();
+        let start = Instant::now();
+
+        // 1200/min = 50ms spacing
+        for _ in 0..3 {
+            limiter.acquire("a.onion", 1200).await;
+        }
+        assert!(start.elapsed() >= Duration::from_millis(95));
+    }
+}
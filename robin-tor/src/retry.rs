@@ -0,0 +1,135 @@
+//! Retry classification and exponential backoff
+//!
+//! [`crate::fetch`] retries failed requests up to `TorConfig::max_retries`
+//! times. Failures are classified so each kind is handled appropriately:
+//! - Timeouts and Tor connection failures back off and retry
+//! - An unreachable SOCKS proxy retries immediately when a proxy pool can
+//!   route the next attempt elsewhere
+//! - HTTP 5xx and 429 responses back off and retry; other statuses are final
+
+use std::fmt;
+use std::time::Duration;
+
+use rand::Rng;
+
+use crate::{fetch::is_proxy_unreachable, FetchResponse, TorConfig, TorError};
+
+/// Why an attempt failed, for retry purposes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FailureKind {
+    /// Request exceeded the configured timeout
+    Timeout,
+    /// The SOCKS proxy itself refused the connection
+    ProxyUnreachable,
+    /// Tor could not connect to the destination (e.g. circuit or descriptor failure)
+    Connect,
+    /// Server answered with a retryable status (5xx or 429)
+    HttpStatus(u16),
+}
+
+impl fmt::Display for FailureKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FailureKind::Timeout => write!(f, "timeout"),
+            FailureKind::ProxyUnreachable => write!(f, "proxy unreachable"),
+            FailureKind::Connect => write!(f, "connection failed"),
+            FailureKind::HttpStatus(status) => write!(f, "HTTP {}", status),
+        }
+    }
+}
+
+/// Classify a request error; `None` means the error is not worth retrying
+pub fn classify_error(err: &TorError) -> Option<FailureKind> {
+    match err {
+        TorError::Timeout(_) => Some(FailureKind::Timeout),
+        TorError::Request(e) if e.is_timeout() => Some(FailureKind::Timeout),
+        TorError::Request(e) if is_proxy_unreachable(e) => Some(FailureKind::ProxyUnreachable),
+        TorError::Request(e) if e.is_connect() || e.is_request() || e.is_body() => {
+            Some(FailureKind::Connect)
+        }
+        _ => None,
+    }
+}
+
+/// Classify a response; `None` means the response is final
+pub fn classify_response(response: &FetchResponse) -> Option<FailureKind> {
+    match response.status {
+        429 | 500..=599 => Some(FailureKind::HttpStatus(response.status)),
+        _ => None,
+    }
+}
+
+/// Delay before retry number `attempt` (0-based)
+///
+/// Exponential in the attempt number, capped at `retry_max_delay_ms`, with
+/// equal jitter (half fixed, half random) so concurrent agents spread out.
+pub fn backoff_delay(config: &TorConfig, attempt: u32) -> Duration {
+    let exp = config
+        .retry_base_delay_ms
+        .saturating_mul(1u64 << attempt.min(32));
+    let capped = exp.min(config.retry_max_delay_ms);
+    let half = capped / 2;
+    let jitter = if half > 0 {
+        rand::thread_rng().gen_range(0..=half)
+    } else {
+        0
+    };
+    Duration::from_millis(capped - half + jitter)
+}
+
+/// Delay before retrying a failure of `kind`
+pub fn retry_delay(config: &TorConfig, kind: FailureKind, attempt: u32) -> Duration {
+    match kind {
+        // Another pool endpoint can take the next attempt right away
+        FailureKind::ProxyUnreachable if config.proxy_pool.as_ref().is_some_and(|p| p.len() > 1) => {
+            Duration::ZERO
+        }
+        _ => backoff_delay(config, attempt),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Cacheability;
+
+    fn response(status: u16) -> FetchResponse {
+        FetchResponse {
+            url: "http://a.onion/".to_string(),
+            status,
+            body: String::new(),
+            cacheability: Cacheability::Unspecified,
+        }
+    }
+
+    #[test]
+    fn test_classify_response() {
+        assert_eq!(classify_response(&response(503)), Some(FailureKind::HttpStatus(503)));
+        assert_eq!(classify_response(&response(429)), Some(FailureKind::HttpStatus(429)));
+        assert_eq!(classify_response(&response(404)), None);
+        assert_eq!(classify_response(&response(200)), None);
+
+        assert_eq!(classify_error(&TorError::Timeout(45)), Some(FailureKind::Timeout));
+        assert_eq!(classify_error(&TorError::Cassette("miss".to_string())), None);
+    }
+
+    #[test]
+    fn test_backoff_grows_and_caps() {
+        let config = TorConfig {
+            retry_base_delay_ms: 100,
+            retry_max_delay_ms: 1000,
+            ..Default::default()
+        };
+
+        for _ in 0..20 {
+            let first = backoff_delay(&config, 0).as_millis();
+            assert!((50..=100).contains(&first), "attempt 0: {}", first);
+
+            let third = backoff_delay(&config, 2).as_millis();
+            assert!((200..=400).contains(&third), "attempt 2: {}", third);
+
+            let late = backoff_delay(&config, 40).as_millis();
+            assert!((500..=1000).contains(&late), "attempt 40: {}", late);
+        }
+    }
+}
@@ -1,11 +1,20 @@
 //! Content scraper for dark web sites
 //!
-//! Fetches and extracts text content from .onion URLs.
+//! Fetches and extracts text content from .onion URLs. Pages a site adapter
+//! recognises are rendered post by post (see [`crate::SiteAdapters`]).
+//! Hidden service links on the page are collected for link following, and
+//! the page is screenshotted when [`TorConfig::screenshots`] is set. The
+//! text's language is detected so agents can translate it, and meta tags,
+//! contact/payment URIs, PGP keys and timestamps are kept as [`PageMetadata`].
 
+use regex::Regex;
+use robin_core::{detect_language, Network, PageMetadata};
 use scraper::{Html, Selector};
+use std::path::PathBuf;
+use std::sync::LazyLock;
 use tracing::{debug, warn};
 
-use crate::{create_tor_client, TorConfig, TorError};
+use crate::{capture_screenshot, fetch, hash_page_images, StructuredPage, TorConfig, TorError};
 
 /// Scraped content from a dark web page
 #[derive(Debug, Clone)]
@@ -20,46 +29,126 @@ pub struct ScrapedPage {
     pub char_count: usize,
     /// Whether content was truncated
     pub truncated: bool,
+    /// Posts found by a site adapter (None = generic extraction)
+    pub structured: Option<StructuredPage>,
+    /// Absolute .onion / .i2p links on the page, in order, without duplicates
+    pub links: Vec<String>,
+    /// PNG of the rendered page, when screenshots are configured and capture worked
+    pub screenshot: Option<PathBuf>,
+    /// Language of the text (ISO 639-1), when it could be told
+    pub language: Option<String>,
+    /// Meta tags, OpenGraph, contact/payment URIs, PGP keys and timestamps
+    pub metadata: PageMetadata,
+    /// Why the download was abandoned (e.g. an oversized body), leaving the page empty
+    pub aborted: Option<String>,
 }
 
-/// Maximum characters to extract per page
-const MAX_CONTENT_LENGTH: usize = 4000;
+impl ScrapedPage {
+    /// A page with no content
+    fn empty(url: &str) -> Self {
+        Self {
+            url: url.to_string(),
+            title: None,
+            text: String::new(),
+            char_count: 0,
+            truncated: false,
+            structured: None,
+            links: Vec::new(),
+            screenshot: None,
+            language: None,
+            metadata: PageMetadata::default(),
+            aborted: None,
+        }
+    }
+}
+
+/// Default for [`TorConfig::max_page_chars`]
+///
+/// Leak listings and forum threads run long, and their most interesting
+/// parts are rarely at the top; agents get long pages in chunks (see
+/// [`chunk_text`]), so this only guards against runaway pages.
+pub const DEFAULT_MAX_PAGE_CHARS: usize = 64_000;
+
+/// Maximum links collected per page
+const MAX_LINKS: usize = 200;
+
+/// Maximum timestamps kept per page
+const MAX_TIMESTAMPS: usize = 50;
+
+static PGP_KEY_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?s)-----BEGIN PGP PUBLIC KEY BLOCK-----.*?-----END PGP PUBLIC KEY BLOCK-----").unwrap()
+});
+
+/// ISO-style dates with an optional time, and day/month/year dates with a time
+static TIMESTAMP_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\b(?:\d{4}-\d{2}-\d{2}(?:[ T]\d{2}:\d{2}(?::\d{2})?(?:Z|[+-]\d{2}:?\d{2}| ?UTC)?)?|\d{1,2}[./]\d{1,2}[./]\d{4},? \d{1,2}:\d{2}(?::\d{2})?)\b").unwrap()
+});
 
 /// Scrape content from a URL
+///
+/// A body over `config.max_body_bytes` is abandoned and gives an empty page
+/// with [`ScrapedPage::aborted`] set rather than an error.
 pub async fn scrape_url(url: &str, config: &TorConfig) -> Result<ScrapedPage, TorError> {
-    let client = create_tor_client(config)?;
-
     debug!("Scraping: {}", url);
+    config.crawl_policy.admit(url, config).await?;
 
-    let response = client.get(url).send().await?;
+    let fetched = fetch(url, config).await;
+    if matches!(fetched, Ok(_) | Err(TorError::BodyTooLarge { .. })) {
+        config.crawl_policy.charge(url);
+    }
+    let response = match fetched {
+        Ok(response) => response,
+        Err(e @ TorError::BodyTooLarge { .. }) => {
+            warn!("Abandoned scrape of {}: {}", url, e);
+            return Ok(ScrapedPage {
+                aborted: Some(e.to_string()),
+                ..ScrapedPage::empty(url)
+            });
+        }
+        Err(e) => return Err(e),
+    };
 
-    if !response.status().is_success() {
-        warn!("Scrape of {} returned status: {}", url, response.status());
-        return Ok(ScrapedPage {
-            url: url.to_string(),
-            title: None,
-            text: String::new(),
-            char_count: 0,
-            truncated: false,
-        });
+    if !response.is_success() {
+        warn!("Scrape of {} returned status: {}", url, response.status);
+        return Ok(ScrapedPage::empty(url));
     }
 
-    let html = response.text().await?;
-    let (title, text) = extract_content(&html);
+    let structured = config.site_adapters.parse(url, &response.body);
+    let (title, text, mut metadata) = match &structured {
+        Some(page) => {
+            debug!("Parsed {} with the {} adapter ({} posts)", url, page.adapter, page.posts.len());
+            (page.title.clone(), page.render(), extract_metadata(&response.body))
+        }
+        None => extract_content(&response.body),
+    };
+    let (final_text, truncated) = truncate_text(&text, config.max_page_chars);
+    if !config.is_replay() {
+        metadata.images = hash_page_images(&response.body, url, config).await;
+    }
 
-    let truncated = text.len() > MAX_CONTENT_LENGTH;
-    let final_text = if truncated {
-        format!("{}...(truncated)", &text[..MAX_CONTENT_LENGTH])
-    } else {
-        text
+    let screenshot = match &config.screenshots {
+        Some(_) if !config.is_replay() => match capture_screenshot(url, config).await {
+            Ok(path) => Some(path),
+            Err(e) => {
+                warn!("No screenshot of {}: {}", url, e);
+                None
+            }
+        },
+        _ => None,
     };
 
     Ok(ScrapedPage {
         url: url.to_string(),
+        language: detect_language(&final_text).map(str::to_string),
         title,
         text: final_text.clone(),
         char_count: final_text.len(),
         truncated,
+        structured,
+        links: extract_links(&response.body, url),
+        screenshot,
+        metadata,
+        aborted: None,
     })
 }
 
@@ -91,10 +180,64 @@ pub async fn scrape_urls(
         .await
 }
 
-/// Extract title and text content from HTML
-fn extract_content(html: &str) -> (Option<String>, String) {
-    use scraper::node::Node;
+/// Truncate text to at most `max_len` bytes on a UTF-8 boundary
+///
+/// Returns the (possibly marked) text and whether truncation happened.
+pub fn truncate_text(text: &str, max_len: usize) -> (String, bool) {
+    if text.len() <= max_len {
+        return (text.to_string(), false);
+    }
+
+    let mut end = max_len;
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+
+    (format!("{}...(truncated)", &text[..end]), true)
+}
+
+/// Split `text` into pieces of at most `max_len` bytes, breaking at whitespace
+///
+/// Returns each piece with its byte offset in `text`. Pieces break at the
+/// last whitespace that keeps them at least half full (mid-word only for a
+/// run of text without any), never inside a UTF-8 character, and the
+/// whitespace at a break belongs to neither piece.
+pub fn chunk_text(text: &str, max_len: usize) -> Vec<(usize, &str)> {
+    let mut chunks = Vec::new();
+    let mut start = 0;
+    while start < text.len() {
+        let rest = &text[start..];
+        if rest.len() <= max_len {
+            chunks.push((start, rest));
+            break;
+        }
 
+        let mut end = max_len;
+        while !rest.is_char_boundary(end) {
+            end -= 1;
+        }
+        if end == 0 {
+            // A single character longer than max_len
+            end = rest.char_indices().nth(1).map_or(rest.len(), |(i, _)| i);
+        }
+        let (piece_end, next) = match rest[..end].rfind(char::is_whitespace) {
+            Some(space) if space >= end / 2 => {
+                let width = rest[space..].chars().next().map_or(1, char::len_utf8);
+                (space, space + width)
+            }
+            _ => (end, end),
+        };
+        chunks.push((start, &rest[..piece_end]));
+        start += next;
+    }
+    chunks
+}
+
+/// Extract title, text content and metadata from HTML
+///
+/// Text excludes `script`, `style` and `noscript` subtrees and has its
+/// whitespace normalized; see [`extract_metadata`] for the metadata.
+pub fn extract_content(html: &str) -> (Option<String>, String, PageMetadata) {
     let document = Html::parse_document(html);
 
     // Extract title
@@ -104,17 +247,103 @@ fn extract_content(html: &str) -> (Option<String>, String) {
         .next()
         .map(|el| el.text().collect::<String>().trim().to_string());
 
-    let body_selector = Selector::parse("body").unwrap();
-    let body = document.select(&body_selector).next();
+    let text_parts: Vec<&str> = visible_text(&document)
+        .into_iter()
+        .map(str::trim)
+        .filter(|t| !t.is_empty())
+        .collect();
+    let text = normalize_whitespace(&text_parts.join(" "));
 
-    let text = if let Some(body) = body {
-        let mut text_parts = Vec::new();
+    (title, text, metadata_of(&document))
+}
+
+/// Structured metadata of a page: meta tags, OpenGraph properties,
+/// `mailto:` and payment URIs, PGP public key blocks and visible timestamps
+pub fn extract_metadata(html: &str) -> PageMetadata {
+    metadata_of(&Html::parse_document(html))
+}
+
+fn metadata_of(document: &Html) -> PageMetadata {
+    let mut metadata = PageMetadata::default();
+
+    let meta = Selector::parse("meta[content]").unwrap();
+    for element in document.select(&meta) {
+        let element = element.value();
+        let Some(key) = element.attr("property").or_else(|| element.attr("name")) else {
+            continue;
+        };
+        let key = key.trim().to_ascii_lowercase();
+        let content = normalize_whitespace(element.attr("content").unwrap_or_default());
+        if key.is_empty() || content.is_empty() {
+            continue;
+        }
+        let map = match key.strip_prefix("og:") {
+            Some(property) => metadata.open_graph.entry(property.to_string()),
+            None => metadata.meta.entry(key),
+        };
+        map.or_insert(content);
+    }
 
-        // Walk all descendants, skip script/style/noscript subtrees
-        for node_ref in body.descendants() {
-            // Skip text nodes inside script/style/noscript
-            if let Node::Text(text_node) = node_ref.value() {
-                // Check if any ancestor is a script/style/noscript
+    let anchor = Selector::parse("a[href]").unwrap();
+    for href in document.select(&anchor).filter_map(|el| el.value().attr("href")) {
+        let href = href.trim();
+        let Some((scheme, rest)) = href.split_once(':') else {
+            continue;
+        };
+        match scheme.to_ascii_lowercase().as_str() {
+            "mailto" => {
+                let addresses = rest.split('?').next().unwrap_or_default();
+                for address in addresses.split(',').map(str::trim).filter(|a| a.contains('@')) {
+                    push_unique(&mut metadata.emails, address);
+                }
+            }
+            "bitcoin" | "monero" | "ethereum" | "litecoin" => push_unique(&mut metadata.payment_uris, href),
+            _ => {}
+        }
+    }
+
+    // Line breaks matter for key blocks, so the text is kept as written
+    let text = visible_text(document).concat();
+    for block in PGP_KEY_REGEX.find_iter(&text) {
+        push_unique(&mut metadata.pgp_keys, block.as_str());
+    }
+
+    let time = Selector::parse("time").unwrap();
+    for element in document.select(&time) {
+        let written = element.text().collect::<String>();
+        let stamp = element.value().attr("datetime").unwrap_or(written.trim());
+        if !stamp.is_empty() {
+            push_unique(&mut metadata.timestamps, stamp);
+        }
+    }
+    for stamp in TIMESTAMP_REGEX.find_iter(&text) {
+        if metadata.timestamps.len() >= MAX_TIMESTAMPS {
+            break;
+        }
+        push_unique(&mut metadata.timestamps, stamp.as_str());
+    }
+    metadata.timestamps.truncate(MAX_TIMESTAMPS);
+
+    metadata
+}
+
+fn push_unique(list: &mut Vec<String>, value: &str) {
+    if !list.iter().any(|v| v == value) {
+        list.push(value.to_string());
+    }
+}
+
+/// Text nodes of the body, as written, outside `script`, `style` and `noscript`
+fn visible_text(document: &Html) -> Vec<&str> {
+    use scraper::node::Node;
+
+    let body_selector = Selector::parse("body").unwrap();
+    let Some(body) = document.select(&body_selector).next() else {
+        return Vec::new();
+    };
+    body.descendants()
+        .filter_map(|node_ref| match node_ref.value() {
+            Node::Text(text_node) => {
                 let in_excluded = node_ref.ancestors().any(|ancestor| {
                     ancestor
                         .value()
@@ -122,22 +351,41 @@ fn extract_content(html: &str) -> (Option<String>, String) {
                         .map(|el| matches!(el.name(), "script" | "style" | "noscript"))
                         .unwrap_or(false)
                 });
-
-                if !in_excluded {
-                    let trimmed = text_node.trim();
-                    if !trimmed.is_empty() {
-                        text_parts.push(trimmed.to_string());
-                    }
-                }
+                (!in_excluded).then_some(&**text_node)
             }
-        }
+            _ => None,
+        })
+        .collect()
+}
 
-        normalize_whitespace(&text_parts.join(" "))
-    } else {
-        String::new()
+/// Hidden service links in `html`, resolved against `base_url`
+///
+/// Keeps `.onion` and `.i2p` http(s) URLs (relative links included), drops
+/// fragments and duplicates, and stops after [`MAX_LINKS`].
+pub fn extract_links(html: &str, base_url: &str) -> Vec<String> {
+    let Ok(base) = reqwest::Url::parse(base_url) else {
+        return Vec::new();
     };
+    let document = Html::parse_document(html);
+    let anchor = Selector::parse("a[href]").unwrap();
 
-    (title, text)
+    let mut links: Vec<String> = Vec::new();
+    for element in document.select(&anchor) {
+        let Some(mut url) = element.value().attr("href").and_then(|href| base.join(href.trim()).ok()) else {
+            continue;
+        };
+        url.set_fragment(None);
+        let http = matches!(url.scheme(), "http" | "https");
+        let url = url.to_string();
+        if !http || Network::of_url(&url).is_none() || links.contains(&url) {
+            continue;
+        }
+        links.push(url);
+        if links.len() == MAX_LINKS {
+            break;
+        }
+    }
+    links
 }
 
 /// Normalize whitespace in text
@@ -163,7 +411,7 @@ mod tests {
             </html>
         "#;
 
-        let (title, text) = extract_content(html);
+        let (title, text, _) = extract_content(html);
 
         assert_eq!(title, Some("Test Page".to_string()));
         assert!(text.contains("Hello World"));
@@ -172,6 +420,130 @@ mod tests {
         assert!(!text.contains("color: red"));
     }
 
+    #[test]
+    fn test_extract_metadata() {
+        let html = r#"
+            <html><head>
+                <meta name="author" content="  dr_wizard ">
+                <meta property="og:site_name" content="Wizard Market">
+                <meta charset="utf-8">
+            </head><body>
+                <p>Posted <time datetime="2024-03-01T12:00:00Z">March 1</time>, edited 2024-03-02 09:15</p>
+                <a href="mailto:wizard@proton.me?subject=order">Contact</a>
+                <a href="bitcoin:1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa?amount=0.1">Pay</a>
+                <pre>-----BEGIN PGP PUBLIC KEY BLOCK-----
+
+mQINBGWx
+=abcd
+-----END PGP PUBLIC KEY BLOCK-----</pre>
+                <script>var built = "2020-01-01";</script>
+            </body></html>
+        "#;
+
+        let (_, text, metadata) = extract_content(html);
+        assert_eq!(metadata.meta.get("author").map(String::as_str), Some("dr_wizard"));
+        assert_eq!(metadata.open_graph.get("site_name").map(String::as_str), Some("Wizard Market"));
+        assert_eq!(metadata.emails, vec!["wizard@proton.me"]);
+        assert_eq!(metadata.payment_uris, vec!["bitcoin:1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa?amount=0.1"]);
+        assert_eq!(metadata.pgp_keys.len(), 1);
+        assert!(metadata.pgp_keys[0].contains("\nmQINBGWx\n"));
+        assert_eq!(metadata.timestamps, vec!["2024-03-01T12:00:00Z", "2024-03-02 09:15"]);
+        assert!(text.contains("Contact"));
+        assert!(metadata.render().contains("mailto: wizard@proton.me"));
+        assert!(extract_metadata("<p>nothing here</p>").is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_scrape_url_from_cassette() {
+        let cassette = crate::Cassette::replay(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/cassettes/ahmia_ransomware.json"
+        ))
+        .unwrap();
+        let config = TorConfig {
+            cassette: Some(cassette),
+            ..Default::default()
+        };
+
+        let page = scrape_url(
+            "http://ujzdegxdncf32epf3dhodzdocis2jhtlgmxgedn73u55xtplpft7v4sd.onion/",
+            &config,
+        )
+        .await
+        .unwrap();
+        assert_eq!(page.title.as_deref(), Some("Payment Portal"));
+        assert!(page.text.contains("1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa"));
+        assert!(!page.text.contains("__session"));
+        assert!(!page.text.contains("live chat"));
+
+        // A persistent 503 is retried until retries run out
+        let unavailable_url = "http://jooa7lqsaj2xuid5zzzzg6zdmen4khvdgajgxbenyjqwx6hh7566tfjd.onion/";
+        let retrying = TorConfig {
+            retry_base_delay_ms: 0,
+            ..config.clone()
+        };
+        assert!(matches!(
+            scrape_url(unavailable_url, &retrying).await,
+            Err(TorError::MaxRetries(3))
+        ));
+
+        // Without retries, the 503 yields an empty page rather than an error
+        let no_retries = TorConfig {
+            max_retries: 0,
+            ..config
+        };
+        let unavailable = scrape_url(unavailable_url, &no_retries).await.unwrap();
+        assert!(unavailable.text.is_empty());
+    }
+
+    #[test]
+    fn test_truncate_text_respects_char_boundaries() {
+        let (text, truncated) = truncate_text("ПриветМир", 5);
+        assert!(truncated);
+        assert_eq!(text, "Пр...(truncated)");
+
+        let (text, truncated) = truncate_text("short", 10);
+        assert!(!truncated);
+        assert_eq!(text, "short");
+    }
+
+    #[test]
+    fn test_chunk_text() {
+        let text = "victim one listed\nvictim two listed";
+        let chunks = chunk_text(text, 20);
+        assert_eq!(chunks, vec![(0, "victim one listed"), (18, "victim two listed")]);
+        for (offset, piece) in &chunks {
+            assert_eq!(&text[*offset..*offset + piece.len()], *piece);
+        }
+
+        // No whitespace: cut on character boundaries
+        assert_eq!(chunk_text("ПриветМир", 5), vec![(0, "Пр"), (4, "ив"), (8, "ет"), (12, "Ми"), (16, "р")]);
+        assert_eq!(chunk_text("短", 1), vec![(0, "短")]);
+        assert_eq!(chunk_text("short", 10), vec![(0, "short")]);
+        assert!(chunk_text("", 10).is_empty());
+    }
+
+    #[test]
+    fn test_extract_links() {
+        let html = r#"
+            <a href="/thread/2#post-9">Next page</a>
+            <a href="/thread/2">Next page again</a>
+            <a href="http://other7lqsaj2xuid5zzzzg6zdmen4khvdgajgxbenyjqwx6hh7566tfjd.onion/">Mirror</a>
+            <a href="http://forum-dev.i2p/thread/42">Eepsite</a>
+            <a href="https://example.com/">Clearnet</a>
+            <a href="mailto:admin@example.com">Mail</a>
+        "#;
+        let links = extract_links(html, "http://forum.onion/thread/1");
+        assert_eq!(
+            links,
+            vec![
+                "http://forum.onion/thread/2".to_string(),
+                "http://other7lqsaj2xuid5zzzzg6zdmen4khvdgajgxbenyjqwx6hh7566tfjd.onion/".to_string(),
+                "http://forum-dev.i2p/thread/42".to_string(),
+            ]
+        );
+    }
+
     #[test]
     fn test_normalize_whitespace() {
         let input = "  hello   world  \n\t  test  ";
@@ -0,0 +1,226 @@
+//! Screenshots of scraped pages
+//!
+//! Reports need visual evidence of marketplaces and leak sites, which the
+//! extracted text doesn't give. With [`TorConfig::screenshots`] set, each
+//! scraped page is also rendered by a headless Chromium (or Chrome) over the
+//! same proxy and saved as a PNG. The browser gets Tor's SOCKS port with all
+//! local DNS resolution disabled, so it can't leak lookups, WebRTC limited
+//! to proxied connections, so it can't leak the real address, and runs
+//! without JavaScript unless asked otherwise. Pages outside the crawl policy
+//! are never opened.
+//!
+//! Certificate errors aren't ignored even where the fetcher would accept
+//! them: Chromium can only ignore them for the whole session, subresources
+//! from other hosts included, so HTTPS pages with self-signed certificates
+//! can't be screenshotted.
+
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::time::Duration;
+use tracing::debug;
+
+use crate::{check_request, rate_limit::host_key, TorConfig, TorError};
+use robin_core::Network;
+
+/// How and where to capture screenshots
+#[derive(Debug, Clone)]
+pub struct ScreenshotConfig {
+    /// Directory the PNGs are written to
+    pub output_dir: PathBuf,
+    /// Chromium or Chrome executable
+    pub browser: PathBuf,
+    /// Give up on a page after this many seconds
+    pub timeout_secs: u64,
+    /// Viewport size in pixels
+    pub width: u32,
+    pub height: u32,
+    /// Run page scripts (off by default: safer, and most onion sites work without)
+    pub javascript: bool,
+    /// Extra browser flags (e.g. `--no-sandbox` when running as root)
+    pub extra_args: Vec<String>,
+}
+
+impl ScreenshotConfig {
+    /// Screenshots into `output_dir` with `chromium` from the PATH
+    pub fn new(output_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            output_dir: output_dir.into(),
+            browser: PathBuf::from("chromium"),
+            timeout_secs: 90,
+            width: 1280,
+            height: 1600,
+            javascript: false,
+            extra_args: Vec::new(),
+        }
+    }
+
+    pub fn with_browser(mut self, browser: impl Into<PathBuf>) -> Self {
+        self.browser = browser.into();
+        self
+    }
+
+    /// PNG file for `url`: its host plus a hash of the full URL
+    pub fn path_for(&self, url: &str) -> PathBuf {
+        let host = host_key(url).unwrap_or_else(|| "page".to_string());
+        let host: String = host.chars().take(64).collect();
+        let hash: String = Sha256::digest(url.as_bytes()).iter().take(6).map(|b| format!("{:02x}", b)).collect();
+        self.output_dir.join(format!("{}-{}.png", host, hash))
+    }
+}
+
+/// Proxy flag value for the browser: the I2P HTTP proxy for eepsites, else Tor's SOCKS port
+pub(crate) fn browser_proxy(url: &str, config: &TorConfig) -> Result<String, TorError> {
+    if Network::of_url(url) == Some(Network::I2p) {
+        let i2p = config.i2p.as_ref().ok_or_else(|| TorError::I2pNotConfigured(url.to_string()))?;
+        return Ok(i2p.proxy_addr.clone());
+    }
+    if config.upstream_proxy.is_some() {
+        return Err(TorError::Screenshot(
+            "the browser cannot chain through an upstream proxy".to_string(),
+        ));
+    }
+    let addr = match &config.proxy_pool {
+        Some(pool) => pool.addrs().into_iter().next().unwrap_or_else(|| config.socks_addr.clone()),
+        None => config.socks_addr.clone(),
+    };
+    // Chromium always resolves through a SOCKS5 proxy and has no socks5h scheme
+    match addr.split_once("://") {
+        Some(("socks5h" | "socks5", rest)) => Ok(format!("socks5://{}", rest)),
+        _ => Err(TorError::Screenshot(format!("the browser needs a SOCKS5 proxy, not {}", addr))),
+    }
+}
+
+/// Command-line flags that render `url` into `path` through `proxy`
+pub fn browser_args(url: &str, path: &Path, proxy: &str, shots: &ScreenshotConfig) -> Vec<String> {
+    let proxy_host = host_key(proxy).unwrap_or_else(|| "127.0.0.1".to_string());
+    let mut args = vec![
+        "--headless=new".to_string(),
+        "--incognito".to_string(),
+        "--no-first-run".to_string(),
+        "--no-default-browser-check".to_string(),
+        "--disable-gpu".to_string(),
+        "--disable-extensions".to_string(),
+        "--disable-sync".to_string(),
+        "--disable-background-networking".to_string(),
+        "--disable-component-update".to_string(),
+        "--hide-scrollbars".to_string(),
+        "--mute-audio".to_string(),
+        format!("--proxy-server={}", proxy),
+        // No lookups outside the proxy, except of the proxy itself
+        format!("--host-resolver-rules=MAP * ~NOTFOUND , EXCLUDE {}", proxy_host),
+        // No WebRTC traffic around the proxy, so scripts can't learn the real address
+        "--force-webrtc-ip-handling-policy=disable_non_proxied_udp".to_string(),
+        format!("--window-size={},{}", shots.width, shots.height),
+        format!("--screenshot={}", path.display()),
+    ];
+    if !shots.javascript {
+        args.push("--blink-settings=scriptEnabled=false".to_string());
+    }
+    args.extend(shots.extra_args.iter().cloned());
+    args.push(url.to_string());
+    args
+}
+
+/// Render `url` to a PNG as configured in `config.screenshots`; returns its path
+pub async fn capture_screenshot(url: &str, config: &TorConfig) -> Result<PathBuf, TorError> {
+    let shots = config
+        .screenshots
+        .as_ref()
+        .ok_or_else(|| TorError::Screenshot("screenshots are not configured".to_string()))?;
+    check_request(url, config)?;
+    config.crawl_policy.check(url).map_err(TorError::OutOfScope)?;
+    // The browser's own traffic can't be metered, but no screenshots past the cap
+    config.bandwidth.check()?;
+    let proxy = browser_proxy(url, config)?;
+    std::fs::create_dir_all(&shots.output_dir)
+        .map_err(|e| TorError::Screenshot(format!("{}: {}", shots.output_dir.display(), e)))?;
+    let path = shots.path_for(url);
+    let _ = std::fs::remove_file(&path);
+
+    debug!("Screenshotting {} to {}", url, path.display());
+    let mut command = tokio::process::Command::new(&shots.browser);
+    command
+        .args(browser_args(url, &path, &proxy, shots))
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .kill_on_drop(true);
+    let status = tokio::time::timeout(Duration::from_secs(shots.timeout_secs), command.status())
+        .await
+        .map_err(|_| TorError::Timeout(shots.timeout_secs))?
+        .map_err(|e| TorError::Screenshot(format!("cannot run {}: {}", shots.browser.display(), e)))?;
+
+    if !status.success() || !path.exists() {
+        return Err(TorError::Screenshot(format!("{} produced no screenshot of {} ({})", shots.browser.display(), url, status)));
+    }
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ONION: &str = "http://2gzyxa5ihm7nsggfxnu52rck2vv4rvmdlkiu3zzui5du4xyclen53wid.onion/forum?id=1";
+
+    #[test]
+    fn test_browser_args() {
+        let shots = ScreenshotConfig::new("/tmp/shots");
+        let path = shots.path_for(ONION);
+        assert!(path.starts_with("/tmp/shots"));
+        assert!(path.to_string_lossy().contains("2gzyxa5ihm7nsggfxnu52rck2vv4rvmdlkiu3zzui5du4xyclen53wid.onion-"));
+        assert_ne!(path, shots.path_for("http://2gzyxa5ihm7nsggfxnu52rck2vv4rvmdlkiu3zzui5du4xyclen53wid.onion/"));
+
+        let config = TorConfig::default();
+        let proxy = browser_proxy(ONION, &config).unwrap();
+        assert_eq!(proxy, "socks5://127.0.0.1:9050");
+        let args = browser_args(ONION, &path, &proxy, &shots);
+        assert!(args.contains(&"--proxy-server=socks5://127.0.0.1:9050".to_string()));
+        assert!(args.contains(&"--host-resolver-rules=MAP * ~NOTFOUND , EXCLUDE 127.0.0.1".to_string()));
+        assert!(args.contains(&"--blink-settings=scriptEnabled=false".to_string()));
+        assert!(args.contains(&"--force-webrtc-ip-handling-policy=disable_non_proxied_udp".to_string()));
+        assert!(!args.iter().any(|a| a.starts_with("--ignore-certificate-errors")));
+        assert_eq!(args.last().unwrap(), ONION);
+
+        // Without an I2P router, eepsites can't be rendered
+        assert!(matches!(browser_proxy("http://legwork.i2p/", &config), Err(TorError::I2pNotConfigured(_))));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_capture_with_browser() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = std::env::temp_dir().join(format!("robin-shots-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        // Stand-in browser that writes whatever --screenshot= names
+        let browser = dir.join("fake-chromium");
+        std::fs::write(
+            &browser,
+            "#!/bin/sh\nfor a in \"$@\"; do case \"$a\" in --screenshot=*) echo png > \"${a#--screenshot=}\";; esac; done\n",
+        )
+        .unwrap();
+        std::fs::set_permissions(&browser, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        let config = TorConfig {
+            screenshots: Some(ScreenshotConfig::new(dir.join("png")).with_browser(&browser)),
+            ..Default::default()
+        };
+        let path = capture_screenshot(ONION, &config).await.unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "png\n");
+
+        let missing = TorConfig {
+            screenshots: Some(ScreenshotConfig::new(dir.join("png")).with_browser(dir.join("no-such-browser"))),
+            ..Default::default()
+        };
+        assert!(matches!(capture_screenshot(ONION, &missing).await, Err(TorError::Screenshot(_))));
+
+        // Denied hosts are never opened
+        let denied = TorConfig {
+            crawl_policy: crate::CrawlPolicy::default().with_deny("2gzyxa5ihm7nsggfxnu52rck2vv4rvmdlkiu3zzui5du4xyclen53wid.onion"),
+            ..config
+        };
+        assert!(matches!(capture_screenshot(ONION, &denied).await, Err(TorError::OutOfScope(_))));
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,292 @@
+//! Cookie jar and authenticated sessions
+//!
+//! Some forums only show content after a login. The operator seeds
+//! [`SessionCredentials`] per target domain (bearer token, basic auth or a
+//! form login), and every request through [`crate::fetch`] to that domain
+//! carries the matching auth header and the cookies collected so far.
+//! Form logins run once per domain, and again after a 401/403.
+//!
+//! Cookies are kept per host and, when the store is opened from a file,
+//! persisted there as JSON so sessions survive restarts.
+
+use reqwest::header::{HeaderMap, COOKIE, SET_COOKIE};
+use reqwest::{Client, RequestBuilder};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tracing::{debug, info, warn};
+
+use crate::TorError;
+
+/// How to authenticate to a domain
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SessionAuth {
+    /// `Authorization: Bearer <token>`
+    Bearer { token: String },
+    /// HTTP basic auth
+    Basic { username: String, password: String },
+    /// POST form fields to a login page and keep the session cookies it sets
+    Form {
+        login_url: String,
+        fields: BTreeMap<String, String>,
+    },
+}
+
+/// Credentials for one target domain (and its subdomains)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SessionCredentials {
+    /// Domain, e.g. `forumabc...xyz.onion`
+    pub domain: String,
+    /// Authentication method
+    pub auth: SessionAuth,
+}
+
+impl SessionCredentials {
+    /// Load a JSON array of credentials from `path`
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Vec<Self>, TorError> {
+        let path = path.as_ref();
+        let json = std::fs::read_to_string(path)
+            .map_err(|e| TorError::Session(format!("failed to read {}: {}", path.display(), e)))?;
+        serde_json::from_str(&json)
+            .map_err(|e| TorError::Session(format!("invalid credentials file {}: {}", path.display(), e)))
+    }
+
+    /// Whether these credentials apply to `host`
+    pub fn matches(&self, host: &str) -> bool {
+        let domain = self.domain.trim_start_matches('.').to_ascii_lowercase();
+        host == domain || host.ends_with(&format!(".{}", domain))
+    }
+}
+
+type CookieJar = BTreeMap<String, BTreeMap<String, String>>;
+
+/// Shared cookie jar and credential set
+///
+/// Cloning is cheap and all clones share the same cookies, so one store can
+/// be handed to every agent through [`crate::TorConfig`].
+#[derive(Debug, Clone, Default)]
+pub struct SessionStore {
+    path: Option<PathBuf>,
+    credentials: Arc<Vec<SessionCredentials>>,
+    cookies: Arc<Mutex<CookieJar>>,
+    /// Hosts with a completed form login; held across the login request
+    logged_in: Arc<tokio::sync::Mutex<HashSet<String>>>,
+}
+
+impl SessionStore {
+    /// In-memory store
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Store persisted at `path`, loading any cookies already saved there
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, TorError> {
+        let path = path.as_ref().to_path_buf();
+        let cookies = match std::fs::read_to_string(&path) {
+            Ok(json) => serde_json::from_str(&json)
+                .map_err(|e| TorError::Session(format!("invalid cookie jar {}: {}", path.display(), e)))?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => CookieJar::new(),
+            Err(e) => return Err(TorError::Session(format!("failed to read {}: {}", path.display(), e))),
+        };
+        Ok(Self {
+            path: Some(path),
+            cookies: Arc::new(Mutex::new(cookies)),
+            ..Default::default()
+        })
+    }
+
+    /// Set the per-domain credentials
+    pub fn with_credentials(mut self, credentials: Vec<SessionCredentials>) -> Self {
+        self.credentials = Arc::new(credentials);
+        self
+    }
+
+    /// Credentials for `host`, if any
+    pub fn credentials_for(&self, host: &str) -> Option<&SessionCredentials> {
+        self.credentials.iter().find(|c| c.matches(host))
+    }
+
+    /// Cookies currently held for `host`
+    pub fn cookies(&self, host: &str) -> BTreeMap<String, String> {
+        self.cookies.lock().unwrap().get(host).cloned().unwrap_or_default()
+    }
+
+    /// Add auth and cookies for `host` to a request, logging in first if needed
+    ///
+    /// `login_client` builds the client for a form login; it must not follow
+    /// redirects, since login pages typically set the session cookie on a 302.
+    pub(crate) async fn authorize(
+        &self,
+        host: &str,
+        mut request: RequestBuilder,
+        login_client: impl FnOnce() -> Result<Client, TorError>,
+    ) -> Result<RequestBuilder, TorError> {
+        match self.credentials_for(host).map(|c| &c.auth) {
+            Some(SessionAuth::Bearer { token }) => request = request.bearer_auth(token),
+            Some(SessionAuth::Basic { username, password }) => {
+                request = request.basic_auth(username, Some(password));
+            }
+            Some(SessionAuth::Form { login_url, fields }) => {
+                let mut logged_in = self.logged_in.lock().await;
+                if !logged_in.contains(host) {
+                    self.login(host, login_url, fields, login_client()?).await?;
+                    logged_in.insert(host.to_string());
+                }
+            }
+            None => {}
+        }
+
+        let cookies = self.cookies(host);
+        if !cookies.is_empty() {
+            let header: Vec<String> = cookies.iter().map(|(k, v)| format!("{}={}", k, v)).collect();
+            request = request.header(COOKIE, header.join("; "));
+        }
+        Ok(request)
+    }
+
+    async fn login(
+        &self,
+        host: &str,
+        login_url: &str,
+        fields: &BTreeMap<String, String>,
+        client: Client,
+    ) -> Result<(), TorError> {
+        info!("Logging in to {}", host);
+        let response = client.post(login_url).form(fields).send().await?;
+        let status = response.status();
+        self.store_cookies(host, response.headers());
+        if status.is_client_error() || status.is_server_error() {
+            return Err(TorError::Session(format!("login to {} failed with status {}", host, status)));
+        }
+        Ok(())
+    }
+
+    /// Forget the form login for `host` so the next request logs in again
+    pub(crate) async fn invalidate(&self, host: &str) {
+        if self.logged_in.lock().await.remove(host) {
+            debug!("Session for {} rejected; will log in again", host);
+        }
+    }
+
+    /// Record the `Set-Cookie` headers of a response from `host`
+    pub(crate) fn store_cookies(&self, host: &str, headers: &HeaderMap) {
+        let mut changed = false;
+        {
+            let mut jar = self.cookies.lock().unwrap();
+            for value in headers.get_all(SET_COOKIE) {
+                let Some((name, value, expired)) = value.to_str().ok().and_then(parse_set_cookie) else {
+                    continue;
+                };
+                let host_cookies = jar.entry(host.to_string()).or_default();
+                if expired {
+                    changed |= host_cookies.remove(&name).is_some();
+                } else {
+                    changed |= host_cookies.insert(name, value.clone()).as_ref() != Some(&value);
+                }
+            }
+        }
+        if changed {
+            if let Err(e) = self.save() {
+                warn!("Failed to save cookie jar: {}", e);
+            }
+        }
+    }
+
+    fn save(&self) -> Result<(), TorError> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let json = serde_json::to_string_pretty(&*self.cookies.lock().unwrap())
+            .map_err(|e| TorError::Session(format!("failed to serialize cookie jar: {}", e)))?;
+        let tmp = path.with_extension(format!("tmp{}", std::process::id()));
+        std::fs::write(&tmp, json)
+            .and_then(|_| std::fs::rename(&tmp, path))
+            .map_err(|e| TorError::Session(format!("failed to write {}: {}", path.display(), e)))
+    }
+}
+
+/// Name, value and whether the cookie is being deleted (`Max-Age<=0`)
+fn parse_set_cookie(header: &str) -> Option<(String, String, bool)> {
+    let mut parts = header.split(';');
+    let (name, value) = parts.next()?.split_once('=')?;
+    let name = name.trim();
+    if name.is_empty() {
+        return None;
+    }
+    let expired = parts.any(|attr| {
+        attr.split_once('=').is_some_and(|(k, v)| {
+            k.trim().eq_ignore_ascii_case("max-age") && v.trim().parse::<i64>().is_ok_and(|age| age <= 0)
+        })
+    });
+    Some((name.to_string(), value.trim().to_string(), expired))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+
+    fn set_cookies(values: &[&str]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for v in values {
+            headers.append(SET_COOKIE, HeaderValue::from_str(v).unwrap());
+        }
+        headers
+    }
+
+    #[test]
+    fn test_credentials_match_subdomains() {
+        let json = r#"[
+            {"domain": "forum.onion", "auth": {"type": "bearer", "token": "t0k"}},
+            {"domain": "market.onion", "auth": {"type": "form", "login_url": "http://market.onion/login",
+             "fields": {"user": "analyst", "pass": "secret"}}}
+        ]"#;
+        let credentials: Vec<SessionCredentials> = serde_json::from_str(json).unwrap();
+        let store = SessionStore::new().with_credentials(credentials);
+
+        assert!(store.credentials_for("www.forum.onion").is_some());
+        assert!(store.credentials_for("notforum.onion").is_none());
+        assert!(matches!(
+            store.credentials_for("market.onion").map(|c| &c.auth),
+            Some(SessionAuth::Form { .. })
+        ));
+    }
+
+    #[test]
+    fn test_cookie_jar_persists_and_expires() {
+        let path = std::env::temp_dir().join(format!("robin-cookies-{}.json", std::process::id()));
+        std::fs::remove_file(&path).ok();
+
+        let store = SessionStore::open(&path).unwrap();
+        store.store_cookies("forum.onion", &set_cookies(&["sid=abc; Path=/; HttpOnly", "theme=dark"]));
+        assert_eq!(SessionStore::open(&path).unwrap().cookies("forum.onion").len(), 2);
+
+        store.store_cookies("forum.onion", &set_cookies(&["theme=; Max-Age=0"]));
+        let reopened = SessionStore::open(&path).unwrap();
+        assert_eq!(reopened.cookies("forum.onion").get("sid").map(String::as_str), Some("abc"));
+        assert!(reopened.cookies("other.onion").is_empty());
+
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[tokio::test]
+    async fn test_authorize_adds_headers() {
+        let store = SessionStore::new().with_credentials(vec![SessionCredentials {
+            domain: "forum.onion".to_string(),
+            auth: SessionAuth::Bearer { token: "t0k".to_string() },
+        }]);
+        store.store_cookies("forum.onion", &set_cookies(&["sid=abc"]));
+
+        let client = Client::new();
+        let request = store
+            .authorize("forum.onion", client.get("http://forum.onion/"), || unreachable!())
+            .await
+            .unwrap()
+            .build()
+            .unwrap();
+        assert_eq!(request.headers()["authorization"], "Bearer t0k");
+        assert_eq!(request.headers()["cookie"], "sid=abc");
+    }
+}
@@ -0,0 +1,417 @@
+//! Tor daemon status over the control port
+//!
+//! `robin-smesh status` asks the daemon itself how it is doing rather than
+//! inferring it from one request: [`query_control_port`] speaks the Tor
+//! control protocol (`PROTOCOLINFO`, `AUTHENTICATE`, `GETINFO`) for the
+//! version, bootstrap progress and built circuits, and [`probe_onions`]
+//! measures round trips to a few long-lived onion services.
+
+use futures::future::join_all;
+use hmac::{Hmac, Mac};
+use rand::RngCore;
+use serde::Serialize;
+use sha2::Sha256;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::tcp::{OwnedReadHalf, OwnedWriteHalf};
+use tokio::net::TcpStream;
+
+use crate::{create_tor_client, TorConfig, TorError};
+
+/// Default Tor control port
+pub const DEFAULT_CONTROL_ADDR: &str = "127.0.0.1:9051";
+
+/// Time allowed for the whole control port exchange
+const CONTROL_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Long-lived onion services used for latency probes
+pub const KNOWN_ONIONS: &[(&str, &str)] = &[
+    ("Tor Project", "http://2gzyxa5ihm7nsggfxnu52rck2vv4rvmdlkiu3zzui5du4xyclen53wid.onion/"),
+    ("DuckDuckGo", "https://duckduckgogg42xjoc72x3sjasowoarfbgcmvfimaftt6twagswzczad.onion/"),
+    ("BBC News", "https://www.bbcweb3hytmzhn5d532owbu6oqadra5z3ar726vq5kgwwn6aucdccrad.onion/"),
+];
+
+/// How to reach the control port
+#[derive(Debug, Clone)]
+pub struct ControlPortConfig {
+    /// `host:port` of the control listener
+    pub addr: String,
+    /// Password for `HashedControlPassword` setups (cookie auth needs none)
+    pub password: Option<String>,
+}
+
+impl Default for ControlPortConfig {
+    fn default() -> Self {
+        Self {
+            addr: DEFAULT_CONTROL_ADDR.to_string(),
+            password: None,
+        }
+    }
+}
+
+/// Bootstrap progress reported by Tor
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Bootstrap {
+    /// 0-100
+    pub percent: u8,
+    /// Current phase, e.g. "Done" or "Loading relay descriptors"
+    pub summary: String,
+}
+
+/// What the daemon reports about itself
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ControlInfo {
+    pub version: Option<String>,
+    pub bootstrap: Option<Bootstrap>,
+    /// Circuits in the BUILT state
+    pub circuits_built: usize,
+}
+
+/// Round trip to one known onion service
+#[derive(Debug, Clone, Serialize)]
+pub struct OnionLatency {
+    pub name: String,
+    pub url: String,
+    /// None when the service could not be reached
+    pub latency_ms: Option<u64>,
+}
+
+/// Query version, bootstrap phase and circuit status from the control port
+pub async fn query_control_port(config: &ControlPortConfig) -> Result<ControlInfo, TorError> {
+    tokio::time::timeout(CONTROL_TIMEOUT, async {
+        let mut conn = ControlConnection::connect(&config.addr).await?;
+        conn.authenticate(config.password.as_deref()).await?;
+        let reply = conn
+            .command("GETINFO version status/bootstrap-phase circuit-status")
+            .await?;
+        conn.command("QUIT").await.ok();
+
+        let value = |key: &str| getinfo_value(&reply, key);
+        Ok(ControlInfo {
+            version: value("version").map(|v| v.split_whitespace().next().unwrap_or(v).to_string()),
+            bootstrap: value("status/bootstrap-phase").and_then(parse_bootstrap),
+            circuits_built: value("circuit-status").map_or(0, count_built_circuits),
+        })
+    })
+    .await
+    .map_err(|_| TorError::Control(format!("no answer from {} within {}s", config.addr, CONTROL_TIMEOUT.as_secs())))?
+}
+
+/// Measure the round trip to each of [`KNOWN_ONIONS`], concurrently
+pub async fn probe_onions(config: &TorConfig) -> Result<Vec<OnionLatency>, TorError> {
+    let client = create_tor_client(config)?;
+    let probes = KNOWN_ONIONS.iter().map(|(name, url)| {
+        let client = client.clone();
+        async move {
+            let start = Instant::now();
+            let ok = match client.get(*url).send().await {
+                Ok(resp) => resp.status().is_success() || resp.status().is_redirection(),
+                Err(_) => false,
+            };
+            OnionLatency {
+                name: name.to_string(),
+                url: url.to_string(),
+                latency_ms: ok.then(|| start.elapsed().as_millis() as u64),
+            }
+        }
+    });
+    Ok(join_all(probes).await)
+}
+
+struct ControlConnection {
+    reader: BufReader<OwnedReadHalf>,
+    writer: OwnedWriteHalf,
+}
+
+impl ControlConnection {
+    async fn connect(addr: &str) -> Result<Self, TorError> {
+        let stream = TcpStream::connect(addr)
+            .await
+            .map_err(|e| TorError::Control(format!("cannot connect to {}: {}", addr, e)))?;
+        let (reader, writer) = stream.into_split();
+        Ok(Self {
+            reader: BufReader::new(reader),
+            writer,
+        })
+    }
+
+    /// Authenticate with the first method the daemon offers that we can use
+    ///
+    /// Cookie auth prefers SAFECOOKIE, which proves knowledge of the cookie
+    /// without sending it, and falls back to plain COOKIE.
+    async fn authenticate(&mut self, password: Option<&str>) -> Result<(), TorError> {
+        let reply = self.command("PROTOCOLINFO 1").await?;
+        let (methods, cookie_file) = parse_protocolinfo(&reply);
+        let offers = |method: &str| methods.iter().any(|m| m == method);
+        let read_cookie = |path: &PathBuf| {
+            std::fs::read(path)
+                .map_err(|e| TorError::Control(format!("cannot read auth cookie {}: {}", path.display(), e)))
+        };
+
+        let command = if offers("NULL") {
+            "AUTHENTICATE".to_string()
+        } else if let (true, Some(password)) = (offers("HASHEDPASSWORD"), password) {
+            format!("AUTHENTICATE \"{}\"", password.replace('\\', "\\\\").replace('"', "\\\""))
+        } else if let (true, Some(path)) = (offers("SAFECOOKIE"), &cookie_file) {
+            let cookie = read_cookie(path)?;
+            let mut client_nonce = [0u8; 32];
+            rand::thread_rng().fill_bytes(&mut client_nonce);
+            let reply = self
+                .command(&format!("AUTHCHALLENGE SAFECOOKIE {}", to_hex(&client_nonce)))
+                .await?;
+            let (server_hash, server_nonce) = parse_authchallenge(&reply)
+                .ok_or_else(|| TorError::Control(format!("malformed AUTHCHALLENGE reply: {}", reply.join(" "))))?;
+            let message = [cookie.as_slice(), &client_nonce, &server_nonce].concat();
+            if safecookie_hmac(SAFECOOKIE_SERVER_KEY, &message) != server_hash {
+                return Err(TorError::Control(
+                    "AUTHCHALLENGE server hash does not match the auth cookie".to_string(),
+                ));
+            }
+            format!("AUTHENTICATE {}", to_hex(&safecookie_hmac(SAFECOOKIE_CONTROLLER_KEY, &message)))
+        } else if let (true, Some(path)) = (offers("COOKIE"), &cookie_file) {
+            format!("AUTHENTICATE {}", to_hex(&read_cookie(path)?))
+        } else {
+            return Err(TorError::Control(format!(
+                "no usable authentication method (daemon offers {})",
+                methods.join(", ")
+            )));
+        };
+        self.command(&command).await.map(|_| ())
+    }
+
+    /// Send one command and collect its reply lines (without status codes)
+    async fn command(&mut self, command: &str) -> Result<Vec<String>, TorError> {
+        self.writer
+            .write_all(format!("{}\r\n", command).as_bytes())
+            .await
+            .map_err(|e| TorError::Control(e.to_string()))?;
+
+        let mut lines = Vec::new();
+        loop {
+            let line = self.read_line().await?;
+            let (Some(code), Some(separator), Some(rest)) = (line.get(..3), line.get(3..4), line.get(4..)) else {
+                return Err(TorError::Control(format!("malformed reply: {}", line)));
+            };
+            if !code.starts_with('2') {
+                return Err(TorError::Control(format!("{} {}", code, rest)));
+            }
+            match separator {
+                " " => {
+                    lines.push(rest.to_string());
+                    return Ok(lines);
+                }
+                "+" => {
+                    // Data reply: lines up to a lone "."
+                    let mut data = Vec::new();
+                    loop {
+                        let line = self.read_line().await?;
+                        if line == "." {
+                            break;
+                        }
+                        data.push(line.strip_prefix('.').map(str::to_string).unwrap_or(line));
+                    }
+                    lines.push(format!("{}{}", rest, data.join("\n")));
+                }
+                _ => lines.push(rest.to_string()),
+            }
+        }
+    }
+
+    async fn read_line(&mut self) -> Result<String, TorError> {
+        let mut line = String::new();
+        let read = self
+            .reader
+            .read_line(&mut line)
+            .await
+            .map_err(|e| TorError::Control(e.to_string()))?;
+        if read == 0 {
+            return Err(TorError::Control("connection closed".to_string()));
+        }
+        Ok(line.trim_end_matches(['\r', '\n']).to_string())
+    }
+}
+
+/// Value of `key` in a GETINFO reply
+fn getinfo_value<'a>(reply: &'a [String], key: &str) -> Option<&'a str> {
+    reply.iter().find_map(|line| line.strip_prefix(key)?.strip_prefix('='))
+}
+
+/// Auth methods and cookie file from a PROTOCOLINFO reply
+fn parse_protocolinfo(reply: &[String]) -> (Vec<String>, Option<PathBuf>) {
+    let Some(auth) = reply.iter().find_map(|line| line.strip_prefix("AUTH ")) else {
+        return (Vec::new(), None);
+    };
+    let methods = auth
+        .split_whitespace()
+        .find_map(|field| field.strip_prefix("METHODS="))
+        .map(|m| m.split(',').map(str::to_string).collect())
+        .unwrap_or_default();
+    let cookie_file = auth
+        .split_once("COOKIEFILE=\"")
+        .and_then(|(_, rest)| rest.split_once('"'))
+        .map(|(path, _)| PathBuf::from(path.replace("\\\\", "\\")));
+    (methods, cookie_file)
+}
+
+/// HMAC key for the daemon's SAFECOOKIE proof
+const SAFECOOKIE_SERVER_KEY: &[u8] = b"Tor safe cookie authentication server-to-controller hash";
+
+/// HMAC key for our SAFECOOKIE proof
+const SAFECOOKIE_CONTROLLER_KEY: &[u8] = b"Tor safe cookie authentication controller-to-server hash";
+
+fn safecookie_hmac(key: &[u8], message: &[u8]) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts any key length");
+    mac.update(message);
+    mac.finalize().into_bytes().to_vec()
+}
+
+/// Server hash and nonce from `AUTHCHALLENGE SERVERHASH=... SERVERNONCE=...`
+fn parse_authchallenge(reply: &[String]) -> Option<(Vec<u8>, Vec<u8>)> {
+    let line = reply.iter().find_map(|line| line.strip_prefix("AUTHCHALLENGE "))?;
+    let field = |key: &str| {
+        line.split_whitespace()
+            .find_map(|field| field.strip_prefix(key)?.strip_prefix('='))
+            .and_then(from_hex)
+    };
+    Some((field("SERVERHASH")?, field("SERVERNONCE")?))
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn from_hex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+/// Parse `NOTICE BOOTSTRAP PROGRESS=100 TAG=done SUMMARY="Done"`
+fn parse_bootstrap(phase: &str) -> Option<Bootstrap> {
+    let percent = phase
+        .split_whitespace()
+        .find_map(|field| field.strip_prefix("PROGRESS="))?
+        .parse()
+        .ok()?;
+    let summary = phase
+        .split_once("SUMMARY=\"")
+        .and_then(|(_, rest)| rest.split_once('"'))
+        .map_or_else(String::new, |(summary, _)| summary.to_string());
+    Some(Bootstrap { percent, summary })
+}
+
+/// Count `BUILT` circuits in a `circuit-status` listing
+fn count_built_circuits(listing: &str) -> usize {
+    listing
+        .lines()
+        .filter(|line| line.split_whitespace().nth(1) == Some("BUILT"))
+        .count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::AsyncReadExt;
+    use tokio::net::TcpListener;
+
+    #[test]
+    fn test_parse_control_replies() {
+        let info = vec![
+            "PROTOCOLINFO 1".to_string(),
+            r#"AUTH METHODS=COOKIE,SAFECOOKIE COOKIEFILE="/run/tor/control.authcookie""#.to_string(),
+            "OK".to_string(),
+        ];
+        let (methods, cookie) = parse_protocolinfo(&info);
+        assert_eq!(methods, vec!["COOKIE", "SAFECOOKIE"]);
+        assert_eq!(cookie, Some(PathBuf::from("/run/tor/control.authcookie")));
+
+        let bootstrap = parse_bootstrap(r#"NOTICE BOOTSTRAP PROGRESS=85 TAG=ap_conn SUMMARY="Connecting to a relay""#);
+        assert_eq!(bootstrap, Some(Bootstrap { percent: 85, summary: "Connecting to a relay".to_string() }));
+        assert_eq!(count_built_circuits("1 BUILT $A~a,$B~b PURPOSE=GENERAL\n2 EXTENDED $C~c\n3 BUILT $D~d"), 2);
+    }
+
+    #[tokio::test]
+    async fn test_query_control_port() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+        tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let replies = [
+                "250-PROTOCOLINFO 1\r\n250-AUTH METHODS=HASHEDPASSWORD\r\n250 OK\r\n",
+                "250 OK\r\n",
+                "250-version=0.4.8.10 (git-abc)\r\n\
+                 250-status/bootstrap-phase=NOTICE BOOTSTRAP PROGRESS=100 TAG=done SUMMARY=\"Done\"\r\n\
+                 250+circuit-status=\r\n1 BUILT $A~a PURPOSE=GENERAL\r\n2 BUILT $B~b PURPOSE=HS_CLIENT_REND\r\n.\r\n\
+                 250 OK\r\n",
+            ];
+            let mut buf = [0u8; 512];
+            for reply in replies {
+                let n = stream.read(&mut buf).await.unwrap();
+                if String::from_utf8_lossy(&buf[..n]).starts_with("AUTHENTICATE") {
+                    assert_eq!(&buf[..n], b"AUTHENTICATE \"s3cret\"\r\n");
+                }
+                stream.write_all(reply.as_bytes()).await.unwrap();
+            }
+        });
+
+        let config = ControlPortConfig {
+            addr,
+            password: Some("s3cret".to_string()),
+        };
+        let info = query_control_port(&config).await.unwrap();
+        assert_eq!(info.version.as_deref(), Some("0.4.8.10"));
+        assert_eq!(info.bootstrap.map(|b| b.percent), Some(100));
+        assert_eq!(info.circuits_built, 2);
+    }
+
+    #[tokio::test]
+    async fn test_safecookie_auth() {
+        let cookie_path = std::env::temp_dir().join(format!("robin-safecookie-{}", uuid::Uuid::new_v4()));
+        let cookie = [7u8; 32];
+        std::fs::write(&cookie_path, cookie).unwrap();
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+        let protocolinfo = format!(
+            "250-PROTOCOLINFO 1\r\n250-AUTH METHODS=COOKIE,SAFECOOKIE COOKIEFILE=\"{}\"\r\n250 OK\r\n",
+            cookie_path.display()
+        );
+        let server = tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let (reader, mut writer) = stream.into_split();
+            let mut lines = BufReader::new(reader).lines();
+            let server_nonce = [9u8; 32];
+
+            assert_eq!(lines.next_line().await.unwrap().unwrap(), "PROTOCOLINFO 1");
+            writer.write_all(protocolinfo.as_bytes()).await.unwrap();
+
+            let challenge = lines.next_line().await.unwrap().unwrap();
+            let client_nonce = from_hex(challenge.strip_prefix("AUTHCHALLENGE SAFECOOKIE ").unwrap()).unwrap();
+            let message = [cookie.as_slice(), &client_nonce, &server_nonce].concat();
+            let reply = format!(
+                "250 AUTHCHALLENGE SERVERHASH={} SERVERNONCE={}\r\n",
+                to_hex(&safecookie_hmac(SAFECOOKIE_SERVER_KEY, &message)),
+                to_hex(&server_nonce)
+            );
+            writer.write_all(reply.as_bytes()).await.unwrap();
+
+            // The cookie itself never goes over the wire
+            let authenticate = lines.next_line().await.unwrap().unwrap();
+            assert_eq!(
+                authenticate,
+                format!("AUTHENTICATE {}", to_hex(&safecookie_hmac(SAFECOOKIE_CONTROLLER_KEY, &message)))
+            );
+            writer.write_all(b"250 OK\r\n").await.unwrap();
+        });
+
+        let mut connection = ControlConnection::connect(&addr).await.unwrap();
+        connection.authenticate(None).await.unwrap();
+        server.await.unwrap();
+        let _ = std::fs::remove_file(&cookie_path);
+    }
+}
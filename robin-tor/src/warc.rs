@@ -0,0 +1,311 @@
+//! WARC archival of fetched content
+//!
+//! Investigations need their evidence preserved in a form other tools can
+//! read. With [`TorConfig::archive`] set, every request that goes through
+//! [`crate::fetch_request`] (search pages, scraped pages, directories) is
+//! written to a WARC 1.1 file as a `request`/`response` record pair, which
+//! `warcio`, pywb or replayweb.page can index and replay. Agents that fetch
+//! outside the Tor layer (the paste monitor) write to the same archive.
+//!
+//! Bodies are stored as the fetcher read them: decoded to UTF-8, so the
+//! `Content-Length` of the archived response is recomputed and transfer or
+//! content encodings are dropped from its headers. Responses served from
+//! the cache are archived too (without their original headers); replayed
+//! cassette responses are not.
+
+use chrono::{SecondsFormat, Utc};
+use sha2::{Digest, Sha256};
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use uuid::Uuid;
+
+use robin_core::HttpMethod;
+
+use crate::{FetchRequest, FetchResponse, TorError};
+
+/// WARC version written in every record header
+pub const WARC_VERSION: &str = "WARC/1.1";
+
+/// Response headers that no longer describe the archived (decoded) body
+const DROPPED_HEADERS: &[&str] = &["content-length", "content-encoding", "transfer-encoding"];
+
+/// Shared handle to a WARC file
+///
+/// Cloning is cheap and all clones append to the same file, so one archive
+/// can be handed to every agent through [`crate::TorConfig`].
+#[derive(Debug, Clone)]
+pub struct WarcWriter {
+    path: PathBuf,
+    file: Arc<Mutex<File>>,
+}
+
+impl WarcWriter {
+    /// Open (or create) a WARC file for appending and write its `warcinfo` record
+    ///
+    /// Reopening an investigation's archive appends a new `warcinfo` record
+    /// followed by the new traffic, which is valid WARC.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, TorError> {
+        let path = path.as_ref().to_path_buf();
+        if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir).map_err(|e| archive_error(dir, e))?;
+        }
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .map_err(|e| archive_error(&path, e))?;
+        let writer = Self {
+            path,
+            file: Arc::new(Mutex::new(file)),
+        };
+
+        let filename = writer.path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+        let info = format!(
+            "software: robin-smesh/{}\r\nformat: WARC File Format 1.1\r\n\
+             conformsTo: http://iipc.github.io/warc-specifications/specifications/warc-format/warc-1.1/\r\n",
+            env!("CARGO_PKG_VERSION")
+        );
+        let (_, record) = warc_record(
+            "warcinfo",
+            &[("WARC-Filename", filename)],
+            "application/warc-fields",
+            info.as_bytes(),
+        );
+        writer.write(&record)?;
+        Ok(writer)
+    }
+
+    /// File the archive is written to
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Archive one request and the response it got
+    ///
+    /// `headers` are the response headers as received (empty when unknown,
+    /// e.g. for cache hits).
+    pub fn write_exchange(
+        &self,
+        request: &FetchRequest,
+        response: &FetchResponse,
+        headers: &[(String, String)],
+    ) -> Result<(), TorError> {
+        let uri = response.url.as_str();
+        let payload_digest = digest(response.body.as_bytes());
+        let (response_id, response_record) = warc_record(
+            "response",
+            &[
+                ("WARC-Target-URI", uri.to_string()),
+                ("WARC-Payload-Digest", payload_digest),
+            ],
+            "application/http;msgtype=response",
+            &http_response(response, headers),
+        );
+        let (_, request_record) = warc_record(
+            "request",
+            &[
+                ("WARC-Target-URI", uri.to_string()),
+                ("WARC-Concurrent-To", response_id),
+            ],
+            "application/http;msgtype=request",
+            &http_request(request),
+        );
+
+        // One write per pair, so concurrent agents never interleave records
+        let mut pair = response_record;
+        pair.extend_from_slice(&request_record);
+        self.write(&pair)
+    }
+
+    fn write(&self, bytes: &[u8]) -> Result<(), TorError> {
+        let mut file = self.file.lock().unwrap();
+        file.write_all(bytes)
+            .and_then(|_| file.flush())
+            .map_err(|e| archive_error(&self.path, e))
+    }
+}
+
+fn archive_error(path: &Path, e: std::io::Error) -> TorError {
+    TorError::Archive(format!("{}: {}", path.display(), e))
+}
+
+/// A complete WARC record (header, block, separator) and its record ID
+fn warc_record(kind: &str, fields: &[(&str, String)], content_type: &str, block: &[u8]) -> (String, Vec<u8>) {
+    let id = format!("<urn:uuid:{}>", Uuid::new_v4());
+    let mut header = format!(
+        "{}\r\nWARC-Type: {}\r\nWARC-Record-ID: {}\r\nWARC-Date: {}\r\n",
+        WARC_VERSION,
+        kind,
+        id,
+        Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true)
+    );
+    for (name, value) in fields {
+        header.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    header.push_str(&format!(
+        "WARC-Block-Digest: {}\r\nContent-Type: {}\r\nContent-Length: {}\r\n\r\n",
+        digest(block),
+        content_type,
+        block.len()
+    ));
+
+    let mut record = header.into_bytes();
+    record.extend_from_slice(block);
+    record.extend_from_slice(b"\r\n\r\n");
+    (id, record)
+}
+
+/// `sha256:` digest in the base32 form WARC tools expect
+fn digest(bytes: &[u8]) -> String {
+    format!("sha256:{}", base32(&Sha256::digest(bytes)))
+}
+
+/// RFC 4648 base32, padded
+fn base32(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 32] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+    let mut out = String::new();
+    for chunk in bytes.chunks(5) {
+        let mut buf = [0u8; 5];
+        buf[..chunk.len()].copy_from_slice(chunk);
+        let bits = buf.iter().fold(0u64, |acc, b| (acc << 8) | u64::from(*b));
+        let chars = (chunk.len() * 8).div_ceil(5);
+        for i in 0..8 {
+            if i < chars {
+                out.push(ALPHABET[((bits >> (35 - 5 * i)) & 31) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+/// The HTTP request message as sent
+fn http_request(request: &FetchRequest) -> Vec<u8> {
+    let (target, host) = match reqwest::Url::parse(&request.url) {
+        Ok(url) => {
+            let mut target = url.path().to_string();
+            if let Some(query) = url.query() {
+                target.push('?');
+                target.push_str(query);
+            }
+            let host = match (url.host_str(), url.port()) {
+                (Some(host), Some(port)) => format!("{}:{}", host, port),
+                (host, None) => host.unwrap_or_default().to_string(),
+                (None, Some(_)) => String::new(),
+            };
+            (target, host)
+        }
+        Err(_) => (request.url.clone(), String::new()),
+    };
+
+    let mut message = format!("{} {} HTTP/1.1\r\nHost: {}\r\n", request.method.as_str(), target, host);
+    let body = request.body.as_deref().unwrap_or_default();
+    if request.method == HttpMethod::Post || !body.is_empty() {
+        message.push_str("Content-Type: application/x-www-form-urlencoded\r\n");
+        message.push_str(&format!("Content-Length: {}\r\n", body.len()));
+    }
+    for (name, value) in &request.headers {
+        message.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    message.push_str("\r\n");
+    message.push_str(body);
+    message.into_bytes()
+}
+
+/// The HTTP response message, with headers fitted to the decoded body
+fn http_response(response: &FetchResponse, headers: &[(String, String)]) -> Vec<u8> {
+    let reason = reqwest::StatusCode::from_u16(response.status)
+        .ok()
+        .and_then(|s| s.canonical_reason())
+        .unwrap_or("");
+    let mut message = format!("HTTP/1.1 {} {}\r\n", response.status, reason);
+    for (name, value) in headers {
+        if !DROPPED_HEADERS.contains(&name.to_ascii_lowercase().as_str()) {
+            message.push_str(&format!("{}: {}\r\n", name, value));
+        }
+    }
+    message.push_str(&format!("Content-Length: {}\r\n\r\n", response.body.len()));
+    message.push_str(&response.body);
+    message.into_bytes()
+}
+
+/// Response headers as strings, for [`WarcWriter::write_exchange`]
+pub fn header_pairs(headers: &reqwest::header::HeaderMap) -> Vec<(String, String)> {
+    headers
+        .iter()
+        .filter_map(|(name, value)| Some((name.as_str().to_string(), value.to_str().ok()?.to_string())))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Cacheability;
+
+    /// Split a WARC file into (header, block) pairs using each record's Content-Length
+    fn records(bytes: &[u8]) -> Vec<(String, Vec<u8>)> {
+        let mut records = Vec::new();
+        let mut rest = bytes;
+        while !rest.is_empty() {
+            let end = rest.windows(4).position(|w| w == b"\r\n\r\n").unwrap();
+            let header = String::from_utf8(rest[..end].to_vec()).unwrap();
+            let length: usize = header
+                .lines()
+                .find_map(|l| l.strip_prefix("Content-Length: "))
+                .unwrap()
+                .parse()
+                .unwrap();
+            let block = rest[end + 4..end + 4 + length].to_vec();
+            assert_eq!(&rest[end + 4 + length..end + 8 + length], b"\r\n\r\n");
+            rest = &rest[end + 8 + length..];
+            records.push((header, block));
+        }
+        records
+    }
+
+    #[test]
+    fn test_writes_parseable_records() {
+        let path = std::env::temp_dir().join(format!("robin-warc-{}/run.warc", std::process::id()));
+        let warc = WarcWriter::open(&path).unwrap();
+        let response = FetchResponse {
+            url: "http://a.onion:8080/search?q=lockbit".to_string(),
+            status: 200,
+            body: "<html>résultats</html>".to_string(),
+            cacheability: Cacheability::Unspecified,
+        };
+        let headers = vec![
+            ("content-type".to_string(), "text/html".to_string()),
+            ("content-encoding".to_string(), "gzip".to_string()),
+        ];
+        warc.write_exchange(&FetchRequest::get(&response.url), &response, &headers).unwrap();
+        warc.clone()
+            .write_exchange(&FetchRequest::post_form("http://b.onion/login", "user=x"), &response, &[])
+            .unwrap();
+
+        let records = records(&std::fs::read(&path).unwrap());
+        std::fs::remove_dir_all(path.parent().unwrap()).ok();
+        let kinds: Vec<&str> = records
+            .iter()
+            .map(|(h, _)| h.lines().find_map(|l| l.strip_prefix("WARC-Type: ")).unwrap())
+            .collect();
+        assert_eq!(kinds, vec!["warcinfo", "response", "request", "response", "request"]);
+
+        for (header, block) in &records {
+            assert!(header.starts_with("WARC/1.1\r\n"));
+            assert!(header.contains(&format!("WARC-Block-Digest: {}", digest(block))));
+        }
+        let (response_header, response_block) = &records[1];
+        let block = String::from_utf8(response_block.clone()).unwrap();
+        assert!(block.starts_with("HTTP/1.1 200 OK\r\ncontent-type: text/html\r\n"));
+        assert!(!block.contains("gzip"));
+        assert!(block.ends_with(&format!("Content-Length: {}\r\n\r\n<html>résultats</html>", response.body.len())));
+        let response_id = response_header.lines().find_map(|l| l.strip_prefix("WARC-Record-ID: ")).unwrap();
+        assert!(records[2].0.contains(&format!("WARC-Concurrent-To: {}", response_id)));
+        assert!(String::from_utf8_lossy(&records[2].1).starts_with("GET /search?q=lockbit HTTP/1.1\r\nHost: a.onion:8080\r\n"));
+        assert!(String::from_utf8_lossy(&records[4].1).ends_with("Content-Length: 6\r\n\r\nuser=x"));
+        assert_eq!(base32(b"foobar"), "MZXW6YTBOI======");
+    }
+}